@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
-use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, DecoderConfig};
 use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 use lnmp_embedding::{EmbeddingType, Vector};
 
@@ -95,5 +95,31 @@ fn bench_zero_copy_suite(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_zero_copy_suite);
+/// Routing-style decode of 3 FIDs out of a wide record
+fn bench_fid_projection(c: &mut Criterion) {
+    let mut record = LnmpRecord::new();
+    for fid in 0..200u16 {
+        let value = match fid % 4 {
+            0 => LnmpValue::Int(fid as i64 * 1_000),
+            1 => LnmpValue::String(format!("value-{}-{}", fid, "x".repeat(64))),
+            2 => LnmpValue::FloatArray(vec![0.5; 32]),
+            _ => LnmpValue::StringArray(vec!["tag".to_string(); 8]),
+        };
+        record.add_field(LnmpField { fid, value });
+    }
+    let bytes = BinaryEncoder::new().encode(&record).unwrap();
+
+    let full = BinaryDecoder::new();
+    let projected = BinaryDecoder::with_config(DecoderConfig::new().with_fid_filter([7, 12, 100]));
+
+    let mut group = c.benchmark_group("fid_projection");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("wide_rec_full", |b| b.iter(|| full.decode(&bytes).unwrap()));
+    group.bench_function("wide_rec_3_fids", |b| {
+        b.iter(|| projected.decode(&bytes).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_zero_copy_suite, bench_fid_projection);
 criterion_main!(benches);
@@ -185,6 +185,9 @@ fn stream_large_payload() {
             StreamingEvent::StreamError { message } => {
                 println!("   ✗ Error: {}", message);
             }
+            StreamingEvent::BatchReceived { records } => {
+                println!("   Received batch of {} records", records.len());
+            }
         }
     }
 
@@ -0,0 +1,437 @@
+//! Asynchronous Streaming Frame Layer decoding over tokio
+//!
+//! [`AsyncStreamingDecoder`] reads SFL frames from any [`AsyncRead`], feeds
+//! them to a [`StreamingDecoder`] and yields the resulting [`StreamingEvent`]s,
+//! either one at a time with [`AsyncStreamingDecoder::next_event`] or as a
+//! [`Stream`] via [`AsyncStreamingDecoder::into_stream`].
+//!
+//! With a [`BackpressureController`] configured, the bytes of every received
+//! chunk count as in flight until the consumer acknowledges them through a
+//! [`StreamAcker`]. While the window is full the decoder stops reading, so the
+//! sender is slowed down by the transport's own flow control.
+//!
+//! ```no_run
+//! # async fn run(socket: tokio::net::TcpStream) -> Result<(), lnmp_codec::binary::streaming::StreamingError> {
+//! use lnmp_codec::binary::async_streaming::AsyncStreamingDecoder;
+//! use lnmp_codec::binary::streaming::{BackpressureController, StreamingEvent};
+//!
+//! let mut decoder = AsyncStreamingDecoder::new(socket)
+//!     .with_backpressure(BackpressureController::with_window_size(256 * 1024));
+//! let acker = decoder.acker();
+//! while let Some(event) = decoder.next_event().await {
+//!     if let StreamingEvent::ChunkReceived { bytes } = event? {
+//!         // ... hand the chunk to a worker, which later calls:
+//!         acker.ack(bytes);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::error::BinaryError;
+use super::streaming::{
+    BackpressureController, FrameFlags, FrameType, StreamingConfig, StreamingDecoder,
+    StreamingError, StreamingEvent,
+};
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::Notify;
+
+/// Largest payload accepted in BEGIN, END and ERROR frames
+pub const MAX_CONTROL_PAYLOAD: usize = 64 * 1024;
+
+/// Largest payload accepted in BATCH frames
+pub const MAX_BATCH_PAYLOAD: usize = 16 * 1024 * 1024;
+
+/// Bytes of the fixed CHECKSUM field and of the optional CRC32C trailer
+const CHECKSUM_LEN: usize = 4;
+
+/// Shared flow-control state between the decoder and its ackers
+#[derive(Debug)]
+struct Flow {
+    controller: Mutex<Option<BackpressureController>>,
+    notify: Notify,
+}
+
+impl Flow {
+    fn controller(&self) -> std::sync::MutexGuard<'_, Option<BackpressureController>> {
+        self.controller.lock().expect("backpressure lock poisoned")
+    }
+}
+
+/// Acknowledges processed chunk bytes, reopening the decoder's window
+///
+/// Cloneable so acknowledgements can come from whichever task consumes the
+/// chunks. Acknowledging without a configured controller has no effect.
+#[derive(Debug, Clone)]
+pub struct StreamAcker {
+    flow: Arc<Flow>,
+}
+
+impl StreamAcker {
+    /// Marks `bytes` as processed
+    pub fn ack(&self, bytes: usize) {
+        if let Some(controller) = self.flow.controller().as_mut() {
+            controller.on_chunk_acked(bytes);
+        }
+        self.flow.notify.notify_one();
+    }
+}
+
+/// Streaming decoder reading frames from an [`AsyncRead`]
+pub struct AsyncStreamingDecoder<R> {
+    reader: R,
+    config: StreamingConfig,
+    decoder: StreamingDecoder,
+    flow: Arc<Flow>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncStreamingDecoder<R> {
+    /// Creates a decoder with the default [`StreamingConfig`] and no backpressure
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, StreamingConfig::new())
+    }
+
+    /// Creates a decoder with a custom configuration
+    ///
+    /// CHUNK frames larger than `config.chunk_size` are rejected with
+    /// [`StreamingError::ChunkSizeExceeded`].
+    pub fn with_config(reader: R, config: StreamingConfig) -> Self {
+        Self {
+            reader,
+            decoder: StreamingDecoder::with_config(config.clone()),
+            config,
+            flow: Arc::new(Flow {
+                controller: Mutex::new(None),
+                notify: Notify::new(),
+            }),
+            done: false,
+        }
+    }
+
+    /// Pauses reading while `controller` reports no available window
+    ///
+    /// Received chunk bytes are recorded with
+    /// [`BackpressureController::on_chunk_sent`] and released by
+    /// [`StreamAcker::ack`].
+    pub fn with_backpressure(self, controller: BackpressureController) -> Self {
+        *self.flow.controller() = Some(controller);
+        self
+    }
+
+    /// Returns a handle for acknowledging processed chunks
+    pub fn acker(&self) -> StreamAcker {
+        StreamAcker {
+            flow: self.flow.clone(),
+        }
+    }
+
+    /// Snapshot of the backpressure window, if one is configured
+    pub fn backpressure(&self) -> Option<BackpressureController> {
+        self.flow.controller().clone()
+    }
+
+    /// Returns the reassembled payload once the stream is complete
+    pub fn complete_payload(&self) -> Option<&[u8]> {
+        self.decoder.get_complete_payload()
+    }
+
+    /// Returns the underlying synchronous decoder
+    pub fn decoder(&self) -> &StreamingDecoder {
+        &self.decoder
+    }
+
+    /// Consumes the decoder, returning the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next frame and returns its event
+    ///
+    /// Returns `None` once the reader ends cleanly between frames. After an
+    /// error, or after end of input, every further call returns `None`.
+    pub async fn next_event(&mut self) -> Option<Result<StreamingEvent, StreamingError>> {
+        if self.done {
+            return None;
+        }
+        wait_for_window(&self.flow).await;
+        let result = match self.read_frame().await {
+            Ok(Some(frame)) => self.decoder.feed_frame(&frame),
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => Err(err),
+        };
+        match &result {
+            Ok(StreamingEvent::ChunkReceived { bytes }) => {
+                if let Some(controller) = self.flow.controller().as_mut() {
+                    controller.on_chunk_sent(*bytes);
+                }
+            }
+            Ok(_) => {}
+            Err(_) => self.done = true,
+        }
+        Some(result)
+    }
+
+    /// Reads one complete frame, or `None` on a clean end of input
+    async fn read_frame(&mut self) -> Result<Option<Vec<u8>>, StreamingError> {
+        let mut frame = vec![0u8; 2];
+        match self.reader.read(&mut frame[..1]).await.map_err(io_error)? {
+            0 => return Ok(None),
+            _ => self.read_into(&mut frame[1..]).await?,
+        }
+        let frame_type = FrameType::from_u8(frame[0])?;
+        let flags = FrameFlags::from_u8(frame[1]);
+
+        // CHUNK_SIZE is a VarInt terminated by a byte without the high bit
+        loop {
+            let mut byte = [0u8; 1];
+            self.read_into(&mut byte).await?;
+            frame.push(byte[0]);
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            if frame.len() - 2 >= 10 {
+                return Err(BinaryError::InvalidVarInt {
+                    reason: "chunk size VarInt too long".to_string(),
+                }
+                .into());
+            }
+        }
+        let (size, _) = super::varint::decode(&frame[2..])?;
+        let size = usize::try_from(size).map_err(|_| StreamingError::CorruptFrame {
+            reason: format!("negative chunk size {}", size),
+        })?;
+        let max = match frame_type {
+            FrameType::Chunk => self.config.chunk_size,
+            FrameType::Batch => MAX_BATCH_PAYLOAD,
+            _ => MAX_CONTROL_PAYLOAD,
+        };
+        if size > max {
+            return Err(StreamingError::ChunkSizeExceeded { size, max });
+        }
+
+        let trailer = if flags.has_crc32c { CHECKSUM_LEN } else { 0 };
+        let start = frame.len();
+        frame.resize(start + CHECKSUM_LEN + size + trailer, 0);
+        self.read_into(&mut frame[start..]).await?;
+        Ok(Some(frame))
+    }
+
+    async fn read_into(&mut self, buf: &mut [u8]) -> Result<(), StreamingError> {
+        self.reader.read_exact(buf).await.map_err(io_error)?;
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send + 'static> AsyncStreamingDecoder<R> {
+    /// Turns the decoder into a [`Stream`] of events
+    ///
+    /// Take an [`acker`](Self::acker) first if backpressure is configured.
+    pub fn into_stream(self) -> EventStream<R> {
+        EventStream {
+            state: StreamState::Idle(Box::new(self)),
+        }
+    }
+}
+
+type NextEvent<R> = Pin<
+    Box<
+        dyn Future<
+                Output = (
+                    Box<AsyncStreamingDecoder<R>>,
+                    Option<Result<StreamingEvent, StreamingError>>,
+                ),
+            > + Send,
+    >,
+>;
+
+enum StreamState<R> {
+    Idle(Box<AsyncStreamingDecoder<R>>),
+    Reading(NextEvent<R>),
+    Finished,
+}
+
+/// [`Stream`] of events produced by [`AsyncStreamingDecoder::into_stream`]
+pub struct EventStream<R> {
+    state: StreamState<R>,
+}
+
+impl<R: AsyncRead + Unpin + Send + 'static> Stream for EventStream<R> {
+    type Item = Result<StreamingEvent, StreamingError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match std::mem::replace(&mut self.state, StreamState::Finished) {
+                StreamState::Idle(mut decoder) => {
+                    self.state = StreamState::Reading(Box::pin(async move {
+                        let event = decoder.next_event().await;
+                        (decoder, event)
+                    }));
+                }
+                StreamState::Reading(mut next) => match next.as_mut().poll(cx) {
+                    Poll::Ready((decoder, Some(event))) => {
+                        self.state = StreamState::Idle(decoder);
+                        return Poll::Ready(Some(event));
+                    }
+                    Poll::Ready((_, None)) => return Poll::Ready(None),
+                    Poll::Pending => {
+                        self.state = StreamState::Reading(next);
+                        return Poll::Pending;
+                    }
+                },
+                StreamState::Finished => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Waits until the backpressure window has room
+async fn wait_for_window(flow: &Flow) {
+    loop {
+        let open = flow
+            .controller()
+            .as_ref()
+            .is_none_or(BackpressureController::can_send);
+        if open {
+            return;
+        }
+        flow.notify.notified().await;
+    }
+}
+
+fn io_error(err: std::io::Error) -> StreamingError {
+    StreamingError::Io {
+        kind: err.kind(),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::streaming::StreamingEncoder;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    fn encoded_stream(config: StreamingConfig, chunks: &[&[u8]]) -> Vec<u8> {
+        let mut encoder = StreamingEncoder::with_config(config);
+        let mut bytes = encoder.begin_stream().unwrap();
+        for chunk in chunks {
+            bytes.extend(encoder.write_chunk(chunk).unwrap());
+        }
+        bytes.extend(encoder.end_stream().unwrap());
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_decodes_stream_split_across_reads() {
+        let config = StreamingConfig::new().with_crc32c(true);
+        let bytes = encoded_stream(config.clone(), &[b"hello ", b"streaming ", b"world"]);
+        let (mut writer, reader) = tokio::io::duplex(3);
+        tokio::spawn(async move { writer.write_all(&bytes).await });
+
+        let mut decoder = AsyncStreamingDecoder::with_config(reader, config);
+        let mut events = Vec::new();
+        while let Some(event) = decoder.next_event().await {
+            events.push(event.unwrap());
+        }
+
+        assert_eq!(events.first(), Some(&StreamingEvent::StreamStarted));
+        assert_eq!(
+            events.last(),
+            Some(&StreamingEvent::StreamComplete { total_bytes: 21 })
+        );
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            decoder.complete_payload(),
+            Some(&b"hello streaming world"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_truncated_and_oversized_frames_fail() {
+        let bytes = encoded_stream(StreamingConfig::new(), &[b"abcdef"]);
+        let mut decoder = AsyncStreamingDecoder::new(&bytes[..bytes.len() - 3]);
+        let mut last = None;
+        while let Some(event) = decoder.next_event().await {
+            last = Some(event);
+        }
+        assert!(matches!(
+            last,
+            Some(Err(StreamingError::Io {
+                kind: std::io::ErrorKind::UnexpectedEof,
+                ..
+            }))
+        ));
+
+        let mut decoder = AsyncStreamingDecoder::with_config(
+            &bytes[..],
+            StreamingConfig::new().with_chunk_size(4),
+        );
+        assert!(matches!(
+            decoder.next_event().await,
+            Some(Ok(StreamingEvent::StreamStarted))
+        ));
+        assert_eq!(
+            decoder.next_event().await,
+            Some(Err(StreamingError::ChunkSizeExceeded { size: 6, max: 4 }))
+        );
+        assert_eq!(decoder.next_event().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_pauses_until_acked() {
+        let bytes = encoded_stream(StreamingConfig::new(), &[b"aaaa", b"bbbb", b"cccc"]);
+        let mut decoder = AsyncStreamingDecoder::new(&bytes[..])
+            .with_backpressure(BackpressureController::with_window_size(8));
+        let acker = decoder.acker();
+
+        for _ in 0..3 {
+            decoder.next_event().await.unwrap().unwrap();
+        }
+        assert_eq!(decoder.backpressure().unwrap().bytes_in_flight(), 8);
+
+        // Window is full: the next read waits for an acknowledgement
+        let paused = tokio::time::timeout(Duration::from_millis(20), decoder.next_event()).await;
+        assert!(paused.is_err());
+
+        acker.ack(4);
+        assert_eq!(
+            decoder.next_event().await,
+            Some(Ok(StreamingEvent::ChunkReceived { bytes: 4 }))
+        );
+        assert_eq!(decoder.backpressure().unwrap().bytes_in_flight(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        use std::future::poll_fn;
+
+        let bytes = encoded_stream(StreamingConfig::new(), &[b"xyz"]);
+        let (mut writer, reader) = tokio::io::duplex(64);
+        writer.write_all(&bytes).await.unwrap();
+        drop(writer);
+
+        let mut stream = AsyncStreamingDecoder::new(reader).into_stream();
+        let mut events = Vec::new();
+        while let Some(event) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            events.push(event.unwrap());
+        }
+        assert_eq!(
+            events,
+            vec![
+                StreamingEvent::StreamStarted,
+                StreamingEvent::ChunkReceived { bytes: 3 },
+                StreamingEvent::StreamComplete { total_bytes: 3 },
+            ]
+        );
+    }
+}
@@ -3,10 +3,16 @@
 //! The BinaryDecoder converts LNMP records from binary format (v0.4) to text format (v0.3).
 //! It validates the binary structure and ensures canonical form compliance.
 
-use super::error::BinaryError;
+use super::entry::skip_unknown_entry;
+use super::error::{BinaryError, DecodeWarning};
 use super::frame::BinaryFrame;
+use super::view::StringTableView;
+use crate::duplicates::DuplicateFieldPolicy;
 use crate::encoder::Encoder;
-use lnmp_core::LnmpRecord;
+use crate::encryption::KeyProvider;
+use crate::signing::SignatureVerifier;
+use lnmp_core::{FieldId, LnmpRecord};
+use std::sync::Arc;
 
 /// Configuration for binary decoding
 #[derive(Debug, Clone)]
@@ -25,6 +31,16 @@ pub struct DecoderConfig {
     pub allow_delta: bool,
     /// Maximum nesting depth for nested structures (v0.5)
     pub max_depth: usize,
+    /// How repeated field IDs are resolved
+    pub duplicate_fields: DuplicateFieldPolicy,
+    /// Whether entries with unknown type tags are skipped instead of failing the frame
+    pub skip_unknown_tags: bool,
+    /// Keys used to decrypt encrypted frames
+    pub key_provider: Option<Arc<dyn KeyProvider>>,
+    /// Verifier that every frame's signature must satisfy
+    pub signature_verifier: Option<Arc<dyn SignatureVerifier>>,
+    /// Sorted top-level FIDs to decode; `None` decodes every field
+    pub fid_filter: Option<Vec<FieldId>>,
 }
 
 impl Default for DecoderConfig {
@@ -37,6 +53,11 @@ impl Default for DecoderConfig {
             validate_nesting: false,
             allow_delta: false,
             max_depth: 32,
+            duplicate_fields: DuplicateFieldPolicy::KeepAll,
+            skip_unknown_tags: false,
+            key_provider: None,
+            signature_verifier: None,
+            fid_filter: None,
         }
     }
 }
@@ -84,6 +105,68 @@ impl DecoderConfig {
         self.max_depth = depth;
         self
     }
+
+    /// Sets how repeated field IDs are resolved
+    pub fn with_duplicate_fields(mut self, policy: DuplicateFieldPolicy) -> Self {
+        self.duplicate_fields = policy;
+        self
+    }
+
+    /// Sets whether entries with unknown type tags are skipped
+    ///
+    /// Lets older decoders read frames from newer producers: unknown tags carry a
+    /// length prefix, so their entries are dropped and reported as
+    /// [`DecodeWarning`]s (see [`BinaryDecoder::decode_with_warnings`]).
+    pub fn with_skip_unknown_tags(mut self, skip: bool) -> Self {
+        self.skip_unknown_tags = skip;
+        self
+    }
+
+    /// Sets the keys used to decrypt encrypted frames
+    ///
+    /// Without a key provider, encrypted frames fail with
+    /// [`EncryptionError::MissingKeyProvider`](crate::encryption::EncryptionError::MissingKeyProvider).
+    pub fn with_key_provider(mut self, keys: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(keys);
+        self
+    }
+
+    /// Requires every frame to carry a signature accepted by `verifier`
+    ///
+    /// Unsigned frames fail with
+    /// [`SignatureError::Unsigned`](crate::signing::SignatureError::Unsigned) and
+    /// tampered ones with
+    /// [`SignatureError::BadSignature`](crate::signing::SignatureError::BadSignature).
+    /// Without a verifier, signed frames are decoded without checking the signature.
+    pub fn with_signature_verifier(mut self, verifier: Arc<dyn SignatureVerifier>) -> Self {
+        self.signature_verifier = Some(verifier);
+        self
+    }
+
+    /// Decodes only the listed top-level fields
+    ///
+    /// Other entries are stepped over using their length prefixes and counts,
+    /// without materializing their values, which makes routing on a few FIDs
+    /// of a large record cheap. Skipped entries are still bounds-checked but
+    /// their contents are not validated, and FIDs inside nested records are not
+    /// filtered.
+    ///
+    /// ```
+    /// use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, DecoderConfig};
+    ///
+    /// let binary = BinaryEncoder::new().encode_text("F7=1;F12=14532;F20=\"x\"").unwrap();
+    /// let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_fid_filter([7, 20]));
+    /// let record = decoder.decode(&binary).unwrap();
+    /// assert!(record.get_field(12).is_none());
+    /// assert_eq!(record.fields().len(), 2);
+    /// ```
+    pub fn with_fid_filter(mut self, fids: impl IntoIterator<Item = FieldId>) -> Self {
+        let mut fids: Vec<FieldId> = fids.into_iter().collect();
+        fids.sort_unstable();
+        fids.dedup();
+        self.fid_filter = Some(fids);
+        self
+    }
 }
 
 /// Binary decoder for LNMP v0.4
@@ -137,11 +220,12 @@ impl BinaryDecoder {
     /// Decodes binary format to LnmpRecord
     ///
     /// The decoder will:
-    /// 1. Validate the version byte (must be 0x04)
+    /// 1. Validate the version byte (0x04, or 0x05 for frames with nested values)
     /// 2. Decode the BinaryFrame from bytes
-    /// 3. Validate field ordering (if validate_ordering is enabled)
-    /// 4. Check for trailing data (if strict_parsing is enabled)
-    /// 5. Convert the frame to an LnmpRecord
+    /// 3. Resolve duplicate field IDs according to `duplicate_fields`
+    /// 4. Validate field ordering (if validate_ordering is enabled)
+    /// 5. Check for trailing data (if strict_parsing is enabled)
+    /// 6. Convert the frame to an LnmpRecord
     ///
     /// # Arguments
     ///
@@ -154,20 +238,91 @@ impl BinaryDecoder {
     /// # Errors
     ///
     /// Returns `BinaryError` if:
-    /// - Version byte is not 0x04 (UnsupportedVersion)
+    /// - Version byte is not 0x04 or 0x05 (UnsupportedVersion)
     /// - Binary data is malformed (UnexpectedEof, InvalidVarInt, etc.)
     /// - Field ordering is invalid (CanonicalViolation, if validate_ordering is enabled)
     /// - Trailing data is present (TrailingData, if strict_parsing is enabled)
+    /// - A duplicate field ID is rejected by `duplicate_fields` (DuplicateFieldId)
+    /// - The frame is encrypted and cannot be decrypted with `key_provider` (Encryption)
+    /// - The frame is unsigned or its signature is rejected by `signature_verifier` (Signature)
     pub fn decode(&self, bytes: &[u8]) -> Result<LnmpRecord, BinaryError> {
-        // Decode the binary frame
-        let frame = if self.config.validate_ordering {
-            BinaryFrame::decode(bytes)?
-        } else {
-            BinaryFrame::decode_allow_unsorted(bytes)?
+        let (record, _warnings) = self.decode_with_warnings(bytes)?;
+        #[cfg(feature = "log")]
+        for warning in &_warnings {
+            log::warn!("{}", warning);
+        }
+        Ok(record)
+    }
+
+    /// Decodes binary format to LnmpRecord, returning non-fatal warnings
+    ///
+    /// Warnings are only produced when `skip_unknown_tags` is enabled, one per
+    /// skipped entry (including entries inside nested records).
+    pub fn decode_with_warnings(
+        &self,
+        bytes: &[u8],
+    ) -> Result<(LnmpRecord, Vec<DecodeWarning>), BinaryError> {
+        let mut warnings = Vec::new();
+
+        // Verify and strip the signature if a verifier is configured
+        let (unsigned, signed_len) = match &self.config.signature_verifier {
+            Some(verifier) => {
+                let (inner, _, consumed) =
+                    BinaryFrame::open_signed(bytes, Some(verifier.as_ref()))?;
+                (inner, Some(consumed))
+            }
+            None => (bytes, None),
+        };
+
+        // Decrypt the frame if it is encrypted and keys are available
+        let opened;
+        let (frame_bytes, sealed_len) = match &self.config.key_provider {
+            Some(keys) if BinaryFrame::is_encrypted(unsigned) => {
+                let (inner, consumed) = BinaryFrame::open_sealed(unsigned, keys.as_ref())?;
+                opened = inner;
+                (opened.as_slice(), Some(consumed))
+            }
+            _ => (unsigned, None),
+        };
+
+        // Decode the binary frame (decompressing it if needed)
+        let (frame, consumed) = BinaryFrame::decode_counting(
+            frame_bytes,
+            self.config.validate_ordering,
+            self.config.max_depth,
+            self.config.skip_unknown_tags.then_some(&mut warnings),
+            self.config.fid_filter.as_deref(),
+        )?;
+        let consumed = match sealed_len {
+            Some(_) if consumed != frame_bytes.len() => {
+                return Err(BinaryError::TrailingData {
+                    bytes_remaining: frame_bytes.len() - consumed,
+                });
+            }
+            Some(sealed_len) => sealed_len,
+            None => consumed,
+        };
+        let consumed = match signed_len {
+            Some(_) if consumed != unsigned.len() => {
+                return Err(BinaryError::TrailingData {
+                    bytes_remaining: unsigned.len() - consumed,
+                });
+            }
+            Some(signed_len) => signed_len,
+            None => consumed,
         };
 
         // Convert frame to record
-        let record = frame.to_record();
+        let mut record = frame.to_record();
+
+        // Resolve duplicate field IDs
+        if self.config.duplicate_fields != DuplicateFieldPolicy::KeepAll {
+            record = self
+                .config
+                .duplicate_fields
+                .apply(&record)
+                .map_err(|fid| BinaryError::DuplicateFieldId { fid })?;
+        }
 
         // Validate field ordering if enabled
         if self.config.validate_ordering {
@@ -175,17 +330,13 @@ impl BinaryDecoder {
         }
 
         // Check for trailing data if strict parsing is enabled
-        if self.config.strict_parsing {
-            // Calculate how many bytes were consumed
-            let consumed = self.calculate_frame_size(bytes)?;
-            if consumed < bytes.len() {
-                return Err(BinaryError::TrailingData {
-                    bytes_remaining: bytes.len() - consumed,
-                });
-            }
+        if self.config.strict_parsing && consumed < bytes.len() {
+            return Err(BinaryError::TrailingData {
+                bytes_remaining: bytes.len() - consumed,
+            });
         }
 
-        Ok(record)
+        Ok((record, warnings))
     }
 
     /// Decodes binary format to text format
@@ -268,10 +419,52 @@ impl BinaryDecoder {
     /// # Returns
     ///
     /// An `LnmpRecordView` strictly borrowing from the input `bytes`.
+    ///
+    /// Signed frames are verified against the configured signature verifier, as
+    /// in [`decode`](Self::decode). Compressed and encrypted frames cannot be
+    /// viewed without copying and are rejected with `UnsupportedFeature`.
     pub fn decode_view<'a>(
         &self,
         bytes: &'a [u8],
     ) -> Result<lnmp_core::LnmpRecordView<'a>, BinaryError> {
+        // Verify and strip the signature, mirroring decode_with_warnings
+        let (view, consumed) = match &self.config.signature_verifier {
+            Some(verifier) => self.decode_signed_view(bytes, Some(verifier.as_ref()))?,
+            None if BinaryFrame::is_signed(bytes) => self.decode_signed_view(bytes, None)?,
+            None => self.decode_view_frame(bytes)?,
+        };
+
+        // Check trailing data
+        if self.config.strict_parsing && consumed < bytes.len() {
+            return Err(BinaryError::TrailingData {
+                bytes_remaining: bytes.len() - consumed,
+            });
+        }
+
+        Ok(view)
+    }
+
+    /// Views the frame inside a signed frame, which must fill the signed region
+    fn decode_signed_view<'a>(
+        &self,
+        bytes: &'a [u8],
+        verifier: Option<&dyn SignatureVerifier>,
+    ) -> Result<(lnmp_core::LnmpRecordView<'a>, usize), BinaryError> {
+        let (inner, _, signed_len) = BinaryFrame::open_signed(bytes, verifier)?;
+        let (view, consumed) = self.decode_view_frame(inner)?;
+        if consumed != inner.len() {
+            return Err(BinaryError::TrailingData {
+                bytes_remaining: inner.len() - consumed,
+            });
+        }
+        Ok((view, signed_len))
+    }
+
+    /// Views an unsigned frame, returning it with the bytes consumed
+    fn decode_view_frame<'a>(
+        &self,
+        bytes: &'a [u8],
+    ) -> Result<(lnmp_core::LnmpRecordView<'a>, usize), BinaryError> {
         // We implement a specialized zero-copy parser here to avoid allocations
         // Logic parallels BinaryFrame::decode but produces Views
 
@@ -302,8 +495,27 @@ impl BinaryDecoder {
                 found: bytes.len(),
             });
         }
-        let _flags = bytes[offset]; // Ignored in v0.4
+        let flags = bytes[offset];
         offset += 1;
+        if flags & super::frame::FLAG_COMPRESSED != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: "zero-copy view of a compressed frame".to_string(),
+            });
+        }
+        if flags & super::frame::FLAG_ENCRYPTED != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: "zero-copy view of an encrypted frame".to_string(),
+            });
+        }
+
+        // STRING_TABLE (optional)
+        let table = if flags & super::frame::FLAG_STRING_TABLE != 0 {
+            let (table, consumed) = StringTableView::scan(&bytes[offset..])?;
+            offset += consumed;
+            Some(table)
+        } else {
+            None
+        };
 
         // ENTRY_COUNT
         let (entry_count, consumed) =
@@ -321,10 +533,18 @@ impl BinaryDecoder {
         }
         let entry_count = entry_count as usize;
 
-        let mut fields = Vec::with_capacity(entry_count);
+        let mut fields = Vec::with_capacity(entry_count.min(bytes.len()));
 
         for _ in 0..entry_count {
-            let (field, consumed) = self.decode_view_entry(&bytes[offset..])?;
+            if self.config.skip_unknown_tags {
+                if let Some((_warning, consumed)) = skip_unknown_entry(&bytes[offset..])? {
+                    #[cfg(feature = "log")]
+                    log::warn!("{}", _warning);
+                    offset += consumed;
+                    continue;
+                }
+            }
+            let (field, consumed) = self.decode_view_entry(&bytes[offset..], table.as_ref())?;
             offset += consumed;
             fields.push(field);
         }
@@ -349,19 +569,13 @@ impl BinaryDecoder {
             }
         }
 
-        // Check trailing data
-        if self.config.strict_parsing && offset < bytes.len() {
-            return Err(BinaryError::TrailingData {
-                bytes_remaining: bytes.len() - offset,
-            });
-        }
-
-        Ok(view)
+        Ok((view, offset))
     }
 
     fn decode_view_entry<'a>(
         &self,
         bytes: &'a [u8],
+        table: Option<&StringTableView<'a>>,
     ) -> Result<(lnmp_core::LnmpFieldView<'a>, usize), BinaryError> {
         use super::types::TypeTag;
         use lnmp_core::{LnmpFieldView, LnmpValueView};
@@ -453,6 +667,35 @@ impl BinaryDecoder {
                 offset += len;
                 LnmpValueView::String(s)
             }
+            TypeTag::StringRef => {
+                let (idx, c) = super::varint::decode(&bytes[offset..]).map_err(|_| {
+                    BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: "Invalid string reference".into(),
+                    }
+                })?;
+                offset += c;
+                let table = table.ok_or_else(|| BinaryError::InvalidValue {
+                    field_id: fid,
+                    type_tag: tag.to_u8(),
+                    reason: "String reference in a frame without a string table".into(),
+                })?;
+                let s_bytes = usize::try_from(idx)
+                    .ok()
+                    .and_then(|idx| table.get(idx))
+                    .ok_or_else(|| BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: format!(
+                            "String reference {} out of range (table has {} entries)",
+                            idx, table.count
+                        ),
+                    })?;
+                let s = std::str::from_utf8(s_bytes)
+                    .map_err(|_| BinaryError::InvalidUtf8 { field_id: fid })?;
+                LnmpValueView::String(s)
+            }
             TypeTag::StringArray => {
                 let (count, c) = super::varint::decode(&bytes[offset..]).map_err(|_| {
                     BinaryError::InvalidValue {
@@ -463,7 +706,7 @@ impl BinaryDecoder {
                 })?;
                 offset += c;
                 let count = count as usize;
-                let mut arr = Vec::with_capacity(count);
+                let mut arr = Vec::with_capacity(count.min(bytes.len()));
                 for _ in 0..count {
                     let (len, c) = super::varint::decode(&bytes[offset..]).map_err(|_| {
                         BinaryError::InvalidValue {
@@ -498,7 +741,7 @@ impl BinaryDecoder {
                 })?;
                 offset += c;
                 let count = count as usize;
-                let mut arr = Vec::with_capacity(count);
+                let mut arr = Vec::with_capacity(count.min(bytes.len()));
                 for _ in 0..count {
                     let (val, c) = super::varint::decode(&bytes[offset..]).map_err(|_| {
                         BinaryError::InvalidValue {
@@ -523,7 +766,7 @@ impl BinaryDecoder {
                 })?;
                 offset += c;
                 let count = count as usize;
-                let mut arr = Vec::with_capacity(count);
+                let mut arr = Vec::with_capacity(count.min(bytes.len()));
                 for _ in 0..count {
                     if bytes.len() < offset + 8 {
                         return Err(BinaryError::UnexpectedEof {
@@ -554,7 +797,7 @@ impl BinaryDecoder {
                         found: bytes.len(),
                     });
                 }
-                let mut arr = Vec::with_capacity(count);
+                let mut arr = Vec::with_capacity(count.min(bytes.len()));
                 for i in 0..count {
                     arr.push(bytes[offset + i] != 0);
                 }
@@ -614,56 +857,6 @@ impl BinaryDecoder {
         Ok(())
     }
 
-    /// Calculates the size of the frame in bytes
-    fn calculate_frame_size(&self, bytes: &[u8]) -> Result<usize, BinaryError> {
-        // Decode the frame to determine its size
-        // We need to re-decode to track the exact number of bytes consumed
-        let mut offset = 0;
-
-        // VERSION (1 byte)
-        if bytes.is_empty() {
-            return Err(BinaryError::UnexpectedEof {
-                expected: 1,
-                found: bytes.len(),
-            });
-        }
-        offset += 1;
-
-        // FLAGS (1 byte)
-        if bytes.len() < offset + 1 {
-            return Err(BinaryError::UnexpectedEof {
-                expected: offset + 1,
-                found: bytes.len(),
-            });
-        }
-        offset += 1;
-
-        // ENTRY_COUNT (VarInt)
-        let (entry_count, consumed) =
-            super::varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidVarInt {
-                reason: "Invalid entry count VarInt".to_string(),
-            })?;
-        offset += consumed;
-
-        if entry_count < 0 {
-            return Err(BinaryError::InvalidValue {
-                field_id: 0,
-                type_tag: 0,
-                reason: format!("Negative entry count: {}", entry_count),
-            });
-        }
-
-        let entry_count = entry_count as usize;
-
-        // Decode each entry to calculate size
-        for _ in 0..entry_count {
-            let (_, consumed) = super::entry::BinaryEntry::decode(&bytes[offset..])?;
-            offset += consumed;
-        }
-
-        Ok(offset)
-    }
-
     /// Detects the binary format version from the first byte
     ///
     /// This method examines the version byte to determine which version of the
@@ -1171,6 +1364,42 @@ mod tests {
         assert_eq!(current, text);
     }
 
+    #[test]
+    fn test_decode_view_resolves_string_table() {
+        let text = "F1=\"alpha\";F2=\"beta\";F3=\"alpha\";F4=42";
+        let encoder = BinaryEncoder::with_config(
+            super::super::encoder::EncoderConfig::new().with_string_table(true),
+        );
+        let binary = encoder.encode_text(text).unwrap();
+        assert_ne!(binary[1] & super::super::frame::FLAG_STRING_TABLE, 0);
+
+        let view = BinaryDecoder::new().decode_view(&binary).unwrap();
+        let values: Vec<_> = view.fields().iter().map(|f| f.value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                lnmp_core::LnmpValueView::String("alpha"),
+                lnmp_core::LnmpValueView::String("beta"),
+                lnmp_core::LnmpValueView::String("alpha"),
+                lnmp_core::LnmpValueView::Int(42),
+            ]
+        );
+        assert_eq!(
+            view.to_lnmp_record(),
+            BinaryDecoder::new().decode(&binary).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_view_rejects_out_of_range_string_ref() {
+        // Table with one string, entry referencing index 3
+        let bytes = [0x04, 0x02, 0x01, 0x01, b'a', 0x01, 0x01, 0x00, 0x0E, 0x03];
+        assert!(matches!(
+            BinaryDecoder::new().decode_view(&bytes),
+            Err(BinaryError::InvalidValue { field_id: 1, .. })
+        ));
+    }
+
     #[test]
     fn test_decode_view_zero_copy() {
         let text = "F7=1;F12=14532;F23=[\"admin\",\"dev\"]";
@@ -1517,4 +1746,163 @@ mod tests {
             LnmpValue::Int(i64::MIN)
         );
     }
+
+    #[test]
+    fn test_decode_duplicate_field_policy() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(1),
+        });
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(2),
+        });
+        let binary = BinaryEncoder::new().encode(&record).unwrap();
+
+        let decoded = BinaryDecoder::new().decode(&binary).unwrap();
+        assert_eq!(decoded.fields().len(), 2);
+
+        let config = DecoderConfig::new().with_duplicate_fields(DuplicateFieldPolicy::KeepLast);
+        let decoded = BinaryDecoder::with_config(config).decode(&binary).unwrap();
+        assert_eq!(decoded.fields().len(), 1);
+        assert_eq!(decoded.get_field(1).unwrap().value, LnmpValue::Int(2));
+
+        let config = DecoderConfig::new().with_duplicate_fields(DuplicateFieldPolicy::Error);
+        let err = BinaryDecoder::with_config(config)
+            .decode(&binary)
+            .unwrap_err();
+        assert_eq!(err, BinaryError::DuplicateFieldId { fid: 1 });
+    }
+
+    /// Frame with F1=7, an F2 entry using future tag 0x20 (3-byte payload), and F3=true
+    fn frame_with_unknown_tag() -> Vec<u8> {
+        vec![
+            0x04, 0x00, 0x03, // VERSION, FLAGS, ENTRY_COUNT
+            0x01, 0x00, 0x01, 0x07, // F1: Int 7
+            0x02, 0x00, 0x20, 0x03, 0xAA, 0xBB, 0xCC, // F2: tag 0x20, LENGTH 3
+            0x03, 0x00, 0x03, 0x01, // F3: Bool true
+        ]
+    }
+
+    #[test]
+    fn test_unknown_tag_rejected_by_default() {
+        let err = BinaryDecoder::new()
+            .decode(&frame_with_unknown_tag())
+            .unwrap_err();
+        assert_eq!(err, BinaryError::InvalidTypeTag { tag: 0x20 });
+    }
+
+    #[test]
+    fn test_skip_unknown_tags_reports_warnings() {
+        let bytes = frame_with_unknown_tag();
+        let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_skip_unknown_tags(true));
+        let (record, warnings) = decoder.decode_with_warnings(&bytes).unwrap();
+
+        assert_eq!(record.fields().len(), 2);
+        assert_eq!(record.get_field(1).unwrap().value, LnmpValue::Int(7));
+        assert_eq!(record.get_field(3).unwrap().value, LnmpValue::Bool(true));
+        assert_eq!(
+            warnings,
+            vec![DecodeWarning::UnknownTypeTag {
+                fid: 2,
+                tag: 0x20,
+                len: 3
+            }]
+        );
+
+        let view = decoder.decode_view(&bytes).unwrap();
+        assert_eq!(view.fields().len(), 2);
+    }
+
+    #[test]
+    fn test_skip_unknown_tags_rejects_truncated_payload() {
+        let mut bytes = frame_with_unknown_tag();
+        bytes[10] = 0x3F; // LENGTH beyond the end of the frame
+        let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_skip_unknown_tags(true));
+        assert!(matches!(
+            decoder.decode(&bytes),
+            Err(BinaryError::UnexpectedEof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hostile_counts_are_rejected_without_panicking() {
+        // Array count of u64::MAX-ish in a string array
+        let huge_count = [
+            0x04, 0x00, 0x01, 0x0C, 0x00, 0x05, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xFF, 0x00,
+        ];
+        assert!(BinaryDecoder::new().decode(&huge_count).is_err());
+
+        // Float array whose byte size saturates
+        let mut float_array = vec![0x04, 0x00, 0x01, 0x0C, 0x00, 0x0D];
+        float_array.extend_from_slice(&[0xFF; 9]);
+        float_array.push(0x01);
+        assert!(matches!(
+            BinaryDecoder::new().decode(&float_array),
+            Err(BinaryError::UnexpectedEof { .. }) | Err(BinaryError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fid_filter_projects_top_level_fields() {
+        let mut nested = LnmpRecord::new();
+        nested.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(5),
+        });
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Bool(true),
+        });
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        record.add_field(LnmpField {
+            fid: 20,
+            value: LnmpValue::StringArray(vec!["a".to_string(), "bc".to_string()]),
+        });
+        record.add_field(LnmpField {
+            fid: 30,
+            value: LnmpValue::NestedRecord(Box::new(nested.clone())),
+        });
+        record.add_field(LnmpField {
+            fid: 100,
+            value: LnmpValue::FloatArray(vec![1.0, 2.0]),
+        });
+        let config = super::super::encoder::EncoderConfig::new().with_nested_binary(true);
+        let binary = BinaryEncoder::with_config(config).encode(&record).unwrap();
+
+        let decoder = BinaryDecoder::with_config(
+            DecoderConfig::new()
+                .with_fid_filter([100, 7, 30, 7])
+                .with_strict_parsing(true),
+        );
+        let projected = decoder.decode(&binary).unwrap();
+        let fids: Vec<_> = projected.fields().iter().map(|f| f.fid).collect();
+        assert_eq!(fids, vec![7, 30, 100]);
+        assert_eq!(
+            projected.get_field(30).unwrap().value,
+            LnmpValue::NestedRecord(Box::new(nested))
+        );
+
+        let none = BinaryDecoder::with_config(DecoderConfig::new().with_fid_filter([]))
+            .decode(&binary)
+            .unwrap();
+        assert!(none.fields().is_empty());
+    }
+
+    #[test]
+    fn test_fid_filter_skips_do_not_read_past_frame() {
+        // F12 is a string claiming 48 bytes but only 2 remain
+        let truncated = [0x04, 0x00, 0x01, 0x0C, 0x00, 0x04, 0x30, b'h', b'i'];
+        let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_fid_filter([7]));
+        assert!(matches!(
+            decoder.decode(&truncated),
+            Err(BinaryError::UnexpectedEof { .. })
+        ));
+    }
 }
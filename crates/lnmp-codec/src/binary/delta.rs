@@ -4,6 +4,7 @@
 //! in record updates, minimizing bandwidth usage for incremental changes.
 
 use super::error::BinaryError;
+use super::types::TypeTag;
 use lnmp_core::{FieldId, LnmpRecord, LnmpValue};
 
 /// Delta operation types for partial updates
@@ -18,6 +19,11 @@ pub enum DeltaOperation {
     UpdateField = 0x03,
     /// Merge nested record (0x04)
     MergeRecord = 0x04,
+    /// Set field to an explicitly empty value (0x05)
+    ///
+    /// The payload is the single type tag of the empty value (e.g. `0x05` for an
+    /// empty string array), so a cleared field stays distinct from a deleted one.
+    ClearField = 0x05,
 }
 
 impl DeltaOperation {
@@ -28,6 +34,7 @@ impl DeltaOperation {
             0x02 => Ok(DeltaOperation::DeleteField),
             0x03 => Ok(DeltaOperation::UpdateField),
             0x04 => Ok(DeltaOperation::MergeRecord),
+            0x05 => Ok(DeltaOperation::ClearField),
             _ => Err(DeltaError::InvalidOperation { op_code: byte }),
         }
     }
@@ -218,11 +225,29 @@ impl DeltaEncoder {
             });
         }
 
-        self.diff_records(old, new)
+        self.diff_ops(old, new)
+    }
+
+    /// Diffs two records and encodes the delta packet that turns `base` into `updated`
+    ///
+    /// Nested records are diffed recursively; a MERGE_RECORD is only emitted when
+    /// it is smaller than replacing the nested record outright. Apply the result
+    /// with [`DeltaDecoder::apply`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `DeltaError` if delta is disabled in the config or a value cannot be encoded
+    pub fn diff_records(
+        &self,
+        base: &LnmpRecord,
+        updated: &LnmpRecord,
+    ) -> Result<Vec<u8>, DeltaError> {
+        let ops = self.compute_delta(base, updated)?;
+        self.encode_delta(&ops)
     }
 
     /// Identifies changed, added, and deleted fields between two records
-    fn diff_records(&self, old: &LnmpRecord, new: &LnmpRecord) -> Result<Vec<DeltaOp>, DeltaError> {
+    fn diff_ops(&self, old: &LnmpRecord, new: &LnmpRecord) -> Result<Vec<DeltaOp>, DeltaError> {
         use std::collections::HashSet;
 
         let mut ops = Vec::new();
@@ -240,6 +265,14 @@ impl DeltaEncoder {
             let new_field = new.get_field(fid);
 
             match (old_field, new_field) {
+                (old_f, Some(new_f))
+                    if is_clearable_empty(&new_f.value)
+                        && old_f.map(|f| &f.value) != Some(&new_f.value) =>
+                {
+                    // Field explicitly cleared - use CLEAR_FIELD
+                    let payload = vec![empty_value_tag(&new_f.value)];
+                    ops.push(DeltaOp::new(fid, DeltaOperation::ClearField, payload));
+                }
                 (None, Some(new_f)) => {
                     // Field added - use SET_FIELD
                     let payload = self.encode_value(&new_f.value)?;
@@ -258,10 +291,20 @@ impl DeltaEncoder {
                                 LnmpValue::NestedRecord(old_rec),
                                 LnmpValue::NestedRecord(new_rec),
                             ) => {
-                                // Recursively compute delta for nested record
-                                let nested_ops = self.diff_records(old_rec, new_rec)?;
-                                let payload = self.encode_nested_ops(&nested_ops)?;
-                                ops.push(DeltaOp::new(fid, DeltaOperation::MergeRecord, payload));
+                                // Recursively compute delta for nested record, falling
+                                // back to a full update when that is smaller
+                                let nested_ops = self.diff_ops(old_rec, new_rec)?;
+                                let merge = self.encode_nested_ops(&nested_ops)?;
+                                let update = self.encode_value(&new_f.value)?;
+                                if update.len() < merge.len() {
+                                    ops.push(DeltaOp::new(
+                                        fid,
+                                        DeltaOperation::UpdateField,
+                                        update,
+                                    ));
+                                } else {
+                                    ops.push(DeltaOp::new(fid, DeltaOperation::MergeRecord, merge));
+                                }
                             }
                             _ => {
                                 // Value changed - use UPDATE_FIELD
@@ -535,6 +578,21 @@ impl DeltaDecoder {
         Ok(ops)
     }
 
+    /// Decodes a delta packet and applies it to a copy of `base`
+    ///
+    /// The result holds the same fields as the record the delta was computed
+    /// against (see [`DeltaEncoder::diff_records`]), sorted by FID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DeltaError` if the packet is malformed or cannot be applied to `base`
+    pub fn apply(&self, base: &LnmpRecord, delta: &[u8]) -> Result<LnmpRecord, DeltaError> {
+        let ops = self.decode_delta(delta)?;
+        let mut record = base.clone();
+        self.apply_delta(&mut record, &ops)?;
+        Ok(LnmpRecord::from_sorted_fields(record.sorted_fields()))
+    }
+
     /// Applies delta operations to a base record.
     pub fn apply_delta(&self, base: &mut LnmpRecord, ops: &[DeltaOp]) -> Result<(), DeltaError> {
         self.apply_delta_with_context(base, ops, &DeltaApplyContext::default())
@@ -565,6 +623,7 @@ impl DeltaDecoder {
 
         for op in ops {
             // Validate target FID exists for operations that require it
+            #[allow(clippy::collapsible_match)]
             match op.operation {
                 DeltaOperation::UpdateField | DeltaOperation::MergeRecord => {
                     if base.get_field(op.target_fid).is_none() {
@@ -589,6 +648,15 @@ impl DeltaDecoder {
                     // Remove field from record
                     base.remove_field(op.target_fid);
                 }
+                DeltaOperation::ClearField => {
+                    // Replace field with the empty value of the payload's type
+                    let value = empty_value_for_tag(&op.payload)?;
+                    base.remove_field(op.target_fid);
+                    base.add_field(LnmpField {
+                        fid: op.target_fid,
+                        value,
+                    });
+                }
                 DeltaOperation::UpdateField => {
                     // Decode value from payload and update field
                     let value = self.decode_value(&op.payload)?;
@@ -728,6 +796,61 @@ impl Default for DeltaDecoder {
     }
 }
 
+/// Returns true if `value` is empty and can be sent as CLEAR_FIELD
+fn is_clearable_empty(value: &LnmpValue) -> bool {
+    match value {
+        LnmpValue::String(s) => s.is_empty(),
+        LnmpValue::StringArray(arr) => arr.is_empty(),
+        LnmpValue::IntArray(arr) => arr.is_empty(),
+        LnmpValue::FloatArray(arr) => arr.is_empty(),
+        LnmpValue::BoolArray(arr) => arr.is_empty(),
+        LnmpValue::NestedRecord(record) => record.fields().is_empty(),
+        LnmpValue::NestedArray(arr) => arr.is_empty(),
+        _ => false,
+    }
+}
+
+/// Type tag carried by CLEAR_FIELD for an empty `value`
+fn empty_value_tag(value: &LnmpValue) -> u8 {
+    let tag = match value {
+        LnmpValue::StringArray(_) => TypeTag::StringArray,
+        LnmpValue::IntArray(_) => TypeTag::IntArray,
+        LnmpValue::FloatArray(_) => TypeTag::FloatArray,
+        LnmpValue::BoolArray(_) => TypeTag::BoolArray,
+        LnmpValue::NestedRecord(_) => TypeTag::NestedRecord,
+        LnmpValue::NestedArray(_) => TypeTag::NestedArray,
+        _ => TypeTag::String,
+    };
+    tag.to_u8()
+}
+
+/// Builds the empty value named by a CLEAR_FIELD payload
+fn empty_value_for_tag(payload: &[u8]) -> Result<LnmpValue, DeltaError> {
+    let [tag] = payload else {
+        return Err(DeltaError::DeltaApplicationFailed {
+            reason: format!(
+                "CLEAR_FIELD payload must be 1 byte, found {}",
+                payload.len()
+            ),
+        });
+    };
+    let value = match TypeTag::from_u8(*tag)? {
+        TypeTag::String => LnmpValue::String(String::new()),
+        TypeTag::StringArray => LnmpValue::StringArray(Vec::new()),
+        TypeTag::IntArray => LnmpValue::IntArray(Vec::new()),
+        TypeTag::FloatArray => LnmpValue::FloatArray(Vec::new()),
+        TypeTag::BoolArray => LnmpValue::BoolArray(Vec::new()),
+        TypeTag::NestedRecord => LnmpValue::NestedRecord(Box::new(LnmpRecord::new())),
+        TypeTag::NestedArray => LnmpValue::NestedArray(Vec::new()),
+        _ => {
+            return Err(DeltaError::DeltaApplicationFailed {
+                reason: format!("Type tag 0x{:02X} has no empty value", tag),
+            })
+        }
+    };
+    Ok(value)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::approx_constant)]
@@ -757,7 +880,7 @@ mod tests {
     #[test]
     fn test_delta_operation_from_u8_invalid() {
         assert!(DeltaOperation::from_u8(0x00).is_err());
-        assert!(DeltaOperation::from_u8(0x05).is_err());
+        assert!(DeltaOperation::from_u8(0x06).is_err());
         assert!(DeltaOperation::from_u8(0xFF).is_err());
     }
 
@@ -767,6 +890,7 @@ mod tests {
         assert_eq!(DeltaOperation::DeleteField.to_u8(), 0x02);
         assert_eq!(DeltaOperation::UpdateField.to_u8(), 0x03);
         assert_eq!(DeltaOperation::MergeRecord.to_u8(), 0x04);
+        assert_eq!(DeltaOperation::ClearField.to_u8(), 0x05);
     }
 
     #[test]
@@ -857,6 +981,52 @@ mod tests {
         assert_eq!(ops[0].operation, DeltaOperation::UpdateField);
     }
 
+    #[test]
+    fn test_clear_field_round_trip() {
+        use lnmp_core::LnmpField;
+
+        let mut base = LnmpRecord::new();
+        base.add_field(LnmpField {
+            fid: 23,
+            value: LnmpValue::StringArray(vec!["a".to_string()]),
+        });
+        base.add_field(LnmpField {
+            fid: 24,
+            value: LnmpValue::String("b".to_string()),
+        });
+
+        let mut updated = LnmpRecord::new();
+        updated.add_field(LnmpField {
+            fid: 23,
+            value: LnmpValue::StringArray(vec![]),
+        });
+
+        let config = DeltaConfig::new().with_enable_delta(true);
+        let encoder = DeltaEncoder::with_config(config.clone());
+        let ops = encoder.compute_delta(&base, &updated).unwrap();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].operation, DeltaOperation::ClearField);
+        assert_eq!(ops[0].payload, vec![TypeTag::StringArray.to_u8()]);
+        assert_eq!(ops[1].operation, DeltaOperation::DeleteField);
+
+        let decoder = DeltaDecoder::with_config(config);
+        let decoded = decoder
+            .decode_delta(&encoder.encode_delta(&ops).unwrap())
+            .unwrap();
+        decoder.apply_delta(&mut base, &decoded).unwrap();
+        assert_eq!(base, updated);
+    }
+
+    #[test]
+    fn test_clear_field_rejects_bad_payload() {
+        let decoder = DeltaDecoder::with_config(DeltaConfig::new().with_enable_delta(true));
+        let mut record = LnmpRecord::new();
+        for payload in [vec![], vec![0x04, 0x04], vec![TypeTag::Int.to_u8()]] {
+            let op = DeltaOp::new(1, DeltaOperation::ClearField, payload);
+            assert!(decoder.apply_delta(&mut record, &[op]).is_err());
+        }
+    }
+
     #[test]
     fn test_delta_error_display_invalid_target_fid() {
         let err = DeltaError::InvalidTargetFid { fid: 999 };
@@ -0,0 +1,309 @@
+//! Record-by-record diffs between binary LNMP files
+//!
+//! A binary file here is a sequence of back-to-back binary frames, as written
+//! by calling [`BinaryEncoder::encode`](super::BinaryEncoder::encode) once per
+//! record. [`diff_files`] pairs the records of two such files by position and
+//! describes each pair as the [`DeltaOp`]s that turn the left record into the
+//! right one, which is what a state-sync peer would have to send.
+//!
+//! ```
+//! use lnmp_codec::binary::diff::{diff_files, RecordChange};
+//! use lnmp_codec::binary::BinaryEncoder;
+//!
+//! let encoder = BinaryEncoder::new();
+//! let mut a = encoder.encode_text("F1=1;F2=\"on\"").unwrap();
+//! a.extend(encoder.encode_text("F1=2").unwrap());
+//! let b = encoder.encode_text("F1=1;F2=\"off\"").unwrap();
+//!
+//! let diff = diff_files(&a, &b).unwrap();
+//! assert_eq!(diff.records[0].change, RecordChange::Changed);
+//! assert_eq!(diff.records[1].change, RecordChange::Removed);
+//! assert_eq!(diff.stats.ops, 2);
+//! ```
+//!
+//! [`DiffStats`] sums up the records, operation kinds and encoded delta size of
+//! a diff, for example to report how much state a sync would transfer.
+
+use super::delta::{DeltaConfig, DeltaEncoder, DeltaError, DeltaOp, DeltaOperation};
+use super::entry::DEFAULT_MAX_DEPTH;
+use super::error::BinaryError;
+use super::frame::BinaryFrame;
+use lnmp_core::LnmpRecord;
+use std::fmt;
+
+/// How a record differs between the two files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordChange {
+    /// Both records are equal
+    Unchanged,
+    /// Both records exist and differ
+    Changed,
+    /// Only the right file has a record at this position
+    Added,
+    /// Only the left file has a record at this position
+    Removed,
+}
+
+/// Difference between the records at one position
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordDiff {
+    /// Zero-based position of the records in their files
+    pub index: usize,
+    /// Kind of difference
+    pub change: RecordChange,
+    /// Operations turning the left record (empty if absent) into the right one
+    pub ops: Vec<DeltaOp>,
+    /// Size of `ops` as an encoded delta packet (0 when unchanged)
+    pub delta_bytes: usize,
+}
+
+/// Summary counts for a [`BinaryDiff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    /// Records in the left file
+    pub left_records: usize,
+    /// Records in the right file
+    pub right_records: usize,
+    /// Positions whose records are equal
+    pub unchanged: usize,
+    /// Positions whose records differ
+    pub changed: usize,
+    /// Records only in the right file
+    pub added: usize,
+    /// Records only in the left file
+    pub removed: usize,
+    /// Total delta operations
+    pub ops: usize,
+    /// SET_FIELD operations
+    pub set_fields: usize,
+    /// UPDATE_FIELD operations
+    pub updated_fields: usize,
+    /// DELETE_FIELD operations
+    pub deleted_fields: usize,
+    /// MERGE_RECORD operations
+    pub merged_records: usize,
+    /// CLEAR_FIELD operations
+    pub cleared_fields: usize,
+    /// Total size of the encoded delta packets
+    pub delta_bytes: usize,
+}
+
+impl DiffStats {
+    fn record(&mut self, diff: &RecordDiff) {
+        match diff.change {
+            RecordChange::Unchanged => self.unchanged += 1,
+            RecordChange::Changed => self.changed += 1,
+            RecordChange::Added => self.added += 1,
+            RecordChange::Removed => self.removed += 1,
+        }
+        for op in &diff.ops {
+            self.ops += 1;
+            match op.operation {
+                DeltaOperation::SetField => self.set_fields += 1,
+                DeltaOperation::UpdateField => self.updated_fields += 1,
+                DeltaOperation::DeleteField => self.deleted_fields += 1,
+                DeltaOperation::MergeRecord => self.merged_records += 1,
+                DeltaOperation::ClearField => self.cleared_fields += 1,
+            }
+        }
+        self.delta_bytes += diff.delta_bytes;
+    }
+}
+
+impl fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vs {} records: {} unchanged, {} changed, {} added, {} removed; \
+             {} ops (set {}, update {}, delete {}, merge {}, clear {}), {} delta bytes",
+            self.left_records,
+            self.right_records,
+            self.unchanged,
+            self.changed,
+            self.added,
+            self.removed,
+            self.ops,
+            self.set_fields,
+            self.updated_fields,
+            self.deleted_fields,
+            self.merged_records,
+            self.cleared_fields,
+            self.delta_bytes
+        )
+    }
+}
+
+/// Result of diffing two files
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryDiff {
+    /// One entry per position, including unchanged ones
+    pub records: Vec<RecordDiff>,
+    /// Summary counts
+    pub stats: DiffStats,
+}
+
+impl BinaryDiff {
+    /// Returns true if both files hold the same records
+    pub fn is_empty(&self) -> bool {
+        self.stats.ops == 0 && self.stats.left_records == self.stats.right_records
+    }
+
+    /// Iterates over the positions that differ
+    pub fn changes(&self) -> impl Iterator<Item = &RecordDiff> {
+        self.records
+            .iter()
+            .filter(|diff| diff.change != RecordChange::Unchanged)
+    }
+}
+
+/// Decodes every record of a file of back-to-back binary frames
+///
+/// Compressed, string-table and signed frames are accepted (signatures are not
+/// verified); encrypted frames fail with `MissingKeyProvider`.
+pub fn decode_records(bytes: &[u8]) -> Result<Vec<LnmpRecord>, BinaryError> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (frame, consumed) =
+            BinaryFrame::decode_counting(&bytes[offset..], false, DEFAULT_MAX_DEPTH, None, None)?;
+        offset += consumed;
+        records.push(frame.to_record());
+    }
+    Ok(records)
+}
+
+/// Diffs two files of back-to-back binary frames record by record
+pub fn diff_files(left: &[u8], right: &[u8]) -> Result<BinaryDiff, DeltaError> {
+    diff_records(&decode_records(left)?, &decode_records(right)?)
+}
+
+/// Diffs two record sequences position by position
+pub fn diff_records(left: &[LnmpRecord], right: &[LnmpRecord]) -> Result<BinaryDiff, DeltaError> {
+    let encoder = DeltaEncoder::with_config(DeltaConfig::new().with_enable_delta(true));
+    let empty = LnmpRecord::new();
+    let mut diff = BinaryDiff {
+        records: Vec::with_capacity(left.len().max(right.len())),
+        stats: DiffStats {
+            left_records: left.len(),
+            right_records: right.len(),
+            ..Default::default()
+        },
+    };
+
+    for index in 0..left.len().max(right.len()) {
+        let (old, new, change) = match (left.get(index), right.get(index)) {
+            (Some(old), Some(new)) if old.canonical_eq(new) => (old, new, RecordChange::Unchanged),
+            (Some(old), Some(new)) => (old, new, RecordChange::Changed),
+            (None, Some(new)) => (&empty, new, RecordChange::Added),
+            (Some(old), None) => (old, &empty, RecordChange::Removed),
+            (None, None) => unreachable!("index is below the longer length"),
+        };
+        let ops = match change {
+            RecordChange::Unchanged => Vec::new(),
+            _ => encoder.compute_delta(old, new)?,
+        };
+        let delta_bytes = if ops.is_empty() {
+            0
+        } else {
+            encoder.encode_delta(&ops)?.len()
+        };
+        let record = RecordDiff {
+            index,
+            change,
+            ops,
+            delta_bytes,
+        };
+        diff.stats.record(&record);
+        diff.records.push(record);
+    }
+
+    #[cfg(feature = "log")]
+    log::debug!("binary diff: {}", diff.stats);
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::delta::DeltaDecoder;
+    use crate::binary::BinaryEncoder;
+
+    fn file(texts: &[&str]) -> Vec<u8> {
+        let encoder = BinaryEncoder::new();
+        texts
+            .iter()
+            .flat_map(|text| encoder.encode_text(text).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_records_splits_frames() {
+        let bytes = file(&["F1=1", "F2=\"x\";F3=1.5", "F4=[a,b]"]);
+        let records = decode_records(&bytes).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].fields().len(), 2);
+
+        assert!(decode_records(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_records(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_files_classifies_records() {
+        let left = file(&["F1=1;F2=\"on\"", "F1=2", "F1=3;F9=0"]);
+        let right = file(&["F1=1;F2=\"on\"", "F1=2;F5=1", "F9=0", "F1=4"]);
+        let diff = diff_files(&left, &right).unwrap();
+
+        let changes: Vec<_> = diff.records.iter().map(|d| d.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                RecordChange::Unchanged,
+                RecordChange::Changed,
+                RecordChange::Changed,
+                RecordChange::Added,
+            ]
+        );
+        assert_eq!(diff.changes().count(), 3);
+        assert_eq!(
+            diff.stats,
+            DiffStats {
+                left_records: 3,
+                right_records: 4,
+                unchanged: 1,
+                changed: 2,
+                added: 1,
+                removed: 0,
+                ops: 3,
+                set_fields: 2,
+                updated_fields: 0,
+                deleted_fields: 1,
+                merged_records: 0,
+                cleared_fields: 0,
+                delta_bytes: diff.records.iter().map(|d| d.delta_bytes).sum(),
+            }
+        );
+        assert!(!diff.is_empty());
+        assert!(diff_files(&left, &left).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ops_replay_left_into_right() {
+        let left_records = decode_records(&file(&["F1=1;F2=\"a\"", "F3=1"])).unwrap();
+        let right_records = decode_records(&file(&["F1=7;F4=[x]"])).unwrap();
+        let diff = diff_records(&left_records, &right_records).unwrap();
+        let decoder = DeltaDecoder::with_config(DeltaConfig::new().with_enable_delta(true));
+
+        let mut replayed = left_records[0].clone();
+        decoder
+            .apply_delta(&mut replayed, &diff.records[0].ops)
+            .unwrap();
+        assert!(replayed.canonical_eq(&right_records[0]));
+
+        let mut removed = left_records[1].clone();
+        decoder
+            .apply_delta(&mut removed, &diff.records[1].ops)
+            .unwrap();
+        assert!(removed.fields().is_empty());
+        assert_eq!(diff.records[1].change, RecordChange::Removed);
+    }
+}
@@ -6,9 +6,18 @@
 use super::delta::{DeltaConfig, DeltaEncoder};
 use super::error::BinaryError;
 use super::frame::BinaryFrame;
+use crate::compression::CompressionConfig;
 use crate::config::{ParserConfig, ParsingMode, TextInputMode};
+use crate::encryption::PayloadCipher;
 use crate::parser::Parser;
-use lnmp_core::{LnmpField, LnmpRecord};
+use crate::signing::FrameSigner;
+use lnmp_core::{FieldId, LnmpField, LnmpRecord};
+use lnmp_sfe::SemanticDictionary;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Importance assumed for fields the dictionary has no level for
+pub const DEFAULT_FIELD_IMPORTANCE: u8 = 128;
 
 /// Configuration for binary encoding
 #[derive(Debug, Clone)]
@@ -21,6 +30,8 @@ pub struct EncoderConfig {
     pub text_input_mode: TextInputMode,
     /// Optional semantic dictionary for value normalization prior to encoding
     pub semantic_dictionary: Option<lnmp_sfe::SemanticDictionary>,
+    /// Optional shared, hot-reloadable dictionary (takes precedence over `semantic_dictionary`)
+    pub shared_dictionary: Option<lnmp_sfe::SharedDictionary>,
 
     // v0.5 fields
     /// Whether to enable nested binary structure encoding (v0.5)
@@ -33,6 +44,14 @@ pub struct EncoderConfig {
     pub delta_mode: bool,
     /// Chunk size for streaming mode in bytes (v0.5)
     pub chunk_size: usize,
+    /// Optional payload compression; frames below its threshold stay uncompressed
+    pub compression: Option<CompressionConfig>,
+    /// Whether to intern repeated strings in a per-frame string table (v0.6)
+    pub string_table: bool,
+    /// Optional payload encryption, applied after compression
+    pub encryption: Option<PayloadCipher>,
+    /// Optional frame signing, applied last so signatures cover encrypted bytes
+    pub signer: Option<FrameSigner>,
 }
 
 impl Default for EncoderConfig {
@@ -42,12 +61,17 @@ impl Default for EncoderConfig {
             sort_fields: true,
             text_input_mode: TextInputMode::Strict,
             semantic_dictionary: None,
+            shared_dictionary: None,
             // v0.5 defaults
             enable_nested_binary: false,
             max_depth: 32,
             streaming_mode: false,
             delta_mode: false,
             chunk_size: 4096,
+            compression: None,
+            string_table: false,
+            encryption: None,
+            signer: None,
         }
     }
 }
@@ -83,6 +107,12 @@ impl EncoderConfig {
         self
     }
 
+    /// Attaches a shared dictionary; encoders see its updates without being rebuilt.
+    pub fn with_shared_dictionary(mut self, dict: lnmp_sfe::SharedDictionary) -> Self {
+        self.shared_dictionary = Some(dict);
+        self
+    }
+
     // v0.5 builder methods
 
     /// Enables nested binary structure encoding (v0.5)
@@ -115,6 +145,39 @@ impl EncoderConfig {
         self
     }
 
+    /// Compresses encoded frames with the given configuration
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sets whether repeated strings are written once in a per-frame string table (v0.6)
+    ///
+    /// Only enable this when the peer negotiated `supports_string_table`; older
+    /// decoders reject frames carrying a string table.
+    pub fn with_string_table(mut self, enable: bool) -> Self {
+        self.string_table = enable;
+        self
+    }
+
+    /// Encrypts encoded frames with the given cipher
+    ///
+    /// Decoders need a matching key provider (see
+    /// [`DecoderConfig::with_key_provider`](super::DecoderConfig::with_key_provider)).
+    pub fn with_encryption(mut self, cipher: PayloadCipher) -> Self {
+        self.encryption = Some(cipher);
+        self
+    }
+
+    /// Signs encoded frames with the given signer
+    ///
+    /// Decoders reject tampered frames when configured with a matching verifier (see
+    /// [`DecoderConfig::with_signature_verifier`](super::DecoderConfig::with_signature_verifier)).
+    pub fn with_signer(mut self, signer: FrameSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
     /// Configures the encoder for v0.4 compatibility mode
     ///
     /// This disables all v0.5 features (nested structures, streaming, delta encoding)
@@ -134,10 +197,66 @@ impl EncoderConfig {
         self.enable_nested_binary = false;
         self.streaming_mode = false;
         self.delta_mode = false;
+        self.string_table = false;
         self
     }
 }
 
+/// Order in which [`BinaryEncoder::encode_bounded`] drops fields
+#[derive(Debug, Clone)]
+pub enum DropPolicy {
+    /// Never drop fields; records over the budget fail
+    Never,
+    /// Drop fields with the lowest dictionary importance first
+    ///
+    /// Fields without an importance level count as
+    /// [`DEFAULT_FIELD_IMPORTANCE`]. Ties drop the higher FID first.
+    Importance(Arc<SemanticDictionary>),
+    /// Keep the listed FIDs longest, in order of decreasing priority
+    ///
+    /// Unlisted fields are dropped first (higher FID first), then listed ones
+    /// from the end of the list.
+    Priority(Vec<FieldId>),
+}
+
+impl DropPolicy {
+    /// FIDs of `record` in the order they are dropped
+    pub fn drop_order(&self, record: &LnmpRecord) -> Vec<FieldId> {
+        let fids: BTreeSet<FieldId> = record.fields().iter().map(|f| f.fid).collect();
+        let mut order: Vec<FieldId> = fids.into_iter().rev().collect();
+        match self {
+            DropPolicy::Never => order.clear(),
+            DropPolicy::Importance(dict) => order.sort_by_key(|fid| {
+                dict.get_importance(*fid)
+                    .unwrap_or(DEFAULT_FIELD_IMPORTANCE)
+            }),
+            DropPolicy::Priority(priority) => order.sort_by_key(|fid| {
+                priority
+                    .iter()
+                    .position(|p| p == fid)
+                    .map_or(0, |rank| priority.len() - rank)
+            }),
+        }
+        order
+    }
+}
+
+/// Frame produced by [`BinaryEncoder::encode_bounded`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundedEncoding {
+    /// Encoded frame, no larger than the requested budget
+    pub bytes: Vec<u8>,
+    /// FIDs left out to fit the budget, in the order they were dropped
+    pub dropped: Vec<FieldId>,
+}
+
+impl BoundedEncoding {
+    /// Returns true if no field had to be dropped
+    pub fn is_complete(&self) -> bool {
+        self.dropped.is_empty()
+    }
+}
+
 /// Binary encoder for LNMP v0.4
 ///
 /// Converts LNMP records from text format (v0.3) to binary format (v0.4).
@@ -183,12 +302,11 @@ impl BinaryEncoder {
 
     /// Creates a binary encoder with custom configuration
     pub fn with_config(config: EncoderConfig) -> Self {
-        let normalizer = config.semantic_dictionary.as_ref().map(|dict| {
-            crate::normalizer::ValueNormalizer::new(crate::normalizer::NormalizationConfig {
-                semantic_dictionary: Some(dict.clone()),
-                ..crate::normalizer::NormalizationConfig::default()
-            })
-        });
+        let normalizer = crate::normalizer::NormalizationConfig::for_dictionaries(
+            config.semantic_dictionary.as_ref(),
+            config.shared_dictionary.as_ref(),
+        )
+        .map(crate::normalizer::ValueNormalizer::new);
         Self { config, normalizer }
     }
 
@@ -216,28 +334,201 @@ impl BinaryEncoder {
     /// # Errors
     ///
     /// Returns `BinaryError` if:
-    /// - The record contains nested structures when v0.4 compatibility is enabled
+    /// - The record contains nested structures and `enable_nested_binary` is off
+    /// - Nesting is deeper than `max_depth` (NestingDepthExceeded)
     /// - Field conversion fails
     pub fn encode(&self, record: &LnmpRecord) -> Result<Vec<u8>, BinaryError> {
+        let frame = self.prepare_frame(record)?;
+        self.finish_frame(&frame)
+    }
+
+    /// Encodes a record into at most `max_bytes`, dropping fields if needed
+    ///
+    /// Fields are dropped in [`DropPolicy::drop_order`], all occurrences of a
+    /// FID at once, and as few as possible are dropped. The size checked is
+    /// that of the final frame, after compression, encryption and signing.
+    /// The same record, budget and policy always give the same result.
+    ///
+    /// # Errors
+    ///
+    /// - `RecordSizeExceeded` if the record does not fit even after dropping
+    ///   every field the policy allows
+    /// - Same as [`BinaryEncoder::encode`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lnmp_codec::binary::{BinaryEncoder, DropPolicy};
+    /// use lnmp_codec::Parser;
+    ///
+    /// let record = Parser::new("F1=1;F2=\"a long description\";F3=3")
+    ///     .unwrap()
+    ///     .parse_record()
+    ///     .unwrap();
+    /// let bounded = BinaryEncoder::new()
+    ///     .encode_bounded(&record, 16, &DropPolicy::Priority(vec![1, 3]))
+    ///     .unwrap();
+    /// assert_eq!(bounded.dropped, vec![2]);
+    /// assert!(bounded.bytes.len() <= 16);
+    /// ```
+    pub fn encode_bounded(
+        &self,
+        record: &LnmpRecord,
+        max_bytes: usize,
+        policy: &DropPolicy,
+    ) -> Result<BoundedEncoding, BinaryError> {
+        let bytes = self.encode(record)?;
+        if bytes.len() <= max_bytes {
+            return Ok(BoundedEncoding {
+                bytes,
+                dropped: Vec::new(),
+            });
+        }
+
+        let order = policy.drop_order(record);
+        let without = |count: usize| -> Result<Vec<u8>, BinaryError> {
+            let dropped: BTreeSet<FieldId> = order[..count].iter().copied().collect();
+            let mut kept = LnmpRecord::new();
+            for field in record.fields() {
+                if !dropped.contains(&field.fid) {
+                    kept.add_field(field.clone());
+                }
+            }
+            self.encode(&kept)
+        };
+
+        let smallest = without(order.len())?;
+        if smallest.len() > max_bytes {
+            return Err(BinaryError::RecordSizeExceeded {
+                size: smallest.len(),
+                max: max_bytes,
+            });
+        }
+
+        // Fewest leading fields of `order` whose removal fits the budget
+        let (mut low, mut high, mut best) = (1, order.len(), smallest);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let candidate = without(mid)?;
+            if candidate.len() <= max_bytes {
+                high = mid;
+                best = candidate;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        #[cfg(feature = "log")]
+        log::debug!(
+            "dropped {} of {} fields to fit {} bytes",
+            high,
+            order.len(),
+            max_bytes
+        );
+        Ok(BoundedEncoding {
+            bytes: best,
+            dropped: order[..high].to_vec(),
+        })
+    }
+
+    /// Computes a BLAKE3 content hash of `record` in canonical binary form
+    ///
+    /// The hash covers the plain frame (fields sorted by FID, no string table,
+    /// compression, encryption or signature), so records with the same fields
+    /// hash alike regardless of how they are shipped, and the hash can be used
+    /// to deduplicate records or to reference them from envelopes. Semantic
+    /// normalization configured on this encoder is applied first.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BinaryEncoder::encode`], plus `UnsupportedFeature` when the
+    /// `blake3` feature is disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "blake3")]
+    /// # {
+    /// use lnmp_codec::binary::BinaryEncoder;
+    /// use lnmp_codec::Parser;
+    ///
+    /// let encoder = BinaryEncoder::new();
+    /// let a = Parser::new("F12=1;F7=0").unwrap().parse_record().unwrap();
+    /// let b = Parser::new("F7=0;F12=1").unwrap().parse_record().unwrap();
+    /// assert_eq!(
+    ///     encoder.canonical_hash(&a).unwrap(),
+    ///     encoder.canonical_hash(&b).unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn canonical_hash(&self, record: &LnmpRecord) -> Result<[u8; 32], BinaryError> {
+        let frame = self.prepare_frame(record)?;
+        #[cfg(feature = "blake3")]
+        {
+            Ok(*blake3::hash(&frame.encode()).as_bytes())
+        }
+        #[cfg(not(feature = "blake3"))]
+        {
+            let _ = frame;
+            Err(BinaryError::UnsupportedFeature {
+                feature: "canonical hashing (requires the `blake3` feature)".to_string(),
+            })
+        }
+    }
+
+    /// Encodes an LnmpRecord, appending the frame to `buf`
+    ///
+    /// Plain frames (no string table, compression, encryption or signing) are
+    /// written straight into `buf` without an intermediate vector, so a
+    /// producer can hand `buf.split().freeze()` to its client without copying.
+    ///
+    /// Returns the number of bytes appended.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BinaryEncoder::encode`].
+    #[cfg(feature = "bytes")]
+    pub fn encode_into_bytes(
+        &self,
+        record: &LnmpRecord,
+        buf: &mut bytes::BytesMut,
+    ) -> Result<usize, BinaryError> {
+        let frame = self.prepare_frame(record)?;
+        let start = buf.len();
+        if self.is_plain() {
+            frame.encode_into(buf);
+        } else {
+            buf.extend_from_slice(&self.finish_frame(&frame)?);
+        }
+        Ok(buf.len() - start)
+    }
+
+    /// Whether frames are written without any post-processing
+    #[cfg(feature = "bytes")]
+    fn is_plain(&self) -> bool {
+        !self.config.string_table
+            && self.config.compression.is_none()
+            && self.config.encryption.is_none()
+            && self.config.signer.is_none()
+    }
+
+    /// Validates and normalizes `record` and converts it to a sorted frame
+    fn prepare_frame(&self, record: &LnmpRecord) -> Result<BinaryFrame, BinaryError> {
         // Guardrails for unimplemented v0.5 features
         if self.config.streaming_mode {
             return Err(BinaryError::UnsupportedFeature {
                 feature: "binary streaming mode".to_string(),
             });
         }
-        if self.config.enable_nested_binary {
-            return Err(BinaryError::UnsupportedFeature {
-                feature: "nested binary encoding".to_string(),
-            });
-        }
         if self.config.chunk_size == 0 {
             return Err(BinaryError::UnsupportedFeature {
                 feature: "chunk_size=0 is invalid".to_string(),
             });
         }
 
-        // Check for v0.4 compatibility mode
-        if !self.config.enable_nested_binary {
+        if self.config.enable_nested_binary {
+            self.validate_nesting_depth(record)?;
+        } else {
             // In v0.4 compatibility mode, validate that the record doesn't contain nested structures
             self.validate_v0_4_compatibility(record)?;
         }
@@ -258,10 +549,46 @@ impl BinaryEncoder {
         };
 
         // Convert record to BinaryFrame (this automatically sorts by FID)
-        let frame = BinaryFrame::from_record(&normalized_record)?;
+        BinaryFrame::from_record(&normalized_record)
+    }
 
+    /// Encodes `frame` and applies string table, compression, encryption and signing
+    fn finish_frame(&self, frame: &BinaryFrame) -> Result<Vec<u8>, BinaryError> {
         // Encode frame to bytes
-        Ok(frame.encode())
+        let bytes = if self.config.string_table {
+            frame.encode_with_string_table()
+        } else {
+            frame.encode()
+        };
+        let bytes = match &self.config.compression {
+            Some(compression) => BinaryFrame::compress_encoded(bytes, compression)?,
+            None => bytes,
+        };
+        let bytes = match &self.config.encryption {
+            Some(cipher) => BinaryFrame::seal_encoded(&bytes, cipher)?,
+            None => bytes,
+        };
+        match &self.config.signer {
+            Some(signer) => BinaryFrame::sign_encoded(&bytes, signer),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Validates that nested structures do not exceed `max_depth`
+    fn validate_nesting_depth(&self, record: &LnmpRecord) -> Result<(), BinaryError> {
+        let depth = record
+            .fields()
+            .iter()
+            .map(|field| field.value.depth())
+            .max()
+            .unwrap_or(0);
+        if depth > self.config.max_depth {
+            return Err(BinaryError::NestingDepthExceeded {
+                depth,
+                max: self.config.max_depth,
+            });
+        }
+        Ok(())
     }
 
     /// Validates that a record is compatible with v0.4 binary format
@@ -930,6 +1257,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encoder_sees_shared_dictionary_updates() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 23,
+            value: LnmpValue::String("Admin".to_string()),
+        });
+
+        let shared = lnmp_sfe::SharedDictionary::default();
+        let encoder =
+            BinaryEncoder::with_config(EncoderConfig::new().with_shared_dictionary(shared.clone()));
+        let decode = |binary: Vec<u8>| {
+            BinaryDecoder::new()
+                .decode(&binary)
+                .unwrap()
+                .get_field(23)
+                .unwrap()
+                .value
+                .clone()
+        };
+
+        let before = decode(encoder.encode(&record).unwrap());
+        assert_eq!(before, LnmpValue::String("Admin".to_string()));
+
+        shared.update(|dict| dict.add_equivalence(23, "Admin".to_string(), "admin".to_string()));
+        let after = decode(encoder.encode(&record).unwrap());
+        assert_eq!(after, LnmpValue::String("admin".to_string()));
+    }
+
     #[test]
     fn test_encoder_rejects_streaming_mode_until_implemented() {
         let config = EncoderConfig::new().with_streaming_mode(true);
@@ -943,17 +1299,145 @@ mod tests {
         assert!(matches!(err, BinaryError::UnsupportedFeature { .. }));
     }
 
+    fn nested_sample() -> LnmpRecord {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::String("alice".to_string()),
+        });
+        inner.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::IntArray(vec![1, -2, 3]),
+        });
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 50,
+            value: LnmpValue::NestedArray(vec![inner.clone(), LnmpRecord::new()]),
+        });
+        record.add_field(LnmpField {
+            fid: 10,
+            value: LnmpValue::NestedRecord(Box::new(inner)),
+        });
+        record.add_field(LnmpField {
+            fid: 3,
+            value: LnmpValue::BoolArray(vec![true, false]),
+        });
+        record
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_encoder_compression_round_trip() {
+        let mut record = LnmpRecord::new();
+        for fid in 1..=32 {
+            record.add_field(LnmpField {
+                fid,
+                value: LnmpValue::String("repeated payload text".to_string()),
+            });
+        }
+        let plain = BinaryEncoder::new().encode(&record).unwrap();
+        let config = EncoderConfig::new().with_compression(CompressionConfig::default());
+        let compressed = BinaryEncoder::with_config(config).encode(&record).unwrap();
+        assert!(compressed.len() < plain.len());
+
+        let decoder = crate::binary::BinaryDecoder::new();
+        assert_eq!(decoder.decode(&compressed).unwrap(), record);
+    }
+
     #[test]
-    fn test_encoder_rejects_nested_binary_flag_until_implemented() {
+    fn test_encoder_string_table_round_trip() {
+        let mut record = LnmpRecord::new();
+        for fid in 1..=8 {
+            record.add_field(LnmpField {
+                fid,
+                value: LnmpValue::String("pending-review".to_string()),
+            });
+        }
+        let plain = BinaryEncoder::new().encode(&record).unwrap();
+        let config = EncoderConfig::new().with_string_table(true);
+        let interned = BinaryEncoder::with_config(config).encode(&record).unwrap();
+        assert!(interned.len() < plain.len());
+
+        let decoder = crate::binary::BinaryDecoder::new();
+        assert_eq!(decoder.decode(&interned).unwrap(), record);
+    }
+
+    #[test]
+    fn test_encoder_nested_binary_round_trip() {
         let config = EncoderConfig::new().with_nested_binary(true);
         let encoder = BinaryEncoder::with_config(config);
+        let record = nested_sample();
+
+        let bytes = encoder.encode(&record).unwrap();
+        let decoded = crate::binary::BinaryDecoder::with_config(
+            crate::binary::DecoderConfig::new().with_validate_ordering(true),
+        )
+        .decode(&bytes)
+        .unwrap();
+
+        assert_eq!(decoded.fields().len(), 3);
+        assert_eq!(
+            decoded.get_field(3).unwrap().value,
+            LnmpValue::BoolArray(vec![true, false])
+        );
+        match &decoded.get_field(10).unwrap().value {
+            LnmpValue::NestedRecord(inner) => {
+                // Nested fields are written in canonical order
+                assert_eq!(inner.fields()[0].fid, 2);
+                assert_eq!(inner.fields()[0].value, LnmpValue::IntArray(vec![1, -2, 3]));
+                assert_eq!(
+                    inner.fields()[1].value,
+                    LnmpValue::String("alice".to_string())
+                );
+            }
+            other => panic!("expected nested record, got {:?}", other),
+        }
+        match &decoded.get_field(50).unwrap().value {
+            LnmpValue::NestedArray(records) => {
+                assert_eq!(records.len(), 2);
+                assert_eq!(records[0].fields().len(), 2);
+                assert!(records[1].fields().is_empty());
+            }
+            other => panic!("expected nested array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encoder_nested_binary_enforces_max_depth() {
         let mut record = LnmpRecord::new();
         record.add_field(LnmpField {
             fid: 1,
             value: LnmpValue::Int(1),
         });
+        for fid in 2..5 {
+            let mut outer = LnmpRecord::new();
+            outer.add_field(LnmpField {
+                fid,
+                value: LnmpValue::NestedRecord(Box::new(record)),
+            });
+            record = outer;
+        }
+
+        let encoder = BinaryEncoder::with_config(
+            EncoderConfig::new()
+                .with_nested_binary(true)
+                .with_max_depth(2),
+        );
         let err = encoder.encode(&record).unwrap_err();
-        assert!(matches!(err, BinaryError::UnsupportedFeature { .. }));
+        assert!(matches!(
+            err,
+            BinaryError::NestingDepthExceeded { depth: 3, max: 2 }
+        ));
+
+        let encoder = BinaryEncoder::with_config(EncoderConfig::new().with_nested_binary(true));
+        let bytes = encoder.encode(&record).unwrap();
+        let decoder = crate::binary::BinaryDecoder::with_config(
+            crate::binary::DecoderConfig::new().with_max_depth(2),
+        );
+        assert!(matches!(
+            decoder.decode(&bytes),
+            Err(BinaryError::NestingDepthExceeded { max: 2, .. })
+        ));
     }
 
     #[test]
@@ -993,4 +1477,108 @@ mod tests {
         assert_eq!(binary[1], 0x00); // FLAGS
         assert_eq!(binary[2], 0x01); // ENTRY_COUNT=1
     }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_canonical_hash_ignores_order_and_transport_options() {
+        let mut a = LnmpRecord::new();
+        a.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::String("alpha".to_string()),
+        });
+        a.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Int(1),
+        });
+        let mut b = LnmpRecord::new();
+        b.add_field(a.fields()[1].clone());
+        b.add_field(a.fields()[0].clone());
+
+        let plain = BinaryEncoder::new();
+        let hash = plain.canonical_hash(&a).unwrap();
+        assert_eq!(hash, plain.canonical_hash(&b).unwrap());
+        assert_eq!(hash, *blake3::hash(&plain.encode(&b).unwrap()).as_bytes());
+
+        let with_table = BinaryEncoder::with_config(EncoderConfig::new().with_string_table(true));
+        assert_eq!(hash, with_table.canonical_hash(&a).unwrap());
+
+        b.add_field(LnmpField {
+            fid: 20,
+            value: LnmpValue::Bool(false),
+        });
+        assert_ne!(hash, plain.canonical_hash(&b).unwrap());
+    }
+
+    fn bounded_record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(1),
+        });
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::String("x".repeat(40)),
+        });
+        record.add_field(LnmpField {
+            fid: 3,
+            value: LnmpValue::String("y".repeat(20)),
+        });
+        record.add_field(LnmpField {
+            fid: 4,
+            value: LnmpValue::Bool(true),
+        });
+        record
+    }
+
+    #[test]
+    fn test_encode_bounded_keeps_fitting_records_whole() {
+        let encoder = BinaryEncoder::new();
+        let record = bounded_record();
+        let full = encoder.encode(&record).unwrap();
+        let bounded = encoder
+            .encode_bounded(&record, full.len(), &DropPolicy::Never)
+            .unwrap();
+        assert!(bounded.is_complete());
+        assert_eq!(bounded.bytes, full);
+
+        assert!(matches!(
+            encoder.encode_bounded(&record, full.len() - 1, &DropPolicy::Never),
+            Err(BinaryError::RecordSizeExceeded { .. })
+        ));
+        assert!(matches!(
+            encoder.encode_bounded(&record, 2, &DropPolicy::Priority(vec![])),
+            Err(BinaryError::RecordSizeExceeded { size: 3, max: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_encode_bounded_priority_drops_fewest_fields() {
+        let encoder = BinaryEncoder::new();
+        let record = bounded_record();
+        let policy = DropPolicy::Priority(vec![2, 1]);
+        assert_eq!(policy.drop_order(&record), vec![4, 3, 1, 2]);
+
+        // Dropping F4 alone saves too little; F4 and F3 together fit
+        let bounded = encoder.encode_bounded(&record, 52, &policy).unwrap();
+        assert_eq!(bounded.dropped, vec![4, 3]);
+        assert!(bounded.bytes.len() <= 52);
+        let decoded = BinaryDecoder::new().decode(&bounded.bytes).unwrap();
+        let fids: Vec<_> = decoded.fields().iter().map(|f| f.fid).collect();
+        assert_eq!(fids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_encode_bounded_importance_order() {
+        let mut dict = lnmp_sfe::SemanticDictionary::new();
+        dict.add_importance(2, 10);
+        dict.add_importance(3, 200);
+        let policy = DropPolicy::Importance(Arc::new(dict));
+        // F2 (10) first, then F4 and F1 (default 128, higher FID first), then F3
+        assert_eq!(policy.drop_order(&bounded_record()), vec![2, 4, 1, 3]);
+
+        let bounded = BinaryEncoder::new()
+            .encode_bounded(&bounded_record(), 40, &policy)
+            .unwrap();
+        assert_eq!(bounded.dropped, vec![2]);
+    }
 }
@@ -4,13 +4,27 @@
 //! - FID (Field Identifier): 2 bytes, little-endian
 //! - TAG (Type Tag): 1 byte
 //! - VALUE: Variable length, encoding depends on type
-
-use super::error::BinaryError;
+//!
+//! Nested records and nested arrays (v0.5) are encoded inline as length-prefixed
+//! bodies of regular entries, so a reader can skip them without decoding:
+//! ```text
+//! NestedRecord: LENGTH (VarInt) | FIELD_COUNT (VarInt) | ENTRY*
+//! NestedArray:  LENGTH (VarInt) | ELEMENT_COUNT (VarInt) | { FIELD_COUNT | ENTRY* }*
+//! ```
+//! `LENGTH` counts the bytes that follow it. Nested fields are written in ascending
+//! FID order.
+
+use super::error::{BinaryError, DecodeWarning};
+use super::string_table::StringTable;
 use super::types::{BinaryValue, TypeTag};
 use super::varint;
-use lnmp_core::{FieldId, LnmpField};
+use super::view::value_len;
+use lnmp_core::{FieldId, LnmpField, LnmpRecord};
 use lnmp_embedding::{Decoder as EmbeddingDecoder, Encoder as EmbeddingEncoder};
 
+/// Default maximum nesting depth accepted by [`BinaryEntry::decode`]
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
 /// A single field encoded in binary format
 #[derive(Debug, Clone, PartialEq)]
 pub struct BinaryEntry {
@@ -79,12 +93,43 @@ impl BinaryEntry {
     /// │ (2 bytes)│ (1 byte) │   (variable)     │
     /// └──────────┴──────────┴──────────────────┘
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a nested record contains a value that has no binary form
+    /// (e.g. `EmbeddingDelta`). [`BinaryEntry::from_field`] rejects such values.
     pub fn encode(&self) -> Vec<u8> {
+        self.encode_with_table(None)
+    }
+
+    /// Encodes the entry, writing strings found in `table` as [`TypeTag::StringRef`]
+    pub(crate) fn encode_with_table(&self, table: Option<&StringTable>) -> Vec<u8> {
         let mut bytes = Vec::new();
 
         // Write FID (2 bytes, little-endian)
         bytes.extend_from_slice(&self.fid.to_le_bytes());
 
+        // Strings in the frame's string table are written as an index
+        if let (Some(table), BinaryValue::String(s)) = (table, &self.value) {
+            if let Some(idx) = table.index_of(s) {
+                bytes.push(TypeTag::StringRef.to_u8());
+                bytes.extend_from_slice(&varint::encode(idx as i64));
+                return bytes;
+            }
+        }
+
+        self.encode_tagged_value_into(&mut bytes, table);
+        bytes
+    }
+
+    /// Encodes `TAG | VALUE`, the entry without its FID
+    pub(crate) fn encode_tagged_value(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.encode_tagged_value_into(&mut bytes, None);
+        bytes
+    }
+
+    fn encode_tagged_value_into(&self, bytes: &mut Vec<u8>, table: Option<&StringTable>) {
         // Write TAG (1 byte)
         bytes.push(self.tag.to_u8());
 
@@ -138,11 +183,19 @@ impl BinaryEntry {
                     bytes.push(if *b { 0x01 } else { 0x00 });
                 }
             }
-            BinaryValue::NestedRecord(_) | BinaryValue::NestedArray(_) => {
-                // Nested structure encoding will be implemented in task 2
-                // For now, this is a placeholder that should not be reached
-                // as nested encoding requires special handling
-                panic!("Nested structure encoding not yet implemented - use BinaryNestedEncoder");
+            BinaryValue::NestedRecord(record) => {
+                let mut body = Vec::new();
+                encode_record_body(record, &mut body, table);
+                bytes.extend_from_slice(&varint::encode(body.len() as i64));
+                bytes.extend_from_slice(&body);
+            }
+            BinaryValue::NestedArray(records) => {
+                let mut body = varint::encode(records.len() as i64);
+                for record in records {
+                    encode_record_body(record, &mut body, table);
+                }
+                bytes.extend_from_slice(&varint::encode(body.len() as i64));
+                bytes.extend_from_slice(&body);
             }
             BinaryValue::Embedding(vec) => {
                 // Encode using lnmp-embedding crate
@@ -175,8 +228,6 @@ impl BinaryEntry {
                 }
             }
         }
-
-        bytes
     }
 
     /// Decodes an entry from bytes
@@ -191,9 +242,61 @@ impl BinaryEntry {
     /// - `InvalidVarInt`: Malformed VarInt
     /// - `InvalidUtf8`: Invalid UTF-8 in string
     /// - `InvalidValue`: Other value decoding errors
+    /// - `NestingDepthExceeded`: Nested records deeper than [`DEFAULT_MAX_DEPTH`]
     pub fn decode(bytes: &[u8]) -> Result<(Self, usize), BinaryError> {
-        let mut offset = 0;
+        Self::decode_with_max_depth(bytes, DEFAULT_MAX_DEPTH)
+    }
 
+    /// Decodes an entry from bytes, rejecting nested records deeper than `max_depth`
+    pub fn decode_with_max_depth(
+        bytes: &[u8],
+        max_depth: usize,
+    ) -> Result<(Self, usize), BinaryError> {
+        Self::decode_at_depth(bytes, 0, max_depth, &mut DecodeContext::default())
+    }
+
+    /// Decodes an entry within a frame described by `ctx`
+    ///
+    /// Returns `None` for an entry that was skipped because of an unknown type tag
+    /// or because its FID is outside `ctx.fid_filter`.
+    pub(crate) fn decode_with_context(
+        bytes: &[u8],
+        max_depth: usize,
+        ctx: &mut DecodeContext<'_>,
+    ) -> Result<(Option<Self>, usize), BinaryError> {
+        if let (Some(filter), [lo, hi, tag, rest @ ..]) = (ctx.fid_filter, bytes) {
+            let fid = u16::from_le_bytes([*lo, *hi]);
+            if filter.binary_search(&fid).is_err() {
+                // Unknown tags fall through to the regular skip-or-fail handling
+                if let Ok(tag) = TypeTag::from_u8(*tag) {
+                    return Ok((None, 3 + value_len(rest, fid, tag)?));
+                }
+            }
+        }
+        Self::decode_or_skip(bytes, 0, max_depth, ctx)
+    }
+
+    fn decode_or_skip(
+        bytes: &[u8],
+        depth: usize,
+        max_depth: usize,
+        ctx: &mut DecodeContext<'_>,
+    ) -> Result<(Option<Self>, usize), BinaryError> {
+        if let Some(warnings) = ctx.warnings.as_deref_mut() {
+            if let Some((warning, consumed)) = skip_unknown_entry(bytes)? {
+                warnings.push(warning);
+                return Ok((None, consumed));
+            }
+        }
+        Self::decode_at_depth(bytes, depth, max_depth, ctx).map(|(entry, used)| (Some(entry), used))
+    }
+
+    fn decode_at_depth(
+        bytes: &[u8],
+        depth: usize,
+        max_depth: usize,
+        ctx: &mut DecodeContext<'_>,
+    ) -> Result<(Self, usize), BinaryError> {
         // Read FID (2 bytes, little-endian)
         if bytes.len() < 2 {
             return Err(BinaryError::UnexpectedEof {
@@ -202,8 +305,29 @@ impl BinaryEntry {
             });
         }
         let fid = u16::from_le_bytes([bytes[0], bytes[1]]);
-        offset += 2;
+        Self::decode_tagged_value_at(bytes, 2, fid, depth, max_depth, ctx)
+    }
 
+    /// Decodes `TAG | VALUE` written by [`encode_tagged_value`](Self::encode_tagged_value),
+    /// returning an entry with `fid` and the bytes consumed
+    pub(crate) fn decode_tagged_value(
+        bytes: &[u8],
+        fid: FieldId,
+        max_depth: usize,
+    ) -> Result<(Self, usize), BinaryError> {
+        Self::decode_tagged_value_at(bytes, 0, fid, 0, max_depth, &mut DecodeContext::default())
+    }
+
+    /// Decodes the `TAG | VALUE` that starts at `offset` in `bytes`; the returned
+    /// length includes the first `offset` bytes
+    fn decode_tagged_value_at(
+        bytes: &[u8],
+        mut offset: usize,
+        fid: FieldId,
+        depth: usize,
+        max_depth: usize,
+        ctx: &mut DecodeContext<'_>,
+    ) -> Result<(Self, usize), BinaryError> {
         // Read TAG (1 byte)
         if bytes.len() < offset + 1 {
             return Err(BinaryError::UnexpectedEof {
@@ -217,14 +341,129 @@ impl BinaryEntry {
         // Read VALUE (depends on type)
         let value = match tag {
             TypeTag::NestedRecord | TypeTag::NestedArray => {
-                // Nested structure decoding will be implemented in task 3
-                return Err(BinaryError::InvalidValue {
-                    field_id: fid,
-                    type_tag: tag.to_u8(),
-                    reason:
-                        "Nested structure decoding not yet implemented - use BinaryNestedDecoder"
-                            .to_string(),
-                });
+                if ctx.reject_nested {
+                    return Err(BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason:
+                            "Nested structures not supported in v0.4 frames (version 0x05 required)"
+                                .to_string(),
+                    });
+                }
+                if depth >= max_depth {
+                    return Err(BinaryError::NestingDepthExceeded {
+                        depth: depth + 1,
+                        max: max_depth,
+                    });
+                }
+                let (length, consumed) =
+                    varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: "Invalid nested length VarInt".to_string(),
+                    })?;
+                offset += consumed;
+                if length < 0 {
+                    return Err(BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: format!("Negative nested length: {}", length),
+                    });
+                }
+                let length = length as usize;
+                if bytes.len() < offset + length {
+                    return Err(BinaryError::UnexpectedEof {
+                        expected: offset + length,
+                        found: bytes.len(),
+                    });
+                }
+
+                let body = &bytes[offset..offset + length];
+                let (value, used) = if tag == TypeTag::NestedRecord {
+                    let (record, used) = decode_record_body(body, depth + 1, max_depth, ctx)?;
+                    (BinaryValue::NestedRecord(Box::new(record)), used)
+                } else {
+                    let (count, mut used) = read_count(body, fid, tag)?;
+                    let mut records = Vec::with_capacity(count.min(body.len()));
+                    for _ in 0..count {
+                        let (record, consumed) =
+                            decode_record_body(&body[used..], depth + 1, max_depth, ctx)?;
+                        used += consumed;
+                        records.push(record);
+                    }
+                    (BinaryValue::NestedArray(records), used)
+                };
+                if used != length {
+                    return Err(BinaryError::InvalidNestedStructure {
+                        reason: format!(
+                            "F{}: nested length is {} bytes but body uses {}",
+                            fid, length, used
+                        ),
+                    });
+                }
+                offset += length;
+                value
+            }
+            TypeTag::IntArray => {
+                let (count, consumed) = read_count(&bytes[offset..], fid, tag)?;
+                offset += consumed;
+                let mut values = Vec::with_capacity(count.min(bytes.len()));
+                for _ in 0..count {
+                    let (value, consumed) = varint::decode(&bytes[offset..]).map_err(|_| {
+                        BinaryError::InvalidValue {
+                            field_id: fid,
+                            type_tag: tag.to_u8(),
+                            reason: "Invalid VarInt in integer array".to_string(),
+                        }
+                    })?;
+                    offset += consumed;
+                    values.push(value);
+                }
+                BinaryValue::IntArray(values)
+            }
+            TypeTag::FloatArray => {
+                let (count, consumed) = read_count(&bytes[offset..], fid, tag)?;
+                offset += consumed;
+                let byte_size = count.saturating_mul(8);
+                if bytes.len() - offset < byte_size {
+                    return Err(BinaryError::UnexpectedEof {
+                        expected: offset.saturating_add(byte_size),
+                        found: bytes.len(),
+                    });
+                }
+                let values = bytes[offset..offset + byte_size]
+                    .chunks_exact(8)
+                    .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunk of 8 bytes")))
+                    .collect();
+                offset += byte_size;
+                BinaryValue::FloatArray(values)
+            }
+            TypeTag::BoolArray => {
+                let (count, consumed) = read_count(&bytes[offset..], fid, tag)?;
+                offset += consumed;
+                if bytes.len() - offset < count {
+                    return Err(BinaryError::UnexpectedEof {
+                        expected: offset.saturating_add(count),
+                        found: bytes.len(),
+                    });
+                }
+                let values = bytes[offset..offset + count]
+                    .iter()
+                    .map(|b| match b {
+                        0x00 => Ok(false),
+                        0x01 => Ok(true),
+                        other => Err(BinaryError::InvalidValue {
+                            field_id: fid,
+                            type_tag: tag.to_u8(),
+                            reason: format!(
+                                "Invalid boolean value: 0x{:02X} (expected 0x00 or 0x01)",
+                                other
+                            ),
+                        }),
+                    })
+                    .collect::<Result<_, _>>()?;
+                offset += count;
+                BinaryValue::BoolArray(values)
             }
             TypeTag::HybridNumericArray => {
                 // Decode flags byte
@@ -278,12 +517,36 @@ impl BinaryEntry {
                     data,
                 })
             }
-            TypeTag::QuantizedEmbedding
-            | TypeTag::IntArray
-            | TypeTag::FloatArray
-            | TypeTag::BoolArray
-            | TypeTag::Reserved0E
-            | TypeTag::Reserved0F => {
+            TypeTag::StringRef => {
+                let (idx, consumed) =
+                    varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: "Invalid string reference VarInt".to_string(),
+                    })?;
+                offset += consumed;
+                let Some(table) = ctx.table else {
+                    return Err(BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: "String reference in a frame without a string table".to_string(),
+                    });
+                };
+                let resolved = u32::try_from(idx).ok().and_then(|idx| table.get(idx));
+                let Some(s) = resolved else {
+                    return Err(BinaryError::InvalidValue {
+                        field_id: fid,
+                        type_tag: tag.to_u8(),
+                        reason: format!(
+                            "String reference {} out of range (table has {} entries)",
+                            idx,
+                            table.len()
+                        ),
+                    });
+                };
+                BinaryValue::String(s.to_string())
+            }
+            TypeTag::QuantizedEmbedding | TypeTag::Reserved0F => {
                 return Err(BinaryError::InvalidValue {
                     field_id: fid,
                     type_tag: tag.to_u8(),
@@ -390,7 +653,7 @@ impl BinaryEntry {
                 }
 
                 let count = count as usize;
-                let mut strings = Vec::with_capacity(count);
+                let mut strings = Vec::with_capacity(count.min(bytes.len()));
 
                 for _ in 0..count {
                     let (length, consumed) = varint::decode(&bytes[offset..]).map_err(|_| {
@@ -466,10 +729,123 @@ impl BinaryEntry {
             }
         };
 
+        // Resolved string references become plain strings
+        let tag = if tag == TypeTag::StringRef {
+            TypeTag::String
+        } else {
+            tag
+        };
+
         Ok((Self { fid, tag, value }, offset))
     }
 }
 
+/// Writes `FIELD_COUNT | ENTRY*` for a nested record, with entries in ascending FID order
+fn encode_record_body(record: &LnmpRecord, bytes: &mut Vec<u8>, table: Option<&StringTable>) {
+    let fields = record.sorted_fields();
+    bytes.extend_from_slice(&varint::encode(fields.len() as i64));
+    for field in &fields {
+        let entry =
+            BinaryEntry::from_field(field).expect("nested value has no binary representation");
+        bytes.extend_from_slice(&entry.encode_with_table(table));
+    }
+}
+
+/// Frame-wide state used while decoding entries
+#[derive(Debug, Default)]
+pub(crate) struct DecodeContext<'a> {
+    /// String table of the frame, if it has one
+    pub table: Option<&'a StringTable>,
+    /// Sink for skipped entries; unknown type tags are errors when `None`
+    pub warnings: Option<&'a mut Vec<DecodeWarning>>,
+    /// Sorted top-level FIDs to decode; other entries are skipped unread
+    pub fid_filter: Option<&'a [FieldId]>,
+    /// Rejects nested records and arrays, as required in version 0x04 frames
+    pub reject_nested: bool,
+}
+
+/// Skips an entry whose type tag is unknown to this decoder
+///
+/// Unknown tags (reserved 0x0F and anything above) carry `LENGTH (VarInt) | PAYLOAD`,
+/// so the entry can be stepped over. Returns `None` for known tags.
+pub(crate) fn skip_unknown_entry(
+    bytes: &[u8],
+) -> Result<Option<(DecodeWarning, usize)>, BinaryError> {
+    let (fid, tag) = match bytes {
+        [lo, hi, tag, ..] => (u16::from_le_bytes([*lo, *hi]), *tag),
+        _ => return Ok(None),
+    };
+    match TypeTag::from_u8(tag) {
+        Ok(TypeTag::Reserved0F) => {}
+        Err(_) if tag > TypeTag::Reserved0F.to_u8() => {}
+        _ => return Ok(None),
+    }
+
+    let (len, consumed) = varint::decode(&bytes[3..]).map_err(|_| BinaryError::InvalidValue {
+        field_id: fid,
+        type_tag: tag,
+        reason: "Invalid unknown-tag length VarInt".to_string(),
+    })?;
+    let len = usize::try_from(len).map_err(|_| BinaryError::InvalidValue {
+        field_id: fid,
+        type_tag: tag,
+        reason: format!("Negative unknown-tag length: {}", len),
+    })?;
+    let end = 3 + consumed + len;
+    if bytes.len() < end {
+        return Err(BinaryError::UnexpectedEof {
+            expected: end,
+            found: bytes.len(),
+        });
+    }
+    Ok(Some((DecodeWarning::UnknownTypeTag { fid, tag, len }, end)))
+}
+
+/// Reads `FIELD_COUNT | ENTRY*` at `depth`, returning the record and bytes consumed
+fn decode_record_body(
+    bytes: &[u8],
+    depth: usize,
+    max_depth: usize,
+    ctx: &mut DecodeContext<'_>,
+) -> Result<(LnmpRecord, usize), BinaryError> {
+    let (count, mut offset) =
+        varint::decode(bytes).map_err(|_| BinaryError::InvalidNestedStructure {
+            reason: "Invalid nested field count VarInt".to_string(),
+        })?;
+    if count < 0 {
+        return Err(BinaryError::InvalidNestedStructure {
+            reason: format!("Negative nested field count: {}", count),
+        });
+    }
+    let mut fields = Vec::with_capacity((count as usize).min(bytes.len()));
+    for _ in 0..count {
+        let (entry, consumed) =
+            BinaryEntry::decode_or_skip(&bytes[offset..], depth, max_depth, ctx)?;
+        offset += consumed;
+        if let Some(entry) = entry {
+            fields.push(entry.to_field());
+        }
+    }
+    Ok((LnmpRecord::from_sorted_fields(fields), offset))
+}
+
+/// Reads a non-negative VarInt element count
+fn read_count(bytes: &[u8], fid: FieldId, tag: TypeTag) -> Result<(usize, usize), BinaryError> {
+    let (count, consumed) = varint::decode(bytes).map_err(|_| BinaryError::InvalidValue {
+        field_id: fid,
+        type_tag: tag.to_u8(),
+        reason: "Invalid array count VarInt".to_string(),
+    })?;
+    if count < 0 {
+        return Err(BinaryError::InvalidValue {
+            field_id: fid,
+            type_tag: tag.to_u8(),
+            reason: format!("Negative array count: {}", count),
+        });
+    }
+    Ok((count as usize, consumed))
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::approx_constant)]
@@ -1029,4 +1405,75 @@ mod tests {
         let (decoded_neg_inf, _) = BinaryEntry::decode(&bytes_neg_inf).unwrap();
         assert_eq!(decoded_neg_inf, entry_neg_inf);
     }
+
+    #[test]
+    fn test_typed_arrays_roundtrip() {
+        for value in [
+            BinaryValue::IntArray(vec![0, -1, i64::MAX]),
+            BinaryValue::FloatArray(vec![1.5, -0.25]),
+            BinaryValue::BoolArray(vec![true, false, true]),
+            BinaryValue::IntArray(vec![]),
+        ] {
+            let entry = BinaryEntry::new(9, value);
+            let bytes = entry.encode();
+            let (decoded, consumed) = BinaryEntry::decode(&bytes).unwrap();
+            assert_eq!(decoded, entry);
+            assert_eq!(consumed, bytes.len());
+        }
+    }
+
+    #[test]
+    fn test_nested_record_layout() {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Bool(true),
+        });
+        let entry = BinaryEntry::new(20, BinaryValue::NestedRecord(Box::new(inner)));
+        let bytes = entry.encode();
+
+        assert_eq!(
+            bytes,
+            vec![
+                0x14, 0x00, 0x06, // FID 20, NestedRecord
+                0x05, // LENGTH
+                0x01, // FIELD_COUNT
+                0x01, 0x00, 0x03, 0x01, // F1 Bool(true)
+            ]
+        );
+        let (decoded, consumed) = BinaryEntry::decode(&bytes).unwrap();
+        assert_eq!(decoded, entry);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_nested_length_mismatch_rejected() {
+        // LENGTH says 6 bytes but the body only needs 5
+        let bytes = vec![0x14, 0x00, 0x06, 0x06, 0x01, 0x01, 0x00, 0x03, 0x01, 0x00];
+        assert!(matches!(
+            BinaryEntry::decode(&bytes),
+            Err(BinaryError::InvalidNestedStructure { .. })
+        ));
+    }
+
+    #[test]
+    fn test_nested_depth_limit() {
+        let mut record = LnmpRecord::new();
+        for fid in 0..3 {
+            let mut outer = LnmpRecord::new();
+            outer.add_field(LnmpField {
+                fid,
+                value: LnmpValue::NestedRecord(Box::new(record)),
+            });
+            record = outer;
+        }
+        let entry = BinaryEntry::new(7, BinaryValue::NestedRecord(Box::new(record)));
+        let bytes = entry.encode();
+
+        assert!(BinaryEntry::decode_with_max_depth(&bytes, 4).is_ok());
+        assert!(matches!(
+            BinaryEntry::decode_with_max_depth(&bytes, 3),
+            Err(BinaryError::NestingDepthExceeded { depth: 4, max: 3 })
+        ));
+    }
 }
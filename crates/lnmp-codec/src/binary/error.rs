@@ -1,6 +1,9 @@
 //! Error types for LNMP binary format operations.
 
+use crate::compression::CompressionError;
+use crate::encryption::EncryptionError;
 use crate::error::LnmpError;
+use crate::signing::SignatureError;
 
 /// Error type for binary format operations
 #[derive(Debug, Clone, PartialEq)]
@@ -109,6 +112,26 @@ pub enum BinaryError {
         /// Reason describing the delta error
         reason: String,
     },
+    /// Duplicate field ID rejected by the configured policy
+    DuplicateFieldId {
+        /// The duplicated field ID
+        fid: u16,
+    },
+    /// A field's semantic checksum changed while rewriting a frame
+    ChecksumMismatch {
+        /// The affected field ID
+        fid: u16,
+        /// Checksum of the original field
+        expected: u32,
+        /// Checksum after the rewrite (`None` if the field was lost)
+        found: Option<u32>,
+    },
+    /// Payload compression or decompression failed
+    Compression(CompressionError),
+    /// Payload encryption or decryption failed
+    Encryption(EncryptionError),
+    /// Frame signature is missing or failed verification
+    Signature(SignatureError),
 }
 
 impl std::fmt::Display for BinaryError {
@@ -186,6 +209,30 @@ impl std::fmt::Display for BinaryError {
             BinaryError::DeltaError { reason } => {
                 write!(f, "Delta error: {}", reason)
             }
+            BinaryError::DuplicateFieldId { fid } => {
+                write!(f, "Duplicate field ID {}", fid)
+            }
+            BinaryError::ChecksumMismatch {
+                fid,
+                expected,
+                found,
+            } => match found {
+                Some(found) => write!(
+                    f,
+                    "Checksum of field {} changed from {:08X} to {:08X}",
+                    fid, expected, found
+                ),
+                None => write!(f, "Field {} (checksum {:08X}) was lost", fid, expected),
+            },
+            BinaryError::Compression(err) => {
+                write!(f, "Compression error: {}", err)
+            }
+            BinaryError::Encryption(err) => {
+                write!(f, "Encryption error: {}", err)
+            }
+            BinaryError::Signature(err) => {
+                write!(f, "Signature error: {}", err)
+            }
         }
     }
 }
@@ -198,6 +245,24 @@ impl From<LnmpError> for BinaryError {
     }
 }
 
+impl From<CompressionError> for BinaryError {
+    fn from(err: CompressionError) -> Self {
+        BinaryError::Compression(err)
+    }
+}
+
+impl From<EncryptionError> for BinaryError {
+    fn from(err: EncryptionError) -> Self {
+        BinaryError::Encryption(err)
+    }
+}
+
+impl From<SignatureError> for BinaryError {
+    fn from(err: SignatureError) -> Self {
+        BinaryError::Signature(err)
+    }
+}
+
 impl From<crate::binary::delta::DeltaError> for BinaryError {
     fn from(err: crate::binary::delta::DeltaError) -> Self {
         BinaryError::DeltaError {
@@ -205,3 +270,29 @@ impl From<crate::binary::delta::DeltaError> for BinaryError {
         }
     }
 }
+
+/// Non-fatal issue reported by tolerant decoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeWarning {
+    /// An entry with a type tag this decoder does not know was skipped
+    UnknownTypeTag {
+        /// Field ID of the skipped entry
+        fid: u16,
+        /// The unknown type tag
+        tag: u8,
+        /// Length of the skipped value payload in bytes
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeWarning::UnknownTypeTag { fid, tag, len } => write!(
+                f,
+                "Skipped F{} with unknown type tag 0x{:02X} ({} bytes)",
+                fid, tag, len
+            ),
+        }
+    }
+}
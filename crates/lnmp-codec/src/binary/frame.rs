@@ -7,35 +7,77 @@
 //! │ (1 byte)│(1 byte) │  (VarInt)   │     (variable)       │
 //! └─────────┴─────────┴─────────────┴──────────────────────┘
 //! ```
+//!
+//! `VERSION` is 0x04, or 0x05 when the frame contains nested records or arrays;
+//! nested entries in a 0x04 frame are rejected.
+//!
+//! When [`FLAG_COMPRESSED`] is set, `ENTRY_COUNT` and the entries are stored
+//! compressed; see [`BinaryFrame::encode_compressed`].
+//!
+//! When [`FLAG_STRING_TABLE`] is set, a [`StringTable`] precedes `ENTRY_COUNT`
+//! and repeated strings are written as indexes into it; see
+//! [`BinaryFrame::encode_with_string_table`]. Both flags may be combined, in which
+//! case the table is compressed together with the entries.
+//!
+//! When [`FLAG_ENCRYPTED`] is set, the whole inner frame (including its own
+//! flags) is sealed with a [`PayloadCipher`]; see [`BinaryFrame::seal_encoded`].
+//!
+//! When [`FLAG_SIGNED`] is set, the inner frame is followed by a detached
+//! signature extension; see [`BinaryFrame::sign_encoded`].
 
-use super::entry::BinaryEntry;
-use super::error::BinaryError;
+use super::entry::{BinaryEntry, DecodeContext, DEFAULT_MAX_DEPTH};
+use super::error::{BinaryError, DecodeWarning};
+use super::string_table::StringTable;
+use super::types::BinaryValue;
 use super::varint;
-use lnmp_core::{LnmpField, LnmpRecord};
+use crate::compression::{self, CompressionAlgorithm, CompressionConfig};
+use crate::encryption::{self, EncryptionError, KeyProvider, PayloadCipher};
+use crate::signing::{
+    FrameSignature, FrameSigner, SignatureError, SignatureVerifier, SIGNATURE_EXT_LEN,
+};
+use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpValue};
 
 /// Protocol version for LNMP v0.4 binary format
 const VERSION_0_4: u8 = 0x04;
 
+/// Protocol version for LNMP v0.5 frames that contain nested records or arrays
+const VERSION_0_5: u8 = 0x05;
+
+/// Frame flag indicating that the entries are compressed
+pub const FLAG_COMPRESSED: u8 = 0x01;
+
+/// Frame flag indicating that a string table precedes the entries
+pub const FLAG_STRING_TABLE: u8 = 0x02;
+
+/// Frame flag indicating that an encrypted inner frame follows
+pub const FLAG_ENCRYPTED: u8 = 0x04;
+
+/// Frame flag indicating that a signed inner frame and its signature follow
+pub const FLAG_SIGNED: u8 = 0x08;
+
 /// Binary frame representing a complete LNMP record
 #[derive(Debug, Clone, PartialEq)]
 pub struct BinaryFrame {
-    /// Protocol version byte (0x04 for v0.4)
+    /// Protocol version byte (0x04 for v0.4, 0x05 when nested values are present)
     version: u8,
-    /// Flags byte (reserved for future use, 0x00 in v0.4)
+    /// Flags byte ([`FLAG_COMPRESSED`], [`FLAG_STRING_TABLE`], [`FLAG_ENCRYPTED`],
+    /// [`FLAG_SIGNED`]; other bits reserved)
     flags: u8,
     /// Entries in the frame
     entries: Vec<BinaryEntry>,
 }
 
 impl BinaryFrame {
-    /// Creates a new frame with version 0x04 and flags 0x00
+    /// Creates a new frame with flags 0x00
+    ///
+    /// The version is 0x05 if any entry holds a nested record or array, 0x04 otherwise.
     ///
     /// # Arguments
     ///
     /// * `entries` - Vector of binary entries (should be sorted by FID for canonical form)
     pub fn new(entries: Vec<BinaryEntry>) -> Self {
         Self {
-            version: VERSION_0_4,
+            version: version_for(&entries),
             flags: 0x00,
             entries,
         }
@@ -44,7 +86,7 @@ impl BinaryFrame {
     /// Encodes the frame to bytes
     ///
     /// Binary layout:
-    /// - VERSION (1 byte): 0x04
+    /// - VERSION (1 byte): 0x04, or 0x05 with nested values
     /// - FLAGS (1 byte): 0x00
     /// - ENTRY_COUNT (VarInt): Number of entries
     /// - ENTRIES: Each entry encoded sequentially
@@ -68,6 +110,209 @@ impl BinaryFrame {
         bytes
     }
 
+    /// Appends the frame to `buf`, using the same layout as [`BinaryFrame::encode`]
+    #[cfg(feature = "bytes")]
+    pub fn encode_into(&self, buf: &mut bytes::BytesMut) {
+        buf.reserve(2 + self.entries.len() * 4);
+        buf.extend_from_slice(&[self.version, self.flags]);
+        buf.extend_from_slice(&varint::encode(self.entries.len() as i64));
+        for entry in &self.entries {
+            buf.extend_from_slice(&entry.encode());
+        }
+    }
+
+    /// Encodes the frame, compressing the entries when `config` deems it worthwhile
+    ///
+    /// Compressed layout:
+    /// - VERSION (1 byte): same as the plain frame
+    /// - FLAGS (1 byte): [`FLAG_COMPRESSED`] set
+    /// - ALGORITHM (1 byte): [`CompressionAlgorithm`] identifier
+    /// - RAW_LEN (VarInt): size of the decompressed `ENTRY_COUNT | ENTRIES`
+    /// - DATA_LEN (VarInt): size of the compressed data
+    /// - DATA: compressed `ENTRY_COUNT | ENTRIES`
+    ///
+    /// Frames below the threshold, or that would not shrink, are encoded as with
+    /// [`encode`](Self::encode).
+    pub fn encode_compressed(&self, config: &CompressionConfig) -> Result<Vec<u8>, BinaryError> {
+        Self::compress_encoded(self.encode(), config)
+    }
+
+    /// Encodes the frame with a string table for repeated string values
+    ///
+    /// String table layout:
+    /// - VERSION (1 byte): same as the plain frame
+    /// - FLAGS (1 byte): [`FLAG_STRING_TABLE`] set
+    /// - STRING_TABLE: see [`StringTable`]
+    /// - ENTRY_COUNT (VarInt): Number of entries
+    /// - ENTRIES: interned strings encoded as `StringRef` indexes
+    ///
+    /// Frames without strings worth interning are encoded as with
+    /// [`encode`](Self::encode). Only send this to peers that negotiated
+    /// string table support.
+    pub fn encode_with_string_table(&self) -> Vec<u8> {
+        let table = StringTable::from_entries(&self.entries);
+        if table.is_empty() {
+            return self.encode();
+        }
+
+        let mut bytes = vec![self.version, self.flags | FLAG_STRING_TABLE];
+        table.encode_into(&mut bytes);
+        bytes.extend_from_slice(&varint::encode(self.entries.len() as i64));
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.encode_with_table(Some(&table)));
+        }
+        bytes
+    }
+
+    /// Compresses the body of an already encoded frame when `config` deems it worthwhile
+    pub(crate) fn compress_encoded(
+        plain: Vec<u8>,
+        config: &CompressionConfig,
+    ) -> Result<Vec<u8>, BinaryError> {
+        let body = &plain[2..];
+        let Some(data) = config.compress_if_worthwhile(body)? else {
+            return Ok(plain);
+        };
+
+        let mut bytes = Vec::with_capacity(data.len() + 16);
+        bytes.push(plain[0]);
+        bytes.push(plain[1] | FLAG_COMPRESSED);
+        bytes.push(config.algorithm.id());
+        bytes.extend_from_slice(&varint::encode(body.len() as i64));
+        bytes.extend_from_slice(&varint::encode(data.len() as i64));
+        bytes.extend_from_slice(&data);
+        Ok(bytes)
+    }
+
+    /// Encrypts an already encoded frame
+    ///
+    /// Encrypted layout:
+    /// - VERSION (1 byte): same as the inner frame
+    /// - FLAGS (1 byte): only [`FLAG_ENCRYPTED`] set
+    /// - SEALED_LEN (VarInt): size of the sealed inner frame
+    /// - SEALED: the complete inner frame sealed by [`PayloadCipher::seal`], with
+    ///   `VERSION | FLAGS` as associated data
+    pub fn seal_encoded(plain: &[u8], cipher: &PayloadCipher) -> Result<Vec<u8>, BinaryError> {
+        let prefix = [outer_version(plain), FLAG_ENCRYPTED];
+        let sealed = cipher.seal(plain, &prefix)?;
+
+        let mut bytes = Vec::with_capacity(sealed.len() + 8);
+        bytes.extend_from_slice(&prefix);
+        bytes.extend_from_slice(&varint::encode(sealed.len() as i64));
+        bytes.extend_from_slice(&sealed);
+        Ok(bytes)
+    }
+
+    /// Returns true if `bytes` starts with an encrypted frame
+    pub fn is_encrypted(bytes: &[u8]) -> bool {
+        bytes.len() >= 2 && bytes[1] & FLAG_ENCRYPTED != 0
+    }
+
+    /// Decrypts a frame written by [`seal_encoded`](Self::seal_encoded)
+    ///
+    /// Returns the inner frame bytes and the number of bytes consumed from `bytes`.
+    pub fn open_sealed(
+        bytes: &[u8],
+        keys: &dyn KeyProvider,
+    ) -> Result<(Vec<u8>, usize), BinaryError> {
+        if bytes.len() < 2 {
+            return Err(BinaryError::UnexpectedEof {
+                expected: 2,
+                found: bytes.len(),
+            });
+        }
+        let (len, consumed) =
+            varint::decode(&bytes[2..]).map_err(|_| BinaryError::InvalidVarInt {
+                reason: "Invalid sealed length VarInt".to_string(),
+            })?;
+        let len = usize::try_from(len).map_err(|_| BinaryError::InvalidVarInt {
+            reason: format!("Negative sealed length: {}", len),
+        })?;
+        let offset = 2 + consumed;
+        if bytes.len() - offset < len {
+            return Err(BinaryError::UnexpectedEof {
+                expected: offset + len,
+                found: bytes.len(),
+            });
+        }
+        let plain = encryption::open(keys, &bytes[offset..offset + len], &bytes[..2])?;
+        Ok((plain, offset + len))
+    }
+
+    /// Signs an already encoded (and possibly encrypted) frame
+    ///
+    /// Signed layout:
+    /// - VERSION (1 byte): same as the inner frame
+    /// - FLAGS (1 byte): only [`FLAG_SIGNED`] set
+    /// - INNER_LEN (VarInt): size of the inner frame
+    /// - INNER: the complete inner frame
+    /// - SIGNATURE: [`FrameSignature`] extension over everything before it
+    ///
+    /// An already signed frame cannot be signed again.
+    pub fn sign_encoded(inner: &[u8], signer: &FrameSigner) -> Result<Vec<u8>, BinaryError> {
+        if Self::is_signed(inner) {
+            return Err(SignatureError::Malformed("frame is already signed".to_string()).into());
+        }
+        let mut bytes = Vec::with_capacity(inner.len() + SIGNATURE_EXT_LEN + 8);
+        bytes.push(outer_version(inner));
+        bytes.push(FLAG_SIGNED);
+        bytes.extend_from_slice(&varint::encode(inner.len() as i64));
+        bytes.extend_from_slice(inner);
+        let signature = signer.sign(&bytes)?;
+        bytes.extend_from_slice(&signature.encode());
+        Ok(bytes)
+    }
+
+    /// Returns true if `bytes` starts with a signed frame
+    pub fn is_signed(bytes: &[u8]) -> bool {
+        bytes.len() >= 2 && bytes[1] & FLAG_SIGNED != 0
+    }
+
+    /// Unwraps a frame written by [`sign_encoded`](Self::sign_encoded)
+    ///
+    /// With a `verifier`, the signature is checked first and unsigned frames are
+    /// rejected with [`SignatureError::Unsigned`]. An inner frame that is itself
+    /// signed is rejected as malformed, so decoding never recurses through more
+    /// than one signature layer. Returns the inner frame, its signature and the
+    /// number of bytes consumed from `bytes`.
+    pub fn open_signed<'a>(
+        bytes: &'a [u8],
+        verifier: Option<&dyn SignatureVerifier>,
+    ) -> Result<(&'a [u8], FrameSignature, usize), BinaryError> {
+        if !Self::is_signed(bytes) {
+            return Err(SignatureError::Unsigned.into());
+        }
+        let (len, consumed) =
+            varint::decode(&bytes[2..]).map_err(|_| BinaryError::InvalidVarInt {
+                reason: "Invalid signed frame length VarInt".to_string(),
+            })?;
+        let len = usize::try_from(len).map_err(|_| BinaryError::InvalidVarInt {
+            reason: format!("Negative signed frame length: {}", len),
+        })?;
+        let inner_start = 2 + consumed;
+        let signed_end = inner_start + len;
+        if bytes.len() < signed_end + SIGNATURE_EXT_LEN {
+            return Err(BinaryError::UnexpectedEof {
+                expected: signed_end + SIGNATURE_EXT_LEN,
+                found: bytes.len(),
+            });
+        }
+        let signature = FrameSignature::decode(&bytes[signed_end..])?;
+        if let Some(verifier) = verifier {
+            verifier.verify(&bytes[..signed_end], &signature)?;
+        }
+        if Self::is_signed(&bytes[inner_start..signed_end]) {
+            return Err(
+                SignatureError::Malformed("inner frame is itself signed".to_string()).into(),
+            );
+        }
+        Ok((
+            &bytes[inner_start..signed_end],
+            signature,
+            signed_end + SIGNATURE_EXT_LEN,
+        ))
+    }
+
     /// Decodes a frame from bytes
     ///
     /// # Errors
@@ -78,15 +323,41 @@ impl BinaryFrame {
     /// - `InvalidVarInt`: Malformed entry count
     /// - Entry decoding errors
     pub fn decode(bytes: &[u8]) -> Result<Self, BinaryError> {
-        Self::decode_with_options(bytes, true)
+        Self::decode_with_options(bytes, true, DEFAULT_MAX_DEPTH)
     }
 
     /// Decodes binary frame without enforcing canonical FID ordering.
     pub fn decode_allow_unsorted(bytes: &[u8]) -> Result<Self, BinaryError> {
-        Self::decode_with_options(bytes, false)
+        Self::decode_with_options(bytes, false, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Decodes a frame, optionally enforcing canonical FID ordering at every nesting
+    /// level, and rejecting nested records deeper than `max_depth`.
+    pub fn decode_with_options(
+        bytes: &[u8],
+        enforce_sorted: bool,
+        max_depth: usize,
+    ) -> Result<Self, BinaryError> {
+        Self::decode_counting(bytes, enforce_sorted, max_depth, None, None).map(|(frame, _)| frame)
     }
 
-    fn decode_with_options(bytes: &[u8], enforce_sorted: bool) -> Result<Self, BinaryError> {
+    /// Decodes a frame and returns it together with the number of bytes consumed.
+    ///
+    /// Compressed frames are decompressed and string references resolved
+    /// transparently; the returned frame has [`FLAG_COMPRESSED`] and
+    /// [`FLAG_STRING_TABLE`] cleared.
+    ///
+    /// With a `warnings` sink, entries with unknown type tags are skipped and
+    /// reported there instead of failing the whole frame. With a sorted
+    /// `fid_filter`, top-level entries whose FID is not listed are skipped
+    /// without decoding their values.
+    pub(crate) fn decode_counting(
+        bytes: &[u8],
+        enforce_sorted: bool,
+        max_depth: usize,
+        warnings: Option<&mut Vec<DecodeWarning>>,
+        fid_filter: Option<&[FieldId]>,
+    ) -> Result<(Self, usize), BinaryError> {
         let mut offset = 0;
 
         // Read VERSION (1 byte)
@@ -100,10 +371,10 @@ impl BinaryFrame {
         offset += 1;
 
         // Validate version
-        if version != VERSION_0_4 {
+        if version != VERSION_0_4 && version != VERSION_0_5 {
             return Err(BinaryError::UnsupportedVersion {
                 found: version,
-                supported: vec![VERSION_0_4],
+                supported: vec![VERSION_0_4, VERSION_0_5],
             });
         }
 
@@ -117,30 +388,46 @@ impl BinaryFrame {
         let flags = bytes[offset];
         offset += 1;
 
-        // Decode ENTRY_COUNT (VarInt)
-        let (entry_count, consumed) =
-            varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidVarInt {
-                reason: "Invalid entry count VarInt".to_string(),
-            })?;
-        offset += consumed;
-
-        if entry_count < 0 {
-            return Err(BinaryError::InvalidValue {
-                field_id: 0,
-                type_tag: 0,
-                reason: format!("Negative entry count: {}", entry_count),
-            });
+        if flags & FLAG_SIGNED != 0 {
+            // Signatures are checked by `BinaryDecoder` when it has a verifier;
+            // here the inner frame is decoded as is.
+            let (inner, _, consumed) = Self::open_signed(bytes, None)?;
+            let (frame, used) =
+                Self::decode_counting(inner, enforce_sorted, max_depth, warnings, fid_filter)?;
+            if used != inner.len() {
+                return Err(BinaryError::TrailingData {
+                    bytes_remaining: inner.len() - used,
+                });
+            }
+            return Ok((frame, consumed));
+        }
+        if flags & FLAG_ENCRYPTED != 0 {
+            return Err(EncryptionError::MissingKeyProvider.into());
         }
 
-        let entry_count = entry_count as usize;
-        let mut entries = Vec::with_capacity(entry_count);
-
-        // Decode each entry
-        for _ in 0..entry_count {
-            let (entry, consumed) = BinaryEntry::decode(&bytes[offset..])?;
+        let entries = if flags & FLAG_COMPRESSED != 0 {
+            let (body, consumed) = read_compressed_body(&bytes[offset..])?;
             offset += consumed;
-            entries.push(entry);
-        }
+            let (entries, used) =
+                decode_entries(&body, version, flags, max_depth, warnings, fid_filter)?;
+            if used != body.len() {
+                return Err(BinaryError::TrailingData {
+                    bytes_remaining: body.len() - used,
+                });
+            }
+            entries
+        } else {
+            let (entries, used) = decode_entries(
+                &bytes[offset..],
+                version,
+                flags,
+                max_depth,
+                warnings,
+                fid_filter,
+            )?;
+            offset += used;
+            entries
+        };
 
         if enforce_sorted {
             let mut prev_fid: Option<u16> = None;
@@ -156,14 +443,24 @@ impl BinaryFrame {
                     }
                 }
                 prev_fid = Some(entry.fid);
+                match &entry.value {
+                    BinaryValue::NestedRecord(record) => validate_nested_order(record)?,
+                    BinaryValue::NestedArray(records) => {
+                        records.iter().try_for_each(validate_nested_order)?
+                    }
+                    _ => {}
+                }
             }
         }
 
-        Ok(Self {
-            version,
-            flags,
-            entries,
-        })
+        Ok((
+            Self {
+                version,
+                flags: flags & !(FLAG_COMPRESSED | FLAG_STRING_TABLE),
+                entries,
+            },
+            offset,
+        ))
     }
 
     /// Converts to LnmpRecord
@@ -177,8 +474,7 @@ impl BinaryFrame {
     ///
     /// # Errors
     ///
-    /// Returns `BinaryError::InvalidValue` if any field contains nested structures
-    /// (not supported in v0.4 binary format)
+    /// Returns `BinaryError::InvalidValue` if any field has no binary representation
     pub fn from_record(record: &LnmpRecord) -> Result<Self, BinaryError> {
         // Get fields sorted by FID for canonical form
         let sorted_fields = record.sorted_fields();
@@ -191,6 +487,237 @@ impl BinaryFrame {
 
         Ok(Self::new(entries))
     }
+
+    /// Encodes several frames as one batch body sharing a header and string table
+    ///
+    /// Layout: `VERSION | FLAGS | [STRING_TABLE] | RECORD_COUNT | (ENTRY_COUNT | ENTRIES)*`,
+    /// where each record uses the same entry encoding as a single frame. The
+    /// table is only written when `string_table` is set and some string repeats.
+    pub(crate) fn encode_batch(frames: &[BinaryFrame], string_table: bool) -> Vec<u8> {
+        let table = string_table
+            .then(|| StringTable::from_entries(frames.iter().flat_map(|f| &f.entries)))
+            .filter(|table| !table.is_empty());
+
+        let version = if frames.iter().any(|f| f.version == VERSION_0_5) {
+            VERSION_0_5
+        } else {
+            VERSION_0_4
+        };
+        let mut bytes = vec![version, 0x00];
+        if let Some(table) = &table {
+            bytes[1] |= FLAG_STRING_TABLE;
+            table.encode_into(&mut bytes);
+        }
+        bytes.extend_from_slice(&varint::encode(frames.len() as i64));
+        for frame in frames {
+            bytes.extend_from_slice(&varint::encode(frame.entries.len() as i64));
+            for entry in &frame.entries {
+                bytes.extend_from_slice(&entry.encode_with_table(table.as_ref()));
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a batch body written by [`BinaryFrame::encode_batch`]
+    pub(crate) fn decode_batch(bytes: &[u8], max_depth: usize) -> Result<Vec<Self>, BinaryError> {
+        let (version, flags) = match bytes {
+            [version, flags, ..] => (*version, *flags),
+            _ => {
+                return Err(BinaryError::UnexpectedEof {
+                    expected: 2,
+                    found: bytes.len(),
+                })
+            }
+        };
+        if version != VERSION_0_4 && version != VERSION_0_5 {
+            return Err(BinaryError::UnsupportedVersion {
+                found: version,
+                supported: vec![VERSION_0_4, VERSION_0_5],
+            });
+        }
+        if flags & !FLAG_STRING_TABLE != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: format!("batch flags 0x{:02X}", flags),
+            });
+        }
+
+        let mut offset = 2;
+        let table = if flags & FLAG_STRING_TABLE != 0 {
+            let (table, consumed) = StringTable::decode(&bytes[offset..])?;
+            offset += consumed;
+            Some(table)
+        } else {
+            None
+        };
+
+        let (count, consumed) =
+            varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidVarInt {
+                reason: "Invalid batch record count VarInt".to_string(),
+            })?;
+        offset += consumed;
+        let count = usize::try_from(count).map_err(|_| BinaryError::InvalidValue {
+            field_id: 0,
+            type_tag: 0,
+            reason: format!("Negative batch record count: {}", count),
+        })?;
+
+        let mut ctx = DecodeContext {
+            table: table.as_ref(),
+            reject_nested: version == VERSION_0_4,
+            ..Default::default()
+        };
+        let mut frames = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let (entries, used) = decode_entry_list(&bytes[offset..], max_depth, &mut ctx)?;
+            offset += used;
+            frames.push(Self::new(entries));
+        }
+        if offset != bytes.len() {
+            return Err(BinaryError::TrailingData {
+                bytes_remaining: bytes.len() - offset,
+            });
+        }
+        Ok(frames)
+    }
+}
+
+/// Decodes `[STRING_TABLE] | ENTRY_COUNT | ENTRIES`, returning the entries and bytes consumed
+fn decode_entries(
+    bytes: &[u8],
+    version: u8,
+    flags: u8,
+    max_depth: usize,
+    warnings: Option<&mut Vec<DecodeWarning>>,
+    fid_filter: Option<&[FieldId]>,
+) -> Result<(Vec<BinaryEntry>, usize), BinaryError> {
+    let (table, offset) = if flags & FLAG_STRING_TABLE != 0 {
+        let (table, consumed) = StringTable::decode(bytes)?;
+        (Some(table), consumed)
+    } else {
+        (None, 0)
+    };
+
+    let mut ctx = DecodeContext {
+        table: table.as_ref(),
+        warnings,
+        fid_filter,
+        reject_nested: version == VERSION_0_4,
+    };
+    let (entries, used) = decode_entry_list(&bytes[offset..], max_depth, &mut ctx)?;
+    Ok((entries, offset + used))
+}
+
+/// Decodes `ENTRY_COUNT | ENTRIES`, returning the entries and bytes consumed
+fn decode_entry_list(
+    bytes: &[u8],
+    max_depth: usize,
+    ctx: &mut DecodeContext<'_>,
+) -> Result<(Vec<BinaryEntry>, usize), BinaryError> {
+    let (entry_count, mut offset) =
+        varint::decode(bytes).map_err(|_| BinaryError::InvalidVarInt {
+            reason: "Invalid entry count VarInt".to_string(),
+        })?;
+
+    if entry_count < 0 {
+        return Err(BinaryError::InvalidValue {
+            field_id: 0,
+            type_tag: 0,
+            reason: format!("Negative entry count: {}", entry_count),
+        });
+    }
+
+    let entry_count = entry_count as usize;
+    let mut entries = Vec::with_capacity(entry_count.min(bytes.len()));
+
+    // Decode each entry
+    for _ in 0..entry_count {
+        let (entry, consumed) = BinaryEntry::decode_with_context(&bytes[offset..], max_depth, ctx)?;
+        offset += consumed;
+        entries.extend(entry);
+    }
+
+    Ok((entries, offset))
+}
+
+/// Reads `ALGORITHM | RAW_LEN | DATA_LEN | DATA` and returns the decompressed body
+/// together with the number of bytes consumed
+fn read_compressed_body(bytes: &[u8]) -> Result<(Vec<u8>, usize), BinaryError> {
+    let algorithm = *bytes.first().ok_or(BinaryError::UnexpectedEof {
+        expected: 1,
+        found: 0,
+    })?;
+    let algorithm = CompressionAlgorithm::from_id(algorithm)?;
+    let mut offset = 1;
+
+    let mut read_len = |what: &str| -> Result<usize, BinaryError> {
+        let (len, consumed) =
+            varint::decode(&bytes[offset..]).map_err(|_| BinaryError::InvalidVarInt {
+                reason: format!("Invalid compressed {} VarInt", what),
+            })?;
+        offset += consumed;
+        usize::try_from(len).map_err(|_| BinaryError::InvalidVarInt {
+            reason: format!("Negative compressed {}: {}", what, len),
+        })
+    };
+    let raw_len = read_len("raw length")?;
+    let data_len = read_len("data length")?;
+
+    if bytes.len() - offset < data_len {
+        return Err(BinaryError::UnexpectedEof {
+            expected: offset + data_len,
+            found: bytes.len(),
+        });
+    }
+    let body = compression::decompress(algorithm, &bytes[offset..offset + data_len], raw_len)?;
+    Ok((body, offset + data_len))
+}
+
+/// Version byte for a frame holding `entries`: 0x05 if any value is nested
+fn version_for(entries: &[BinaryEntry]) -> u8 {
+    let nested = entries.iter().any(|entry| {
+        matches!(
+            entry.value,
+            BinaryValue::NestedRecord(_) | BinaryValue::NestedArray(_)
+        )
+    });
+    if nested {
+        VERSION_0_5
+    } else {
+        VERSION_0_4
+    }
+}
+
+/// Version byte of an encrypted or signed wrapper: that of the frame it wraps
+fn outer_version(inner: &[u8]) -> u8 {
+    match inner.first() {
+        Some(&VERSION_0_5) => VERSION_0_5,
+        _ => VERSION_0_4,
+    }
+}
+
+/// Checks that a nested record and everything below it is in ascending FID order
+fn validate_nested_order(record: &LnmpRecord) -> Result<(), BinaryError> {
+    let fields = record.fields();
+    for pair in fields.windows(2) {
+        if pair[1].fid < pair[0].fid {
+            return Err(BinaryError::CanonicalViolation {
+                reason: format!(
+                    "nested entries must be sorted by FID (saw {} after {})",
+                    pair[1].fid, pair[0].fid
+                ),
+            });
+        }
+    }
+    for field in fields {
+        match &field.value {
+            LnmpValue::NestedRecord(inner) => validate_nested_order(inner)?,
+            LnmpValue::NestedArray(records) => {
+                records.iter().try_for_each(validate_nested_order)?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -575,11 +1102,12 @@ mod tests {
 
     #[test]
     fn test_version_validation() {
-        // Test that only version 0x04 is accepted
-        let valid_bytes = vec![0x04, 0x00, 0x00];
-        assert!(BinaryFrame::decode(&valid_bytes).is_ok());
+        // Test that only versions 0x04 and 0x05 are accepted
+        for version in [0x04, 0x05] {
+            assert!(BinaryFrame::decode(&[version, 0x00, 0x00]).is_ok());
+        }
 
-        let invalid_versions = vec![0x00, 0x01, 0x02, 0x03, 0x05, 0xFF];
+        let invalid_versions = vec![0x00, 0x01, 0x02, 0x03, 0x06, 0xFF];
         for version in invalid_versions {
             let bytes = vec![version, 0x00, 0x00];
             let result = BinaryFrame::decode(&bytes);
@@ -589,4 +1117,211 @@ mod tests {
             ));
         }
     }
+
+    #[test]
+    fn test_version_follows_nested_content() {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(1),
+        });
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::Bool(true),
+        });
+        assert_eq!(
+            BinaryFrame::from_record(&record).unwrap().encode()[0],
+            VERSION_0_4
+        );
+
+        record.add_field(LnmpField {
+            fid: 10,
+            value: LnmpValue::NestedArray(vec![inner]),
+        });
+        let bytes = BinaryFrame::from_record(&record).unwrap().encode();
+        assert_eq!(bytes[0], VERSION_0_5);
+        assert_eq!(BinaryFrame::decode(&bytes).unwrap().to_record(), record);
+
+        // The same entries behind a 0x04 header are rejected
+        let mut v04 = bytes;
+        v04[0] = VERSION_0_4;
+        assert!(matches!(
+            BinaryFrame::decode(&v04),
+            Err(BinaryError::InvalidValue {
+                field_id: 10,
+                type_tag: 0x07,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_unsorted_nested_entries() {
+        // F10 = NestedRecord { F5=1, F2=1 } with the inner entries out of order
+        let bytes = vec![
+            0x05, 0x00, 0x01, // VERSION, FLAGS, ENTRY_COUNT
+            0x0A, 0x00, 0x06, 0x09, 0x02, // F10 NestedRecord, LENGTH 9, FIELD_COUNT 2
+            0x05, 0x00, 0x01, 0x01, // F5 Int(1)
+            0x02, 0x00, 0x01, 0x01, // F2 Int(1)
+        ];
+        assert!(matches!(
+            BinaryFrame::decode(&bytes),
+            Err(BinaryError::CanonicalViolation { .. })
+        ));
+
+        let frame = BinaryFrame::decode_allow_unsorted(&bytes).unwrap();
+        match &frame.to_record().get_field(10).unwrap().value {
+            lnmp_core::LnmpValue::NestedRecord(inner) => {
+                assert_eq!(inner.fields()[0].fid, 5);
+            }
+            other => panic!("expected nested record, got {:?}", other),
+        }
+    }
+
+    fn repetitive_frame() -> BinaryFrame {
+        let entries = (1..=40)
+            .map(|fid| BinaryEntry {
+                fid,
+                tag: TypeTag::String,
+                value: BinaryValue::String("status=active;region=eu-west".to_string()),
+            })
+            .collect();
+        BinaryFrame::new(entries)
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_encode_compressed_round_trip() {
+        let frame = repetitive_frame();
+        let config = CompressionConfig::default();
+        let bytes = frame.encode_compressed(&config).unwrap();
+
+        assert_eq!(bytes[1], FLAG_COMPRESSED);
+        assert_eq!(bytes[2], CompressionAlgorithm::Zstd.id());
+        assert!(bytes.len() < frame.encode().len());
+
+        let decoded = BinaryFrame::decode(&bytes).unwrap();
+        assert_eq!(decoded, frame);
+        assert_eq!(decoded.flags, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn test_encode_compressed_below_threshold_is_plain() {
+        let frame = BinaryFrame::new(vec![BinaryEntry {
+            fid: 1,
+            tag: TypeTag::Int,
+            value: BinaryValue::Int(42),
+        }]);
+        let config = CompressionConfig::new(CompressionAlgorithm::Lz4);
+        assert_eq!(frame.encode_compressed(&config).unwrap(), frame.encode());
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn test_decode_rejects_corrupt_compressed_frame() {
+        let config = CompressionConfig::new(CompressionAlgorithm::Lz4);
+        let mut bytes = repetitive_frame().encode_compressed(&config).unwrap();
+        bytes[2] = 0x7F;
+        assert!(matches!(
+            BinaryFrame::decode(&bytes),
+            Err(BinaryError::Compression(
+                crate::compression::CompressionError::UnknownAlgorithm(0x7F)
+            ))
+        ));
+
+        bytes[2] = CompressionAlgorithm::Lz4.id();
+        bytes.truncate(bytes.len() - 4);
+        assert!(BinaryFrame::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_encode_with_string_table_round_trip() {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("status=active;region=eu-west".to_string()),
+        });
+        let mut entries = repetitive_frame().entries;
+        entries.push(BinaryEntry {
+            fid: 50,
+            tag: TypeTag::NestedRecord,
+            value: BinaryValue::NestedRecord(Box::new(inner)),
+        });
+        let frame = BinaryFrame::new(entries);
+        let bytes = frame.encode_with_string_table();
+
+        assert_eq!(bytes[1], FLAG_STRING_TABLE);
+        assert!(bytes.len() < frame.encode().len() / 4);
+
+        let decoded = BinaryFrame::decode(&bytes).unwrap();
+        assert_eq!(decoded, frame);
+        assert_eq!(decoded.flags, 0);
+    }
+
+    #[test]
+    fn test_encode_with_string_table_without_repeats_is_plain() {
+        let frame = BinaryFrame::new(vec![BinaryEntry {
+            fid: 1,
+            tag: TypeTag::String,
+            value: BinaryValue::String("only-once".to_string()),
+        }]);
+        assert_eq!(frame.encode_with_string_table(), frame.encode());
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn test_string_table_with_compression_round_trip() {
+        let mut entries = repetitive_frame().entries;
+        entries.push(BinaryEntry {
+            fid: 41,
+            tag: TypeTag::StringArray,
+            value: BinaryValue::StringArray(vec!["eu-west".to_string(); 64]),
+        });
+        let frame = BinaryFrame::new(entries);
+        let config = CompressionConfig::new(CompressionAlgorithm::Lz4).with_threshold(16);
+        let bytes =
+            BinaryFrame::compress_encoded(frame.encode_with_string_table(), &config).unwrap();
+
+        assert_eq!(bytes[1], FLAG_COMPRESSED | FLAG_STRING_TABLE);
+        assert_eq!(BinaryFrame::decode(&bytes).unwrap(), frame);
+    }
+
+    #[test]
+    fn test_decode_rejects_out_of_range_string_ref() {
+        // VERSION | FLAGS | 1 string "ab" | 1 entry: FID 1, StringRef 5
+        let bytes = [
+            0x04,
+            FLAG_STRING_TABLE,
+            0x01,
+            0x02,
+            b'a',
+            b'b',
+            0x01,
+            0x01,
+            0x00,
+            TypeTag::StringRef.to_u8(),
+            0x05,
+        ];
+        assert!(matches!(
+            BinaryFrame::decode(&bytes),
+            Err(BinaryError::InvalidValue { .. })
+        ));
+
+        // Same entry without the table flag
+        let bytes = [
+            0x04,
+            0x00,
+            0x01,
+            0x01,
+            0x00,
+            TypeTag::StringRef.to_u8(),
+            0x00,
+        ];
+        assert!(matches!(
+            BinaryFrame::decode(&bytes),
+            Err(BinaryError::InvalidValue { .. })
+        ));
+    }
 }
@@ -0,0 +1,562 @@
+//! Indexed container files for random record access
+//!
+//! An indexed file stores many binary records back to back and ends with an
+//! index, so readers can seek to record N or select records by timestamp or
+//! field without decoding the whole file.
+//!
+//! ```text
+//! HEADER:  MAGIC "LNMI" | VERSION (1 byte)
+//! RECORDS: binary frames as produced by BinaryEncoder
+//! INDEX:   ENTRY* (fixed 53 bytes each, little-endian)
+//!          OFFSET (u64) | LEN (u32) | FLAGS (u8) | TIMESTAMP (u64) | FID_BLOOM (32 bytes)
+//! FOOTER:  INDEX_OFFSET (u64) | COUNT (u32) | INDEX_CRC32C (u32) | MAGIC "LNMI"
+//! ```
+//!
+//! Timestamps are supplied by the writer, typically the envelope timestamp
+//! (milliseconds since the Unix epoch) of each record. The FID bloom filter
+//! covers top-level fields only.
+
+use super::decoder::BinaryDecoder;
+use super::encoder::BinaryEncoder;
+use super::error::BinaryError;
+use super::streaming::CRC32C;
+use lnmp_core::{FieldId, LnmpRecord};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::RangeBounds;
+
+/// Magic bytes at the start and end of an indexed file
+pub const INDEXED_MAGIC: [u8; 4] = *b"LNMI";
+
+/// Current indexed file format version
+pub const INDEXED_VERSION: u8 = 0x01;
+
+const HEADER_LEN: u64 = 5;
+const ENTRY_LEN: usize = 53;
+const FOOTER_LEN: usize = 20;
+const FLAG_HAS_TIMESTAMP: u8 = 0x01;
+
+/// 256-bit bloom filter over the top-level FIDs of a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FidBloom([u8; 32]);
+
+impl FidBloom {
+    /// Creates an empty filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a filter containing every top-level FID of `record`
+    pub fn from_record(record: &LnmpRecord) -> Self {
+        let mut bloom = Self::new();
+        for field in record.fields() {
+            bloom.insert(field.fid);
+        }
+        bloom
+    }
+
+    /// Adds `fid` to the filter
+    pub fn insert(&mut self, fid: FieldId) {
+        for bit in Self::bits(fid) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Returns false if `fid` is definitely absent; true may be a false positive
+    pub fn may_contain(&self, fid: FieldId) -> bool {
+        Self::bits(fid)
+            .iter()
+            .all(|bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Returns the raw filter bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn bits(fid: FieldId) -> [usize; 2] {
+        let h = (fid as u32).wrapping_mul(0x9E37_79B1);
+        [(h >> 24) as usize, ((h >> 16) & 0xFF) as usize]
+    }
+}
+
+/// Index entry describing one record in an indexed file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Byte offset of the record frame from the start of the file
+    pub offset: u64,
+    /// Length of the record frame in bytes
+    pub len: u32,
+    /// Timestamp supplied when the record was written
+    pub timestamp: Option<u64>,
+    /// Bloom filter over the record's top-level FIDs
+    pub fid_bloom: FidBloom,
+}
+
+impl IndexEntry {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.offset.to_le_bytes());
+        out.extend_from_slice(&self.len.to_le_bytes());
+        let flags = if self.timestamp.is_some() {
+            FLAG_HAS_TIMESTAMP
+        } else {
+            0
+        };
+        out.push(flags);
+        out.extend_from_slice(&self.timestamp.unwrap_or(0).to_le_bytes());
+        out.extend_from_slice(self.fid_bloom.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let mut bloom = [0u8; 32];
+        bloom.copy_from_slice(&bytes[21..53]);
+        let timestamp = u64::from_le_bytes(bytes[13..21].try_into().unwrap());
+        Self {
+            offset: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            len: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+            timestamp: (bytes[12] & FLAG_HAS_TIMESTAMP != 0).then_some(timestamp),
+            fid_bloom: FidBloom(bloom),
+        }
+    }
+}
+
+/// Writes records to an indexed file
+///
+/// Records are streamed to the underlying writer as they are appended; the index
+/// is kept in memory and written by [`IndexedFileWriter::finish`].
+#[derive(Debug)]
+pub struct IndexedFileWriter<W: Write> {
+    inner: W,
+    encoder: BinaryEncoder,
+    entries: Vec<IndexEntry>,
+    position: u64,
+}
+
+impl<W: Write> IndexedFileWriter<W> {
+    /// Creates a writer and writes the file header
+    pub fn new(mut inner: W) -> Result<Self, IndexedFileError> {
+        inner.write_all(&INDEXED_MAGIC)?;
+        inner.write_all(&[INDEXED_VERSION])?;
+        Ok(Self {
+            inner,
+            encoder: BinaryEncoder::new(),
+            entries: Vec::new(),
+            position: HEADER_LEN,
+        })
+    }
+
+    /// Sets the encoder used for record frames
+    pub fn with_encoder(mut self, encoder: BinaryEncoder) -> Self {
+        self.encoder = encoder;
+        self
+    }
+
+    /// Appends a record and returns its index
+    pub fn append(
+        &mut self,
+        record: &LnmpRecord,
+        timestamp: Option<u64>,
+    ) -> Result<usize, IndexedFileError> {
+        let frame = self.encoder.encode(record)?;
+        let len = u32::try_from(frame.len()).map_err(|_| IndexedFileError::CorruptIndex {
+            reason: format!("record frame of {} bytes exceeds u32", frame.len()),
+        })?;
+        self.inner.write_all(&frame)?;
+        self.entries.push(IndexEntry {
+            offset: self.position,
+            len,
+            timestamp,
+            fid_bloom: FidBloom::from_record(record),
+        });
+        self.position += frame.len() as u64;
+        Ok(self.entries.len() - 1)
+    }
+
+    /// Returns the number of records written so far
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no records have been written
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the index and footer, then returns the underlying writer
+    pub fn finish(mut self) -> Result<W, IndexedFileError> {
+        let mut index = Vec::with_capacity(self.entries.len() * ENTRY_LEN);
+        for entry in &self.entries {
+            entry.encode_into(&mut index);
+        }
+        let count =
+            u32::try_from(self.entries.len()).map_err(|_| IndexedFileError::CorruptIndex {
+                reason: format!("{} records exceed u32", self.entries.len()),
+            })?;
+        self.inner.write_all(&index)?;
+        self.inner.write_all(&self.position.to_le_bytes())?;
+        self.inner.write_all(&count.to_le_bytes())?;
+        self.inner
+            .write_all(&CRC32C.checksum(&index).to_le_bytes())?;
+        self.inner.write_all(&INDEXED_MAGIC)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads records from an indexed file by position, timestamp or FID
+#[derive(Debug)]
+pub struct IndexedFileReader<R: Read + Seek> {
+    inner: R,
+    decoder: BinaryDecoder,
+    entries: Vec<IndexEntry>,
+}
+
+impl<R: Read + Seek> IndexedFileReader<R> {
+    /// Opens an indexed file, validating its header and loading the index
+    pub fn open(mut inner: R) -> Result<Self, IndexedFileError> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut header)?;
+        if header[..4] != INDEXED_MAGIC {
+            return Err(IndexedFileError::InvalidMagic);
+        }
+        if header[4] != INDEXED_VERSION {
+            return Err(IndexedFileError::UnsupportedVersion { found: header[4] });
+        }
+
+        let file_len = inner.seek(SeekFrom::End(0))?;
+        if file_len < HEADER_LEN + FOOTER_LEN as u64 {
+            return Err(IndexedFileError::CorruptIndex {
+                reason: "file too short for footer".to_string(),
+            });
+        }
+        let mut footer = [0u8; FOOTER_LEN];
+        inner.seek(SeekFrom::End(-(FOOTER_LEN as i64)))?;
+        inner.read_exact(&mut footer)?;
+        if footer[16..] != INDEXED_MAGIC {
+            return Err(IndexedFileError::InvalidMagic);
+        }
+        let index_offset = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        let count = u32::from_le_bytes(footer[8..12].try_into().unwrap()) as u64;
+        let expected_crc = u32::from_le_bytes(footer[12..16].try_into().unwrap());
+
+        let index_end = file_len - FOOTER_LEN as u64;
+        if index_offset < HEADER_LEN
+            || index_offset > index_end
+            || index_end - index_offset != count * ENTRY_LEN as u64
+        {
+            return Err(IndexedFileError::CorruptIndex {
+                reason: format!(
+                    "index of {} entries does not fit at offset {}",
+                    count, index_offset
+                ),
+            });
+        }
+
+        let mut index = vec![0u8; (index_end - index_offset) as usize];
+        inner.seek(SeekFrom::Start(index_offset))?;
+        inner.read_exact(&mut index)?;
+        let found_crc = CRC32C.checksum(&index);
+        if found_crc != expected_crc {
+            return Err(IndexedFileError::CorruptIndex {
+                reason: format!(
+                    "CRC32C mismatch: expected 0x{:08X}, found 0x{:08X}",
+                    expected_crc, found_crc
+                ),
+            });
+        }
+
+        let entries: Vec<IndexEntry> = index
+            .chunks_exact(ENTRY_LEN)
+            .map(IndexEntry::decode)
+            .collect();
+        if let Some(entry) = entries.iter().find(|e| {
+            e.offset < HEADER_LEN
+                || e.offset
+                    .checked_add(e.len as u64)
+                    .is_none_or(|end| end > index_offset)
+        }) {
+            return Err(IndexedFileError::CorruptIndex {
+                reason: format!(
+                    "record at offset {} with length {} lies outside the record area",
+                    entry.offset, entry.len
+                ),
+            });
+        }
+
+        Ok(Self {
+            inner,
+            decoder: BinaryDecoder::new(),
+            entries,
+        })
+    }
+
+    /// Sets the decoder used for record frames
+    pub fn with_decoder(mut self, decoder: BinaryDecoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    /// Returns the number of records in the file
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the file holds no records
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the loaded index
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Reads the raw binary frame of record `n`
+    pub fn read_raw(&mut self, n: usize) -> Result<Vec<u8>, IndexedFileError> {
+        let entry = *self
+            .entries
+            .get(n)
+            .ok_or(IndexedFileError::RecordOutOfRange {
+                index: n,
+                len: self.entries.len(),
+            })?;
+        let mut frame = vec![0u8; entry.len as usize];
+        self.inner.seek(SeekFrom::Start(entry.offset))?;
+        self.inner.read_exact(&mut frame)?;
+        Ok(frame)
+    }
+
+    /// Reads and decodes record `n`
+    pub fn read_record(&mut self, n: usize) -> Result<LnmpRecord, IndexedFileError> {
+        let frame = self.read_raw(n)?;
+        Ok(self.decoder.decode(&frame)?)
+    }
+
+    /// Returns the indices of records whose timestamp lies in `range`
+    ///
+    /// Records written without a timestamp never match.
+    pub fn records_in_time_range(&self, range: impl RangeBounds<u64>) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.timestamp.is_some_and(|ts| range.contains(&ts)))
+            .map(|(n, _)| n)
+            .collect()
+    }
+
+    /// Returns the indices of records that may contain top-level field `fid`
+    ///
+    /// The check uses the bloom filter only, so the result can include records
+    /// without the field but never misses one that has it.
+    pub fn candidates_with_fid(&self, fid: FieldId) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.fid_bloom.may_contain(fid))
+            .map(|(n, _)| n)
+            .collect()
+    }
+}
+
+/// Error type for indexed file operations
+#[derive(Debug)]
+pub enum IndexedFileError {
+    /// I/O error from the underlying reader or writer
+    Io(std::io::Error),
+
+    /// Header or footer magic bytes do not match `LNMI`
+    InvalidMagic,
+
+    /// File format version is not supported
+    UnsupportedVersion {
+        /// The version byte found in the header
+        found: u8,
+    },
+
+    /// Index or footer is inconsistent or fails its CRC32C check
+    CorruptIndex {
+        /// Description of the inconsistency
+        reason: String,
+    },
+
+    /// Requested record index is past the end of the file
+    RecordOutOfRange {
+        /// Requested record index
+        index: usize,
+        /// Number of records in the file
+        len: usize,
+    },
+
+    /// Binary encoding/decoding error
+    BinaryError(BinaryError),
+}
+
+impl std::fmt::Display for IndexedFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexedFileError::Io(err) => write!(f, "I/O error: {}", err),
+            IndexedFileError::InvalidMagic => {
+                write!(f, "Invalid magic: not an indexed LNMP file")
+            }
+            IndexedFileError::UnsupportedVersion { found } => {
+                write!(f, "Unsupported indexed file version: 0x{:02X}", found)
+            }
+            IndexedFileError::CorruptIndex { reason } => {
+                write!(f, "Corrupt index: {}", reason)
+            }
+            IndexedFileError::RecordOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "Record index {} out of range for file with {} records",
+                    index, len
+                )
+            }
+            IndexedFileError::BinaryError(err) => {
+                write!(f, "Binary error: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IndexedFileError {}
+
+impl From<std::io::Error> for IndexedFileError {
+    fn from(err: std::io::Error) -> Self {
+        IndexedFileError::Io(err)
+    }
+}
+
+impl From<BinaryError> for IndexedFileError {
+    fn from(err: BinaryError) -> Self {
+        IndexedFileError::BinaryError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+    use std::io::Cursor;
+
+    fn record(n: i64) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(n),
+        });
+        if n % 2 == 0 {
+            record.add_field(LnmpField {
+                fid: 500,
+                value: LnmpValue::String(format!("even-{}", n)),
+            });
+        }
+        record
+    }
+
+    fn write_file(count: i64) -> Vec<u8> {
+        let mut writer = IndexedFileWriter::new(Vec::new()).unwrap();
+        for n in 0..count {
+            writer
+                .append(&record(n), Some(1_000 + n as u64 * 10))
+                .unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_seek_to_record() {
+        let mut reader = IndexedFileReader::open(Cursor::new(write_file(20))).unwrap();
+        assert_eq!(reader.len(), 20);
+        assert_eq!(reader.read_record(13).unwrap(), record(13));
+        assert_eq!(reader.read_record(0).unwrap(), record(0));
+        assert!(matches!(
+            reader.read_record(20),
+            Err(IndexedFileError::RecordOutOfRange { index: 20, len: 20 })
+        ));
+    }
+
+    #[test]
+    fn test_filter_by_timestamp() {
+        let mut writer = IndexedFileWriter::new(Vec::new()).unwrap();
+        writer.append(&record(0), Some(100)).unwrap();
+        writer.append(&record(1), None).unwrap();
+        writer.append(&record(2), Some(300)).unwrap();
+        let reader = IndexedFileReader::open(Cursor::new(writer.finish().unwrap())).unwrap();
+
+        assert_eq!(reader.records_in_time_range(100..=300), vec![0, 2]);
+        assert_eq!(reader.records_in_time_range(200..), vec![2]);
+        assert!(reader.records_in_time_range(..100).is_empty());
+        assert_eq!(reader.index()[1].timestamp, None);
+    }
+
+    #[test]
+    fn test_fid_bloom_has_no_false_negatives() {
+        let reader = IndexedFileReader::open(Cursor::new(write_file(10))).unwrap();
+        let candidates = reader.candidates_with_fid(500);
+        for n in (0..10).step_by(2) {
+            assert!(candidates.contains(&n));
+        }
+        assert_eq!(reader.candidates_with_fid(1).len(), 10);
+
+        let bloom = FidBloom::from_record(&record(1));
+        assert!(bloom.may_contain(1));
+        assert!(!bloom.may_contain(500));
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let bytes = IndexedFileWriter::new(Vec::new())
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(bytes.len(), HEADER_LEN as usize + FOOTER_LEN);
+        let reader = IndexedFileReader::open(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_corrupt_files() {
+        let bytes = write_file(3);
+
+        let mut corrupt = bytes.clone();
+        let index_byte = corrupt.len() - FOOTER_LEN - 1;
+        corrupt[index_byte] ^= 0xFF;
+        assert!(matches!(
+            IndexedFileReader::open(Cursor::new(corrupt)),
+            Err(IndexedFileError::CorruptIndex { .. })
+        ));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            IndexedFileReader::open(Cursor::new(bad_magic)),
+            Err(IndexedFileError::InvalidMagic)
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 0x7F;
+        assert!(matches!(
+            IndexedFileReader::open(Cursor::new(bad_version)),
+            Err(IndexedFileError::UnsupportedVersion { found: 0x7F })
+        ));
+
+        let truncated = bytes[..bytes.len() - 1].to_vec();
+        assert!(IndexedFileReader::open(Cursor::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_overflowing_index_entry() {
+        let mut hostile = write_file(1);
+        let index_start = hostile.len() - FOOTER_LEN - ENTRY_LEN;
+        // Record offset near u64::MAX so that offset + len wraps around
+        hostile[index_start..index_start + 8].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+        let crc = CRC32C.checksum(&hostile[index_start..index_start + ENTRY_LEN]);
+        let crc_start = hostile.len() - FOOTER_LEN + 12;
+        hostile[crc_start..crc_start + 4].copy_from_slice(&crc.to_le_bytes());
+
+        assert!(matches!(
+            IndexedFileReader::open(Cursor::new(hostile)),
+            Err(IndexedFileError::CorruptIndex { .. })
+        ));
+    }
+}
@@ -0,0 +1,609 @@
+//! Append-only record log with segment rotation
+//!
+//! A log is a directory of segment files, each named after the sequence number
+//! of its first record (`00000000000000000042.lnmplog`). Every segment starts
+//! with a header and holds length-prefixed, checksummed record frames:
+//!
+//! ```text
+//! SEGMENT: MAGIC "LNML" | VERSION (1 byte) | FRAME*
+//! FRAME:   LEN (u32 LE) | CRC32C (u32 LE) | PAYLOAD (binary record frame, LEN bytes)
+//! ```
+//!
+//! A crash can leave a partially written frame at the end of a segment (a torn
+//! tail). [`LogReader`] stops at a torn tail instead of failing, and
+//! [`LogWriter::open`] truncates it before appending. A bad frame that is
+//! followed by more data is reported as corruption.
+
+use super::decoder::BinaryDecoder;
+use super::encoder::BinaryEncoder;
+use super::error::BinaryError;
+use super::streaming::CRC32C;
+use lnmp_core::LnmpRecord;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Magic bytes at the start of every segment
+pub const LOG_MAGIC: [u8; 4] = *b"LNML";
+
+/// Current segment format version
+pub const LOG_VERSION: u8 = 0x01;
+
+/// File extension of segment files
+pub const SEGMENT_EXTENSION: &str = "lnmplog";
+
+const SEGMENT_HEADER_LEN: u64 = 5;
+const FRAME_HEADER_LEN: usize = 8;
+
+/// When the writer flushes appended frames to stable storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// fsync after every append (default)
+    #[default]
+    Always,
+    /// fsync after every N appends
+    EveryN(u32),
+    /// fsync only on rotation, [`LogWriter::sync`] and drop
+    Never,
+}
+
+/// Configuration for [`LogWriter`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogConfig {
+    /// Segment size in bytes after which a new segment is started (default: 64 MiB)
+    pub max_segment_bytes: u64,
+    /// When appended frames are fsynced
+    pub fsync: FsyncPolicy,
+}
+
+impl LogConfig {
+    /// Creates a new LogConfig with default values
+    pub fn new() -> Self {
+        Self {
+            max_segment_bytes: 64 * 1024 * 1024,
+            fsync: FsyncPolicy::Always,
+        }
+    }
+
+    /// Sets the segment size that triggers rotation
+    pub fn with_max_segment_bytes(mut self, bytes: u64) -> Self {
+        self.max_segment_bytes = bytes;
+        self
+    }
+
+    /// Sets the fsync policy
+    pub fn with_fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appends records to a log directory
+#[derive(Debug)]
+pub struct LogWriter {
+    dir: PathBuf,
+    config: LogConfig,
+    encoder: BinaryEncoder,
+    file: File,
+    segment_len: u64,
+    segment_records: u64,
+    next_seq: u64,
+    unsynced: u32,
+    truncated_bytes: u64,
+}
+
+impl LogWriter {
+    /// Opens the log in `dir`, creating it if needed
+    ///
+    /// Appending continues in the newest segment after any torn tail has been
+    /// truncated; see [`LogWriter::truncated_bytes`].
+    pub fn open(dir: impl AsRef<Path>, config: LogConfig) -> Result<Self, LogError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let segments = list_segments(&dir)?;
+
+        let Some((first_seq, path)) = segments.last() else {
+            let file = create_segment(&dir, 0)?;
+            return Ok(Self::with_segment(dir, config, file, 0, 0, 0));
+        };
+
+        let bytes = fs::read(path)?;
+        let scan = scan_segment(path, &bytes)?;
+        let truncated = bytes.len() as u64 - scan.valid_len;
+        if scan.valid_len == 0 {
+            // Torn segment header: start the segment over
+            let file = create_segment(&dir, *first_seq)?;
+            let mut writer = Self::with_segment(dir, config, file, 0, 0, *first_seq);
+            writer.truncated_bytes = truncated;
+            return Ok(writer);
+        }
+        let file = OpenOptions::new().append(true).open(path)?;
+        if truncated > 0 {
+            file.set_len(scan.valid_len)?;
+            file.sync_all()?;
+        }
+        let records = scan.frames.len() as u64;
+        let mut writer = Self::with_segment(
+            dir,
+            config,
+            file,
+            scan.valid_len,
+            records,
+            first_seq + records,
+        );
+        writer.truncated_bytes = truncated;
+        Ok(writer)
+    }
+
+    fn with_segment(
+        dir: PathBuf,
+        config: LogConfig,
+        file: File,
+        segment_len: u64,
+        segment_records: u64,
+        next_seq: u64,
+    ) -> Self {
+        Self {
+            dir,
+            config,
+            encoder: BinaryEncoder::new(),
+            file,
+            segment_len: segment_len.max(SEGMENT_HEADER_LEN),
+            segment_records,
+            next_seq,
+            unsynced: 0,
+            truncated_bytes: 0,
+        }
+    }
+
+    /// Sets the encoder used for record frames
+    pub fn with_encoder(mut self, encoder: BinaryEncoder) -> Self {
+        self.encoder = encoder;
+        self
+    }
+
+    /// Appends a record and returns its sequence number
+    pub fn append(&mut self, record: &LnmpRecord) -> Result<u64, LogError> {
+        let payload = self.encoder.encode(record)?;
+        let len = u32::try_from(payload.len()).map_err(|_| LogError::FrameTooLarge {
+            size: payload.len(),
+        })?;
+
+        let frame_len = (FRAME_HEADER_LEN + payload.len()) as u64;
+        if self.segment_records > 0 && self.segment_len + frame_len > self.config.max_segment_bytes
+        {
+            self.rotate()?;
+        }
+
+        let mut frame = Vec::with_capacity(frame_len as usize);
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(&CRC32C.checksum(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        self.file.write_all(&frame)?;
+
+        self.segment_len += frame_len;
+        self.segment_records += 1;
+        self.unsynced += 1;
+        match self.config.fsync {
+            FsyncPolicy::Always => self.sync()?,
+            FsyncPolicy::EveryN(n) if self.unsynced >= n => self.sync()?,
+            _ => {}
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    /// Flushes all appended frames to stable storage
+    pub fn sync(&mut self) -> Result<(), LogError> {
+        self.file.sync_data()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// Returns the sequence number the next appended record will get
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Returns the number of torn-tail bytes removed when the log was opened
+    pub fn truncated_bytes(&self) -> u64 {
+        self.truncated_bytes
+    }
+
+    /// Returns the log directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn rotate(&mut self) -> Result<(), LogError> {
+        self.sync()?;
+        self.file = create_segment(&self.dir, self.next_seq)?;
+        self.segment_len = SEGMENT_HEADER_LEN;
+        self.segment_records = 0;
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        let _ = self.file.sync_data();
+    }
+}
+
+/// Reads records from a log directory in sequence order
+///
+/// Iterating yields `(sequence, record)` pairs and ends early at a torn tail.
+#[derive(Debug)]
+pub struct LogReader {
+    decoder: BinaryDecoder,
+    segments: Vec<(u64, PathBuf)>,
+    next_segment: usize,
+    current: Option<LoadedSegment>,
+    skipped_bytes: u64,
+}
+
+#[derive(Debug)]
+struct LoadedSegment {
+    bytes: Vec<u8>,
+    frames: Vec<(usize, usize)>,
+    next_frame: usize,
+    first_seq: u64,
+}
+
+impl LogReader {
+    /// Opens the log in `dir`
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, LogError> {
+        Ok(Self {
+            decoder: BinaryDecoder::new(),
+            segments: list_segments(dir.as_ref())?,
+            next_segment: 0,
+            current: None,
+            skipped_bytes: 0,
+        })
+    }
+
+    /// Sets the decoder used for record frames
+    pub fn with_decoder(mut self, decoder: BinaryDecoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    /// Returns the segment files of the log in sequence order
+    pub fn segments(&self) -> impl Iterator<Item = &Path> {
+        self.segments.iter().map(|(_, path)| path.as_path())
+    }
+
+    /// Returns the number of torn-tail bytes skipped so far
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    fn next_record(&mut self) -> Result<Option<(u64, LnmpRecord)>, LogError> {
+        loop {
+            if let Some(segment) = &mut self.current {
+                if let Some(&(start, end)) = segment.frames.get(segment.next_frame) {
+                    let seq = segment.first_seq + segment.next_frame as u64;
+                    segment.next_frame += 1;
+                    let record = self.decoder.decode(&segment.bytes[start..end])?;
+                    return Ok(Some((seq, record)));
+                }
+                self.current = None;
+            }
+
+            let Some((first_seq, path)) = self.segments.get(self.next_segment) else {
+                return Ok(None);
+            };
+            self.next_segment += 1;
+            let bytes = fs::read(path)?;
+            let scan = scan_segment(path, &bytes)?;
+            self.skipped_bytes += bytes.len() as u64 - scan.valid_len;
+            self.current = Some(LoadedSegment {
+                bytes,
+                frames: scan.frames,
+                next_frame: 0,
+                first_seq: *first_seq,
+            });
+        }
+    }
+}
+
+impl Iterator for LogReader {
+    type Item = Result<(u64, LnmpRecord), LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Result of scanning one segment
+struct SegmentScan {
+    /// Payload ranges of valid frames
+    frames: Vec<(usize, usize)>,
+    /// Length of the segment up to the end of the last valid frame
+    valid_len: u64,
+}
+
+/// Validates a segment, stopping at a torn tail
+fn scan_segment(path: &Path, bytes: &[u8]) -> Result<SegmentScan, LogError> {
+    if bytes.len() < SEGMENT_HEADER_LEN as usize {
+        // Crash before the header was fully written
+        return Ok(SegmentScan {
+            frames: Vec::new(),
+            valid_len: 0,
+        });
+    }
+    if bytes[..4] != LOG_MAGIC {
+        return Err(LogError::InvalidSegment {
+            path: path.to_path_buf(),
+            reason: "invalid magic".to_string(),
+        });
+    }
+    if bytes[4] != LOG_VERSION {
+        return Err(LogError::InvalidSegment {
+            path: path.to_path_buf(),
+            reason: format!("unsupported version 0x{:02X}", bytes[4]),
+        });
+    }
+
+    let mut frames = Vec::new();
+    let mut offset = SEGMENT_HEADER_LEN as usize;
+    while offset < bytes.len() {
+        let Some(header) = bytes.get(offset..offset + FRAME_HEADER_LEN) else {
+            break;
+        };
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let expected_crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let start = offset + FRAME_HEADER_LEN;
+        let Some(payload) = bytes.get(start..start + len) else {
+            break;
+        };
+        if CRC32C.checksum(payload) != expected_crc {
+            if start + len == bytes.len() {
+                break;
+            }
+            return Err(LogError::CorruptFrame {
+                path: path.to_path_buf(),
+                offset: offset as u64,
+            });
+        }
+        frames.push((start, start + len));
+        offset = start + len;
+    }
+
+    Ok(SegmentScan {
+        frames,
+        valid_len: offset.min(bytes.len()) as u64,
+    })
+}
+
+fn segment_path(dir: &Path, first_seq: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", first_seq, SEGMENT_EXTENSION))
+}
+
+fn create_segment(dir: &Path, first_seq: u64) -> Result<File, LogError> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(segment_path(dir, first_seq))?;
+    file.write_all(&LOG_MAGIC)?;
+    file.write_all(&[LOG_VERSION])?;
+    file.sync_all()?;
+    if let Ok(dir) = File::open(dir) {
+        // Persist the new directory entry; not supported on every platform
+        let _ = dir.sync_all();
+    }
+    Ok(file)
+}
+
+fn list_segments(dir: &Path) -> Result<Vec<(u64, PathBuf)>, LogError> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(seq) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok())
+        {
+            segments.push((seq, path));
+        }
+    }
+    segments.sort_unstable_by_key(|(seq, _)| *seq);
+    Ok(segments)
+}
+
+/// Error type for log operations
+#[derive(Debug)]
+pub enum LogError {
+    /// I/O error from the file system
+    Io(std::io::Error),
+
+    /// Segment header is missing or invalid
+    InvalidSegment {
+        /// Segment file
+        path: PathBuf,
+        /// Description of the problem
+        reason: String,
+    },
+
+    /// Frame failed its CRC32C check and is not at the end of its segment
+    CorruptFrame {
+        /// Segment file
+        path: PathBuf,
+        /// Byte offset of the frame in the segment
+        offset: u64,
+    },
+
+    /// Encoded record does not fit in a frame
+    FrameTooLarge {
+        /// Encoded record size in bytes
+        size: usize,
+    },
+
+    /// Binary encoding/decoding error
+    BinaryError(BinaryError),
+}
+
+impl std::fmt::Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::Io(err) => write!(f, "I/O error: {}", err),
+            LogError::InvalidSegment { path, reason } => {
+                write!(f, "Invalid log segment {}: {}", path.display(), reason)
+            }
+            LogError::CorruptFrame { path, offset } => {
+                write!(
+                    f,
+                    "Corrupt log frame in {} at offset {}",
+                    path.display(),
+                    offset
+                )
+            }
+            LogError::FrameTooLarge { size } => {
+                write!(f, "Log frame too large: {} bytes exceeds u32", size)
+            }
+            LogError::BinaryError(err) => write!(f, "Binary error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<std::io::Error> for LogError {
+    fn from(err: std::io::Error) -> Self {
+        LogError::Io(err)
+    }
+}
+
+impl From<BinaryError> for LogError {
+    fn from(err: BinaryError) -> Self {
+        LogError::BinaryError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    fn record(n: i64) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(n),
+        });
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::String("payload".repeat(4)),
+        });
+        record
+    }
+
+    fn read_all(dir: &Path) -> Vec<(u64, LnmpRecord)> {
+        LogReader::open(dir)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_append_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = LogWriter::open(dir.path(), LogConfig::new()).unwrap();
+        for n in 0..5 {
+            assert_eq!(writer.append(&record(n)).unwrap(), n as u64);
+        }
+        drop(writer);
+
+        let records = read_all(dir.path());
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[3], (3, record(3)));
+    }
+
+    #[test]
+    fn test_rotation_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogConfig::new()
+            .with_max_segment_bytes(128)
+            .with_fsync(FsyncPolicy::EveryN(4));
+        let mut writer = LogWriter::open(dir.path(), config.clone()).unwrap();
+        for n in 0..6 {
+            writer.append(&record(n)).unwrap();
+        }
+        drop(writer);
+
+        let mut writer = LogWriter::open(dir.path(), config).unwrap();
+        assert_eq!(writer.next_seq(), 6);
+        assert_eq!(writer.append(&record(6)).unwrap(), 6);
+        drop(writer);
+
+        assert!(LogReader::open(dir.path()).unwrap().segments().count() > 1);
+        let seqs: Vec<u64> = read_all(dir.path()).into_iter().map(|(s, _)| s).collect();
+        assert_eq!(seqs, (0..7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_torn_tail_is_skipped_and_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = LogWriter::open(dir.path(), LogConfig::new()).unwrap();
+        writer.append(&record(0)).unwrap();
+        writer.append(&record(1)).unwrap();
+        drop(writer);
+
+        // Simulate a crash in the middle of writing the second frame
+        let path = segment_path(dir.path(), 0);
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let mut reader = LogReader::open(dir.path()).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), (0, record(0)));
+        assert!(reader.next().is_none());
+        assert!(reader.skipped_bytes() > 0);
+
+        let mut writer = LogWriter::open(dir.path(), LogConfig::new()).unwrap();
+        assert!(writer.truncated_bytes() > 0);
+        assert_eq!(writer.append(&record(7)).unwrap(), 1);
+        drop(writer);
+        assert_eq!(read_all(dir.path())[1], (1, record(7)));
+    }
+
+    #[test]
+    fn test_corrupt_frame_before_tail_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = LogWriter::open(dir.path(), LogConfig::new()).unwrap();
+        writer.append(&record(0)).unwrap();
+        writer.append(&record(1)).unwrap();
+        drop(writer);
+
+        let path = segment_path(dir.path(), 0);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[SEGMENT_HEADER_LEN as usize + FRAME_HEADER_LEN] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+
+        let mut reader = LogReader::open(dir.path()).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(LogError::CorruptFrame { offset: 5, .. }))
+        ));
+        assert!(matches!(
+            LogWriter::open(dir.path(), LogConfig::new()),
+            Err(LogError::CorruptFrame { .. })
+        ));
+    }
+}
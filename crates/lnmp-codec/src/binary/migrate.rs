@@ -0,0 +1,254 @@
+//! Binary frame version migration
+//!
+//! Detects which binary layout a frame was written with and rewrites frames
+//! from earlier encoders into the current canonical binary (version `0x05`,
+//! entries sorted by FID, no compression or string table).
+//!
+//! Migration never changes field values: the semantic checksum of every field
+//! decoded from the original frame, read as its original version, is compared
+//! with the same field decoded from the rewritten frame, and the upgrade fails
+//! with [`BinaryError::ChecksumMismatch`] if any of them differs.
+//!
+//! ```
+//! use lnmp_codec::binary::migrate::{detect_version, upgrade_frame, FrameVersion};
+//!
+//! // v0.4 frame, F1=42
+//! let old = vec![0x04, 0x00, 0x01, 0x01, 0x00, 0x01, 0x2A];
+//! assert_eq!(detect_version(&old).unwrap(), FrameVersion::V0_4);
+//!
+//! let upgraded = upgrade_frame(&old).unwrap();
+//! assert_eq!(detect_version(&upgraded).unwrap(), FrameVersion::CURRENT);
+//! ```
+
+use super::decoder::{BinaryDecoder, DecoderConfig};
+use super::encoder::{BinaryEncoder, EncoderConfig};
+use super::error::BinaryError;
+use super::frame::{FLAG_ENCRYPTED, FLAG_SIGNED};
+use lnmp_core::checksum::SemanticChecksum;
+
+/// Binary frame layouts recognised by [`detect_version`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FrameVersion {
+    /// `0x04`: flat records only
+    V0_4,
+    /// `0x05`: records that may contain nested records and arrays
+    V0_5,
+}
+
+impl FrameVersion {
+    /// Latest layout; every recognised layout can be upgraded to it
+    pub const CURRENT: FrameVersion = FrameVersion::V0_5;
+
+    /// All recognised layouts, oldest first
+    pub const ALL: &'static [FrameVersion] = &[FrameVersion::V0_4, FrameVersion::V0_5];
+
+    /// Version byte written at the start of the frame
+    pub fn byte(&self) -> u8 {
+        match self {
+            FrameVersion::V0_4 => 0x04,
+            FrameVersion::V0_5 => 0x05,
+        }
+    }
+
+    /// Looks up the layout for a version byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|v| v.byte() == byte)
+    }
+
+    /// Whether frames of this layout are already at the latest version
+    pub fn is_current(&self) -> bool {
+        *self == Self::CURRENT
+    }
+}
+
+/// Reads the version byte of a binary frame.
+///
+/// # Errors
+///
+/// - `UnexpectedEof` if `bytes` is empty
+/// - `UnsupportedVersion` if the version byte is not a known layout
+pub fn detect_version(bytes: &[u8]) -> Result<FrameVersion, BinaryError> {
+    let byte = *bytes.first().ok_or(BinaryError::UnexpectedEof {
+        expected: 1,
+        found: 0,
+    })?;
+    FrameVersion::from_byte(byte).ok_or_else(|| BinaryError::UnsupportedVersion {
+        found: byte,
+        supported: FrameVersion::ALL.iter().map(FrameVersion::byte).collect(),
+    })
+}
+
+/// Rewrites a frame of any recognised layout into the current canonical binary.
+///
+/// The frame is decoded as the version it declares and re-encoded with version
+/// [`FrameVersion::CURRENT`]. Frames that are already current are re-encoded as
+/// well, so the output is always canonical (sorted, uncompressed, no string table).
+///
+/// # Errors
+///
+/// - Any error from [`detect_version`]
+/// - `UnsupportedFeature` for encrypted or signed frames, which must be opened
+///   before they can be migrated
+/// - Decoding errors for malformed frames, including `TrailingData`
+/// - `ChecksumMismatch` if a field's semantic checksum would change
+pub fn upgrade_frame(bytes: &[u8]) -> Result<Vec<u8>, BinaryError> {
+    detect_version(bytes)?;
+    let flags = bytes.get(1).copied().unwrap_or(0);
+    if flags & (FLAG_ENCRYPTED | FLAG_SIGNED) != 0 {
+        return Err(BinaryError::UnsupportedFeature {
+            feature: "migration of encrypted or signed frames".to_string(),
+        });
+    }
+
+    let original =
+        BinaryDecoder::with_config(DecoderConfig::new().with_strict_parsing(true)).decode(bytes)?;
+    let mut upgraded = canonical_encoder().encode(&original)?;
+    upgraded[0] = FrameVersion::CURRENT.byte();
+
+    let before = original.sorted_fields();
+    let after = BinaryDecoder::new().decode(&upgraded)?;
+    for (index, old) in before.iter().enumerate() {
+        let expected = SemanticChecksum::compute(old.fid, None, &old.value);
+        let found = after
+            .fields()
+            .get(index)
+            .filter(|new| new.fid == old.fid)
+            .map(|new| SemanticChecksum::compute(new.fid, None, &new.value));
+        if found != Some(expected) {
+            return Err(BinaryError::ChecksumMismatch {
+                fid: old.fid,
+                expected,
+                found,
+            });
+        }
+    }
+
+    #[cfg(feature = "log")]
+    log::debug!(
+        "upgraded binary frame from 0x{:02X} to 0x{:02X} ({} fields)",
+        bytes[0],
+        FrameVersion::CURRENT.byte(),
+        before.len()
+    );
+    Ok(upgraded)
+}
+
+fn canonical_encoder() -> BinaryEncoder {
+    BinaryEncoder::with_config(EncoderConfig::new().with_nested_binary(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::BinaryFrame;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+
+    fn sample_record() -> LnmpRecord {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("nested".to_string()),
+        });
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Bool(true),
+        });
+        record.add_field(LnmpField {
+            fid: 30,
+            value: LnmpValue::NestedRecord(Box::new(inner)),
+        });
+        record
+    }
+
+    #[test]
+    fn test_detect_version() {
+        assert_eq!(
+            detect_version(&[0x04, 0x00, 0x00]).unwrap(),
+            FrameVersion::V0_4
+        );
+        assert_eq!(
+            detect_version(&[0x05, 0x00, 0x00]).unwrap(),
+            FrameVersion::V0_5
+        );
+        assert!(matches!(
+            detect_version(&[]),
+            Err(BinaryError::UnexpectedEof { .. })
+        ));
+        assert!(matches!(
+            detect_version(&[0x03]),
+            Err(BinaryError::UnsupportedVersion { found: 0x03, ref supported })
+                if supported == &vec![0x04, 0x05]
+        ));
+    }
+
+    #[test]
+    fn test_upgrade_v0_5_nested_frame() {
+        let current = canonical_encoder().encode(&sample_record()).unwrap();
+        assert_eq!(detect_version(&current).unwrap(), FrameVersion::V0_5);
+
+        let upgraded = upgrade_frame(&current).unwrap();
+        assert_eq!(upgraded, current);
+        assert!(BinaryDecoder::new()
+            .decode(&upgraded)
+            .unwrap()
+            .canonical_eq(&sample_record()));
+    }
+
+    #[test]
+    fn test_upgrade_never_downgrades() {
+        // F7=true as a v0.4 frame
+        let old = [0x04, 0x00, 0x01, 0x07, 0x00, 0x03, 0x01];
+        let upgraded = upgrade_frame(&old).unwrap();
+        assert_eq!(upgraded, [0x05, 0x00, 0x01, 0x07, 0x00, 0x03, 0x01]);
+        assert_eq!(upgrade_frame(&upgraded).unwrap(), upgraded);
+    }
+
+    #[test]
+    fn test_upgrade_reads_original_version() {
+        // Nested entries are not valid in a v0.4 frame, so relabelling a v0.5
+        // frame as 0x04 must not upgrade
+        let mut old = canonical_encoder().encode(&sample_record()).unwrap();
+        old[0] = 0x04;
+        assert!(matches!(
+            upgrade_frame(&old),
+            Err(BinaryError::InvalidValue { field_id: 30, .. })
+        ));
+    }
+
+    #[test]
+    fn test_upgrade_sorts_and_unpacks_current_frames() {
+        // F12=1 written before F7=2
+        let unsorted = [
+            0x04, 0x00, 0x02, 0x0C, 0x00, 0x01, 0x01, 0x07, 0x00, 0x01, 0x02,
+        ];
+        let upgraded = upgrade_frame(&unsorted).unwrap();
+        assert_eq!(
+            upgraded,
+            [0x05, 0x00, 0x02, 0x07, 0x00, 0x01, 0x02, 0x0C, 0x00, 0x01, 0x01]
+        );
+
+        let frame = BinaryFrame::from_record(&sample_record()).unwrap();
+        let with_table = frame.encode_with_string_table();
+        assert_eq!(upgrade_frame(&with_table).unwrap(), frame.encode());
+    }
+
+    #[test]
+    fn test_upgrade_rejects_trailing_and_sealed_frames() {
+        let mut trailing = canonical_encoder().encode(&sample_record()).unwrap();
+        trailing.push(0x00);
+        assert!(matches!(
+            upgrade_frame(&trailing),
+            Err(BinaryError::TrailingData { .. })
+        ));
+
+        assert!(matches!(
+            upgrade_frame(&[0x04, FLAG_ENCRYPTED, 0x00]),
+            Err(BinaryError::UnsupportedFeature { .. })
+        ));
+    }
+}
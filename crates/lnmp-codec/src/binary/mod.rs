@@ -266,28 +266,42 @@
 //! - [`EncoderConfig`]: Configuration for binary encoding
 //! - [`DecoderConfig`]: Configuration for binary decoding
 
+#[cfg(feature = "tokio")]
+pub mod async_streaming;
 pub mod decoder;
 pub mod delta;
+pub mod diff;
 pub mod encoder;
 pub mod entry;
 pub mod error;
 pub mod frame;
+pub mod indexed;
+pub mod log;
+pub mod migrate;
 pub mod negotiation;
 pub mod nested_decoder;
 pub mod nested_encoder;
 pub mod streaming;
+pub mod string_table;
 pub mod types;
 pub mod varint;
+#[cfg(feature = "bytes")]
+pub mod vectored;
+pub mod view;
 
 pub use crate::config::TextInputMode;
 pub use decoder::{BinaryDecoder, DecoderConfig};
 pub use delta::{
     DeltaConfig, DeltaDecoder, DeltaEncoder, DeltaError, DeltaOp, DeltaOperation, DELTA_TAG,
 };
-pub use encoder::{BinaryEncoder, EncoderConfig};
+pub use encoder::{
+    BinaryEncoder, BoundedEncoding, DropPolicy, EncoderConfig, DEFAULT_FIELD_IMPORTANCE,
+};
 pub use entry::BinaryEntry;
-pub use error::BinaryError;
+pub use error::{BinaryError, DecodeWarning};
 pub use frame::BinaryFrame;
+pub use indexed::{FidBloom, IndexEntry, IndexedFileError, IndexedFileReader, IndexedFileWriter};
+pub use log::{FsyncPolicy, LogConfig, LogError, LogReader, LogWriter};
 pub use negotiation::{
     Capabilities, ErrorCode, FeatureFlags, FidDefStatus, FidDefinition, NegotiationError,
     NegotiationMessage, NegotiationResponse, NegotiationSession, NegotiationState,
@@ -296,7 +310,10 @@ pub use negotiation::{
 pub use nested_decoder::{BinaryNestedDecoder, NestedDecoderConfig};
 pub use nested_encoder::{BinaryNestedEncoder, NestedEncoderConfig};
 pub use streaming::{
-    BackpressureController, FrameFlags, FrameType, StreamingConfig, StreamingDecoder,
-    StreamingEncoder, StreamingError, StreamingEvent, StreamingFrame, StreamingState,
+    BackpressureController, BatchConfig, FrameFlags, FrameType, RecordBatcher, StreamingConfig,
+    StreamingDecoder, StreamingEncoder, StreamingError, StreamingEvent, StreamingFrame,
+    StreamingState,
 };
+pub use string_table::StringTable;
 pub use types::{BinaryValue, TypeTag};
+pub use view::{BinaryRecordView, EntryView};
@@ -2,8 +2,12 @@
 //!
 //! This module provides capability exchange and schema version negotiation
 //! between communicating parties to ensure compatibility and detect conflicts.
+//! Messages have a compact binary encoding ([`NegotiationMessage::encode`]) so the
+//! exchange can run over any byte transport.
 
+use super::error::BinaryError;
 use super::types::TypeTag;
+use super::varint;
 use std::collections::HashMap;
 
 /// Feature flags for optional protocol features
@@ -17,6 +21,8 @@ pub struct FeatureFlags {
     pub supports_delta: bool,
     /// Support for LLM optimization layer
     pub supports_llb: bool,
+    /// Support for per-frame string tables in binary frames (v0.6)
+    pub supports_string_table: bool,
     /// Require checksums for data integrity
     pub requires_checksums: bool,
     /// Require canonical field ordering
@@ -31,6 +37,7 @@ impl FeatureFlags {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: false,
         }
@@ -43,6 +50,7 @@ impl FeatureFlags {
             supports_streaming: true,
             supports_delta: true,
             supports_llb: true,
+            supports_string_table: true,
             requires_checksums: true,
             requires_canonical: true,
         }
@@ -55,6 +63,7 @@ impl FeatureFlags {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: true,
         }
@@ -67,10 +76,41 @@ impl FeatureFlags {
             supports_streaming: self.supports_streaming && other.supports_streaming,
             supports_delta: self.supports_delta && other.supports_delta,
             supports_llb: self.supports_llb && other.supports_llb,
+            supports_string_table: self.supports_string_table && other.supports_string_table,
             requires_checksums: self.requires_checksums || other.requires_checksums,
             requires_canonical: self.requires_canonical || other.requires_canonical,
         }
     }
+
+    /// Packs the flags into a bitfield, in field declaration order from bit 0
+    pub fn to_bits(&self) -> u8 {
+        [
+            self.supports_nested,
+            self.supports_streaming,
+            self.supports_delta,
+            self.supports_llb,
+            self.supports_string_table,
+            self.requires_checksums,
+            self.requires_canonical,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (bit, set)| bits | ((*set as u8) << bit))
+    }
+
+    /// Unpacks a bitfield written by [`to_bits`](Self::to_bits); unknown bits are ignored
+    pub fn from_bits(bits: u8) -> Self {
+        let bit = |n: u8| bits & (1 << n) != 0;
+        Self {
+            supports_nested: bit(0),
+            supports_streaming: bit(1),
+            supports_delta: bit(2),
+            supports_llb: bit(3),
+            supports_string_table: bit(4),
+            requires_checksums: bit(5),
+            requires_canonical: bit(6),
+        }
+    }
 }
 
 impl Default for FeatureFlags {
@@ -302,6 +342,316 @@ impl ErrorCode {
     }
 }
 
+// Wire encoding of negotiation messages:
+// TYPE (1 byte) followed by the message fields. Strings are VarInt length + UTF-8,
+// lists are VarInt count + items, FIDs are VarInt, session IDs are u64 BE.
+const MSG_CAPABILITIES: u8 = 0x01;
+const MSG_CAPABILITIES_ACK: u8 = 0x02;
+const MSG_SELECT_SCHEMA: u8 = 0x03;
+const MSG_READY: u8 = 0x04;
+const MSG_ERROR: u8 = 0x05;
+const MSG_REQUEST_REGISTRY: u8 = 0x06;
+const MSG_REGISTRY_RESPONSE: u8 = 0x07;
+const MSG_REGISTRY_DELTA: u8 = 0x08;
+
+impl NegotiationMessage {
+    /// Encodes the message for transmission over a byte transport
+    ///
+    /// FID mappings are written in ascending FID order so the encoding is deterministic.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            NegotiationMessage::Capabilities {
+                version,
+                features,
+                supported_types,
+            } => {
+                out.push(MSG_CAPABILITIES);
+                out.push(*version);
+                out.push(features.to_bits());
+                write_len(&mut out, supported_types.len());
+                out.extend(supported_types.iter().map(|tag| tag.to_u8()));
+            }
+            NegotiationMessage::CapabilitiesAck { version, features } => {
+                out.push(MSG_CAPABILITIES_ACK);
+                out.push(*version);
+                out.push(features.to_bits());
+            }
+            NegotiationMessage::SelectSchema {
+                schema_id,
+                fid_mappings,
+            } => {
+                out.push(MSG_SELECT_SCHEMA);
+                write_str(&mut out, schema_id);
+                let mut mappings: Vec<_> = fid_mappings.iter().collect();
+                mappings.sort_by_key(|(fid, _)| **fid);
+                write_len(&mut out, mappings.len());
+                for (fid, name) in mappings {
+                    write_len(&mut out, *fid as usize);
+                    write_str(&mut out, name);
+                }
+            }
+            NegotiationMessage::Ready { session_id } => {
+                out.push(MSG_READY);
+                out.extend_from_slice(&session_id.to_be_bytes());
+            }
+            NegotiationMessage::Error { code, message } => {
+                out.push(MSG_ERROR);
+                out.push(code.to_u8());
+                write_str(&mut out, message);
+            }
+            NegotiationMessage::RequestRegistry {
+                fid_range,
+                include_types,
+                local_version,
+            } => {
+                out.push(MSG_REQUEST_REGISTRY);
+                match fid_range {
+                    Some((start, end)) => {
+                        out.push(1);
+                        write_len(&mut out, *start as usize);
+                        write_len(&mut out, *end as usize);
+                    }
+                    None => out.push(0),
+                }
+                out.push(*include_types as u8);
+                write_opt_str(&mut out, local_version.as_deref());
+            }
+            NegotiationMessage::RegistryResponse {
+                version,
+                protocol_version,
+                fids,
+            } => {
+                out.push(MSG_REGISTRY_RESPONSE);
+                write_str(&mut out, version);
+                write_str(&mut out, protocol_version);
+                write_fid_definitions(&mut out, fids);
+            }
+            NegotiationMessage::RegistryDelta {
+                base_version,
+                target_version,
+                added,
+                deprecated,
+                tombstoned,
+            } => {
+                out.push(MSG_REGISTRY_DELTA);
+                write_str(&mut out, base_version);
+                write_str(&mut out, target_version);
+                write_fid_definitions(&mut out, added);
+                for fids in [deprecated, tombstoned] {
+                    write_len(&mut out, fids.len());
+                    for fid in fids {
+                        write_len(&mut out, *fid as usize);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Decodes a message written by [`encode`](Self::encode)
+    pub fn decode(bytes: &[u8]) -> Result<Self, BinaryError> {
+        let mut reader = WireReader { bytes, pos: 0 };
+        let message = match reader.u8()? {
+            MSG_CAPABILITIES => {
+                let version = reader.u8()?;
+                let features = FeatureFlags::from_bits(reader.u8()?);
+                let count = reader.len()?;
+                let supported_types = (0..count)
+                    .map(|_| TypeTag::from_u8(reader.u8()?))
+                    .collect::<Result<_, _>>()?;
+                NegotiationMessage::Capabilities {
+                    version,
+                    features,
+                    supported_types,
+                }
+            }
+            MSG_CAPABILITIES_ACK => NegotiationMessage::CapabilitiesAck {
+                version: reader.u8()?,
+                features: FeatureFlags::from_bits(reader.u8()?),
+            },
+            MSG_SELECT_SCHEMA => {
+                let schema_id = reader.string()?;
+                let count = reader.len()?;
+                let mut fid_mappings = HashMap::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    let fid = reader.fid()?;
+                    fid_mappings.insert(fid, reader.string()?);
+                }
+                NegotiationMessage::SelectSchema {
+                    schema_id,
+                    fid_mappings,
+                }
+            }
+            MSG_READY => NegotiationMessage::Ready {
+                session_id: u64::from_be_bytes(reader.array()?),
+            },
+            MSG_ERROR => {
+                let byte = reader.u8()?;
+                let code = ErrorCode::from_u8(byte)
+                    .ok_or_else(|| invalid(format!("unknown error code 0x{:02X}", byte)))?;
+                NegotiationMessage::Error {
+                    code,
+                    message: reader.string()?,
+                }
+            }
+            MSG_REQUEST_REGISTRY => {
+                let fid_range = match reader.u8()? {
+                    0 => None,
+                    _ => Some((reader.fid()?, reader.fid()?)),
+                };
+                NegotiationMessage::RequestRegistry {
+                    fid_range,
+                    include_types: reader.u8()? != 0,
+                    local_version: reader.opt_string()?,
+                }
+            }
+            MSG_REGISTRY_RESPONSE => NegotiationMessage::RegistryResponse {
+                version: reader.string()?,
+                protocol_version: reader.string()?,
+                fids: reader.fid_definitions()?,
+            },
+            MSG_REGISTRY_DELTA => NegotiationMessage::RegistryDelta {
+                base_version: reader.string()?,
+                target_version: reader.string()?,
+                added: reader.fid_definitions()?,
+                deprecated: reader.fids()?,
+                tombstoned: reader.fids()?,
+            },
+            other => {
+                return Err(invalid(format!(
+                    "unknown negotiation message type 0x{:02X}",
+                    other
+                )))
+            }
+        };
+        if reader.pos != bytes.len() {
+            return Err(BinaryError::TrailingData {
+                bytes_remaining: bytes.len() - reader.pos,
+            });
+        }
+        Ok(message)
+    }
+}
+
+fn invalid(reason: String) -> BinaryError {
+    BinaryError::InvalidValue {
+        field_id: 0,
+        type_tag: 0,
+        reason,
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&varint::encode(len as i64));
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_opt_str(out: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            out.push(1);
+            write_str(out, s);
+        }
+        None => out.push(0),
+    }
+}
+
+fn write_fid_definitions(out: &mut Vec<u8>, fids: &[FidDefinition]) {
+    write_len(out, fids.len());
+    for def in fids {
+        write_len(out, def.fid as usize);
+        write_str(out, &def.name);
+        out.push(def.type_tag.to_u8());
+        out.push(def.status.to_u8());
+        write_str(out, &def.since);
+    }
+}
+
+struct WireReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl WireReader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], BinaryError> {
+        let remaining = self.bytes.len() - self.pos;
+        if remaining < n {
+            return Err(BinaryError::UnexpectedEof {
+                expected: n,
+                found: remaining,
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, BinaryError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], BinaryError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn len(&mut self) -> Result<usize, BinaryError> {
+        let (value, consumed) = varint::decode(&self.bytes[self.pos..])?;
+        self.pos += consumed;
+        usize::try_from(value).map_err(|_| invalid(format!("negative length {}", value)))
+    }
+
+    fn fid(&mut self) -> Result<u16, BinaryError> {
+        let value = self.len()?;
+        u16::try_from(value).map_err(|_| invalid(format!("FID {} out of range", value)))
+    }
+
+    fn fids(&mut self) -> Result<Vec<u16>, BinaryError> {
+        let count = self.len()?;
+        (0..count).map(|_| self.fid()).collect()
+    }
+
+    fn string(&mut self) -> Result<String, BinaryError> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| BinaryError::InvalidUtf8 { field_id: 0 })
+    }
+
+    fn opt_string(&mut self) -> Result<Option<String>, BinaryError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => self.string().map(Some),
+        }
+    }
+
+    fn fid_definitions(&mut self) -> Result<Vec<FidDefinition>, BinaryError> {
+        let count = self.len()?;
+        (0..count)
+            .map(|_| {
+                let fid = self.fid()?;
+                let name = self.string()?;
+                let type_tag = TypeTag::from_u8(self.u8()?)?;
+                let byte = self.u8()?;
+                let status = FidDefStatus::from_u8(byte)
+                    .ok_or_else(|| invalid(format!("unknown FID status 0x{:02X}", byte)))?;
+                Ok(FidDefinition {
+                    fid,
+                    name,
+                    type_tag,
+                    status,
+                    since: self.string()?,
+                })
+            })
+            .collect()
+    }
+}
+
 /// Negotiation state for the state machine
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NegotiationState {
@@ -480,6 +830,19 @@ impl std::fmt::Display for NegotiationError {
 
 impl std::error::Error for NegotiationError {}
 
+impl NegotiationError {
+    /// Error code to report to the remote party in a [`NegotiationMessage::Error`]
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            NegotiationError::FidConflict { .. } => ErrorCode::FidConflict,
+            NegotiationError::TypeMismatch { .. } => ErrorCode::TypeMismatch,
+            NegotiationError::UnsupportedFeature { .. } => ErrorCode::UnsupportedFeature,
+            NegotiationError::ProtocolVersionMismatch { .. } => ErrorCode::ProtocolVersionMismatch,
+            NegotiationError::InvalidState { .. } => ErrorCode::InvalidState,
+        }
+    }
+}
+
 impl SchemaNegotiator {
     /// Creates a new schema negotiator with local capabilities
     pub fn new(local_capabilities: Capabilities) -> Self {
@@ -507,6 +870,12 @@ impl SchemaNegotiator {
         Self::new(Capabilities::v0_4())
     }
 
+    /// Replaces the local capabilities, e.g. to retry with an older protocol version
+    pub fn with_local_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.local_capabilities = capabilities;
+        self
+    }
+
     /// Sets FID mappings for the negotiator
     pub fn with_fid_mappings(mut self, mappings: HashMap<u16, String>) -> Self {
         self.fid_mappings = mappings;
@@ -925,6 +1294,7 @@ mod tests {
         assert!(!flags.supports_streaming);
         assert!(!flags.supports_delta);
         assert!(!flags.supports_llb);
+        assert!(!flags.supports_string_table);
         assert!(!flags.requires_checksums);
         assert!(!flags.requires_canonical);
     }
@@ -936,6 +1306,7 @@ mod tests {
         assert!(flags.supports_streaming);
         assert!(flags.supports_delta);
         assert!(flags.supports_llb);
+        assert!(flags.supports_string_table);
         assert!(flags.requires_checksums);
         assert!(flags.requires_canonical);
     }
@@ -947,6 +1318,7 @@ mod tests {
         assert!(!flags.supports_streaming);
         assert!(!flags.supports_delta);
         assert!(!flags.supports_llb);
+        assert!(!flags.supports_string_table);
         assert!(!flags.requires_checksums);
         assert!(flags.requires_canonical);
     }
@@ -958,6 +1330,7 @@ mod tests {
             supports_streaming: true,
             supports_delta: false,
             supports_llb: true,
+            supports_string_table: true,
             requires_checksums: false,
             requires_canonical: true,
         };
@@ -967,6 +1340,7 @@ mod tests {
             supports_streaming: false,
             supports_delta: true,
             supports_llb: true,
+            supports_string_table: false,
             requires_checksums: true,
             requires_canonical: false,
         };
@@ -976,6 +1350,7 @@ mod tests {
         assert!(!intersection.supports_streaming);
         assert!(!intersection.supports_delta);
         assert!(intersection.supports_llb);
+        assert!(!intersection.supports_string_table);
         assert!(intersection.requires_checksums); // OR logic
         assert!(intersection.requires_canonical); // OR logic
     }
@@ -1033,6 +1408,7 @@ mod tests {
                 supports_streaming: true,
                 supports_delta: false,
                 supports_llb: true,
+                supports_string_table: true,
                 requires_checksums: false,
                 requires_canonical: true,
             },
@@ -1046,6 +1422,7 @@ mod tests {
                 supports_streaming: false,
                 supports_delta: true,
                 supports_llb: true,
+                supports_string_table: true,
                 requires_checksums: true,
                 requires_canonical: false,
             },
@@ -1217,6 +1594,106 @@ mod tests {
         assert_eq!(ErrorCode::Generic.to_u8(), 0xFF);
     }
 
+    #[test]
+    fn test_feature_flags_bits_round_trip() {
+        for flags in [
+            FeatureFlags::new(),
+            FeatureFlags::v0_5_full(),
+            FeatureFlags::v0_4_compatible(),
+        ] {
+            assert_eq!(FeatureFlags::from_bits(flags.to_bits()), flags);
+        }
+        assert_eq!(FeatureFlags::v0_4_compatible().to_bits(), 0b0100_0000);
+    }
+
+    #[test]
+    fn test_message_wire_round_trip() {
+        let fid_def = FidDefinition {
+            fid: 12,
+            name: "user_id".to_string(),
+            type_tag: TypeTag::Int,
+            status: FidDefStatus::Active,
+            since: "0.5.14".to_string(),
+        };
+        let messages = vec![
+            NegotiationMessage::Capabilities {
+                version: 5,
+                features: FeatureFlags::v0_5_full(),
+                supported_types: vec![TypeTag::Int, TypeTag::String, TypeTag::NestedRecord],
+            },
+            NegotiationMessage::CapabilitiesAck {
+                version: 4,
+                features: FeatureFlags::v0_4_compatible(),
+            },
+            NegotiationMessage::SelectSchema {
+                schema_id: "default".to_string(),
+                fid_mappings: HashMap::from([(1, "id".to_string()), (300, "name".to_string())]),
+            },
+            NegotiationMessage::Ready {
+                session_id: u64::MAX,
+            },
+            NegotiationMessage::Error {
+                code: ErrorCode::ProtocolVersionMismatch,
+                message: "version 5 unsupported".to_string(),
+            },
+            NegotiationMessage::RequestRegistry {
+                fid_range: Some((0, 1000)),
+                include_types: true,
+                local_version: None,
+            },
+            NegotiationMessage::RegistryResponse {
+                version: "1.2.0".to_string(),
+                protocol_version: "0.5".to_string(),
+                fids: vec![fid_def.clone()],
+            },
+            NegotiationMessage::RegistryDelta {
+                base_version: "1.1.0".to_string(),
+                target_version: "1.2.0".to_string(),
+                added: vec![fid_def],
+                deprecated: vec![7],
+                tombstoned: vec![8, 9],
+            },
+        ];
+        for message in messages {
+            let bytes = message.encode();
+            assert_eq!(NegotiationMessage::decode(&bytes).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_message_wire_rejects_malformed_input() {
+        let bytes = NegotiationMessage::Ready { session_id: 1 }.encode();
+        assert!(matches!(
+            NegotiationMessage::decode(&bytes[..4]),
+            Err(BinaryError::UnexpectedEof { .. })
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            NegotiationMessage::decode(&trailing),
+            Err(BinaryError::TrailingData { bytes_remaining: 1 })
+        );
+
+        assert!(matches!(
+            NegotiationMessage::decode(&[0x7F]),
+            Err(BinaryError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_negotiation_error_codes() {
+        let err = NegotiationError::ProtocolVersionMismatch {
+            local: 5,
+            remote: 4,
+        };
+        assert_eq!(err.error_code(), ErrorCode::ProtocolVersionMismatch);
+        let err = NegotiationError::UnsupportedFeature {
+            feature: "delta".to_string(),
+        };
+        assert_eq!(err.error_code(), ErrorCode::UnsupportedFeature);
+    }
+
     #[test]
     fn test_error_code_round_trip() {
         let codes = vec![
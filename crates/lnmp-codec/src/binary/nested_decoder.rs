@@ -2,13 +2,15 @@
 
 //! Nested structure decoding for LNMP v0.5 binary format.
 //!
-//! This module provides decoding support for nested records and arrays in the binary format.
-//! It implements recursive decoding with depth validation to prevent stack overflow attacks.
+//! This module decodes the standalone nested records and arrays written by
+//! [`BinaryNestedEncoder`](super::BinaryNestedEncoder), with depth validation to
+//! prevent stack overflow attacks. The input is `TAG | VALUE` in the same layout a
+//! [`BinaryEntry`] uses inside a frame.
 
+use super::entry::BinaryEntry;
 use super::error::BinaryError;
-use super::types::TypeTag;
-use super::varint;
-use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+use super::types::{BinaryValue, TypeTag};
+use lnmp_core::{LnmpRecord, LnmpValue};
 
 /// Configuration for nested structure decoding (v0.5)
 #[derive(Debug, Clone)]
@@ -81,10 +83,10 @@ impl BinaryNestedDecoder {
     ///
     /// Binary layout:
     /// ```text
-    /// ┌──────────┬──────────────┬─────────────────────────────────┐
-    /// │   TAG    │ FIELD_COUNT  │      FIELD ENTRIES...           │
-    /// │ (1 byte) │  (VarInt)    │  { FID | VALUE }*               │
-    /// └──────────┴──────────────┴─────────────────────────────────┘
+    /// ┌──────────┬──────────┬──────────────┬─────────────────────────────┐
+    /// │   TAG    │  LENGTH  │ FIELD_COUNT  │         ENTRIES...          │
+    /// │ (1 byte) │ (VarInt) │  (VarInt)    │  { FID | TAG | VALUE }*     │
+    /// └──────────┴──────────┴──────────────┴─────────────────────────────┘
     /// ```
     ///
     /// # Arguments
@@ -102,239 +104,9 @@ impl BinaryNestedDecoder {
     /// - Binary data is malformed
     /// - TAG byte is not 0x06
     pub fn decode_nested_record(&self, bytes: &[u8]) -> Result<(LnmpRecord, usize), BinaryError> {
-        self.decode_nested_record_with_depth(bytes, 0)
-    }
-
-    /// Decodes a nested record with depth tracking
-    fn decode_nested_record_with_depth(
-        &self,
-        bytes: &[u8],
-        current_depth: usize,
-    ) -> Result<(LnmpRecord, usize), BinaryError> {
-        // Validate depth
-        if current_depth >= self.config.max_depth {
-            return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
-                max: self.config.max_depth,
-            });
-        }
-
-        let mut offset = 0;
-
-        // Read and validate TAG byte (0x06)
-        if bytes.is_empty() {
-            return Err(BinaryError::UnexpectedEof {
-                expected: 1,
-                found: bytes.len(),
-            });
-        }
-
-        let tag = bytes[offset];
-        offset += 1;
-
-        if tag != TypeTag::NestedRecord.to_u8() {
-            return Err(BinaryError::InvalidTypeTag { tag });
-        }
-
-        // Decode FIELD_COUNT from VarInt
-        let (field_count, consumed) = varint::decode(&bytes[offset..])?;
-        offset += consumed;
-
-        if field_count < 0 {
-            return Err(BinaryError::InvalidNestedStructure {
-                reason: format!("Negative field count: {}", field_count),
-            });
-        }
-
-        let field_count = field_count as usize;
-
-        // Recursively decode each field entry
-        let mut record = LnmpRecord::new();
-
-        for _ in 0..field_count {
-            // Decode FID as VarInt
-            let (fid_i64, consumed) = varint::decode(&bytes[offset..])?;
-            offset += consumed;
-
-            if fid_i64 < 0 || fid_i64 > u16::MAX as i64 {
-                return Err(BinaryError::InvalidFID {
-                    fid: fid_i64 as u16,
-                    reason: format!("FID out of range: {}", fid_i64),
-                });
-            }
-
-            let fid = fid_i64 as u16;
-
-            // Decode VALUE recursively (depth stays the same, will increment inside nested record/array)
-            let (value, consumed) = self.decode_value_recursive(&bytes[offset..], current_depth)?;
-            offset += consumed;
-
-            // Add field to record
-            record.add_field(LnmpField { fid, value });
-        }
-
-        Ok((record, offset))
-    }
-
-    /// Decodes a value recursively, handling nested structures
-    fn decode_value_recursive(
-        &self,
-        bytes: &[u8],
-        current_depth: usize,
-    ) -> Result<(LnmpValue, usize), BinaryError> {
-        // Validate depth for nested structures
-        if current_depth >= self.config.max_depth {
-            return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
-                max: self.config.max_depth,
-            });
-        }
-
-        let mut offset = 0;
-
-        // Read type tag
-        if bytes.is_empty() {
-            return Err(BinaryError::UnexpectedEof {
-                expected: 1,
-                found: bytes.len(),
-            });
-        }
-
-        let tag_byte = bytes[offset];
-        offset += 1;
-
-        let type_tag = TypeTag::from_u8(tag_byte)?;
-
-        match type_tag {
-            TypeTag::Int => {
-                let (value, consumed) = varint::decode(&bytes[offset..])?;
-                offset += consumed;
-                Ok((LnmpValue::Int(value), offset))
-            }
-            TypeTag::Float => {
-                if bytes.len() < offset + 8 {
-                    return Err(BinaryError::UnexpectedEof {
-                        expected: offset + 8,
-                        found: bytes.len(),
-                    });
-                }
-                let float_bytes: [u8; 8] = bytes[offset..offset + 8].try_into().unwrap();
-                let value = f64::from_le_bytes(float_bytes);
-                offset += 8;
-                Ok((LnmpValue::Float(value), offset))
-            }
-            TypeTag::Bool => {
-                if bytes.len() < offset + 1 {
-                    return Err(BinaryError::UnexpectedEof {
-                        expected: offset + 1,
-                        found: bytes.len(),
-                    });
-                }
-                let value = bytes[offset] != 0x00;
-                offset += 1;
-                Ok((LnmpValue::Bool(value), offset))
-            }
-            TypeTag::String => {
-                let (length, consumed) = varint::decode(&bytes[offset..])?;
-                offset += consumed;
-
-                if length < 0 {
-                    return Err(BinaryError::InvalidValue {
-                        field_id: 0,
-                        type_tag: tag_byte,
-                        reason: format!("Negative string length: {}", length),
-                    });
-                }
-
-                let length = length as usize;
-
-                if bytes.len() < offset + length {
-                    return Err(BinaryError::UnexpectedEof {
-                        expected: offset + length,
-                        found: bytes.len(),
-                    });
-                }
-
-                let string_bytes = &bytes[offset..offset + length];
-                let value = String::from_utf8(string_bytes.to_vec())
-                    .map_err(|_| BinaryError::InvalidUtf8 { field_id: 0 })?;
-                offset += length;
-                Ok((LnmpValue::String(value), offset))
-            }
-            TypeTag::StringArray => {
-                let (count, consumed) = varint::decode(&bytes[offset..])?;
-                offset += consumed;
-
-                if count < 0 {
-                    return Err(BinaryError::InvalidValue {
-                        field_id: 0,
-                        type_tag: tag_byte,
-                        reason: format!("Negative array count: {}", count),
-                    });
-                }
-
-                let count = count as usize;
-                let mut array = Vec::with_capacity(count);
-
-                for _ in 0..count {
-                    let (length, consumed) = varint::decode(&bytes[offset..])?;
-                    offset += consumed;
-
-                    if length < 0 {
-                        return Err(BinaryError::InvalidValue {
-                            field_id: 0,
-                            type_tag: tag_byte,
-                            reason: format!("Negative string length in array: {}", length),
-                        });
-                    }
-
-                    let length = length as usize;
-
-                    if bytes.len() < offset + length {
-                        return Err(BinaryError::UnexpectedEof {
-                            expected: offset + length,
-                            found: bytes.len(),
-                        });
-                    }
-
-                    let string_bytes = &bytes[offset..offset + length];
-                    let string = String::from_utf8(string_bytes.to_vec())
-                        .map_err(|_| BinaryError::InvalidUtf8 { field_id: 0 })?;
-                    offset += length;
-                    array.push(string);
-                }
-
-                Ok((LnmpValue::StringArray(array), offset))
-            }
-            TypeTag::NestedRecord => {
-                // Check if nested structures are allowed
-                if !self.config.allow_nested {
-                    return Err(BinaryError::NestedStructureNotSupported);
-                }
-
-                // Recursively decode nested record (offset already advanced past tag)
-                // Increment depth when entering a nested record
-                let (record, consumed) =
-                    self.decode_nested_record_with_depth(&bytes[offset - 1..], current_depth + 1)?;
-                // Subtract 1 because we already consumed the tag byte
-                offset += consumed - 1;
-                Ok((LnmpValue::NestedRecord(Box::new(record)), offset))
-            }
-            TypeTag::NestedArray => {
-                // Check if nested structures are allowed
-                if !self.config.allow_nested {
-                    return Err(BinaryError::NestedStructureNotSupported);
-                }
-
-                // Recursively decode nested array (offset already advanced past tag)
-                // Increment depth when entering a nested array
-                let (records, consumed) =
-                    self.decode_nested_array_with_depth(&bytes[offset - 1..], current_depth + 1)?;
-                // Subtract 1 because we already consumed the tag byte
-                offset += consumed - 1;
-                Ok((LnmpValue::NestedArray(records), offset))
-            }
-            _ => Err(BinaryError::InvalidTypeTag { tag: tag_byte }),
+        match self.decode(bytes, TypeTag::NestedRecord)? {
+            (BinaryValue::NestedRecord(record), consumed) => Ok((*record, consumed)),
+            _ => unreachable!("tag checked before decoding"),
         }
     }
 
@@ -342,10 +114,10 @@ impl BinaryNestedDecoder {
     ///
     /// Binary layout:
     /// ```text
-    /// ┌──────────┬──────────────┬─────────────────────────────────┐
-    /// │   TAG    │ ELEM_COUNT   │      RECORD ENTRIES...          │
-    /// │ (1 byte) │  (VarInt)    │  [ RECORD(...) ]*               │
-    /// └──────────┴──────────────┴─────────────────────────────────┘
+    /// ┌──────────┬──────────┬──────────────┬─────────────────────────────┐
+    /// │   TAG    │  LENGTH  │ ELEM_COUNT   │         RECORDS...          │
+    /// │ (1 byte) │ (VarInt) │  (VarInt)    │  { FIELD_COUNT | ENTRY* }*  │
+    /// └──────────┴──────────┴──────────────┴─────────────────────────────┘
     /// ```
     ///
     /// # Arguments
@@ -366,68 +138,44 @@ impl BinaryNestedDecoder {
         &self,
         bytes: &[u8],
     ) -> Result<(Vec<LnmpRecord>, usize), BinaryError> {
-        self.decode_nested_array_with_depth(bytes, 0)
-    }
-
-    /// Decodes a nested array with depth tracking
-    fn decode_nested_array_with_depth(
-        &self,
-        bytes: &[u8],
-        current_depth: usize,
-    ) -> Result<(Vec<LnmpRecord>, usize), BinaryError> {
-        // Validate depth
-        if current_depth >= self.config.max_depth {
-            return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
-                max: self.config.max_depth,
-            });
+        match self.decode(bytes, TypeTag::NestedArray)? {
+            (BinaryValue::NestedArray(records), consumed) => Ok((records, consumed)),
+            _ => unreachable!("tag checked before decoding"),
         }
+    }
 
-        let mut offset = 0;
-
-        // Read and validate TAG byte (0x07)
-        if bytes.is_empty() {
-            return Err(BinaryError::UnexpectedEof {
-                expected: 1,
-                found: bytes.len(),
-            });
-        }
-
-        let tag = bytes[offset];
-        offset += 1;
-
-        if tag != TypeTag::NestedArray.to_u8() {
+    fn decode(&self, bytes: &[u8], expected: TypeTag) -> Result<(BinaryValue, usize), BinaryError> {
+        let tag = *bytes.first().ok_or(BinaryError::UnexpectedEof {
+            expected: 1,
+            found: 0,
+        })?;
+        if tag != expected.to_u8() {
             return Err(BinaryError::InvalidTypeTag { tag });
         }
 
-        // Decode ELEMENT_COUNT from VarInt
-        let (element_count, consumed) = varint::decode(&bytes[offset..])?;
-        offset += consumed;
-
-        if element_count < 0 {
-            return Err(BinaryError::InvalidNestedStructure {
-                reason: format!("Negative element count: {}", element_count),
-            });
-        }
-
-        let element_count = element_count as usize;
-
-        // Recursively decode each record
-        let mut records = Vec::with_capacity(element_count);
-
-        for _ in 0..element_count {
-            // Each record in the array is encoded as a nested record
-            // Depth stays the same since we're already inside the array
-            let (record, consumed) =
-                self.decode_nested_record_with_depth(&bytes[offset..], current_depth)?;
-            offset += consumed;
-            records.push(record);
+        let (entry, consumed) = BinaryEntry::decode_tagged_value(bytes, 0, self.config.max_depth)?;
+        if !self.config.allow_nested && has_nested_fields(&entry.value) {
+            return Err(BinaryError::NestedStructureNotSupported);
         }
-
-        Ok((records, offset))
+        Ok((entry.value, consumed))
     }
 }
 
+/// Returns true if any record inside `value` holds a nested record or array
+fn has_nested_fields(value: &BinaryValue) -> bool {
+    let records = match value {
+        BinaryValue::NestedRecord(record) => std::slice::from_ref(record.as_ref()),
+        BinaryValue::NestedArray(records) => records.as_slice(),
+        _ => return false,
+    };
+    records.iter().flat_map(LnmpRecord::fields).any(|field| {
+        matches!(
+            field.value,
+            LnmpValue::NestedRecord(_) | LnmpValue::NestedArray(_)
+        )
+    })
+}
+
 impl Default for BinaryNestedDecoder {
     fn default() -> Self {
         Self::new()
@@ -487,6 +235,9 @@ mod tests {
     }
 }
 
+#[cfg(test)]
+use lnmp_core::LnmpField;
+
 #[test]
 fn test_decode_empty_nested_record() {
     use crate::binary::nested_encoder::BinaryNestedEncoder;
@@ -555,8 +306,8 @@ fn test_decode_nested_record_insufficient_data() {
 #[test]
 fn test_decode_nested_record_negative_field_count() {
     let decoder = BinaryNestedDecoder::new();
-    // TAG (0x06) + negative field count
-    let bytes = vec![0x06, 0x7F]; // -1 in VarInt
+    // TAG (0x06) + LENGTH (1) + negative field count
+    let bytes = vec![0x06, 0x01, 0x7F]; // -1 in VarInt
 
     let result = decoder.decode_nested_record(&bytes);
     assert!(matches!(
@@ -791,13 +542,13 @@ fn test_decode_nested_array_invalid_tag() {
 #[test]
 fn test_decode_nested_array_negative_element_count() {
     let decoder = BinaryNestedDecoder::new();
-    // TAG (0x07) + negative element count
-    let bytes = vec![0x07, 0x7F]; // -1 in VarInt
+    // TAG (0x07) + LENGTH (1) + negative element count
+    let bytes = vec![0x07, 0x01, 0x7F]; // -1 in VarInt
 
     let result = decoder.decode_nested_array(&bytes);
     assert!(matches!(
         result,
-        Err(BinaryError::InvalidNestedStructure { .. })
+        Err(BinaryError::InvalidValue { type_tag: 0x07, .. })
     ));
 }
 
@@ -934,7 +685,7 @@ fn test_decode_deeply_nested_record_depth_3() {
 #[test]
 fn test_decode_malformed_nested_structure_incomplete_field() {
     let decoder = BinaryNestedDecoder::new();
-    // TAG (0x06) + FIELD_COUNT (1) + FID (1) but no VALUE
+    // TAG (0x06) + LENGTH (1) + FIELD_COUNT (1) but no entry
     let bytes = vec![0x06, 0x01, 0x01];
 
     let result = decoder.decode_nested_record(&bytes);
@@ -942,13 +693,16 @@ fn test_decode_malformed_nested_structure_incomplete_field() {
 }
 
 #[test]
-fn test_decode_malformed_nested_structure_invalid_fid() {
+fn test_decode_malformed_nested_structure_length_mismatch() {
     let decoder = BinaryNestedDecoder::new();
-    // TAG (0x06) + FIELD_COUNT (1) + negative FID
-    let bytes = vec![0x06, 0x01, 0x7F]; // -1 as FID
+    // TAG (0x06) + LENGTH (2) + FIELD_COUNT (0) + stray byte inside LENGTH
+    let bytes = vec![0x06, 0x02, 0x00, 0x00];
 
     let result = decoder.decode_nested_record(&bytes);
-    assert!(matches!(result, Err(BinaryError::InvalidFID { .. })));
+    assert!(matches!(
+        result,
+        Err(BinaryError::InvalidNestedStructure { .. })
+    ));
 }
 
 #[test]
@@ -956,11 +710,11 @@ fn test_decode_nested_structure_not_allowed() {
     let config = NestedDecoderConfig::new().with_allow_nested(false);
     let decoder = BinaryNestedDecoder::with_config(config);
 
-    // Try to decode a nested record value
-    // TAG (0x06) + FIELD_COUNT (0)
-    let bytes = vec![0x06, 0x00];
+    // F1 = {} inside the outer record
+    // TAG (0x06) + LENGTH (6) + FIELD_COUNT (1) + F1 NestedRecord LENGTH (1) FIELD_COUNT (0)
+    let bytes = vec![0x06, 0x06, 0x01, 0x01, 0x00, 0x06, 0x01, 0x00];
 
-    let result = decoder.decode_value_recursive(&bytes, 0);
+    let result = decoder.decode_nested_record(&bytes);
     assert!(matches!(
         result,
         Err(BinaryError::NestedStructureNotSupported)
@@ -972,11 +726,11 @@ fn test_decode_nested_array_not_allowed() {
     let config = NestedDecoderConfig::new().with_allow_nested(false);
     let decoder = BinaryNestedDecoder::with_config(config);
 
-    // Try to decode a nested array value
-    // TAG (0x07) + ELEMENT_COUNT (0)
-    let bytes = vec![0x07, 0x00];
+    // F1 = [] inside the outer record
+    // TAG (0x06) + LENGTH (6) + FIELD_COUNT (1) + F1 NestedArray LENGTH (1) ELEMENT_COUNT (0)
+    let bytes = vec![0x06, 0x06, 0x01, 0x01, 0x00, 0x07, 0x01, 0x00];
 
-    let result = decoder.decode_value_recursive(&bytes, 0);
+    let result = decoder.decode_nested_record(&bytes);
     assert!(matches!(
         result,
         Err(BinaryError::NestedStructureNotSupported)
@@ -1175,8 +929,9 @@ fn test_decode_depth_limit_enforced_at_exact_limit() {
 #[test]
 fn test_decode_invalid_utf8_in_string() {
     let decoder = BinaryNestedDecoder::new();
-    // TAG (0x06) + FIELD_COUNT (1) + FID (1) + String TAG (0x04) + length (3) + invalid UTF-8
-    let bytes = vec![0x06, 0x01, 0x01, 0x04, 0x03, 0xFF, 0xFE, 0xFD];
+    // TAG (0x06) + LENGTH (8) + FIELD_COUNT (1) + FID (1) + String TAG (0x04) + length (3)
+    // + invalid UTF-8
+    let bytes = vec![0x06, 0x08, 0x01, 0x01, 0x00, 0x04, 0x03, 0xFF, 0xFE, 0xFD];
 
     let result = decoder.decode_nested_record(&bytes);
     assert!(matches!(result, Err(BinaryError::InvalidUtf8 { .. })));
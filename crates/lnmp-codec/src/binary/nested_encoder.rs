@@ -1,12 +1,13 @@
 //! Nested structure encoding for LNMP v0.5 binary format.
 //!
-//! This module provides encoding support for nested records and arrays in the binary format.
-//! It implements recursive encoding with depth validation and size limits to prevent
-//! stack overflow and memory exhaustion attacks.
+//! This module encodes standalone nested records and arrays with depth validation and
+//! size limits to prevent stack overflow and memory exhaustion attacks. The output is
+//! `TAG | VALUE` in the same layout a [`BinaryEntry`] uses inside a frame, i.e. the
+//! entry without its FID.
 
+use super::entry::BinaryEntry;
 use super::error::BinaryError;
-use super::types::TypeTag;
-use super::varint;
+use super::types::BinaryValue;
 use lnmp_core::{LnmpRecord, LnmpValue};
 
 /// Configuration for nested structure encoding (v0.5)
@@ -80,12 +81,14 @@ impl BinaryNestedEncoder {
     ///
     /// Binary layout:
     /// ```text
-    /// ┌──────────┬──────────────┬─────────────────────────────────┐
-    /// │   TAG    │ FIELD_COUNT  │      FIELD ENTRIES...           │
-    /// │ (1 byte) │  (VarInt)    │  { FID | VALUE }*               │
-    /// └──────────┴──────────────┴─────────────────────────────────┘
+    /// ┌──────────┬──────────┬──────────────┬─────────────────────────────┐
+    /// │   TAG    │  LENGTH  │ FIELD_COUNT  │         ENTRIES...          │
+    /// │ (1 byte) │ (VarInt) │  (VarInt)    │  { FID | TAG | VALUE }*     │
+    /// └──────────┴──────────┴──────────────┴─────────────────────────────┘
     /// ```
     ///
+    /// `LENGTH` counts the bytes after it; entries are written in ascending FID order.
+    ///
     /// # Arguments
     ///
     /// * `record` - The nested record to encode
@@ -101,66 +104,17 @@ impl BinaryNestedEncoder {
     /// - Record size exceeds configured maximum
     /// - Field encoding fails
     pub fn encode_nested_record(&self, record: &LnmpRecord) -> Result<Vec<u8>, BinaryError> {
-        self.encode_nested_record_with_depth(record, 0)
-    }
-
-    /// Encodes a nested record with depth tracking
-    fn encode_nested_record_with_depth(
-        &self,
-        record: &LnmpRecord,
-        current_depth: usize,
-    ) -> Result<Vec<u8>, BinaryError> {
-        // Validate depth
-        if current_depth >= self.config.max_depth {
-            return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
-                max: self.config.max_depth,
-            });
-        }
-
-        let mut buffer = Vec::new();
-
-        // Write TAG byte (0x06 for NestedRecord)
-        buffer.push(TypeTag::NestedRecord.to_u8());
-
-        // Get fields (sorted for canonical ordering)
-        let fields = record.sorted_fields();
-
-        // Encode FIELD_COUNT as VarInt
-        let field_count = fields.len() as i64;
-        buffer.extend_from_slice(&varint::encode(field_count));
-
-        // Recursively encode each field
-        for field in fields {
-            // Encode FID as VarInt
-            buffer.extend_from_slice(&varint::encode(field.fid as i64));
-
-            // Encode VALUE recursively
-            let value_bytes = self.encode_value_recursive(&field.value, current_depth + 1)?;
-            buffer.extend_from_slice(&value_bytes);
-
-            // Check size limit if configured
-            if let Some(max_size) = self.config.max_record_size {
-                if buffer.len() > max_size {
-                    return Err(BinaryError::RecordSizeExceeded {
-                        size: buffer.len(),
-                        max: max_size,
-                    });
-                }
-            }
-        }
-
-        Ok(buffer)
+        self.encode(LnmpValue::NestedRecord(Box::new(record.clone())))
     }
 
     /// Encodes a nested array to binary format
     ///
     /// Binary layout:
     /// ```text
-    /// ┌──────────┬──────────────┬─────────────────────────────────┐
-    /// │   TAG    │ ELEM_COUNT   │      RECORD ENTRIES...          │
-    /// │ (1 byte) │  (VarInt)    │  [ RECORD(...) ]*               │
-    /// └──────────┴──────────────┴─────────────────────────────────┘
+    /// ┌──────────┬──────────┬──────────────┬─────────────────────────────┐
+    /// │   TAG    │  LENGTH  │ ELEM_COUNT   │         RECORDS...          │
+    /// │ (1 byte) │ (VarInt) │  (VarInt)    │  { FIELD_COUNT | ENTRY* }*  │
+    /// └──────────┴──────────┴──────────────┴─────────────────────────────┘
     /// ```
     ///
     /// # Arguments
@@ -178,198 +132,44 @@ impl BinaryNestedEncoder {
     /// - Record size exceeds configured maximum
     /// - Record encoding fails
     pub fn encode_nested_array(&self, records: &[LnmpRecord]) -> Result<Vec<u8>, BinaryError> {
-        self.encode_nested_array_with_depth(records, 0)
+        self.encode(LnmpValue::NestedArray(records.to_vec()))
     }
 
-    /// Encodes a nested array with depth tracking
-    fn encode_nested_array_with_depth(
-        &self,
-        records: &[LnmpRecord],
-        current_depth: usize,
-    ) -> Result<Vec<u8>, BinaryError> {
-        // Validate depth
-        if current_depth >= self.config.max_depth {
-            return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
-                max: self.config.max_depth,
-            });
-        }
+    fn encode(&self, value: LnmpValue) -> Result<Vec<u8>, BinaryError> {
+        self.check_depth(&value, 1)?;
+        let value = BinaryValue::from_lnmp_value(&value)?;
+        let bytes = BinaryEntry::new(0, value).encode_tagged_value();
 
-        let mut buffer = Vec::new();
-
-        // Write TAG byte (0x07 for NestedArray)
-        buffer.push(TypeTag::NestedArray.to_u8());
-
-        // Encode ELEMENT_COUNT as VarInt
-        let element_count = records.len() as i64;
-        buffer.extend_from_slice(&varint::encode(element_count));
-
-        // Recursively encode each record
-        for record in records {
-            // For records within arrays, we need to encode them with canonical ordering
-            let record_bytes = self.encode_nested_record_with_depth(record, current_depth + 1)?;
-            buffer.extend_from_slice(&record_bytes);
-
-            // Check size limit if configured
-            if let Some(max_size) = self.config.max_record_size {
-                if buffer.len() > max_size {
-                    return Err(BinaryError::RecordSizeExceeded {
-                        size: buffer.len(),
-                        max: max_size,
-                    });
-                }
+        if let Some(max_size) = self.config.max_record_size {
+            if bytes.len() > max_size {
+                return Err(BinaryError::RecordSizeExceeded {
+                    size: bytes.len(),
+                    max: max_size,
+                });
             }
         }
+        Ok(bytes)
+    }
 
-        Ok(buffer)
-    }
-
-    /// Encodes a value recursively, handling nested structures
-    fn encode_value_recursive(
-        &self,
-        value: &LnmpValue,
-        current_depth: usize,
-    ) -> Result<Vec<u8>, BinaryError> {
-        // Validate depth for nested structures
-        if current_depth >= self.config.max_depth
-            && matches!(
-                value,
-                LnmpValue::NestedRecord(_) | LnmpValue::NestedArray(_)
-            )
-        {
+    /// Rejects nested values more than `max_depth` levels deep; `value` is at `depth`
+    fn check_depth(&self, value: &LnmpValue, depth: usize) -> Result<(), BinaryError> {
+        let records = match value {
+            LnmpValue::NestedRecord(record) => std::slice::from_ref(record.as_ref()),
+            LnmpValue::NestedArray(records) => records.as_slice(),
+            _ => return Ok(()),
+        };
+        if depth > self.config.max_depth {
             return Err(BinaryError::NestingDepthExceeded {
-                depth: current_depth,
+                depth,
                 max: self.config.max_depth,
             });
         }
-
-        match value {
-            LnmpValue::Int(i) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::Int.to_u8());
-                buffer.extend_from_slice(&varint::encode(*i));
-                Ok(buffer)
-            }
-            LnmpValue::Float(f) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::Float.to_u8());
-                buffer.extend_from_slice(&f.to_le_bytes());
-                Ok(buffer)
-            }
-            LnmpValue::Bool(b) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::Bool.to_u8());
-                buffer.push(if *b { 0x01 } else { 0x00 });
-                Ok(buffer)
-            }
-            LnmpValue::String(s) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::String.to_u8());
-                let bytes = s.as_bytes();
-                buffer.extend_from_slice(&varint::encode(bytes.len() as i64));
-                buffer.extend_from_slice(bytes);
-                Ok(buffer)
-            }
-            LnmpValue::StringArray(arr) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::StringArray.to_u8());
-                buffer.extend_from_slice(&varint::encode(arr.len() as i64));
-                for s in arr {
-                    let bytes = s.as_bytes();
-                    buffer.extend_from_slice(&varint::encode(bytes.len() as i64));
-                    buffer.extend_from_slice(bytes);
-                }
-                Ok(buffer)
-            }
-            LnmpValue::EmbeddingDelta(delta) => {
-                let mut buffer = Vec::new();
-                // Note: Delta handling in nested encoder - we encode the delta directly
-                buffer.push(TypeTag::Embedding.to_u8()); // TypeTag::Embedding (deltas use same tag)
-                let delta_bytes = delta.encode().map_err(|_| BinaryError::InvalidValue {
-                    field_id: 0,
-                    type_tag: TypeTag::Embedding.to_u8(),
-                    reason: "Failed to encode delta".to_string(),
-                })?;
-                buffer.extend_from_slice(&(delta_bytes.len() as u32).to_le_bytes());
-                buffer.extend_from_slice(&delta_bytes);
-                Ok(buffer)
-            }
-            LnmpValue::Embedding(vec) => {
-                // Tag + Length + Encoded Vector
-                let encoded = lnmp_embedding::Encoder::encode(vec).map_err(|_| {
-                    BinaryError::InvalidValue {
-                        field_id: 0,
-                        type_tag: TypeTag::Embedding.to_u8(),
-                        reason: "Failed to encode embedding".to_string(),
-                    }
-                })?;
-
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::Embedding.to_u8());
-                buffer.extend_from_slice(&varint::encode(encoded.len() as i64));
-                buffer.extend_from_slice(&encoded);
-                Ok(buffer)
-            }
-            LnmpValue::QuantizedEmbedding(qv) => {
-                // TAG + SCHEME + SCALE + ZERO_POINT + MIN_VAL + DIM + DATA
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::QuantizedEmbedding.to_u8()); // 0x0A
-
-                // Encode scheme as 1 byte
-                buffer.push(qv.scheme as u8);
-
-                // Encode scale (f32, 4 bytes LE)
-                buffer.extend_from_slice(&qv.scale.to_le_bytes());
-
-                // Encode zero_point (i8, 1 byte)
-                buffer.push(qv.zero_point as u8);
-
-                // Encode min_val (f32, 4 bytes LE)
-                buffer.extend_from_slice(&qv.min_val.to_le_bytes());
-
-                // Encode dim (u32, 4 bytes LE)
-                buffer.extend_from_slice(&qv.dim.to_le_bytes());
-
-                // Encode data length and data bytes
-                buffer.extend_from_slice(&varint::encode(qv.data.len() as i64));
-                buffer.extend_from_slice(&qv.data);
-
-                Ok(buffer)
-            }
-            LnmpValue::IntArray(arr) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::IntArray.to_u8());
-                buffer.extend_from_slice(&varint::encode(arr.len() as i64));
-                for i in arr {
-                    buffer.extend_from_slice(&varint::encode(*i));
-                }
-                Ok(buffer)
-            }
-            LnmpValue::FloatArray(arr) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::FloatArray.to_u8());
-                buffer.extend_from_slice(&varint::encode(arr.len() as i64));
-                for f in arr {
-                    buffer.extend_from_slice(&f.to_le_bytes());
-                }
-                Ok(buffer)
-            }
-            LnmpValue::BoolArray(arr) => {
-                let mut buffer = Vec::new();
-                buffer.push(TypeTag::BoolArray.to_u8());
-                buffer.extend_from_slice(&varint::encode(arr.len() as i64));
-                for b in arr {
-                    buffer.push(if *b { 0x01 } else { 0x00 });
-                }
-                Ok(buffer)
-            }
-            LnmpValue::NestedRecord(record) => {
-                self.encode_nested_record_with_depth(record, current_depth)
-            }
-            LnmpValue::NestedArray(records) => {
-                self.encode_nested_array_with_depth(records, current_depth)
+        for record in records {
+            for field in record.fields() {
+                self.check_depth(&field.value, depth + 1)?;
             }
         }
+        Ok(())
     }
 }
 
@@ -413,10 +213,8 @@ mod tests {
 
         let result = encoder.encode_nested_record(&record).unwrap();
 
-        // Should have TAG (0x06) + FIELD_COUNT (0)
-        assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x00); // Field count = 0
-        assert_eq!(result.len(), 2);
+        // TAG (0x06) + LENGTH (1) + FIELD_COUNT (0)
+        assert_eq!(result, vec![0x06, 0x01, 0x00]);
     }
 
     #[test]
@@ -434,9 +232,10 @@ mod tests {
 
         let result = encoder.encode_nested_record(&record).unwrap();
 
-        // Should start with TAG (0x06) + FIELD_COUNT (2)
+        // Should start with TAG (0x06) + LENGTH + FIELD_COUNT (2)
         assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x02); // Field count = 2
+        assert_eq!(result[1] as usize, result.len() - 2); // LENGTH of the body
+        assert_eq!(result[2], 0x02); // Field count = 2
     }
 
     #[test]
@@ -461,11 +260,11 @@ mod tests {
 
         // Verify TAG and count
         assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x03); // Field count = 3
+        assert_eq!(result[2], 0x03); // Field count = 3
 
         // Fields should be encoded in sorted order: 2, 5, 10
-        // After TAG and COUNT, we should see FID=2 first
-        assert_eq!(result[2], 0x02); // FID = 2 (VarInt)
+        // After TAG, LENGTH and COUNT, we should see FID=2 first
+        assert_eq!(&result[3..5], &[0x02, 0x00]); // FID = 2 (little-endian)
     }
 
     #[test]
@@ -475,10 +274,8 @@ mod tests {
 
         let result = encoder.encode_nested_array(&records).unwrap();
 
-        // Should have TAG (0x07) + ELEMENT_COUNT (0)
-        assert_eq!(result[0], 0x07); // NestedArray tag
-        assert_eq!(result[1], 0x00); // Element count = 0
-        assert_eq!(result.len(), 2);
+        // TAG (0x07) + LENGTH (1) + ELEMENT_COUNT (0)
+        assert_eq!(result, vec![0x07, 0x01, 0x00]);
     }
 
     #[test]
@@ -492,11 +289,15 @@ mod tests {
 
         let result = encoder.encode_nested_array(&[record]).unwrap();
 
-        // Should start with TAG (0x07) + ELEMENT_COUNT (1)
-        assert_eq!(result[0], 0x07); // NestedArray tag
-        assert_eq!(result[1], 0x01); // Element count = 1
-                                     // Next should be the nested record (TAG 0x06)
-        assert_eq!(result[2], 0x06); // NestedRecord tag
+        // Elements are record bodies without a tag of their own
+        assert_eq!(
+            result,
+            vec![
+                0x07, 0x06, 0x01, // TAG, LENGTH, ELEMENT_COUNT
+                0x01, // FIELD_COUNT
+                0x01, 0x00, 0x01, 0x2A, // F1 Int(42)
+            ]
+        );
     }
 
     #[test]
@@ -516,9 +317,9 @@ mod tests {
 
         let result = encoder.encode_nested_array(&[record1, record2]).unwrap();
 
-        // Should start with TAG (0x07) + ELEMENT_COUNT (2)
+        // Should start with TAG (0x07) + LENGTH + ELEMENT_COUNT (2)
         assert_eq!(result[0], 0x07); // NestedArray tag
-        assert_eq!(result[1], 0x02); // Element count = 2
+        assert_eq!(result[2], 0x02); // Element count = 2
     }
 
     #[test]
@@ -602,13 +403,14 @@ mod tests {
 
         let result = encoder.encode_nested_record(&outer_record).unwrap();
 
-        // Should start with TAG (0x06) + FIELD_COUNT (1)
-        assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x01); // Field count = 1
-                                     // Next should be FID=2
-        assert_eq!(result[2], 0x02); // FID = 2
-                                     // Next should be nested record TAG (0x06)
-        assert_eq!(result[3], 0x06); // Inner NestedRecord tag
+        assert_eq!(
+            result,
+            vec![
+                0x06, 0x0A, 0x01, // TAG, LENGTH, FIELD_COUNT
+                0x02, 0x00, 0x06, 0x05, 0x01, // F2 NestedRecord, LENGTH, FIELD_COUNT
+                0x01, 0x00, 0x01, 0x2A, // F1 Int(42)
+            ]
+        );
     }
 
     #[test]
@@ -851,8 +653,8 @@ mod tests {
 
         // Verify outer level is sorted (FID 1 should come first)
         assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x03); // Field count = 3
-        assert_eq!(result[2], 0x01); // First FID = 1 (sorted)
+        assert_eq!(result[2], 0x03); // Field count = 3
+        assert_eq!(&result[3..5], &[0x01, 0x00]); // First FID = 1 (sorted)
     }
 
     #[test]
@@ -868,11 +670,13 @@ mod tests {
 
         let result = encoder.encode_nested_record(&outer).unwrap();
 
-        assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x01); // Field count = 1
-        assert_eq!(result[2], 0x01); // FID = 1
-        assert_eq!(result[3], 0x06); // Inner NestedRecord tag
-        assert_eq!(result[4], 0x00); // Inner field count = 0
+        assert_eq!(
+            result,
+            vec![
+                0x06, 0x06, 0x01, // TAG, LENGTH, FIELD_COUNT
+                0x01, 0x00, 0x06, 0x01, 0x00, // F1 NestedRecord, LENGTH, FIELD_COUNT = 0
+            ]
+        );
     }
 
     #[test]
@@ -887,11 +691,13 @@ mod tests {
 
         let result = encoder.encode_nested_record(&record).unwrap();
 
-        assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x01); // Field count = 1
-        assert_eq!(result[2], 0x01); // FID = 1
-        assert_eq!(result[3], 0x07); // NestedArray tag
-        assert_eq!(result[4], 0x00); // Element count = 0
+        assert_eq!(
+            result,
+            vec![
+                0x06, 0x06, 0x01, // TAG, LENGTH, FIELD_COUNT
+                0x01, 0x00, 0x07, 0x01, 0x00, // F1 NestedArray, LENGTH, ELEMENT_COUNT = 0
+            ]
+        );
     }
 
     #[test]
@@ -923,12 +729,11 @@ mod tests {
 
         // Verify array structure
         assert_eq!(result[0], 0x07); // NestedArray tag
-        assert_eq!(result[1], 0x02); // Element count = 2
+        assert_eq!(result[2], 0x02); // Element count = 2
 
         // First record should have sorted fields
-        assert_eq!(result[2], 0x06); // First record tag
         assert_eq!(result[3], 0x02); // First record field count = 2
-        assert_eq!(result[4], 0x05); // First FID = 5 (sorted)
+        assert_eq!(&result[4..6], &[0x05, 0x00]); // First FID = 5 (sorted)
     }
 
     #[test]
@@ -958,7 +763,7 @@ mod tests {
         let result = encoder.encode_nested_record(&outer).unwrap();
 
         assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x03); // Field count = 3
+        assert_eq!(result[2], 0x03); // Field count = 3
     }
 
     #[test]
@@ -990,6 +795,6 @@ mod tests {
         let result = encoder.encode_nested_record(&record).unwrap();
 
         assert_eq!(result[0], 0x06); // NestedRecord tag
-        assert_eq!(result[1], 0x05); // Field count = 5
+        assert_eq!(result[2], 0x05); // Field count = 5
     }
 }
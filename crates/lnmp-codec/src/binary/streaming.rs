@@ -2,8 +2,25 @@
 //!
 //! This module provides chunked transmission support for large LNMP payloads,
 //! enabling streaming with backpressure control and integrity validation.
+//!
+//! Chunk payloads carry an XOR checksum. For protection against transport
+//! bit-flips in any part of a frame, enable [`StreamingConfig::with_crc32c`]:
+//! every frame then ends with a CRC32C trailer over its header and payload, and
+//! the decoder rejects mismatching frames with [`StreamingError::CorruptFrame`].
+//!
+//! Many small records can be sent as BATCH frames instead of one frame each:
+//! a [`RecordBatcher`] collects records until a [`BatchConfig`] threshold is
+//! reached and emits them in one frame sharing a binary header and string
+//! table, and the decoder unpacks them into [`StreamingEvent::BatchReceived`].
 
+use super::entry::DEFAULT_MAX_DEPTH;
 use super::error::BinaryError;
+use super::frame::BinaryFrame;
+use crc::{Crc, CRC_32_ISCSI};
+use lnmp_core::LnmpRecord;
+
+/// CRC32C (Castagnoli) used for frame trailers
+pub(crate) const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
 
 /// Configuration for streaming operations
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +31,8 @@ pub struct StreamingConfig {
     pub enable_compression: bool,
     /// Enable checksum validation for chunks
     pub enable_checksums: bool,
+    /// Append a CRC32C trailer to every frame and require it when decoding
+    pub enable_crc32c: bool,
 }
 
 impl StreamingConfig {
@@ -23,6 +42,7 @@ impl StreamingConfig {
             chunk_size: 4096, // 4KB default
             enable_compression: false,
             enable_checksums: true,
+            enable_crc32c: false,
         }
     }
 
@@ -43,6 +63,15 @@ impl StreamingConfig {
         self.enable_checksums = enabled;
         self
     }
+
+    /// Enables or disables per-frame CRC32C trailers
+    ///
+    /// Both ends must agree: a decoder with trailers enabled rejects frames
+    /// without one.
+    pub fn with_crc32c(mut self, enabled: bool) -> Self {
+        self.enable_crc32c = enabled;
+        self
+    }
 }
 
 impl Default for StreamingConfig {
@@ -168,6 +197,24 @@ impl StreamingEncoder {
         self.encode_frame(&frame)
     }
 
+    /// Emits a BATCH frame carrying `records`
+    ///
+    /// Batches are self-contained and may be sent outside of a BEGIN/END
+    /// stream. Records share one binary header and, when `string_table` is
+    /// set, one string table for their repeated strings.
+    pub fn batch_frame(
+        &self,
+        records: &[LnmpRecord],
+        string_table: bool,
+    ) -> Result<Vec<u8>, StreamingError> {
+        let frames = records
+            .iter()
+            .map(BinaryFrame::from_record)
+            .collect::<Result<Vec<_>, _>>()?;
+        let frame = StreamingFrame::batch(BinaryFrame::encode_batch(&frames, string_table));
+        self.encode_frame(&frame)
+    }
+
     /// Returns the current streaming state
     pub fn state(&self) -> &StreamingState {
         &self.state
@@ -181,13 +228,15 @@ impl StreamingEncoder {
         bytes.push(frame.frame_type.to_u8());
 
         // FLAGS (1 byte)
-        bytes.push(frame.flags.to_u8());
+        let mut flags = frame.flags;
+        flags.has_crc32c = self.config.enable_crc32c;
+        bytes.push(flags.to_u8());
 
         // CHUNK_SIZE (VarInt)
         bytes.extend(super::varint::encode(frame.chunk_size as i64));
 
-        // CHECKSUM (4 bytes) - only for CHUNK frames with checksums enabled
-        if frame.frame_type == FrameType::Chunk && self.config.enable_checksums {
+        // CHECKSUM (4 bytes) - only for CHUNK and BATCH frames with checksums enabled
+        if frame.frame_type.has_checksum() && self.config.enable_checksums {
             bytes.extend(&frame.checksum.to_le_bytes());
         } else {
             bytes.extend(&[0u8; 4]);
@@ -196,6 +245,12 @@ impl StreamingEncoder {
         // PAYLOAD (variable)
         bytes.extend(&frame.payload);
 
+        // CRC32C (4 bytes) - over everything above
+        if self.config.enable_crc32c {
+            let crc = StreamingFrame::compute_crc32c(&bytes);
+            bytes.extend(&crc.to_le_bytes());
+        }
+
         Ok(bytes)
     }
 }
@@ -226,6 +281,11 @@ pub enum StreamingEvent {
         /// Error message
         message: String,
     },
+    /// Records unpacked from a BATCH frame
+    BatchReceived {
+        /// Records in the order they were batched
+        records: Vec<LnmpRecord>,
+    },
 }
 
 /// Streaming decoder for receiving chunked transmissions
@@ -322,6 +382,16 @@ impl StreamingDecoder {
                 self.state = StreamingState::Error(message.clone());
                 Ok(StreamingEvent::StreamError { message })
             }
+            FrameType::Batch => {
+                if self.config.enable_checksums {
+                    frame.validate_checksum()?;
+                }
+                let records = BinaryFrame::decode_batch(&frame.payload, DEFAULT_MAX_DEPTH)?
+                    .iter()
+                    .map(BinaryFrame::to_record)
+                    .collect();
+                Ok(StreamingEvent::BatchReceived { records })
+            }
         }
     }
 
@@ -381,6 +451,31 @@ impl StreamingDecoder {
             }));
         }
         let payload = bytes[pos..pos + chunk_size].to_vec();
+        pos += chunk_size;
+
+        // CRC32C (4 bytes)
+        if flags.has_crc32c {
+            if pos + 4 > bytes.len() {
+                return Err(StreamingError::CorruptFrame {
+                    reason: "truncated CRC32C trailer".to_string(),
+                });
+            }
+            let expected =
+                u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]);
+            let computed = StreamingFrame::compute_crc32c(&bytes[..pos]);
+            if computed != expected {
+                return Err(StreamingError::CorruptFrame {
+                    reason: format!(
+                        "CRC32C mismatch: expected 0x{:08X}, computed 0x{:08X}",
+                        expected, computed
+                    ),
+                });
+            }
+        } else if self.config.enable_crc32c {
+            return Err(StreamingError::CorruptFrame {
+                reason: "missing CRC32C trailer".to_string(),
+            });
+        }
 
         Ok(StreamingFrame {
             frame_type,
@@ -466,6 +561,134 @@ impl Default for BackpressureController {
     }
 }
 
+/// Thresholds at which a [`RecordBatcher`] emits a BATCH frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchConfig {
+    /// Maximum number of records per batch (default: 256)
+    pub max_records: usize,
+    /// Encoded size of pending records that triggers a batch (default: 64 KiB)
+    pub max_bytes: usize,
+    /// Share a string table between the records of a batch (default: true)
+    pub string_table: bool,
+}
+
+impl BatchConfig {
+    /// Creates a new BatchConfig with default values
+    pub fn new() -> Self {
+        Self {
+            max_records: 256,
+            max_bytes: 64 * 1024,
+            string_table: true,
+        }
+    }
+
+    /// Sets the maximum number of records per batch
+    pub fn with_max_records(mut self, max: usize) -> Self {
+        self.max_records = max;
+        self
+    }
+
+    /// Sets the encoded size that triggers a batch
+    pub fn with_max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = max;
+        self
+    }
+
+    /// Enables or disables the shared string table
+    pub fn with_string_table(mut self, enabled: bool) -> Self {
+        self.string_table = enabled;
+        self
+    }
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collects records and emits them as BATCH frames
+///
+/// A batch is emitted as soon as it holds `max_records` records or their
+/// encoded size (measured without the string table) reaches `max_bytes`.
+/// Call [`RecordBatcher::flush`] to emit a partial batch, e.g. on a timer or
+/// at shutdown.
+pub struct RecordBatcher {
+    encoder: StreamingEncoder,
+    config: BatchConfig,
+    pending: Vec<BinaryFrame>,
+    pending_bytes: usize,
+}
+
+impl RecordBatcher {
+    /// Creates a batcher with default streaming and batch configuration
+    pub fn new() -> Self {
+        Self::with_config(StreamingConfig::new(), BatchConfig::new())
+    }
+
+    /// Creates a batcher with custom configuration
+    ///
+    /// `streaming` controls the frame checksums and CRC32C trailer.
+    pub fn with_config(streaming: StreamingConfig, config: BatchConfig) -> Self {
+        Self {
+            encoder: StreamingEncoder::with_config(streaming),
+            config,
+            pending: Vec::new(),
+            pending_bytes: 0,
+        }
+    }
+
+    /// Adds a record, returning a BATCH frame if a threshold was reached
+    ///
+    /// Records without a binary representation are rejected and not added.
+    pub fn push(&mut self, record: &LnmpRecord) -> Result<Option<Vec<u8>>, StreamingError> {
+        let frame = BinaryFrame::from_record(record)?;
+        // The VERSION and FLAGS bytes are shared by the whole batch
+        self.pending_bytes += frame.encode().len() - 2;
+        self.pending.push(frame);
+        if self.pending.len() >= self.config.max_records
+            || self.pending_bytes >= self.config.max_bytes
+        {
+            return self.flush();
+        }
+        Ok(None)
+    }
+
+    /// Emits the pending records as a BATCH frame, if there are any
+    pub fn flush(&mut self) -> Result<Option<Vec<u8>>, StreamingError> {
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+        let body = BinaryFrame::encode_batch(&self.pending, self.config.string_table);
+        self.pending.clear();
+        self.pending_bytes = 0;
+        self.encoder
+            .encode_frame(&StreamingFrame::batch(body))
+            .map(Some)
+    }
+
+    /// Number of records waiting to be batched
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns true if no records are waiting
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Encoded size of the waiting records
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+}
+
+impl Default for RecordBatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Frame type identifiers for streaming protocol
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -478,6 +701,8 @@ pub enum FrameType {
     End = 0xA2,
     /// Error frame - signals error condition
     Error = 0xA3,
+    /// Batch frame - carries several complete records
+    Batch = 0xA4,
 }
 
 impl FrameType {
@@ -488,6 +713,7 @@ impl FrameType {
             0xA1 => Ok(FrameType::Chunk),
             0xA2 => Ok(FrameType::End),
             0xA3 => Ok(FrameType::Error),
+            0xA4 => Ok(FrameType::Batch),
             _ => Err(StreamingError::InvalidFrameType { found: byte }),
         }
     }
@@ -496,6 +722,11 @@ impl FrameType {
     pub fn to_u8(self) -> u8 {
         self as u8
     }
+
+    /// Whether frames of this type carry a payload checksum
+    pub fn has_checksum(self) -> bool {
+        matches!(self, FrameType::Chunk | FrameType::Batch)
+    }
 }
 
 /// Flags byte layout for streaming frames
@@ -505,7 +736,9 @@ pub struct FrameFlags {
     pub has_more: bool,
     /// Bit 1: COMPRESSED - indicates payload is compressed
     pub compressed: bool,
-    // Bits 2-7: Reserved for future use
+    /// Bit 2: CRC32C - indicates a CRC32C trailer follows the payload
+    pub has_crc32c: bool,
+    // Bits 3-7: Reserved for future use
 }
 
 impl FrameFlags {
@@ -514,6 +747,7 @@ impl FrameFlags {
         Self {
             has_more: false,
             compressed: false,
+            has_crc32c: false,
         }
     }
 
@@ -522,6 +756,7 @@ impl FrameFlags {
         Self {
             has_more: (byte & 0x01) != 0,
             compressed: (byte & 0x02) != 0,
+            has_crc32c: (byte & 0x04) != 0,
         }
     }
 
@@ -534,6 +769,9 @@ impl FrameFlags {
         if self.compressed {
             byte |= 0x02;
         }
+        if self.has_crc32c {
+            byte |= 0x04;
+        }
         byte
     }
 }
@@ -553,6 +791,9 @@ impl Default for FrameFlags {
 /// │ (1 byte) │ (1 byte) │  (VarInt)    │ (4 bytes)│  (variable) │
 /// └──────────┴──────────┴──────────────┴──────────┴─────────────┘
 /// ```
+///
+/// When [`FrameFlags::has_crc32c`] is set, a 4-byte little-endian CRC32C of all
+/// preceding frame bytes follows the payload.
 #[derive(Debug, Clone, PartialEq)]
 pub struct StreamingFrame {
     /// Frame type identifier
@@ -595,6 +836,17 @@ impl StreamingFrame {
         }
     }
 
+    /// Creates a new BATCH frame with an encoded batch body
+    pub fn batch(payload: Vec<u8>) -> Self {
+        Self {
+            frame_type: FrameType::Batch,
+            flags: FrameFlags::new(),
+            chunk_size: payload.len(),
+            checksum: Self::compute_xor_checksum(&payload),
+            payload,
+        }
+    }
+
     /// Creates a new END frame
     pub fn end() -> Self {
         Self {
@@ -633,9 +885,14 @@ impl StreamingFrame {
         checksum
     }
 
+    /// Computes the CRC32C (Castagnoli) used for frame trailers
+    pub fn compute_crc32c(data: &[u8]) -> u32 {
+        CRC32C.checksum(data)
+    }
+
     /// Validates the checksum of this frame
     pub fn validate_checksum(&self) -> Result<(), StreamingError> {
-        if self.frame_type == FrameType::Chunk {
+        if self.frame_type.has_checksum() {
             let computed = Self::compute_xor_checksum(&self.payload);
             if computed != self.checksum {
                 return Err(StreamingError::ChecksumMismatch {
@@ -673,6 +930,12 @@ pub enum StreamingError {
         found: FrameType,
     },
 
+    /// Frame failed its CRC32C integrity check or lacks a required trailer
+    CorruptFrame {
+        /// Description of the integrity failure
+        reason: String,
+    },
+
     /// Stream not started (no BEGIN frame received)
     StreamNotStarted,
 
@@ -689,6 +952,14 @@ pub enum StreamingError {
 
     /// Binary encoding/decoding error
     BinaryError(BinaryError),
+
+    /// I/O error while reading frames from a stream
+    Io {
+        /// Kind of the underlying I/O error
+        kind: std::io::ErrorKind,
+        /// Message of the underlying I/O error
+        message: String,
+    },
 }
 
 impl std::fmt::Display for StreamingError {
@@ -711,6 +982,9 @@ impl std::fmt::Display for StreamingError {
                     expected, found
                 )
             }
+            StreamingError::CorruptFrame { reason } => {
+                write!(f, "Corrupt frame: {}", reason)
+            }
             StreamingError::StreamNotStarted => {
                 write!(f, "Stream not started: no BEGIN frame received")
             }
@@ -727,6 +1001,9 @@ impl std::fmt::Display for StreamingError {
             StreamingError::BinaryError(err) => {
                 write!(f, "Binary error: {}", err)
             }
+            StreamingError::Io { message, .. } => {
+                write!(f, "I/O error: {}", message)
+            }
         }
     }
 }
@@ -751,13 +1028,14 @@ mod tests {
         assert_eq!(FrameType::from_u8(0xA1).unwrap(), FrameType::Chunk);
         assert_eq!(FrameType::from_u8(0xA2).unwrap(), FrameType::End);
         assert_eq!(FrameType::from_u8(0xA3).unwrap(), FrameType::Error);
+        assert_eq!(FrameType::from_u8(0xA4).unwrap(), FrameType::Batch);
     }
 
     #[test]
     fn test_frame_type_from_u8_invalid() {
         assert!(FrameType::from_u8(0x00).is_err());
         assert!(FrameType::from_u8(0xFF).is_err());
-        assert!(FrameType::from_u8(0xA4).is_err());
+        assert!(FrameType::from_u8(0xA5).is_err());
     }
 
     #[test]
@@ -775,6 +1053,7 @@ mod tests {
             FrameType::Chunk,
             FrameType::End,
             FrameType::Error,
+            FrameType::Batch,
         ];
 
         for frame_type in types {
@@ -826,13 +1105,21 @@ mod tests {
         assert_eq!(flags.to_u8(), 0x03);
     }
 
+    #[test]
+    fn test_frame_flags_crc32c_bit() {
+        let flags = FrameFlags::from_u8(0x04);
+        assert!(flags.has_crc32c);
+        assert!(!flags.has_more);
+        assert_eq!(flags.to_u8(), 0x04);
+    }
+
     #[test]
     fn test_frame_flags_round_trip() {
         for byte in 0..=0xFF {
             let flags = FrameFlags::from_u8(byte);
             let back = flags.to_u8();
-            // Only bits 0 and 1 are used, so mask the result
-            assert_eq!(back, byte & 0x03);
+            // Only bits 0-2 are used, so mask the result
+            assert_eq!(back, byte & 0x07);
         }
     }
 
@@ -1335,6 +1622,51 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_compute_crc32c_check_value() {
+        assert_eq!(StreamingFrame::compute_crc32c(b"123456789"), 0xE306_9283);
+    }
+
+    #[test]
+    fn test_streaming_decoder_crc32c_detects_bit_flips() {
+        let config = StreamingConfig::new().with_crc32c(true);
+        let mut encoder = StreamingEncoder::with_config(config.clone());
+        let mut decoder = StreamingDecoder::with_config(config);
+
+        let begin = encoder.begin_stream().unwrap();
+        assert_eq!(begin[1] & 0x04, 0x04);
+        decoder.feed_frame(&begin).unwrap();
+
+        let chunk = encoder.write_chunk(&[1, 2, 3, 4, 5]).unwrap();
+        for bit in [0, 8 * 7] {
+            let mut corrupted = chunk.clone();
+            corrupted[bit / 8] ^= 1 << (bit % 8);
+            assert!(matches!(
+                decoder.feed_frame(&corrupted),
+                Err(StreamingError::CorruptFrame { .. } | StreamingError::InvalidFrameType { .. })
+            ));
+        }
+        // Flip inside the payload, which the XOR checksum alone would also catch
+        let mut corrupted = chunk.clone();
+        corrupted[8] ^= 0x10;
+        assert!(matches!(
+            decoder.feed_frame(&corrupted),
+            Err(StreamingError::CorruptFrame { .. })
+        ));
+
+        assert!(decoder.feed_frame(&chunk).is_ok());
+        decoder.feed_frame(&encoder.end_stream().unwrap()).unwrap();
+        assert_eq!(decoder.get_complete_payload(), Some(&[1, 2, 3, 4, 5][..]));
+    }
+
+    #[test]
+    fn test_streaming_decoder_requires_crc32c_when_enabled() {
+        let mut encoder = StreamingEncoder::new();
+        let mut decoder = StreamingDecoder::with_config(StreamingConfig::new().with_crc32c(true));
+        let result = decoder.feed_frame(&encoder.begin_stream().unwrap());
+        assert!(matches!(result, Err(StreamingError::CorruptFrame { .. })));
+    }
+
     #[test]
     fn test_streaming_decoder_invalid_frame_bytes() {
         let mut decoder = StreamingDecoder::new();
@@ -0,0 +1,227 @@
+//! Per-frame string table for repeated string values.
+//!
+//! Records with enum-like fields repeat the same strings many times. When a frame
+//! carries [`FLAG_STRING_TABLE`](super::frame::FLAG_STRING_TABLE), the strings are
+//! written once in a table ahead of the entries and each occurrence is encoded as a
+//! [`TypeTag::StringRef`] index instead of the full UTF-8 bytes.
+//!
+//! Table layout:
+//!
+//! ```text
+//! ┌──────────────┬──────────────────────────────┐
+//! │ STRING_COUNT │ (LENGTH VarInt | UTF-8)...   │
+//! │   (VarInt)   │                              │
+//! └──────────────┴──────────────────────────────┘
+//! ```
+//!
+//! Only scalar string values are interned, including those inside nested records;
+//! string arrays keep their inline encoding.
+
+use super::entry::BinaryEntry;
+use super::error::BinaryError;
+use super::types::{BinaryValue, TypeTag};
+use super::varint;
+use lnmp_core::{LnmpRecord, LnmpValue};
+use std::collections::HashMap;
+
+/// Table of strings shared by the entries of one frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StringTable {
+    strings: Vec<String>,
+    index: HashMap<String, u32>,
+}
+
+impl StringTable {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a table holding the strings that are cheaper to reference than to repeat
+    ///
+    /// Strings are ordered by first occurrence so the table is deterministic.
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a BinaryEntry>) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = Vec::new();
+        for entry in entries {
+            match &entry.value {
+                BinaryValue::String(s) => count_string(s, &mut counts, &mut order),
+                BinaryValue::NestedRecord(record) => count_record(record, &mut counts, &mut order),
+                BinaryValue::NestedArray(records) => {
+                    for record in records {
+                        count_record(record, &mut counts, &mut order);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut table = Self::new();
+        for s in order {
+            let count = counts[s];
+            // Inline: count × (LENGTH + bytes). Table: (LENGTH + bytes) + count × INDEX.
+            let inline_size = varint::encode(s.len() as i64).len() + s.len();
+            let index_size = varint::encode(table.len() as i64).len();
+            if count >= 2 && (count - 1) * inline_size > count * index_size {
+                table.insert(s);
+            }
+        }
+        table
+    }
+
+    /// Adds a string, returning its index
+    pub fn insert(&mut self, s: &str) -> u32 {
+        if let Some(&idx) = self.index.get(s) {
+            return idx;
+        }
+        let idx = self.strings.len() as u32;
+        self.strings.push(s.to_string());
+        self.index.insert(s.to_string(), idx);
+        idx
+    }
+
+    /// Returns the index of `s`, if it is in the table
+    pub fn index_of(&self, s: &str) -> Option<u32> {
+        self.index.get(s).copied()
+    }
+
+    /// Returns the string at `idx`
+    pub fn get(&self, idx: u32) -> Option<&str> {
+        self.strings.get(idx as usize).map(String::as_str)
+    }
+
+    /// Number of strings in the table
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if the table holds no strings
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Appends `STRING_COUNT | (LENGTH | UTF-8)*` to `bytes`
+    pub fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&varint::encode(self.strings.len() as i64));
+        for s in &self.strings {
+            bytes.extend_from_slice(&varint::encode(s.len() as i64));
+            bytes.extend_from_slice(s.as_bytes());
+        }
+    }
+
+    /// Decodes a table, returning it with the number of bytes consumed
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), BinaryError> {
+        let (count, mut offset) = read_len(bytes, "string table count")?;
+        let mut table = Self::new();
+        for _ in 0..count {
+            let (len, consumed) = read_len(&bytes[offset..], "string table entry length")?;
+            offset += consumed;
+            if bytes.len() - offset < len {
+                return Err(BinaryError::UnexpectedEof {
+                    expected: offset + len,
+                    found: bytes.len(),
+                });
+            }
+            let s = std::str::from_utf8(&bytes[offset..offset + len])
+                .map_err(|_| BinaryError::InvalidUtf8 { field_id: 0 })?;
+            offset += len;
+            if table.index_of(s).is_some() {
+                return Err(BinaryError::InvalidValue {
+                    field_id: 0,
+                    type_tag: TypeTag::StringRef.to_u8(),
+                    reason: format!("duplicate string table entry {:?}", s),
+                });
+            }
+            table.insert(s);
+        }
+        Ok((table, offset))
+    }
+}
+
+fn count_string<'a>(s: &'a str, counts: &mut HashMap<&'a str, usize>, order: &mut Vec<&'a str>) {
+    let count = counts.entry(s).or_insert(0);
+    if *count == 0 {
+        order.push(s);
+    }
+    *count += 1;
+}
+
+fn count_record<'a>(
+    record: &'a LnmpRecord,
+    counts: &mut HashMap<&'a str, usize>,
+    order: &mut Vec<&'a str>,
+) {
+    for field in record.fields() {
+        match &field.value {
+            LnmpValue::String(s) => count_string(s, counts, order),
+            LnmpValue::NestedRecord(inner) => count_record(inner, counts, order),
+            LnmpValue::NestedArray(records) => {
+                for inner in records {
+                    count_record(inner, counts, order);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_len(bytes: &[u8], what: &str) -> Result<(usize, usize), BinaryError> {
+    let (len, consumed) = varint::decode(bytes).map_err(|_| BinaryError::InvalidVarInt {
+        reason: format!("Invalid {} VarInt", what),
+    })?;
+    let len = usize::try_from(len).map_err(|_| BinaryError::InvalidVarInt {
+        reason: format!("Negative {}: {}", what, len),
+    })?;
+    Ok((len, consumed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_entry(fid: u16, s: &str) -> BinaryEntry {
+        BinaryEntry {
+            fid,
+            tag: TypeTag::String,
+            value: BinaryValue::String(s.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_from_entries_keeps_repeated_strings_only() {
+        let entries = vec![
+            string_entry(1, "active"),
+            string_entry(2, "unique-value"),
+            string_entry(3, "active"),
+            string_entry(4, "x"),
+            string_entry(5, "x"),
+        ];
+        let table = StringTable::from_entries(&entries);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.index_of("active"), Some(0));
+        assert_eq!(table.index_of("unique-value"), None);
+        // Single-byte strings repeated twice do not save space
+        assert_eq!(table.index_of("x"), None);
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let mut table = StringTable::new();
+        table.insert("eu-west");
+        table.insert("active");
+        let mut bytes = Vec::new();
+        table.encode_into(&mut bytes);
+
+        let (decoded, consumed) = StringTable::decode(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(decoded, table);
+        assert_eq!(decoded.get(1), Some("active"));
+        assert_eq!(decoded.get(2), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_and_duplicate_entries() {
+        assert!(StringTable::decode(&[0x01, 0x05, b'a']).is_err());
+        assert!(StringTable::decode(&[0x02, 0x01, b'a', 0x01, b'a']).is_err());
+    }
+}
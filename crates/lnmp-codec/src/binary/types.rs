@@ -34,8 +34,8 @@ pub enum TypeTag {
     FloatArray = 0x0C,
     /// Boolean array type (v0.6) - TAG + COUNT + BOOL entries
     BoolArray = 0x0D,
-    /// Reserved for future use (v0.5+)
-    Reserved0E = 0x0E,
+    /// String table reference (v0.6) - TAG + INDEX (VarInt) into the frame's string table
+    StringRef = 0x0E,
     /// Reserved for future use (v0.5+)
     Reserved0F = 0x0F,
 }
@@ -57,7 +57,7 @@ impl TypeTag {
             0x0B => Ok(TypeTag::IntArray),
             0x0C => Ok(TypeTag::FloatArray),
             0x0D => Ok(TypeTag::BoolArray),
-            0x0E => Ok(TypeTag::Reserved0E),
+            0x0E => Ok(TypeTag::StringRef),
             0x0F => Ok(TypeTag::Reserved0F),
             _ => Err(BinaryError::InvalidTypeTag { tag: byte }),
         }
@@ -80,14 +80,14 @@ impl TypeTag {
                 | TypeTag::IntArray
                 | TypeTag::FloatArray
                 | TypeTag::BoolArray
-                | TypeTag::Reserved0E
+                | TypeTag::StringRef
                 | TypeTag::Reserved0F
         )
     }
 
     /// Returns true if this is a reserved type tag
     pub fn is_reserved(&self) -> bool {
-        matches!(self, TypeTag::Reserved0E | TypeTag::Reserved0F)
+        matches!(self, TypeTag::Reserved0F)
     }
 }
 
@@ -122,6 +122,28 @@ pub enum BinaryValue {
     HybridNumericArray(HybridArray),
 }
 
+/// Checks that every value inside a nested record has a binary representation
+fn validate_nested_fields(record: &lnmp_core::LnmpRecord) -> Result<(), BinaryError> {
+    for field in record.fields() {
+        match &field.value {
+            LnmpValue::NestedRecord(inner) => validate_nested_fields(inner)?,
+            LnmpValue::NestedArray(records) => {
+                records.iter().try_for_each(validate_nested_fields)?
+            }
+            LnmpValue::EmbeddingDelta(_) => {
+                return Err(BinaryError::InvalidValue {
+                    field_id: field.fid,
+                    type_tag: TypeTag::Embedding.to_u8(),
+                    reason: "EmbeddingDelta cannot be encoded as BinaryValue, use full embedding"
+                        .into(),
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 /// Hybrid numeric array supporting multiple data types and encoding modes
 #[derive(Debug, Clone, PartialEq)]
 pub struct HybridArray {
@@ -421,8 +443,14 @@ impl BinaryValue {
             LnmpValue::IntArray(arr) => Ok(BinaryValue::IntArray(arr.clone())),
             LnmpValue::FloatArray(arr) => Ok(BinaryValue::FloatArray(arr.clone())),
             LnmpValue::BoolArray(arr) => Ok(BinaryValue::BoolArray(arr.clone())),
-            LnmpValue::NestedRecord(rec) => Ok(BinaryValue::NestedRecord(rec.clone())),
-            LnmpValue::NestedArray(arr) => Ok(BinaryValue::NestedArray(arr.clone())),
+            LnmpValue::NestedRecord(rec) => {
+                validate_nested_fields(rec)?;
+                Ok(BinaryValue::NestedRecord(rec.clone()))
+            }
+            LnmpValue::NestedArray(arr) => {
+                arr.iter().try_for_each(validate_nested_fields)?;
+                Ok(BinaryValue::NestedArray(arr.clone()))
+            }
             LnmpValue::Embedding(vec) => Ok(BinaryValue::Embedding(vec.clone())),
             LnmpValue::EmbeddingDelta(_) => Err(BinaryError::InvalidValue {
                 reason: "EmbeddingDelta cannot be encoded as BinaryValue, use full embedding"
@@ -590,7 +618,7 @@ mod tests {
         assert_eq!(TypeTag::from_u8(0x0B).unwrap(), TypeTag::IntArray);
         assert_eq!(TypeTag::from_u8(0x0C).unwrap(), TypeTag::FloatArray);
         assert_eq!(TypeTag::from_u8(0x0D).unwrap(), TypeTag::BoolArray);
-        assert_eq!(TypeTag::from_u8(0x0E).unwrap(), TypeTag::Reserved0E);
+        assert_eq!(TypeTag::from_u8(0x0E).unwrap(), TypeTag::StringRef);
         assert_eq!(TypeTag::from_u8(0x0F).unwrap(), TypeTag::Reserved0F);
     }
 
@@ -619,7 +647,7 @@ mod tests {
             TypeTag::IntArray,
             TypeTag::FloatArray,
             TypeTag::BoolArray,
-            TypeTag::Reserved0E,
+            TypeTag::StringRef,
             TypeTag::Reserved0F,
         ];
 
@@ -648,7 +676,7 @@ mod tests {
         assert!(TypeTag::IntArray.is_v0_5_type());
         assert!(TypeTag::FloatArray.is_v0_5_type());
         assert!(TypeTag::BoolArray.is_v0_5_type());
-        assert!(TypeTag::Reserved0E.is_v0_5_type());
+        assert!(TypeTag::StringRef.is_v0_5_type());
         assert!(TypeTag::Reserved0F.is_v0_5_type());
     }
 
@@ -670,7 +698,7 @@ mod tests {
         assert!(!TypeTag::IntArray.is_reserved());
         assert!(!TypeTag::FloatArray.is_reserved());
         assert!(!TypeTag::BoolArray.is_reserved());
-        assert!(TypeTag::Reserved0E.is_reserved());
+        assert!(!TypeTag::StringRef.is_reserved());
         assert!(TypeTag::Reserved0F.is_reserved());
     }
 
@@ -0,0 +1,200 @@
+//! Vectored writes of encoded frames
+//!
+//! Frames encoded with [`BinaryEncoder::encode_into_bytes`] can be frozen into
+//! [`Bytes`] and written back to back without copying them into one buffer.
+//! On a byte stream each frame is preceded by its length as a big-endian `u32`:
+//!
+//! ```text
+//! ┌────────────┬─────────────┬────────────┬─────────────┬─────
+//! │ LEN (u32)  │   FRAME 1   │ LEN (u32)  │   FRAME 2   │ ...
+//! └────────────┴─────────────┴────────────┴─────────────┴─────
+//! ```
+//!
+//! [`FrameSlices`] builds the [`IoSlice`]s for producers that take them directly,
+//! and [`write_frames`] drives `write_vectored` until everything is written.
+//!
+//! [`BinaryEncoder::encode_into_bytes`]: super::BinaryEncoder::encode_into_bytes
+
+use bytes::Bytes;
+use std::io::{self, IoSlice, Write};
+
+/// Length prefixes and `IoSlice`s for a batch of frames
+#[derive(Debug)]
+pub struct FrameSlices<'a> {
+    frames: &'a [Bytes],
+    prefixes: Vec<[u8; 4]>,
+}
+
+impl<'a> FrameSlices<'a> {
+    /// Computes the length prefix of every frame
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a frame is longer than `u32::MAX` bytes.
+    pub fn new(frames: &'a [Bytes]) -> io::Result<Self> {
+        let prefixes = frames
+            .iter()
+            .map(|frame| {
+                u32::try_from(frame.len())
+                    .map(u32::to_be_bytes)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self { frames, prefixes })
+    }
+
+    /// Prefix and frame slices, alternating, in write order
+    pub fn io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.prefixes
+            .iter()
+            .zip(self.frames)
+            .flat_map(|(prefix, frame)| [IoSlice::new(prefix), IoSlice::new(frame)])
+            .collect()
+    }
+
+    /// Total number of bytes the slices cover
+    pub fn total_len(&self) -> usize {
+        self.frames.iter().map(|frame| 4 + frame.len()).sum()
+    }
+}
+
+/// Writes `frames` to `writer` as length-prefixed frames using vectored writes.
+///
+/// Partial writes are resumed until every byte is written. Returns the number
+/// of bytes written.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` for frames longer than `u32::MAX` bytes, `WriteZero`
+/// if the writer stops accepting data, and any error from the writer.
+pub fn write_frames<W: Write + ?Sized>(writer: &mut W, frames: &[Bytes]) -> io::Result<usize> {
+    let batch = FrameSlices::new(frames)?;
+    let mut slices = batch.io_slices();
+    let mut remaining = &mut slices[..];
+    while !remaining.is_empty() {
+        match writer.write_vectored(remaining) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole frame batch",
+                ))
+            }
+            Ok(written) => IoSlice::advance_slices(&mut remaining, written),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(batch.total_len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::{BinaryDecoder, BinaryEncoder, EncoderConfig};
+    use bytes::BytesMut;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+
+    fn record(value: i64) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(value),
+        });
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("sensor".to_string()),
+        });
+        record
+    }
+
+    /// Accepts at most `limit` bytes per call to exercise partial writes
+    struct Trickle {
+        out: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.limit);
+            self.out.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            let mut written = 0;
+            for buf in bufs {
+                let n = self.write(&buf[..buf.len().min(self.limit - written)])?;
+                written += n;
+                if written == self.limit {
+                    break;
+                }
+            }
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_encode_into_bytes_matches_encode() {
+        let encoder = BinaryEncoder::new();
+        let mut buf = BytesMut::from(&b"head"[..]);
+        let written = encoder.encode_into_bytes(&record(7), &mut buf).unwrap();
+        let expected = encoder.encode(&record(7)).unwrap();
+        assert_eq!(written, expected.len());
+        assert_eq!(&buf[4..], &expected[..]);
+
+        let encoder = BinaryEncoder::with_config(EncoderConfig::new().with_string_table(true));
+        let mut buf = BytesMut::new();
+        encoder.encode_into_bytes(&record(7), &mut buf).unwrap();
+        assert_eq!(&buf[..], &encoder.encode(&record(7)).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_write_frames_survives_partial_writes() {
+        let encoder = BinaryEncoder::new();
+        let mut buf = BytesMut::new();
+        let frames: Vec<Bytes> = (0..3)
+            .map(|i| {
+                encoder.encode_into_bytes(&record(i), &mut buf).unwrap();
+                buf.split().freeze()
+            })
+            .collect();
+
+        let slices = FrameSlices::new(&frames).unwrap();
+        assert_eq!(slices.io_slices().len(), 6);
+
+        let mut writer = Trickle {
+            out: Vec::new(),
+            limit: 5,
+        };
+        let written = write_frames(&mut writer, &frames).unwrap();
+        assert_eq!(written, slices.total_len());
+        assert_eq!(writer.out.len(), written);
+
+        let decoder = BinaryDecoder::new();
+        let mut rest = &writer.out[..];
+        for i in 0..3 {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let decoded = decoder.decode(&rest[4..4 + len]).unwrap();
+            assert!(decoded.canonical_eq(&record(i)));
+            rest = &rest[4 + len..];
+        }
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_write_frames_reports_stalled_writer() {
+        let frames = [Bytes::from_static(&[0x04, 0x00, 0x00])];
+        let mut writer = Trickle {
+            out: Vec::new(),
+            limit: 0,
+        };
+        assert_eq!(
+            write_frames(&mut writer, &frames).unwrap_err().kind(),
+            io::ErrorKind::WriteZero
+        );
+    }
+}
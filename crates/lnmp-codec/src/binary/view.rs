@@ -0,0 +1,521 @@
+//! Zero-copy view over an encoded binary frame.
+//!
+//! [`BinaryRecordView`] borrows the frame bytes (e.g. a network buffer or a memory
+//! mapped file) and reads entries on demand. Looking up a field walks the entries
+//! without decoding or allocating the others, which makes it cheap to inspect a few
+//! fields of a large record. Use [`BinaryRecordView::to_record`] when the whole
+//! record is needed.
+//!
+//! Frames with a string table are supported; compressed frames must be decoded
+//! with [`BinaryDecoder`](super::BinaryDecoder) instead.
+
+use super::entry::DEFAULT_MAX_DEPTH;
+use super::error::BinaryError;
+use super::frame::{BinaryFrame, FLAG_COMPRESSED, FLAG_ENCRYPTED, FLAG_SIGNED, FLAG_STRING_TABLE};
+use super::types::{NumericDType, TypeTag};
+use super::varint;
+use lnmp_core::{FieldId, LnmpRecord};
+
+/// Protocol versions accepted by the view
+const VERSIONS: [u8; 2] = [0x04, 0x05];
+
+/// Borrowed view over the entries of an encoded frame
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryRecordView<'a> {
+    frame: &'a [u8],
+    table: Option<StringTableView<'a>>,
+    entries: &'a [u8],
+    entry_count: usize,
+}
+
+impl<'a> BinaryRecordView<'a> {
+    /// Creates a view over an encoded frame
+    ///
+    /// Only the header (and string table, if any) is read; entries are read when
+    /// accessed.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors for:
+    /// - `UnexpectedEof`: Truncated header
+    /// - `UnsupportedVersion`: Version byte is not 0x04 or 0x05
+    /// - `UnsupportedFeature`: Compressed, encrypted or signed frame
+    ///
+    /// A signed frame is not verified here; open it with
+    /// [`BinaryFrame::open_signed`] and view the returned inner frame instead.
+    pub fn new(frame: &'a [u8]) -> Result<Self, BinaryError> {
+        if frame.len() < 2 {
+            return Err(BinaryError::UnexpectedEof {
+                expected: 2,
+                found: frame.len(),
+            });
+        }
+        if !VERSIONS.contains(&frame[0]) {
+            return Err(BinaryError::UnsupportedVersion {
+                found: frame[0],
+                supported: VERSIONS.to_vec(),
+            });
+        }
+        let flags = frame[1];
+        if flags & FLAG_COMPRESSED != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: "zero-copy view of a compressed frame".to_string(),
+            });
+        }
+        if flags & FLAG_ENCRYPTED != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: "zero-copy view of an encrypted frame".to_string(),
+            });
+        }
+        if flags & FLAG_SIGNED != 0 {
+            return Err(BinaryError::UnsupportedFeature {
+                feature: "zero-copy view of a signed frame (open it with BinaryFrame::open_signed)"
+                    .to_string(),
+            });
+        }
+
+        let mut offset = 2;
+        let table = if flags & FLAG_STRING_TABLE != 0 {
+            let (table, consumed) = StringTableView::scan(&frame[offset..])?;
+            offset += consumed;
+            Some(table)
+        } else {
+            None
+        };
+
+        let (entry_count, consumed) = read_len(&frame[offset..], 0, 0, "entry count")?;
+        offset += consumed;
+
+        Ok(Self {
+            frame,
+            table,
+            entries: &frame[offset..],
+            entry_count,
+        })
+    }
+
+    /// Number of entries in the frame
+    pub fn len(&self) -> usize {
+        self.entry_count
+    }
+
+    /// Returns true if the frame has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entry_count == 0
+    }
+
+    /// Iterates over the entries in encoded order
+    pub fn entries(&self) -> Entries<'a> {
+        Entries {
+            bytes: self.entries,
+            table: self.table,
+            remaining: self.entry_count,
+        }
+    }
+
+    /// Returns the first entry with `fid`, if any
+    pub fn get(&self, fid: FieldId) -> Result<Option<EntryView<'a>>, BinaryError> {
+        for entry in self.entries() {
+            let entry = entry?;
+            if entry.fid == fid {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns an integer field; `None` if absent or not an integer
+    pub fn get_int(&self, fid: FieldId) -> Result<Option<i64>, BinaryError> {
+        self.get(fid)?.map_or(Ok(None), |entry| entry.as_int())
+    }
+
+    /// Returns a float field; `None` if absent or not a float
+    pub fn get_float(&self, fid: FieldId) -> Result<Option<f64>, BinaryError> {
+        Ok(self.get(fid)?.and_then(|entry| entry.as_float()))
+    }
+
+    /// Returns a boolean field; `None` if absent or not a boolean
+    pub fn get_bool(&self, fid: FieldId) -> Result<Option<bool>, BinaryError> {
+        self.get(fid)?.map_or(Ok(None), |entry| entry.as_bool())
+    }
+
+    /// Returns a string field borrowed from the frame; `None` if absent or not a string
+    pub fn get_str(&self, fid: FieldId) -> Result<Option<&'a str>, BinaryError> {
+        self.get(fid)?.map_or(Ok(None), |entry| entry.as_str())
+    }
+
+    /// Decodes the whole frame into an owned record
+    pub fn to_record(&self) -> Result<LnmpRecord, BinaryError> {
+        BinaryFrame::decode_with_options(self.frame, false, DEFAULT_MAX_DEPTH)
+            .map(|frame| frame.to_record())
+    }
+}
+
+/// Iterator over the entries of a [`BinaryRecordView`]
+///
+/// Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Entries<'a> {
+    bytes: &'a [u8],
+    table: Option<StringTableView<'a>>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<EntryView<'a>, BinaryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match EntryView::read(self.bytes, self.table) {
+            Ok((entry, consumed)) => {
+                self.bytes = &self.bytes[consumed..];
+                Some(Ok(entry))
+            }
+            Err(err) => {
+                self.remaining = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Borrowed view of a single entry
+#[derive(Debug, Clone, Copy)]
+pub struct EntryView<'a> {
+    /// Field identifier
+    pub fid: FieldId,
+    /// Type tag as encoded (string references keep [`TypeTag::StringRef`])
+    pub tag: TypeTag,
+    value: &'a [u8],
+    table: Option<StringTableView<'a>>,
+}
+
+impl<'a> EntryView<'a> {
+    /// Raw encoded value bytes (everything after the type tag)
+    pub fn raw_value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns the value if the entry is an integer
+    pub fn as_int(&self) -> Result<Option<i64>, BinaryError> {
+        if self.tag != TypeTag::Int {
+            return Ok(None);
+        }
+        Ok(Some(varint::decode(self.value)?.0))
+    }
+
+    /// Returns the value if the entry is a float
+    pub fn as_float(&self) -> Option<f64> {
+        (self.tag == TypeTag::Float)
+            .then(|| f64::from_le_bytes(self.value.try_into().expect("float value is 8 bytes")))
+    }
+
+    /// Returns the value if the entry is a boolean
+    pub fn as_bool(&self) -> Result<Option<bool>, BinaryError> {
+        if self.tag != TypeTag::Bool {
+            return Ok(None);
+        }
+        match self.value[0] {
+            0x00 => Ok(Some(false)),
+            0x01 => Ok(Some(true)),
+            other => Err(self.invalid(format!(
+                "Invalid boolean value: 0x{:02X} (expected 0x00 or 0x01)",
+                other
+            ))),
+        }
+    }
+
+    /// Returns the string borrowed from the frame if the entry is a string
+    ///
+    /// String table references are resolved.
+    pub fn as_str(&self) -> Result<Option<&'a str>, BinaryError> {
+        let bytes = match self.tag {
+            TypeTag::String => {
+                let (len, consumed) =
+                    read_len(self.value, self.fid, self.tag.to_u8(), "string length")?;
+                &self.value[consumed..consumed + len]
+            }
+            TypeTag::StringRef => {
+                let (idx, _) = varint::decode(self.value)?;
+                let table = self.table.ok_or_else(|| {
+                    self.invalid("String reference in a frame without a string table".to_string())
+                })?;
+                usize::try_from(idx)
+                    .ok()
+                    .and_then(|idx| table.get(idx))
+                    .ok_or_else(|| {
+                        self.invalid(format!(
+                            "String reference {} out of range (table has {} entries)",
+                            idx, table.count
+                        ))
+                    })?
+            }
+            _ => return Ok(None),
+        };
+        std::str::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| BinaryError::InvalidUtf8 { field_id: self.fid })
+    }
+
+    /// Reads the entry at the start of `bytes`, returning it with its encoded size
+    fn read(
+        bytes: &'a [u8],
+        table: Option<StringTableView<'a>>,
+    ) -> Result<(Self, usize), BinaryError> {
+        if bytes.len() < 3 {
+            return Err(BinaryError::UnexpectedEof {
+                expected: 3,
+                found: bytes.len(),
+            });
+        }
+        let fid = u16::from_le_bytes([bytes[0], bytes[1]]);
+        let tag = TypeTag::from_u8(bytes[2])?;
+        let len = value_len(&bytes[3..], fid, tag)?;
+        if bytes.len() - 3 < len {
+            return Err(BinaryError::UnexpectedEof {
+                expected: 3 + len,
+                found: bytes.len(),
+            });
+        }
+        let entry = Self {
+            fid,
+            tag,
+            value: &bytes[3..3 + len],
+            table,
+        };
+        Ok((entry, 3 + len))
+    }
+
+    fn invalid(&self, reason: String) -> BinaryError {
+        BinaryError::InvalidValue {
+            field_id: self.fid,
+            type_tag: self.tag.to_u8(),
+            reason,
+        }
+    }
+}
+
+/// String table of a frame, read lazily from the frame bytes
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StringTableView<'a> {
+    strings: &'a [u8],
+    pub(crate) count: usize,
+}
+
+impl<'a> StringTableView<'a> {
+    /// Finds the end of the table, returning it with the bytes consumed
+    pub(crate) fn scan(bytes: &'a [u8]) -> Result<(Self, usize), BinaryError> {
+        let (count, start) = read_len(bytes, 0, 0, "string table count")?;
+        let mut offset = start;
+        for _ in 0..count {
+            let (len, consumed) = read_len(&bytes[offset..], 0, 0, "string table entry length")?;
+            offset += consumed + len;
+            if offset > bytes.len() {
+                return Err(BinaryError::UnexpectedEof {
+                    expected: offset,
+                    found: bytes.len(),
+                });
+            }
+        }
+        let table = Self {
+            strings: &bytes[start..offset],
+            count,
+        };
+        Ok((table, offset))
+    }
+
+    /// Returns the bytes of the string at `idx`
+    pub(crate) fn get(&self, idx: usize) -> Option<&'a [u8]> {
+        if idx >= self.count {
+            return None;
+        }
+        let mut rest = self.strings;
+        for _ in 0..idx {
+            let (len, consumed) = read_len(rest, 0, 0, "").ok()?;
+            rest = &rest[consumed + len..];
+        }
+        let (len, consumed) = read_len(rest, 0, 0, "").ok()?;
+        Some(&rest[consumed..consumed + len])
+    }
+}
+
+/// Computes the encoded size of a value without decoding it
+pub(crate) fn value_len(bytes: &[u8], fid: FieldId, tag: TypeTag) -> Result<usize, BinaryError> {
+    let type_tag = tag.to_u8();
+    let len = match tag {
+        TypeTag::Int | TypeTag::StringRef => varint::decode(bytes)?.1,
+        TypeTag::Float => 8,
+        TypeTag::Bool => 1,
+        TypeTag::String | TypeTag::Embedding | TypeTag::NestedRecord | TypeTag::NestedArray => {
+            let (len, consumed) = read_len(bytes, fid, type_tag, "value length")?;
+            consumed + len
+        }
+        TypeTag::StringArray => {
+            let (count, mut offset) = read_len(bytes, fid, type_tag, "array count")?;
+            for _ in 0..count {
+                let (len, consumed) = read_len(
+                    bytes.get(offset..).unwrap_or(&[]),
+                    fid,
+                    type_tag,
+                    "string length",
+                )?;
+                offset += consumed + len;
+            }
+            offset
+        }
+        TypeTag::IntArray => {
+            let (count, mut offset) = read_len(bytes, fid, type_tag, "array count")?;
+            for _ in 0..count {
+                offset += varint::decode(bytes.get(offset..).unwrap_or(&[]))?.1;
+            }
+            offset
+        }
+        TypeTag::FloatArray => {
+            let (count, consumed) = read_len(bytes, fid, type_tag, "array count")?;
+            consumed + count.saturating_mul(8)
+        }
+        TypeTag::BoolArray => {
+            let (count, consumed) = read_len(bytes, fid, type_tag, "array count")?;
+            consumed + count
+        }
+        TypeTag::HybridNumericArray => {
+            let flags = *bytes.first().ok_or(BinaryError::UnexpectedEof {
+                expected: 1,
+                found: 0,
+            })?;
+            if flags & 0x04 != 0 {
+                return Err(BinaryError::InvalidValue {
+                    field_id: fid,
+                    type_tag,
+                    reason: "Sparse HybridNumericArray decoding not yet implemented".to_string(),
+                });
+            }
+            let (dim, consumed) = read_len(&bytes[1..], fid, type_tag, "array dimension")?;
+            let byte_size = NumericDType::from_flags(flags).byte_size();
+            1 + consumed + dim.saturating_mul(byte_size)
+        }
+        TypeTag::QuantizedEmbedding | TypeTag::Reserved0F => {
+            return Err(BinaryError::InvalidValue {
+                field_id: fid,
+                type_tag,
+                reason: format!(
+                    "Type tag 0x{:02X} not yet implemented in entry decoder",
+                    type_tag
+                ),
+            })
+        }
+    };
+    if len > bytes.len() {
+        return Err(BinaryError::UnexpectedEof {
+            expected: len,
+            found: bytes.len(),
+        });
+    }
+    Ok(len)
+}
+
+/// Reads a non-negative VarInt length or count
+fn read_len(
+    bytes: &[u8],
+    fid: FieldId,
+    type_tag: u8,
+    what: &str,
+) -> Result<(usize, usize), BinaryError> {
+    let (len, consumed) = varint::decode(bytes)?;
+    let len = usize::try_from(len).map_err(|_| BinaryError::InvalidValue {
+        field_id: fid,
+        type_tag,
+        reason: format!("Negative {}: {}", what, len),
+    })?;
+    Ok((len, consumed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::{BinaryEncoder, EncoderConfig};
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    fn sample_record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        for (fid, value) in [
+            (1, LnmpValue::Int(-42)),
+            (2, LnmpValue::Float(2.5)),
+            (3, LnmpValue::Bool(true)),
+            (4, LnmpValue::String("pending-review".to_string())),
+            (
+                5,
+                LnmpValue::StringArray(vec!["a".to_string(), "bc".to_string()]),
+            ),
+            (6, LnmpValue::IntArray(vec![1, -300, 70000])),
+            (7, LnmpValue::String("pending-review".to_string())),
+            (8, LnmpValue::String("pending-review".to_string())),
+        ] {
+            record.add_field(LnmpField { fid, value });
+        }
+        record
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+        let view = BinaryRecordView::new(&bytes).unwrap();
+
+        assert_eq!(view.len(), 8);
+        assert_eq!(view.get_int(1).unwrap(), Some(-42));
+        assert_eq!(view.get_float(2).unwrap(), Some(2.5));
+        assert_eq!(view.get_bool(3).unwrap(), Some(true));
+        assert_eq!(view.get_str(8).unwrap(), Some("pending-review"));
+        // Wrong type or missing field
+        assert_eq!(view.get_int(4).unwrap(), None);
+        assert_eq!(view.get_str(99).unwrap(), None);
+    }
+
+    #[test]
+    fn test_strings_borrow_from_frame() {
+        let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+        let view = BinaryRecordView::new(&bytes).unwrap();
+        let s = view.get_str(4).unwrap().unwrap();
+        let range = bytes.as_ptr_range();
+        assert!(range.contains(&s.as_ptr()));
+    }
+
+    #[test]
+    fn test_string_table_references_resolve() {
+        let config = EncoderConfig::new().with_string_table(true);
+        let bytes = BinaryEncoder::with_config(config)
+            .encode(&sample_record())
+            .unwrap();
+        assert_ne!(bytes[1] & FLAG_STRING_TABLE, 0);
+
+        let view = BinaryRecordView::new(&bytes).unwrap();
+        assert_eq!(view.get(7).unwrap().unwrap().tag, TypeTag::StringRef);
+        assert_eq!(view.get_str(7).unwrap(), Some("pending-review"));
+        assert_eq!(view.to_record().unwrap(), sample_record());
+    }
+
+    #[test]
+    fn test_to_record_matches_decoder() {
+        let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+        let view = BinaryRecordView::new(&bytes).unwrap();
+        assert_eq!(view.to_record().unwrap(), sample_record());
+        let fids: Vec<_> = view.entries().map(|e| e.unwrap().fid).collect();
+        assert_eq!(fids, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_truncated_frame_reports_error() {
+        let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+        let truncated = &bytes[..bytes.len() - 3];
+        let view = BinaryRecordView::new(truncated).unwrap();
+        assert_eq!(view.get_int(1).unwrap(), Some(-42));
+        assert!(view.get(8).is_err());
+        assert!(matches!(
+            BinaryRecordView::new(&[0x06, 0x00]),
+            Err(BinaryError::UnsupportedVersion { .. })
+        ));
+    }
+}
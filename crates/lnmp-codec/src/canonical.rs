@@ -0,0 +1,399 @@
+//! Canonical form validation reports.
+//!
+//! Strict parsing stops at the first [`LnmpError::StrictModeViolation`]. This module
+//! instead scans a whole document and collects every deviation from the v0.2 canonical
+//! text form, so a formatter can fix them in one pass and an LLM can be given precise
+//! feedback about what to change.
+//!
+//! Field ordering violations are reported as a minimal edit: only the fields that must
+//! move (those outside the longest already-sorted run) are listed.
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_codec::canonical::{validate_canonical, CanonicalViolationKind};
+//!
+//! let report = validate_canonical("F12=1;F7=0\nF23=[a,b]").unwrap();
+//! assert!(!report.is_canonical());
+//! assert!(report
+//!     .violations()
+//!     .iter()
+//!     .any(|v| matches!(v.kind, CanonicalViolationKind::FieldOrder { fid: 7, .. })));
+//! assert_eq!(report.canonical_text(), "F7=0\nF12=1\nF23=[a,b]");
+//! ```
+
+use std::fmt;
+
+use crate::encoder::Encoder;
+use crate::error::LnmpError;
+use crate::parser::Parser;
+use lnmp_core::FieldId;
+
+/// Category of a canonical form violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalViolationKind {
+    /// A top-level field is out of FID order and must be moved.
+    FieldOrder {
+        /// Field that must be moved
+        fid: FieldId,
+        /// Field it should be placed after (`None` means the start of the record)
+        move_after: Option<FieldId>,
+    },
+    /// A top-level semicolon separator (canonical form uses newlines).
+    Separator,
+    /// Whitespace outside of quoted strings.
+    Whitespace,
+    /// An empty line between fields.
+    BlankLine,
+    /// A comment line.
+    Comment,
+}
+
+/// A single deviation from canonical form, with its position in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalViolation {
+    /// Violation category
+    pub kind: CanonicalViolationKind,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Column number (1-based)
+    pub column: usize,
+}
+
+impl fmt::Display for CanonicalViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            CanonicalViolationKind::FieldOrder {
+                fid,
+                move_after: Some(after),
+            } => write!(
+                f,
+                "line {}, column {}: move F{} after F{} (fields are sorted by FID)",
+                self.line, self.column, fid, after
+            ),
+            CanonicalViolationKind::FieldOrder {
+                fid,
+                move_after: None,
+            } => write!(
+                f,
+                "line {}, column {}: move F{} to the start of the record (fields are sorted by FID)",
+                self.line, self.column, fid
+            ),
+            CanonicalViolationKind::Separator => write!(
+                f,
+                "line {}, column {}: replace ';' with a newline",
+                self.line, self.column
+            ),
+            CanonicalViolationKind::Whitespace => write!(
+                f,
+                "line {}, column {}: remove whitespace outside quoted strings",
+                self.line, self.column
+            ),
+            CanonicalViolationKind::BlankLine => {
+                write!(f, "line {}: remove empty line", self.line)
+            }
+            CanonicalViolationKind::Comment => {
+                write!(f, "line {}: remove comment line", self.line)
+            }
+        }
+    }
+}
+
+/// Result of validating a document against the canonical text form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalReport {
+    violations: Vec<CanonicalViolation>,
+    canonical: String,
+}
+
+impl CanonicalReport {
+    /// Returns true if no violations were found.
+    pub fn is_canonical(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns all violations, ordered by position in the input.
+    pub fn violations(&self) -> &[CanonicalViolation] {
+        &self.violations
+    }
+
+    /// Returns the canonical encoding of the parsed document.
+    pub fn canonical_text(&self) -> &str {
+        &self.canonical
+    }
+
+    /// Renders the violations as a short bullet list suitable for LLM feedback prompts.
+    ///
+    /// Returns an empty string when the document is already canonical.
+    pub fn to_feedback(&self) -> String {
+        self.violations
+            .iter()
+            .map(|v| format!("- {}", v))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Validates `input` against the canonical text form and reports every violation.
+///
+/// The input is parsed in loose mode first; an error is returned only if it cannot be
+/// parsed at all.
+pub fn validate_canonical(input: &str) -> Result<CanonicalReport, LnmpError> {
+    let record = Parser::new(input)?.parse_record()?;
+    let canonical = Encoder::new().encode(&record);
+
+    let scan = scan(input);
+    let mut violations = scan.violations;
+    violations.extend(order_violations(&scan.fields));
+    violations.sort_by_key(|v| (v.line, v.column));
+
+    Ok(CanonicalReport {
+        violations,
+        canonical,
+    })
+}
+
+struct FieldStart {
+    fid: FieldId,
+    line: usize,
+    column: usize,
+}
+
+struct Scan {
+    violations: Vec<CanonicalViolation>,
+    fields: Vec<FieldStart>,
+}
+
+/// Single pass over the input tracking quoting and nesting depth.
+fn scan(input: &str) -> Scan {
+    let mut violations = Vec::new();
+    let mut fields = Vec::new();
+    let lines: Vec<&str> = input.split('\n').collect();
+    let last_line = lines.len();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+
+    for (idx, raw) in lines.iter().enumerate() {
+        let line_no = idx + 1;
+        let line = raw.strip_suffix('\r').unwrap_or(raw);
+
+        if depth == 0 && !in_quotes {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                // A single trailing newline at end of input is tolerated.
+                if !(line_no == last_line && line.is_empty()) {
+                    violations.push(violation(CanonicalViolationKind::BlankLine, line_no, 1));
+                }
+                continue;
+            }
+            if trimmed.starts_with('#') {
+                violations.push(violation(CanonicalViolationKind::Comment, line_no, 1));
+                continue;
+            }
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut field_expected = depth == 0 && !in_quotes;
+        let mut prev_ws = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let ch = chars[i];
+            let column = i + 1;
+            if in_quotes {
+                match ch {
+                    '\\' => i += 1,
+                    '"' => in_quotes = false,
+                    _ => {}
+                }
+                i += 1;
+                continue;
+            }
+
+            let is_ws = ch == ' ' || ch == '\t';
+            if is_ws && !prev_ws {
+                violations.push(violation(
+                    CanonicalViolationKind::Whitespace,
+                    line_no,
+                    column,
+                ));
+            }
+            prev_ws = is_ws;
+            if is_ws {
+                i += 1;
+                continue;
+            }
+
+            match ch {
+                '"' => in_quotes = true,
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                ';' if depth == 0 => {
+                    violations.push(violation(
+                        CanonicalViolationKind::Separator,
+                        line_no,
+                        column,
+                    ));
+                    field_expected = true;
+                    i += 1;
+                    continue;
+                }
+                'F' if depth == 0 && field_expected => {
+                    let digits: String = chars[i + 1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .collect();
+                    if let Ok(fid) = digits.parse::<FieldId>() {
+                        fields.push(FieldStart {
+                            fid,
+                            line: line_no,
+                            column,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            field_expected = false;
+            i += 1;
+        }
+    }
+
+    Scan { violations, fields }
+}
+
+/// Reports the fields that must move so the top-level FIDs become sorted.
+///
+/// Fields on the longest non-decreasing subsequence stay in place; every other field
+/// is reported together with the kept field it should follow.
+fn order_violations(fields: &[FieldStart]) -> Vec<CanonicalViolation> {
+    let n = fields.len();
+    if n < 2 {
+        return Vec::new();
+    }
+
+    // O(n^2) LNDS is fine for record-sized inputs.
+    let mut len = vec![1usize; n];
+    let mut prev = vec![usize::MAX; n];
+    for i in 0..n {
+        for j in 0..i {
+            if fields[j].fid <= fields[i].fid && len[j] + 1 > len[i] {
+                len[i] = len[j] + 1;
+                prev[i] = j;
+            }
+        }
+    }
+
+    let mut keep = vec![false; n];
+    let mut cursor = (0..n).max_by_key(|&i| (len[i], std::cmp::Reverse(i)));
+    while let Some(i) = cursor {
+        keep[i] = true;
+        cursor = (prev[i] != usize::MAX).then_some(prev[i]);
+    }
+
+    let kept: Vec<FieldId> = (0..n).filter(|&i| keep[i]).map(|i| fields[i].fid).collect();
+    (0..n)
+        .filter(|&i| !keep[i])
+        .map(|i| {
+            let fid = fields[i].fid;
+            let move_after = kept.iter().copied().filter(|&k| k <= fid).max();
+            violation(
+                CanonicalViolationKind::FieldOrder { fid, move_after },
+                fields[i].line,
+                fields[i].column,
+            )
+        })
+        .collect()
+}
+
+fn violation(kind: CanonicalViolationKind, line: usize, column: usize) -> CanonicalViolation {
+    CanonicalViolation { kind, line, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(report: &CanonicalReport) -> Vec<CanonicalViolationKind> {
+        report.violations().iter().map(|v| v.kind.clone()).collect()
+    }
+
+    #[test]
+    fn test_canonical_input_has_no_violations() {
+        let report = validate_canonical("F7=1\nF12=14532\nF23=[admin,dev]\n").unwrap();
+        assert!(report.is_canonical());
+        assert_eq!(report.to_feedback(), "");
+    }
+
+    #[test]
+    fn test_reports_separators_and_whitespace() {
+        let report = validate_canonical("F1=1; F2 = 2").unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                CanonicalViolationKind::Separator,
+                CanonicalViolationKind::Whitespace,
+                CanonicalViolationKind::Whitespace,
+                CanonicalViolationKind::Whitespace,
+            ]
+        );
+        assert_eq!(report.violations()[0].column, 5);
+    }
+
+    #[test]
+    fn test_quoted_whitespace_and_nested_separators_are_canonical() {
+        let report = validate_canonical("F1=\"hello world\"\nF2={F1=1;F2=2}").unwrap();
+        assert!(report.is_canonical(), "{:?}", report.violations());
+    }
+
+    #[test]
+    fn test_field_order_reports_minimal_moves() {
+        // Only F3 is misplaced; F1, F5, F7, F9 are already sorted.
+        let report = validate_canonical("F1=a\nF5=b\nF3=c\nF7=d\nF9=e").unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![CanonicalViolationKind::FieldOrder {
+                fid: 3,
+                move_after: Some(1),
+            }]
+        );
+        assert_eq!(report.violations()[0].line, 3);
+    }
+
+    #[test]
+    fn test_field_order_move_to_start() {
+        let report = validate_canonical("F5=a\nF6=b\nF1=c").unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![CanonicalViolationKind::FieldOrder {
+                fid: 1,
+                move_after: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_and_comments() {
+        let report = validate_canonical("# header\nF1=1\n\nF2=2").unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                CanonicalViolationKind::Comment,
+                CanonicalViolationKind::BlankLine
+            ]
+        );
+        assert_eq!(report.canonical_text(), "F1=1\nF2=2");
+    }
+
+    #[test]
+    fn test_feedback_lists_each_violation() {
+        let report = validate_canonical("F2=1;F1=2").unwrap();
+        let feedback = report.to_feedback();
+        assert_eq!(feedback.lines().count(), 2);
+        assert!(feedback.contains("replace ';' with a newline"));
+        assert!(feedback.contains("move F1 to the start of the record"));
+    }
+
+    #[test]
+    fn test_unparseable_input_is_an_error() {
+        assert!(validate_canonical("F1=[unterminated").is_err());
+    }
+}
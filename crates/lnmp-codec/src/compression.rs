@@ -0,0 +1,236 @@
+//! Payload compression for binary frames and `.lnmp` containers.
+//!
+//! Compression is opt-in and applied per frame: payloads smaller than
+//! [`CompressionConfig::threshold`] are written uncompressed, as are payloads that
+//! do not shrink. Decoders detect compressed payloads from the frame or container
+//! flags, so reading is transparent.
+//!
+//! Algorithms are behind the opt-in `zstd` and `lz4` cargo features. Using an
+//! algorithm whose feature is disabled yields [`CompressionError::Unavailable`].
+
+use std::fmt;
+
+/// Largest decompressed size accepted from a compressed payload (64 MiB)
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Compression algorithm identifiers
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// Zstandard (better ratio)
+    Zstd = 0x01,
+    /// LZ4 block format (faster)
+    Lz4 = 0x02,
+}
+
+impl CompressionAlgorithm {
+    /// Returns the on-wire identifier
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Parses an on-wire identifier
+    pub fn from_id(id: u8) -> Result<Self, CompressionError> {
+        match id {
+            0x01 => Ok(CompressionAlgorithm::Zstd),
+            0x02 => Ok(CompressionAlgorithm::Lz4),
+            other => Err(CompressionError::UnknownAlgorithm(other)),
+        }
+    }
+}
+
+/// Configuration for payload compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionConfig {
+    /// Algorithm used to compress payloads
+    pub algorithm: CompressionAlgorithm,
+    /// Minimum payload size in bytes before compression is attempted
+    pub threshold: usize,
+    /// Compression level (zstd only; ignored by LZ4)
+    pub level: i32,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: CompressionAlgorithm::Zstd,
+            threshold: 256,
+            level: 3,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Creates a configuration using `algorithm` with default threshold and level
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the minimum payload size in bytes before compression is attempted
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the zstd compression level
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Compresses `payload` if it reaches the threshold and gets smaller
+    ///
+    /// Returns `None` when the payload should be written uncompressed.
+    pub fn compress_if_worthwhile(
+        &self,
+        payload: &[u8],
+    ) -> Result<Option<Vec<u8>>, CompressionError> {
+        if payload.len() < self.threshold {
+            return Ok(None);
+        }
+        let compressed = compress(self.algorithm, self.level, payload)?;
+        Ok((compressed.len() < payload.len()).then_some(compressed))
+    }
+}
+
+/// Errors raised while compressing or decompressing payloads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The algorithm identifier is not known
+    UnknownAlgorithm(u8),
+    /// The algorithm's cargo feature is not enabled
+    Unavailable(CompressionAlgorithm),
+    /// The declared decompressed size exceeds [`MAX_DECOMPRESSED_LEN`]
+    TooLarge(usize),
+    /// The decompressed size differs from the declared size
+    LengthMismatch {
+        /// Declared size
+        expected: usize,
+        /// Actual decompressed size
+        actual: usize,
+    },
+    /// The compression library reported an error
+    Codec(String),
+}
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::UnknownAlgorithm(id) => {
+                write!(f, "unknown compression algorithm 0x{:02X}", id)
+            }
+            CompressionError::Unavailable(algorithm) => {
+                write!(f, "compression algorithm {:?} is not enabled", algorithm)
+            }
+            CompressionError::TooLarge(len) => write!(
+                f,
+                "decompressed size {} exceeds limit of {} bytes",
+                len, MAX_DECOMPRESSED_LEN
+            ),
+            CompressionError::LengthMismatch { expected, actual } => write!(
+                f,
+                "decompressed {} bytes but {} were declared",
+                actual, expected
+            ),
+            CompressionError::Codec(reason) => write!(f, "compression failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+/// Compresses `data` with `algorithm`
+#[cfg_attr(not(all(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+pub fn compress(
+    algorithm: CompressionAlgorithm,
+    level: i32,
+    data: &[u8],
+) -> Result<Vec<u8>, CompressionError> {
+    match algorithm {
+        #[cfg(feature = "zstd")]
+        CompressionAlgorithm::Zstd => {
+            zstd::bulk::compress(data, level).map_err(|e| CompressionError::Codec(e.to_string()))
+        }
+        #[cfg(feature = "lz4")]
+        CompressionAlgorithm::Lz4 => Ok(lz4_flex::block::compress(data)),
+        #[allow(unreachable_patterns)]
+        other => Err(CompressionError::Unavailable(other)),
+    }
+}
+
+/// Decompresses `data` produced by [`compress`], which must expand to exactly `len` bytes
+#[cfg_attr(not(any(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+pub fn decompress(
+    algorithm: CompressionAlgorithm,
+    data: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    if len > MAX_DECOMPRESSED_LEN {
+        return Err(CompressionError::TooLarge(len));
+    }
+    let decompressed: Vec<u8> = match algorithm {
+        #[cfg(feature = "zstd")]
+        CompressionAlgorithm::Zstd => {
+            zstd::bulk::decompress(data, len).map_err(|e| CompressionError::Codec(e.to_string()))
+        }
+        #[cfg(feature = "lz4")]
+        CompressionAlgorithm::Lz4 => lz4_flex::block::decompress(data, len)
+            .map_err(|e| CompressionError::Codec(e.to_string())),
+        #[allow(unreachable_patterns)]
+        other => Err(CompressionError::Unavailable(other)),
+    }?;
+    if decompressed.len() != len {
+        return Err(CompressionError::LengthMismatch {
+            expected: len,
+            actual: decompressed.len(),
+        });
+    }
+    Ok(decompressed)
+}
+
+#[cfg(all(test, feature = "zstd", feature = "lz4"))]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        b"F12=14532;F7=1;F23=[admin,dev];".repeat(32)
+    }
+
+    #[test]
+    fn test_roundtrip_all_algorithms() {
+        let data = sample();
+        for algorithm in [CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4] {
+            let compressed = compress(algorithm, 3, &data).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(
+                decompress(algorithm, &compressed, data.len()).unwrap(),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_threshold_skips_small_payloads() {
+        let config = CompressionConfig::new(CompressionAlgorithm::Lz4).with_threshold(64);
+        assert_eq!(config.compress_if_worthwhile(b"F1=1").unwrap(), None);
+        assert!(config.compress_if_worthwhile(&sample()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_decompress_rejects_bad_input() {
+        let data = sample();
+        let compressed = compress(CompressionAlgorithm::Zstd, 3, &data).unwrap();
+        assert!(decompress(CompressionAlgorithm::Zstd, &compressed, data.len() - 1).is_err());
+        assert_eq!(
+            decompress(CompressionAlgorithm::Lz4, &[], MAX_DECOMPRESSED_LEN + 1),
+            Err(CompressionError::TooLarge(MAX_DECOMPRESSED_LEN + 1))
+        );
+        assert_eq!(
+            CompressionAlgorithm::from_id(0x7F),
+            Err(CompressionError::UnknownAlgorithm(0x7F))
+        );
+    }
+}
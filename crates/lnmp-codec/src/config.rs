@@ -1,8 +1,10 @@
 //! Configuration types for LNMP parsing and encoding.
 
+use crate::duplicates::DuplicateFieldPolicy;
 use crate::equivalence::EquivalenceMapper;
 use crate::normalizer::NormalizationConfig;
 
+use lnmp_core::coercion::CoercionEngine;
 use lnmp_core::profile::{LnmpProfile, StrictDeterministicConfig};
 use lnmp_core::registry::{FidRegistry, ValidationMode};
 use lnmp_core::StructuralLimits;
@@ -58,12 +60,20 @@ pub struct ParserConfig {
     pub structural_limits: Option<StructuralLimits>,
     /// Optional semantic dictionary for equivalence normalization
     pub semantic_dictionary: Option<lnmp_sfe::SemanticDictionary>,
+    /// Optional shared, hot-reloadable dictionary (takes precedence over `semantic_dictionary`)
+    pub shared_dictionary: Option<lnmp_sfe::SharedDictionary>,
     /// Profile configuration from lnmp-core (v0.5.4)
     pub profile_config: Option<StrictDeterministicConfig>,
     /// Optional FID registry for validation (v0.5.14)
     pub fid_registry: Option<Arc<FidRegistry>>,
     /// Validation mode when registry is present (v0.5.14)
     pub fid_validation_mode: ValidationMode,
+    /// Whether to rewrite locale-formatted numbers (e.g. `1.234,56`) to canonical form
+    pub locale_numbers: bool,
+    /// How repeated field IDs are resolved in loose mode (strict mode always rejects them)
+    pub duplicate_fields: DuplicateFieldPolicy,
+    /// Optional coercion engine applied to field values in loose mode
+    pub coercion: Option<CoercionEngine>,
 }
 
 impl Default for ParserConfig {
@@ -77,9 +87,13 @@ impl Default for ParserConfig {
             text_input_mode: TextInputMode::Strict,
             structural_limits: None,
             semantic_dictionary: None,
+            shared_dictionary: None,
             profile_config: None, // None means use standard defaults
             fid_registry: None,
             fid_validation_mode: ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         }
     }
 }
@@ -97,9 +111,13 @@ impl ParserConfig {
             text_input_mode: TextInputMode::Strict,
             structural_limits: None,
             semantic_dictionary: None,
+            shared_dictionary: None,
             profile_config: Some(config),
             fid_registry: None,
             fid_validation_mode: ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         }
     }
 
@@ -119,12 +137,37 @@ impl ParserConfig {
         self
     }
 
+    /// Enables lenient parsing of locale-formatted numbers (see [`crate::locale`]).
+    pub fn with_locale_numbers(mut self, enabled: bool) -> Self {
+        self.locale_numbers = enabled;
+        self
+    }
+
+    /// Coerces field values towards their target types in loose mode
+    /// (see [`lnmp_core::coercion`]).
+    pub fn with_coercion(mut self, engine: CoercionEngine) -> Self {
+        self.coercion = Some(engine);
+        self
+    }
+
+    /// Sets how repeated field IDs are resolved in loose mode.
+    pub fn with_duplicate_fields(mut self, policy: DuplicateFieldPolicy) -> Self {
+        self.duplicate_fields = policy;
+        self
+    }
+
     /// Attaches a semantic dictionary for equivalence normalization.
     pub fn with_semantic_dictionary(mut self, dict: lnmp_sfe::SemanticDictionary) -> Self {
         self.semantic_dictionary = Some(dict);
         self
     }
 
+    /// Attaches a shared dictionary; parsers see its updates without being rebuilt.
+    pub fn with_shared_dictionary(mut self, dict: lnmp_sfe::SharedDictionary) -> Self {
+        self.shared_dictionary = Some(dict);
+        self
+    }
+
     /// Sets the FID registry for validation (v0.5.14)
     pub fn with_fid_registry(mut self, registry: Arc<FidRegistry>) -> Self {
         self.fid_registry = Some(registry);
@@ -151,6 +194,18 @@ pub struct PromptOptimizationConfig {
 
 // Default implementation derived via #[derive(Default)] on the struct
 
+/// How the text encoder renders `Float` and `FloatArray` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// Rust `Display` formatting (default; `1.0` is rendered as `1`)
+    #[default]
+    Display,
+    /// Exactly this many digits after the decimal point
+    FixedPrecision(usize),
+    /// Shortest digits that parse back to the same `f64` (ryu); always keeps a decimal point
+    ShortestRoundTrip,
+}
+
 /// Encoder configuration
 #[derive(Debug, Clone)]
 pub struct EncoderConfig {
@@ -170,10 +225,23 @@ pub struct EncoderConfig {
     pub equivalence_mapper: Option<EquivalenceMapper>,
     /// Optional semantic dictionary for value normalization
     pub semantic_dictionary: Option<lnmp_sfe::SemanticDictionary>,
+    /// Optional shared, hot-reloadable dictionary (takes precedence over `semantic_dictionary`)
+    pub shared_dictionary: Option<lnmp_sfe::SharedDictionary>,
     /// Optional FID registry for validation (v0.5.14)
     pub fid_registry: Option<Arc<FidRegistry>>,
     /// Validation mode when registry is present (v0.5.14)
     pub fid_validation_mode: ValidationMode,
+    /// Float rendering style
+    pub float_format: FloatFormat,
+    /// Whether to expand exponent notation (`1e-7`) to positional digits; the text
+    /// grammar has no exponents, so disabling this produces output the parser rejects
+    pub suppress_scientific: bool,
+    /// Whether to keep empty strings, arrays and nested structures instead of omitting
+    /// them, so an explicitly cleared field (`F23=[]`) stays distinct from an absent one
+    pub preserve_empty: bool,
+    /// Whether [`Encoder::encode_envelope`](crate::Encoder::encode_envelope) writes the
+    /// `#ENVELOPE` header line; without it only the record is emitted
+    pub emit_envelope_header: bool,
 }
 
 impl Default for EncoderConfig {
@@ -187,8 +255,13 @@ impl Default for EncoderConfig {
             normalization_config: NormalizationConfig::default(),
             equivalence_mapper: None,
             semantic_dictionary: None,
+            shared_dictionary: None,
             fid_registry: None,
             fid_validation_mode: ValidationMode::None,
+            float_format: FloatFormat::Display,
+            suppress_scientific: true,
+            preserve_empty: false,
+            emit_envelope_header: false,
         }
     }
 }
@@ -235,6 +308,12 @@ impl EncoderConfig {
         self
     }
 
+    /// Attaches a shared dictionary; encoders see its updates without being rebuilt.
+    pub fn with_shared_dictionary(mut self, dict: lnmp_sfe::SharedDictionary) -> Self {
+        self.shared_dictionary = Some(dict);
+        self
+    }
+
     /// Enables type hints in output
     pub fn with_type_hints(mut self, enable: bool) -> Self {
         self.include_type_hints = enable;
@@ -247,6 +326,30 @@ impl EncoderConfig {
         self
     }
 
+    /// Sets the float rendering style
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Sets whether exponent notation is expanded to positional digits
+    pub fn with_suppress_scientific(mut self, suppress: bool) -> Self {
+        self.suppress_scientific = suppress;
+        self
+    }
+
+    /// Sets whether empty fields are kept instead of omitted
+    pub fn with_preserve_empty(mut self, preserve: bool) -> Self {
+        self.preserve_empty = preserve;
+        self
+    }
+
+    /// Sets whether envelopes are encoded with their `#ENVELOPE` header
+    pub fn with_envelope_header(mut self, enable: bool) -> Self {
+        self.emit_envelope_header = enable;
+        self
+    }
+
     /// Sets the FID registry for validation (v0.5.14)
     pub fn with_fid_registry(mut self, registry: Arc<FidRegistry>) -> Self {
         self.fid_registry = Some(registry);
@@ -323,6 +426,7 @@ mod tests {
             float_precision: Some(2),
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let config = EncoderConfig::new().with_normalization(norm_config.clone());
         assert_eq!(
@@ -404,9 +508,13 @@ mod tests {
             text_input_mode: TextInputMode::Strict,
             structural_limits: None,
             semantic_dictionary: None,
+            shared_dictionary: None,
             profile_config: None,
             fid_registry: None,
             fid_validation_mode: ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         };
         assert_eq!(config.mode, ParsingMode::Strict);
         assert!(config.validate_checksums);
@@ -424,9 +532,13 @@ mod tests {
             text_input_mode: TextInputMode::Strict,
             structural_limits: None,
             semantic_dictionary: None,
+            shared_dictionary: None,
             profile_config: None,
             fid_registry: None,
             fid_validation_mode: ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         };
         assert!(config.validate_checksums);
         assert!(config.require_checksums);
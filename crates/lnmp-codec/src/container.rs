@@ -1,14 +1,18 @@
 //! Container-aware helpers that bridge `.lnmp` headers with codec entry points.
 
-use std::{fmt, str};
+use std::{borrow::Cow, fmt, str};
 
 use crate::{
     binary::{delta::DeltaApplyContext, BinaryDecoder, BinaryEncoder, BinaryError},
-    Encoder, LnmpError, Parser,
+    compression::{self, CompressionAlgorithm, CompressionConfig, CompressionError},
+    encryption::{self, EncryptionError, KeyProvider, PayloadCipher},
+    signing::{FrameSignature, FrameSigner, SignatureError, SignatureVerifier, SIGNATURE_EXT_LEN},
+    Encoder, EncoderConfig, LnmpError, Parser,
 };
 use lnmp_core::{
-    LnmpContainerError, LnmpContainerHeader, LnmpFileMode, LnmpRecord, LNMP_FLAG_CHECKSUM_REQUIRED,
-    LNMP_FLAG_COMPRESSED, LNMP_FLAG_ENCRYPTED, LNMP_HEADER_SIZE,
+    checksum::SemanticChecksum, FieldId, LnmpContainerError, LnmpContainerHeader, LnmpFileMode,
+    LnmpRecord, LNMP_FLAG_CHECKSUM_REQUIRED, LNMP_FLAG_COMPRESSED, LNMP_FLAG_ENCRYPTED,
+    LNMP_FLAG_QSIG, LNMP_HEADER_SIZE,
 };
 
 /// Borrowed view over a `.lnmp` container.
@@ -17,6 +21,8 @@ pub struct ContainerFrame<'a> {
     header: LnmpContainerHeader,
     metadata: &'a [u8],
     payload: &'a [u8],
+    signed: &'a [u8],
+    signature: Option<FrameSignature>,
 }
 
 /// Helper that builds `.lnmp` containers from parsed records or raw payloads.
@@ -27,8 +33,15 @@ pub struct ContainerBuilder {
     checksum_confirmed: bool,
     stream_meta: Option<StreamMetadata>,
     delta_meta: Option<DeltaMetadata>,
+    compression: Option<CompressionConfig>,
+    encryption: Option<PayloadCipher>,
+    signer: Option<FrameSigner>,
 }
 
+/// Size of the prefix written before compressed container payloads
+/// (`ALGORITHM` byte plus big-endian `u32` decompressed length).
+const COMPRESSED_PREFIX_LEN: usize = 5;
+
 /// Decoded view over stream metadata (mode `0x03`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct StreamMetadata {
@@ -60,6 +73,9 @@ impl ContainerBuilder {
             checksum_confirmed: true,
             stream_meta: None,
             delta_meta: None,
+            compression: None,
+            encryption: None,
+            signer: None,
         }
     }
 
@@ -87,23 +103,65 @@ impl ContainerBuilder {
         self
     }
 
+    /// Compresses the payload when it reaches the configured threshold.
+    ///
+    /// The compressed flag is set on the header only when the payload is actually
+    /// compressed; it cannot be requested through [`with_flags`](Self::with_flags).
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Encrypts the payload (after compression) and sets the encrypted flag.
+    ///
+    /// The header and metadata are authenticated together with the payload, so
+    /// they cannot be altered without failing decryption.
+    pub fn with_encryption(mut self, cipher: PayloadCipher) -> Self {
+        self.encryption = Some(cipher);
+        self
+    }
+
+    /// Signs the container and sets the signature flag (`qsig`).
+    ///
+    /// The signature covers the header, metadata and final payload and is appended
+    /// as a [`FrameSignature`] extension after the payload.
+    pub fn with_signer(mut self, signer: FrameSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
     /// Returns the current header snapshot.
     pub const fn header(&self) -> LnmpContainerHeader {
         self.header
     }
 
     /// Wraps an existing payload slice with the configured header/metadata.
+    ///
+    /// When the checksum flag is set, a text payload must carry a valid checksum on
+    /// every field and stream metadata must declare a checksum type. Other modes
+    /// cannot carry field checksums, so the flag is not enforced for them.
     pub fn wrap_payload(self, payload: &[u8]) -> Result<Vec<u8>, ContainerEncodeError> {
+        if self.checksums_required() {
+            self.validate_checksum_requirements()?;
+            self.validate_payload_checksums(payload)?;
+        }
         self.wrap_payload_internal(payload)
     }
 
     /// Encodes a record according to the selected mode and wraps it in a container.
+    ///
+    /// When the checksum flag is set, text payloads are emitted with a semantic
+    /// checksum on every field.
     pub fn encode_record(self, record: &LnmpRecord) -> Result<Vec<u8>, ContainerEncodeError> {
         self.validate_flags()?;
         self.validate_checksum_requirements()?;
         match self.header.mode {
             LnmpFileMode::Text => {
-                let encoder = Encoder::new();
+                let encoder = if self.checksums_required() {
+                    Encoder::with_config(EncoderConfig::new().with_checksums(true))
+                } else {
+                    Encoder::new()
+                };
                 let text = encoder.encode(record);
                 self.wrap_payload_internal(text.as_bytes())
             }
@@ -147,13 +205,69 @@ impl ContainerBuilder {
         self.validate_flags()?;
         encode_validate_metadata_requirements(self.header.mode, self.metadata.len())?;
         encode_validate_metadata_semantics(self.header.mode, &self.metadata)?;
-        let mut buffer = Vec::with_capacity(LNMP_HEADER_SIZE + self.metadata.len() + payload.len());
+        let compressed = self.compress_payload(payload)?;
+        let payload = compressed.as_deref().unwrap_or(payload);
+        if self.signer.is_some() {
+            // Set before encryption so the flag is part of the authenticated header.
+            self.header.flags |= LNMP_FLAG_QSIG;
+        }
+        let sealed = self.encrypt_payload(payload)?;
+        let payload = sealed.as_deref().unwrap_or(payload);
+        let mut buffer = Vec::with_capacity(
+            LNMP_HEADER_SIZE + self.metadata.len() + payload.len() + SIGNATURE_EXT_LEN,
+        );
         buffer.extend_from_slice(&self.header.encode());
         buffer.extend_from_slice(&self.metadata);
         buffer.extend_from_slice(payload);
+        if let Some(signer) = &self.signer {
+            let signature = signer
+                .sign(&buffer)
+                .map_err(ContainerEncodeError::Signature)?;
+            buffer.extend_from_slice(&signature.encode());
+        }
         Ok(buffer)
     }
 
+    /// Returns `ALGORITHM | RAW_LEN | DATA` and sets the compressed flag when
+    /// compression is configured and worthwhile.
+    fn compress_payload(
+        &mut self,
+        payload: &[u8],
+    ) -> Result<Option<Vec<u8>>, ContainerEncodeError> {
+        let Some(config) = self.compression else {
+            return Ok(None);
+        };
+        let raw_len = u32::try_from(payload.len()).map_err(|_| {
+            ContainerEncodeError::Compression(CompressionError::TooLarge(payload.len()))
+        })?;
+        let Some(data) = config
+            .compress_if_worthwhile(payload)
+            .map_err(ContainerEncodeError::Compression)?
+        else {
+            return Ok(None);
+        };
+        let mut buffer = Vec::with_capacity(COMPRESSED_PREFIX_LEN + data.len());
+        buffer.push(config.algorithm.id());
+        buffer.extend_from_slice(&raw_len.to_be_bytes());
+        buffer.extend_from_slice(&data);
+        self.header.flags |= LNMP_FLAG_COMPRESSED;
+        Ok(Some(buffer))
+    }
+
+    /// Seals the payload with `HEADER | METADATA` as associated data and sets the
+    /// encrypted flag when encryption is configured.
+    fn encrypt_payload(&mut self, payload: &[u8]) -> Result<Option<Vec<u8>>, ContainerEncodeError> {
+        let Some(cipher) = &self.encryption else {
+            return Ok(None);
+        };
+        self.header.flags |= LNMP_FLAG_ENCRYPTED;
+        let aad = [self.header.encode().as_slice(), &self.metadata].concat();
+        cipher
+            .seal(payload, &aad)
+            .map(Some)
+            .map_err(ContainerEncodeError::Encryption)
+    }
+
     fn checked_metadata_len(len: usize) -> Result<u32, ContainerEncodeError> {
         u32::try_from(len).map_err(|_| ContainerEncodeError::MetadataTooLarge(len))
     }
@@ -181,21 +295,26 @@ impl ContainerBuilder {
 
     fn validate_flags(&self) -> Result<(), ContainerEncodeError> {
         let flags = self.header.flags;
-        // In v1 only the checksum flag is allowed.
+        // In v1 only the checksum flag can be requested; the compressed,
+        // encrypted and signature flags are set by the builder itself.
         let reserved = flags & !LNMP_FLAG_CHECKSUM_REQUIRED;
         if reserved != 0 {
             return Err(ContainerEncodeError::ReservedFlags(reserved));
         }
-        if flags & (LNMP_FLAG_COMPRESSED | LNMP_FLAG_ENCRYPTED) != 0 {
+        if flags & LNMP_FLAG_ENCRYPTED != 0 {
             return Err(ContainerEncodeError::UnsupportedFlags(
-                flags & (LNMP_FLAG_COMPRESSED | LNMP_FLAG_ENCRYPTED),
+                flags & LNMP_FLAG_ENCRYPTED,
             ));
         }
         Ok(())
     }
 
+    fn checksums_required(&self) -> bool {
+        self.header.flags & LNMP_FLAG_CHECKSUM_REQUIRED != 0
+    }
+
     fn validate_checksum_requirements(&self) -> Result<(), ContainerEncodeError> {
-        if self.header.flags & LNMP_FLAG_CHECKSUM_REQUIRED == 0 {
+        if !self.checksums_required() {
             return Ok(());
         }
         if !self.checksum_confirmed {
@@ -203,6 +322,109 @@ impl ContainerBuilder {
         }
         Ok(())
     }
+
+    fn validate_payload_checksums(&self, payload: &[u8]) -> Result<(), ContainerEncodeError> {
+        match self.header.mode {
+            LnmpFileMode::Text => {
+                let text = str::from_utf8(payload)
+                    .map_err(|err| ContainerEncodeError::InvalidTextPayload(err.to_string()))?;
+                let failures = verify_text_checksums(text)
+                    .map_err(|err| ContainerEncodeError::InvalidTextPayload(err.to_string()))?;
+                if failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ContainerEncodeError::ChecksumFailures(failures))
+                }
+            }
+            LnmpFileMode::Stream => match self.stream_meta {
+                Some(meta) if meta.checksum_type == 0 => {
+                    Err(ContainerEncodeError::StreamChecksumTypeMissing)
+                }
+                Some(_) => Ok(()),
+                None => match parse_stream_metadata(&self.metadata) {
+                    Ok(meta) if meta.checksum_type == 0 => {
+                        Err(ContainerEncodeError::StreamChecksumTypeMissing)
+                    }
+                    // Length errors are reported by the regular metadata validation.
+                    _ => Ok(()),
+                },
+            },
+            // Binary, delta and spatial payloads cannot carry field checksums.
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reason a field failed checksum verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumFailureKind {
+    /// The field carries no checksum.
+    Missing,
+    /// The field checksum does not match its value.
+    Mismatch {
+        /// Checksum computed from the field value.
+        expected: u32,
+        /// Checksum present in the payload.
+        found: u32,
+    },
+}
+
+/// A single field that failed checksum verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumFailure {
+    /// Field identifier.
+    pub fid: FieldId,
+    /// Line of the field in the text payload (1-based).
+    pub line: usize,
+    /// Failure reason.
+    pub kind: ChecksumFailureKind,
+}
+
+impl fmt::Display for ChecksumFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ChecksumFailureKind::Missing => {
+                write!(f, "F{} (line {}): checksum missing", self.fid, self.line)
+            }
+            ChecksumFailureKind::Mismatch { expected, found } => write!(
+                f,
+                "F{} (line {}): checksum mismatch (expected {}, found {})",
+                self.fid,
+                self.line,
+                SemanticChecksum::format(expected),
+                SemanticChecksum::format(found)
+            ),
+        }
+    }
+}
+
+/// Verifies the semantic checksum of every top-level field in LNMP text.
+///
+/// Returns all failing fields instead of stopping at the first one; an empty
+/// list means every field carries a valid checksum.
+pub fn verify_text_checksums(text: &str) -> Result<Vec<ChecksumFailure>, LnmpError> {
+    let mut parser = Parser::new(text)?;
+    parser.track_checksums();
+    parser.parse_record()?;
+    Ok(parser
+        .take_checksum_observations()
+        .into_iter()
+        .filter_map(|obs| {
+            let kind = match obs.provided {
+                None => ChecksumFailureKind::Missing,
+                Some(found) if found != obs.computed => ChecksumFailureKind::Mismatch {
+                    expected: obs.computed,
+                    found,
+                },
+                Some(_) => return None,
+            };
+            Some(ChecksumFailure {
+                fid: obs.fid,
+                line: obs.line,
+                kind,
+            })
+        })
+        .collect())
 }
 
 impl<'a> ContainerFrame<'a> {
@@ -232,16 +454,34 @@ impl<'a> ContainerFrame<'a> {
         let metadata_start = LNMP_HEADER_SIZE;
         let metadata_end = metadata_start + metadata_len;
         let metadata = &bytes[metadata_start..metadata_end];
-        let payload = &bytes[metadata_end..];
 
         validate_reserved_flags(header.flags)?;
         validate_metadata_requirements(header.mode, metadata_len)?;
         validate_metadata_semantics(header.mode, metadata)?;
 
+        let (signed, signature) = if header.flags & LNMP_FLAG_QSIG != 0 {
+            let signed_len = bytes
+                .len()
+                .checked_sub(SIGNATURE_EXT_LEN)
+                .filter(|len| *len >= metadata_end)
+                .ok_or_else(|| {
+                    ContainerFrameError::Signature(SignatureError::Malformed(
+                        "signature extension is truncated".to_string(),
+                    ))
+                })?;
+            let signature = FrameSignature::decode(&bytes[signed_len..])
+                .map_err(ContainerFrameError::Signature)?;
+            (&bytes[..signed_len], Some(signature))
+        } else {
+            (bytes, None)
+        };
+
         Ok(Self {
             header,
             metadata,
-            payload,
+            payload: &signed[metadata_end..],
+            signed,
+            signature,
         })
     }
 
@@ -255,11 +495,85 @@ impl<'a> ContainerFrame<'a> {
         self.metadata
     }
 
-    /// Raw payload region.
+    /// Raw payload region (still compressed or encrypted if the flags say so).
     pub fn payload(&self) -> &'a [u8] {
         self.payload
     }
 
+    /// Returns true if the header sets the compressed flag.
+    pub const fn is_compressed(&self) -> bool {
+        self.header.flags & LNMP_FLAG_COMPRESSED != 0
+    }
+
+    /// Returns true if the header sets the signature flag (`qsig`).
+    pub const fn is_signed(&self) -> bool {
+        self.header.flags & LNMP_FLAG_QSIG != 0
+    }
+
+    /// Detached signature carried after the payload, if the container is signed.
+    pub fn signature(&self) -> Option<FrameSignature> {
+        self.signature
+    }
+
+    /// Checks the container signature with `verifier`.
+    ///
+    /// Unsigned containers fail with [`SignatureError::Unsigned`], so callers that
+    /// require authenticated input can reject them.
+    pub fn verify_signature(
+        &self,
+        verifier: &dyn SignatureVerifier,
+    ) -> Result<(), ContainerDecodeError> {
+        let signature = self
+            .signature
+            .ok_or(ContainerDecodeError::Signature(SignatureError::Unsigned))?;
+        verifier
+            .verify(self.signed, &signature)
+            .map_err(ContainerDecodeError::Signature)
+    }
+
+    /// Returns true if the header sets the encrypted flag.
+    pub const fn is_encrypted(&self) -> bool {
+        self.header.flags & LNMP_FLAG_ENCRYPTED != 0
+    }
+
+    /// Payload with compression removed; borrowed when the payload is not compressed.
+    ///
+    /// Encrypted payloads fail with [`EncryptionError::MissingKeyProvider`]; use
+    /// [`decrypted_payload`](Self::decrypted_payload) instead.
+    pub fn decompressed_payload(&self) -> Result<Cow<'a, [u8]>, ContainerDecodeError> {
+        self.plain_payload(None)
+    }
+
+    /// Payload with encryption and compression removed, decrypted with `keys`.
+    pub fn decrypted_payload(
+        &self,
+        keys: &dyn KeyProvider,
+    ) -> Result<Cow<'a, [u8]>, ContainerDecodeError> {
+        self.plain_payload(Some(keys))
+    }
+
+    fn plain_payload(
+        &self,
+        keys: Option<&dyn KeyProvider>,
+    ) -> Result<Cow<'a, [u8]>, ContainerDecodeError> {
+        let payload = if self.is_encrypted() {
+            let keys = keys.ok_or(ContainerDecodeError::Encryption(
+                EncryptionError::MissingKeyProvider,
+            ))?;
+            let aad = [self.header.encode().as_slice(), self.metadata].concat();
+            Cow::Owned(
+                encryption::open(keys, self.payload, &aad)
+                    .map_err(ContainerDecodeError::Encryption)?,
+            )
+        } else {
+            Cow::Borrowed(self.payload)
+        };
+        if !self.is_compressed() {
+            return Ok(payload);
+        }
+        decompress_payload(&payload).map(Cow::Owned)
+    }
+
     /// Builds a delta apply context from the metadata (if mode is Delta).
     pub fn delta_apply_context(&self) -> Option<DeltaApplyContext> {
         if self.header.mode != LnmpFileMode::Delta {
@@ -282,12 +596,70 @@ impl<'a> ContainerFrame<'a> {
         }
     }
 
+    /// Returns true if the header sets the checksum flag.
+    pub const fn checksums_required(&self) -> bool {
+        self.header.flags & LNMP_FLAG_CHECKSUM_REQUIRED != 0
+    }
+
+    /// Verifies payload checksums and returns every failing field.
+    ///
+    /// Text payloads are checked field by field. Stream payloads must declare a
+    /// checksum type in their metadata; chunk checksums are verified by the
+    /// streaming decoder. Other modes cannot carry checksums and always verify
+    /// without failures, so containers written before the flag was enforced
+    /// still decode.
+    pub fn verify_checksums(&self) -> Result<Vec<ChecksumFailure>, ContainerDecodeError> {
+        self.verify_checksums_inner(None)
+    }
+
+    fn verify_checksums_inner(
+        &self,
+        keys: Option<&dyn KeyProvider>,
+    ) -> Result<Vec<ChecksumFailure>, ContainerDecodeError> {
+        match self.header.mode {
+            LnmpFileMode::Text => {
+                let payload = self.plain_payload(keys)?;
+                let text = str::from_utf8(&payload).map_err(ContainerDecodeError::InvalidUtf8)?;
+                verify_text_checksums(text).map_err(ContainerDecodeError::TextCodec)
+            }
+            LnmpFileMode::Stream => match parse_stream_metadata(self.metadata) {
+                Ok(meta) if meta.checksum_type != 0 => Ok(Vec::new()),
+                _ => Err(ContainerDecodeError::StreamChecksumTypeMissing),
+            },
+            _ => Ok(Vec::new()),
+        }
+    }
+
     /// Decodes the payload into a [`LnmpRecord`] using mode-specific codecs.
+    ///
+    /// If the checksum flag is set, checksums are verified first and all failing
+    /// fields are reported in [`ContainerDecodeError::ChecksumFailures`].
     pub fn decode_record(&self) -> Result<LnmpRecord, ContainerDecodeError> {
+        self.decode_record_inner(None)
+    }
+
+    /// Decodes an encrypted (or plain) payload into a [`LnmpRecord`], decrypting with `keys`.
+    pub fn decode_record_with_keys(
+        &self,
+        keys: &dyn KeyProvider,
+    ) -> Result<LnmpRecord, ContainerDecodeError> {
+        self.decode_record_inner(Some(keys))
+    }
+
+    fn decode_record_inner(
+        &self,
+        keys: Option<&dyn KeyProvider>,
+    ) -> Result<LnmpRecord, ContainerDecodeError> {
+        if self.checksums_required() {
+            let failures = self.verify_checksums_inner(keys)?;
+            if !failures.is_empty() {
+                return Err(ContainerDecodeError::ChecksumFailures(failures));
+            }
+        }
         match self.header.mode {
-            LnmpFileMode::Text => self.decode_text_record(),
+            LnmpFileMode::Text => self.decode_text_record(keys),
             LnmpFileMode::Binary | LnmpFileMode::Stream | LnmpFileMode::Delta => {
-                self.decode_binary_record()
+                self.decode_binary_record(keys)
             }
             mode => Err(ContainerDecodeError::UnsupportedMode(mode)),
         }
@@ -316,22 +688,48 @@ impl<'a> ContainerFrame<'a> {
         Ok(encoder.encode(&record))
     }
 
-    fn decode_text_record(&self) -> Result<LnmpRecord, ContainerDecodeError> {
-        let text = str::from_utf8(self.payload).map_err(ContainerDecodeError::InvalidUtf8)?;
+    fn decode_text_record(
+        &self,
+        keys: Option<&dyn KeyProvider>,
+    ) -> Result<LnmpRecord, ContainerDecodeError> {
+        let payload = self.plain_payload(keys)?;
+        let text = str::from_utf8(&payload).map_err(ContainerDecodeError::InvalidUtf8)?;
         let mut parser = Parser::new(text).map_err(ContainerDecodeError::TextCodec)?;
         parser
             .parse_record()
             .map_err(ContainerDecodeError::TextCodec)
     }
 
-    fn decode_binary_record(&self) -> Result<LnmpRecord, ContainerDecodeError> {
+    fn decode_binary_record(
+        &self,
+        keys: Option<&dyn KeyProvider>,
+    ) -> Result<LnmpRecord, ContainerDecodeError> {
         let decoder = BinaryDecoder::new();
         decoder
-            .decode(self.payload)
+            .decode(&self.plain_payload(keys)?)
             .map_err(ContainerDecodeError::BinaryCodec)
     }
 }
 
+/// Decompresses `ALGORITHM | RAW_LEN | DATA` written by [`ContainerBuilder`].
+fn decompress_payload(payload: &[u8]) -> Result<Vec<u8>, ContainerDecodeError> {
+    if payload.len() < COMPRESSED_PREFIX_LEN {
+        return Err(ContainerDecodeError::Compression(CompressionError::Codec(
+            "compressed payload is missing its header".to_string(),
+        )));
+    }
+    let algorithm =
+        CompressionAlgorithm::from_id(payload[0]).map_err(ContainerDecodeError::Compression)?;
+    let mut raw_len = [0u8; 4];
+    raw_len.copy_from_slice(&payload[1..COMPRESSED_PREFIX_LEN]);
+    compression::decompress(
+        algorithm,
+        &payload[COMPRESSED_PREFIX_LEN..],
+        u32::from_be_bytes(raw_len) as usize,
+    )
+    .map_err(ContainerDecodeError::Compression)
+}
+
 /// High-level view over the payload region for each mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerBody<'a> {
@@ -385,6 +783,8 @@ pub enum ContainerFrameError {
         /// Offending value.
         value: u8,
     },
+    /// Signature flag is set but the signature extension is malformed.
+    Signature(SignatureError),
 }
 
 impl fmt::Display for ContainerFrameError {
@@ -421,6 +821,7 @@ impl fmt::Display for ContainerFrameError {
                     "mode {mode:?} metadata field {field} contains unsupported value 0x{value:02X}"
                 )
             }
+            ContainerFrameError::Signature(err) => write!(f, "{err}"),
         }
     }
 }
@@ -429,6 +830,7 @@ impl std::error::Error for ContainerFrameError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             ContainerFrameError::Header(err) => Some(err),
+            ContainerFrameError::Signature(err) => Some(err),
             ContainerFrameError::ReservedFlags(_) => None,
             ContainerFrameError::InvalidMetadataLength { .. } => None,
             _ => None,
@@ -443,7 +845,8 @@ impl From<LnmpContainerError> for ContainerFrameError {
 }
 
 fn validate_reserved_flags(flags: u16) -> Result<(), ContainerFrameError> {
-    const ALLOWED: u16 = LNMP_FLAG_CHECKSUM_REQUIRED;
+    const ALLOWED: u16 =
+        LNMP_FLAG_CHECKSUM_REQUIRED | LNMP_FLAG_COMPRESSED | LNMP_FLAG_ENCRYPTED | LNMP_FLAG_QSIG;
     let reserved = flags & !ALLOWED;
     if reserved != 0 {
         return Err(ContainerFrameError::ReservedFlags(reserved));
@@ -451,6 +854,7 @@ fn validate_reserved_flags(flags: u16) -> Result<(), ContainerFrameError> {
     Ok(())
 }
 
+#[allow(clippy::collapsible_match)]
 fn validate_metadata_requirements(
     mode: LnmpFileMode,
     metadata_len: usize,
@@ -508,6 +912,7 @@ fn validate_metadata_semantics(
     Ok(())
 }
 
+#[allow(clippy::collapsible_match)]
 fn encode_validate_metadata_requirements(
     mode: LnmpFileMode,
     metadata_len: usize,
@@ -605,6 +1010,24 @@ pub enum ContainerDecodeError {
     BinaryCodec(BinaryError),
     /// Mode is not currently supported by the decoder.
     UnsupportedMode(LnmpFileMode),
+    /// Checksum flag is set but fields failed verification.
+    ChecksumFailures(Vec<ChecksumFailure>),
+    /// Checksum flag is set but stream metadata declares no checksum type.
+    StreamChecksumTypeMissing,
+    /// Compressed payload could not be decompressed.
+    Compression(CompressionError),
+    /// Encrypted payload could not be decrypted.
+    Encryption(EncryptionError),
+    /// Signature is missing or failed verification.
+    Signature(SignatureError),
+}
+
+fn format_checksum_failures(failures: &[ChecksumFailure]) -> String {
+    failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl fmt::Display for ContainerDecodeError {
@@ -617,6 +1040,19 @@ impl fmt::Display for ContainerDecodeError {
             ContainerDecodeError::UnsupportedMode(mode) => {
                 write!(f, "mode {mode:?} is not supported yet")
             }
+            ContainerDecodeError::ChecksumFailures(failures) => write!(
+                f,
+                "{} field(s) failed checksum verification: {}",
+                failures.len(),
+                format_checksum_failures(failures)
+            ),
+            ContainerDecodeError::StreamChecksumTypeMissing => write!(
+                f,
+                "checksum flag is set but stream metadata declares no checksum type"
+            ),
+            ContainerDecodeError::Compression(err) => write!(f, "{err}"),
+            ContainerDecodeError::Encryption(err) => write!(f, "{err}"),
+            ContainerDecodeError::Signature(err) => write!(f, "{err}"),
         }
     }
 }
@@ -628,7 +1064,12 @@ impl std::error::Error for ContainerDecodeError {
             ContainerDecodeError::InvalidUtf8(err) => Some(err),
             ContainerDecodeError::TextCodec(err) => Some(err),
             ContainerDecodeError::BinaryCodec(err) => Some(err),
-            ContainerDecodeError::UnsupportedMode(_) => None,
+            ContainerDecodeError::Compression(err) => Some(err),
+            ContainerDecodeError::Encryption(err) => Some(err),
+            ContainerDecodeError::Signature(err) => Some(err),
+            ContainerDecodeError::UnsupportedMode(_)
+            | ContainerDecodeError::ChecksumFailures(_)
+            | ContainerDecodeError::StreamChecksumTypeMissing => None,
         }
     }
 }
@@ -654,6 +1095,18 @@ pub enum ContainerEncodeError {
     ReservedFlags(u16),
     /// Checksum flag set but record lacks checksum hints.
     ChecksumFlagMissingHints,
+    /// Checksum flag set but payload fields are missing or carry invalid checksums.
+    ChecksumFailures(Vec<ChecksumFailure>),
+    /// Checksum flag set but stream metadata declares no checksum type.
+    StreamChecksumTypeMissing,
+    /// Text payload could not be parsed for checksum verification.
+    InvalidTextPayload(String),
+    /// Payload compression failed.
+    Compression(CompressionError),
+    /// Payload encryption failed.
+    Encryption(EncryptionError),
+    /// Container signing failed.
+    Signature(SignatureError),
     /// Metadata length does not satisfy mode requirements.
     InvalidMetadataLength {
         /// Mode provided.
@@ -686,7 +1139,7 @@ impl fmt::Display for ContainerEncodeError {
             }
             ContainerEncodeError::UnsupportedFlags(bits) => write!(
                 f,
-                "flags {bits:#06X} require compression/encryption which is not implemented"
+                "flags {bits:#06X} are set by the builder; use with_encryption instead"
             ),
             ContainerEncodeError::ReservedFlags(bits) => {
                 write!(f, "reserved flags are not allowed in v1: {bits:#06X}")
@@ -695,6 +1148,22 @@ impl fmt::Display for ContainerEncodeError {
                 f,
                 "checksum flag is set but no fields contain embedded checksum hints"
             ),
+            ContainerEncodeError::ChecksumFailures(failures) => write!(
+                f,
+                "checksum flag is set but {} field(s) failed verification: {}",
+                failures.len(),
+                format_checksum_failures(failures)
+            ),
+            ContainerEncodeError::StreamChecksumTypeMissing => write!(
+                f,
+                "checksum flag is set but stream metadata declares no checksum type"
+            ),
+            ContainerEncodeError::InvalidTextPayload(reason) => {
+                write!(f, "text payload is invalid: {reason}")
+            }
+            ContainerEncodeError::Compression(err) => write!(f, "{err}"),
+            ContainerEncodeError::Encryption(err) => write!(f, "{err}"),
+            ContainerEncodeError::Signature(err) => write!(f, "{err}"),
             ContainerEncodeError::InvalidMetadataLength {
                 mode,
                 expected,
@@ -715,6 +1184,9 @@ impl std::error::Error for ContainerEncodeError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             ContainerEncodeError::BinaryCodec(err) => Some(err),
+            ContainerEncodeError::Compression(err) => Some(err),
+            ContainerEncodeError::Encryption(err) => Some(err),
+            ContainerEncodeError::Signature(err) => Some(err),
             _ => None,
         }
     }
@@ -874,6 +1346,48 @@ mod tests {
         assert!(!frame.payload().is_empty());
     }
 
+    #[cfg(feature = "zstd")]
+    fn large_record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        for fid in 1..=32 {
+            record.add_field(LnmpField {
+                fid,
+                value: LnmpValue::String("repeated payload text".to_string()),
+            });
+        }
+        record
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn builder_compresses_large_payloads() {
+        let record = large_record();
+        for mode in [LnmpFileMode::Text, LnmpFileMode::Binary] {
+            let plain = ContainerBuilder::new(mode).encode_record(&record).unwrap();
+            let bytes = ContainerBuilder::new(mode)
+                .with_compression(CompressionConfig::default())
+                .encode_record(&record)
+                .unwrap();
+            assert!(bytes.len() < plain.len());
+
+            let frame = ContainerFrame::parse(&bytes).unwrap();
+            assert!(frame.is_compressed());
+            assert_eq!(frame.decode_record().unwrap(), record);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn builder_leaves_small_payloads_uncompressed() {
+        let bytes = ContainerBuilder::new(LnmpFileMode::Text)
+            .with_compression(CompressionConfig::new(CompressionAlgorithm::Lz4))
+            .wrap_payload(b"F7=1\n")
+            .unwrap();
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(!frame.is_compressed());
+        assert_eq!(frame.payload(), b"F7=1\n");
+    }
+
     #[test]
     fn builder_rejects_compression_flag() {
         let mut record = LnmpRecord::new();
@@ -911,6 +1425,134 @@ mod tests {
         ));
     }
 
+    fn checksum_record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(10),
+        });
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::String("alice".into()),
+        });
+        record
+    }
+
+    #[test]
+    fn checksum_flag_text_round_trip() {
+        let record = checksum_record();
+        let bytes = ContainerBuilder::new(LnmpFileMode::Text)
+            .with_flags(LNMP_FLAG_CHECKSUM_REQUIRED)
+            .encode_record(&record)
+            .unwrap();
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(frame.checksums_required());
+        assert!(str::from_utf8(frame.payload()).unwrap().contains('#'));
+        assert!(frame.verify_checksums().unwrap().is_empty());
+        let decoded = frame.decode_record().unwrap();
+        assert_eq!(decoded.get_field(12).unwrap().value, LnmpValue::Int(10));
+    }
+
+    #[test]
+    fn checksum_flag_rejects_payload_without_checksums() {
+        let err = ContainerBuilder::new(LnmpFileMode::Text)
+            .with_flags(LNMP_FLAG_CHECKSUM_REQUIRED)
+            .wrap_payload(b"F7=1\nF12=10")
+            .unwrap_err();
+        match err {
+            ContainerEncodeError::ChecksumFailures(failures) => {
+                let fids: Vec<_> = failures.iter().map(|f| f.fid).collect();
+                assert_eq!(fids, vec![7, 12]);
+                assert!(failures
+                    .iter()
+                    .all(|f| f.kind == ChecksumFailureKind::Missing));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn checksum_flag_reader_reports_every_failure() {
+        let encoder = Encoder::with_config(EncoderConfig::new().with_checksums(true));
+        let text = encoder.encode(&checksum_record());
+        // Tamper with F12 and drop the checksum from F7.
+        let tampered: Vec<String> = text
+            .lines()
+            .map(|line| {
+                if line.starts_with("F12=") {
+                    line.replacen("F12=10", "F12=11", 1)
+                } else {
+                    line.split('#').next().unwrap().to_string()
+                }
+            })
+            .collect();
+        let mut header = LnmpContainerHeader::new(LnmpFileMode::Text);
+        header.flags = LNMP_FLAG_CHECKSUM_REQUIRED;
+        let mut bytes = header.encode().to_vec();
+        bytes.extend_from_slice(tampered.join("\n").as_bytes());
+
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        let failures = frame.verify_checksums().unwrap();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].fid, 7);
+        assert_eq!(failures[0].kind, ChecksumFailureKind::Missing);
+        assert_eq!(failures[1].fid, 12);
+        assert_eq!(failures[1].line, 2);
+        assert!(matches!(
+            failures[1].kind,
+            ChecksumFailureKind::Mismatch { .. }
+        ));
+        assert!(matches!(
+            frame.decode_record(),
+            Err(ContainerDecodeError::ChecksumFailures(f)) if f.len() == 2
+        ));
+    }
+
+    #[test]
+    fn checksum_flag_is_not_enforced_for_binary_mode() {
+        let record = checksum_record();
+        let bytes = ContainerBuilder::new(LnmpFileMode::Binary)
+            .with_flags(LNMP_FLAG_CHECKSUM_REQUIRED)
+            .with_checksum_confirmation(true)
+            .encode_record(&record)
+            .unwrap();
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(frame.checksums_required());
+        assert!(frame.verify_checksums().unwrap().is_empty());
+        assert!(frame.decode_record().unwrap().canonical_eq(&record));
+    }
+
+    #[test]
+    fn checksum_flag_requires_stream_checksum_type() {
+        let meta = StreamMetadata {
+            chunk_size: 4096,
+            checksum_type: 0,
+            flags: 0,
+        };
+        let err = ContainerBuilder::new(LnmpFileMode::Stream)
+            .with_flags(LNMP_FLAG_CHECKSUM_REQUIRED)
+            .with_stream_metadata(meta)
+            .unwrap()
+            .wrap_payload(b"chunk")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerEncodeError::StreamChecksumTypeMissing
+        ));
+
+        let bytes = ContainerBuilder::new(LnmpFileMode::Stream)
+            .with_flags(LNMP_FLAG_CHECKSUM_REQUIRED)
+            .with_stream_metadata(StreamMetadata {
+                checksum_type: 0x02,
+                ..meta
+            })
+            .unwrap()
+            .wrap_payload(b"chunk")
+            .unwrap();
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(frame.verify_checksums().unwrap().is_empty());
+    }
+
     #[test]
     fn builder_requires_stream_metadata_length() {
         let builder = ContainerBuilder::new(LnmpFileMode::Stream)
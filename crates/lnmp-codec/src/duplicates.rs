@@ -0,0 +1,222 @@
+//! Resolution of repeated field IDs within a record.
+//!
+//! Strict mode always rejects duplicates. In loose mode the [`DuplicateFieldPolicy`]
+//! configured on [`ParserConfig`](crate::ParserConfig),
+//! [`DecoderConfig`](crate::binary::DecoderConfig) or passed to
+//! [`canonicalize_record_with_policy`](crate::encoder::canonicalize_record_with_policy)
+//! decides what happens to them, so downstream code does not need to deduplicate.
+
+use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpValue};
+
+/// How repeated field IDs within one record are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateFieldPolicy {
+    /// Keep every occurrence in input order (default)
+    #[default]
+    KeepAll,
+    /// Keep the first occurrence and drop later ones
+    KeepFirst,
+    /// Keep the last occurrence, at the position of the first one
+    KeepLast,
+    /// Concatenate arrays of the same kind; any other duplicate is an error
+    MergeArrays,
+    /// Reject the record
+    Error,
+}
+
+impl DuplicateFieldPolicy {
+    /// Adds `field` to `record`, resolving a clash with an existing field of the same FID.
+    ///
+    /// Returns the conflicting FID if the policy rejects the duplicate.
+    pub fn insert(self, record: &mut LnmpRecord, field: LnmpField) -> Result<(), FieldId> {
+        if self == DuplicateFieldPolicy::KeepAll || record.get_field(field.fid).is_none() {
+            record.add_field(field);
+            return Ok(());
+        }
+
+        let fid = field.fid;
+        match self {
+            DuplicateFieldPolicy::KeepAll | DuplicateFieldPolicy::KeepFirst => Ok(()),
+            DuplicateFieldPolicy::KeepLast => {
+                replace_value(record, fid, |_| Some(field.value));
+                Ok(())
+            }
+            DuplicateFieldPolicy::MergeArrays => {
+                let mut merged = true;
+                replace_value(record, fid, |existing| {
+                    let value = merge_arrays(existing, field.value);
+                    merged = value.is_some();
+                    value
+                });
+                if merged {
+                    Ok(())
+                } else {
+                    Err(fid)
+                }
+            }
+            DuplicateFieldPolicy::Error => Err(fid),
+        }
+    }
+
+    /// Applies the policy to every level of `record`, including nested records and arrays.
+    ///
+    /// Returns the first conflicting FID if the policy rejects a duplicate.
+    pub fn apply(self, record: &LnmpRecord) -> Result<LnmpRecord, FieldId> {
+        let mut resolved = LnmpRecord::new();
+        for field in record.fields() {
+            let value = match &field.value {
+                LnmpValue::NestedRecord(nested) => {
+                    LnmpValue::NestedRecord(Box::new(self.apply(nested)?))
+                }
+                LnmpValue::NestedArray(records) => LnmpValue::NestedArray(
+                    records
+                        .iter()
+                        .map(|r| self.apply(r))
+                        .collect::<Result<_, _>>()?,
+                ),
+                other => other.clone(),
+            };
+            self.insert(
+                &mut resolved,
+                LnmpField {
+                    fid: field.fid,
+                    value,
+                },
+            )?;
+        }
+        Ok(resolved)
+    }
+}
+
+/// Replaces the value of the first field with `fid`, keeping its position.
+///
+/// If `f` returns `None` the existing value is left untouched.
+fn replace_value<F>(record: &mut LnmpRecord, fid: FieldId, f: F)
+where
+    F: FnOnce(&LnmpValue) -> Option<LnmpValue>,
+{
+    let mut fields = std::mem::take(record).into_fields();
+    if let Some(existing) = fields.iter_mut().find(|field| field.fid == fid) {
+        if let Some(value) = f(&existing.value) {
+            existing.value = value;
+        }
+    }
+    *record = LnmpRecord::from_sorted_fields(fields);
+}
+
+fn merge_arrays(existing: &LnmpValue, incoming: LnmpValue) -> Option<LnmpValue> {
+    fn concat<T: Clone>(a: &[T], mut b: Vec<T>) -> Vec<T> {
+        let mut out = a.to_vec();
+        out.append(&mut b);
+        out
+    }
+
+    match (existing, incoming) {
+        (LnmpValue::StringArray(a), LnmpValue::StringArray(b)) => {
+            Some(LnmpValue::StringArray(concat(a, b)))
+        }
+        (LnmpValue::IntArray(a), LnmpValue::IntArray(b)) => Some(LnmpValue::IntArray(concat(a, b))),
+        (LnmpValue::FloatArray(a), LnmpValue::FloatArray(b)) => {
+            Some(LnmpValue::FloatArray(concat(a, b)))
+        }
+        (LnmpValue::BoolArray(a), LnmpValue::BoolArray(b)) => {
+            Some(LnmpValue::BoolArray(concat(a, b)))
+        }
+        (LnmpValue::NestedArray(a), LnmpValue::NestedArray(b)) => {
+            Some(LnmpValue::NestedArray(concat(a, b)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(fid: FieldId, value: LnmpValue) -> LnmpField {
+        LnmpField { fid, value }
+    }
+
+    fn record(fields: Vec<LnmpField>) -> LnmpRecord {
+        LnmpRecord::from_sorted_fields(fields)
+    }
+
+    #[test]
+    fn test_keep_all_is_identity() {
+        let input = record(vec![
+            field(1, LnmpValue::Int(1)),
+            field(1, LnmpValue::Int(2)),
+        ]);
+        assert_eq!(DuplicateFieldPolicy::KeepAll.apply(&input).unwrap(), input);
+    }
+
+    #[test]
+    fn test_keep_first_and_last() {
+        let input = record(vec![
+            field(1, LnmpValue::Int(1)),
+            field(2, LnmpValue::Int(5)),
+            field(1, LnmpValue::Int(2)),
+        ]);
+
+        let first = DuplicateFieldPolicy::KeepFirst.apply(&input).unwrap();
+        assert_eq!(first.fields().len(), 2);
+        assert_eq!(first.get_field(1).unwrap().value, LnmpValue::Int(1));
+
+        let last = DuplicateFieldPolicy::KeepLast.apply(&input).unwrap();
+        assert_eq!(last.fields().len(), 2);
+        assert_eq!(last.fields()[0], field(1, LnmpValue::Int(2)));
+    }
+
+    #[test]
+    fn test_merge_arrays() {
+        let input = record(vec![
+            field(
+                1,
+                LnmpValue::StringArray(vec!["a".to_string(), "b".to_string()]),
+            ),
+            field(1, LnmpValue::StringArray(vec!["c".to_string()])),
+        ]);
+        let merged = DuplicateFieldPolicy::MergeArrays.apply(&input).unwrap();
+        assert_eq!(
+            merged.fields(),
+            &[field(
+                1,
+                LnmpValue::StringArray(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            )]
+        );
+    }
+
+    #[test]
+    fn test_merge_arrays_rejects_scalars_and_mixed_kinds() {
+        let scalars = record(vec![
+            field(1, LnmpValue::Int(1)),
+            field(1, LnmpValue::Int(2)),
+        ]);
+        assert_eq!(DuplicateFieldPolicy::MergeArrays.apply(&scalars), Err(1));
+
+        let mixed = record(vec![
+            field(3, LnmpValue::IntArray(vec![1])),
+            field(3, LnmpValue::FloatArray(vec![1.5])),
+        ]);
+        assert_eq!(DuplicateFieldPolicy::MergeArrays.apply(&mixed), Err(3));
+    }
+
+    #[test]
+    fn test_error_policy_applies_to_nested_records() {
+        let nested = record(vec![
+            field(1, LnmpValue::Int(1)),
+            field(1, LnmpValue::Int(2)),
+        ]);
+        let input = record(vec![field(
+            50,
+            LnmpValue::NestedRecord(Box::new(nested.clone())),
+        )]);
+        assert_eq!(DuplicateFieldPolicy::Error.apply(&input), Err(1));
+
+        let deduped = DuplicateFieldPolicy::KeepFirst.apply(&input).unwrap();
+        match &deduped.get_field(50).unwrap().value {
+            LnmpValue::NestedRecord(inner) => assert_eq!(inner.fields().len(), 1),
+            other => panic!("expected nested record, got {:?}", other),
+        }
+    }
+}
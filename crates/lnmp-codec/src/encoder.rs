@@ -1,10 +1,13 @@
 //! Encoder for converting structured records into LNMP text format.
 
-use crate::config::EncoderConfig;
+use crate::config::{EncoderConfig, FloatFormat};
+use crate::duplicates::DuplicateFieldPolicy;
 use crate::error::LnmpError;
 use lnmp_core::checksum::SemanticChecksum;
 use lnmp_core::registry::{ValidationMode, ValidationResult};
 use lnmp_core::{LnmpField, LnmpRecord, LnmpValue, TypeHint};
+use lnmp_envelope::text_codec::TextEncoder;
+use lnmp_envelope::LnmpEnvelope;
 
 /// Encodes a quantized embedding into compact text format
 ///
@@ -31,6 +34,34 @@ fn encode_quantized_embedding(qv: &lnmp_quant::QuantizedVector) -> String {
     result
 }
 
+/// Encodes an embedding into its canonical text format
+///
+/// Format: `E[dim,dtype,"base64_data"]`, followed by `,n`, `,c` or `,nc` when the
+/// vector is marked normalized and/or centered. `dtype` is one of `f32`, `f16`,
+/// `i8`, `u8` or `binary`; the data is standard padded base64 of the raw
+/// little-endian values.
+/// Example: `E[3,f32,"AACAPwAAAEAAAEBA"]`
+fn encode_embedding(vec: &lnmp_embedding::Vector) -> String {
+    use base64::Engine;
+    use lnmp_embedding::EmbeddingType;
+
+    let dtype = match vec.dtype {
+        EmbeddingType::F32 => "f32",
+        EmbeddingType::F16 => "f16",
+        EmbeddingType::I8 => "i8",
+        EmbeddingType::U8 => "u8",
+        EmbeddingType::Binary => "binary",
+    };
+    let data = base64::engine::general_purpose::STANDARD.encode(&vec.data);
+    let flags = match (vec.transform.normalized, vec.transform.centered) {
+        (false, false) => "",
+        (true, false) => ",n",
+        (false, true) => ",c",
+        (true, true) => ",nc",
+    };
+    format!("E[{},{},\"{}\"{}]", vec.dim, dtype, data, flags)
+}
+
 /// Encoder for LNMP text format
 pub struct Encoder {
     use_semicolons: bool,
@@ -51,12 +82,11 @@ impl Encoder {
 
     /// Creates a new encoder with custom configuration
     pub fn with_config(config: EncoderConfig) -> Self {
-        let normalizer = config.semantic_dictionary.as_ref().map(|dict| {
-            crate::normalizer::ValueNormalizer::new(crate::normalizer::NormalizationConfig {
-                semantic_dictionary: Some(dict.clone()),
-                ..crate::normalizer::NormalizationConfig::default()
-            })
-        });
+        let normalizer = crate::normalizer::NormalizationConfig::for_dictionaries(
+            config.semantic_dictionary.as_ref(),
+            config.shared_dictionary.as_ref(),
+        )
+        .map(crate::normalizer::ValueNormalizer::new);
 
         Self {
             // If canonical is enabled, prefer newlines; otherwise use semicolons for inline format
@@ -81,7 +111,11 @@ impl Encoder {
     /// Encodes a complete record into LNMP text format (canonical format with sorted fields)
     pub fn encode(&self, record: &LnmpRecord) -> String {
         // Canonicalize the record first (sorts fields and nested structures)
-        let canonical = canonicalize_record(record);
+        let canonical = if self.config.preserve_empty {
+            canonicalize_record_preserving_empty(record)
+        } else {
+            canonicalize_record(record)
+        };
 
         let fields: Vec<String> = canonical
             .fields()
@@ -107,6 +141,26 @@ impl Encoder {
         }
     }
 
+    /// Encodes an envelope, preceded by its `#ENVELOPE` header line when
+    /// [`EncoderConfig::emit_envelope_header`] is set
+    ///
+    /// Envelopes with empty metadata are written without a header. The output
+    /// parses back with [`Parser::parse_envelope`](crate::Parser::parse_envelope).
+    pub fn encode_envelope(&self, envelope: &LnmpEnvelope) -> Result<String, LnmpError> {
+        let body = self.encode(&envelope.record);
+        if !self.config.emit_envelope_header || envelope.metadata.is_empty() {
+            return Ok(body);
+        }
+
+        let header =
+            TextEncoder::encode(&envelope.metadata).map_err(|e| LnmpError::InvalidEnvelope {
+                reason: e.to_string(),
+                line: 1,
+                column: 1,
+            })?;
+        Ok(format!("{}\n{}", header, body))
+    }
+
     /// Encodes a complete record with FID validation (v0.5.14)
     ///
     /// Returns an error if any field fails FID validation.
@@ -262,7 +316,7 @@ impl Encoder {
     fn encode_value(&self, value: &LnmpValue) -> String {
         match value {
             LnmpValue::Int(i) => i.to_string(),
-            LnmpValue::Float(f) => f.to_string(),
+            LnmpValue::Float(f) => self.format_float(*f),
             LnmpValue::Bool(b) => {
                 if *b {
                     "1".to_string()
@@ -281,7 +335,7 @@ impl Encoder {
                 format!("[{}]", items.join(","))
             }
             LnmpValue::FloatArray(arr) => {
-                let items: Vec<String> = arr.iter().map(|f| f.to_string()).collect();
+                let items: Vec<String> = arr.iter().map(|f| self.format_float(*f)).collect();
                 format!("[{}]", items.join(","))
             }
             LnmpValue::BoolArray(arr) => {
@@ -294,9 +348,8 @@ impl Encoder {
             LnmpValue::NestedRecord(record) => self.encode_nested_record(record),
             LnmpValue::NestedArray(records) => self.encode_nested_array(records),
             LnmpValue::Embedding(vec) => {
-                // Text format representation for embeddings is not yet standardized.
-                // We use a placeholder format that indicates the dimension.
-                format!("[vector dim={}]", vec.dim)
+                // Canonical text format: E[dim,dtype,"base64_data"]
+                encode_embedding(vec)
             }
             LnmpValue::EmbeddingDelta(delta) => {
                 // Text format representation for embedding deltas is not yet standardized.
@@ -380,6 +433,23 @@ impl Encoder {
         false
     }
 
+    /// Formats a float according to `float_format` and `suppress_scientific`
+    fn format_float(&self, f: f64) -> String {
+        if !f.is_finite() {
+            return f.to_string();
+        }
+        let formatted = match self.config.float_format {
+            FloatFormat::Display => f.to_string(),
+            FloatFormat::FixedPrecision(digits) => format!("{:.*}", digits, f),
+            FloatFormat::ShortestRoundTrip => ryu::Buffer::new().format_finite(f).to_string(),
+        };
+        if self.config.suppress_scientific {
+            expand_exponent(&formatted)
+        } else {
+            formatted
+        }
+    }
+
     /// Escapes special characters in a string
     fn escape_string(&self, s: &str) -> String {
         let mut result = String::new();
@@ -411,16 +481,28 @@ impl Default for Encoder {
 /// - Omitting redundant empty fields (empty strings, empty arrays, empty nested structures)
 /// - Maintaining structural integrity
 pub fn canonicalize_record(record: &LnmpRecord) -> LnmpRecord {
+    canonicalize(record, false)
+}
+
+/// Canonicalizes a record like [`canonicalize_record`] but keeps empty fields
+///
+/// Use this when an empty value means "explicitly cleared" (e.g. `F23=[]`) and must
+/// stay distinguishable from an absent field.
+pub fn canonicalize_record_preserving_empty(record: &LnmpRecord) -> LnmpRecord {
+    canonicalize(record, true)
+}
+
+fn canonicalize(record: &LnmpRecord, preserve_empty: bool) -> LnmpRecord {
     let mut canonical = LnmpRecord::new();
 
     // Sort fields by FID (stable sort preserves insertion order for duplicates)
     let sorted = record.sorted_fields();
 
     for field in sorted {
-        let canonical_value = canonicalize_value(&field.value);
+        let canonical_value = canonicalize_value(&field.value, preserve_empty);
 
         // Omit redundant empty fields
-        if !is_empty_value(&canonical_value) {
+        if preserve_empty || !is_empty_value(&canonical_value) {
             canonical.add_field(LnmpField {
                 fid: field.fid,
                 value: canonical_value,
@@ -431,8 +513,32 @@ pub fn canonicalize_record(record: &LnmpRecord) -> LnmpRecord {
     canonical
 }
 
+/// Canonicalizes a record after resolving duplicate FIDs with `policy`
+///
+/// The policy is applied at every nesting level before sorting, so "first" and "last"
+/// refer to input order. [`canonicalize_record`] is equivalent to
+/// [`DuplicateFieldPolicy::KeepAll`].
+///
+/// # Errors
+///
+/// Returns [`LnmpError::DuplicateFieldId`] (without position information) if the policy
+/// rejects a duplicate.
+pub fn canonicalize_record_with_policy(
+    record: &LnmpRecord,
+    policy: DuplicateFieldPolicy,
+) -> Result<LnmpRecord, LnmpError> {
+    let resolved = policy
+        .apply(record)
+        .map_err(|field_id| LnmpError::DuplicateFieldId {
+            field_id,
+            line: 0,
+            column: 0,
+        })?;
+    Ok(canonicalize_record(&resolved))
+}
+
 /// Canonicalizes a value by recursively processing nested structures
-fn canonicalize_value(value: &LnmpValue) -> LnmpValue {
+fn canonicalize_value(value: &LnmpValue, preserve_empty: bool) -> LnmpValue {
     match value {
         // Primitive values are already canonical
         LnmpValue::Int(i) => LnmpValue::Int(*i),
@@ -446,13 +552,16 @@ fn canonicalize_value(value: &LnmpValue) -> LnmpValue {
 
         // Recursively canonicalize nested record
         LnmpValue::NestedRecord(nested) => {
-            let canonical_nested = canonicalize_record(nested);
+            let canonical_nested = canonicalize(nested, preserve_empty);
             LnmpValue::NestedRecord(Box::new(canonical_nested))
         }
 
         // Recursively canonicalize each record in nested array
         LnmpValue::NestedArray(arr) => {
-            let canonical_arr: Vec<LnmpRecord> = arr.iter().map(canonicalize_record).collect();
+            let canonical_arr: Vec<LnmpRecord> = arr
+                .iter()
+                .map(|record| canonicalize(record, preserve_empty))
+                .collect();
             LnmpValue::NestedArray(canonical_arr)
         }
         // Embeddings are already canonical (binary data)
@@ -499,6 +608,35 @@ pub fn validate_round_trip_stability(record: &LnmpRecord) -> bool {
     canonical_once == canonical_twice
 }
 
+/// Rewrites exponent notation (`1.5e-7`) as positional digits (`0.00000015`)
+///
+/// Inputs without an exponent are returned unchanged.
+fn expand_exponent(s: &str) -> String {
+    let Some((mantissa, exponent)) = s.split_once(['e', 'E']) else {
+        return s.to_string();
+    };
+    let Ok(exponent) = exponent.parse::<i64>() else {
+        return s.to_string();
+    };
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i64 + exponent;
+
+    let body = if point <= 0 {
+        format!("0.{}{}", "0".repeat((-point) as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}.0", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        let (int_digits, frac_digits) = digits.split_at(point as usize);
+        format!("{}.{}", int_digits, frac_digits)
+    };
+    format!("{}{}", sign, body)
+}
+
 /// Checks if a character is safe for unquoted strings
 fn is_safe_unquoted_char(ch: char) -> bool {
     ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' || ch == '.'
@@ -1029,6 +1167,108 @@ mod tests {
         assert_eq!(output2, output3);
     }
 
+    fn encode_floats(config: EncoderConfig, values: Vec<f64>) -> String {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Float(values[0]),
+        });
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::FloatArray(values),
+        });
+        Encoder::with_config(config).encode(&record)
+    }
+
+    #[test]
+    fn test_float_format_fixed_precision() {
+        let config = EncoderConfig::new().with_float_format(FloatFormat::FixedPrecision(2));
+        let output = encode_floats(config, vec![1.0, 2.345, -0.5]);
+        assert_eq!(output, "F1=1.00\nF2=[1.00,2.35,-0.50]");
+    }
+
+    #[test]
+    fn test_float_format_shortest_round_trip() {
+        let config = EncoderConfig::new().with_float_format(FloatFormat::ShortestRoundTrip);
+        let output = encode_floats(config, vec![1.0, 1e-7, 1.5e20, 0.1]);
+        assert_eq!(
+            output,
+            "F1=1.0\nF2=[1.0,0.0000001,150000000000000000000.0,0.1]"
+        );
+
+        let hinted = encode_floats(
+            EncoderConfig::new()
+                .with_float_format(FloatFormat::ShortestRoundTrip)
+                .with_type_hints(true),
+            vec![1.0, 1e-7, 1.5e20, 0.1],
+        );
+        let parsed = crate::parser::Parser::new(&hinted)
+            .unwrap()
+            .parse_record()
+            .unwrap();
+        assert_eq!(
+            parsed.get_field(2).unwrap().value,
+            LnmpValue::FloatArray(vec![1.0, 1e-7, 1.5e20, 0.1])
+        );
+    }
+
+    #[test]
+    fn test_float_format_scientific_when_not_suppressed() {
+        let config = EncoderConfig::new()
+            .with_float_format(FloatFormat::ShortestRoundTrip)
+            .with_suppress_scientific(false);
+        let output = encode_floats(config, vec![1.5e-7, -2e21]);
+        assert_eq!(output, "F1=1.5e-7\nF2=[1.5e-7,-2e21]");
+    }
+
+    #[test]
+    fn test_float_format_default_is_display() {
+        let output = encode_floats(EncoderConfig::new(), vec![1.0, 2.5]);
+        assert_eq!(output, "F1=1\nF2=[1,2.5]");
+    }
+
+    #[test]
+    fn test_expand_exponent() {
+        assert_eq!(expand_exponent("1e-7"), "0.0000001");
+        assert_eq!(expand_exponent("-1.25e-3"), "-0.00125");
+        assert_eq!(expand_exponent("1.25e1"), "12.5");
+        assert_eq!(expand_exponent("1.5e3"), "1500.0");
+        assert_eq!(expand_exponent("3.5"), "3.5");
+    }
+
+    #[test]
+    fn test_canonicalize_record_with_policy() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 9,
+            value: LnmpValue::IntArray(vec![1]),
+        });
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::Bool(true),
+        });
+        record.add_field(LnmpField {
+            fid: 9,
+            value: LnmpValue::IntArray(vec![2, 3]),
+        });
+
+        let kept = canonicalize_record_with_policy(&record, DuplicateFieldPolicy::KeepAll).unwrap();
+        assert_eq!(kept, canonicalize_record(&record));
+
+        let merged =
+            canonicalize_record_with_policy(&record, DuplicateFieldPolicy::MergeArrays).unwrap();
+        assert_eq!(merged.fields().len(), 2);
+        assert_eq!(merged.fields()[0].fid, 2);
+        assert_eq!(merged.fields()[1].value, LnmpValue::IntArray(vec![1, 2, 3]));
+
+        let err =
+            canonicalize_record_with_policy(&record, DuplicateFieldPolicy::Error).unwrap_err();
+        assert!(matches!(
+            err,
+            LnmpError::DuplicateFieldId { field_id: 9, .. }
+        ));
+    }
+
     #[test]
     fn test_canonicalize_record_basic() {
         // Test basic field sorting
@@ -2197,6 +2437,66 @@ mod tests {
         assert_eq!(fields[1].value, LnmpValue::String("not_empty".to_string()));
     }
 
+    #[test]
+    fn test_canonicalize_preserving_empty_fields() {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::String("".to_string()),
+        });
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 30,
+            value: LnmpValue::NestedRecord(Box::new(inner)),
+        });
+        record.add_field(LnmpField {
+            fid: 23,
+            value: LnmpValue::StringArray(vec![]),
+        });
+
+        let canonical = canonicalize_record_preserving_empty(&record);
+        assert_eq!(canonical.fields().len(), 2);
+        assert_eq!(canonical.fields()[0].fid, 23);
+        match &canonical.fields()[1].value {
+            LnmpValue::NestedRecord(nested) => assert_eq!(nested.fields().len(), 1),
+            other => panic!("Expected nested record, got {:?}", other),
+        }
+        assert!(canonicalize_record(&record).fields().is_empty());
+    }
+
+    #[test]
+    fn test_encode_preserve_empty_round_trip() {
+        use crate::config::EncoderConfig;
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 23,
+            value: LnmpValue::StringArray(vec![]),
+        });
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("".to_string()),
+        });
+
+        let encoder = Encoder::with_config(EncoderConfig::new().with_preserve_empty(true));
+        let output = encoder.encode(&record);
+        assert_eq!(output, "F1=\"\"\nF23=[]");
+
+        let mut parser = crate::Parser::new(&output).unwrap();
+        let parsed = parser.parse_record().unwrap();
+        assert_eq!(
+            parsed.get_field(1).unwrap().value,
+            LnmpValue::String("".to_string())
+        );
+        assert_eq!(
+            parsed.get_field(23).unwrap().value,
+            LnmpValue::StringArray(vec![])
+        );
+
+        assert_eq!(Encoder::new().encode(&record), "");
+    }
+
     #[test]
     fn test_canonicalize_empty_field_omission_nested() {
         // Test that empty fields are omitted in nested structures
@@ -2432,6 +2732,56 @@ mod tests {
         assert!(validate_round_trip_stability(&outer));
     }
 
+    #[test]
+    fn test_encode_envelope_round_trip() {
+        use lnmp_envelope::EnvelopeBuilder;
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Bool(true),
+        });
+        let envelope = EnvelopeBuilder::new(record)
+            .timestamp(1732373147000)
+            .source("auth-service")
+            .trace_id("abc 123")
+            .expires_at(1732373207000)
+            .label("tenant", "acme")
+            .build();
+
+        let encoder = Encoder::with_config(EncoderConfig::new().with_envelope_header(true));
+        let text = encoder.encode_envelope(&envelope).unwrap();
+        assert!(text.starts_with("#ENVELOPE timestamp=1732373147000 source=auth-service"));
+        assert!(text.ends_with("F7=1\nF12=14532"));
+
+        let mut parser = crate::Parser::new(&text).unwrap();
+        let decoded = parser.parse_envelope().unwrap();
+        assert_eq!(decoded.metadata, envelope.metadata);
+        assert_eq!(decoded.record, canonicalize_record(&envelope.record));
+    }
+
+    #[test]
+    fn test_encode_envelope_header_disabled() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(42),
+        });
+        let envelope = lnmp_envelope::EnvelopeBuilder::new(record)
+            .source("auth-service")
+            .build();
+
+        assert_eq!(Encoder::new().encode_envelope(&envelope).unwrap(), "F1=42");
+
+        let encoder = Encoder::with_config(EncoderConfig::new().with_envelope_header(true));
+        let bare = LnmpEnvelope::new(envelope.record.clone());
+        assert_eq!(encoder.encode_envelope(&bare).unwrap(), "F1=42");
+    }
+
     #[test]
     fn test_is_empty_value() {
         // Test the is_empty_value helper function
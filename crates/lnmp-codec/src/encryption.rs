@@ -0,0 +1,519 @@
+//! Authenticated payload encryption for binary frames and `.lnmp` containers.
+//!
+//! Payloads are sealed with AES-256-GCM. Keys come from a [`KeyProvider`], which
+//! picks the key used to encrypt (optionally per source) and resolves key IDs
+//! when decrypting, so keys can be rotated without breaking older payloads.
+//!
+//! Sealed layout:
+//!
+//! ```text
+//! ┌──────────┬───────────┬──────────────────────────────┐
+//! │  KEY_ID  │   NONCE   │     CIPHERTEXT | TAG (16)    │
+//! │ (u32 BE) │ (12 bytes)│                              │
+//! └──────────┴───────────┴──────────────────────────────┘
+//! ```
+//!
+//! Nonces are random per payload. Callers bind surrounding headers to the
+//! ciphertext by passing them as associated data.
+//!
+//! Encryption is behind the `encryption` cargo feature. Without it the types are
+//! still available but sealing or opening yields [`EncryptionError::Unavailable`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Length of an AES-256 key in bytes
+pub const KEY_LEN: usize = 32;
+
+/// Length of the GCM nonce in bytes
+pub const NONCE_LEN: usize = 12;
+
+/// Length of the GCM authentication tag in bytes
+pub const TAG_LEN: usize = 16;
+
+/// Bytes added to a payload by [`PayloadCipher::seal`] (`KEY_ID | NONCE | TAG`)
+pub const SEALED_OVERHEAD: usize = 4 + NONCE_LEN + TAG_LEN;
+
+/// Identifier written in front of sealed payloads to select the decryption key
+pub type KeyId = u32;
+
+/// AES-256 key material
+///
+/// The `Debug` output never includes the key bytes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    /// Wraps raw key bytes
+    pub fn new(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Builds a key from a slice, which must be exactly [`KEY_LEN`] bytes
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, EncryptionError> {
+        let bytes: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| EncryptionError::InvalidKeyLength(bytes.len()))?;
+        Ok(Self(bytes))
+    }
+
+    /// Raw key bytes
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// Supplies keys for sealing and opening payloads
+pub trait KeyProvider: Send + Sync + fmt::Debug {
+    /// Returns the key ID and key used to encrypt payloads from `source`
+    ///
+    /// `source` is `None` when the caller did not name one.
+    fn encryption_key(
+        &self,
+        source: Option<&str>,
+    ) -> Result<(KeyId, EncryptionKey), EncryptionError>;
+
+    /// Returns the key registered under `key_id`
+    fn decryption_key(&self, key_id: KeyId) -> Result<EncryptionKey, EncryptionError>;
+}
+
+/// A single key used for every source
+#[derive(Debug, Clone)]
+pub struct StaticKeyProvider {
+    key_id: KeyId,
+    key: EncryptionKey,
+}
+
+impl StaticKeyProvider {
+    /// Creates a provider that uses `key` under key ID 0
+    pub fn new(key: EncryptionKey) -> Self {
+        Self { key_id: 0, key }
+    }
+
+    /// Sets the key ID written in front of sealed payloads
+    pub fn with_key_id(mut self, key_id: KeyId) -> Self {
+        self.key_id = key_id;
+        self
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn encryption_key(
+        &self,
+        _source: Option<&str>,
+    ) -> Result<(KeyId, EncryptionKey), EncryptionError> {
+        Ok((self.key_id, self.key.clone()))
+    }
+
+    fn decryption_key(&self, key_id: KeyId) -> Result<EncryptionKey, EncryptionError> {
+        if key_id == self.key_id {
+            Ok(self.key.clone())
+        } else {
+            Err(EncryptionError::UnknownKeyId(key_id))
+        }
+    }
+}
+
+/// A separate key for each source, with an optional fallback
+#[derive(Debug, Clone, Default)]
+pub struct SourceKeyProvider {
+    keys: HashMap<KeyId, EncryptionKey>,
+    sources: HashMap<String, KeyId>,
+    default_key: Option<KeyId>,
+}
+
+impl SourceKeyProvider {
+    /// Creates a provider without keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encrypts payloads from `source` with `key` under `key_id`
+    pub fn with_source(
+        mut self,
+        source: impl Into<String>,
+        key_id: KeyId,
+        key: EncryptionKey,
+    ) -> Self {
+        self.keys.insert(key_id, key);
+        self.sources.insert(source.into(), key_id);
+        self
+    }
+
+    /// Encrypts payloads from unlisted or unnamed sources with `key` under `key_id`
+    pub fn with_default(mut self, key_id: KeyId, key: EncryptionKey) -> Self {
+        self.keys.insert(key_id, key);
+        self.default_key = Some(key_id);
+        self
+    }
+}
+
+impl KeyProvider for SourceKeyProvider {
+    fn encryption_key(
+        &self,
+        source: Option<&str>,
+    ) -> Result<(KeyId, EncryptionKey), EncryptionError> {
+        let key_id = source
+            .and_then(|source| self.sources.get(source).copied())
+            .or(self.default_key)
+            .ok_or_else(|| EncryptionError::NoKeyForSource(source.map(str::to_string)))?;
+        Ok((key_id, self.decryption_key(key_id)?))
+    }
+
+    fn decryption_key(&self, key_id: KeyId) -> Result<EncryptionKey, EncryptionError> {
+        self.keys
+            .get(&key_id)
+            .cloned()
+            .ok_or(EncryptionError::UnknownKeyId(key_id))
+    }
+}
+
+/// An active key plus retired keys that can still decrypt older payloads
+#[derive(Debug, Clone)]
+pub struct RotatingKeyProvider {
+    keys: HashMap<KeyId, EncryptionKey>,
+    active: KeyId,
+}
+
+impl RotatingKeyProvider {
+    /// Creates a provider whose active key is `key` under `key_id`
+    pub fn new(key_id: KeyId, key: EncryptionKey) -> Self {
+        Self {
+            keys: HashMap::from([(key_id, key)]),
+            active: key_id,
+        }
+    }
+
+    /// Makes `key` the active key; the previous keys remain available for decryption
+    pub fn rotate(&mut self, key_id: KeyId, key: EncryptionKey) {
+        self.keys.insert(key_id, key);
+        self.active = key_id;
+    }
+
+    /// Removes a retired key; payloads sealed with it can no longer be opened
+    ///
+    /// The active key cannot be retired.
+    pub fn retire(&mut self, key_id: KeyId) -> bool {
+        key_id != self.active && self.keys.remove(&key_id).is_some()
+    }
+
+    /// ID of the key used for encryption
+    pub fn active_key_id(&self) -> KeyId {
+        self.active
+    }
+}
+
+impl KeyProvider for RotatingKeyProvider {
+    fn encryption_key(
+        &self,
+        _source: Option<&str>,
+    ) -> Result<(KeyId, EncryptionKey), EncryptionError> {
+        Ok((self.active, self.decryption_key(self.active)?))
+    }
+
+    fn decryption_key(&self, key_id: KeyId) -> Result<EncryptionKey, EncryptionError> {
+        self.keys
+            .get(&key_id)
+            .cloned()
+            .ok_or(EncryptionError::UnknownKeyId(key_id))
+    }
+}
+
+/// Seals and opens payloads with keys from a [`KeyProvider`]
+#[derive(Debug, Clone)]
+pub struct PayloadCipher {
+    keys: Arc<dyn KeyProvider>,
+    source: Option<String>,
+}
+
+impl PayloadCipher {
+    /// Creates a cipher that asks `keys` for keys without naming a source
+    pub fn new(keys: Arc<dyn KeyProvider>) -> Self {
+        Self { keys, source: None }
+    }
+
+    /// Names the source passed to [`KeyProvider::encryption_key`]
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Key provider backing this cipher
+    pub fn key_provider(&self) -> &Arc<dyn KeyProvider> {
+        &self.keys
+    }
+
+    /// Encrypts `plaintext`, authenticating `aad` alongside it
+    pub fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let (key_id, key) = self.keys.encryption_key(self.source.as_deref())?;
+        seal_with_key(key_id, &key, plaintext, aad)
+    }
+
+    /// Decrypts a payload produced by [`seal`](Self::seal) with the same `aad`
+    pub fn open(&self, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        open(self.keys.as_ref(), sealed, aad)
+    }
+}
+
+/// Decrypts a sealed payload, looking up its key in `keys`
+pub fn open(keys: &dyn KeyProvider, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    if sealed.len() < SEALED_OVERHEAD {
+        return Err(EncryptionError::Truncated(sealed.len()));
+    }
+    let key_id = sealed_key_id(sealed)?;
+    let key = keys.decryption_key(key_id)?;
+    open_with_key(
+        &key,
+        &sealed[4..4 + NONCE_LEN],
+        &sealed[4 + NONCE_LEN..],
+        aad,
+    )
+}
+
+/// Reads the key ID of a sealed payload without decrypting it
+pub fn sealed_key_id(sealed: &[u8]) -> Result<KeyId, EncryptionError> {
+    let bytes: [u8; 4] = sealed
+        .get(..4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EncryptionError::Truncated(sealed.len()))?;
+    Ok(KeyId::from_be_bytes(bytes))
+}
+
+#[cfg(feature = "encryption")]
+fn seal_with_key(
+    key_id: KeyId,
+    key: &EncryptionKey,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use aes_gcm::Aes256Gcm;
+
+    let cipher = Aes256Gcm::new(key.as_bytes().into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| EncryptionError::EncryptionFailed)?;
+
+    let mut sealed = Vec::with_capacity(4 + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&key_id.to_be_bytes());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn seal_with_key(
+    _key_id: KeyId,
+    _key: &EncryptionKey,
+    _plaintext: &[u8],
+    _aad: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    Err(EncryptionError::Unavailable)
+}
+
+#[cfg(feature = "encryption")]
+fn open_with_key(
+    key: &EncryptionKey,
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Nonce};
+
+    let cipher = Aes256Gcm::new(key.as_bytes().into());
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| EncryptionError::AuthenticationFailed)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn open_with_key(
+    _key: &EncryptionKey,
+    _nonce: &[u8],
+    _ciphertext: &[u8],
+    _aad: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    Err(EncryptionError::Unavailable)
+}
+
+/// Errors raised while encrypting or decrypting payloads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    /// The `encryption` cargo feature is not enabled
+    Unavailable,
+    /// The payload is encrypted but no key provider was configured
+    MissingKeyProvider,
+    /// The key provider has no key for the source (`None` when unnamed)
+    NoKeyForSource(Option<String>),
+    /// The key ID of a sealed payload is not known to the key provider
+    UnknownKeyId(KeyId),
+    /// Key material has the wrong length
+    InvalidKeyLength(usize),
+    /// The sealed payload is shorter than its fixed overhead
+    Truncated(usize),
+    /// The ciphertext, tag or associated data do not match the key
+    AuthenticationFailed,
+    /// The cipher refused to encrypt the payload
+    EncryptionFailed,
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::Unavailable => {
+                write!(f, "encryption support is not enabled")
+            }
+            EncryptionError::MissingKeyProvider => {
+                write!(f, "payload is encrypted but no key provider is configured")
+            }
+            EncryptionError::NoKeyForSource(Some(source)) => {
+                write!(f, "no encryption key for source '{}'", source)
+            }
+            EncryptionError::NoKeyForSource(None) => {
+                write!(f, "no default encryption key")
+            }
+            EncryptionError::UnknownKeyId(key_id) => write!(f, "unknown key ID {}", key_id),
+            EncryptionError::InvalidKeyLength(len) => {
+                write!(f, "key is {} bytes but {} are required", len, KEY_LEN)
+            }
+            EncryptionError::Truncated(len) => write!(
+                f,
+                "sealed payload of {} bytes is shorter than the {} byte overhead",
+                len, SEALED_OVERHEAD
+            ),
+            EncryptionError::AuthenticationFailed => {
+                write!(
+                    f,
+                    "payload failed authentication (wrong key or tampered data)"
+                )
+            }
+            EncryptionError::EncryptionFailed => write!(f, "payload encryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::new([byte; KEY_LEN])
+    }
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let cipher = PayloadCipher::new(Arc::new(StaticKeyProvider::new(key(1)).with_key_id(9)));
+        let sealed = cipher.seal(b"F12=14532", b"header").unwrap();
+        assert_eq!(sealed.len(), 9 + SEALED_OVERHEAD);
+        assert_eq!(sealed_key_id(&sealed).unwrap(), 9);
+        assert_eq!(cipher.open(&sealed, b"header").unwrap(), b"F12=14532");
+    }
+
+    #[test]
+    fn test_nonces_are_unique() {
+        let cipher = PayloadCipher::new(Arc::new(StaticKeyProvider::new(key(1))));
+        let a = cipher.seal(b"same", b"").unwrap();
+        let b = cipher.seal(b"same", b"").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let cipher = PayloadCipher::new(Arc::new(StaticKeyProvider::new(key(1))));
+        let mut sealed = cipher.seal(b"payload", b"aad").unwrap();
+        assert_eq!(
+            cipher.open(&sealed, b"other"),
+            Err(EncryptionError::AuthenticationFailed)
+        );
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        assert_eq!(
+            cipher.open(&sealed, b"aad"),
+            Err(EncryptionError::AuthenticationFailed)
+        );
+        assert_eq!(
+            cipher.open(&sealed[..10], b"aad"),
+            Err(EncryptionError::Truncated(10))
+        );
+    }
+
+    #[test]
+    fn test_source_keys() {
+        let provider = SourceKeyProvider::new()
+            .with_source("sensor-a", 1, key(1))
+            .with_source("sensor-b", 2, key(2));
+        let keys: Arc<dyn KeyProvider> = Arc::new(provider);
+
+        let sealed = PayloadCipher::new(keys.clone())
+            .with_source("sensor-b")
+            .seal(b"reading", b"")
+            .unwrap();
+        assert_eq!(sealed_key_id(&sealed).unwrap(), 2);
+        assert_eq!(open(keys.as_ref(), &sealed, b"").unwrap(), b"reading");
+
+        assert_eq!(
+            PayloadCipher::new(keys.clone())
+                .with_source("sensor-c")
+                .seal(b"reading", b""),
+            Err(EncryptionError::NoKeyForSource(Some("sensor-c".into())))
+        );
+        assert_eq!(
+            PayloadCipher::new(keys).seal(b"reading", b""),
+            Err(EncryptionError::NoKeyForSource(None))
+        );
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_keys_readable() {
+        let mut provider = RotatingKeyProvider::new(1, key(1));
+        let old = PayloadCipher::new(Arc::new(provider.clone()))
+            .seal(b"old", b"")
+            .unwrap();
+
+        provider.rotate(2, key(2));
+        assert_eq!(provider.active_key_id(), 2);
+        assert!(!provider.retire(2));
+        let cipher = PayloadCipher::new(Arc::new(provider.clone()));
+        let new = cipher.seal(b"new", b"").unwrap();
+        assert_eq!(sealed_key_id(&new).unwrap(), 2);
+        assert_eq!(cipher.open(&old, b"").unwrap(), b"old");
+
+        assert!(provider.retire(1));
+        assert_eq!(
+            open(&provider, &old, b""),
+            Err(EncryptionError::UnknownKeyId(1))
+        );
+    }
+
+    #[test]
+    fn test_key_from_slice_checks_length() {
+        assert_eq!(
+            EncryptionKey::from_slice(&[0u8; 16]),
+            Err(EncryptionError::InvalidKeyLength(16))
+        );
+        assert_eq!(format!("{:?}", key(7)), "EncryptionKey(..)");
+    }
+}
@@ -23,10 +23,68 @@
 //! assert_eq!(mapper.map(7, "no"), Some("0".to_string()));
 //! assert_eq!(mapper.map(7, "unmapped"), None);
 //! ```
+//!
+//! Numeric fields can additionally be given a tolerance and unit scales so that
+//! benign float noise or unit differences do not break equality checks:
+//!
+//! ```
+//! use lnmp_codec::equivalence::{EquivalenceMapper, NumericTolerance};
+//! use lnmp_core::LnmpValue;
+//!
+//! let mut mapper = EquivalenceMapper::new();
+//! mapper.set_numeric_tolerance(5, NumericTolerance::absolute(1e-6));
+//! mapper.add_unit_scale(5, "mm", 0.001);
+//!
+//! assert!(mapper.values_equivalent(5, &LnmpValue::Float(2.5000001), &LnmpValue::Float(2.5)));
+//! assert!(mapper.values_equivalent(
+//!     5,
+//!     &LnmpValue::String("1500mm".to_string()),
+//!     &LnmpValue::Float(1.5),
+//! ));
+//! ```
 
-use lnmp_core::FieldId;
+use lnmp_core::{FieldId, LnmpRecord, LnmpValue};
 use std::collections::HashMap;
 
+/// Tolerance used when comparing numeric values of a field.
+///
+/// Two numbers `a` and `b` are equivalent when
+/// `|a - b| <= max(absolute, relative * max(|a|, |b|))`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericTolerance {
+    /// Absolute tolerance
+    pub absolute: f64,
+    /// Relative tolerance (fraction of the larger magnitude)
+    pub relative: f64,
+}
+
+impl NumericTolerance {
+    /// Creates an absolute-only tolerance
+    pub fn absolute(absolute: f64) -> Self {
+        Self {
+            absolute,
+            relative: 0.0,
+        }
+    }
+
+    /// Creates a relative-only tolerance
+    pub fn relative(relative: f64) -> Self {
+        Self {
+            absolute: 0.0,
+            relative,
+        }
+    }
+
+    /// Returns true if `a` and `b` are within this tolerance
+    pub fn within(&self, a: f64, b: f64) -> bool {
+        if a == b {
+            return true;
+        }
+        let bound = self.absolute.max(self.relative * a.abs().max(b.abs()));
+        (a - b).abs() <= bound
+    }
+}
+
 /// Equivalence mapper for semantic synonym mapping
 ///
 /// Maps field values to their canonical forms based on field-specific
@@ -36,6 +94,10 @@ use std::collections::HashMap;
 pub struct EquivalenceMapper {
     /// Field-specific mappings: FieldId → (from_value → to_value)
     mappings: HashMap<FieldId, HashMap<String, String>>,
+    /// Field-specific numeric tolerances
+    tolerances: HashMap<FieldId, NumericTolerance>,
+    /// Field-specific unit scales: FieldId → (unit suffix → factor to base unit)
+    unit_scales: HashMap<FieldId, HashMap<String, f64>>,
 }
 
 impl EquivalenceMapper {
@@ -43,6 +105,8 @@ impl EquivalenceMapper {
     pub fn new() -> Self {
         Self {
             mappings: HashMap::new(),
+            tolerances: HashMap::new(),
+            unit_scales: HashMap::new(),
         }
     }
 
@@ -202,6 +266,187 @@ impl EquivalenceMapper {
     /// Clears all mappings
     pub fn clear(&mut self) {
         self.mappings.clear();
+        self.tolerances.clear();
+        self.unit_scales.clear();
+    }
+
+    /// Sets the numeric tolerance for a specific field
+    ///
+    /// Without a tolerance, numeric values of the field must match exactly.
+    pub fn set_numeric_tolerance(&mut self, fid: FieldId, tolerance: NumericTolerance) {
+        self.tolerances.insert(fid, tolerance);
+    }
+
+    /// Returns the numeric tolerance configured for a field, if any
+    pub fn numeric_tolerance(&self, fid: FieldId) -> Option<NumericTolerance> {
+        self.tolerances.get(&fid).copied()
+    }
+
+    /// Registers a unit suffix for a field with its factor to the field's base unit
+    ///
+    /// String values such as `"1500mm"` are converted to the base unit
+    /// (`1500 * 0.001 = 1.5`) before numeric comparison.
+    ///
+    /// # Arguments
+    ///
+    /// * `fid` - The field ID the unit applies to
+    /// * `unit` - The unit suffix (e.g. `"mm"`)
+    /// * `factor` - Multiplier converting the unit to the base unit
+    pub fn add_unit_scale(&mut self, fid: FieldId, unit: &str, factor: f64) {
+        self.unit_scales
+            .entry(fid)
+            .or_default()
+            .insert(unit.to_string(), factor);
+    }
+
+    /// Interprets a value as a number in the field's base unit
+    ///
+    /// Accepts `Int`, `Float`, and strings holding a number with an optional
+    /// registered unit suffix.
+    pub fn numeric_value(&self, fid: FieldId, value: &LnmpValue) -> Option<f64> {
+        match value {
+            LnmpValue::Int(i) => Some(*i as f64),
+            LnmpValue::Float(f) => Some(*f),
+            LnmpValue::String(s) => self.parse_scaled(fid, s),
+            _ => None,
+        }
+    }
+
+    fn parse_scaled(&self, fid: FieldId, s: &str) -> Option<f64> {
+        let s = s.trim();
+        if let Ok(n) = s.parse::<f64>() {
+            return Some(n);
+        }
+        let units = self.unit_scales.get(&fid)?;
+        // Prefer the longest matching suffix so "mm" wins over "m".
+        units
+            .iter()
+            .filter(|(unit, _)| s.ends_with(unit.as_str()))
+            .max_by_key(|(unit, _)| unit.len())
+            .and_then(|(unit, factor)| {
+                s[..s.len() - unit.len()]
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .map(|n| n * factor)
+            })
+    }
+
+    /// Checks whether two numbers are equivalent for a field
+    ///
+    /// Uses the field's tolerance if one is configured, exact equality otherwise.
+    pub fn numbers_equivalent(&self, fid: FieldId, a: f64, b: f64) -> bool {
+        match self.tolerances.get(&fid) {
+            Some(tolerance) => tolerance.within(a, b),
+            None => a == b,
+        }
+    }
+
+    /// Returns true if the field has a numeric tolerance or unit scales
+    fn is_numeric_field(&self, fid: FieldId) -> bool {
+        self.tolerances.contains_key(&fid) || self.unit_scales.contains_key(&fid)
+    }
+
+    /// Checks whether two values are semantically equivalent for a field
+    ///
+    /// Numeric values are compared using the field's tolerance, strings are
+    /// compared after synonym mapping, and arrays and nested records are compared
+    /// element-wise. Strings are only read as numbers (with optional unit suffix)
+    /// on fields that have a tolerance or unit scales, so `"1.0"` and `"1"` are
+    /// distinct by default.
+    pub fn values_equivalent(&self, fid: FieldId, a: &LnmpValue, b: &LnmpValue) -> bool {
+        if a == b {
+            return true;
+        }
+        match (a, b) {
+            (LnmpValue::FloatArray(xs), LnmpValue::FloatArray(ys)) => {
+                xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .zip(ys)
+                        .all(|(x, y)| self.numbers_equivalent(fid, *x, *y))
+            }
+            (LnmpValue::IntArray(xs), LnmpValue::FloatArray(ys))
+            | (LnmpValue::FloatArray(ys), LnmpValue::IntArray(xs)) => {
+                xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .zip(ys)
+                        .all(|(x, y)| self.numbers_equivalent(fid, *x as f64, *y))
+            }
+            (LnmpValue::NestedRecord(x), LnmpValue::NestedRecord(y)) => {
+                self.records_equivalent(x, y)
+            }
+            (LnmpValue::NestedArray(xs), LnmpValue::NestedArray(ys)) => {
+                xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .zip(ys)
+                        .all(|(x, y)| self.records_equivalent(x, y))
+            }
+            _ => {
+                let has_string =
+                    matches!(a, LnmpValue::String(_)) || matches!(b, LnmpValue::String(_));
+                if !has_string || self.is_numeric_field(fid) {
+                    if let (Some(x), Some(y)) =
+                        (self.numeric_value(fid, a), self.numeric_value(fid, b))
+                    {
+                        return self.numbers_equivalent(fid, x, y);
+                    }
+                }
+                match (a, b) {
+                    (LnmpValue::String(x), LnmpValue::String(y)) => {
+                        let x = self.map(fid, x).unwrap_or_else(|| x.clone());
+                        let y = self.map(fid, y).unwrap_or_else(|| y.clone());
+                        x == y
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Checks whether two records are semantically equivalent
+    ///
+    /// Fields are matched by FID regardless of order; both records must contain
+    /// the same set of FIDs.
+    pub fn records_equivalent(&self, a: &LnmpRecord, b: &LnmpRecord) -> bool {
+        let a_sorted = a.sorted_fields();
+        let b_sorted = b.sorted_fields();
+        a_sorted.len() == b_sorted.len()
+            && a_sorted
+                .iter()
+                .zip(&b_sorted)
+                .all(|(x, y)| x.fid == y.fid && self.values_equivalent(x.fid, &x.value, &y.value))
+    }
+
+    /// Snaps a numeric value onto the field's tolerance grid
+    ///
+    /// Values closer together than the absolute tolerance will usually quantize to
+    /// the same number, which makes them hash identically when computing semantic
+    /// checksums. Values without a tolerance (or with a zero absolute tolerance) are
+    /// returned unchanged; unit-suffixed strings are converted to the base unit.
+    pub fn canonicalize_value(&self, fid: FieldId, value: &LnmpValue) -> LnmpValue {
+        let step = self
+            .tolerances
+            .get(&fid)
+            .map(|t| t.absolute)
+            .filter(|step| *step > 0.0);
+        let snap = |f: f64| match step {
+            Some(step) => (f / step).round() * step,
+            None => f,
+        };
+        match value {
+            LnmpValue::Float(f) => LnmpValue::Float(snap(*f)),
+            LnmpValue::FloatArray(fs) => {
+                LnmpValue::FloatArray(fs.iter().map(|f| snap(*f)).collect())
+            }
+            LnmpValue::String(s) => match self.parse_scaled(fid, s) {
+                Some(n) if s.trim().parse::<f64>().is_err() => LnmpValue::Float(snap(n)),
+                _ => value.clone(),
+            },
+            _ => value.clone(),
+        }
     }
 }
 
@@ -448,4 +693,143 @@ mod tests {
         assert_eq!(mapper.map(12, "café"), Some("coffee_shop".to_string()));
         assert_eq!(mapper.map(12, "日本"), Some("japan".to_string()));
     }
+
+    #[test]
+    fn test_numeric_tolerance_absolute() {
+        let mut mapper = EquivalenceMapper::new();
+        mapper.set_numeric_tolerance(5, NumericTolerance::absolute(1e-6));
+
+        let a = LnmpValue::Float(2.5000001);
+        let b = LnmpValue::Float(2.5);
+        assert!(mapper.values_equivalent(5, &a, &b));
+        assert!(!mapper.values_equivalent(5, &a, &LnmpValue::Float(3.15)));
+        // No tolerance on other fields
+        assert!(!mapper.values_equivalent(6, &a, &b));
+    }
+
+    #[test]
+    fn test_numeric_tolerance_relative() {
+        let tolerance = NumericTolerance::relative(0.01);
+        assert!(tolerance.within(1000.0, 1009.0));
+        assert!(!tolerance.within(1000.0, 1011.0));
+        assert!(tolerance.within(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_int_float_equivalence() {
+        let mapper = EquivalenceMapper::new();
+        assert!(mapper.values_equivalent(1, &LnmpValue::Int(3), &LnmpValue::Float(3.0)));
+        assert!(!mapper.values_equivalent(1, &LnmpValue::Int(3), &LnmpValue::Float(3.5)));
+    }
+
+    #[test]
+    fn test_numeric_strings_are_distinct_without_tolerance() {
+        let one = LnmpValue::String("1".to_string());
+        let one_point_zero = LnmpValue::String("1.0".to_string());
+
+        let mut mapper = EquivalenceMapper::new();
+        assert!(!mapper.values_equivalent(4, &one, &one_point_zero));
+        assert!(!mapper.values_equivalent(4, &one, &LnmpValue::Int(1)));
+
+        mapper.set_numeric_tolerance(4, NumericTolerance::absolute(0.0));
+        assert!(mapper.values_equivalent(4, &one, &one_point_zero));
+        assert!(mapper.values_equivalent(4, &one, &LnmpValue::Int(1)));
+    }
+
+    #[test]
+    fn test_unit_scaled_comparison() {
+        let mut mapper = EquivalenceMapper::new();
+        mapper.add_unit_scale(9, "m", 1.0);
+        mapper.add_unit_scale(9, "mm", 0.001);
+        mapper.add_unit_scale(9, "km", 1000.0);
+
+        let meters = LnmpValue::Float(1.5);
+        assert!(mapper.values_equivalent(9, &LnmpValue::String("1500mm".to_string()), &meters));
+        assert!(mapper.values_equivalent(
+            9,
+            &LnmpValue::String("0.0015km".to_string()),
+            &LnmpValue::String("1.5 m".to_string())
+        ));
+        assert_eq!(
+            mapper.numeric_value(9, &LnmpValue::String("2mm".to_string())),
+            Some(0.002)
+        );
+        assert_eq!(
+            mapper.numeric_value(9, &LnmpValue::String("2ft".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_string_values_use_synonym_mappings() {
+        let mut mapper = EquivalenceMapper::new();
+        mapper.add_mapping(12, "admin".to_string(), "administrator".to_string());
+
+        assert!(mapper.values_equivalent(
+            12,
+            &LnmpValue::String("admin".to_string()),
+            &LnmpValue::String("administrator".to_string())
+        ));
+        assert!(!mapper.values_equivalent(
+            12,
+            &LnmpValue::String("admin".to_string()),
+            &LnmpValue::String("user".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_float_array_and_record_equivalence() {
+        use lnmp_core::LnmpField;
+
+        let mut mapper = EquivalenceMapper::new();
+        mapper.set_numeric_tolerance(3, NumericTolerance::absolute(1e-4));
+
+        let mut a = LnmpRecord::new();
+        a.add_field(LnmpField {
+            fid: 3,
+            value: LnmpValue::FloatArray(vec![0.1, 0.2]),
+        });
+        a.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(7),
+        });
+        let mut b = LnmpRecord::new();
+        b.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(7),
+        });
+        b.add_field(LnmpField {
+            fid: 3,
+            value: LnmpValue::FloatArray(vec![0.10001, 0.19999]),
+        });
+
+        assert!(mapper.records_equivalent(&a, &b));
+
+        b.add_field(LnmpField {
+            fid: 4,
+            value: LnmpValue::Bool(true),
+        });
+        assert!(!mapper.records_equivalent(&a, &b));
+    }
+
+    #[test]
+    fn test_canonicalize_value_snaps_to_tolerance() {
+        let mut mapper = EquivalenceMapper::new();
+        mapper.set_numeric_tolerance(5, NumericTolerance::absolute(0.001));
+        mapper.add_unit_scale(5, "mm", 0.001);
+
+        assert_eq!(
+            mapper.canonicalize_value(5, &LnmpValue::Float(2.5000001)),
+            mapper.canonicalize_value(5, &LnmpValue::Float(2.5))
+        );
+        assert_eq!(
+            mapper.canonicalize_value(5, &LnmpValue::String("1500mm".to_string())),
+            LnmpValue::Float(1.5)
+        );
+        // Untoleranced fields are untouched
+        assert_eq!(
+            mapper.canonicalize_value(6, &LnmpValue::Float(2.5000001)),
+            LnmpValue::Float(2.5000001)
+        );
+    }
 }
@@ -221,6 +221,15 @@ pub enum LnmpError {
         /// Column where error was detected
         column: usize,
     },
+    /// Malformed `#ENVELOPE` header line
+    InvalidEnvelope {
+        /// Reason the header was rejected
+        reason: String,
+        /// Line number of the header
+        line: usize,
+        /// Column number where the error occurred
+        column: usize,
+    },
     /// Validation error (e.g. field ordering violation)
     ValidationError(String),
     /// FID Registry validation error (v0.5.14)
@@ -294,6 +303,7 @@ impl LnmpError {
             LnmpError::InvalidNestedStructure { line, column, .. } => (*line, *column),
             LnmpError::DuplicateFieldId { line, column, .. } => (*line, *column),
             LnmpError::UnclosedNestedStructure { line, column, .. } => (*line, *column),
+            LnmpError::InvalidEnvelope { line, column, .. } => (*line, *column),
             LnmpError::ValidationError(_) => (0, 0), // Validation errors might not have specific line/col
             LnmpError::FidValidation { line, column, .. } => (*line, *column),
         }
@@ -432,6 +442,15 @@ impl std::fmt::Display for LnmpError {
                 "DuplicateFieldId: Field ID {} appears multiple times at line {}, column {}",
                 field_id, line, column
             ),
+            LnmpError::InvalidEnvelope {
+                reason,
+                line,
+                column,
+            } => write!(
+                f,
+                "Invalid envelope header at line {}, column {}: {}",
+                line, column, reason
+            ),
             LnmpError::ValidationError(msg) => write!(f, "Validation Error: {}", msg),
             LnmpError::FidValidation { fid, reason, line, column } => write!(
                 f,
@@ -0,0 +1,503 @@
+//! Conversion between LNMP records and `serde_json::Value`.
+//!
+//! Two representations are available, selected with [`JsonMode`]:
+//!
+//! - [`JsonMode::Plain`] maps values onto their natural JSON counterparts
+//!   (`{"12":14532,"7":true}`). Type distinctions that JSON cannot express, such as
+//!   `Int(3)` vs `Float(3.0)`, are inferred on the way back.
+//! - [`JsonMode::Tagged`] wraps every value in a `{"t":<type hint>,"v":<value>}`
+//!   object (`{"12":{"t":"i","v":14532}}`) so records round-trip through JSON
+//!   without losing Int/Float/Bool distinctions. Tags are the LNMP type hint codes.
+//!
+//! Records are JSON objects keyed by the decimal field ID.
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_codec::json::{record_from_json, record_to_json, JsonMode};
+//! use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(14532) });
+//! record.add_field(LnmpField { fid: 5, value: LnmpValue::Float(2.0) });
+//!
+//! let tagged = record_to_json(&record, JsonMode::Tagged).unwrap();
+//! assert_eq!(tagged["12"]["t"], "i");
+//! assert_eq!(tagged["12"]["v"], 14532);
+//!
+//! let back = record_from_json(&tagged, JsonMode::Tagged).unwrap();
+//! assert_eq!(back.get_field(5).unwrap().value, LnmpValue::Float(2.0));
+//! ```
+
+use std::fmt;
+
+use lnmp_core::coercion::{CoercionEngine, CoercionError};
+use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpValue, TypeHint};
+use lnmp_embedding::Vector;
+use serde_json::{Map, Number, Value};
+
+/// Selects the JSON representation used for LNMP values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonMode {
+    /// Natural JSON mapping; types are inferred when converting back.
+    #[default]
+    Plain,
+    /// `{"t":<type hint>,"v":<value>}` wrappers preserving the exact LNMP type.
+    Tagged,
+}
+
+/// Errors that can occur while converting between LNMP and JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonError {
+    /// The JSON document does not have the expected shape.
+    InvalidShape {
+        /// Location of the offending value (e.g. `F12`)
+        path: String,
+        /// Description of the problem
+        reason: String,
+    },
+    /// An object key is not a valid field ID.
+    InvalidFieldId(String),
+    /// A tagged value uses an unknown type tag.
+    UnknownTag(String),
+    /// The value cannot be represented in JSON.
+    Unsupported {
+        /// Field containing the value
+        fid: FieldId,
+        /// Value kind that is not supported
+        kind: &'static str,
+    },
+    /// A float is NaN or infinite and has no JSON representation.
+    NonFiniteFloat(FieldId),
+    /// A value could not be coerced to its target type.
+    Coercion(CoercionError),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::InvalidShape { path, reason } => write!(f, "{}: {}", path, reason),
+            JsonError::InvalidFieldId(key) => write!(f, "invalid field ID key: {:?}", key),
+            JsonError::UnknownTag(tag) => write!(f, "unknown type tag: {:?}", tag),
+            JsonError::Unsupported { fid, kind } => {
+                write!(f, "F{}: {} values cannot be converted to JSON", fid, kind)
+            }
+            JsonError::NonFiniteFloat(fid) => {
+                write!(f, "F{}: non-finite float cannot be converted to JSON", fid)
+            }
+            JsonError::Coercion(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<CoercionError> for JsonError {
+    fn from(err: CoercionError) -> Self {
+        JsonError::Coercion(err)
+    }
+}
+
+/// Converts a record into a JSON object keyed by field ID.
+pub fn record_to_json(record: &LnmpRecord, mode: JsonMode) -> Result<Value, JsonError> {
+    let mut map = Map::new();
+    for field in record.fields() {
+        map.insert(
+            field.fid.to_string(),
+            value_to_json(field.fid, &field.value, mode)?,
+        );
+    }
+    Ok(Value::Object(map))
+}
+
+/// Converts a JSON object keyed by field ID into a record.
+pub fn record_from_json(json: &Value, mode: JsonMode) -> Result<LnmpRecord, JsonError> {
+    let map = json.as_object().ok_or_else(|| JsonError::InvalidShape {
+        path: "$".to_string(),
+        reason: "expected a JSON object".to_string(),
+    })?;
+    let mut record = LnmpRecord::new();
+    for (key, value) in map {
+        let fid: FieldId = key
+            .strip_prefix('F')
+            .unwrap_or(key)
+            .parse()
+            .map_err(|_| JsonError::InvalidFieldId(key.clone()))?;
+        record.add_field(LnmpField {
+            fid,
+            value: value_from_json(fid, value, mode)?,
+        });
+    }
+    Ok(record)
+}
+
+/// Converts a JSON object into a record and coerces fields to their target types.
+///
+/// Uses the same [`CoercionEngine`] as the parser's loose mode, so `{"7":"yes"}`
+/// and `F7=yes` produce the same value for a boolean field.
+pub fn record_from_json_coerced(
+    json: &Value,
+    mode: JsonMode,
+    engine: &CoercionEngine,
+) -> Result<LnmpRecord, JsonError> {
+    let record = record_from_json(json, mode)?;
+    Ok(engine.coerce_record(&record)?)
+}
+
+/// Converts a single field value into JSON.
+pub fn value_to_json(fid: FieldId, value: &LnmpValue, mode: JsonMode) -> Result<Value, JsonError> {
+    let plain = match value {
+        LnmpValue::Int(i) => Value::from(*i),
+        LnmpValue::Float(f) => float_to_json(fid, *f)?,
+        LnmpValue::Bool(b) => Value::Bool(*b),
+        LnmpValue::String(s) => Value::String(s.clone()),
+        LnmpValue::StringArray(items) => items.iter().cloned().map(Value::String).collect(),
+        LnmpValue::IntArray(items) => items.iter().copied().map(Value::from).collect(),
+        LnmpValue::FloatArray(items) => items
+            .iter()
+            .map(|f| float_to_json(fid, *f))
+            .collect::<Result<Value, _>>()?,
+        LnmpValue::BoolArray(items) => items.iter().copied().map(Value::Bool).collect(),
+        LnmpValue::NestedRecord(record) => record_to_json(record, mode)?,
+        LnmpValue::NestedArray(records) => records
+            .iter()
+            .map(|r| record_to_json(r, mode))
+            .collect::<Result<Value, _>>()?,
+        LnmpValue::Embedding(vector) => vector
+            .as_f32()
+            .map_err(|_| JsonError::Unsupported {
+                fid,
+                kind: "non-f32 embedding",
+            })?
+            .into_iter()
+            .map(|f| float_to_json(fid, f as f64))
+            .collect::<Result<Value, _>>()?,
+        LnmpValue::EmbeddingDelta(_) => {
+            return Err(JsonError::Unsupported {
+                fid,
+                kind: "embedding delta",
+            })
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            return Err(JsonError::Unsupported {
+                fid,
+                kind: "quantized embedding",
+            })
+        }
+    };
+
+    Ok(match mode {
+        JsonMode::Plain => plain,
+        JsonMode::Tagged => {
+            let mut map = Map::new();
+            map.insert("t".to_string(), Value::String(tag_of(value).to_string()));
+            map.insert("v".to_string(), plain);
+            Value::Object(map)
+        }
+    })
+}
+
+/// Converts a JSON value into a field value.
+pub fn value_from_json(fid: FieldId, json: &Value, mode: JsonMode) -> Result<LnmpValue, JsonError> {
+    match mode {
+        JsonMode::Plain => infer_value(fid, json, mode),
+        JsonMode::Tagged => {
+            let obj = json
+                .as_object()
+                .ok_or_else(|| shape(fid, "expected a tagged object"))?;
+            let tag = obj
+                .get("t")
+                .and_then(Value::as_str)
+                .ok_or_else(|| shape(fid, "missing string tag \"t\""))?;
+            let v = obj
+                .get("v")
+                .ok_or_else(|| shape(fid, "missing value \"v\""))?;
+            let hint =
+                TypeHint::parse(tag).ok_or_else(|| JsonError::UnknownTag(tag.to_string()))?;
+            typed_value(fid, hint, v, mode)
+        }
+    }
+}
+
+fn tag_of(value: &LnmpValue) -> &'static str {
+    match value {
+        LnmpValue::Int(_) => TypeHint::Int.as_str(),
+        LnmpValue::Float(_) => TypeHint::Float.as_str(),
+        LnmpValue::Bool(_) => TypeHint::Bool.as_str(),
+        LnmpValue::String(_) => TypeHint::String.as_str(),
+        LnmpValue::StringArray(_) => TypeHint::StringArray.as_str(),
+        LnmpValue::IntArray(_) => TypeHint::IntArray.as_str(),
+        LnmpValue::FloatArray(_) => TypeHint::FloatArray.as_str(),
+        LnmpValue::BoolArray(_) => TypeHint::BoolArray.as_str(),
+        LnmpValue::NestedRecord(_) => TypeHint::Record.as_str(),
+        LnmpValue::NestedArray(_) => TypeHint::RecordArray.as_str(),
+        _ => TypeHint::Embedding.as_str(),
+    }
+}
+
+fn typed_value(
+    fid: FieldId,
+    hint: TypeHint,
+    v: &Value,
+    mode: JsonMode,
+) -> Result<LnmpValue, JsonError> {
+    let array = || v.as_array().ok_or_else(|| shape(fid, "expected an array"));
+    Ok(match hint {
+        TypeHint::Int => LnmpValue::Int(as_int(fid, v)?),
+        TypeHint::Float => LnmpValue::Float(as_float(fid, v)?),
+        TypeHint::Bool => LnmpValue::Bool(as_bool(fid, v)?),
+        TypeHint::String => LnmpValue::String(as_string(fid, v)?),
+        TypeHint::StringArray => LnmpValue::StringArray(
+            array()?
+                .iter()
+                .map(|item| as_string(fid, item))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeHint::IntArray => LnmpValue::IntArray(
+            array()?
+                .iter()
+                .map(|item| as_int(fid, item))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeHint::FloatArray => LnmpValue::FloatArray(
+            array()?
+                .iter()
+                .map(|item| as_float(fid, item))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeHint::BoolArray => LnmpValue::BoolArray(
+            array()?
+                .iter()
+                .map(|item| as_bool(fid, item))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeHint::Record => LnmpValue::NestedRecord(Box::new(record_from_json(v, mode)?)),
+        TypeHint::RecordArray => LnmpValue::NestedArray(
+            array()?
+                .iter()
+                .map(|item| record_from_json(item, mode))
+                .collect::<Result<_, _>>()?,
+        ),
+        TypeHint::Embedding => LnmpValue::Embedding(Vector::from_f32(
+            array()?
+                .iter()
+                .map(|item| as_float(fid, item).map(|f| f as f32))
+                .collect::<Result<_, _>>()?,
+        )),
+        #[allow(unreachable_patterns)]
+        other => return Err(JsonError::UnknownTag(other.as_str().to_string())),
+    })
+}
+
+fn infer_value(fid: FieldId, json: &Value, mode: JsonMode) -> Result<LnmpValue, JsonError> {
+    Ok(match json {
+        Value::Bool(b) => LnmpValue::Bool(*b),
+        Value::Number(n) => number_value(n),
+        Value::String(s) => LnmpValue::String(s.clone()),
+        Value::Object(_) => LnmpValue::NestedRecord(Box::new(record_from_json(json, mode)?)),
+        Value::Array(items) => infer_array(fid, items, mode)?,
+        Value::Null => return Err(shape(fid, "null has no LNMP representation")),
+    })
+}
+
+fn infer_array(fid: FieldId, items: &[Value], mode: JsonMode) -> Result<LnmpValue, JsonError> {
+    if items.is_empty() || items.iter().all(Value::is_string) {
+        return typed_value(fid, TypeHint::StringArray, &Value::from(items), mode);
+    }
+    if items.iter().all(Value::is_boolean) {
+        return typed_value(fid, TypeHint::BoolArray, &Value::from(items), mode);
+    }
+    if items.iter().all(Value::is_object) {
+        return typed_value(fid, TypeHint::RecordArray, &Value::from(items), mode);
+    }
+    if items.iter().all(|v| v.is_i64()) {
+        return typed_value(fid, TypeHint::IntArray, &Value::from(items), mode);
+    }
+    if items.iter().all(Value::is_number) {
+        return typed_value(fid, TypeHint::FloatArray, &Value::from(items), mode);
+    }
+    Err(shape(fid, "array elements must share a single type"))
+}
+
+fn number_value(n: &Number) -> LnmpValue {
+    match n.as_i64() {
+        Some(i) => LnmpValue::Int(i),
+        None => LnmpValue::Float(n.as_f64().unwrap_or_default()),
+    }
+}
+
+fn float_to_json(fid: FieldId, f: f64) -> Result<Value, JsonError> {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .ok_or(JsonError::NonFiniteFloat(fid))
+}
+
+fn as_int(fid: FieldId, v: &Value) -> Result<i64, JsonError> {
+    v.as_i64().ok_or_else(|| shape(fid, "expected an integer"))
+}
+
+fn as_float(fid: FieldId, v: &Value) -> Result<f64, JsonError> {
+    v.as_f64().ok_or_else(|| shape(fid, "expected a number"))
+}
+
+fn as_bool(fid: FieldId, v: &Value) -> Result<bool, JsonError> {
+    // LNMP text encodes booleans as 1/0, so accept both spellings.
+    match v {
+        Value::Bool(b) => Ok(*b),
+        Value::Number(n) if n.as_i64() == Some(1) => Ok(true),
+        Value::Number(n) if n.as_i64() == Some(0) => Ok(false),
+        _ => Err(shape(fid, "expected a boolean or 0/1")),
+    }
+}
+
+fn as_string(fid: FieldId, v: &Value) -> Result<String, JsonError> {
+    v.as_str()
+        .map(str::to_string)
+        .ok_or_else(|| shape(fid, "expected a string"))
+}
+
+fn shape(fid: FieldId, reason: &str) -> JsonError {
+    JsonError::InvalidShape {
+        path: format!("F{}", fid),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> LnmpRecord {
+        let mut nested = LnmpRecord::new();
+        nested.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(1),
+        });
+        let mut record = LnmpRecord::new();
+        for (fid, value) in [
+            (1, LnmpValue::Int(14532)),
+            (2, LnmpValue::Float(3.0)),
+            (3, LnmpValue::Bool(true)),
+            (4, LnmpValue::String("1".to_string())),
+            (5, LnmpValue::StringArray(vec!["a".into(), "b".into()])),
+            (6, LnmpValue::IntArray(vec![1, 2])),
+            (7, LnmpValue::FloatArray(vec![1.0, 2.5])),
+            (8, LnmpValue::BoolArray(vec![true, false])),
+            (9, LnmpValue::NestedRecord(Box::new(nested.clone()))),
+            (10, LnmpValue::NestedArray(vec![nested])),
+        ] {
+            record.add_field(LnmpField { fid, value });
+        }
+        record
+    }
+
+    #[test]
+    fn test_tagged_round_trip_is_lossless() {
+        let record = sample();
+        let json = record_to_json(&record, JsonMode::Tagged).unwrap();
+        assert_eq!(json["1"], json!({"t": "i", "v": 14532}));
+        assert_eq!(json["2"]["t"], "f");
+        assert_eq!(json["9"]["v"]["1"], json!({"t": "i", "v": 1}));
+
+        let back = record_from_json(&json, JsonMode::Tagged).unwrap();
+        assert_eq!(back.sorted_fields(), record.sorted_fields());
+    }
+
+    #[test]
+    fn test_plain_mapping_infers_types() {
+        let json = record_to_json(&sample(), JsonMode::Plain).unwrap();
+        assert_eq!(json["1"], json!(14532));
+        assert_eq!(json["3"], json!(true));
+        assert_eq!(json["9"], json!({"1": 1}));
+
+        let back = record_from_json(&json, JsonMode::Plain).unwrap();
+        // 3.0 serializes as a float and survives; float arrays stay floats
+        assert_eq!(back.get_field(2).unwrap().value, LnmpValue::Float(3.0));
+        assert_eq!(
+            back.get_field(7).unwrap().value,
+            LnmpValue::FloatArray(vec![1.0, 2.5])
+        );
+        assert_eq!(
+            back.get_field(6).unwrap().value,
+            LnmpValue::IntArray(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn test_tagged_bool_accepts_one_and_zero() {
+        let json = json!({"7": {"t": "b", "v": 1}, "8": {"t": "i", "v": 1}});
+        let record = record_from_json(&json, JsonMode::Tagged).unwrap();
+        assert_eq!(record.get_field(7).unwrap().value, LnmpValue::Bool(true));
+        assert_eq!(record.get_field(8).unwrap().value, LnmpValue::Int(1));
+    }
+
+    #[test]
+    fn test_field_keys_accept_f_prefix() {
+        let record = record_from_json(&json!({"F12": 5}), JsonMode::Plain).unwrap();
+        assert_eq!(record.get_field(12).unwrap().value, LnmpValue::Int(5));
+        assert!(matches!(
+            record_from_json(&json!({"x": 5}), JsonMode::Plain),
+            Err(JsonError::InvalidFieldId(_))
+        ));
+    }
+
+    #[test]
+    fn test_tagged_errors() {
+        assert!(matches!(
+            record_from_json(&json!({"1": {"t": "zz", "v": 1}}), JsonMode::Tagged),
+            Err(JsonError::UnknownTag(_))
+        ));
+        assert!(matches!(
+            record_from_json(&json!({"1": {"t": "i", "v": "x"}}), JsonMode::Tagged),
+            Err(JsonError::InvalidShape { .. })
+        ));
+        assert!(matches!(
+            record_from_json(&json!({"1": 5}), JsonMode::Tagged),
+            Err(JsonError::InvalidShape { .. })
+        ));
+    }
+
+    #[test]
+    fn test_non_finite_float_is_rejected() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Float(f64::NAN),
+        });
+        assert_eq!(
+            record_to_json(&record, JsonMode::Plain),
+            Err(JsonError::NonFiniteFloat(1))
+        );
+    }
+
+    #[test]
+    fn test_record_from_json_coerced() {
+        use lnmp_core::registry::ExpectedType;
+
+        let engine = CoercionEngine::default()
+            .with_field_target(1, ExpectedType::Int)
+            .with_field_target(2, ExpectedType::Bool)
+            .with_field_target(3, ExpectedType::FloatArray);
+        let json = json!({"1": "42", "2": "no", "3": [1, 2.5], "4": "untouched"});
+        let record = record_from_json_coerced(&json, JsonMode::Plain, &engine).unwrap();
+
+        assert_eq!(record.get_field(1).unwrap().value, LnmpValue::Int(42));
+        assert_eq!(record.get_field(2).unwrap().value, LnmpValue::Bool(false));
+        assert_eq!(
+            record.get_field(3).unwrap().value,
+            LnmpValue::FloatArray(vec![1.0, 2.5])
+        );
+        assert_eq!(
+            record.get_field(4).unwrap().value,
+            LnmpValue::String("untouched".to_string())
+        );
+
+        let bad = json!({"1": "forty-two"});
+        assert!(matches!(
+            record_from_json_coerced(&bad, JsonMode::Plain, &engine),
+            Err(JsonError::Coercion(_))
+        ));
+    }
+}
@@ -125,6 +125,22 @@ impl<'a> Lexer<'a> {
         Some(ch)
     }
 
+    /// Reads raw text up to (not including) the next newline
+    ///
+    /// Used for header lines such as `#ENVELOPE` whose syntax differs from
+    /// LNMP fields.
+    pub fn read_line(&mut self) -> String {
+        let mut line = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\n' {
+                break;
+            }
+            line.push(ch);
+            self.advance();
+        }
+        line
+    }
+
     /// Skips whitespace (spaces and tabs, but not newlines)
     fn skip_whitespace(&mut self) {
         while let Some(ch) = self.peek() {
@@ -152,24 +152,49 @@
 #![warn(clippy::all)]
 
 pub mod binary;
+pub mod canonical;
+pub mod compression;
 pub mod config;
 pub mod container;
+pub mod duplicates;
 pub mod encoder;
+pub mod encryption;
 pub mod equivalence;
 pub mod error;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod lexer;
+pub mod locale;
 pub mod normalizer;
 pub mod parser;
+pub mod signing;
 
 pub use binary::delta::DeltaApplyContext;
-pub use config::{EncoderConfig, ParserConfig, ParsingMode, TextInputMode};
+pub use canonical::{
+    validate_canonical, CanonicalReport, CanonicalViolation, CanonicalViolationKind,
+};
+pub use compression::{CompressionAlgorithm, CompressionConfig, CompressionError};
+pub use config::{EncoderConfig, FloatFormat, ParserConfig, ParsingMode, TextInputMode};
 pub use container::{
-    delta_apply_context_from_metadata, parse_delta_metadata, parse_stream_metadata, ContainerBody,
-    ContainerBuilder, ContainerDecodeError, ContainerEncodeError, ContainerFrame,
-    ContainerFrameError, DeltaMetadata, MetadataError, StreamMetadata,
+    delta_apply_context_from_metadata, parse_delta_metadata, parse_stream_metadata,
+    verify_text_checksums, ChecksumFailure, ChecksumFailureKind, ContainerBody, ContainerBuilder,
+    ContainerDecodeError, ContainerEncodeError, ContainerFrame, ContainerFrameError, DeltaMetadata,
+    MetadataError, StreamMetadata,
+};
+pub use duplicates::DuplicateFieldPolicy;
+pub use encoder::{
+    canonicalize_record, canonicalize_record_preserving_empty, canonicalize_record_with_policy,
+    Encoder,
 };
-pub use encoder::{canonicalize_record, Encoder};
-pub use equivalence::EquivalenceMapper;
+pub use encryption::{
+    EncryptionError, EncryptionKey, KeyProvider, PayloadCipher, RotatingKeyProvider,
+    SourceKeyProvider, StaticKeyProvider,
+};
+pub use equivalence::{EquivalenceMapper, NumericTolerance};
 pub use error::LnmpError;
-pub use normalizer::{NormalizationConfig, StringCaseRule, ValueNormalizer};
+pub use locale::NumberRewrite;
+pub use normalizer::{NormalizationConfig, NormalizationRule, StringCaseRule, ValueNormalizer};
 pub use parser::Parser;
+pub use signing::{
+    Ed25519Verifier, FrameSignature, FrameSigner, SignatureError, SignatureVerifier,
+};
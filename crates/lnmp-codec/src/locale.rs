@@ -0,0 +1,148 @@
+//! Lenient parsing of locale-formatted numbers.
+//!
+//! LLMs often emit numbers in the convention of the prompt language, such as
+//! `1.234,56` (German), `1 234,56` (French) or `1'234.56` (Swiss). When
+//! [`ParserConfig::locale_numbers`](crate::ParserConfig::locale_numbers) is enabled,
+//! scalar field values are rewritten to canonical dot-decimal form before lenient
+//! sanitization and lexing, and every rewrite is recorded in a [`NumberRewrite`]
+//! with its position in the caller's input.
+//!
+//! Literals are interpreted by [`normalize_number`], shared with the
+//! `locale_numbers` rule of `lnmp-sanitize` so both read `1,234` the same way.
+//!
+//! Only unquoted scalar values are considered; arrays, nested records and quoted
+//! strings are never rewritten.
+
+use std::borrow::Cow;
+
+pub use lnmp_sanitize::normalize_number;
+
+/// A numeric literal rewritten from a locale format to canonical form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberRewrite {
+    /// Line number of the value in the original input (1-based)
+    pub line: usize,
+    /// Column number of the value in the original input (1-based)
+    pub column: usize,
+    /// Literal as it appeared in the input
+    pub original: String,
+    /// Canonical replacement
+    pub normalized: String,
+}
+
+/// Rewrites locale-formatted numeric values in `input` to canonical form.
+///
+/// Returns the (possibly unchanged) text together with the applied rewrites.
+pub fn normalize_locale_numbers(input: &str) -> (Cow<'_, str>, Vec<NumberRewrite>) {
+    let mut rewrites = Vec::new();
+    let mut output = String::with_capacity(input.len());
+    let mut copied_to = 0;
+
+    for (start, end) in scalar_value_spans(input) {
+        let raw = &input[start..end];
+        let trimmed = raw.trim_end();
+        if let Some(normalized) = normalize_number(trimmed) {
+            let (line, column) = line_col(input, start);
+            output.push_str(&input[copied_to..start]);
+            output.push_str(&normalized);
+            copied_to = start + trimmed.len();
+            rewrites.push(NumberRewrite {
+                line,
+                column,
+                original: trimmed.to_string(),
+                normalized,
+            });
+        }
+    }
+
+    if rewrites.is_empty() {
+        return (Cow::Borrowed(input), rewrites);
+    }
+    output.push_str(&input[copied_to..]);
+    (Cow::Owned(output), rewrites)
+}
+
+/// Finds byte spans of unquoted scalar values of top-level fields.
+///
+/// A value starts after `=` and ends at a newline, `;`, or a `#` checksum. Values
+/// that begin with a quote, `[` or `{` are skipped.
+fn scalar_value_spans(input: &str) -> Vec<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let mut spans = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_quotes {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_quotes = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match b {
+            b'"' => in_quotes = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => {
+                let mut start = i + 1;
+                while start < bytes.len() && (bytes[start] == b' ' || bytes[start] == b'\t') {
+                    start += 1;
+                }
+                if start < bytes.len() && !matches!(bytes[start], b'"' | b'[' | b'{') {
+                    let end = input[start..]
+                        .find(['\n', '\r', ';', '#'])
+                        .map(|off| start + off)
+                        .unwrap_or(bytes.len());
+                    spans.push((start, end));
+                    i = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    spans
+}
+
+fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map(|nl| before[nl + 1..].chars().count())
+        .unwrap_or_else(|| before.chars().count())
+        + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrites_only_scalar_values() {
+        let input = "F1=1.234,56\nF2=\"1.234,56\"\nF3=[1,5]\nF4=3,14#ABCD1234";
+        let (text, rewrites) = normalize_locale_numbers(input);
+        assert_eq!(
+            text,
+            "F1=1234.56\nF2=\"1.234,56\"\nF3=[1,5]\nF4=3.14#ABCD1234"
+        );
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(rewrites[0].original, "1.234,56");
+        assert_eq!((rewrites[0].line, rewrites[0].column), (1, 4));
+        assert_eq!(rewrites[1].normalized, "3.14");
+        assert_eq!(rewrites[1].line, 4);
+    }
+
+    #[test]
+    fn test_unchanged_input_is_borrowed() {
+        let (text, rewrites) = normalize_locale_numbers("F1=3.14\nF2=hello");
+        assert!(matches!(text, Cow::Borrowed(_)));
+        assert!(rewrites.is_empty());
+    }
+}
@@ -5,9 +5,69 @@
 //! - Boolean: Convert all representations (true/false, yes/no, 1/0) to canonical form
 //! - Float: Convert -0.0 to 0.0, remove trailing zeros
 //! - String: Apply case transformation based on configuration
+//!
+//! With the `log` feature every value changed by a [`NormalizationRule`] is logged at
+//! `debug` level under the `lnmp::normalize` target as
+//! `rule=<id> fid=<F..|-> before=.. after=..`.
 
 use lnmp_core::LnmpValue;
-use lnmp_sfe::SemanticDictionary;
+use lnmp_sfe::{SemanticDictionary, SharedDictionary};
+use std::fmt;
+
+/// Identifies a normalization or lenient coercion applied to a field value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalizationRule {
+    /// `-0.0` was rewritten to `0.0`
+    NegativeZero,
+    /// A float was rounded to the configured precision
+    FloatPrecision,
+    /// A string was replaced by its semantic dictionary equivalent
+    DictionaryEquivalence,
+    /// A string was lower- or upper-cased
+    StringCase,
+    /// An unquoted `0`/`1` or text literal (`true`/`yes`/`no`/...) was parsed as a boolean
+    BooleanLiteral,
+    /// A value was coerced to the field's target type or type hint
+    TypeCoercion,
+}
+
+impl NormalizationRule {
+    /// Stable identifier used in log records
+    pub fn id(&self) -> &'static str {
+        match self {
+            NormalizationRule::NegativeZero => "normalize.negative_zero",
+            NormalizationRule::FloatPrecision => "normalize.float_precision",
+            NormalizationRule::DictionaryEquivalence => "normalize.dictionary",
+            NormalizationRule::StringCase => "normalize.string_case",
+            NormalizationRule::BooleanLiteral => "coerce.boolean_literal",
+            NormalizationRule::TypeCoercion => "coerce.type",
+        }
+    }
+}
+
+/// Logs a value changed by `rule`
+#[inline]
+pub(crate) fn report_normalization(
+    rule: NormalizationRule,
+    fid: Option<u16>,
+    before: &dyn fmt::Debug,
+    after: &dyn fmt::Debug,
+) {
+    #[cfg(feature = "log")]
+    if log::log_enabled!(target: "lnmp::normalize", log::Level::Debug) {
+        let fid = fid.map_or_else(|| "-".to_string(), |fid| format!("F{}", fid));
+        log::debug!(
+            target: "lnmp::normalize",
+            "rule={} fid={} before={:?} after={:?}",
+            rule.id(),
+            fid,
+            before,
+            after
+        );
+    }
+    #[cfg(not(feature = "log"))]
+    let _ = (rule, fid, before, after);
+}
 
 /// String case transformation rules
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -34,6 +94,9 @@ pub struct NormalizationConfig {
     pub remove_trailing_zeros: bool,
     /// Optional semantic dictionary for equivalence normalization
     pub semantic_dictionary: Option<SemanticDictionary>,
+    /// Optional shared dictionary; takes precedence over `semantic_dictionary` and is
+    /// read on every lookup, so updates apply without rebuilding the normalizer
+    pub shared_dictionary: Option<SharedDictionary>,
 }
 
 impl Default for NormalizationConfig {
@@ -43,10 +106,30 @@ impl Default for NormalizationConfig {
             float_precision: None,
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         }
     }
 }
 
+impl NormalizationConfig {
+    /// Builds a config that only applies dictionary equivalences.
+    ///
+    /// Returns `None` if neither dictionary is set.
+    pub(crate) fn for_dictionaries(
+        semantic_dictionary: Option<&SemanticDictionary>,
+        shared_dictionary: Option<&SharedDictionary>,
+    ) -> Option<Self> {
+        if semantic_dictionary.is_none() && shared_dictionary.is_none() {
+            return None;
+        }
+        Some(Self {
+            semantic_dictionary: semantic_dictionary.cloned(),
+            shared_dictionary: shared_dictionary.cloned(),
+            ..Self::default()
+        })
+    }
+}
+
 /// Value normalizer for semantic equivalence
 #[derive(Debug)]
 pub struct ValueNormalizer {
@@ -68,7 +151,7 @@ impl ValueNormalizer {
     pub fn normalize_with_fid(&self, fid: Option<u16>, value: &LnmpValue) -> LnmpValue {
         match value {
             LnmpValue::Int(i) => LnmpValue::Int(*i),
-            LnmpValue::Float(f) => LnmpValue::Float(self.normalize_float(*f)),
+            LnmpValue::Float(f) => LnmpValue::Float(self.normalize_float(fid, *f)),
             LnmpValue::Bool(b) => LnmpValue::Bool(*b),
             LnmpValue::String(s) => LnmpValue::String(self.normalize_string_for(fid, s)),
             LnmpValue::StringArray(arr) => LnmpValue::StringArray(
@@ -84,7 +167,7 @@ impl ValueNormalizer {
             LnmpValue::IntArray(arr) => LnmpValue::IntArray(arr.clone()),
             LnmpValue::FloatArray(arr) => {
                 // Normalize each float in the array
-                let normalized_arr = arr.iter().map(|f| self.normalize_float(*f)).collect();
+                let normalized_arr = arr.iter().map(|f| self.normalize_float(fid, *f)).collect();
                 LnmpValue::FloatArray(normalized_arr)
             }
             LnmpValue::BoolArray(arr) => LnmpValue::BoolArray(arr.clone()),
@@ -109,14 +192,26 @@ impl ValueNormalizer {
     /// - Converts -0.0 to 0.0
     /// - Removes trailing zeros after decimal point (if configured)
     /// - Applies precision rounding (if configured)
-    fn normalize_float(&self, f: f64) -> f64 {
+    fn normalize_float(&self, fid: Option<u16>, f: f64) -> f64 {
         // Convert -0.0 to 0.0
         let mut normalized = if f == 0.0 { 0.0 } else { f };
+        if f == 0.0 && f.is_sign_negative() {
+            report_normalization(NormalizationRule::NegativeZero, fid, &f, &normalized);
+        }
 
         // Apply precision if configured
         if let Some(precision) = self.config.float_precision {
             let multiplier = 10_f64.powi(precision as i32);
-            normalized = (normalized * multiplier).round() / multiplier;
+            let rounded = (normalized * multiplier).round() / multiplier;
+            if rounded != normalized {
+                report_normalization(
+                    NormalizationRule::FloatPrecision,
+                    fid,
+                    &normalized,
+                    &rounded,
+                );
+            }
+            normalized = rounded;
         }
 
         normalized
@@ -126,20 +221,42 @@ impl ValueNormalizer {
     ///
     /// Applies case transformation based on configuration
     fn normalize_string_for(&self, fid: Option<u16>, s: &str) -> String {
-        if let (Some(dict), Some(fid)) = (&self.config.semantic_dictionary, fid) {
-            if let Some(eq) = dict.get_equivalence(fid, s) {
-                return eq.to_string();
-            }
-            if let Some(eq) = dict.get_equivalence_normalized(fid, s) {
-                return eq.to_string();
+        if let Some(fid) = fid {
+            let equivalent = |dict: &SemanticDictionary| {
+                dict.get_equivalence(fid, s)
+                    .or_else(|| dict.get_equivalence_normalized(fid, s))
+                    .map(str::to_string)
+            };
+            let mapped = match (
+                &self.config.shared_dictionary,
+                &self.config.semantic_dictionary,
+            ) {
+                (Some(shared), _) => equivalent(&shared.snapshot()),
+                (None, Some(dict)) => equivalent(dict),
+                (None, None) => None,
+            };
+            if let Some(eq) = mapped {
+                if eq != s {
+                    report_normalization(
+                        NormalizationRule::DictionaryEquivalence,
+                        Some(fid),
+                        &s,
+                        &eq,
+                    );
+                }
+                return eq;
             }
         }
 
-        match self.config.string_case {
+        let cased = match self.config.string_case {
             StringCaseRule::Lower => s.to_lowercase(),
             StringCaseRule::Upper => s.to_uppercase(),
-            StringCaseRule::None => s.to_string(),
+            StringCaseRule::None => return s.to_string(),
+        };
+        if cased != s {
+            report_normalization(NormalizationRule::StringCase, fid, &s, &cased);
         }
+        cased
     }
 
     /// Formats a normalized float as a string with trailing zeros removed
@@ -250,6 +367,7 @@ mod tests {
             float_precision: Some(2),
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let normalizer = ValueNormalizer::new(config);
 
@@ -276,6 +394,7 @@ mod tests {
             float_precision: None,
             remove_trailing_zeros: false,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let normalizer = ValueNormalizer::new(config);
 
@@ -298,6 +417,7 @@ mod tests {
             float_precision: None,
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let normalizer = ValueNormalizer::new(config);
 
@@ -313,6 +433,7 @@ mod tests {
             float_precision: None,
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let normalizer = ValueNormalizer::new(config);
 
@@ -328,6 +449,7 @@ mod tests {
             float_precision: None,
             remove_trailing_zeros: true,
             semantic_dictionary: None,
+            shared_dictionary: None,
         };
         let normalizer = ValueNormalizer::new(config);
 
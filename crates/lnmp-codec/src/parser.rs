@@ -3,12 +3,17 @@
 use std::borrow::Cow;
 
 use crate::config::{ParserConfig, ParsingMode, TextInputMode};
+use crate::duplicates::DuplicateFieldPolicy;
 use crate::error::LnmpError;
 use crate::lexer::{Lexer, Token};
-use crate::normalizer::ValueNormalizer;
+use crate::locale::{normalize_locale_numbers, NumberRewrite};
+use crate::normalizer::{report_normalization, NormalizationRule, ValueNormalizer};
 use lnmp_core::checksum::SemanticChecksum;
-use lnmp_core::registry::{ValidationMode, ValidationResult};
+use lnmp_core::coercion::CoercionRules;
+use lnmp_core::registry::{ExpectedType, ValidationMode, ValidationResult};
 use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpValue, TypeHint};
+use lnmp_envelope::text_codec::TextDecoder;
+use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
 use lnmp_sanitize::{sanitize_lnmp_text, SanitizationConfig};
 
 /// Parser for LNMP text format
@@ -19,6 +24,21 @@ pub struct Parser<'a> {
     // current nesting depth for nested records/arrays
     nesting_depth: usize,
     normalizer: Option<ValueNormalizer>,
+    // checksum observations for top-level fields, collected when enabled
+    checksum_observations: Option<Vec<ChecksumObservation>>,
+    // locale-formatted numbers rewritten before lexing
+    number_rewrites: Vec<NumberRewrite>,
+    // field whose value is being parsed, for normalization logs
+    current_fid: Option<FieldId>,
+}
+
+/// Checksum seen (or missing) on a top-level field while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ChecksumObservation {
+    pub(crate) fid: FieldId,
+    pub(crate) provided: Option<u32>,
+    pub(crate) computed: u32,
+    pub(crate) line: usize,
 }
 
 impl<'a> Parser<'a> {
@@ -69,10 +89,23 @@ impl<'a> Parser<'a> {
 
     /// Creates a new parser with specified configuration
     pub fn with_config(input: &'a str, config: ParserConfig) -> Result<Self, LnmpError> {
-        let input_cow = match config.text_input_mode {
-            TextInputMode::Strict => Cow::Borrowed(input),
-            TextInputMode::Lenient => sanitize_lnmp_text(input, &SanitizationConfig::default()),
-        };
+        // Locale numbers are rewritten before sanitization so that rewrite
+        // positions refer to the caller's input.
+        let mut input_cow = Cow::Borrowed(input);
+        let mut number_rewrites = Vec::new();
+        if config.locale_numbers {
+            let (normalized, rewrites) = normalize_locale_numbers(input);
+            input_cow = normalized;
+            number_rewrites = rewrites;
+        }
+
+        if config.text_input_mode == TextInputMode::Lenient {
+            if let Cow::Owned(text) =
+                sanitize_lnmp_text(input_cow.as_ref(), &SanitizationConfig::default())
+            {
+                input_cow = Cow::Owned(text);
+            }
+        }
 
         if config.mode == ParsingMode::Strict {
             Self::check_for_comments(input_cow.as_ref())?;
@@ -87,12 +120,11 @@ impl<'a> Parser<'a> {
         };
         let current_token = lexer.next_token()?;
 
-        let normalizer = config.semantic_dictionary.as_ref().map(|dict| {
-            ValueNormalizer::new(crate::normalizer::NormalizationConfig {
-                semantic_dictionary: Some(dict.clone()),
-                ..crate::normalizer::NormalizationConfig::default()
-            })
-        });
+        let normalizer = crate::normalizer::NormalizationConfig::for_dictionaries(
+            config.semantic_dictionary.as_ref(),
+            config.shared_dictionary.as_ref(),
+        )
+        .map(ValueNormalizer::new);
 
         Ok(Self {
             lexer,
@@ -100,6 +132,9 @@ impl<'a> Parser<'a> {
             config,
             nesting_depth: 0,
             normalizer,
+            checksum_observations: None,
+            number_rewrites,
+            current_fid: None,
         })
     }
 
@@ -108,6 +143,23 @@ impl<'a> Parser<'a> {
         self.config.mode
     }
 
+    /// Returns the locale-formatted numbers rewritten to canonical form.
+    ///
+    /// Always empty unless [`ParserConfig::locale_numbers`] is enabled.
+    pub fn number_rewrites(&self) -> &[NumberRewrite] {
+        &self.number_rewrites
+    }
+
+    /// Starts collecting checksum observations for top-level fields.
+    pub(crate) fn track_checksums(&mut self) {
+        self.checksum_observations = Some(Vec::new());
+    }
+
+    /// Returns the checksum observations collected so far.
+    pub(crate) fn take_checksum_observations(&mut self) -> Vec<ChecksumObservation> {
+        self.checksum_observations.take().unwrap_or_default()
+    }
+
     /// Advances to the next token
     fn advance(&mut self) -> Result<(), LnmpError> {
         self.current_token = self.lexer.next_token()?;
@@ -154,8 +206,16 @@ impl<'a> Parser<'a> {
     fn check_for_comments(input: &str) -> Result<(), LnmpError> {
         // Check if input contains comment lines (lines starting with #)
         // Note: # after a value is a checksum, not a comment
+        let mut first_line = true;
         for (line_idx, line) in input.lines().enumerate() {
             let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            // A leading #ENVELOPE header is metadata, not a comment
+            if std::mem::take(&mut first_line) && is_envelope_header(trimmed) {
+                continue;
+            }
             // If line starts with #, it's a comment
             if trimmed.starts_with('#') {
                 return Err(LnmpError::StrictModeViolation {
@@ -244,12 +304,15 @@ impl<'a> Parser<'a> {
                 }
 
                 // If normalization is enabled, interpret 0/1 as booleans but do not reject other numbers
-                if self.config.normalize_values {
-                    if num_str == "0" {
-                        return Ok(LnmpValue::Bool(false));
-                    } else if num_str == "1" {
-                        return Ok(LnmpValue::Bool(true));
-                    }
+                if self.config.normalize_values && (num_str == "0" || num_str == "1") {
+                    let b = num_str == "1";
+                    report_normalization(
+                        NormalizationRule::BooleanLiteral,
+                        self.current_fid,
+                        &num_str,
+                        &b,
+                    );
+                    return Ok(LnmpValue::Bool(b));
                 }
 
                 // Try to parse as float if it contains a dot
@@ -284,13 +347,21 @@ impl<'a> Parser<'a> {
             Token::UnquotedString(s) => {
                 let s = s.clone();
                 self.advance()?;
+                // E[dim,dtype,"base64_data"] is an embedding
+                if s == "E" && self.current_token == Token::LeftBracket {
+                    return self.parse_embedding(line, column);
+                }
                 // If a boolean type hint is present or normalization is enabled, allow
                 // text values 'true'/'false' or 'yes'/'no' to be interpreted as booleans.
                 if type_hint == Some(TypeHint::Bool) || self.config.normalize_values {
-                    match s.to_ascii_lowercase().as_str() {
-                        "true" | "yes" => return Ok(LnmpValue::Bool(true)),
-                        "false" | "no" => return Ok(LnmpValue::Bool(false)),
-                        _ => {}
+                    if let Some(b) = self.coercion_rules().bool_from_text(&s) {
+                        report_normalization(
+                            NormalizationRule::BooleanLiteral,
+                            self.current_fid,
+                            &s,
+                            &b,
+                        );
+                        return Ok(LnmpValue::Bool(b));
                     }
                 }
                 Ok(LnmpValue::String(s))
@@ -507,6 +578,114 @@ impl<'a> Parser<'a> {
         Ok(LnmpValue::FloatArray(items))
     }
 
+    /// Parses the body of an embedding `E[dim,dtype,"base64_data"(,flags)]`;
+    /// the `E` has already been consumed
+    fn parse_embedding(&mut self, line: usize, column: usize) -> Result<LnmpValue, LnmpError> {
+        use base64::Engine;
+        use lnmp_embedding::{EmbeddingType, Vector, VectorTransform};
+
+        let field_id = self.current_fid.unwrap_or(0);
+        let invalid = |reason: String| LnmpError::InvalidValue {
+            field_id,
+            reason,
+            line,
+            column,
+        };
+        self.expect(Token::LeftBracket)?;
+
+        let dim = match &self.current_token {
+            Token::Number(num_str) => num_str
+                .parse::<u16>()
+                .map_err(|_| invalid(format!("invalid embedding dimension: {}", num_str)))?,
+            _ => {
+                return Err(LnmpError::UnexpectedToken {
+                    expected: "embedding dimension".to_string(),
+                    found: self.current_token.clone(),
+                    line,
+                    column,
+                })
+            }
+        };
+        self.advance()?;
+        self.expect(Token::Comma)?;
+
+        let dtype = match &self.current_token {
+            Token::UnquotedString(s) => match s.as_str() {
+                "f32" => EmbeddingType::F32,
+                "f16" => EmbeddingType::F16,
+                "i8" => EmbeddingType::I8,
+                "u8" => EmbeddingType::U8,
+                "binary" => EmbeddingType::Binary,
+                other => return Err(invalid(format!("unknown embedding dtype: {}", other))),
+            },
+            _ => {
+                return Err(LnmpError::UnexpectedToken {
+                    expected: "embedding dtype".to_string(),
+                    found: self.current_token.clone(),
+                    line,
+                    column,
+                })
+            }
+        };
+        self.advance()?;
+        self.expect(Token::Comma)?;
+
+        let data = match &self.current_token {
+            Token::QuotedString(s) => base64::engine::general_purpose::STANDARD
+                .decode(s)
+                .map_err(|e| invalid(format!("invalid embedding data: {}", e)))?,
+            _ => {
+                return Err(LnmpError::UnexpectedToken {
+                    expected: "quoted base64 embedding data".to_string(),
+                    found: self.current_token.clone(),
+                    line,
+                    column,
+                })
+            }
+        };
+        self.advance()?;
+
+        let mut transform = VectorTransform::default();
+        if self.current_token == Token::Comma {
+            self.advance()?;
+            match &self.current_token {
+                Token::UnquotedString(flags) if matches!(flags.as_str(), "n" | "c" | "nc") => {
+                    transform.normalized = flags.contains('n');
+                    transform.centered = flags.contains('c');
+                }
+                _ => {
+                    return Err(LnmpError::UnexpectedToken {
+                        expected: "embedding flags (n, c or nc)".to_string(),
+                        found: self.current_token.clone(),
+                        line,
+                        column,
+                    })
+                }
+            }
+            self.advance()?;
+        }
+        self.expect(Token::RightBracket)?;
+
+        let expected_len = match dtype {
+            EmbeddingType::F32 => dim as usize * 4,
+            EmbeddingType::F16 => dim as usize * 2,
+            EmbeddingType::I8 | EmbeddingType::U8 => dim as usize,
+            EmbeddingType::Binary => (dim as usize).div_ceil(8),
+        };
+        if data.len() != expected_len {
+            return Err(invalid(format!(
+                "embedding data is {} bytes, expected {} for dim {}",
+                data.len(),
+                expected_len,
+                dim
+            )));
+        }
+
+        Ok(LnmpValue::Embedding(
+            Vector::new(dtype, dim, data).with_transform(transform),
+        ))
+    }
+
     fn parse_bool_array(&mut self) -> Result<LnmpValue, LnmpError> {
         let mut items = Vec::new();
 
@@ -604,17 +783,7 @@ impl<'a> Parser<'a> {
             // Parse field assignments within the nested record
             loop {
                 let field = self.parse_field_assignment()?;
-                // In strict mode, detect duplicate field IDs in nested records and error early
-                if self.config.mode == ParsingMode::Strict && record.get_field(field.fid).is_some()
-                {
-                    let (line, column) = self.lexer.position_original();
-                    return Err(LnmpError::DuplicateFieldId {
-                        field_id: field.fid,
-                        line,
-                        column,
-                    });
-                }
-                record.add_field(field);
+                self.add_field_with_policy(&mut record, field)?;
 
                 // Check for separator or closing brace
                 match &self.current_token {
@@ -723,6 +892,57 @@ impl<'a> Parser<'a> {
         result
     }
 
+    /// Rules used to interpret boolean literals in loose mode
+    fn coercion_rules(&self) -> CoercionRules {
+        self.config
+            .coercion
+            .as_ref()
+            .map(|engine| *engine.rules())
+            .unwrap_or_default()
+    }
+
+    /// Coerces a parsed value towards its configured target type or type hint
+    ///
+    /// Only applies in loose mode with a [`ParserConfig::coercion`] engine.
+    fn coerce_value(
+        &self,
+        fid: FieldId,
+        type_hint: Option<TypeHint>,
+        value: LnmpValue,
+    ) -> Result<LnmpValue, LnmpError> {
+        let engine = match &self.config.coercion {
+            Some(engine) if self.config.mode == ParsingMode::Loose => engine,
+            _ => return Ok(value),
+        };
+        let target = engine
+            .target_for(fid)
+            .or_else(|| type_hint.and_then(ExpectedType::from_type_hint));
+        let Some(target) = target else {
+            return Ok(value);
+        };
+        #[cfg(feature = "log")]
+        let before = value.clone();
+        let coerced = engine.coerce_to(fid, value, target).map_err(|err| {
+            let (line, column) = self.lexer.position_original();
+            LnmpError::InvalidValue {
+                field_id: fid,
+                reason: err.to_string(),
+                line,
+                column,
+            }
+        })?;
+        #[cfg(feature = "log")]
+        if coerced != before {
+            report_normalization(
+                NormalizationRule::TypeCoercion,
+                Some(fid),
+                &before,
+                &coerced,
+            );
+        }
+        Ok(coerced)
+    }
+
     /// Parses a type hint (optional :type after field ID)
     fn parse_type_hint(&mut self) -> Result<Option<TypeHint>, LnmpError> {
         if let Token::TypeHint(hint_str) = &self.current_token {
@@ -747,13 +967,16 @@ impl<'a> Parser<'a> {
 
     /// Parses a field assignment (F<id>=<value> or F<id>:<type>=<value>)
     fn parse_field_assignment(&mut self) -> Result<LnmpField, LnmpError> {
+        let (field_line, _) = self.lexer.position_original();
         let fid = self.parse_field_id()?;
 
         // Check for optional type hint
         let type_hint = self.parse_type_hint()?;
 
         self.expect(Token::Equals)?;
+        self.current_fid = Some(fid);
         let value = self.parse_value_with_hint(type_hint)?;
+        let value = self.coerce_value(fid, type_hint, value)?;
 
         // Validate type hint if present
         if let Some(hint) = type_hint {
@@ -770,9 +993,22 @@ impl<'a> Parser<'a> {
         }
 
         // Check for optional checksum
-        if self.current_token == Token::Hash {
-            self.parse_and_validate_checksum(fid, type_hint, &value)?;
-        } else if self.config.require_checksums {
+        let provided = if self.current_token == Token::Hash {
+            Some(self.parse_and_validate_checksum(fid, type_hint, &value)?)
+        } else {
+            None
+        };
+        if self.nesting_depth == 0 {
+            if let Some(observations) = self.checksum_observations.as_mut() {
+                observations.push(ChecksumObservation {
+                    fid,
+                    provided,
+                    computed: SemanticChecksum::compute(fid, type_hint, &value),
+                    line: field_line,
+                });
+            }
+        }
+        if provided.is_none() && self.config.require_checksums {
             let (line, column) = self.lexer.position_original();
             return Err(LnmpError::ChecksumMismatch {
                 field_id: fid,
@@ -891,7 +1127,7 @@ impl<'a> Parser<'a> {
         fid: FieldId,
         type_hint: Option<TypeHint>,
         value: &LnmpValue,
-    ) -> Result<(), LnmpError> {
+    ) -> Result<u32, LnmpError> {
         let (line, column) = self.lexer.position_original();
 
         // Consume the hash token
@@ -954,7 +1190,7 @@ impl<'a> Parser<'a> {
             }
         }
 
-        Ok(())
+        Ok(provided_checksum)
     }
 
     /// Validates that fields are sorted by FID (strict mode only)
@@ -980,6 +1216,29 @@ impl<'a> Parser<'a> {
     // Duplicate field IDs are now detected during parsing and a DuplicateFieldId
     // error is emitted at parse time with an accurate lexer position.
 
+    /// Adds a parsed field, resolving duplicate FIDs.
+    ///
+    /// Strict mode always rejects duplicates; loose mode applies
+    /// [`ParserConfig::duplicate_fields`].
+    fn add_field_with_policy(
+        &self,
+        record: &mut LnmpRecord,
+        field: LnmpField,
+    ) -> Result<(), LnmpError> {
+        let policy = match self.config.mode {
+            ParsingMode::Strict => DuplicateFieldPolicy::Error,
+            ParsingMode::Loose => self.config.duplicate_fields,
+        };
+        policy.insert(record, field).map_err(|field_id| {
+            let (line, column) = self.lexer.position_original();
+            LnmpError::DuplicateFieldId {
+                field_id,
+                line,
+                column,
+            }
+        })
+    }
+
     /// Validates separator (strict mode rejects semicolons)
     fn validate_separator(&self, is_semicolon: bool) -> Result<(), LnmpError> {
         if self.config.mode == ParsingMode::Strict && is_semicolon {
@@ -1010,16 +1269,7 @@ impl<'a> Parser<'a> {
         // Parse field assignments until EOF
         while self.current_token != Token::Eof {
             let field = self.parse_field_assignment()?;
-            // In strict mode, detect duplicate field IDs and error early
-            if self.config.mode == ParsingMode::Strict && record.get_field(field.fid).is_some() {
-                let (line, column) = self.lexer.position_original();
-                return Err(LnmpError::DuplicateFieldId {
-                    field_id: field.fid,
-                    line,
-                    column,
-                });
-            }
-            record.add_field(field);
+            self.add_field_with_policy(&mut record, field)?;
 
             // Handle separator (semicolon or newline)
             match &self.current_token {
@@ -1080,6 +1330,40 @@ impl<'a> Parser<'a> {
 
         Ok(record)
     }
+
+    /// Parses a record preceded by an optional `#ENVELOPE` header line
+    ///
+    /// Input without a header yields an envelope with empty metadata, so
+    /// plain records remain valid input.
+    pub fn parse_envelope(&mut self) -> Result<LnmpEnvelope, LnmpError> {
+        self.skip_newlines()?;
+
+        let mut metadata = EnvelopeMetadata::new();
+        if self.current_token == Token::Hash {
+            let (line, column) = self.lexer.position_original();
+            let header = format!("#{}", self.lexer.read_line());
+            if is_envelope_header(&header) {
+                metadata = TextDecoder::decode(&header)
+                    .map_err(|e| LnmpError::InvalidEnvelope {
+                        reason: e.to_string(),
+                        line,
+                        column: column.saturating_sub(1),
+                    })?
+                    .unwrap_or_default();
+            }
+            // Anything else on the line was an ordinary comment
+            self.advance()?;
+        }
+
+        let record = self.parse_record()?;
+        Ok(LnmpEnvelope { record, metadata })
+    }
+}
+
+/// Returns true if `line` is an `#ENVELOPE` header rather than a comment
+fn is_envelope_header(line: &str) -> bool {
+    line.strip_prefix("#ENVELOPE")
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
 }
 
 #[cfg(test)]
@@ -1170,6 +1454,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_duplicate_field_policy_in_loose_mode() {
+        let input = "F1=10;F2=[a];F1=20;F2=[b,c]";
+        let parse = |policy| {
+            let config = ParserConfig::default().with_duplicate_fields(policy);
+            Parser::with_config(input, config).unwrap().parse_record()
+        };
+
+        let first = parse(DuplicateFieldPolicy::KeepFirst).unwrap();
+        assert_eq!(first.fields().len(), 2);
+        assert_eq!(first.get_field(1).unwrap().value, LnmpValue::Int(10));
+
+        let last = parse(DuplicateFieldPolicy::KeepLast).unwrap();
+        assert_eq!(last.get_field(1).unwrap().value, LnmpValue::Int(20));
+        assert_eq!(
+            last.get_field(2).unwrap().value,
+            LnmpValue::StringArray(vec!["b".to_string(), "c".to_string()])
+        );
+
+        match parse(DuplicateFieldPolicy::Error).unwrap_err() {
+            LnmpError::DuplicateFieldId { field_id, .. } => assert_eq!(field_id, 1),
+            err => panic!("expected DuplicateFieldId error, got: {:?}", err),
+        }
+
+        // F1 holds scalars, so merging fails on it
+        assert!(parse(DuplicateFieldPolicy::MergeArrays).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_field_policy_merges_nested_arrays() {
+        let config =
+            ParserConfig::default().with_duplicate_fields(DuplicateFieldPolicy::MergeArrays);
+        let mut parser = Parser::with_config("F50={F2=[a];F2=[b]}", config).unwrap();
+        let record = parser.parse_record().unwrap();
+        match &record.get_field(50).unwrap().value {
+            LnmpValue::NestedRecord(nested) => assert_eq!(
+                nested.fields(),
+                &[LnmpField {
+                    fid: 2,
+                    value: LnmpValue::StringArray(vec!["a".to_string(), "b".to_string()]),
+                }]
+            ),
+            other => panic!("expected nested record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_strict_mode_ignores_duplicate_field_policy() {
+        let config = ParserConfig {
+            mode: ParsingMode::Strict,
+            ..ParserConfig::default()
+        }
+        .with_duplicate_fields(DuplicateFieldPolicy::KeepFirst);
+        let mut parser = Parser::with_config("F1=1\nF1=2", config).unwrap();
+        assert!(matches!(
+            parser.parse_record(),
+            Err(LnmpError::DuplicateFieldId { field_id: 1, .. })
+        ));
+    }
+
     #[test]
     fn test_parse_quoted_string() {
         let mut parser = Parser::new(r#"F4="hello world""#).unwrap();
@@ -1281,6 +1625,67 @@ mod tests {
         assert_eq!(record.fields().len(), 0);
     }
 
+    #[test]
+    fn test_parse_envelope_header() {
+        let input =
+            "#ENVELOPE timestamp=1732373147000 source=auth-service tenant=acme\nF12=14532\nF7=1";
+        let mut parser = Parser::new(input).unwrap();
+        let envelope = parser.parse_envelope().unwrap();
+
+        assert_eq!(envelope.metadata.timestamp, Some(1732373147000));
+        assert_eq!(envelope.metadata.source.as_deref(), Some("auth-service"));
+        assert_eq!(envelope.metadata.label("tenant"), Some("acme"));
+        assert_eq!(envelope.record.fields().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_envelope_without_header() {
+        let input = "# just a comment\nF1=42";
+        let mut parser = Parser::new(input).unwrap();
+        let envelope = parser.parse_envelope().unwrap();
+
+        assert!(envelope.metadata.is_empty());
+        assert_eq!(
+            envelope.record.get_field(1).unwrap().value,
+            LnmpValue::Int(42)
+        );
+    }
+
+    #[test]
+    fn test_parse_envelope_malformed_header() {
+        let input = "#ENVELOPE timestamp=soon\nF1=42";
+        let mut parser = Parser::new(input).unwrap();
+        let err = parser.parse_envelope().unwrap_err();
+
+        assert!(matches!(
+            err,
+            LnmpError::InvalidEnvelope {
+                line: 1,
+                column: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_envelope_strict_mode() {
+        let input = "#ENVELOPE source=auth-service\nF1=42";
+        let mut parser = Parser::with_mode(input, ParsingMode::Strict).unwrap();
+        let envelope = parser.parse_envelope().unwrap();
+        assert_eq!(envelope.metadata.source.as_deref(), Some("auth-service"));
+
+        // Only a leading header is exempt from the no-comments rule
+        assert!(Parser::with_mode("F1=42\n#ENVELOPE source=x", ParsingMode::Strict).is_err());
+    }
+
+    #[test]
+    fn test_parse_record_skips_envelope_header() {
+        let input = "#ENVELOPE source=auth-service\nF1=42";
+        let mut parser = Parser::new(input).unwrap();
+        let record = parser.parse_record().unwrap();
+        assert_eq!(record.fields().len(), 1);
+    }
+
     #[test]
     fn test_parse_field_id_out_of_range() {
         let result = Parser::new("F99999=42");
@@ -2378,6 +2783,43 @@ F5:sa=[a,b]"#;
         }
     }
 
+    #[test]
+    fn test_parse_shared_dictionary_overrides_and_hot_reloads() {
+        use crate::config::ParserConfig;
+
+        let mut fixed = lnmp_sfe::SemanticDictionary::new();
+        fixed.add_equivalence(23, "admin".to_string(), "root".to_string());
+        let shared = lnmp_sfe::SharedDictionary::default();
+        let config = ParserConfig::default()
+            .with_semantic_dictionary(fixed)
+            .with_shared_dictionary(shared.clone());
+
+        let parse = |config: ParserConfig| {
+            let mut parser = Parser::with_config("F23=admin", config).unwrap();
+            parser
+                .parse_record()
+                .unwrap()
+                .get_field(23)
+                .unwrap()
+                .value
+                .clone()
+        };
+
+        // The shared handle takes precedence even while empty
+        assert_eq!(
+            parse(config.clone()),
+            LnmpValue::String("admin".to_string())
+        );
+
+        shared.update(|dict| {
+            dict.add_equivalence(23, "admin".to_string(), "administrator".to_string())
+        });
+        assert_eq!(
+            parse(config),
+            LnmpValue::String("administrator".to_string())
+        );
+    }
+
     #[test]
     fn test_parse_multiple_fields_with_checksums() {
         use crate::config::ParserConfig;
@@ -2532,4 +2974,101 @@ F5:sa=[a,b]"#;
             LnmpValue::BoolArray(Vec::new())
         );
     }
+
+    #[test]
+    fn test_locale_numbers_disabled_by_default() {
+        let mut parser = Parser::new("F1=3.5\nF2=7").unwrap();
+        parser.parse_record().unwrap();
+        assert!(parser.number_rewrites().is_empty());
+    }
+
+    #[test]
+    fn test_locale_numbers_rewritten_and_reported() {
+        let config = ParserConfig::default().with_locale_numbers(true);
+        let mut parser = Parser::with_config("F1=1.234,56\nF2=1 000\nF3=\"3,5\"", config).unwrap();
+        let record = parser.parse_record().unwrap();
+        assert_eq!(
+            record.get_field(1).unwrap().value,
+            LnmpValue::Float(1234.56)
+        );
+        assert_eq!(record.get_field(2).unwrap().value, LnmpValue::Int(1000));
+        assert_eq!(
+            record.get_field(3).unwrap().value,
+            LnmpValue::String("3,5".to_string())
+        );
+        let rewrites = parser.number_rewrites();
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(rewrites[0].original, "1.234,56");
+        assert_eq!(rewrites[1].normalized, "1000");
+        assert_eq!(rewrites[1].line, 2);
+    }
+
+    #[test]
+    fn test_locale_rewrites_report_original_positions() {
+        // Lenient sanitization quotes `a b` and `c d`, shifting later columns
+        let config = ParserConfig {
+            text_input_mode: TextInputMode::Lenient,
+            ..ParserConfig::default()
+        }
+        .with_locale_numbers(true);
+        let input = "F1=a b;F2=1.234,5;F3=2,5\nF4=c d;F5=3,25";
+        let mut parser = Parser::with_config(input, config).unwrap();
+        let record = parser.parse_record().unwrap();
+        assert_eq!(record.get_field(3).unwrap().value, LnmpValue::Float(2.5));
+
+        let positions: Vec<_> = parser
+            .number_rewrites()
+            .iter()
+            .map(|r| (r.original.as_str(), r.line, r.column))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![("1.234,5", 1, 11), ("2,5", 1, 22), ("3,25", 2, 11)]
+        );
+    }
+
+    #[test]
+    fn test_coercion_applies_type_hints_and_targets() {
+        use lnmp_core::coercion::{CoercionEngine, CoercionRules};
+        use lnmp_core::registry::ExpectedType;
+
+        let engine = CoercionEngine::new(CoercionRules::lenient())
+            .with_field_target(5, ExpectedType::IntArray)
+            .with_field_target(7, ExpectedType::Bool);
+        let config = ParserConfig::default().with_coercion(engine);
+        let mut parser =
+            Parser::with_config("F1:f=3\nF5=[\"1\",\"2\",\"3\"]\nF7=\"yes\"", config).unwrap();
+        let record = parser.parse_record().unwrap();
+
+        assert_eq!(record.get_field(1).unwrap().value, LnmpValue::Float(3.0));
+        assert_eq!(
+            record.get_field(5).unwrap().value,
+            LnmpValue::IntArray(vec![1, 2, 3])
+        );
+        assert_eq!(record.get_field(7).unwrap().value, LnmpValue::Bool(true));
+    }
+
+    #[test]
+    fn test_coercion_failure_is_invalid_value() {
+        use lnmp_core::coercion::CoercionEngine;
+        use lnmp_core::registry::ExpectedType;
+
+        let engine = CoercionEngine::default().with_field_target(2, ExpectedType::Int);
+        let config = ParserConfig::default().with_coercion(engine);
+        let mut parser = Parser::with_config("F2=abc", config).unwrap();
+        assert!(matches!(
+            parser.parse_record(),
+            Err(LnmpError::InvalidValue { field_id: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_coercion_disabled_without_engine() {
+        let mut parser = Parser::new("F5=[\"1\",\"2\"]").unwrap();
+        let record = parser.parse_record().unwrap();
+        assert!(matches!(
+            record.get_field(5).unwrap().value,
+            LnmpValue::StringArray(_)
+        ));
+    }
 }
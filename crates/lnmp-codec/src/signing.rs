@@ -0,0 +1,321 @@
+//! Ed25519 signatures for binary frames and `.lnmp` containers.
+//!
+//! A signature is detached from the signed bytes and carried in a small TLV
+//! extension:
+//!
+//! ```text
+//! ┌──────────┬──────────┬──────────┬──────────────────┐
+//! │   TYPE   │   LEN    │  KEY_ID  │    SIGNATURE     │
+//! │ (1 byte) │ (1 byte) │ (u32 BE) │    (64 bytes)    │
+//! └──────────┴──────────┴──────────┴──────────────────┘
+//! ```
+//!
+//! Producers sign with a [`FrameSigner`]; recipients check signatures with a
+//! [`SignatureVerifier`] such as [`Ed25519Verifier`], which maps key IDs to
+//! trusted public keys.
+//!
+//! Signing is behind the `signing` cargo feature. Without it the types are still
+//! available but signing or verifying yields [`SignatureError::Unavailable`].
+
+use crate::encryption::KeyId;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Length of an Ed25519 secret key in bytes
+pub const SECRET_KEY_LEN: usize = 32;
+
+/// Length of an Ed25519 public key in bytes
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Length of an Ed25519 signature in bytes
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Extension type of an Ed25519 signature
+pub const EXT_ED25519_SIGNATURE: u8 = 0x01;
+
+/// Encoded size of a signature extension (`TYPE | LEN | KEY_ID | SIGNATURE`)
+pub const SIGNATURE_EXT_LEN: usize = 2 + 4 + SIGNATURE_LEN;
+
+/// A detached signature and the ID of the key that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameSignature {
+    /// Identifies the public key needed to verify the signature
+    pub key_id: KeyId,
+    /// Ed25519 signature bytes
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl FrameSignature {
+    /// Encodes the signature as a TLV extension
+    pub fn encode(&self) -> [u8; SIGNATURE_EXT_LEN] {
+        let mut bytes = [0u8; SIGNATURE_EXT_LEN];
+        bytes[0] = EXT_ED25519_SIGNATURE;
+        bytes[1] = (SIGNATURE_EXT_LEN - 2) as u8;
+        bytes[2..6].copy_from_slice(&self.key_id.to_be_bytes());
+        bytes[6..].copy_from_slice(&self.signature);
+        bytes
+    }
+
+    /// Decodes a TLV extension written by [`encode`](Self::encode)
+    pub fn decode(bytes: &[u8]) -> Result<Self, SignatureError> {
+        if bytes.len() < SIGNATURE_EXT_LEN {
+            return Err(SignatureError::Malformed(format!(
+                "signature extension needs {} bytes, found {}",
+                SIGNATURE_EXT_LEN,
+                bytes.len()
+            )));
+        }
+        if bytes[0] != EXT_ED25519_SIGNATURE {
+            return Err(SignatureError::Malformed(format!(
+                "unknown signature extension type 0x{:02X}",
+                bytes[0]
+            )));
+        }
+        if bytes[1] as usize != SIGNATURE_EXT_LEN - 2 {
+            return Err(SignatureError::Malformed(format!(
+                "Ed25519 signature extension declares {} bytes",
+                bytes[1]
+            )));
+        }
+        let mut key_id = [0u8; 4];
+        key_id.copy_from_slice(&bytes[2..6]);
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&bytes[6..SIGNATURE_EXT_LEN]);
+        Ok(Self {
+            key_id: KeyId::from_be_bytes(key_id),
+            signature,
+        })
+    }
+}
+
+/// Signs frames with an Ed25519 secret key
+///
+/// The `Debug` output never includes the secret key.
+#[derive(Clone)]
+pub struct FrameSigner {
+    key_id: KeyId,
+    secret: [u8; SECRET_KEY_LEN],
+}
+
+impl FrameSigner {
+    /// Creates a signer from a secret key, publishing signatures under `key_id`
+    pub fn new(key_id: KeyId, secret: [u8; SECRET_KEY_LEN]) -> Self {
+        Self { key_id, secret }
+    }
+
+    /// ID written next to each signature
+    pub fn key_id(&self) -> KeyId {
+        self.key_id
+    }
+
+    /// Public key recipients need to verify this signer's signatures
+    pub fn public_key(&self) -> Result<[u8; PUBLIC_KEY_LEN], SignatureError> {
+        #[cfg(feature = "signing")]
+        {
+            let key = ed25519_dalek::SigningKey::from_bytes(&self.secret);
+            Ok(key.verifying_key().to_bytes())
+        }
+        #[cfg(not(feature = "signing"))]
+        {
+            let _ = &self.secret;
+            Err(SignatureError::Unavailable)
+        }
+    }
+
+    /// Signs `message`
+    pub fn sign(&self, message: &[u8]) -> Result<FrameSignature, SignatureError> {
+        #[cfg(feature = "signing")]
+        {
+            use ed25519_dalek::Signer;
+
+            let key = ed25519_dalek::SigningKey::from_bytes(&self.secret);
+            Ok(FrameSignature {
+                key_id: self.key_id,
+                signature: key.sign(message).to_bytes(),
+            })
+        }
+        #[cfg(not(feature = "signing"))]
+        {
+            let _ = (message, &self.secret);
+            Err(SignatureError::Unavailable)
+        }
+    }
+}
+
+impl fmt::Debug for FrameSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameSigner")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Checks detached signatures against trusted keys
+pub trait SignatureVerifier: Send + Sync + fmt::Debug {
+    /// Returns `Ok(())` if `signature` is a valid signature of `message` by a trusted key
+    fn verify(&self, message: &[u8], signature: &FrameSignature) -> Result<(), SignatureError>;
+}
+
+/// Verifies Ed25519 signatures against a set of trusted public keys
+#[derive(Debug, Clone, Default)]
+pub struct Ed25519Verifier {
+    keys: HashMap<KeyId, [u8; PUBLIC_KEY_LEN]>,
+}
+
+impl Ed25519Verifier {
+    /// Creates a verifier that trusts no keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts `public_key` for signatures carrying `key_id`
+    pub fn with_key(mut self, key_id: KeyId, public_key: [u8; PUBLIC_KEY_LEN]) -> Self {
+        self.add_key(key_id, public_key);
+        self
+    }
+
+    /// Trusts `public_key` for signatures carrying `key_id`
+    pub fn add_key(&mut self, key_id: KeyId, public_key: [u8; PUBLIC_KEY_LEN]) {
+        self.keys.insert(key_id, public_key);
+    }
+
+    /// Stops trusting the key registered under `key_id`
+    pub fn remove_key(&mut self, key_id: KeyId) -> bool {
+        self.keys.remove(&key_id).is_some()
+    }
+}
+
+impl SignatureVerifier for Ed25519Verifier {
+    fn verify(&self, message: &[u8], signature: &FrameSignature) -> Result<(), SignatureError> {
+        let public_key = self
+            .keys
+            .get(&signature.key_id)
+            .ok_or(SignatureError::UnknownKey(signature.key_id))?;
+        #[cfg(feature = "signing")]
+        {
+            use ed25519_dalek::Verifier;
+
+            let key = ed25519_dalek::VerifyingKey::from_bytes(public_key)
+                .map_err(|_| SignatureError::InvalidPublicKey(signature.key_id))?;
+            key.verify(
+                message,
+                &ed25519_dalek::Signature::from_bytes(&signature.signature),
+            )
+            .map_err(|_| SignatureError::BadSignature(signature.key_id))
+        }
+        #[cfg(not(feature = "signing"))]
+        {
+            let _ = (message, public_key);
+            Err(SignatureError::Unavailable)
+        }
+    }
+}
+
+/// Errors raised while signing or verifying frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The `signing` cargo feature is not enabled
+    Unavailable,
+    /// A signature is required but the frame is not signed
+    Unsigned,
+    /// The signature extension is truncated or of an unknown type
+    Malformed(String),
+    /// No trusted public key is registered for the key ID
+    UnknownKey(KeyId),
+    /// The trusted public key for the key ID is not a valid Ed25519 key
+    InvalidPublicKey(KeyId),
+    /// The signature does not match the signed bytes
+    BadSignature(KeyId),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Unavailable => write!(f, "signing support is not enabled"),
+            SignatureError::Unsigned => write!(f, "frame is not signed"),
+            SignatureError::Malformed(reason) => write!(f, "malformed signature: {}", reason),
+            SignatureError::UnknownKey(key_id) => {
+                write!(f, "no trusted public key for key ID {}", key_id)
+            }
+            SignatureError::InvalidPublicKey(key_id) => {
+                write!(
+                    f,
+                    "public key for key ID {} is not a valid Ed25519 key",
+                    key_id
+                )
+            }
+            SignatureError::BadSignature(key_id) => write!(
+                f,
+                "signature by key ID {} does not match (tampered or forged data)",
+                key_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+
+    fn signer() -> FrameSigner {
+        FrameSigner::new(3, [7u8; SECRET_KEY_LEN])
+    }
+
+    fn verifier() -> Ed25519Verifier {
+        Ed25519Verifier::new().with_key(3, signer().public_key().unwrap())
+    }
+
+    #[test]
+    fn test_sign_verify_round_trip() {
+        let signature = signer().sign(b"F12=14532").unwrap();
+        assert_eq!(signature.key_id, 3);
+        assert_eq!(verifier().verify(b"F12=14532", &signature), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_message_is_rejected() {
+        let signature = signer().sign(b"F12=14532").unwrap();
+        assert_eq!(
+            verifier().verify(b"F12=14533", &signature),
+            Err(SignatureError::BadSignature(3))
+        );
+    }
+
+    #[test]
+    fn test_untrusted_key_is_rejected() {
+        let signature = FrameSigner::new(4, [9u8; SECRET_KEY_LEN])
+            .sign(b"payload")
+            .unwrap();
+        assert_eq!(
+            verifier().verify(b"payload", &signature),
+            Err(SignatureError::UnknownKey(4))
+        );
+
+        let mut verifier = verifier();
+        assert!(verifier.remove_key(3));
+        let signature = signer().sign(b"payload").unwrap();
+        assert_eq!(
+            verifier.verify(b"payload", &signature),
+            Err(SignatureError::UnknownKey(3))
+        );
+    }
+
+    #[test]
+    fn test_extension_round_trip() {
+        let signature = signer().sign(b"payload").unwrap();
+        let encoded = signature.encode();
+        assert_eq!(encoded[0], EXT_ED25519_SIGNATURE);
+        assert_eq!(FrameSignature::decode(&encoded), Ok(signature));
+        assert!(matches!(
+            FrameSignature::decode(&encoded[..10]),
+            Err(SignatureError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        assert_eq!(format!("{:?}", signer()), "FrameSigner { key_id: 3, .. }");
+    }
+}
@@ -6,6 +6,7 @@
 //! - v0.5 decoder can parse v0.4 binary format
 //! - v0.5 decoder can parse v0.3 text format
 //! - v0.4 decoder rejects v0.5 nested types with clear error
+//! - nested v0.5 entries decode from version 0x05 frames
 //! - v0.5 encoder produces v0.4-compatible output when nested features disabled
 //! - Semantic equivalence is maintained across version boundaries
 
@@ -216,6 +217,55 @@ fn test_v04_decoder_rejects_v05_nested_array() {
     }
 }
 
+/// Test that the decoder reads v0.5 nested record entries in a version 0x05 frame
+#[test]
+fn test_decoder_reads_v05_nested_record() {
+    let bytes = vec![
+        0x05, // VERSION (nested content)
+        0x00, // FLAGS
+        0x01, // ENTRY_COUNT = 1
+        0x0A, 0x00, // FID = 10 (little-endian)
+        0x06, // TAG = NestedRecord (0x06) - v0.5 type
+        0x01, // LENGTH = 1
+        0x00, // FIELD_COUNT = 0
+    ];
+
+    let record = BinaryDecoder::new().decode(&bytes).unwrap();
+    assert_eq!(
+        record.get_field(10).unwrap().value,
+        LnmpValue::NestedRecord(Box::new(LnmpRecord::new()))
+    );
+}
+
+/// Test that the decoder reads v0.5 nested array entries in a version 0x05 frame
+#[test]
+fn test_decoder_reads_v05_nested_array() {
+    let bytes = vec![
+        0x05, // VERSION (nested content)
+        0x00, // FLAGS
+        0x01, // ENTRY_COUNT = 1
+        0x0A, 0x00, // FID = 10 (little-endian)
+        0x07, // TAG = NestedArray (0x07) - v0.5 type
+        0x01, // LENGTH = 1
+        0x00, // ELEMENT_COUNT = 0
+    ];
+
+    let record = BinaryDecoder::new().decode(&bytes).unwrap();
+    assert_eq!(
+        record.get_field(10).unwrap().value,
+        LnmpValue::NestedArray(vec![])
+    );
+}
+
+/// Test that a truncated nested entry is rejected with a clear error
+#[test]
+fn test_decoder_rejects_truncated_nested_record() {
+    let bytes = vec![0x05, 0x00, 0x01, 0x0A, 0x00, 0x06, 0x05, 0x01];
+
+    let result = BinaryDecoder::new().decode(&bytes);
+    assert!(matches!(result, Err(BinaryError::UnexpectedEof { .. })));
+}
+
 /// Test that v0.5 encoder rejects nested structures in v0.4 compatibility mode
 #[test]
 fn test_v05_encoder_rejects_nested_in_v04_mode() {
@@ -273,33 +273,47 @@ fn test_invalid_type_tag_rejected() {
 }
 
 #[test]
-fn test_v0_5_type_tags_not_yet_implemented() {
-    // v0.5 type tags (0x06, 0x07) should be recognized but return an error
-    // indicating they're not yet implemented in BinaryEntry
-    let v0_5_tags = vec![0x06, 0x07];
-
-    for tag in v0_5_tags {
+fn test_v0_5_nested_tags_decode_length_prefixed_body() {
+    // v0.5 nested tags (0x06, 0x07) carry a LENGTH-prefixed body
+    for tag in [0x06, 0x07] {
         let bytes = vec![
             0x01, 0x00, // FID = 1
             tag,  // v0.5 TAG
-            0x00, // Some data
+            0x01, // LENGTH = 1
+            0x00, // FIELD_COUNT / ELEMENT_COUNT = 0
         ];
+        let (entry, consumed) = BinaryEntry::decode(&bytes).unwrap();
+        assert_eq!(entry.type_tag().to_u8(), tag);
+        assert_eq!(consumed, bytes.len());
 
-        let result = BinaryEntry::decode(&bytes);
+        // A zero LENGTH leaves no room for the count
+        let empty = vec![0x01, 0x00, tag, 0x00];
         assert!(
-            matches!(result, Err(BinaryError::InvalidValue { type_tag: t, .. }) if t == tag),
-            "Expected InvalidValue error for v0.5 tag 0x{:02X}, got: {:?}",
-            tag,
-            result
+            BinaryEntry::decode(&empty).is_err(),
+            "Expected error for empty body of tag 0x{:02X}",
+            tag
         );
     }
 }
 
+#[test]
+fn test_string_ref_without_table_rejected() {
+    let bytes = vec![0x01, 0x00, 0x0E, 0x00];
+    match BinaryEntry::decode(&bytes) {
+        Err(BinaryError::InvalidValue { reason, .. }) => {
+            assert!(reason.contains("without a string table"), "{}", reason)
+        }
+        other => panic!("Expected InvalidValue error, got: {:?}", other),
+    }
+}
+
 #[test]
 fn test_reserved_type_tags_rejected() {
-    // Reserved type tags (0x0A-0x0F) should be recognized but return an error
-    // Note: 0x09 (HybridNumericArray) is now implemented
-    let reserved_tags = vec![0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
+    // Reserved type tag 0x0F and the entry-level quantized embedding (0x0A)
+    // should be recognized but return an error
+    // Note: 0x09 (HybridNumericArray), typed arrays (0x0B-0x0D) and string
+    // references (0x0E) are now implemented
+    let reserved_tags = vec![0x0A, 0x0F];
 
     for tag in reserved_tags {
         let bytes = vec![
@@ -10,7 +10,7 @@
 //! - Trailing data detection
 //! - Canonical form violations
 
-use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, BinaryError, DecoderConfig};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, BinaryError, DecodeWarning, DecoderConfig};
 use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 
 // ============================================================================
@@ -50,15 +50,15 @@ fn test_unsupported_version_0x03() {
 }
 
 #[test]
-fn test_unsupported_version_0x05() {
-    let bytes = vec![0x05, 0x00, 0x00]; // Version 0x05 (future version)
+fn test_unsupported_version_0x06() {
+    let bytes = vec![0x06, 0x00, 0x00]; // Version 0x06 (future version)
     let decoder = BinaryDecoder::new();
     let result = decoder.decode(&bytes);
 
     assert!(result.is_err());
     match result {
         Err(BinaryError::UnsupportedVersion { found, .. }) => {
-            assert_eq!(found, 0x05);
+            assert_eq!(found, 0x06);
         }
         _ => panic!("Expected UnsupportedVersion error"),
     }
@@ -154,10 +154,10 @@ fn test_invalid_type_tag_0x00() {
 }
 
 #[test]
-fn test_v0_5_type_tag_0x06_not_yet_implemented() {
-    // v0.5 type tags are now valid but not yet implemented in the decoder
+fn test_v0_5_type_tag_0x06_missing_length() {
+    // v0.5 nested tags must be followed by a LENGTH VarInt
     let bytes = vec![
-        0x04, 0x00, // VERSION, FLAGS
+        0x05, 0x00, // VERSION, FLAGS
         0x01, // ENTRY_COUNT = 1
         0x01, 0x00, // FID = 1
         0x06, // TAG = 0x06 (NestedRecord - v0.5)
@@ -173,8 +173,8 @@ fn test_v0_5_type_tag_0x06_not_yet_implemented() {
         }) => {
             assert_eq!(type_tag, 0x06);
             assert!(
-                reason.contains("not yet implemented"),
-                "Expected 'not yet implemented' in error message"
+                reason.contains("nested length"),
+                "Expected 'nested length' in error message"
             );
         }
         _ => panic!("Expected InvalidValue error for v0.5 type tag"),
@@ -182,10 +182,10 @@ fn test_v0_5_type_tag_0x06_not_yet_implemented() {
 }
 
 #[test]
-fn test_v0_5_type_tag_0x07_not_yet_implemented() {
-    // v0.5 type tags are now valid but not yet implemented in the decoder
+fn test_v0_5_type_tag_0x07_missing_length() {
+    // v0.5 nested tags must be followed by a LENGTH VarInt
     let bytes = vec![
-        0x04, 0x00, // VERSION, FLAGS
+        0x05, 0x00, // VERSION, FLAGS
         0x01, // ENTRY_COUNT = 1
         0x01, 0x00, // FID = 1
         0x07, // TAG = 0x07 (NestedArray - v0.5)
@@ -201,8 +201,8 @@ fn test_v0_5_type_tag_0x07_not_yet_implemented() {
         }) => {
             assert_eq!(type_tag, 0x07);
             assert!(
-                reason.contains("not yet implemented"),
-                "Expected 'not yet implemented' in error message"
+                reason.contains("nested length"),
+                "Expected 'nested length' in error message"
             );
         }
         _ => panic!("Expected InvalidValue error for v0.5 type tag"),
@@ -308,6 +308,41 @@ fn test_valid_type_tag_0x05_string_array() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_unknown_type_tag_skipped_in_nested_record() {
+    let bytes = vec![
+        0x05, 0x00, // VERSION, FLAGS
+        0x01, // ENTRY_COUNT = 1
+        0x0A, 0x00, // FID = 10
+        0x06, // TAG = NestedRecord (0x06)
+        0x0B, // LENGTH = 11
+        0x02, // FIELD_COUNT = 2
+        0x01, 0x00, 0x0F, 0x02, 0xDE, 0xAD, // F1: reserved tag 0x0F, LENGTH 2
+        0x02, 0x00, 0x03, 0x01, // F2: Bool true
+    ];
+
+    let decoder = BinaryDecoder::new();
+    assert!(decoder.decode(&bytes).is_err());
+
+    let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_skip_unknown_tags(true));
+    let (record, warnings) = decoder.decode_with_warnings(&bytes).unwrap();
+    match &record.get_field(10).unwrap().value {
+        LnmpValue::NestedRecord(inner) => {
+            assert_eq!(inner.fields().len(), 1);
+            assert_eq!(inner.get_field(2).unwrap().value, LnmpValue::Bool(true));
+        }
+        other => panic!("Expected nested record, got: {:?}", other),
+    }
+    assert_eq!(
+        warnings,
+        vec![DecodeWarning::UnknownTypeTag {
+            fid: 1,
+            tag: 0x0F,
+            len: 2
+        }]
+    );
+}
+
 // ============================================================================
 // Task 9.3: Value Validation Tests
 // Requirements: 6.3, 6.4
@@ -346,16 +346,23 @@ fn test_version_0x03_rejected() {
 }
 
 #[test]
-fn test_version_0x05_rejected() {
+fn test_version_0x05_accepted() {
     let bytes = vec![0x05, 0x00, 0x00];
+    let frame = BinaryFrame::decode(&bytes).unwrap();
+    assert_eq!(frame.encode(), bytes);
+}
+
+#[test]
+fn test_version_0x06_rejected() {
+    let bytes = vec![0x06, 0x00, 0x00];
     let result = BinaryFrame::decode(&bytes);
 
     assert!(
         matches!(
             result,
-            Err(BinaryError::UnsupportedVersion { found: 0x05, .. })
+            Err(BinaryError::UnsupportedVersion { found: 0x06, .. })
         ),
-        "Version 0x05 should be rejected"
+        "Version 0x06 should be rejected"
     );
 }
 
@@ -374,10 +381,10 @@ fn test_version_0x_ff_rejected() {
 }
 
 #[test]
-fn test_all_non_0x04_versions_rejected() {
+fn test_all_unknown_versions_rejected() {
     for version in 0x00..=0xFF {
-        if version == 0x04 {
-            continue; // Skip valid version
+        if version == 0x04 || version == 0x05 {
+            continue; // Skip valid versions
         }
 
         let bytes = vec![version, 0x00, 0x00];
@@ -637,3 +637,94 @@ fn delta_fixture_valid_payload_applies() {
         LnmpValue::String("added".to_string())
     );
 }
+
+fn nested_record(fields: &[(u16, i64)]) -> LnmpRecord {
+    let mut record = LnmpRecord::new();
+    for &(fid, value) in fields {
+        record.add_field(LnmpField {
+            fid,
+            value: LnmpValue::Int(value),
+        });
+    }
+    record
+}
+
+#[test]
+fn test_diff_records_round_trip_with_nested_changes() {
+    let mut inner_old = nested_record(&[(1, 10), (2, 20)]);
+    inner_old.add_field(LnmpField {
+        fid: 3,
+        value: LnmpValue::NestedRecord(Box::new(nested_record(&[(1, 1), (2, 2)]))),
+    });
+    let mut inner_new = nested_record(&[(1, 10), (2, 21)]);
+    inner_new.add_field(LnmpField {
+        fid: 3,
+        value: LnmpValue::NestedRecord(Box::new(nested_record(&[(1, 1), (3, 3)]))),
+    });
+
+    let mut base = nested_record(&[(1, 1), (9, 9)]);
+    base.add_field(LnmpField {
+        fid: 50,
+        value: LnmpValue::NestedRecord(Box::new(inner_old)),
+    });
+    let mut updated = nested_record(&[(1, 2), (4, 4)]);
+    updated.add_field(LnmpField {
+        fid: 50,
+        value: LnmpValue::NestedRecord(Box::new(inner_new)),
+    });
+
+    let config = DeltaConfig::new().with_enable_delta(true);
+    let delta = DeltaEncoder::with_config(config.clone())
+        .diff_records(&base, &updated)
+        .unwrap();
+    let rebuilt = DeltaDecoder::with_config(config)
+        .apply(&base, &delta)
+        .unwrap();
+
+    assert!(rebuilt.canonical_eq(&updated));
+    assert_eq!(rebuilt.fields(), updated.sorted_fields().as_slice());
+}
+
+#[test]
+fn test_diff_records_picks_smaller_nested_encoding() {
+    let wide: Vec<(u16, i64)> = (1..=20).map(|fid| (fid, fid as i64 * 1000)).collect();
+    let mut one_changed = wide.clone();
+    one_changed[4].1 = -1;
+    let replaced = [(100, 1)];
+
+    let wrap = |fields: &[(u16, i64)]| {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::NestedRecord(Box::new(nested_record(fields))),
+        });
+        record
+    };
+
+    let encoder = DeltaEncoder::with_config(DeltaConfig::new().with_enable_delta(true));
+    let merge_ops = encoder
+        .compute_delta(&wrap(&wide), &wrap(&one_changed))
+        .unwrap();
+    assert_eq!(merge_ops[0].operation, DeltaOperation::MergeRecord);
+
+    let update_ops = encoder
+        .compute_delta(&wrap(&wide), &wrap(&replaced))
+        .unwrap();
+    assert_eq!(update_ops[0].operation, DeltaOperation::UpdateField);
+}
+
+#[test]
+fn test_diff_records_identical_records_produce_empty_delta() {
+    let record = nested_record(&[(1, 1), (2, 2)]);
+    let config = DeltaConfig::new().with_enable_delta(true);
+    let delta = DeltaEncoder::with_config(config.clone())
+        .diff_records(&record, &record)
+        .unwrap();
+    assert_eq!(delta, vec![0xB0, 0x00]);
+    assert_eq!(
+        DeltaDecoder::with_config(config)
+            .apply(&record, &delta)
+            .unwrap(),
+        record
+    );
+}
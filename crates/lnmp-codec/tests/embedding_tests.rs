@@ -1,5 +1,7 @@
 use lnmp_codec::binary::entry::BinaryEntry;
 use lnmp_codec::binary::types::{BinaryValue, TypeTag};
+use lnmp_codec::{Encoder, EncoderConfig, Parser};
+use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 use lnmp_embedding::{EmbeddingType, Vector};
 
 #[test]
@@ -26,3 +28,86 @@ fn test_embedding_encode_decode() {
     }
     assert_eq!(consumed, bytes.len());
 }
+
+fn text_round_trip(vector: Vector) {
+    let mut record = LnmpRecord::new();
+    record.add_field(LnmpField {
+        fid: 1,
+        value: LnmpValue::String("doc".to_string()),
+    });
+    record.add_field(LnmpField {
+        fid: 60,
+        value: LnmpValue::Embedding(vector),
+    });
+
+    for config in [
+        EncoderConfig::default(),
+        EncoderConfig::new().with_type_hints(true),
+    ] {
+        let text = Encoder::with_config(config).encode(&record);
+        let parsed = Parser::new(&text).unwrap().parse_record().unwrap();
+        assert_eq!(parsed, record, "{}", text);
+        let lenient = Parser::new_lenient(&text).unwrap().parse_record().unwrap();
+        assert_eq!(lenient, record, "lenient: {}", text);
+    }
+}
+
+#[test]
+fn test_embedding_text_encoding() {
+    let record = LnmpRecord::from_fields(vec![LnmpField {
+        fid: 60,
+        value: LnmpValue::Embedding(Vector::from_f32(vec![1.0, 2.0, 3.0])),
+    }]);
+    assert_eq!(
+        Encoder::new().encode(&record),
+        "F60=E[3,f32,\"AACAPwAAAEAAAEBA\"]"
+    );
+}
+
+#[test]
+fn test_embedding_text_round_trip() {
+    text_round_trip(Vector::from_f32(vec![0.25, -1.5, 3.0e-7, 42.0]));
+    text_round_trip(Vector::from_f32(vec![0.5, -0.5]).to_f16().unwrap());
+    text_round_trip(Vector::new(EmbeddingType::I8, 3, vec![0x81, 0x00, 0x7F]));
+    text_round_trip(Vector::new(EmbeddingType::Binary, 10, vec![0xFF, 0x02]));
+    text_round_trip(Vector::from_f32(vec![3.0, 4.0]).normalize().unwrap());
+
+    let mut centered = Vector::from_f32(vec![1.0, 3.0]);
+    centered.center_in_place(&[2.0, 2.0]).unwrap();
+    text_round_trip(centered.clone());
+    centered.normalize_in_place().unwrap();
+    text_round_trip(centered);
+}
+
+#[test]
+fn test_embedding_in_nested_record() {
+    let inner = LnmpRecord::from_fields(vec![LnmpField {
+        fid: 2,
+        value: LnmpValue::Embedding(Vector::from_f32(vec![1.0, -1.0])),
+    }]);
+    let record = LnmpRecord::from_fields(vec![LnmpField {
+        fid: 1,
+        value: LnmpValue::NestedRecord(Box::new(inner)),
+    }]);
+    let text = Encoder::new().encode(&record);
+    assert_eq!(Parser::new(&text).unwrap().parse_record().unwrap(), record);
+}
+
+#[test]
+fn test_invalid_embedding_text() {
+    for text in [
+        "F60=E[3,f32,\"AACAPw==\"]",    // too short for dim 3
+        "F60=E[1,f64,\"AACAPw==\"]",    // unknown dtype
+        "F60=E[1,f32,\"not base64!\"]", // bad data
+        "F60=E[1,f32,\"AACAPw==\",x]",  // unknown flag
+        "F60=E[1,f32,AACAPw]",          // unquoted data
+    ] {
+        assert!(
+            Parser::new(text)
+                .and_then(|mut p| p.parse_record())
+                .is_err(),
+            "{}",
+            text
+        );
+    }
+}
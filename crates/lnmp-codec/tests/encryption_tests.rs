@@ -0,0 +1,175 @@
+#![cfg(feature = "encryption")]
+
+//! Payload encryption tests for binary frames and `.lnmp` containers
+//!
+//! These tests verify that:
+//! - Encrypted binary frames round-trip through encoder and decoder
+//! - Compression and encryption can be combined
+//! - Decoders without keys, or with the wrong keys, fail with clear errors
+//! - Container headers and metadata are authenticated with the payload
+
+use lnmp_codec::binary::frame::FLAG_ENCRYPTED;
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, BinaryError, DecoderConfig, EncoderConfig};
+use lnmp_codec::encryption::{EncryptionKey, KeyProvider, KEY_LEN};
+use lnmp_codec::{
+    CompressionConfig, ContainerBuilder, ContainerDecodeError, ContainerFrame, EncryptionError,
+    PayloadCipher, RotatingKeyProvider, StaticKeyProvider,
+};
+use lnmp_core::{LnmpField, LnmpFileMode, LnmpRecord, LnmpValue, LNMP_FLAG_ENCRYPTED};
+use std::sync::Arc;
+
+fn keys() -> Arc<dyn KeyProvider> {
+    Arc::new(StaticKeyProvider::new(EncryptionKey::new([0x42; KEY_LEN])))
+}
+
+fn sample_record() -> LnmpRecord {
+    let mut record = LnmpRecord::new();
+    record.add_field(LnmpField {
+        fid: 7,
+        value: LnmpValue::Bool(true),
+    });
+    record.add_field(LnmpField {
+        fid: 12,
+        value: LnmpValue::Int(14532),
+    });
+    record.add_field(LnmpField {
+        fid: 20,
+        value: LnmpValue::String("sensor reading ".repeat(40)),
+    });
+    record
+}
+
+#[test]
+fn test_encrypted_frame_round_trip() {
+    let record = sample_record();
+    let config = EncoderConfig::new().with_encryption(PayloadCipher::new(keys()));
+    let bytes = BinaryEncoder::with_config(config).encode(&record).unwrap();
+    assert_eq!(bytes[1], FLAG_ENCRYPTED);
+
+    let decoder = BinaryDecoder::with_config(
+        DecoderConfig::new()
+            .with_key_provider(keys())
+            .with_strict_parsing(true),
+    );
+    assert_eq!(decoder.decode(&bytes).unwrap(), record);
+}
+
+#[test]
+fn test_compressed_and_encrypted_frame_round_trip() {
+    let record = sample_record();
+    let plain = BinaryEncoder::new().encode(&record).unwrap();
+    let config = EncoderConfig::new()
+        .with_compression(CompressionConfig::default())
+        .with_encryption(PayloadCipher::new(keys()));
+    let bytes = BinaryEncoder::with_config(config).encode(&record).unwrap();
+    assert!(bytes.len() < plain.len());
+
+    let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_key_provider(keys()));
+    assert_eq!(decoder.decode(&bytes).unwrap(), record);
+}
+
+#[test]
+fn test_encrypted_frame_without_keys_fails() {
+    let config = EncoderConfig::new().with_encryption(PayloadCipher::new(keys()));
+    let bytes = BinaryEncoder::with_config(config)
+        .encode(&sample_record())
+        .unwrap();
+
+    let err = BinaryDecoder::new().decode(&bytes).unwrap_err();
+    assert_eq!(
+        err,
+        BinaryError::Encryption(EncryptionError::MissingKeyProvider)
+    );
+}
+
+#[test]
+fn test_encrypted_frame_with_wrong_key_fails() {
+    let config = EncoderConfig::new().with_encryption(PayloadCipher::new(keys()));
+    let bytes = BinaryEncoder::with_config(config)
+        .encode(&sample_record())
+        .unwrap();
+
+    let other: Arc<dyn KeyProvider> =
+        Arc::new(StaticKeyProvider::new(EncryptionKey::new([0x24; KEY_LEN])));
+    let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_key_provider(other));
+    assert_eq!(
+        decoder.decode(&bytes).unwrap_err(),
+        BinaryError::Encryption(EncryptionError::AuthenticationFailed)
+    );
+
+    let unknown: Arc<dyn KeyProvider> =
+        Arc::new(StaticKeyProvider::new(EncryptionKey::new([0x42; KEY_LEN])).with_key_id(5));
+    let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_key_provider(unknown));
+    assert_eq!(
+        decoder.decode(&bytes).unwrap_err(),
+        BinaryError::Encryption(EncryptionError::UnknownKeyId(0))
+    );
+}
+
+#[test]
+fn test_rotated_keys_decrypt_older_frames() {
+    let mut provider = RotatingKeyProvider::new(1, EncryptionKey::new([1; KEY_LEN]));
+    let old_config =
+        EncoderConfig::new().with_encryption(PayloadCipher::new(Arc::new(provider.clone())));
+    let old = BinaryEncoder::with_config(old_config)
+        .encode(&sample_record())
+        .unwrap();
+
+    provider.rotate(2, EncryptionKey::new([2; KEY_LEN]));
+    let keys: Arc<dyn KeyProvider> = Arc::new(provider);
+    let new_config = EncoderConfig::new().with_encryption(PayloadCipher::new(keys.clone()));
+    let new = BinaryEncoder::with_config(new_config)
+        .encode(&sample_record())
+        .unwrap();
+
+    let decoder = BinaryDecoder::with_config(DecoderConfig::new().with_key_provider(keys));
+    assert_eq!(decoder.decode(&old).unwrap(), sample_record());
+    assert_eq!(decoder.decode(&new).unwrap(), sample_record());
+}
+
+#[test]
+fn test_encrypted_container_round_trip() {
+    let record = sample_record();
+    for mode in [LnmpFileMode::Text, LnmpFileMode::Binary] {
+        let bytes = ContainerBuilder::new(mode)
+            .with_compression(CompressionConfig::default())
+            .with_encryption(PayloadCipher::new(keys()))
+            .encode_record(&record)
+            .unwrap();
+
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(frame.is_encrypted());
+        assert!(frame.is_compressed());
+        assert_ne!(frame.header().flags & LNMP_FLAG_ENCRYPTED, 0);
+        assert_eq!(
+            frame.decode_record_with_keys(keys().as_ref()).unwrap(),
+            record
+        );
+        assert!(matches!(
+            frame.decode_record(),
+            Err(ContainerDecodeError::Encryption(
+                EncryptionError::MissingKeyProvider
+            ))
+        ));
+    }
+}
+
+#[test]
+fn test_container_header_is_authenticated() {
+    let mut bytes = ContainerBuilder::new(LnmpFileMode::Binary)
+        .with_metadata(vec![0xAA, 0xBB])
+        .unwrap()
+        .with_encryption(PayloadCipher::new(keys()))
+        .encode_record(&sample_record())
+        .unwrap();
+
+    // Flip a metadata byte: the payload is intact but no longer authenticates.
+    bytes[12] ^= 0x01;
+    let frame = ContainerFrame::parse(&bytes).unwrap();
+    assert!(matches!(
+        frame.decode_record_with_keys(keys().as_ref()),
+        Err(ContainerDecodeError::Encryption(
+            EncryptionError::AuthenticationFailed
+        ))
+    ));
+}
@@ -200,6 +200,7 @@ fn test_programmatic_registry() {
         status: FidStatus::Active,
         since: "0.1.0".to_string(),
         description: "Test field".to_string(),
+        ttl_ms: None,
     });
 
     let entry = registry.get(100).unwrap();
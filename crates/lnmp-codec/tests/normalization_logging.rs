@@ -0,0 +1,111 @@
+#![cfg(feature = "log")]
+
+//! Logging of normalizer and lenient coercion decisions
+//!
+//! Values rewritten by the normalizer or coerced by a lenient parser must be logged
+//! with the rule id, field ID and the value before and after.
+
+use lnmp_codec::{NormalizationConfig, Parser, ParserConfig, StringCaseRule, ValueNormalizer};
+use lnmp_core::coercion::{CoercionEngine, CoercionRules};
+use lnmp_core::registry::ExpectedType;
+use lnmp_core::LnmpValue;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::cell::RefCell;
+use std::sync::Once;
+
+thread_local! {
+    static RECORDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("lnmp::")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.with(|records| records.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger;
+static INIT: Once = Once::new();
+
+/// Runs `f` and returns the log records emitted on this thread
+fn capture(f: impl FnOnce()) -> Vec<String> {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Debug);
+    });
+    RECORDS.with(|records| records.borrow_mut().clear());
+    f();
+    RECORDS.with(|records| records.take())
+}
+
+#[test]
+fn test_numeric_to_bool_normalization_is_logged() {
+    let records = capture(|| {
+        let record = Parser::new("F7=1;F8=2").unwrap().parse_record().unwrap();
+        assert_eq!(record.get_field(7).unwrap().value, LnmpValue::Bool(true));
+    });
+    assert_eq!(
+        records,
+        vec![r#"rule=coerce.boolean_literal fid=F7 before="1" after=true"#]
+    );
+}
+
+#[test]
+fn test_type_coercion_is_logged() {
+    let engine =
+        CoercionEngine::new(CoercionRules::lenient()).with_field_target(5, ExpectedType::IntArray);
+    let records = capture(|| {
+        let config = ParserConfig::default().with_coercion(engine);
+        Parser::with_config("F5=[\"1\",\"2\"]", config)
+            .unwrap()
+            .parse_record()
+            .unwrap();
+    });
+    assert_eq!(
+        records,
+        vec![r#"rule=coerce.type fid=F5 before=StringArray(["1", "2"]) after=IntArray([1, 2])"#]
+    );
+}
+
+#[test]
+fn test_normalizer_rules_are_logged() {
+    let normalizer = ValueNormalizer::new(NormalizationConfig {
+        string_case: StringCaseRule::Lower,
+        float_precision: Some(1),
+        ..NormalizationConfig::default()
+    });
+    let records = capture(|| {
+        normalizer.normalize_with_fid(Some(3), &LnmpValue::String("Admin".to_string()));
+        normalizer.normalize_with_fid(Some(4), &LnmpValue::Float(-0.0));
+        normalizer.normalize_with_fid(Some(5), &LnmpValue::Float(2.25));
+        normalizer.normalize_with_fid(Some(6), &LnmpValue::String("ok".to_string()));
+    });
+    assert_eq!(
+        records,
+        vec![
+            r#"rule=normalize.string_case fid=F3 before="Admin" after="admin""#,
+            "rule=normalize.negative_zero fid=F4 before=-0.0 after=0.0",
+            "rule=normalize.float_precision fid=F5 before=2.25 after=2.3",
+        ]
+    );
+}
+
+#[test]
+fn test_lenient_parser_logs_sanitizer_repairs() {
+    let records = capture(|| {
+        Parser::new_lenient("F1=hello world").unwrap();
+    });
+    assert_eq!(
+        records,
+        vec![r#"rule=sanitize.auto_quote fid=F1 before="hello world" after="\"hello world\"""#]
+    );
+}
@@ -75,6 +75,10 @@ proptest! {
                 auto_escape_quotes: false,
                 normalize_booleans: false,
                 normalize_numbers: false,
+                normalize_locale_numbers: false,
+                truncation: Default::default(),
+                extract_payload: false,
+                rules: Default::default(),
             },
         );
 
@@ -165,7 +165,8 @@ fn test_feature_flag_negotiation() {
         supports_nested: true,
         supports_streaming: false, // Not supported
         supports_delta: true,
-        supports_llb: false, // Not supported
+        supports_llb: false,
+        supports_string_table: false, // Not supported
         requires_checksums: true,
         requires_canonical: true,
     };
@@ -0,0 +1,253 @@
+#![cfg(feature = "signing")]
+
+//! Frame signing tests for binary frames and `.lnmp` containers
+//!
+//! These tests verify that:
+//! - Signed binary frames decode with and without a verifier
+//! - Verifying decoders reject unsigned, tampered and untrusted frames
+//! - Signed containers set the `qsig` flag and carry a verifiable signature
+
+use lnmp_codec::binary::frame::FLAG_SIGNED;
+use lnmp_codec::binary::{
+    varint, BinaryDecoder, BinaryEncoder, BinaryError, BinaryFrame, BinaryRecordView,
+    DecoderConfig, EncoderConfig,
+};
+use lnmp_codec::signing::{SECRET_KEY_LEN, SIGNATURE_EXT_LEN};
+use lnmp_codec::{
+    ContainerBuilder, ContainerDecodeError, ContainerFrame, ContainerFrameError, Ed25519Verifier,
+    FrameSigner, SignatureError, SignatureVerifier,
+};
+use lnmp_core::{LnmpField, LnmpFileMode, LnmpRecord, LnmpValue, LNMP_FLAG_QSIG};
+use std::sync::Arc;
+
+fn signer() -> FrameSigner {
+    FrameSigner::new(1, [0x5A; SECRET_KEY_LEN])
+}
+
+fn verifier() -> Arc<dyn SignatureVerifier> {
+    Arc::new(Ed25519Verifier::new().with_key(1, signer().public_key().unwrap()))
+}
+
+fn sample_record() -> LnmpRecord {
+    let mut record = LnmpRecord::new();
+    record.add_field(LnmpField {
+        fid: 7,
+        value: LnmpValue::Bool(true),
+    });
+    record.add_field(LnmpField {
+        fid: 12,
+        value: LnmpValue::Int(14532),
+    });
+    record
+}
+
+fn signed_frame() -> Vec<u8> {
+    let config = EncoderConfig::new().with_signer(signer());
+    BinaryEncoder::with_config(config)
+        .encode(&sample_record())
+        .unwrap()
+}
+
+fn verifying_decoder() -> BinaryDecoder {
+    BinaryDecoder::with_config(
+        DecoderConfig::new()
+            .with_signature_verifier(verifier())
+            .with_strict_parsing(true),
+    )
+}
+
+#[test]
+fn test_signed_frame_round_trip() {
+    let bytes = signed_frame();
+    assert_eq!(bytes[1], FLAG_SIGNED);
+    assert_eq!(verifying_decoder().decode(&bytes).unwrap(), sample_record());
+}
+
+#[test]
+fn test_signed_frame_decodes_without_verifier() {
+    let bytes = signed_frame();
+    assert_eq!(
+        BinaryDecoder::new().decode(&bytes).unwrap(),
+        sample_record()
+    );
+}
+
+#[test]
+fn test_nested_signed_frame_is_rejected() {
+    let inner = signed_frame();
+    let signature = &inner[inner.len() - SIGNATURE_EXT_LEN..];
+    let mut bytes = vec![inner[0], FLAG_SIGNED];
+    bytes.extend_from_slice(&varint::encode(inner.len() as i64));
+    bytes.extend_from_slice(&inner);
+    bytes.extend_from_slice(signature);
+
+    assert!(matches!(
+        BinaryDecoder::new().decode(&bytes),
+        Err(BinaryError::Signature(SignatureError::Malformed(_)))
+    ));
+    assert!(matches!(
+        BinaryFrame::sign_encoded(&inner, &signer()),
+        Err(BinaryError::Signature(SignatureError::Malformed(_)))
+    ));
+}
+
+#[test]
+fn test_unsigned_frame_is_rejected() {
+    let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+    assert_eq!(
+        verifying_decoder().decode(&bytes).unwrap_err(),
+        BinaryError::Signature(SignatureError::Unsigned)
+    );
+}
+
+#[test]
+fn test_tampered_frame_is_rejected() {
+    let mut bytes = signed_frame();
+    // Last byte of the inner frame (the value of F12)
+    let inner_end = bytes.len() - lnmp_codec::signing::SIGNATURE_EXT_LEN - 1;
+    bytes[inner_end] ^= 0x01;
+    assert_eq!(
+        verifying_decoder().decode(&bytes).unwrap_err(),
+        BinaryError::Signature(SignatureError::BadSignature(1))
+    );
+}
+
+#[test]
+fn test_signed_frame_view_round_trip() {
+    let bytes = signed_frame();
+    let view = verifying_decoder().decode_view(&bytes).unwrap();
+    assert_eq!(view.to_lnmp_record(), sample_record());
+
+    let view = BinaryDecoder::new().decode_view(&bytes).unwrap();
+    assert_eq!(view.to_lnmp_record(), sample_record());
+}
+
+#[test]
+fn test_tampered_frame_view_is_rejected() {
+    let mut bytes = signed_frame();
+    let inner_end = bytes.len() - SIGNATURE_EXT_LEN - 1;
+    bytes[inner_end] ^= 0x01;
+    assert_eq!(
+        verifying_decoder().decode_view(&bytes).unwrap_err(),
+        BinaryError::Signature(SignatureError::BadSignature(1))
+    );
+    assert!(matches!(
+        BinaryRecordView::new(&bytes),
+        Err(BinaryError::UnsupportedFeature { .. })
+    ));
+}
+
+#[test]
+fn test_unsigned_frame_view_is_rejected() {
+    let bytes = BinaryEncoder::new().encode(&sample_record()).unwrap();
+    assert_eq!(
+        verifying_decoder().decode_view(&bytes).unwrap_err(),
+        BinaryError::Signature(SignatureError::Unsigned)
+    );
+}
+
+#[test]
+fn test_record_view_of_opened_signed_frame() {
+    let bytes = signed_frame();
+    let (inner, _, _) = BinaryFrame::open_signed(&bytes, Some(verifier().as_ref())).unwrap();
+    let view = BinaryRecordView::new(inner).unwrap();
+    assert_eq!(view.get_int(12).unwrap(), Some(14532));
+}
+
+#[test]
+fn test_untrusted_signer_is_rejected() {
+    let config = EncoderConfig::new().with_signer(FrameSigner::new(2, [0x11; SECRET_KEY_LEN]));
+    let bytes = BinaryEncoder::with_config(config)
+        .encode(&sample_record())
+        .unwrap();
+    assert_eq!(
+        verifying_decoder().decode(&bytes).unwrap_err(),
+        BinaryError::Signature(SignatureError::UnknownKey(2))
+    );
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn test_signed_and_encrypted_frame_round_trip() {
+    use lnmp_codec::encryption::KEY_LEN;
+    use lnmp_codec::{EncryptionKey, KeyProvider, PayloadCipher, StaticKeyProvider};
+
+    let keys: Arc<dyn KeyProvider> =
+        Arc::new(StaticKeyProvider::new(EncryptionKey::new([0x42; KEY_LEN])));
+    let config = EncoderConfig::new()
+        .with_encryption(PayloadCipher::new(keys.clone()))
+        .with_signer(signer());
+    let bytes = BinaryEncoder::with_config(config)
+        .encode(&sample_record())
+        .unwrap();
+
+    let decoder = BinaryDecoder::with_config(
+        DecoderConfig::new()
+            .with_signature_verifier(verifier())
+            .with_key_provider(keys),
+    );
+    assert_eq!(decoder.decode(&bytes).unwrap(), sample_record());
+    assert!(matches!(
+        decoder.decode_view(&bytes),
+        Err(BinaryError::UnsupportedFeature { .. })
+    ));
+}
+
+#[test]
+fn test_signed_container_round_trip() {
+    let bytes = ContainerBuilder::new(LnmpFileMode::Binary)
+        .with_signer(signer())
+        .encode_record(&sample_record())
+        .unwrap();
+
+    let frame = ContainerFrame::parse(&bytes).unwrap();
+    assert!(frame.is_signed());
+    assert_ne!(frame.header().flags & LNMP_FLAG_QSIG, 0);
+    assert_eq!(frame.signature().unwrap().key_id, 1);
+    frame.verify_signature(verifier().as_ref()).unwrap();
+    assert_eq!(frame.decode_record().unwrap(), sample_record());
+}
+
+#[test]
+fn test_tampered_container_is_rejected() {
+    let mut bytes = ContainerBuilder::new(LnmpFileMode::Text)
+        .with_signer(signer())
+        .encode_record(&sample_record())
+        .unwrap();
+    // Change the text payload "F7=1" to "F7=0"
+    let pos = bytes.iter().position(|b| *b == b'1').unwrap();
+    bytes[pos] = b'0';
+
+    let frame = ContainerFrame::parse(&bytes).unwrap();
+    assert!(matches!(
+        frame.verify_signature(verifier().as_ref()),
+        Err(ContainerDecodeError::Signature(
+            SignatureError::BadSignature(1)
+        ))
+    ));
+}
+
+#[test]
+fn test_unsigned_container_is_rejected() {
+    let bytes = ContainerBuilder::new(LnmpFileMode::Binary)
+        .encode_record(&sample_record())
+        .unwrap();
+    let frame = ContainerFrame::parse(&bytes).unwrap();
+    assert!(matches!(
+        frame.verify_signature(verifier().as_ref()),
+        Err(ContainerDecodeError::Signature(SignatureError::Unsigned))
+    ));
+}
+
+#[test]
+fn test_truncated_container_signature_fails_to_parse() {
+    let bytes = ContainerBuilder::new(LnmpFileMode::Binary)
+        .with_signer(signer())
+        .encode_record(&sample_record())
+        .unwrap();
+    let err = ContainerFrame::parse(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert!(matches!(
+        err,
+        ContainerFrameError::Signature(SignatureError::Malformed(_))
+    ));
+}
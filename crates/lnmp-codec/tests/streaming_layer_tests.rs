@@ -3,10 +3,11 @@
 //! Integration tests for Streaming Frame Layer (SFL)
 
 use lnmp_codec::binary::{
-    BackpressureController, FrameType, StreamingConfig, StreamingDecoder, StreamingEncoder,
-    StreamingError, StreamingEvent, StreamingFrame,
+    BackpressureController, BatchConfig, FrameType, RecordBatcher, StreamingConfig,
+    StreamingDecoder, StreamingEncoder, StreamingError, StreamingEvent, StreamingFrame,
 };
 use lnmp_codec::container::ContainerFrame;
+use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 use std::path::Path;
 
 #[test]
@@ -141,6 +142,31 @@ fn test_checksum_validation() {
     ));
 }
 
+#[test]
+fn test_crc32c_trailer_detects_corruption() {
+    let config = StreamingConfig::new().with_crc32c(true);
+    let mut encoder = StreamingEncoder::with_config(config.clone());
+    let mut decoder = StreamingDecoder::with_config(config);
+
+    decoder
+        .feed_frame(&encoder.begin_stream().unwrap())
+        .unwrap();
+
+    // Flip a payload bit and compensate in the XOR checksum so only the CRC catches it
+    let mut chunk = encoder.write_chunk(&[0x10, 0x20, 0x30, 0x40]).unwrap();
+    chunk[7] ^= 0x01;
+    chunk[3] ^= 0x01;
+    let result = decoder.feed_frame(&chunk);
+    assert!(matches!(result, Err(StreamingError::CorruptFrame { .. })));
+    assert!(result.unwrap_err().to_string().contains("CRC32C mismatch"));
+
+    // Frames from a peer without trailers are rejected too
+    let mut plain = StreamingEncoder::new();
+    plain.begin_stream().unwrap();
+    let result = decoder.feed_frame(&plain.write_chunk(&[1, 2, 3]).unwrap());
+    assert!(matches!(result, Err(StreamingError::CorruptFrame { .. })));
+}
+
 #[test]
 fn test_backpressure_mechanism() {
     let mut controller = BackpressureController::with_window_size(4096);
@@ -434,3 +460,91 @@ fn test_backpressure_with_realistic_scenario() {
     assert_eq!(controller.bytes_in_flight(), 0);
     assert_eq!(chunks_sent, total_data / chunk_size);
 }
+
+fn event_record(id: i64) -> LnmpRecord {
+    let mut record = LnmpRecord::new();
+    record.add_field(LnmpField {
+        fid: 1,
+        value: LnmpValue::Int(id),
+    });
+    record.add_field(LnmpField {
+        fid: 2,
+        value: LnmpValue::String("sensor.temperature".to_string()),
+    });
+    record.add_field(LnmpField {
+        fid: 3,
+        value: LnmpValue::String(if id % 2 == 0 { "ok" } else { "warn" }.to_string()),
+    });
+    record
+}
+
+#[test]
+fn test_batch_frame_roundtrip_with_crc() {
+    let config = StreamingConfig::new().with_crc32c(true);
+    let records: Vec<_> = (0..50).map(event_record).collect();
+    let batch = StreamingEncoder::with_config(config.clone())
+        .batch_frame(&records, true)
+        .unwrap();
+    assert_eq!(batch[0], FrameType::Batch.to_u8());
+
+    let mut decoder = StreamingDecoder::with_config(config);
+    assert_eq!(
+        decoder.feed_frame(&batch).unwrap(),
+        StreamingEvent::BatchReceived {
+            records: records.clone()
+        }
+    );
+
+    // One shared header and string table beat fifty separate frames
+    let encoder = lnmp_codec::binary::BinaryEncoder::new();
+    let separate: usize = records
+        .iter()
+        .map(|r| encoder.encode(r).unwrap().len())
+        .sum();
+    assert!(batch.len() < separate / 2);
+}
+
+#[test]
+fn test_record_batcher_thresholds() {
+    let mut batcher = RecordBatcher::with_config(
+        StreamingConfig::new(),
+        BatchConfig::new().with_max_records(4),
+    );
+    let mut decoder = StreamingDecoder::new();
+    let mut received = Vec::new();
+    for id in 0..10 {
+        if let Some(frame) = batcher.push(&event_record(id)).unwrap() {
+            match decoder.feed_frame(&frame).unwrap() {
+                StreamingEvent::BatchReceived { records } => received.push(records.len()),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+    assert_eq!(batcher.len(), 2);
+    let tail = batcher.flush().unwrap().unwrap();
+    assert!(batcher.is_empty());
+    assert_eq!(batcher.flush().unwrap(), None);
+    match decoder.feed_frame(&tail).unwrap() {
+        StreamingEvent::BatchReceived { records } => received.push(records.len()),
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(received, vec![4, 4, 2]);
+
+    let mut by_size =
+        RecordBatcher::with_config(StreamingConfig::new(), BatchConfig::new().with_max_bytes(1));
+    assert!(by_size.push(&event_record(1)).unwrap().is_some());
+    assert_eq!(by_size.pending_bytes(), 0);
+}
+
+#[test]
+fn test_corrupted_batch_is_rejected() {
+    let mut batch = StreamingEncoder::new()
+        .batch_frame(&[event_record(7)], false)
+        .unwrap();
+    let last = batch.len() - 1;
+    batch[last] ^= 0x01;
+    assert!(matches!(
+        StreamingDecoder::new().feed_frame(&batch),
+        Err(StreamingError::ChecksumMismatch { .. })
+    ));
+}
@@ -369,6 +369,7 @@ fn test_large_record_streaming_reassembly() {
             StreamingEvent::StreamError { message } => {
                 panic!("Stream error: {}", message);
             }
+            StreamingEvent::BatchReceived { .. } => panic!("Unexpected batch"),
         }
     }
 
@@ -549,6 +550,7 @@ fn test_successful_client_server_negotiation() {
             supports_streaming: true,
             supports_delta: true,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: true,
             requires_canonical: true,
         },
@@ -571,6 +573,7 @@ fn test_successful_client_server_negotiation() {
             supports_streaming: true,
             supports_delta: false,
             supports_llb: true,
+            supports_string_table: true,
             requires_checksums: false,
             requires_canonical: true,
         },
@@ -649,6 +652,7 @@ fn test_negotiation_with_fid_mappings() {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: true,
         },
@@ -690,6 +694,7 @@ fn test_negotiation_version_mismatch() {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: false,
         },
@@ -703,6 +708,7 @@ fn test_negotiation_version_mismatch() {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: false,
         },
@@ -742,6 +748,7 @@ fn test_negotiation_feature_intersection() {
             supports_streaming: true,
             supports_delta: true,
             supports_llb: true,
+            supports_string_table: true,
             requires_checksums: false,
             requires_canonical: true,
         },
@@ -756,6 +763,7 @@ fn test_negotiation_feature_intersection() {
             supports_streaming: false,
             supports_delta: false,
             supports_llb: false,
+            supports_string_table: false,
             requires_checksums: false,
             requires_canonical: true,
         },
@@ -56,6 +56,7 @@ fn successful_negotiation() {
         supports_streaming: true,
         supports_delta: true,
         supports_llb: true,
+        supports_string_table: true,
         requires_checksums: false,
         requires_canonical: true,
     };
@@ -89,6 +90,7 @@ fn successful_negotiation() {
         supports_streaming: true,
         supports_delta: false, // Server doesn't support delta
         supports_llb: true,
+        supports_string_table: true,
         requires_checksums: true, // Server requires checksums
         requires_canonical: true,
     };
@@ -161,6 +163,7 @@ fn feature_flag_negotiation() {
         supports_streaming: true,
         supports_delta: true,
         supports_llb: true,
+        supports_string_table: true,
         requires_checksums: false,
         requires_canonical: true,
     };
@@ -177,6 +180,7 @@ fn feature_flag_negotiation() {
         supports_streaming: false,
         supports_delta: false,
         supports_llb: false,
+        supports_string_table: false,
         requires_checksums: true,
         requires_canonical: true,
     };
@@ -360,6 +364,7 @@ fn partial_feature_support() {
         supports_streaming: true,
         supports_delta: true,
         supports_llb: true,
+        supports_string_table: true,
         requires_checksums: false,
         requires_canonical: true,
     };
@@ -370,6 +375,7 @@ fn partial_feature_support() {
         supports_streaming: true,
         supports_delta: false,
         supports_llb: false,
+        supports_string_table: false,
         requires_checksums: false,
         requires_canonical: true,
     };
@@ -0,0 +1,500 @@
+//! Value coercion matrix.
+//!
+//! LLM output and JSON input often carry the right information in the wrong type:
+//! `"42"` for an integer, `1` for a boolean, `3` for a float. This module defines which
+//! of those conversions are allowed ([`CoercionRules`]) and applies them towards a target
+//! type ([`CoercionEngine`]). The parser's loose mode and the JSON bridge in `lnmp-codec`
+//! both use it, so a value is coerced the same way regardless of how it arrived.
+//!
+//! | from \ to | Int | Float | Bool | String | arrays |
+//! |-----------|-----|-------|------|--------|--------|
+//! | Int       | -   | `int_to_float` | `number_to_bool` (0/1) | `scalar_to_string` | `scalar_to_array` |
+//! | Float     | `float_to_int` (integral only) | - | - | `scalar_to_string` | `scalar_to_array` |
+//! | Bool      | `bool_to_number` | `bool_to_number` | - | `scalar_to_string` | `scalar_to_array` |
+//! | String    | `string_to_number` | `string_to_number` | `string_to_bool` | - | `scalar_to_array` |
+//!
+//! Arrays are coerced element by element with the same rules, so an untyped
+//! `F5=[1,2,3]` (parsed as a string array) becomes an `IntArray` when F5 is registered as
+//! `int_array`.
+//!
+//! Target types come from, in order of precedence: an explicit per-FID target
+//! ([`CoercionEngine::with_field_target`]), the attached [`FidRegistry`], or the type
+//! requested by the caller (e.g. a `:f` type hint).
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_core::coercion::{CoercionEngine, CoercionRules};
+//! use lnmp_core::registry::ExpectedType;
+//! use lnmp_core::LnmpValue;
+//!
+//! let engine = CoercionEngine::new(CoercionRules::lenient())
+//!     .with_field_target(7, ExpectedType::Bool);
+//!
+//! let value = engine.coerce_to(7, LnmpValue::String("yes".into()), ExpectedType::Bool);
+//! assert_eq!(value.unwrap(), LnmpValue::Bool(true));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::registry::{ExpectedType, FidRegistry};
+use crate::{FieldId, LnmpField, LnmpRecord, LnmpValue};
+
+/// Which conversions between value types are permitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoercionRules {
+    /// Int → Float
+    pub int_to_float: bool,
+    /// Float → Int, only when the float has no fractional part and fits in `i64`
+    pub float_to_int: bool,
+    /// String → Int/Float (`"42"`, `"2.5"`)
+    pub string_to_number: bool,
+    /// String → Bool (`"true"`/`"false"`/`"yes"`/`"no"`/`"1"`/`"0"`, case-insensitive)
+    pub string_to_bool: bool,
+    /// Int → Bool (only `0` and `1`)
+    pub number_to_bool: bool,
+    /// Bool → Int/Float (`1`/`0`)
+    pub bool_to_number: bool,
+    /// Int/Float/Bool → String
+    pub scalar_to_string: bool,
+    /// Single value → one-element array (and record → record array)
+    pub scalar_to_array: bool,
+}
+
+impl Default for CoercionRules {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+impl CoercionRules {
+    /// Rules that permit no conversions.
+    pub fn none() -> Self {
+        Self {
+            int_to_float: false,
+            float_to_int: false,
+            string_to_number: false,
+            string_to_bool: false,
+            number_to_bool: false,
+            bool_to_number: false,
+            scalar_to_string: false,
+            scalar_to_array: false,
+        }
+    }
+
+    /// Rules that permit every lossless conversion (default).
+    pub fn lenient() -> Self {
+        Self {
+            int_to_float: true,
+            float_to_int: true,
+            string_to_number: true,
+            string_to_bool: true,
+            number_to_bool: true,
+            bool_to_number: true,
+            scalar_to_string: true,
+            scalar_to_array: true,
+        }
+    }
+
+    /// Interprets a text literal as a boolean, if `string_to_bool` is enabled.
+    ///
+    /// This is the single boolean truth table shared by the parser, the JSON bridge and
+    /// the `lnmp-sanitize` boolean normalization.
+    pub fn bool_from_text(&self, text: &str) -> Option<bool> {
+        if !self.string_to_bool {
+            return None;
+        }
+        bool_literal(text)
+    }
+
+    /// Converts a single value to `target`, or returns `None` if the rules forbid it.
+    pub fn convert(&self, value: &LnmpValue, target: ExpectedType) -> Option<LnmpValue> {
+        if value_matches(value, target) {
+            return Some(value.clone());
+        }
+        match target {
+            ExpectedType::Any => Some(value.clone()),
+            ExpectedType::Int => self.int_of(value).map(LnmpValue::Int),
+            ExpectedType::Float => self.float_of(value).map(LnmpValue::Float),
+            ExpectedType::Bool => self.bool_of(value).map(LnmpValue::Bool),
+            ExpectedType::String => self.string_of(value).map(LnmpValue::String),
+            ExpectedType::IntArray => self
+                .elements(value, |v| self.int_of(v))
+                .map(LnmpValue::IntArray),
+            ExpectedType::FloatArray => self
+                .elements(value, |v| self.float_of(v))
+                .map(LnmpValue::FloatArray),
+            ExpectedType::BoolArray => self
+                .elements(value, |v| self.bool_of(v))
+                .map(LnmpValue::BoolArray),
+            ExpectedType::StringArray => self
+                .elements(value, |v| self.string_of(v))
+                .map(LnmpValue::StringArray),
+            ExpectedType::Record => None,
+            ExpectedType::RecordArray => match value {
+                LnmpValue::NestedRecord(record) if self.scalar_to_array => {
+                    Some(LnmpValue::NestedArray(vec![(**record).clone()]))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    fn int_of(&self, value: &LnmpValue) -> Option<i64> {
+        match value {
+            LnmpValue::Int(i) => Some(*i),
+            LnmpValue::Float(f) if self.float_to_int => float_as_int(*f),
+            LnmpValue::Bool(b) if self.bool_to_number => Some(*b as i64),
+            LnmpValue::String(s) if self.string_to_number => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn float_of(&self, value: &LnmpValue) -> Option<f64> {
+        match value {
+            LnmpValue::Float(f) => Some(*f),
+            LnmpValue::Int(i) if self.int_to_float => Some(*i as f64),
+            LnmpValue::Bool(b) if self.bool_to_number => Some(if *b { 1.0 } else { 0.0 }),
+            LnmpValue::String(s) if self.string_to_number => {
+                s.trim().parse::<f64>().ok().filter(|f| f.is_finite())
+            }
+            _ => None,
+        }
+    }
+
+    fn bool_of(&self, value: &LnmpValue) -> Option<bool> {
+        match value {
+            LnmpValue::Bool(b) => Some(*b),
+            LnmpValue::Int(0) if self.number_to_bool => Some(false),
+            LnmpValue::Int(1) if self.number_to_bool => Some(true),
+            LnmpValue::String(s) => self.bool_from_text(s),
+            _ => None,
+        }
+    }
+
+    fn string_of(&self, value: &LnmpValue) -> Option<String> {
+        match value {
+            LnmpValue::String(s) => Some(s.clone()),
+            LnmpValue::Int(i) if self.scalar_to_string => Some(i.to_string()),
+            LnmpValue::Float(f) if self.scalar_to_string => Some(f.to_string()),
+            LnmpValue::Bool(b) if self.scalar_to_string => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Converts an array (or a scalar, if `scalar_to_array` is enabled) element-wise.
+    fn elements<T>(
+        &self,
+        value: &LnmpValue,
+        convert: impl Fn(&LnmpValue) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let items: Vec<LnmpValue> = match value {
+            LnmpValue::StringArray(items) => items.iter().cloned().map(LnmpValue::String).collect(),
+            LnmpValue::IntArray(items) => items.iter().copied().map(LnmpValue::Int).collect(),
+            LnmpValue::FloatArray(items) => items.iter().copied().map(LnmpValue::Float).collect(),
+            LnmpValue::BoolArray(items) => items.iter().copied().map(LnmpValue::Bool).collect(),
+            LnmpValue::Int(_) | LnmpValue::Float(_) | LnmpValue::Bool(_) | LnmpValue::String(_)
+                if self.scalar_to_array =>
+            {
+                vec![value.clone()]
+            }
+            _ => return None,
+        };
+        items.iter().map(convert).collect()
+    }
+}
+
+/// Error returned when a value cannot be coerced to its target type.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("cannot coerce F{fid} from {from} to {to:?}")]
+pub struct CoercionError {
+    /// Field ID of the value
+    pub fid: FieldId,
+    /// Kind of the original value (e.g. `"string"`)
+    pub from: &'static str,
+    /// Requested target type
+    pub to: ExpectedType,
+}
+
+/// Applies [`CoercionRules`] towards per-field target types.
+#[derive(Debug, Clone, Default)]
+pub struct CoercionEngine {
+    rules: CoercionRules,
+    field_rules: HashMap<FieldId, CoercionRules>,
+    field_targets: HashMap<FieldId, ExpectedType>,
+    registry: Option<Arc<FidRegistry>>,
+}
+
+impl CoercionEngine {
+    /// Creates an engine applying `rules` to every field.
+    pub fn new(rules: CoercionRules) -> Self {
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+
+    /// Overrides the rules for a single field.
+    pub fn with_field_rules(mut self, fid: FieldId, rules: CoercionRules) -> Self {
+        self.field_rules.insert(fid, rules);
+        self
+    }
+
+    /// Sets the target type of a field, taking precedence over the registry.
+    pub fn with_field_target(mut self, fid: FieldId, target: ExpectedType) -> Self {
+        self.field_targets.insert(fid, target);
+        self
+    }
+
+    /// Uses the registry's expected types as per-field targets.
+    pub fn with_registry(mut self, registry: Arc<FidRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Returns the rules that apply to fields without an override.
+    pub fn rules(&self) -> &CoercionRules {
+        &self.rules
+    }
+
+    /// Returns the rules that apply to `fid`.
+    pub fn rules_for(&self, fid: FieldId) -> &CoercionRules {
+        self.field_rules.get(&fid).unwrap_or(&self.rules)
+    }
+
+    /// Returns the configured target type of `fid`, if any.
+    pub fn target_for(&self, fid: FieldId) -> Option<ExpectedType> {
+        self.field_targets.get(&fid).copied().or_else(|| {
+            self.registry
+                .as_ref()
+                .and_then(|registry| registry.get(fid))
+                .map(|entry| entry.expected_type)
+        })
+    }
+
+    /// Coerces `value` to `target` using the rules for `fid`.
+    pub fn coerce_to(
+        &self,
+        fid: FieldId,
+        value: LnmpValue,
+        target: ExpectedType,
+    ) -> Result<LnmpValue, CoercionError> {
+        if value_matches(&value, target) {
+            return Ok(value);
+        }
+        self.rules_for(fid)
+            .convert(&value, target)
+            .ok_or(CoercionError {
+                fid,
+                from: kind_name(&value),
+                to: target,
+            })
+    }
+
+    /// Coerces a field to its configured target type; fields without a target are
+    /// returned unchanged.
+    pub fn coerce_field(&self, field: LnmpField) -> Result<LnmpField, CoercionError> {
+        match self.target_for(field.fid) {
+            Some(target) => Ok(LnmpField {
+                fid: field.fid,
+                value: self.coerce_to(field.fid, field.value, target)?,
+            }),
+            None => Ok(field),
+        }
+    }
+
+    /// Coerces every top-level field of `record`.
+    pub fn coerce_record(&self, record: &LnmpRecord) -> Result<LnmpRecord, CoercionError> {
+        let fields = record
+            .fields()
+            .iter()
+            .cloned()
+            .map(|field| self.coerce_field(field))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(LnmpRecord::from_sorted_fields(fields))
+    }
+}
+
+/// Boolean literal table: `true`/`yes`/`1` and `false`/`no`/`0`, case-insensitive.
+fn bool_literal(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn float_as_int(f: f64) -> Option<i64> {
+    (f.is_finite() && f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64)
+        .then_some(f as i64)
+}
+
+fn value_matches(value: &LnmpValue, target: ExpectedType) -> bool {
+    matches!(
+        (value, target),
+        (_, ExpectedType::Any)
+            | (LnmpValue::Int(_), ExpectedType::Int)
+            | (LnmpValue::Float(_), ExpectedType::Float)
+            | (LnmpValue::Bool(_), ExpectedType::Bool)
+            | (LnmpValue::String(_), ExpectedType::String)
+            | (LnmpValue::StringArray(_), ExpectedType::StringArray)
+            | (LnmpValue::IntArray(_), ExpectedType::IntArray)
+            | (LnmpValue::FloatArray(_), ExpectedType::FloatArray)
+            | (LnmpValue::BoolArray(_), ExpectedType::BoolArray)
+            | (LnmpValue::NestedRecord(_), ExpectedType::Record)
+            | (LnmpValue::NestedArray(_), ExpectedType::RecordArray)
+    )
+}
+
+fn kind_name(value: &LnmpValue) -> &'static str {
+    match value {
+        LnmpValue::Int(_) => "int",
+        LnmpValue::Float(_) => "float",
+        LnmpValue::Bool(_) => "bool",
+        LnmpValue::String(_) => "string",
+        LnmpValue::StringArray(_) => "string array",
+        LnmpValue::IntArray(_) => "int array",
+        LnmpValue::FloatArray(_) => "float array",
+        LnmpValue::BoolArray(_) => "bool array",
+        LnmpValue::NestedRecord(_) => "record",
+        LnmpValue::NestedArray(_) => "record array",
+        LnmpValue::Embedding(_) | LnmpValue::EmbeddingDelta(_) => "embedding",
+        #[cfg(feature = "quant")]
+        LnmpValue::QuantizedEmbedding(_) => "quantized embedding",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(text: &str) -> LnmpValue {
+        LnmpValue::String(text.to_string())
+    }
+
+    #[test]
+    fn test_scalar_matrix() {
+        let rules = CoercionRules::lenient();
+        assert_eq!(
+            rules.convert(&LnmpValue::Int(3), ExpectedType::Float),
+            Some(LnmpValue::Float(3.0))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::Float(4.0), ExpectedType::Int),
+            Some(LnmpValue::Int(4))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::Float(4.5), ExpectedType::Int),
+            None
+        );
+        assert_eq!(
+            rules.convert(&s(" 42 "), ExpectedType::Int),
+            Some(LnmpValue::Int(42))
+        );
+        assert_eq!(
+            rules.convert(&s("2.5"), ExpectedType::Float),
+            Some(LnmpValue::Float(2.5))
+        );
+        assert_eq!(
+            rules.convert(&s("YES"), ExpectedType::Bool),
+            Some(LnmpValue::Bool(true))
+        );
+        assert_eq!(
+            rules.convert(&s("0"), ExpectedType::Bool),
+            Some(LnmpValue::Bool(false))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::Int(1), ExpectedType::Bool),
+            Some(LnmpValue::Bool(true))
+        );
+        assert_eq!(rules.convert(&LnmpValue::Int(2), ExpectedType::Bool), None);
+        assert_eq!(
+            rules.convert(&LnmpValue::Bool(true), ExpectedType::Int),
+            Some(LnmpValue::Int(1))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::Int(7), ExpectedType::String),
+            Some(s("7"))
+        );
+        assert_eq!(rules.convert(&s("abc"), ExpectedType::Int), None);
+    }
+
+    #[test]
+    fn test_rules_none_only_allows_identity() {
+        let rules = CoercionRules::none();
+        assert_eq!(rules.convert(&LnmpValue::Int(3), ExpectedType::Float), None);
+        assert_eq!(rules.convert(&s("true"), ExpectedType::Bool), None);
+        assert_eq!(
+            rules.convert(&LnmpValue::Int(3), ExpectedType::Int),
+            Some(LnmpValue::Int(3))
+        );
+        assert_eq!(rules.bool_from_text("true"), None);
+    }
+
+    #[test]
+    fn test_array_coercion() {
+        let rules = CoercionRules::lenient();
+        let strings = LnmpValue::StringArray(vec!["1".into(), "2".into(), "3".into()]);
+        assert_eq!(
+            rules.convert(&strings, ExpectedType::IntArray),
+            Some(LnmpValue::IntArray(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::IntArray(vec![1, 2]), ExpectedType::FloatArray),
+            Some(LnmpValue::FloatArray(vec![1.0, 2.0]))
+        );
+        assert_eq!(
+            rules.convert(&LnmpValue::Int(5), ExpectedType::IntArray),
+            Some(LnmpValue::IntArray(vec![5]))
+        );
+        let mixed = LnmpValue::StringArray(vec!["1".into(), "x".into()]);
+        assert_eq!(rules.convert(&mixed, ExpectedType::IntArray), None);
+    }
+
+    #[test]
+    fn test_engine_targets_and_overrides() {
+        let registry = FidRegistry::from_yaml_str(
+            "core:\n  - fid: 20\n    name: ratio\n    type: Float\n    status: ACTIVE\n",
+        )
+        .unwrap();
+        let engine = CoercionEngine::new(CoercionRules::lenient())
+            .with_registry(Arc::new(registry))
+            .with_field_target(7, ExpectedType::Bool)
+            .with_field_rules(8, CoercionRules::none())
+            .with_field_target(8, ExpectedType::Int);
+
+        assert_eq!(engine.target_for(20), Some(ExpectedType::Float));
+        assert_eq!(engine.target_for(99), None);
+
+        let coerced = engine
+            .coerce_field(LnmpField {
+                fid: 20,
+                value: LnmpValue::Int(3),
+            })
+            .unwrap();
+        assert_eq!(coerced.value, LnmpValue::Float(3.0));
+
+        let err = engine
+            .coerce_field(LnmpField {
+                fid: 8,
+                value: s("42"),
+            })
+            .unwrap_err();
+        assert_eq!(
+            err,
+            CoercionError {
+                fid: 8,
+                from: "string",
+                to: ExpectedType::Int
+            }
+        );
+        assert_eq!(err.to_string(), "cannot coerce F8 from string to Int");
+
+        let untouched = LnmpField {
+            fid: 99,
+            value: s("42"),
+        };
+        assert_eq!(engine.coerce_field(untouched.clone()).unwrap(), untouched);
+    }
+}
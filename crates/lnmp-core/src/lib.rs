@@ -68,11 +68,13 @@
 
 pub mod builder;
 pub mod checksum;
+pub mod coercion;
 pub mod container;
 pub mod limits;
 pub mod profile;
 pub mod record;
 pub mod registry;
+pub mod retrieval;
 pub mod types;
 
 pub use builder::RecordBuilder;
@@ -84,4 +86,5 @@ pub use container::{
 pub use limits::{StructuralError, StructuralLimits};
 pub use profile::{LnmpProfile, StrictDeterministicConfig};
 pub use record::{FieldOrderingError, LnmpField, LnmpFieldView, LnmpRecord, LnmpRecordView};
+pub use retrieval::{retrieve_top_k, retrieve_top_k_filtered, RetrievalError};
 pub use types::{FieldId, LnmpValue, LnmpValueView, TypeHint};
@@ -51,6 +51,8 @@ pub struct FidEntry {
     pub since: String,
     /// Description of the field
     pub description: String,
+    /// Freshness horizon in milliseconds; older readings are stale
+    pub ttl_ms: Option<u64>,
 }
 
 /// Expected type for a FID
@@ -155,6 +157,17 @@ pub enum ValidationResult {
     },
 }
 
+/// A field whose reading is older than its registered TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleField {
+    /// Field ID
+    pub fid: u16,
+    /// Age of the reading in milliseconds
+    pub age_ms: u64,
+    /// Registered TTL in milliseconds
+    pub ttl_ms: u64,
+}
+
 /// Error when loading or parsing the registry
 #[derive(Debug, thiserror::Error)]
 pub enum RegistryError {
@@ -252,6 +265,7 @@ impl FidRegistry {
                         "since" => builder.since = Some(value),
                         "description" => builder.description = Some(value),
                         "unit" => builder.unit = Some(value),
+                        "ttl_ms" => builder.ttl_ms = value.parse().ok(),
                         _ => {}
                     }
                 }
@@ -397,6 +411,37 @@ impl FidRegistry {
         }
     }
 
+    /// Get the TTL of a FID in milliseconds, if one is registered
+    pub fn ttl_ms(&self, fid: u16) -> Option<u64> {
+        self.entries.get(&fid).and_then(|entry| entry.ttl_ms)
+    }
+
+    /// Find top-level fields of a record whose readings have outlived their TTL
+    ///
+    /// `timestamp_ms` is when the record was produced (e.g. the envelope
+    /// timestamp) and `now_ms` the current time, both in Unix milliseconds.
+    /// Fields without a registered TTL are never stale.
+    pub fn stale_fields(
+        &self,
+        record: &LnmpRecord,
+        timestamp_ms: u64,
+        now_ms: u64,
+    ) -> Vec<StaleField> {
+        let age_ms = now_ms.saturating_sub(timestamp_ms);
+        record
+            .fields()
+            .iter()
+            .filter_map(|field| {
+                let ttl_ms = self.ttl_ms(field.fid)?;
+                (age_ms > ttl_ms).then_some(StaleField {
+                    fid: field.fid,
+                    age_ms,
+                    ttl_ms,
+                })
+            })
+            .collect()
+    }
+
     /// Add an entry to the registry (for testing/programmatic use)
     pub fn add_entry(&mut self, entry: FidEntry) {
         self.entries.insert(entry.fid, entry);
@@ -439,6 +484,23 @@ impl ExpectedType {
             _ => None,
         }
     }
+
+    /// Returns the expected type corresponding to a type hint, if there is one
+    pub fn from_type_hint(hint: TypeHint) -> Option<Self> {
+        match hint {
+            TypeHint::Int => Some(Self::Int),
+            TypeHint::Float => Some(Self::Float),
+            TypeHint::Bool => Some(Self::Bool),
+            TypeHint::String => Some(Self::String),
+            TypeHint::StringArray => Some(Self::StringArray),
+            TypeHint::IntArray => Some(Self::IntArray),
+            TypeHint::FloatArray => Some(Self::FloatArray),
+            TypeHint::BoolArray => Some(Self::BoolArray),
+            TypeHint::Record => Some(Self::Record),
+            TypeHint::RecordArray => Some(Self::RecordArray),
+            _ => None,
+        }
+    }
 }
 
 impl FidStatus {
@@ -477,6 +539,7 @@ struct FidEntryBuilder {
     description: Option<String>,
     #[allow(dead_code)]
     unit: Option<String>,
+    ttl_ms: Option<u64>,
 }
 
 impl FidEntryBuilder {
@@ -489,6 +552,7 @@ impl FidEntryBuilder {
             since: None,
             description: None,
             unit: None,
+            ttl_ms: None,
         }
     }
 
@@ -515,6 +579,7 @@ impl FidEntryBuilder {
             status,
             since: self.since.unwrap_or_default(),
             description: self.description.unwrap_or_default(),
+            ttl_ms: self.ttl_ms,
         })
     }
 }
@@ -695,6 +760,39 @@ standard:
     description: "Position"
 "#;
 
+    #[test]
+    fn test_stale_fields() {
+        let yaml = TEST_YAML.replace(
+            "    description: \"Position\"",
+            "    description: \"Position\"\n    ttl_ms: 5000",
+        );
+        let registry = FidRegistry::from_yaml_str(&yaml).unwrap();
+        assert_eq!(registry.ttl_ms(256), Some(5000));
+        assert_eq!(registry.ttl_ms(12), None);
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(1),
+        });
+        record.add_field(LnmpField {
+            fid: 256,
+            value: LnmpValue::FloatArray(vec![1.0, 2.0]),
+        });
+
+        assert!(registry.stale_fields(&record, 10_000, 15_000).is_empty());
+        assert_eq!(
+            registry.stale_fields(&record, 10_000, 15_001),
+            vec![StaleField {
+                fid: 256,
+                age_ms: 5001,
+                ttl_ms: 5000,
+            }]
+        );
+        // Timestamps from the future count as fresh
+        assert!(registry.stale_fields(&record, 20_000, 15_000).is_empty());
+    }
+
     #[test]
     fn test_parse_registry() {
         let registry = FidRegistry::from_yaml_str(TEST_YAML).unwrap();
@@ -0,0 +1,252 @@
+//! Top-k similarity retrieval over collections of records.
+//!
+//! [`retrieve_top_k`] scores the embedding stored under one field of every record
+//! against a query vector and returns the best matches. Both `Embedding` and (with
+//! the `quant` feature) `QuantizedEmbedding` values are scored; records without an
+//! embedding in that field are skipped.
+
+use crate::{FieldId, LnmpRecord, LnmpValue};
+use lnmp_embedding::{EmbeddingType, SimilarityMetric, Vector};
+
+/// Errors returned by [`retrieve_top_k`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RetrievalError {
+    /// The query vector cannot be scored (e.g. unsupported dtype).
+    #[error("invalid query vector: {0}")]
+    InvalidQuery(String),
+    /// A record's embedding could not be scored against the query.
+    #[error("record {index}: invalid embedding: {reason}")]
+    InvalidEmbedding {
+        /// Position of the record in the input collection.
+        index: usize,
+        /// Why the embedding could not be scored.
+        reason: String,
+    },
+}
+
+/// Returns the `k` records whose embedding in `embedding_fid` is most similar
+/// (by cosine similarity) to `query`, best first.
+///
+/// Records without an embedding in that field are skipped. Ties keep input order.
+///
+/// # Errors
+///
+/// Returns an error if the query is not an F32/F16 vector, or if a record's
+/// embedding cannot be scored against it (e.g. its dimension differs).
+///
+/// # Example
+///
+/// ```
+/// use lnmp_core::retrieval::retrieve_top_k;
+/// use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+/// use lnmp_embedding::Vector;
+///
+/// let records: Vec<LnmpRecord> = [vec![1.0, 0.0], vec![0.0, 1.0]]
+///     .into_iter()
+///     .map(|values| {
+///         LnmpRecord::from_fields(vec![LnmpField {
+///             fid: 10,
+///             value: LnmpValue::Embedding(Vector::from_f32(values)),
+///         }])
+///     })
+///     .collect();
+///
+/// let query = Vector::from_f32(vec![0.1, 0.9]);
+/// let top = retrieve_top_k(&query, &records, 10, 1).unwrap();
+/// assert!(std::ptr::eq(top[0].0, &records[1]));
+/// ```
+pub fn retrieve_top_k<'a>(
+    query: &Vector,
+    records: &'a [LnmpRecord],
+    embedding_fid: FieldId,
+    k: usize,
+) -> Result<Vec<(&'a LnmpRecord, f32)>, RetrievalError> {
+    retrieve_top_k_filtered(query, records, embedding_fid, k, |_| true)
+}
+
+/// Like [`retrieve_top_k`], but only scores records for which `filter` returns
+/// true.
+///
+/// The filter runs before any embedding is decoded, so cheap predicates over other
+/// fields (tenant, language, timestamps, ...) narrow the candidates for free.
+pub fn retrieve_top_k_filtered<'a, F>(
+    query: &Vector,
+    records: &'a [LnmpRecord],
+    embedding_fid: FieldId,
+    k: usize,
+    mut filter: F,
+) -> Result<Vec<(&'a LnmpRecord, f32)>, RetrievalError>
+where
+    F: FnMut(&LnmpRecord) -> bool,
+{
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+    let query = query.to_f32().map_err(RetrievalError::InvalidQuery)?;
+
+    let mut scored = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        if !filter(record) {
+            continue;
+        }
+        let Some(field) = record.get_field(embedding_fid) else {
+            continue;
+        };
+        let score = match score(&query, &field.value) {
+            Some(Ok(score)) => score,
+            Some(Err(reason)) => return Err(RetrievalError::InvalidEmbedding { index, reason }),
+            None => continue,
+        };
+        scored.push((record, score));
+    }
+
+    // Stable sort: ties keep input order; NaN scores rank last
+    scored.sort_by(|a, b| match (a.1.is_nan(), b.1.is_nan()) {
+        (false, false) => b.1.total_cmp(&a.1),
+        (nan_a, nan_b) => nan_a.cmp(&nan_b),
+    });
+    scored.truncate(k);
+    Ok(scored)
+}
+
+/// Cosine similarity of an embedding value with the (F32) query, or `None` if
+/// the value is not an embedding.
+fn score(query: &Vector, value: &LnmpValue) -> Option<Result<f32, String>> {
+    let vector = match value {
+        LnmpValue::Embedding(vector) if vector.dtype == EmbeddingType::F32 => {
+            return Some(query.similarity(vector, SimilarityMetric::Cosine));
+        }
+        LnmpValue::Embedding(vector) => vector.to_f32(),
+        #[cfg(feature = "quant")]
+        LnmpValue::QuantizedEmbedding(quantized) => {
+            lnmp_quant::dequantize_embedding(quantized).map_err(|e| e.to_string())
+        }
+        _ => return None,
+    };
+    Some(vector.and_then(|vector| query.similarity(&vector, SimilarityMetric::Cosine)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LnmpField;
+
+    fn record(id: i64, embedding: Option<Vec<f32>>) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(id),
+        });
+        if let Some(values) = embedding {
+            record.add_field(LnmpField {
+                fid: 2,
+                value: LnmpValue::Embedding(Vector::from_f32(values)),
+            });
+        }
+        record
+    }
+
+    fn ids(results: &[(&LnmpRecord, f32)]) -> Vec<i64> {
+        results
+            .iter()
+            .map(|(r, _)| match r.get_field(1).unwrap().value {
+                LnmpValue::Int(id) => id,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ranks_and_truncates() {
+        let records = vec![
+            record(1, Some(vec![0.0, 1.0])),
+            record(2, Some(vec![1.0, 0.0])),
+            record(3, None),
+            record(4, Some(vec![1.0, 1.0])),
+            record(5, Some(vec![2.0, 0.0])),
+        ];
+        let query = Vector::from_f32(vec![1.0, 0.0]);
+
+        let top = retrieve_top_k(&query, &records, 2, 3).unwrap();
+        // 2 and 5 tie at 1.0 and keep input order
+        assert_eq!(ids(&top), vec![2, 5, 4]);
+        assert!((top[0].1 - 1.0).abs() < 1e-6);
+        assert!((top[2].1 - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+        assert_eq!(retrieve_top_k(&query, &records, 2, 10).unwrap().len(), 4);
+        assert!(retrieve_top_k(&query, &records, 2, 0).unwrap().is_empty());
+        assert!(retrieve_top_k(&query, &records, 9, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_filter_and_mixed_dtypes() {
+        let mut records = vec![
+            record(1, Some(vec![1.0, 0.0])),
+            record(2, Some(vec![0.9, 0.1])),
+        ];
+        records.push(LnmpRecord::from_fields(vec![
+            LnmpField {
+                fid: 1,
+                value: LnmpValue::Int(3),
+            },
+            LnmpField {
+                fid: 2,
+                value: LnmpValue::Embedding(Vector::from_f32(vec![0.5, 0.5]).to_f16().unwrap()),
+            },
+        ]));
+        let query = Vector::from_f32(vec![1.0, 1.0]).to_f16().unwrap();
+
+        let top = retrieve_top_k_filtered(&query, &records, 2, 5, |r| {
+            !matches!(r.get_field(1).unwrap().value, LnmpValue::Int(1))
+        })
+        .unwrap();
+        assert_eq!(ids(&top), vec![3, 2]);
+        assert!((top[0].1 - 1.0).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "quant")]
+    #[test]
+    fn test_quantized_embeddings() {
+        use lnmp_quant::{quantize_embedding, QuantScheme};
+
+        let near = Vector::from_f32(vec![0.9, 0.1, 0.0, 0.0]);
+        let far = Vector::from_f32(vec![0.0, 0.0, 1.0, 0.0]);
+        let records: Vec<LnmpRecord> = [(1, &far), (2, &near)]
+            .into_iter()
+            .map(|(id, vector)| {
+                LnmpRecord::from_fields(vec![
+                    LnmpField {
+                        fid: 1,
+                        value: LnmpValue::Int(id),
+                    },
+                    LnmpField {
+                        fid: 2,
+                        value: LnmpValue::QuantizedEmbedding(
+                            quantize_embedding(vector, QuantScheme::QInt8).unwrap(),
+                        ),
+                    },
+                ])
+            })
+            .collect();
+
+        let query = Vector::from_f32(vec![1.0, 0.0, 0.0, 0.0]);
+        let top = retrieve_top_k(&query, &records, 2, 1).unwrap();
+        assert_eq!(ids(&top), vec![2]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let records = vec![record(1, Some(vec![1.0, 0.0])), record(2, Some(vec![1.0]))];
+        let query = Vector::from_f32(vec![1.0, 0.0]);
+        assert!(matches!(
+            retrieve_top_k(&query, &records, 2, 1),
+            Err(RetrievalError::InvalidEmbedding { index: 1, .. })
+        ));
+
+        let binary = Vector::new(EmbeddingType::Binary, 8, vec![0xFF]);
+        assert!(matches!(
+            retrieve_top_k(&binary, &records, 2, 1),
+            Err(RetrievalError::InvalidQuery(_))
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use lnmp_embedding::{Decoder, Encoder, SimilarityMetric, Vector, VectorDelta};
+use lnmp_embedding::{batch_similarity, Decoder, Encoder, SimilarityMetric, Vector, VectorDelta};
 
 fn bench_vector_creation(c: &mut Criterion) {
     let mut group = c.benchmark_group("vector_creation");
@@ -66,6 +66,25 @@ fn bench_similarity_cosine(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_batch_similarity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_similarity_cosine_10k");
+
+    for dim in [384, 768, 1536].iter() {
+        let query = Vector::from_f32((0..*dim).map(|i| i as f32 * 0.01).collect());
+        let vectors: Vec<Vector> = (0..10_000)
+            .map(|n| Vector::from_f32((0..*dim).map(|i| ((i + n) % 97) as f32 * 0.01).collect()))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(dim), dim, |b, _| {
+            b.iter(|| {
+                batch_similarity(&query, black_box(&vectors), SimilarityMetric::Cosine).unwrap()
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_similarity_euclidean(c: &mut Criterion) {
     let mut group = c.benchmark_group("similarity_euclidean");
 
@@ -204,6 +223,7 @@ criterion_group!(
     bench_similarity_cosine,
     bench_similarity_euclidean,
     bench_similarity_dotproduct,
+    bench_batch_similarity,
     bench_roundtrip,
     bench_delta_compute,
     bench_delta_encode,
@@ -0,0 +1,241 @@
+//! Batch similarity between one query and many embeddings.
+//!
+//! [`batch_similarity`] scores thousands of F32 vectors per call for context
+//! selection. The query is decoded and (for cosine) normed once, and the inner
+//! loops keep eight independent accumulators so the compiler emits packed SIMD
+//! instructions on stable Rust without `unsafe`. With the `parallel` feature, large
+//! batches are split into chunks scored on the rayon thread pool.
+
+use crate::vector::{EmbeddingType, SimilarityMetric, Vector};
+
+/// Accumulator lanes per inner loop iteration (one AVX register of `f32`).
+const LANES: usize = 8;
+
+/// Smallest batch scored in parallel; below it the thread hand-off costs more
+/// than it saves.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1024;
+
+/// Vectors scored per rayon task.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK: usize = 256;
+
+/// Scores every vector against `query`, in input order.
+///
+/// Scores match [`Vector::similarity`]: cosine similarity, dot product, or
+/// euclidean distance. Only F32 vectors are supported.
+///
+/// # Errors
+///
+/// Returns an error, naming the offending index, if a vector does not have the
+/// query's dtype and dimension.
+///
+/// # Example
+///
+/// ```
+/// use lnmp_embedding::{batch_similarity, SimilarityMetric, Vector};
+///
+/// let query = Vector::from_f32(vec![1.0, 0.0]);
+/// let vectors = vec![
+///     Vector::from_f32(vec![1.0, 0.0]),
+///     Vector::from_f32(vec![0.0, 1.0]),
+/// ];
+/// let scores = batch_similarity(&query, &vectors, SimilarityMetric::Cosine).unwrap();
+/// assert_eq!(scores, vec![1.0, 0.0]);
+/// ```
+pub fn batch_similarity(
+    query: &Vector,
+    vectors: &[Vector],
+    metric: SimilarityMetric,
+) -> Result<Vec<f32>, String> {
+    if query.dtype != EmbeddingType::F32 {
+        return Err("Batch similarity only supported for F32 embeddings".to_string());
+    }
+    let query_values = query.as_f32()?;
+    for (i, vector) in vectors.iter().enumerate() {
+        if vector.dtype != query.dtype {
+            return Err(format!("DType mismatch at index {}", i));
+        }
+        if vector.dim != query.dim || vector.data.len() != query.data.len() {
+            return Err(format!("Dimension mismatch at index {}", i));
+        }
+    }
+
+    let query_norm = match metric {
+        SimilarityMetric::Cosine => dot(&query_values, &query.data).sqrt(),
+        _ => 0.0,
+    };
+    let score = |vector: &Vector| score(&query_values, query_norm, &vector.data, metric);
+
+    #[cfg(feature = "parallel")]
+    if vectors.len() >= PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+        return Ok(vectors
+            .par_chunks(PARALLEL_CHUNK)
+            .flat_map_iter(|chunk| chunk.iter().map(score))
+            .collect());
+    }
+
+    Ok(vectors.iter().map(score).collect())
+}
+
+fn score(query: &[f32], query_norm: f32, data: &[u8], metric: SimilarityMetric) -> f32 {
+    match metric {
+        SimilarityMetric::Cosine => {
+            let (dot, norm_sq) = dot_and_norm(query, data);
+            let norm = norm_sq.sqrt();
+            if query_norm == 0.0 || norm == 0.0 {
+                0.0
+            } else {
+                dot / (query_norm * norm)
+            }
+        }
+        SimilarityMetric::DotProduct => dot(query, data),
+        SimilarityMetric::Euclidean => distance_sq(query, data).sqrt(),
+    }
+}
+
+#[inline]
+fn lane(bytes: &[u8], i: usize) -> f32 {
+    f32::from_le_bytes([
+        bytes[i * 4],
+        bytes[i * 4 + 1],
+        bytes[i * 4 + 2],
+        bytes[i * 4 + 3],
+    ])
+}
+
+/// Dot product of `query` with little-endian f32 `data`.
+#[inline]
+fn dot(query: &[f32], data: &[u8]) -> f32 {
+    let mut acc = [0.0f32; LANES];
+    let q_chunks = query.chunks_exact(LANES);
+    let d_chunks = data.chunks_exact(LANES * 4);
+    let (q_rest, d_rest) = (q_chunks.remainder(), d_chunks.remainder());
+    for (q, d) in q_chunks.zip(d_chunks) {
+        for i in 0..LANES {
+            acc[i] += q[i] * lane(d, i);
+        }
+    }
+    let mut sum: f32 = acc.iter().sum();
+    for (i, q) in q_rest.iter().enumerate() {
+        sum += q * lane(d_rest, i);
+    }
+    sum
+}
+
+/// Dot product of `query` with `data`, and the squared norm of `data`.
+#[inline]
+fn dot_and_norm(query: &[f32], data: &[u8]) -> (f32, f32) {
+    let mut dot = [0.0f32; LANES];
+    let mut norm = [0.0f32; LANES];
+    let q_chunks = query.chunks_exact(LANES);
+    let d_chunks = data.chunks_exact(LANES * 4);
+    let (q_rest, d_rest) = (q_chunks.remainder(), d_chunks.remainder());
+    for (q, d) in q_chunks.zip(d_chunks) {
+        for i in 0..LANES {
+            let v = lane(d, i);
+            dot[i] += q[i] * v;
+            norm[i] += v * v;
+        }
+    }
+    let (mut dot_sum, mut norm_sum): (f32, f32) = (dot.iter().sum(), norm.iter().sum());
+    for (i, q) in q_rest.iter().enumerate() {
+        let v = lane(d_rest, i);
+        dot_sum += q * v;
+        norm_sum += v * v;
+    }
+    (dot_sum, norm_sum)
+}
+
+/// Squared euclidean distance between `query` and `data`.
+#[inline]
+fn distance_sq(query: &[f32], data: &[u8]) -> f32 {
+    let mut acc = [0.0f32; LANES];
+    let q_chunks = query.chunks_exact(LANES);
+    let d_chunks = data.chunks_exact(LANES * 4);
+    let (q_rest, d_rest) = (q_chunks.remainder(), d_chunks.remainder());
+    for (q, d) in q_chunks.zip(d_chunks) {
+        for i in 0..LANES {
+            let diff = q[i] - lane(d, i);
+            acc[i] += diff * diff;
+        }
+    }
+    let mut sum: f32 = acc.iter().sum();
+    for (i, q) in q_rest.iter().enumerate() {
+        let diff = q - lane(d_rest, i);
+        sum += diff * diff;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors(count: usize, dim: usize) -> Vec<Vector> {
+        (0..count)
+            .map(|i| {
+                Vector::from_f32(
+                    (0..dim)
+                        .map(|j| ((i * 31 + j * 17) % 23) as f32 / 11.0 - 1.0)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_pairwise_similarity() {
+        // 19 dims exercises both the 8-lane loop and the remainder
+        let vectors = vectors(3000, 19);
+        let query = Vector::from_f32((0..19).map(|j| j as f32 * 0.1 - 0.9).collect());
+
+        for metric in [
+            SimilarityMetric::Cosine,
+            SimilarityMetric::DotProduct,
+            SimilarityMetric::Euclidean,
+        ] {
+            let scores = batch_similarity(&query, &vectors, metric).unwrap();
+            assert_eq!(scores.len(), vectors.len());
+            for (vector, score) in vectors.iter().zip(&scores) {
+                let expected = query.similarity(vector, metric).unwrap();
+                assert!(
+                    (expected - score).abs() <= 1e-4 * expected.abs().max(1.0),
+                    "{metric:?}: {expected} vs {score}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_vectors_and_empty_batch() {
+        let query = Vector::from_f32(vec![0.0; 4]);
+        let scores = batch_similarity(
+            &query,
+            &[Vector::from_f32(vec![1.0; 4])],
+            SimilarityMetric::Cosine,
+        )
+        .unwrap();
+        assert_eq!(scores, vec![0.0]);
+        assert!(batch_similarity(&query, &[], SimilarityMetric::Cosine)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_vectors() {
+        let query = Vector::from_f32(vec![1.0, 0.0]);
+        let vectors = vec![
+            Vector::from_f32(vec![1.0, 0.0]),
+            Vector::from_f32(vec![1.0, 0.0, 0.0]),
+        ];
+        assert_eq!(
+            batch_similarity(&query, &vectors, SimilarityMetric::Cosine),
+            Err("Dimension mismatch at index 1".to_string())
+        );
+
+        let f16 = Vector::new(EmbeddingType::F16, 2, vec![0; 4]);
+        assert!(batch_similarity(&f16, &vectors, SimilarityMetric::Cosine).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Base + delta chains for continuously updated embeddings.
+//!
+//! [`DeltaChain`] keeps a base vector and the ordered [`VectorDelta`]s applied to it
+//! since. Once the chain grows past its limit it compacts itself, either merging the
+//! deltas into one or folding them into a new base, as its [`UpdateStrategy`]
+//! decides.
+
+use crate::decoder::Decoder;
+use crate::delta::{DeltaChange, UpdateStrategy, VectorDelta};
+use crate::encoder::Encoder;
+use crate::vector::{EmbeddingType, Vector};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+
+/// A base vector plus the deltas applied to it, oldest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaChain {
+    base_id: u16,
+    base: Vector,
+    deltas: Vec<VectorDelta>,
+    max_len: usize,
+    strategy: UpdateStrategy,
+}
+
+impl DeltaChain {
+    /// Starts a chain from an F32 base vector.
+    ///
+    /// Compacts once more than 16 deltas are chained, using the default
+    /// (adaptive) strategy.
+    pub fn new(base_id: u16, base: Vector) -> Result<Self, String> {
+        if base.dtype != EmbeddingType::F32 {
+            return Err("Delta chains only supported for F32 embeddings".to_string());
+        }
+        Ok(Self {
+            base_id,
+            base,
+            deltas: Vec::new(),
+            max_len: 16,
+            strategy: UpdateStrategy::default(),
+        })
+    }
+
+    /// Sets how many deltas are kept before compacting.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len.max(1);
+        self
+    }
+
+    /// Sets the strategy deciding between merging deltas and rebasing.
+    pub fn with_strategy(mut self, strategy: UpdateStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// ID of the current base vector.
+    pub fn base_id(&self) -> u16 {
+        self.base_id
+    }
+
+    /// The current base vector.
+    pub fn base(&self) -> &Vector {
+        &self.base
+    }
+
+    /// The chained deltas, oldest first.
+    pub fn deltas(&self) -> &[VectorDelta] {
+        &self.deltas
+    }
+
+    /// Number of chained deltas.
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    /// Returns true if no delta is chained.
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    /// Appends a delta, compacting if the chain becomes too long.
+    pub fn push(&mut self, delta: VectorDelta) -> Result<(), String> {
+        if let Some(change) = delta.changes.iter().find(|c| c.index >= self.base.dim) {
+            return Err(format!("Invalid index {} in delta", change.index));
+        }
+        self.deltas.push(delta);
+        if self.deltas.len() > self.max_len {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Appends the delta from the current value to `vector`.
+    pub fn push_vector(&mut self, vector: &Vector) -> Result<(), String> {
+        let delta = VectorDelta::from_vectors(&self.materialize()?, vector, self.base_id)?;
+        self.push(delta)
+    }
+
+    /// Returns the current value: the base with every delta applied.
+    ///
+    /// Deltas are applied in place on one buffer, without intermediate vectors.
+    pub fn materialize(&self) -> Result<Vector, String> {
+        let mut values = self.base.as_f32()?;
+        for change in self.deltas.iter().flat_map(|d| &d.changes) {
+            values[change.index as usize] += change.delta;
+        }
+        Ok(Vector::from_f32(values))
+    }
+
+    /// Merges the chained deltas into one; changes that cancel out are dropped.
+    pub fn merged_delta(&self) -> VectorDelta {
+        let mut sums: BTreeMap<u16, f32> = BTreeMap::new();
+        for change in self.deltas.iter().flat_map(|d| &d.changes) {
+            *sums.entry(change.index).or_default() += change.delta;
+        }
+        let changes = sums
+            .into_iter()
+            .filter(|(_, delta)| delta.abs() > f32::EPSILON)
+            .map(|(index, delta)| DeltaChange { index, delta })
+            .collect();
+        VectorDelta::new(self.base_id, changes)
+    }
+
+    /// Folds every delta into the base, which takes `new_base_id`.
+    pub fn rebase(&mut self, new_base_id: u16) -> Result<(), String> {
+        self.base = self.materialize()?;
+        self.base_id = new_base_id;
+        self.deltas.clear();
+        Ok(())
+    }
+
+    /// Shortens the chain: merges the deltas into one if the strategy would still
+    /// send the merged delta, otherwise rebases onto the current value.
+    ///
+    /// A rebase increments the base ID (wrapping).
+    pub fn compact(&mut self) -> Result<(), String> {
+        if self.deltas.is_empty() {
+            return Ok(());
+        }
+        let merged = self.merged_delta();
+        if self.strategy.should_use_delta(&merged, self.base.dim) {
+            self.deltas = vec![merged];
+            Ok(())
+        } else {
+            self.rebase(self.base_id.wrapping_add(1))
+        }
+    }
+
+    /// Encode the chain to binary format
+    /// Format: base_id (u16) | base_len (u32) | base (embedding encoding) |
+    /// delta_count (u16) | [delta (VectorDelta encoding), ...]
+    pub fn encode(&self) -> Result<Vec<u8>, std::io::Error> {
+        let base = Encoder::encode(&self.base)?;
+        let mut buf = Vec::with_capacity(
+            8 + base.len() + self.deltas.iter().map(|d| d.encoded_size()).sum::<usize>(),
+        );
+
+        buf.write_u16::<LittleEndian>(self.base_id)?;
+        buf.write_u32::<LittleEndian>(base.len() as u32)?;
+        buf.extend_from_slice(&base);
+        buf.write_u16::<LittleEndian>(self.deltas.len() as u16)?;
+        for delta in &self.deltas {
+            buf.extend_from_slice(&delta.encode()?);
+        }
+
+        Ok(buf)
+    }
+
+    /// Decode a chain from binary format
+    ///
+    /// The decoded chain uses the default limit and strategy.
+    pub fn decode(data: &[u8]) -> Result<Self, std::io::Error> {
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+        let mut rdr = Cursor::new(data);
+
+        let base_id = rdr.read_u16::<LittleEndian>()?;
+        let base_len = rdr.read_u32::<LittleEndian>()? as usize;
+        let mut base = vec![0u8; base_len.min(data.len())];
+        rdr.read_exact(&mut base)?;
+        if base.len() != base_len {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut chain = Self::new(base_id, Decoder::decode(&base)?).map_err(invalid)?;
+
+        let delta_count = rdr.read_u16::<LittleEndian>()?;
+        let mut pos = rdr.position() as usize;
+        for _ in 0..delta_count {
+            let delta = VectorDelta::decode(&data[pos..])?;
+            pos += delta.encoded_size();
+            if delta.changes.iter().any(|c| c.index >= chain.base.dim) {
+                return Err(invalid("Delta index out of range".to_string()));
+            }
+            chain.deltas.push(delta);
+        }
+
+        Ok(chain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(index: u16, delta: f32) -> DeltaChange {
+        DeltaChange { index, delta }
+    }
+
+    #[test]
+    fn test_materialize_and_merge() {
+        let mut chain = DeltaChain::new(1, Vector::from_f32(vec![1.0, 2.0, 3.0, 4.0])).unwrap();
+        chain
+            .push(VectorDelta::new(1, vec![change(0, 0.5), change(2, 1.0)]))
+            .unwrap();
+        chain
+            .push(VectorDelta::new(1, vec![change(2, -1.0), change(3, 0.25)]))
+            .unwrap();
+        assert_eq!(
+            chain.materialize().unwrap().as_f32().unwrap(),
+            vec![1.5, 2.0, 3.0, 4.25]
+        );
+
+        // The changes to index 2 cancel out
+        let merged = chain.merged_delta();
+        assert_eq!(merged.changes, vec![change(0, 0.5), change(3, 0.25)]);
+
+        assert!(chain
+            .push(VectorDelta::new(1, vec![change(4, 1.0)]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_compaction() {
+        let base = Vector::from_f32(vec![0.0; 100]);
+
+        // Small updates to the same entries: merged into one delta
+        let mut chain = DeltaChain::new(7, base.clone()).unwrap().with_max_len(3);
+        for _ in 0..4 {
+            chain
+                .push(VectorDelta::new(7, vec![change(1, 0.5), change(2, 0.25)]))
+                .unwrap();
+        }
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain.base_id(), 7);
+        assert_eq!(chain.materialize().unwrap().as_f32().unwrap()[1], 2.0);
+
+        // Updates touching most entries: folded into a new base
+        let mut chain = DeltaChain::new(7, base).unwrap().with_max_len(3);
+        for step in 0..4u16 {
+            let changes = (0..20).map(|i| change(step * 20 + i, 1.0)).collect();
+            chain.push(VectorDelta::new(7, changes)).unwrap();
+        }
+        assert!(chain.is_empty());
+        assert_eq!(chain.base_id(), 8);
+        let values = chain.base().as_f32().unwrap();
+        assert_eq!(values.iter().filter(|v| **v == 1.0).count(), 80);
+    }
+
+    #[test]
+    fn test_push_vector_and_encoding() {
+        let mut chain = DeltaChain::new(3, Vector::from_f32(vec![1.0, 2.0, 3.0])).unwrap();
+        let target = Vector::from_f32(vec![1.0, 2.5, 3.0]);
+        chain.push_vector(&target).unwrap();
+        chain
+            .push(VectorDelta::new(3, vec![change(0, -1.0)]))
+            .unwrap();
+
+        let encoded = chain.encode().unwrap();
+        let decoded = DeltaChain::decode(&encoded).unwrap();
+        assert_eq!(decoded, chain);
+        assert_eq!(
+            decoded.materialize().unwrap().as_f32().unwrap(),
+            vec![0.0, 2.5, 3.0]
+        );
+
+        assert!(DeltaChain::decode(&encoded[..encoded.len() - 2]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::vector::{EmbeddingType, Vector};
+use crate::vector::{EmbeddingType, Vector, VectorTransform};
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Cursor;
 
@@ -10,7 +10,8 @@ impl Decoder {
 
         let dim = rdr.read_u16::<LittleEndian>()?;
         let dtype_byte = rdr.read_u8()?;
-        let _similarity = rdr.read_u8()?; // Read and ignore for now
+        // Low bits: similarity hint (ignored for now); high bits: transform flags
+        let similarity = rdr.read_u8()?;
 
         let dtype = match dtype_byte {
             0x01 => EmbeddingType::F32,
@@ -31,10 +32,7 @@ impl Decoder {
 
         // Validation could happen here (check length matches dim * type_size)
 
-        Ok(Vector {
-            dim,
-            dtype,
-            data: vector_data,
-        })
+        Ok(Vector::new(dtype, dim, vector_data)
+            .with_transform(VectorTransform::from_bits(similarity)))
     }
 }
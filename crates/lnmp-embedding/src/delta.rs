@@ -1,3 +1,4 @@
+use crate::sparse::SparseVector;
 use crate::vector::{EmbeddingType, Vector};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
@@ -72,6 +73,72 @@ impl VectorDelta {
         Ok(Vector::from_f32(values))
     }
 
+    /// Compute delta between two sparse vectors
+    /// Entries present in only one of them count as changes from or to zero;
+    /// indices must fit in `u16`
+    pub fn from_sparse(
+        old: &SparseVector,
+        new: &SparseVector,
+        base_id: u16,
+    ) -> Result<Self, String> {
+        if old.dim != new.dim {
+            return Err("Dimension mismatch between vectors".to_string());
+        }
+
+        let mut changes = Vec::new();
+        let mut push = |index: u32, delta: f32| -> Result<(), String> {
+            if delta.abs() > f32::EPSILON {
+                let index = u16::try_from(index)
+                    .map_err(|_| format!("Index {} exceeds delta index range", index))?;
+                changes.push(DeltaChange { index, delta });
+            }
+            Ok(())
+        };
+
+        let (mut a, mut b) = (0, 0);
+        while a < old.nnz() || b < new.nnz() {
+            let old_index = old.indices.get(a).copied().unwrap_or(u32::MAX);
+            let new_index = new.indices.get(b).copied().unwrap_or(u32::MAX);
+            if old_index < new_index {
+                push(old_index, -old.values[a])?;
+                a += 1;
+            } else if new_index < old_index {
+                push(new_index, new.values[b])?;
+                b += 1;
+            } else {
+                push(old_index, new.values[b] - old.values[a])?;
+                a += 1;
+                b += 1;
+            }
+        }
+
+        Ok(Self { base_id, changes })
+    }
+
+    /// Apply delta to a sparse base vector
+    /// Entries that become zero are dropped
+    pub fn apply_sparse(&self, base: &SparseVector) -> Result<SparseVector, String> {
+        let mut pairs: Vec<(u32, f32)> = base
+            .indices
+            .iter()
+            .copied()
+            .zip(base.values.iter().copied())
+            .collect();
+
+        for change in &self.changes {
+            let idx = change.index as u32;
+            if idx >= base.dim {
+                return Err(format!("Invalid index {} in delta", idx));
+            }
+            match pairs.binary_search_by_key(&idx, |(i, _)| *i) {
+                Ok(pos) => pairs[pos].1 += change.delta,
+                Err(pos) => pairs.insert(pos, (idx, change.delta)),
+            }
+        }
+
+        SparseVector::from_pairs(base.dim, pairs)
+    }
+
     /// Encode delta to binary format
     /// Format: base_id (u16) | change_count (u16) | [(index: u16, delta: f32), ...]
     pub fn encode(&self) -> Result<Vec<u8>, std::io::Error> {
@@ -257,6 +324,23 @@ mod tests {
         assert_eq!(new, reconstructed);
     }
 
+    #[test]
+    fn test_sparse_delta_roundtrip() {
+        let old = SparseVector::new(30522, vec![7, 120, 2048], vec![0.5, 1.25, 2.0]).unwrap();
+        let new = SparseVector::new(30522, vec![7, 900, 2048], vec![0.75, 1.5, 2.0]).unwrap();
+
+        let delta = VectorDelta::from_sparse(&old, &new, 3).unwrap();
+        let indices: Vec<u16> = delta.changes.iter().map(|c| c.index).collect();
+        assert_eq!(indices, vec![7, 120, 900]);
+
+        let decoded = VectorDelta::decode(&delta.encode().unwrap()).unwrap();
+        assert_eq!(decoded.apply_sparse(&old).unwrap(), new);
+
+        let wide = SparseVector::new(100_000, vec![70_000], vec![1.0]).unwrap();
+        let empty = SparseVector::new(100_000, vec![], vec![]).unwrap();
+        assert!(VectorDelta::from_sparse(&empty, &wide, 1).is_err());
+    }
+
     #[test]
     fn test_update_strategy() {
         let small_delta = VectorDelta::new(
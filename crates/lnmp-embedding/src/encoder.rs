@@ -21,7 +21,8 @@ impl Encoder {
 
         buf.write_u16::<LittleEndian>(vector.dim)?;
         buf.write_u8(vector.dtype as u8)?;
-        buf.write_u8(0x01)?; // Default to Cosine for now
+        // Default to Cosine for now; the high bits record the applied transforms
+        buf.write_u8(0x01 | vector.transform.to_bits())?;
         buf.write_all(&vector.data)?;
 
         Ok(buf)
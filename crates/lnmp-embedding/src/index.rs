@@ -0,0 +1,803 @@
+//! Approximate nearest-neighbour search over embeddings.
+//!
+//! [`HnswIndex`] is a Hierarchical Navigable Small World graph (Malkov & Yashunin)
+//! over vectors keyed by record ID. It answers top-k queries by cosine similarity,
+//! dot product or euclidean distance in roughly logarithmic time, which makes it
+//! suitable for agent memory retrieval over many thousands of records.
+//!
+//! The index can be saved as an LNMP/Embedding container (`.lnmp` mode `0x06`) and
+//! loaded back without rebuilding the graph.
+
+use crate::vector::{SimilarityMetric, Vector};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::io::{Cursor, Read};
+
+/// Errors raised by [`HnswIndex`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum IndexError {
+    /// The vector does not have the index dimension
+    #[error("Dimension mismatch: index has {expected}, vector has {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+    /// The vector cannot be read as `f32` components
+    #[error("Invalid vector: {0}")]
+    InvalidVector(String),
+    /// Persisted index bytes are malformed
+    #[error("Invalid index data: {0}")]
+    InvalidData(String),
+}
+
+/// Vectors that can be inserted into or queried against an [`HnswIndex`].
+///
+/// Implemented for F32 [`Vector`]s and `f32` slices; `lnmp-quant` implements it for
+/// quantized vectors by dequantizing them.
+pub trait IndexVector {
+    /// Returns the vector components as `f32`.
+    fn to_f32_vec(&self) -> Result<Vec<f32>, String>;
+}
+
+impl IndexVector for Vector {
+    fn to_f32_vec(&self) -> Result<Vec<f32>, String> {
+        self.as_f32()
+    }
+}
+
+impl IndexVector for [f32] {
+    fn to_f32_vec(&self) -> Result<Vec<f32>, String> {
+        Ok(self.to_vec())
+    }
+}
+
+impl IndexVector for Vec<f32> {
+    fn to_f32_vec(&self) -> Result<Vec<f32>, String> {
+        Ok(self.clone())
+    }
+}
+
+/// A query result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// Record ID given on insert
+    pub id: u64,
+    /// Cosine similarity, dot product or euclidean distance, as in
+    /// [`Vector::similarity`]
+    pub score: f32,
+}
+
+/// Highest layer a node may be assigned to.
+const MAX_LEVEL: usize = 16;
+
+/// Payload magic of a persisted index.
+const INDEX_MAGIC: [u8; 4] = *b"HNSW";
+const INDEX_VERSION: u8 = 1;
+
+/// `.lnmp` container header fields, as defined by `lnmp_core::container` (which
+/// depends on this crate and so cannot be used here).
+const CONTAINER_MAGIC: [u8; 4] = *b"LNMP";
+const CONTAINER_VERSION: u8 = 1;
+const CONTAINER_MODE_EMBEDDING: u8 = 0x06;
+const CONTAINER_HEADER_SIZE: usize = 12;
+
+#[derive(Debug, Clone)]
+struct Node {
+    id: u64,
+    vector: Vec<f32>,
+    /// Neighbours per layer, from layer 0 up to the node's level
+    neighbors: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    dist: f32,
+    node: u32,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// HNSW approximate nearest-neighbour index keyed by record ID.
+///
+/// Removing a record only marks its node as deleted so the graph stays connected;
+/// deleted nodes are skipped in results and dropped by [`compact`](Self::compact).
+/// Inserting an existing ID replaces its vector.
+///
+/// # Example
+///
+/// ```
+/// use lnmp_embedding::index::HnswIndex;
+/// use lnmp_embedding::{SimilarityMetric, Vector};
+///
+/// let mut index = HnswIndex::new(3, SimilarityMetric::Cosine);
+/// index.insert(1, &Vector::from_f32(vec![1.0, 0.0, 0.0])).unwrap();
+/// index.insert(2, &Vector::from_f32(vec![0.0, 1.0, 0.0])).unwrap();
+///
+/// let hits = index.query(&Vector::from_f32(vec![0.9, 0.1, 0.0]), 1).unwrap();
+/// assert_eq!(hits[0].id, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HnswIndex {
+    dim: u16,
+    metric: SimilarityMetric,
+    m: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    rng: u64,
+    nodes: Vec<Node>,
+    ids: HashMap<u64, u32>,
+    entry: Option<u32>,
+    deleted: usize,
+}
+
+impl HnswIndex {
+    /// Creates an empty index for `dim`-dimensional vectors.
+    ///
+    /// Defaults: 16 links per node (32 on layer 0), `ef_construction` 200 and
+    /// `ef_search` 50.
+    pub fn new(dim: u16, metric: SimilarityMetric) -> Self {
+        Self {
+            dim,
+            metric,
+            m: 16,
+            ef_construction: 200,
+            ef_search: 50,
+            rng: 0x2545_f491_4f6c_dd1d,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            deleted: 0,
+        }
+    }
+
+    /// Sets the number of links per node (at least 2); more links improve recall
+    /// at the cost of memory and insert time.
+    pub fn with_m(mut self, m: usize) -> Self {
+        self.m = m.max(2);
+        self
+    }
+
+    /// Sets the candidate list size used while inserting.
+    pub fn with_ef_construction(mut self, ef: usize) -> Self {
+        self.ef_construction = ef.max(1);
+        self
+    }
+
+    /// Sets the candidate list size used while querying (raised to `k` if lower).
+    pub fn with_ef_search(mut self, ef: usize) -> Self {
+        self.ef_search = ef.max(1);
+        self
+    }
+
+    /// Seeds the generator picking node levels, for reproducible graphs.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = seed.max(1);
+        self
+    }
+
+    /// Returns the vector dimension.
+    pub fn dim(&self) -> u16 {
+        self.dim
+    }
+
+    /// Returns the similarity metric.
+    pub fn metric(&self) -> SimilarityMetric {
+        self.metric
+    }
+
+    /// Returns the number of records indexed.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns true if no record is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns true if the record is indexed.
+    pub fn contains(&self, id: u64) -> bool {
+        self.ids.contains_key(&id)
+    }
+
+    /// Inserts a record's vector, replacing any previous vector of the same ID.
+    pub fn insert<V: IndexVector + ?Sized>(
+        &mut self,
+        id: u64,
+        vector: &V,
+    ) -> Result<(), IndexError> {
+        let vector = self.prepare(vector)?;
+        if let Some(old) = self.ids.remove(&id) {
+            self.nodes[old as usize].deleted = true;
+            self.deleted += 1;
+        }
+
+        let level = self.random_level();
+        let node = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            id,
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id, node);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return Ok(());
+        };
+        let top = self.level(entry);
+        let query = self.nodes[node as usize].vector.clone();
+
+        let mut found = vec![Candidate {
+            dist: self.distance(&query, entry),
+            node: entry,
+        }];
+        for layer in (level + 1..=top).rev() {
+            found = self.search_layer(&query, &found, 1, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            found = self.search_layer(&query, &found, self.ef_construction, layer);
+            let selected: Vec<u32> = found.iter().take(self.m).map(|c| c.node).collect();
+            for &neighbor in &selected {
+                self.link(neighbor, node, layer);
+            }
+            self.nodes[node as usize].neighbors[layer] = selected;
+        }
+
+        if level > top {
+            self.entry = Some(node);
+        }
+        Ok(())
+    }
+
+    /// Removes a record; returns false if it was not indexed.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let Some(node) = self.ids.remove(&id) else {
+            return false;
+        };
+        self.nodes[node as usize].deleted = true;
+        self.deleted += 1;
+        if self.ids.is_empty() {
+            self.nodes.clear();
+            self.entry = None;
+            self.deleted = 0;
+        }
+        true
+    }
+
+    /// Returns the `k` records closest to `vector`, best first.
+    pub fn query<V: IndexVector + ?Sized>(
+        &self,
+        vector: &V,
+        k: usize,
+    ) -> Result<Vec<Neighbor>, IndexError> {
+        let query = self.prepare(vector)?;
+        let Some(entry) = self.entry else {
+            return Ok(Vec::new());
+        };
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut found = vec![Candidate {
+            dist: self.distance(&query, entry),
+            node: entry,
+        }];
+        for layer in (1..=self.level(entry)).rev() {
+            found = self.search_layer(&query, &found, 1, layer);
+        }
+        // Widen the search by the deleted nodes it may have to step over
+        let ef = self.ef_search.max(k + self.deleted);
+        found = self.search_layer(&query, &found, ef, 0);
+
+        Ok(found
+            .into_iter()
+            .filter(|c| !self.nodes[c.node as usize].deleted)
+            .take(k)
+            .map(|c| Neighbor {
+                id: self.nodes[c.node as usize].id,
+                score: self.score(c.dist),
+            })
+            .collect())
+    }
+
+    /// Rebuilds the graph without deleted nodes.
+    ///
+    /// Worth calling after many removals or replacements, which otherwise slow
+    /// queries down.
+    pub fn compact(&mut self) {
+        if self.deleted == 0 {
+            return;
+        }
+        let nodes = std::mem::take(&mut self.nodes);
+        self.ids.clear();
+        self.entry = None;
+        self.deleted = 0;
+        for node in nodes.into_iter().filter(|n| !n.deleted) {
+            self.insert_prepared(node.id, node.vector);
+        }
+    }
+
+    /// Serializes the index into an LNMP/Embedding container.
+    pub fn to_container_bytes(&self) -> Vec<u8> {
+        let payload = self.to_bytes();
+        let mut buf = Vec::with_capacity(CONTAINER_HEADER_SIZE + payload.len());
+        buf.extend_from_slice(&CONTAINER_MAGIC);
+        buf.push(CONTAINER_VERSION);
+        buf.push(CONTAINER_MODE_EMBEDDING);
+        buf.extend_from_slice(&0u16.to_be_bytes()); // flags
+        buf.extend_from_slice(&0u32.to_be_bytes()); // metadata length
+        buf.extend_from_slice(&payload);
+        buf
+    }
+
+    /// Loads an index saved with [`to_container_bytes`](Self::to_container_bytes).
+    ///
+    /// Compressed, encrypted or signed containers must be unwrapped with
+    /// `lnmp_codec::container` first and the payload passed to
+    /// [`from_bytes`](Self::from_bytes).
+    pub fn from_container_bytes(bytes: &[u8]) -> Result<Self, IndexError> {
+        if bytes.len() < CONTAINER_HEADER_SIZE || bytes[0..4] != CONTAINER_MAGIC {
+            return Err(IndexError::InvalidData("not an LNMP container".to_string()));
+        }
+        if bytes[4] != CONTAINER_VERSION || bytes[5] != CONTAINER_MODE_EMBEDDING {
+            return Err(IndexError::InvalidData(format!(
+                "unsupported container version {} or mode {:#04x}",
+                bytes[4], bytes[5]
+            )));
+        }
+        if bytes[6] != 0 || bytes[7] != 0 {
+            return Err(IndexError::InvalidData(
+                "container flags are not supported".to_string(),
+            ));
+        }
+        let metadata_len = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+        let payload = bytes
+            .get(CONTAINER_HEADER_SIZE + metadata_len..)
+            .ok_or_else(|| IndexError::InvalidData("truncated container".to_string()))?;
+        Self::from_bytes(payload)
+    }
+
+    /// Serializes the index graph (the container payload).
+    ///
+    /// Layout (little-endian):
+    /// ```text
+    /// "HNSW" | u8 version | u8 metric | u16 dim | u16 m | u16 ef_construction |
+    /// u16 ef_search | u64 rng | u32 entry (u32::MAX if empty) | u32 node count |
+    /// nodes: [u64 id | u8 deleted | u8 layers | dim x f32 |
+    ///         layers x (u16 count | count x u32 neighbour)]
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&INDEX_MAGIC);
+        buf.push(INDEX_VERSION);
+        buf.push(self.metric as u8);
+        for value in [
+            self.dim as usize,
+            self.m,
+            self.ef_construction,
+            self.ef_search,
+        ] {
+            buf.write_u16::<LittleEndian>(value.min(u16::MAX as usize) as u16)
+                .unwrap();
+        }
+        buf.write_u64::<LittleEndian>(self.rng).unwrap();
+        buf.write_u32::<LittleEndian>(self.entry.unwrap_or(u32::MAX))
+            .unwrap();
+        buf.write_u32::<LittleEndian>(self.nodes.len() as u32)
+            .unwrap();
+        for node in &self.nodes {
+            buf.write_u64::<LittleEndian>(node.id).unwrap();
+            buf.push(node.deleted as u8);
+            buf.push(node.neighbors.len() as u8);
+            for value in &node.vector {
+                buf.write_f32::<LittleEndian>(*value).unwrap();
+            }
+            for links in &node.neighbors {
+                buf.write_u16::<LittleEndian>(links.len() as u16).unwrap();
+                for link in links {
+                    buf.write_u32::<LittleEndian>(*link).unwrap();
+                }
+            }
+        }
+        buf
+    }
+
+    /// Loads an index graph serialized with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IndexError> {
+        let truncated = |_| IndexError::InvalidData("truncated index".to_string());
+        let mut rdr = Cursor::new(bytes);
+
+        let mut magic = [0u8; 4];
+        rdr.read_exact(&mut magic).map_err(truncated)?;
+        if magic != INDEX_MAGIC {
+            return Err(IndexError::InvalidData("bad index magic".to_string()));
+        }
+        let version = rdr.read_u8().map_err(truncated)?;
+        if version != INDEX_VERSION {
+            return Err(IndexError::InvalidData(format!(
+                "unsupported index version {version}"
+            )));
+        }
+        let metric = match rdr.read_u8().map_err(truncated)? {
+            0x01 => SimilarityMetric::Cosine,
+            0x02 => SimilarityMetric::Euclidean,
+            0x03 => SimilarityMetric::DotProduct,
+            other => {
+                return Err(IndexError::InvalidData(format!(
+                    "unknown metric {other:#04x}"
+                )))
+            }
+        };
+        let dim = rdr.read_u16::<LittleEndian>().map_err(truncated)?;
+        let mut index = HnswIndex::new(dim, metric)
+            .with_m(rdr.read_u16::<LittleEndian>().map_err(truncated)? as usize)
+            .with_ef_construction(rdr.read_u16::<LittleEndian>().map_err(truncated)? as usize)
+            .with_ef_search(rdr.read_u16::<LittleEndian>().map_err(truncated)? as usize)
+            .with_seed(rdr.read_u64::<LittleEndian>().map_err(truncated)?);
+        let entry = rdr.read_u32::<LittleEndian>().map_err(truncated)?;
+        let count = rdr.read_u32::<LittleEndian>().map_err(truncated)?;
+
+        for _ in 0..count {
+            let id = rdr.read_u64::<LittleEndian>().map_err(truncated)?;
+            let deleted = rdr.read_u8().map_err(truncated)? != 0;
+            let layers = rdr.read_u8().map_err(truncated)? as usize;
+            if layers == 0 || layers > MAX_LEVEL + 1 {
+                return Err(IndexError::InvalidData(format!(
+                    "invalid layer count {layers}"
+                )));
+            }
+            let mut vector = Vec::with_capacity(dim as usize);
+            for _ in 0..dim {
+                vector.push(rdr.read_f32::<LittleEndian>().map_err(truncated)?);
+            }
+            let mut neighbors = Vec::with_capacity(layers);
+            for _ in 0..layers {
+                let links = rdr.read_u16::<LittleEndian>().map_err(truncated)?;
+                let mut layer = Vec::with_capacity(links as usize);
+                for _ in 0..links {
+                    let link = rdr.read_u32::<LittleEndian>().map_err(truncated)?;
+                    if link >= count {
+                        return Err(IndexError::InvalidData(format!(
+                            "neighbour {link} out of range"
+                        )));
+                    }
+                    layer.push(link);
+                }
+                neighbors.push(layer);
+            }
+            if deleted {
+                index.deleted += 1;
+            } else {
+                index.ids.insert(id, index.nodes.len() as u32);
+            }
+            index.nodes.push(Node {
+                id,
+                vector,
+                neighbors,
+                deleted,
+            });
+        }
+
+        // Links must point at nodes present on that layer
+        for node in &index.nodes {
+            for (layer, links) in node.neighbors.iter().enumerate() {
+                if links.iter().any(|&l| index.level(l) < layer) {
+                    return Err(IndexError::InvalidData(
+                        "neighbour missing from its layer".to_string(),
+                    ));
+                }
+            }
+        }
+        index.entry = match entry {
+            u32::MAX if count == 0 => None,
+            entry if entry < count => Some(entry),
+            _ => return Err(IndexError::InvalidData("invalid entry point".to_string())),
+        };
+        Ok(index)
+    }
+
+    fn prepare<V: IndexVector + ?Sized>(&self, vector: &V) -> Result<Vec<f32>, IndexError> {
+        let mut values = vector.to_f32_vec().map_err(IndexError::InvalidVector)?;
+        if values.len() != self.dim as usize {
+            return Err(IndexError::DimensionMismatch {
+                expected: self.dim as usize,
+                actual: values.len(),
+            });
+        }
+        if self.metric == SimilarityMetric::Cosine {
+            let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                values.iter_mut().for_each(|v| *v /= norm);
+            }
+        }
+        Ok(values)
+    }
+
+    fn insert_prepared(&mut self, id: u64, vector: Vec<f32>) {
+        // Vectors taken from the index already have the right dimension
+        self.insert(id, &vector)
+            .expect("indexed vectors have the index dimension");
+    }
+
+    fn level(&self, node: u32) -> usize {
+        self.nodes[node as usize].neighbors.len() - 1
+    }
+
+    /// Draws a level from the exponentially decaying HNSW distribution.
+    fn random_level(&mut self) -> usize {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let uniform = ((self.rng >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.m as f64).ln();
+        ((-uniform.ln() * ml) as usize).min(MAX_LEVEL)
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m * 2
+        } else {
+            self.m
+        }
+    }
+
+    /// Distance used to walk the graph: lower is closer for every metric.
+    fn distance(&self, query: &[f32], node: u32) -> f32 {
+        let vector = &self.nodes[node as usize].vector;
+        match self.metric {
+            SimilarityMetric::Cosine => 1.0 - dot(query, vector),
+            SimilarityMetric::DotProduct => -dot(query, vector),
+            SimilarityMetric::Euclidean => query
+                .iter()
+                .zip(vector)
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum(),
+        }
+    }
+
+    fn score(&self, dist: f32) -> f32 {
+        match self.metric {
+            SimilarityMetric::Cosine => 1.0 - dist,
+            SimilarityMetric::DotProduct => -dist,
+            SimilarityMetric::Euclidean => dist.sqrt(),
+        }
+    }
+
+    /// Links `to` from `from` on `layer`, keeping the closest links if over capacity.
+    fn link(&mut self, from: u32, to: u32, layer: usize) {
+        self.nodes[from as usize].neighbors[layer].push(to);
+        let max = self.max_links(layer);
+        if self.nodes[from as usize].neighbors[layer].len() <= max {
+            return;
+        }
+        let base = self.nodes[from as usize].vector.clone();
+        let mut links: Vec<Candidate> = self.nodes[from as usize].neighbors[layer]
+            .iter()
+            .map(|&node| Candidate {
+                dist: self.distance(&base, node),
+                node,
+            })
+            .collect();
+        links.sort();
+        self.nodes[from as usize].neighbors[layer] =
+            links.into_iter().take(max).map(|c| c.node).collect();
+    }
+
+    /// Best-first search of one layer; returns up to `ef` nodes, closest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry: &[Candidate],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = entry.iter().map(|c| c.node).collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> =
+            entry.iter().copied().map(Reverse).collect();
+        let mut results: BinaryHeap<Candidate> = entry.iter().copied().collect();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let furthest = results.peek().map_or(f32::INFINITY, |c| c.dist);
+            if results.len() >= ef && current.dist > furthest {
+                break;
+            }
+            for &neighbor in &self.nodes[current.node as usize].neighbors[layer] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let dist = self.distance(query, neighbor);
+                let furthest = results.peek().map_or(f32::INFINITY, |c| c.dist);
+                if results.len() < ef || dist < furthest {
+                    let candidate = Candidate {
+                        dist,
+                        node: neighbor,
+                    };
+                    candidates.push(Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_codec::container::{ContainerBody, ContainerFrame};
+
+    fn random_vectors(count: usize, dim: usize, mut seed: u64) -> Vec<Vec<f32>> {
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        ((seed >> 33) as f32 / (1u64 << 31) as f32) * 2.0 - 1.0
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn brute_force(vectors: &[Vec<f32>], query: &[f32], k: usize) -> Vec<u64> {
+        let query = Vector::from_f32(query.to_vec());
+        let mut scored: Vec<(u64, f32)> = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let v = Vector::from_f32(v.clone());
+                (
+                    i as u64,
+                    query.similarity(&v, SimilarityMetric::Cosine).unwrap(),
+                )
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().take(k).map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_recall_against_brute_force() {
+        let vectors = random_vectors(1000, 16, 7);
+        let mut index = HnswIndex::new(16, SimilarityMetric::Cosine);
+        for (i, v) in vectors.iter().enumerate() {
+            index.insert(i as u64, v).unwrap();
+        }
+        assert_eq!(index.len(), 1000);
+
+        let mut hits = 0;
+        for query in random_vectors(50, 16, 99) {
+            let expected = brute_force(&vectors, &query, 10);
+            let found: Vec<u64> = index
+                .query(&query, 10)
+                .unwrap()
+                .iter()
+                .map(|n| n.id)
+                .collect();
+            hits += found.iter().filter(|id| expected.contains(id)).count();
+        }
+        assert!(hits as f32 / 500.0 > 0.9, "recall too low: {hits}/500");
+    }
+
+    #[test]
+    fn test_metrics_scores() {
+        let mut dot = HnswIndex::new(2, SimilarityMetric::DotProduct);
+        dot.insert(1, &Vector::from_f32(vec![1.0, 0.0])).unwrap();
+        dot.insert(2, &Vector::from_f32(vec![3.0, 0.0])).unwrap();
+        let hits = dot.query(&[2.0f32, 0.0][..], 2).unwrap();
+        assert_eq!(hits[0], Neighbor { id: 2, score: 6.0 });
+        assert_eq!(hits[1], Neighbor { id: 1, score: 2.0 });
+
+        let mut l2 = HnswIndex::new(2, SimilarityMetric::Euclidean);
+        l2.insert(1, &vec![0.0, 0.0]).unwrap();
+        l2.insert(2, &vec![3.0, 4.0]).unwrap();
+        let hits = l2.query(&vec![3.0, 3.0], 2).unwrap();
+        assert_eq!(hits[0], Neighbor { id: 2, score: 1.0 });
+        assert!((hits[1].score - 18f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_remove_replace_and_compact() {
+        let vectors = random_vectors(200, 8, 3);
+        let mut index = HnswIndex::new(8, SimilarityMetric::Cosine).with_seed(11);
+        for (i, v) in vectors.iter().enumerate() {
+            index.insert(i as u64, v).unwrap();
+        }
+
+        assert!(index.remove(5));
+        assert!(!index.remove(5));
+        assert!(!index.contains(5));
+        let hits = index.query(&vectors[5], 10).unwrap();
+        assert!(hits.iter().all(|n| n.id != 5));
+
+        // Replacing moves the record to its new vector
+        index.insert(6, &vectors[7]).unwrap();
+        let hits = index.query(&vectors[7], 2).unwrap();
+        let ids: Vec<u64> = hits.iter().map(|n| n.id).collect();
+        assert!(ids.contains(&6) && ids.contains(&7));
+        assert_eq!(index.len(), 199);
+
+        index.compact();
+        assert_eq!(index.nodes.len(), 199);
+        assert_eq!(index.query(&vectors[3], 1).unwrap()[0].id, 3);
+
+        for id in 0..200 {
+            index.remove(id);
+        }
+        assert!(index.is_empty());
+        assert!(index.query(&vectors[0], 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_vectors() {
+        let mut index = HnswIndex::new(3, SimilarityMetric::Cosine);
+        assert_eq!(
+            index.insert(1, &vec![1.0, 2.0]),
+            Err(IndexError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+        let f16 = Vector::new(crate::EmbeddingType::F16, 3, vec![0; 6]);
+        assert!(matches!(
+            index.insert(1, &f16),
+            Err(IndexError::InvalidVector(_))
+        ));
+    }
+
+    #[test]
+    fn test_container_round_trip() {
+        let vectors = random_vectors(100, 4, 5);
+        let mut index = HnswIndex::new(4, SimilarityMetric::Cosine).with_m(8);
+        for (i, v) in vectors.iter().enumerate() {
+            index.insert(i as u64 * 10, v).unwrap();
+        }
+        index.remove(20);
+
+        let bytes = index.to_container_bytes();
+        let frame = ContainerFrame::parse(&bytes).unwrap();
+        assert!(matches!(frame.body(), ContainerBody::Embedding(_)));
+
+        let loaded = HnswIndex::from_container_bytes(&bytes).unwrap();
+        assert_eq!(loaded.len(), 99);
+        assert!(!loaded.contains(20));
+        for query in &vectors[..10] {
+            assert_eq!(
+                loaded.query(query, 5).unwrap(),
+                index.query(query, 5).unwrap()
+            );
+        }
+
+        assert!(HnswIndex::from_container_bytes(&bytes[..bytes.len() - 3]).is_err());
+        assert!(HnswIndex::from_bytes(b"HNSW").is_err());
+    }
+}
@@ -1,15 +1,33 @@
+pub mod batch;
+pub mod chain;
 pub mod decoder;
 pub mod delta;
 pub mod encoder;
+pub mod index;
+pub mod normalize;
+pub mod projection;
+pub mod sparse;
+pub mod stream;
 pub mod vector;
 pub mod view;
 
+pub use batch::batch_similarity;
+pub use chain::DeltaChain;
 pub use decoder::Decoder;
 pub use delta::{DeltaChange, UpdateStrategy, VectorDelta};
 pub use encoder::Encoder;
-pub use vector::{EmbeddingType, SimilarityMetric, Vector};
+pub use index::{HnswIndex, IndexError, IndexVector, Neighbor};
+pub use normalize::{center_batch, normalize_batch, NormalizedVector};
+pub use projection::Projection;
+pub use sparse::SparseVector;
+pub use stream::{
+    EmbeddingFrame, EmbeddingStream, EmbeddingStreamConfig, EmbeddingUpdate, FrameMode,
+};
+pub use vector::{EmbeddingType, SimilarityMetric, Vector, VectorTransform};
 pub use view::EmbeddingView;
 
+pub use half::f16;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,183 @@
+//! Normalization and centering of embeddings.
+//!
+//! Both transformations are recorded in the vector's [`VectorTransform`] and travel
+//! with its encoding, so a receiver can tell a unit-length or mean-centered vector
+//! apart from a raw one. [`NormalizedVector`] carries the unit-length guarantee in
+//! the type, letting cosine similarity reduce to a dot product.
+
+use crate::vector::{EmbeddingType, SimilarityMetric, Vector};
+
+/// A vector known to have unit L2 norm.
+///
+/// # Example
+///
+/// ```
+/// use lnmp_embedding::{NormalizedVector, SimilarityMetric, Vector};
+///
+/// let a = NormalizedVector::new(Vector::from_f32(vec![3.0, 4.0])).unwrap();
+/// let b = NormalizedVector::new(Vector::from_f32(vec![4.0, 3.0])).unwrap();
+/// let cosine = a.similarity(&b, SimilarityMetric::Cosine).unwrap();
+/// assert!((cosine - 0.96).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedVector(Vector);
+
+impl NormalizedVector {
+    /// Normalizes an F32 or F16 vector.
+    ///
+    /// Returns an error for zero vectors, which have no unit-length direction.
+    pub fn new(mut vector: Vector) -> Result<Self, String> {
+        vector.normalize_in_place()?;
+        if !vector.transform.normalized {
+            return Err("Cannot normalize a zero vector".to_string());
+        }
+        Ok(Self(vector))
+    }
+
+    /// Wraps a vector already marked normalized (e.g. a decoded one) without
+    /// rescaling it.
+    pub fn from_normalized(vector: Vector) -> Result<Self, String> {
+        if !vector.transform.normalized {
+            return Err("Vector is not marked normalized".to_string());
+        }
+        Ok(Self(vector))
+    }
+
+    /// Returns the underlying vector.
+    pub fn as_vector(&self) -> &Vector {
+        &self.0
+    }
+
+    /// Unwraps the underlying vector.
+    pub fn into_inner(self) -> Vector {
+        self.0
+    }
+
+    /// Similarity with another normalized vector; cosine is computed as the dot
+    /// product, skipping both norms.
+    pub fn similarity(
+        &self,
+        other: &NormalizedVector,
+        metric: SimilarityMetric,
+    ) -> Result<f32, String> {
+        match metric {
+            SimilarityMetric::Cosine => self.0.similarity(&other.0, SimilarityMetric::DotProduct),
+            metric => self.0.similarity(&other.0, metric),
+        }
+    }
+}
+
+impl AsRef<Vector> for NormalizedVector {
+    fn as_ref(&self) -> &Vector {
+        &self.0
+    }
+}
+
+/// Normalizes every vector of a batch in place.
+pub fn normalize_batch(vectors: &mut [Vector]) -> Result<(), String> {
+    vectors.iter_mut().try_for_each(Vector::normalize_in_place)
+}
+
+/// Returns the element-wise mean of a batch of F32 vectors.
+pub fn mean(vectors: &[Vector]) -> Result<Vec<f32>, String> {
+    let Some(first) = vectors.first() else {
+        return Err("Cannot average an empty batch".to_string());
+    };
+    let mut sum = vec![0.0f64; first.dim as usize];
+    for vector in vectors {
+        if vector.dtype != EmbeddingType::F32 {
+            return Err("Mean only supported for F32 embeddings".to_string());
+        }
+        if vector.dim != first.dim {
+            return Err("Dimension mismatch".to_string());
+        }
+        for (acc, val) in sum.iter_mut().zip(vector.as_f32()?) {
+            *acc += val as f64;
+        }
+    }
+    let count = vectors.len() as f64;
+    Ok(sum.into_iter().map(|s| (s / count) as f32).collect())
+}
+
+/// Subtracts the batch mean from every vector in place and returns the mean.
+///
+/// Keep the mean to center queries the same way before comparing them with the
+/// batch.
+pub fn center_batch(vectors: &mut [Vector]) -> Result<Vec<f32>, String> {
+    let mean = mean(vectors)?;
+    for vector in vectors.iter_mut() {
+        vector.center_in_place(&mean)?;
+    }
+    Ok(mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::VectorTransform;
+    use crate::{Decoder, Encoder};
+
+    #[test]
+    fn test_normalized_vector() {
+        let v = NormalizedVector::new(Vector::from_f32(vec![3.0, 4.0])).unwrap();
+        assert_eq!(v.as_vector().as_f32().unwrap(), vec![0.6, 0.8]);
+        assert!(v.as_vector().transform.normalized);
+        assert!(NormalizedVector::new(Vector::from_f32(vec![0.0, 0.0])).is_err());
+
+        let other = NormalizedVector::new(Vector::from_f32(vec![1.0, 1.0])).unwrap();
+        let fast = v.similarity(&other, SimilarityMetric::Cosine).unwrap();
+        let full = v
+            .as_vector()
+            .similarity(other.as_vector(), SimilarityMetric::Cosine)
+            .unwrap();
+        assert!((fast - full).abs() < 1e-6);
+
+        let half = NormalizedVector::new(Vector::from_f32(vec![3.0, 4.0]).to_f16().unwrap());
+        assert_eq!(half.unwrap().as_vector().dtype, EmbeddingType::F16);
+    }
+
+    #[test]
+    fn test_transform_survives_encoding() {
+        let v = NormalizedVector::new(Vector::from_f32(vec![1.0, 2.0, 2.0])).unwrap();
+        let encoded = Encoder::encode(v.as_vector()).unwrap();
+        assert_eq!(encoded[3], 0x11);
+        let decoded = Decoder::decode(&encoded).unwrap();
+        assert_eq!(&decoded, v.as_vector());
+        assert!(NormalizedVector::from_normalized(decoded).is_ok());
+
+        // Vectors encoded before transforms were recorded decode untransformed
+        let plain = Encoder::encode(&Vector::from_f32(vec![1.0])).unwrap();
+        assert_eq!(plain[3], 0x01);
+        let decoded = Decoder::decode(&plain).unwrap();
+        assert_eq!(decoded.transform, VectorTransform::default());
+        assert!(NormalizedVector::from_normalized(decoded).is_err());
+    }
+
+    #[test]
+    fn test_center_batch() {
+        let mut batch = vec![
+            Vector::from_f32(vec![1.0, 2.0]),
+            Vector::from_f32(vec![3.0, 6.0]),
+        ];
+        normalize_batch(&mut batch[..1]).unwrap();
+        let mean = center_batch(&mut batch).unwrap();
+        assert!((mean[0] - (1.0 / 5f32.sqrt() + 3.0) / 2.0).abs() < 1e-6);
+
+        let sum: Vec<f32> = (0..2)
+            .map(|i| batch.iter().map(|v| v.as_f32().unwrap()[i]).sum())
+            .collect();
+        assert!(sum.iter().all(|s| s.abs() < 1e-6));
+        assert!(batch.iter().all(|v| v.transform
+            == VectorTransform {
+                normalized: false,
+                centered: true
+            }));
+
+        assert!(center_batch(&mut []).is_err());
+        let mut mixed = vec![
+            Vector::from_f32(vec![1.0]),
+            Vector::from_f32(vec![1.0, 2.0]),
+        ];
+        assert!(center_batch(&mut mixed).is_err());
+    }
+}
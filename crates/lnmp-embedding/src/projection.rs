@@ -0,0 +1,414 @@
+//! Linear dimensionality reduction.
+//!
+//! A [`Projection`] maps `input_dim`-dimensional embeddings to `output_dim`
+//! dimensions with a fixed matrix, e.g. 1536 → 256 before quantization and
+//! transport. The matrix is either fitted with PCA on sample vectors, drawn at
+//! random (Johnson–Lindenstrauss), or loaded as-is.
+
+use crate::vector::Vector;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Subspace iterations run by [`Projection::fit_pca`].
+const PCA_ITERATIONS: usize = 50;
+
+/// A linear map from `input_dim` to `output_dim` dimensions.
+///
+/// `project(v) = matrix · (v - mean)`, with `matrix` stored row-major
+/// (`output_dim` rows of `input_dim` values) and `mean` optional.
+///
+/// # Example
+///
+/// ```
+/// use lnmp_embedding::{Projection, Vector};
+///
+/// let projection = Projection::random(1536, 256, 7);
+/// let reduced = projection.project(&Vector::from_f32(vec![0.1; 1536])).unwrap();
+/// assert_eq!(reduced.dim, 256);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Projection {
+    input_dim: u16,
+    output_dim: u16,
+    mean: Option<Vec<f32>>,
+    matrix: Vec<f32>,
+}
+
+impl Projection {
+    /// Loads a row-major `output_dim × input_dim` matrix.
+    pub fn from_matrix(input_dim: u16, output_dim: u16, matrix: Vec<f32>) -> Result<Self, String> {
+        if output_dim == 0 || output_dim > input_dim {
+            return Err(format!(
+                "Cannot project {} dimensions to {}",
+                input_dim, output_dim
+            ));
+        }
+        if matrix.len() != input_dim as usize * output_dim as usize {
+            return Err(format!(
+                "Matrix has {} values, expected {}x{}",
+                matrix.len(),
+                output_dim,
+                input_dim
+            ));
+        }
+        Ok(Self {
+            input_dim,
+            output_dim,
+            mean: None,
+            matrix,
+        })
+    }
+
+    /// Subtracts `mean` from every vector before projecting it.
+    pub fn with_mean(mut self, mean: Vec<f32>) -> Result<Self, String> {
+        if mean.len() != self.input_dim as usize {
+            return Err("Mean dimension mismatch".to_string());
+        }
+        self.mean = Some(mean);
+        Ok(self)
+    }
+
+    /// Draws a random projection with `±1/√output_dim` entries, which preserves
+    /// distances in expectation. The same seed always yields the same matrix.
+    pub fn random(input_dim: u16, output_dim: u16, seed: u64) -> Self {
+        let output_dim = output_dim.clamp(1, input_dim.max(1));
+        let scale = 1.0 / (output_dim as f32).sqrt();
+        let mut rng = seed.max(1);
+        let matrix = (0..input_dim as usize * output_dim as usize)
+            .map(|_| {
+                if xorshift(&mut rng) & 1 == 0 {
+                    scale
+                } else {
+                    -scale
+                }
+            })
+            .collect();
+        Self {
+            input_dim,
+            output_dim,
+            mean: None,
+            matrix,
+        }
+    }
+
+    /// Fits a PCA projection onto the `output_dim` directions of largest variance
+    /// of `samples` (F32 or F16), strongest first. The sample mean is subtracted
+    /// before projecting.
+    ///
+    /// Uses subspace iteration on the centered samples, so the covariance matrix
+    /// is never formed.
+    pub fn fit_pca(samples: &[Vector], output_dim: u16) -> Result<Self, String> {
+        let Some(first) = samples.first() else {
+            return Err("Cannot fit PCA without samples".to_string());
+        };
+        let dim = first.dim as usize;
+        let k = output_dim as usize;
+        if k == 0 || k > dim {
+            return Err(format!("Cannot project {} dimensions to {}", dim, k));
+        }
+
+        let mut rows = Vec::with_capacity(samples.len());
+        for sample in samples {
+            if sample.dim != first.dim {
+                return Err("Dimension mismatch".to_string());
+            }
+            let values = sample.to_f32()?.as_f32()?;
+            rows.push(values.into_iter().map(f64::from).collect::<Vec<f64>>());
+        }
+        let mut mean = vec![0.0f64; dim];
+        for row in &rows {
+            for (m, x) in mean.iter_mut().zip(row) {
+                *m += x;
+            }
+        }
+        mean.iter_mut().for_each(|m| *m /= rows.len() as f64);
+        for row in rows.iter_mut() {
+            for (x, m) in row.iter_mut().zip(&mean) {
+                *x -= m;
+            }
+        }
+
+        let mut rng = 0x9E37_79B9_7F4A_7C15u64;
+        let mut basis: Vec<Vec<f64>> = (0..k).map(|_| random_unit(dim, &mut rng)).collect();
+        orthonormalize(&mut basis, &mut rng);
+        for _ in 0..PCA_ITERATIONS {
+            basis = basis.iter().map(|v| covariance_times(&rows, v)).collect();
+            orthonormalize(&mut basis, &mut rng);
+        }
+
+        // Order by explained variance (Rayleigh quotient)
+        let mut components: Vec<(f64, Vec<f64>)> = basis
+            .into_iter()
+            .map(|v| (dot(&v, &covariance_times(&rows, &v)), v))
+            .collect();
+        components.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let matrix = components
+            .into_iter()
+            .flat_map(|(_, v)| v.into_iter().map(|x| x as f32))
+            .collect();
+        Self::from_matrix(first.dim, output_dim, matrix)?
+            .with_mean(mean.into_iter().map(|m| m as f32).collect())
+    }
+
+    /// Input dimension.
+    pub fn input_dim(&self) -> u16 {
+        self.input_dim
+    }
+
+    /// Output dimension.
+    pub fn output_dim(&self) -> u16 {
+        self.output_dim
+    }
+
+    /// The row-major `output_dim × input_dim` matrix.
+    pub fn matrix(&self) -> &[f32] {
+        &self.matrix
+    }
+
+    /// The mean subtracted before projecting, if any.
+    pub fn mean(&self) -> Option<&[f32]> {
+        self.mean.as_deref()
+    }
+
+    /// Projects an F32 or F16 vector; the result is F32.
+    pub fn project(&self, vector: &Vector) -> Result<Vector, String> {
+        if vector.dim != self.input_dim {
+            return Err("Dimension mismatch".to_string());
+        }
+        let mut values = vector.to_f32()?.as_f32()?;
+        if let Some(mean) = &self.mean {
+            for (x, m) in values.iter_mut().zip(mean) {
+                *x -= m;
+            }
+        }
+        let projected = self
+            .matrix
+            .chunks_exact(self.input_dim as usize)
+            .map(|row| row.iter().zip(&values).map(|(a, b)| a * b).sum())
+            .collect();
+        Ok(Vector::from_f32(projected))
+    }
+
+    /// Projects every vector of a batch.
+    pub fn project_batch(&self, vectors: &[Vector]) -> Result<Vec<Vector>, String> {
+        vectors.iter().map(|v| self.project(v)).collect()
+    }
+
+    /// Encode to binary format
+    /// Format: input_dim (u16) | output_dim (u16) | has_mean (u8) |
+    /// [mean: f32; input_dim] (if has_mean) | [matrix: f32; output_dim * input_dim]
+    pub fn encode(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buf = Vec::with_capacity(self.encoded_size());
+
+        buf.write_u16::<LittleEndian>(self.input_dim)?;
+        buf.write_u16::<LittleEndian>(self.output_dim)?;
+        buf.write_u8(self.mean.is_some() as u8)?;
+        for &value in self.mean.iter().flatten().chain(&self.matrix) {
+            buf.write_f32::<LittleEndian>(value)?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Decode from binary format
+    pub fn decode(data: &[u8]) -> Result<Self, std::io::Error> {
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+        let mut rdr = Cursor::new(data);
+
+        let input_dim = rdr.read_u16::<LittleEndian>()?;
+        let output_dim = rdr.read_u16::<LittleEndian>()?;
+        let has_mean = rdr.read_u8()? != 0;
+        let mean_len = if has_mean { input_dim as usize } else { 0 };
+        let matrix_len = input_dim as usize * output_dim as usize;
+        if data.len() < 5 + (mean_len + matrix_len) * 4 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Truncated projection",
+            ));
+        }
+
+        let mut read = |len: usize| -> Result<Vec<f32>, std::io::Error> {
+            (0..len).map(|_| rdr.read_f32::<LittleEndian>()).collect()
+        };
+        let mean = read(mean_len)?;
+        let matrix = read(matrix_len)?;
+
+        let projection = Self::from_matrix(input_dim, output_dim, matrix).map_err(invalid)?;
+        if has_mean {
+            projection.with_mean(mean).map_err(invalid)
+        } else {
+            Ok(projection)
+        }
+    }
+
+    /// Get the size of the encoded projection in bytes
+    pub fn encoded_size(&self) -> usize {
+        5 + (self.mean.as_ref().map_or(0, Vec::len) + self.matrix.len()) * 4
+    }
+}
+
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn random_unit(dim: usize, rng: &mut u64) -> Vec<f64> {
+    let v: Vec<f64> = (0..dim)
+        .map(|_| (xorshift(rng) >> 11) as f64 / (1u64 << 53) as f64 - 0.5)
+        .collect();
+    let norm = dot(&v, &v).sqrt();
+    v.into_iter().map(|x| x / norm).collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// `Xᵀ(X v) / n` for the centered sample rows `X`, i.e. the covariance times `v`.
+fn covariance_times(rows: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; v.len()];
+    for row in rows {
+        let weight = dot(row, v);
+        for (o, x) in out.iter_mut().zip(row) {
+            *o += weight * x;
+        }
+    }
+    out.iter_mut().for_each(|o| *o /= rows.len() as f64);
+    out
+}
+
+/// Modified Gram–Schmidt. Columns that collapse (rank-deficient samples) are
+/// replaced with fresh random directions so the basis stays complete.
+fn orthonormalize(basis: &mut [Vec<f64>], rng: &mut u64) {
+    for i in 0..basis.len() {
+        for attempt in 0..4 {
+            let (done, rest) = basis.split_at_mut(i);
+            let v = &mut rest[0];
+            for u in done.iter() {
+                let d = dot(u, v);
+                v.iter_mut().zip(u).for_each(|(x, y)| *x -= d * y);
+            }
+            let norm = dot(v, v).sqrt();
+            if norm > 1e-9 || attempt == 3 {
+                v.iter_mut().for_each(|x| *x /= norm.max(f64::MIN_POSITIVE));
+                break;
+            }
+            *v = random_unit(v.len(), rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::SimilarityMetric;
+
+    fn samples() -> Vec<Vector> {
+        // Spread along (1, 1, 0), a little along (1, -1, 0), offset by (5, 5, 5)
+        (0..40)
+            .map(|i| {
+                let t = (i / 2) as f32 / 2.0 - 4.75;
+                let s = if i % 2 == 0 { 0.1 } else { -0.1 };
+                Vector::from_f32(vec![5.0 + t + s, 5.0 + t - s, 5.0])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pca_finds_principal_directions() {
+        let projection = Projection::fit_pca(&samples(), 2).unwrap();
+        assert_eq!((projection.input_dim(), projection.output_dim()), (3, 2));
+        for (m, expected) in projection.mean().unwrap().iter().zip([5.0, 5.0, 5.0]) {
+            assert!((m - expected).abs() < 1e-4);
+        }
+
+        let first = &projection.matrix()[..3];
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((first[0].abs() - half).abs() < 1e-4);
+        assert!((first[1] - first[0]).abs() < 1e-4);
+        assert!(first[2].abs() < 1e-4);
+        let second = &projection.matrix()[3..];
+        assert!((second[0] + second[1]).abs() < 1e-4);
+
+        // Projecting keeps the in-plane geometry of the samples
+        let (a, b) = (&samples()[0], &samples()[39]);
+        let original = a.similarity(b, SimilarityMetric::Euclidean).unwrap();
+        let reduced = projection
+            .project(a)
+            .unwrap()
+            .similarity(&projection.project(b).unwrap(), SimilarityMetric::Euclidean)
+            .unwrap();
+        assert!((original - reduced).abs() < 1e-3);
+
+        assert!(Projection::fit_pca(&samples(), 4).is_err());
+        assert!(Projection::fit_pca(&[], 1).is_err());
+    }
+
+    #[test]
+    fn test_pca_rank_deficient_samples() {
+        let samples = vec![
+            Vector::from_f32(vec![1.0, 0.0, 0.0, 0.0]),
+            Vector::from_f32(vec![-1.0, 0.0, 0.0, 0.0]),
+        ];
+        let projection = Projection::fit_pca(&samples, 3).unwrap();
+        let rows: Vec<&[f32]> = projection.matrix().chunks(4).collect();
+        assert!((rows[0][0].abs() - 1.0).abs() < 1e-5);
+        for i in 0..3 {
+            for j in 0..3 {
+                let d: f32 = rows[i].iter().zip(rows[j]).map(|(a, b)| a * b).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((d - expected).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_projection_preserves_distances() {
+        let projection = Projection::random(512, 128, 42);
+        assert_eq!(projection, Projection::random(512, 128, 42));
+
+        let mut rng = 7u64;
+        let vectors: Vec<Vector> = (0..10)
+            .map(|_| {
+                Vector::from_f32(
+                    random_unit(512, &mut rng)
+                        .iter()
+                        .map(|x| *x as f32)
+                        .collect(),
+                )
+            })
+            .collect();
+        let reduced = projection.project_batch(&vectors).unwrap();
+        for i in 1..vectors.len() {
+            let before = vectors[0]
+                .similarity(&vectors[i], SimilarityMetric::Euclidean)
+                .unwrap();
+            let after = reduced[0]
+                .similarity(&reduced[i], SimilarityMetric::Euclidean)
+                .unwrap();
+            assert!((after / before - 1.0).abs() < 0.3, "{before} vs {after}");
+        }
+
+        let half = vectors[0].to_f16().unwrap();
+        assert_eq!(projection.project(&half).unwrap().dim, 128);
+        assert!(projection.project(&Vector::from_f32(vec![1.0; 3])).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let projection = Projection::fit_pca(&samples(), 2).unwrap();
+        let encoded = projection.encode().unwrap();
+        assert_eq!(encoded.len(), projection.encoded_size());
+        assert_eq!(Projection::decode(&encoded).unwrap(), projection);
+        assert!(Projection::decode(&encoded[..encoded.len() - 1]).is_err());
+
+        let plain = Projection::from_matrix(2, 1, vec![1.0, 0.0]).unwrap();
+        assert_eq!(Projection::decode(&plain.encode().unwrap()).unwrap(), plain);
+        assert!(Projection::from_matrix(2, 1, vec![1.0]).is_err());
+        assert!(Projection::from_matrix(2, 3, vec![0.0; 6]).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Sparse embeddings.
+//!
+//! Retrieval models such as SPLADE produce vectors over the whole vocabulary with
+//! only a few hundred non-zero weights. [`SparseVector`] stores just those entries
+//! and scores them against dense [`Vector`]s or other sparse vectors without
+//! expanding them.
+
+use crate::vector::{EmbeddingType, SimilarityMetric, Vector};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use half::f16;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::io::Cursor;
+
+/// A sparse embedding: the non-zero entries of a `dim`-dimensional vector.
+///
+/// `indices` are strictly increasing and below `dim`; `values[i]` is the weight at
+/// `indices[i]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseVector {
+    pub dim: u32,
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+impl SparseVector {
+    /// Creates a sparse vector, checking the indices are sorted, unique and in range.
+    pub fn new(dim: u32, indices: Vec<u32>, values: Vec<f32>) -> Result<Self, String> {
+        if indices.len() != values.len() {
+            return Err(format!(
+                "{} indices but {} values",
+                indices.len(),
+                values.len()
+            ));
+        }
+        if indices.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Indices must be strictly increasing".to_string());
+        }
+        if let Some(&last) = indices.last() {
+            if last >= dim {
+                return Err(format!("Index {} out of range for dim {}", last, dim));
+            }
+        }
+        Ok(Self {
+            dim,
+            indices,
+            values,
+        })
+    }
+
+    /// Creates a sparse vector from `(index, value)` pairs in any order, dropping zeros.
+    pub fn from_pairs(dim: u32, mut pairs: Vec<(u32, f32)>) -> Result<Self, String> {
+        pairs.retain(|(_, v)| *v != 0.0);
+        pairs.sort_by_key(|(i, _)| *i);
+        let (indices, values) = pairs.into_iter().unzip();
+        Self::new(dim, indices, values)
+    }
+
+    /// Keeps the non-zero entries of a dense F32 or F16 vector.
+    pub fn from_dense(vector: &Vector) -> Result<Self, String> {
+        let values = vector.to_f32()?.as_f32()?;
+        let pairs = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (i as u32, v))
+            .collect();
+        Self::from_pairs(vector.dim as u32, pairs)
+    }
+
+    /// Expands to a dense F32 vector.
+    pub fn to_dense(&self) -> Result<Vector, String> {
+        if self.dim > u16::MAX as u32 {
+            return Err(format!("Dim {} too large for a dense vector", self.dim));
+        }
+        let mut values = vec![0.0f32; self.dim as usize];
+        for (&i, &v) in self.indices.iter().zip(&self.values) {
+            values[i as usize] = v;
+        }
+        Ok(Vector::from_f32(values))
+    }
+
+    /// Number of stored (non-zero) entries.
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns the weight at `index` (0.0 when absent).
+    pub fn get(&self, index: u32) -> f32 {
+        self.indices
+            .binary_search(&index)
+            .map_or(0.0, |pos| self.values[pos])
+    }
+
+    fn norm_sq(&self) -> f32 {
+        self.values.iter().map(|v| v * v).sum()
+    }
+
+    /// Similarity with a dense F32 or F16 vector, matching [`Vector::similarity`].
+    ///
+    /// Only the dense entries at this vector's indices are read, except for the
+    /// dense norm needed by cosine and euclidean.
+    pub fn similarity(&self, dense: &Vector, metric: SimilarityMetric) -> Result<f32, String> {
+        if dense.dim as u32 != self.dim {
+            return Err("Dimension mismatch".to_string());
+        }
+        let width = match dense.dtype {
+            EmbeddingType::F32 => 4,
+            EmbeddingType::F16 => 2,
+            other => return Err(format!("Similarity not implemented for {:?}", other)),
+        };
+        if dense.data.len() != self.dim as usize * width {
+            return Err("Invalid data length for dense vector".to_string());
+        }
+        let value_at = |i: usize| {
+            let b = &dense.data[i * width..];
+            if width == 4 {
+                f32::from_le_bytes([b[0], b[1], b[2], b[3]])
+            } else {
+                f16::from_le_bytes([b[0], b[1]]).to_f32()
+            }
+        };
+
+        let dot: f32 = self
+            .indices
+            .iter()
+            .zip(&self.values)
+            .map(|(&i, v)| v * value_at(i as usize))
+            .sum();
+        if metric == SimilarityMetric::DotProduct {
+            return Ok(dot);
+        }
+        let dense_norm_sq: f32 = (0..self.dim as usize)
+            .map(|i| value_at(i) * value_at(i))
+            .sum();
+        Ok(combine(metric, dot, self.norm_sq(), dense_norm_sq))
+    }
+
+    /// Similarity with another sparse vector, matching [`Vector::similarity`].
+    pub fn sparse_similarity(
+        &self,
+        other: &SparseVector,
+        metric: SimilarityMetric,
+    ) -> Result<f32, String> {
+        if self.dim != other.dim {
+            return Err("Dimension mismatch".to_string());
+        }
+        let (mut a, mut b, mut dot) = (0, 0, 0.0f32);
+        while a < self.nnz() && b < other.nnz() {
+            match self.indices[a].cmp(&other.indices[b]) {
+                Ordering::Less => a += 1,
+                Ordering::Greater => b += 1,
+                Ordering::Equal => {
+                    dot += self.values[a] * other.values[b];
+                    a += 1;
+                    b += 1;
+                }
+            }
+        }
+        Ok(combine(metric, dot, self.norm_sq(), other.norm_sq()))
+    }
+
+    /// Encode to binary format
+    /// Format: dim (u32) | nnz (u32) | [index: u32; nnz] | [value: f32; nnz]
+    pub fn encode(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buf = Vec::with_capacity(self.encoded_size());
+
+        buf.write_u32::<LittleEndian>(self.dim)?;
+        buf.write_u32::<LittleEndian>(self.indices.len() as u32)?;
+        for &index in &self.indices {
+            buf.write_u32::<LittleEndian>(index)?;
+        }
+        for &value in &self.values {
+            buf.write_f32::<LittleEndian>(value)?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Decode from binary format
+    pub fn decode(data: &[u8]) -> Result<Self, std::io::Error> {
+        let mut rdr = Cursor::new(data);
+
+        let dim = rdr.read_u32::<LittleEndian>()?;
+        let nnz = rdr.read_u32::<LittleEndian>()? as usize;
+        if data.len() < 8 + nnz * 8 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Truncated sparse vector",
+            ));
+        }
+
+        let mut indices = Vec::with_capacity(nnz);
+        for _ in 0..nnz {
+            indices.push(rdr.read_u32::<LittleEndian>()?);
+        }
+        let mut values = Vec::with_capacity(nnz);
+        for _ in 0..nnz {
+            values.push(rdr.read_f32::<LittleEndian>()?);
+        }
+
+        Self::new(dim, indices, values)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Get the size of the encoded vector in bytes
+    pub fn encoded_size(&self) -> usize {
+        8 + self.indices.len() * 8
+    }
+}
+
+/// Finishes a metric from the dot product and both squared norms.
+fn combine(metric: SimilarityMetric, dot: f32, norm1_sq: f32, norm2_sq: f32) -> f32 {
+    match metric {
+        SimilarityMetric::DotProduct => dot,
+        SimilarityMetric::Cosine => {
+            let (norm1, norm2) = (norm1_sq.sqrt(), norm2_sq.sqrt());
+            if norm1 == 0.0 || norm2 == 0.0 {
+                0.0
+            } else {
+                dot / (norm1 * norm2)
+            }
+        }
+        SimilarityMetric::Euclidean => (norm1_sq + norm2_sq - 2.0 * dot).max(0.0).sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sparse() -> SparseVector {
+        SparseVector::from_pairs(8, vec![(5, 2.0), (1, 1.0), (3, 0.0), (6, -1.0)]).unwrap()
+    }
+
+    #[test]
+    fn test_construction_and_dense_round_trip() {
+        let s = sparse();
+        assert_eq!(s.indices, vec![1, 5, 6]);
+        assert_eq!(s.values, vec![1.0, 2.0, -1.0]);
+        assert_eq!(s.get(5), 2.0);
+        assert_eq!(s.get(4), 0.0);
+
+        let dense = s.to_dense().unwrap();
+        assert_eq!(dense.dim, 8);
+        assert_eq!(SparseVector::from_dense(&dense).unwrap(), s);
+
+        assert!(SparseVector::new(8, vec![2, 1], vec![1.0, 1.0]).is_err());
+        assert!(SparseVector::new(8, vec![8], vec![1.0]).is_err());
+        assert!(SparseVector::new(8, vec![1], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let s = sparse();
+        let encoded = s.encode().unwrap();
+        assert_eq!(encoded.len(), s.encoded_size());
+        assert_eq!(SparseVector::decode(&encoded).unwrap(), s);
+        assert!(SparseVector::decode(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_similarity_matches_dense() {
+        let s = sparse();
+        let dense_s = s.to_dense().unwrap();
+        let other = Vector::from_f32(vec![0.5, 1.0, 0.0, 2.0, 0.0, 0.25, 1.0, -3.0]);
+        let other_sparse = SparseVector::from_dense(&other).unwrap();
+
+        for metric in [
+            SimilarityMetric::Cosine,
+            SimilarityMetric::DotProduct,
+            SimilarityMetric::Euclidean,
+        ] {
+            let expected = dense_s.similarity(&other, metric).unwrap();
+            assert!((s.similarity(&other, metric).unwrap() - expected).abs() < 1e-5);
+            assert!((s.sparse_similarity(&other_sparse, metric).unwrap() - expected).abs() < 1e-5);
+            let half = other.to_f16().unwrap();
+            assert!((s.similarity(&half, metric).unwrap() - expected).abs() < 1e-5);
+        }
+
+        assert!(s
+            .similarity(&Vector::from_f32(vec![1.0; 4]), SimilarityMetric::Cosine)
+            .is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! Snapshot/delta streaming of live embedding updates.
+//!
+//! Mirrors the spatial ABS/DELTA protocol: [`EmbeddingStream::next_frame`] turns
+//! successive vectors into [`EmbeddingFrame`]s, sending a full snapshot when the
+//! interval elapses, when too many entries changed, or when the vector drifted too
+//! far from the last snapshot, and a [`VectorDelta`] otherwise. The same type
+//! rebuilds the vector on the receiving side with
+//! [`EmbeddingStream::process_frame`].
+
+use crate::decoder::Decoder;
+use crate::delta::{UpdateStrategy, VectorDelta};
+use crate::encoder::Encoder;
+use crate::vector::{EmbeddingType, Vector};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::Cursor;
+
+/// Kind of update carried by a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameMode {
+    Snapshot = 0x00,
+    Delta = 0x01,
+}
+
+/// Payload of an [`EmbeddingFrame`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbeddingUpdate {
+    /// The full vector; later deltas refer to it by `base_id`.
+    Snapshot { base_id: u16, vector: Vector },
+    /// Changes since the previous frame.
+    Delta(VectorDelta),
+}
+
+/// One update of an embedding stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingFrame {
+    pub sequence_id: u32,
+    pub update: EmbeddingUpdate,
+}
+
+impl EmbeddingFrame {
+    /// Returns whether the frame is a snapshot or a delta.
+    pub fn mode(&self) -> FrameMode {
+        match self.update {
+            EmbeddingUpdate::Snapshot { .. } => FrameMode::Snapshot,
+            EmbeddingUpdate::Delta(_) => FrameMode::Delta,
+        }
+    }
+
+    /// Encode the frame to binary format
+    /// Format: mode (u8) | sequence_id (u32) | payload, where the payload is
+    /// base_id (u16) | vector (embedding encoding) for snapshots and the
+    /// VectorDelta encoding for deltas
+    pub fn encode(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buf = vec![self.mode() as u8];
+        buf.write_u32::<LittleEndian>(self.sequence_id)?;
+
+        match &self.update {
+            EmbeddingUpdate::Snapshot { base_id, vector } => {
+                buf.write_u16::<LittleEndian>(*base_id)?;
+                buf.extend_from_slice(&Encoder::encode(vector)?);
+            }
+            EmbeddingUpdate::Delta(delta) => buf.extend_from_slice(&delta.encode()?),
+        }
+
+        Ok(buf)
+    }
+
+    /// Decode a frame from binary format
+    pub fn decode(data: &[u8]) -> Result<Self, std::io::Error> {
+        let mut rdr = Cursor::new(data);
+
+        let mode = rdr.read_u8()?;
+        let sequence_id = rdr.read_u32::<LittleEndian>()?;
+
+        let update = match mode {
+            0x00 => {
+                let base_id = rdr.read_u16::<LittleEndian>()?;
+                let vector = Decoder::decode(&data[rdr.position() as usize..])?;
+                EmbeddingUpdate::Snapshot { base_id, vector }
+            }
+            0x01 => {
+                let delta = VectorDelta::decode(&data[rdr.position() as usize..])?;
+                if 5 + delta.encoded_size() != data.len() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Trailing bytes after delta",
+                    ));
+                }
+                EmbeddingUpdate::Delta(delta)
+            }
+            other => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid frame mode: {}", other),
+                ))
+            }
+        };
+
+        Ok(Self {
+            sequence_id,
+            update,
+        })
+    }
+}
+
+/// Policy deciding when [`EmbeddingStream`] sends a snapshot.
+#[derive(Debug, Clone)]
+pub struct EmbeddingStreamConfig {
+    /// Every `snapshot_interval`-th frame is a snapshot, so late joiners and
+    /// receivers that lost a frame can resynchronize.
+    pub snapshot_interval: u32,
+    /// Decides from the change ratio whether a delta is worth sending.
+    pub strategy: UpdateStrategy,
+    /// Largest L2 distance from the last snapshot, relative to the snapshot's norm,
+    /// still sent as a delta.
+    pub max_drift: f32,
+}
+
+impl Default for EmbeddingStreamConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_interval: 100,
+            strategy: UpdateStrategy::default(),
+            max_drift: 0.5,
+        }
+    }
+}
+
+/// Stateful encoder/decoder of embedding update frames.
+///
+/// # Example
+///
+/// ```
+/// use lnmp_embedding::{EmbeddingStream, Vector};
+///
+/// let mut sender = EmbeddingStream::new(100);
+/// let mut receiver = EmbeddingStream::new(100);
+///
+/// for values in [vec![1.0, 2.0, 3.0, 4.0], vec![1.0, 2.5, 3.0, 4.0]] {
+///     let frame = sender.next_frame(&Vector::from_f32(values.clone())).unwrap();
+///     let bytes = frame.encode().unwrap();
+///
+///     let frame = lnmp_embedding::EmbeddingFrame::decode(&bytes).unwrap();
+///     let current = receiver.process_frame(&frame).unwrap();
+///     assert_eq!(current.as_f32().unwrap(), values);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmbeddingStream {
+    config: EmbeddingStreamConfig,
+    sequence_counter: u32,
+    force_snapshot: bool,
+
+    // Sender state
+    next_base_id: u16,
+    sent_base_id: u16,
+    /// The vector as the receiver reconstructs it, so rounding in applied deltas
+    /// never accumulates.
+    last_sent: Option<Vector>,
+    last_snapshot: Option<Vector>,
+
+    // Receiver state
+    last_received_seq: Option<u32>,
+    current_base_id: u16,
+    current: Option<Vector>,
+}
+
+impl EmbeddingStream {
+    pub fn new(snapshot_interval: u32) -> Self {
+        Self::with_config(EmbeddingStreamConfig {
+            snapshot_interval,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_config(config: EmbeddingStreamConfig) -> Self {
+        Self {
+            config,
+            sequence_counter: 0,
+            force_snapshot: false,
+            next_base_id: 0,
+            sent_base_id: 0,
+            last_sent: None,
+            last_snapshot: None,
+            last_received_seq: None,
+            current_base_id: 0,
+            current: None,
+        }
+    }
+
+    /// Makes the next frame a snapshot, e.g. when a receiver asks to resync.
+    pub fn request_snapshot(&mut self) {
+        self.force_snapshot = true;
+    }
+
+    /// Generates the frame for the next vector.
+    /// Automatically decides whether to send a snapshot or a delta.
+    pub fn next_frame(&mut self, vector: &Vector) -> Result<EmbeddingFrame, String> {
+        let seq = self.sequence_counter;
+        let interval = self.config.snapshot_interval.max(1);
+        let force_snapshot = self.force_snapshot || seq.is_multiple_of(interval);
+
+        let delta = match (&self.last_sent, &self.last_snapshot) {
+            (Some(last), Some(snapshot))
+                if !force_snapshot
+                    && vector.dtype == EmbeddingType::F32
+                    && last.dtype == vector.dtype
+                    && last.dim == vector.dim =>
+            {
+                let delta = VectorDelta::from_vectors(last, vector, self.sent_base_id)?;
+                let within_drift = drift(snapshot, vector)? <= self.config.max_drift;
+                (within_drift && self.config.strategy.should_use_delta(&delta, vector.dim))
+                    .then_some(delta)
+            }
+            _ => None,
+        };
+
+        let update = match delta {
+            Some(delta) => {
+                self.last_sent = Some(delta.apply(self.last_sent.as_ref().unwrap())?);
+                EmbeddingUpdate::Delta(delta)
+            }
+            None => {
+                let base_id = self.next_base_id;
+                self.next_base_id = self.next_base_id.wrapping_add(1);
+                self.sent_base_id = base_id;
+                self.last_sent = Some(vector.clone());
+                self.last_snapshot = Some(vector.clone());
+                self.force_snapshot = false;
+                EmbeddingUpdate::Snapshot {
+                    base_id,
+                    vector: vector.clone(),
+                }
+            }
+        };
+
+        self.sequence_counter = self.sequence_counter.wrapping_add(1);
+        Ok(EmbeddingFrame {
+            sequence_id: seq,
+            update,
+        })
+    }
+
+    /// Processes an incoming frame and returns the updated vector.
+    ///
+    /// A delta is only applied directly after the frame it was computed against;
+    /// after a gap, every delta is rejected until the next snapshot arrives.
+    pub fn process_frame(&mut self, frame: &EmbeddingFrame) -> Result<&Vector, String> {
+        if let Some(last_seq) = self.last_received_seq {
+            if frame.sequence_id <= last_seq {
+                return Err(format!(
+                    "Stale frame {} (last received {})",
+                    frame.sequence_id, last_seq
+                ));
+            }
+        }
+
+        match &frame.update {
+            EmbeddingUpdate::Snapshot { base_id, vector } => {
+                self.current_base_id = *base_id;
+                self.current = Some(vector.clone());
+            }
+            EmbeddingUpdate::Delta(delta) => {
+                let Some(current) = &self.current else {
+                    return Err("Received delta frame without prior snapshot".to_string());
+                };
+                if let Some(last_seq) = self.last_received_seq {
+                    if frame.sequence_id != last_seq + 1 {
+                        return Err(format!(
+                            "Frame loss detected (gap {} -> {}). Waiting for snapshot.",
+                            last_seq, frame.sequence_id
+                        ));
+                    }
+                }
+                if delta.base_id != self.current_base_id {
+                    return Err(format!(
+                        "Delta refers to base {} but current base is {}",
+                        delta.base_id, self.current_base_id
+                    ));
+                }
+                self.current = Some(delta.apply(current)?);
+            }
+        }
+
+        self.last_received_seq = Some(frame.sequence_id);
+        Ok(self.current.as_ref().unwrap())
+    }
+
+    /// The receiver's current vector, if a snapshot has been received.
+    pub fn current(&self) -> Option<&Vector> {
+        self.current.as_ref()
+    }
+}
+
+/// L2 distance between `vector` and `snapshot`, relative to the snapshot's norm.
+fn drift(snapshot: &Vector, vector: &Vector) -> Result<f32, String> {
+    let (base, values) = (snapshot.as_f32()?, vector.as_f32()?);
+    let distance_sq: f32 = base.iter().zip(&values).map(|(a, b)| (a - b).powi(2)).sum();
+    let norm_sq: f32 = base.iter().map(|a| a * a).sum();
+    if norm_sq == 0.0 {
+        return Ok(if distance_sq == 0.0 {
+            0.0
+        } else {
+            f32::INFINITY
+        });
+    }
+    Ok((distance_sq / norm_sq).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(changed: usize, value: f32) -> Vector {
+        let mut values = vec![1.0f32; 10];
+        values[..changed].fill(value);
+        Vector::from_f32(values)
+    }
+
+    #[test]
+    fn test_snapshot_and_delta_decisions() {
+        let mut stream = EmbeddingStream::new(4);
+
+        let mut values = vec![1.0f32; 10];
+        let mut modes = Vec::new();
+        let mut send = |values: &[f32]| {
+            let frame = stream.next_frame(&Vector::from_f32(values.to_vec()));
+            modes.push(frame.unwrap().mode());
+        };
+
+        send(&values); // first frame
+        values[0] = 1.1;
+        send(&values); // one entry changed
+        values[1..5].fill(1.1);
+        send(&values); // 40% of entries changed
+        values[9] = 1.2;
+        send(&values); // one entry changed
+        values[8] = 1.2;
+        send(&values); // interval
+        values[0] = 5.0;
+        send(&values); // one entry changed, but drifted far
+
+        assert_eq!(
+            modes,
+            vec![
+                FrameMode::Snapshot,
+                FrameMode::Delta,
+                FrameMode::Snapshot,
+                FrameMode::Delta,
+                FrameMode::Snapshot,
+                FrameMode::Snapshot,
+            ]
+        );
+
+        stream.request_snapshot();
+        assert_eq!(
+            stream.next_frame(&vector(1, 5.0)).unwrap().mode(),
+            FrameMode::Snapshot
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_bytes() {
+        let mut sender = EmbeddingStream::new(100);
+        let mut receiver = EmbeddingStream::new(100);
+
+        for step in 0..20 {
+            let v = vector(step % 3, 1.0 + step as f32 * 0.01);
+            let bytes = sender.next_frame(&v).unwrap().encode().unwrap();
+            let frame = EmbeddingFrame::decode(&bytes).unwrap();
+            assert_eq!(
+                frame,
+                EmbeddingFrame::decode(&frame.encode().unwrap()).unwrap()
+            );
+            let current = receiver.process_frame(&frame).unwrap();
+            assert_eq!(current, sender.last_sent.as_ref().unwrap());
+            for (a, b) in current.as_f32().unwrap().iter().zip(v.as_f32().unwrap()) {
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+
+        assert!(EmbeddingFrame::decode(&[0x02, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_receiver_rejects_gaps_until_snapshot() {
+        let mut sender = EmbeddingStream::new(100);
+        let mut receiver = EmbeddingStream::new(100);
+
+        let first = sender.next_frame(&vector(0, 1.0)).unwrap();
+        let lost = sender.next_frame(&vector(1, 1.1)).unwrap();
+        let after_gap = sender.next_frame(&vector(1, 1.2)).unwrap();
+        assert_eq!(after_gap.mode(), FrameMode::Delta);
+
+        assert!(receiver.process_frame(&lost).is_err());
+        receiver.process_frame(&first).unwrap();
+        assert!(receiver.process_frame(&after_gap).is_err());
+        assert!(receiver.process_frame(&first).is_err());
+
+        sender.request_snapshot();
+        let resync = sender.next_frame(&vector(1, 1.3)).unwrap();
+        let current = receiver.process_frame(&resync).unwrap();
+        assert_eq!(current, &vector(1, 1.3));
+    }
+}
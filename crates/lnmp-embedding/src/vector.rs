@@ -1,3 +1,4 @@
+use half::f16;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -31,16 +32,66 @@ pub enum SimilarityMetric {
     DotProduct = 0x03,
 }
 
+/// Transformations applied to a vector's values.
+///
+/// Recorded in the high bits of the encoded similarity byte, so a decoded vector
+/// still knows it was normalized or centered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorTransform {
+    /// Scaled to unit L2 norm
+    pub normalized: bool,
+    /// Shifted by the mean of a batch
+    pub centered: bool,
+}
+
+impl VectorTransform {
+    const NORMALIZED: u8 = 0x10;
+    const CENTERED: u8 = 0x20;
+
+    /// Returns the flag bits written into the similarity byte.
+    pub fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.normalized {
+            bits |= Self::NORMALIZED;
+        }
+        if self.centered {
+            bits |= Self::CENTERED;
+        }
+        bits
+    }
+
+    /// Reads the flag bits of a similarity byte.
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            normalized: bits & Self::NORMALIZED != 0,
+            centered: bits & Self::CENTERED != 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Vector {
     pub dtype: EmbeddingType,
     pub dim: u16,
     pub data: Vec<u8>, // Raw bytes
+    #[serde(default)]
+    pub transform: VectorTransform,
 }
 
 impl Vector {
     pub fn new(dtype: EmbeddingType, dim: u16, data: Vec<u8>) -> Self {
-        Self { dtype, dim, data }
+        Self {
+            dtype,
+            dim,
+            data,
+            transform: VectorTransform::default(),
+        }
+    }
+
+    /// Sets the recorded transformations.
+    pub fn with_transform(mut self, transform: VectorTransform) -> Self {
+        self.transform = transform;
+        self
     }
 
     pub fn from_f32(data: Vec<f32>) -> Self {
@@ -48,10 +99,60 @@ impl Vector {
         for val in &data {
             bytes.extend_from_slice(&val.to_le_bytes());
         }
-        Self {
-            dtype: EmbeddingType::F32,
-            dim: data.len() as u16,
-            data: bytes,
+        Self::new(EmbeddingType::F32, data.len() as u16, bytes)
+    }
+
+    /// Creates an F16 vector, stored at 2 bytes per element.
+    pub fn from_f16(data: Vec<f16>) -> Self {
+        let mut bytes = Vec::with_capacity(data.len() * 2);
+        for val in &data {
+            bytes.extend_from_slice(&val.to_le_bytes());
+        }
+        Self::new(EmbeddingType::F16, data.len() as u16, bytes)
+    }
+
+    /// Returns the elements of an F16 vector.
+    pub fn as_f16(&self) -> Result<Vec<f16>, String> {
+        if self.dtype != EmbeddingType::F16 {
+            return Err(format!("Cannot convert {:?} to F16", self.dtype));
+        }
+
+        if !self.data.len().is_multiple_of(2) {
+            return Err("Invalid data length for F16".to_string());
+        }
+
+        Ok(self
+            .data
+            .chunks_exact(2)
+            .map(|chunk| f16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect())
+    }
+
+    /// Converts an F32 vector to F16, halving its size.
+    ///
+    /// Uses the same rounding as `lnmp-quant`'s FP16 scheme, so the bytes equal
+    /// those of an `FP16Passthrough` quantized vector. F16 vectors are returned
+    /// unchanged.
+    pub fn to_f16(&self) -> Result<Vector, String> {
+        match self.dtype {
+            EmbeddingType::F16 => Ok(self.clone()),
+            EmbeddingType::F32 => Ok(Self::from_f16(
+                self.as_f32()?.into_iter().map(f16::from_f32).collect(),
+            )
+            .with_transform(self.transform)),
+            other => Err(format!("Cannot convert {:?} to F16", other)),
+        }
+    }
+
+    /// Converts an F16 vector to F32. F32 vectors are returned unchanged.
+    pub fn to_f32(&self) -> Result<Vector, String> {
+        match self.dtype {
+            EmbeddingType::F32 => Ok(self.clone()),
+            EmbeddingType::F16 => Ok(Self::from_f32(
+                self.as_f16()?.into_iter().map(f16::to_f32).collect(),
+            )
+            .with_transform(self.transform)),
+            other => Err(format!("Cannot convert {:?} to F32", other)),
         }
     }
 
@@ -73,6 +174,9 @@ impl Vector {
     }
 
     pub fn normalize(&self) -> Result<Vector, String> {
+        if self.dtype == EmbeddingType::F16 {
+            return self.to_f32()?.normalize()?.to_f16();
+        }
         if self.dtype != EmbeddingType::F32 {
             return Err("Normalization not implemented for this dtype".to_string());
         }
@@ -93,11 +197,67 @@ impl Vector {
             res_data.extend_from_slice(&normalized_val.to_le_bytes());
         }
 
-        Ok(Vector {
-            dtype: self.dtype,
-            dim: self.dim,
-            data: res_data,
-        })
+        Ok(
+            Vector::new(self.dtype, self.dim, res_data).with_transform(VectorTransform {
+                normalized: true,
+                ..self.transform
+            }),
+        )
+    }
+
+    /// Scales the vector to unit L2 norm in place (F32 and F16).
+    ///
+    /// Zero vectors are left as they are and not marked normalized.
+    pub fn normalize_in_place(&mut self) -> Result<(), String> {
+        match self.dtype {
+            EmbeddingType::F32 => {
+                let norm = Self::norm_sq_f32(&self.data).sqrt();
+                if norm == 0.0 {
+                    return Ok(());
+                }
+                for chunk in self.data.chunks_exact_mut(4) {
+                    let val = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    chunk.copy_from_slice(&(val / norm).to_le_bytes());
+                }
+            }
+            EmbeddingType::F16 => {
+                let values = self.as_f16()?;
+                let norm = values
+                    .iter()
+                    .map(|v| v.to_f32() * v.to_f32())
+                    .sum::<f32>()
+                    .sqrt();
+                if norm == 0.0 {
+                    return Ok(());
+                }
+                for (chunk, val) in self.data.chunks_exact_mut(2).zip(values) {
+                    chunk.copy_from_slice(&f16::from_f32(val.to_f32() / norm).to_le_bytes());
+                }
+            }
+            _ => return Err("Normalization not implemented for this dtype".to_string()),
+        }
+        self.transform.normalized = true;
+        Ok(())
+    }
+
+    /// Subtracts `mean` from each element in place (F32 only).
+    ///
+    /// Marks the vector centered; a normalized vector is no longer unit length
+    /// afterwards and loses that mark.
+    pub fn center_in_place(&mut self, mean: &[f32]) -> Result<(), String> {
+        if self.dtype != EmbeddingType::F32 {
+            return Err("Centering only supported for F32 embeddings".to_string());
+        }
+        if mean.len() != self.dim as usize {
+            return Err("Dimension mismatch".to_string());
+        }
+        for (chunk, m) in self.data.chunks_exact_mut(4).zip(mean) {
+            let val = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            chunk.copy_from_slice(&(val - m).to_le_bytes());
+        }
+        self.transform.centered = true;
+        self.transform.normalized = false;
+        Ok(())
     }
 
     pub fn similarity(&self, other: &Vector, metric: SimilarityMetric) -> Result<f32, String> {
@@ -133,11 +293,47 @@ impl Vector {
                     Ok(sum_sq.sqrt())
                 }
             }
+        } else if self.dtype == EmbeddingType::F16 {
+            // Elements are upcast one at a time, without allocating F32 copies
+            let (dot, norm1_sq, norm2_sq, dist_sq) = Self::accumulate_f16(&self.data, &other.data);
+            match metric {
+                SimilarityMetric::Cosine => {
+                    let norm1 = norm1_sq.sqrt();
+                    let norm2 = norm2_sq.sqrt();
+                    if norm1 == 0.0 || norm2 == 0.0 {
+                        return Ok(0.0);
+                    }
+                    Ok(dot / (norm1 * norm2))
+                }
+                SimilarityMetric::DotProduct => Ok(dot),
+                SimilarityMetric::Euclidean => Ok(dist_sq.sqrt()),
+            }
         } else {
             Err("Similarity not implemented for this dtype yet".to_string())
         }
     }
 
+    /// Dot product, both norms squared and distance squared of two f16 byte buffers,
+    /// accumulated in f32
+    #[inline]
+    fn accumulate_f16(data1: &[u8], data2: &[u8]) -> (f32, f32, f32, f32) {
+        let mut dot = 0.0f32;
+        let mut norm1_sq = 0.0f32;
+        let mut norm2_sq = 0.0f32;
+        let mut dist_sq = 0.0f32;
+
+        for (c1, c2) in data1.chunks_exact(2).zip(data2.chunks_exact(2)) {
+            let v1 = f16::from_le_bytes([c1[0], c1[1]]).to_f32();
+            let v2 = f16::from_le_bytes([c2[0], c2[1]]).to_f32();
+            dot += v1 * v2;
+            norm1_sq += v1 * v1;
+            norm2_sq += v2 * v2;
+            dist_sq += (v1 - v2) * (v1 - v2);
+        }
+
+        (dot, norm1_sq, norm2_sq, dist_sq)
+    }
+
     /// Optimized dot product for f32 from raw bytes
     #[inline]
     fn dot_product_f32(data1: &[u8], data2: &[u8]) -> f32 {
@@ -229,4 +425,48 @@ mod tests {
         let norm = (data[0] * data[0] + data[1] * data[1]).sqrt();
         assert!((norm - 1.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_f16_storage() {
+        let v = Vector::from_f32(vec![0.5, -1.25, 3.0]);
+        let half = v.to_f16().unwrap();
+        assert_eq!(half.dtype, EmbeddingType::F16);
+        assert_eq!(half.dim, 3);
+        assert_eq!(half.data.len(), v.data.len() / 2);
+        assert_eq!(half.to_f32().unwrap(), v);
+        assert_eq!(
+            half.as_f16().unwrap(),
+            vec![f16::from_f32(0.5), f16::from_f32(-1.25), f16::from_f32(3.0)]
+        );
+
+        let encoded = crate::Encoder::encode(&half).unwrap();
+        assert_eq!(encoded.len(), 4 + 6);
+        assert_eq!(crate::Decoder::decode(&encoded).unwrap(), half);
+
+        let normalized = Vector::from_f32(vec![3.0, 4.0])
+            .to_f16()
+            .unwrap()
+            .normalize()
+            .unwrap();
+        assert_eq!(normalized.dtype, EmbeddingType::F16);
+        assert_eq!(normalized.as_f16().unwrap()[0], f16::from_f32(0.6));
+    }
+
+    #[test]
+    fn test_f16_similarity_matches_f32() {
+        let a = Vector::from_f32(vec![0.25, -0.5, 1.0, 2.0]);
+        let b = Vector::from_f32(vec![1.0, 0.75, -0.5, 0.125]);
+        let (ha, hb) = (a.to_f16().unwrap(), b.to_f16().unwrap());
+        for metric in [
+            SimilarityMetric::Cosine,
+            SimilarityMetric::DotProduct,
+            SimilarityMetric::Euclidean,
+        ] {
+            assert_eq!(
+                ha.similarity(&hb, metric).unwrap(),
+                a.similarity(&b, metric).unwrap()
+            );
+        }
+        assert!(ha.similarity(&b, SimilarityMetric::Cosine).is_err());
+    }
 }
@@ -0,0 +1,520 @@
+//! Batch TLV codec for multi-record frames
+//!
+//! When many records travel in one transport message, their envelopes usually
+//! share source and trace metadata and carry nearly consecutive timestamps and
+//! sequence numbers. The batch codec writes the shared values once and keeps
+//! per-record overrides small.
+//!
+//! ## Batch Format
+//!
+//! ```text
+//! COUNT (4 bytes, BE) | SHARED_LEN (4 bytes, BE) | SHARED | RECORD*
+//! RECORD: LEN (4 bytes, BE) | TLV*
+//! ```
+//!
+//! `SHARED` is a regular envelope TLV block (see [`binary_codec`](crate::binary_codec)):
+//!
+//! - Source/TraceID/ContentType/SchemaVersion/CorrelationID/CausationID/
+//!   PartitionKey/ExpiresAt appear when every record has the same value.
+//! - Timestamp/Sequence appear when every record has one; the value is the base
+//!   that the first record's delta applies to.
+//!
+//! Per-record TLVs use the standard types for values that differ from the
+//! shared block, the record's signature as a `0x1E` entry, its labels as
+//! `0x1F` entries, plus:
+//!
+//! - `0x20`: Timestamp delta from the previous record (zigzag varint)
+//! - `0x21`: Sequence delta from the previous record (zigzag varint)
+//!
+//! A delta of zero is omitted. Entries within each block MUST appear in
+//! ascending type order.
+
+use crate::binary_codec::{
+    decode_label, encode_label, insert_label, tlv_type, TlvDecoder, TlvEncoder,
+};
+use crate::signature::{decode_signature, encode_signature};
+use crate::{EnvelopeError, EnvelopeMetadata, Result};
+
+/// Batch-only TLV type codes
+pub mod batch_tlv_type {
+    /// Timestamp delta from the previous record (zigzag varint)
+    pub const TIMESTAMP_DELTA: u8 = 0x20;
+    /// Sequence delta from the previous record (zigzag varint)
+    pub const SEQUENCE_DELTA: u8 = 0x21;
+}
+
+/// Batch TLV encoder for the metadata of multiple envelopes
+pub struct BatchTlvEncoder;
+
+impl BatchTlvEncoder {
+    /// Encodes the metadata of a batch of envelopes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lnmp_envelope::batch_codec::{BatchTlvDecoder, BatchTlvEncoder};
+    /// use lnmp_envelope::EnvelopeMetadata;
+    ///
+    /// let batch: Vec<EnvelopeMetadata> = (0..3)
+    ///     .map(|i| {
+    ///         let mut metadata = EnvelopeMetadata::new();
+    ///         metadata.timestamp = Some(1732373147000 + i * 10);
+    ///         metadata.source = Some("sensor-12".to_string());
+    ///         metadata.sequence = Some(40 + i);
+    ///         metadata
+    ///     })
+    ///     .collect();
+    ///
+    /// let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+    /// assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+    /// ```
+    pub fn encode(batch: &[EnvelopeMetadata]) -> Result<Vec<u8>> {
+        let count =
+            u32::try_from(batch.len()).map_err(|_| EnvelopeError::InvalidTlvLength(batch.len()))?;
+
+        let mut shared = EnvelopeMetadata::new();
+        if let Some(first) = batch.first() {
+            if batch.iter().all(|m| m.source == first.source) {
+                shared.source = first.source.clone();
+            }
+            if batch.iter().all(|m| m.trace_id == first.trace_id) {
+                shared.trace_id = first.trace_id.clone();
+            }
+            if batch.iter().all(|m| m.content_type == first.content_type) {
+                shared.content_type = first.content_type.clone();
+            }
+            if batch
+                .iter()
+                .all(|m| m.schema_version == first.schema_version)
+            {
+                shared.schema_version = first.schema_version.clone();
+            }
+            if batch
+                .iter()
+                .all(|m| m.correlation_id == first.correlation_id)
+            {
+                shared.correlation_id = first.correlation_id.clone();
+            }
+            if batch.iter().all(|m| m.causation_id == first.causation_id) {
+                shared.causation_id = first.causation_id.clone();
+            }
+            if batch.iter().all(|m| m.partition_key == first.partition_key) {
+                shared.partition_key = first.partition_key.clone();
+            }
+            if batch.iter().all(|m| m.expires_at == first.expires_at) {
+                shared.expires_at = first.expires_at;
+            }
+            if batch.iter().all(|m| m.timestamp.is_some()) {
+                shared.timestamp = first.timestamp;
+            }
+            if batch.iter().all(|m| m.sequence.is_some()) {
+                shared.sequence = first.sequence;
+            }
+        }
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&count.to_be_bytes());
+        write_block(&mut buf, &TlvEncoder::encode(&shared)?)?;
+
+        let mut prev_timestamp = shared.timestamp;
+        let mut prev_sequence = shared.sequence;
+        for metadata in batch {
+            let mut record = Vec::new();
+
+            // Canonical order: 0x10-0x1A absolute values, 0x1E signature, 0x1F labels,
+            // then 0x20-0x21 deltas
+            if shared.timestamp.is_none() {
+                if let Some(ts) = metadata.timestamp {
+                    write_tlv(&mut record, tlv_type::TIMESTAMP, &ts.to_be_bytes())?;
+                }
+            }
+            if shared.source.is_none() {
+                if let Some(ref source) = metadata.source {
+                    write_tlv(&mut record, tlv_type::SOURCE, source.as_bytes())?;
+                }
+            }
+            if shared.trace_id.is_none() {
+                if let Some(ref trace_id) = metadata.trace_id {
+                    write_tlv(&mut record, tlv_type::TRACE_ID, trace_id.as_bytes())?;
+                }
+            }
+            if shared.sequence.is_none() {
+                if let Some(seq) = metadata.sequence {
+                    write_tlv(&mut record, tlv_type::SEQUENCE, &seq.to_be_bytes())?;
+                }
+            }
+            if shared.content_type.is_none() {
+                if let Some(ref content_type) = metadata.content_type {
+                    write_tlv(&mut record, tlv_type::CONTENT_TYPE, content_type.as_bytes())?;
+                }
+            }
+            if shared.schema_version.is_none() {
+                if let Some(ref version) = metadata.schema_version {
+                    write_tlv(&mut record, tlv_type::SCHEMA_VERSION, version.as_bytes())?;
+                }
+            }
+            if shared.correlation_id.is_none() {
+                if let Some(ref id) = metadata.correlation_id {
+                    write_tlv(&mut record, tlv_type::CORRELATION_ID, id.as_bytes())?;
+                }
+            }
+            if shared.causation_id.is_none() {
+                if let Some(ref id) = metadata.causation_id {
+                    write_tlv(&mut record, tlv_type::CAUSATION_ID, id.as_bytes())?;
+                }
+            }
+            if shared.partition_key.is_none() {
+                if let Some(ref key) = metadata.partition_key {
+                    write_tlv(&mut record, tlv_type::PARTITION_KEY, key.as_bytes())?;
+                }
+            }
+            if shared.expires_at.is_none() {
+                if let Some(exp) = metadata.expires_at {
+                    write_tlv(&mut record, tlv_type::EXPIRES_AT, &exp.to_be_bytes())?;
+                }
+            }
+            if let Some(ref sig) = metadata.signature {
+                write_tlv(&mut record, tlv_type::SIGNATURE, &encode_signature(sig)?)?;
+            }
+            for (key, value) in &metadata.labels {
+                write_tlv(&mut record, tlv_type::LABEL, &encode_label(key, value)?)?;
+            }
+            if let (Some(prev), Some(ts)) = (prev_timestamp, metadata.timestamp) {
+                write_delta(&mut record, batch_tlv_type::TIMESTAMP_DELTA, prev, ts)?;
+                prev_timestamp = Some(ts);
+            }
+            if let (Some(prev), Some(seq)) = (prev_sequence, metadata.sequence) {
+                write_delta(&mut record, batch_tlv_type::SEQUENCE_DELTA, prev, seq)?;
+                prev_sequence = Some(seq);
+            }
+
+            write_block(&mut buf, &record)?;
+        }
+
+        Ok(buf)
+    }
+}
+
+/// Batch TLV decoder for the metadata of multiple envelopes
+pub struct BatchTlvDecoder;
+
+impl BatchTlvDecoder {
+    /// Decodes the metadata of a batch of envelopes
+    ///
+    /// Unknown TLV types are skipped for forward compatibility.
+    pub fn decode(data: &[u8]) -> Result<Vec<EnvelopeMetadata>> {
+        let mut offset = 0;
+        let count = read_u32_be(data, &mut offset)? as usize;
+        let shared = TlvDecoder::decode(read_block(data, &mut offset)?)?;
+
+        // Every record takes at least its 4-byte length prefix
+        if count > (data.len() - offset) / 4 {
+            return Err(EnvelopeError::UnexpectedEof(offset));
+        }
+
+        let mut batch = Vec::with_capacity(count);
+        let mut prev_timestamp = shared.timestamp;
+        let mut prev_sequence = shared.sequence;
+        for _ in 0..count {
+            let block = read_block(data, &mut offset)?;
+            let mut metadata = EnvelopeMetadata {
+                source: shared.source.clone(),
+                trace_id: shared.trace_id.clone(),
+                content_type: shared.content_type.clone(),
+                schema_version: shared.schema_version.clone(),
+                correlation_id: shared.correlation_id.clone(),
+                causation_id: shared.causation_id.clone(),
+                partition_key: shared.partition_key.clone(),
+                expires_at: shared.expires_at,
+                ..EnvelopeMetadata::new()
+            };
+            let mut timestamp_delta = 0;
+            let mut sequence_delta = 0;
+
+            let mut pos = 0;
+            let mut last_type: Option<u8> = None;
+            while pos < block.len() {
+                let tlv_type = block[pos];
+                let length = read_u16_be(block, pos + 1)? as usize;
+                let value = block
+                    .get(pos + 3..pos + 3 + length)
+                    .ok_or(EnvelopeError::UnexpectedEof(offset))?;
+                pos += 3 + length;
+
+                if let Some(prev) = last_type {
+                    if tlv_type < prev || (tlv_type == prev && tlv_type != tlv_type::LABEL) {
+                        return Err(EnvelopeError::NonCanonicalOrder(tlv_type, prev));
+                    }
+                }
+                last_type = Some(tlv_type);
+
+                match tlv_type {
+                    tlv_type::TIMESTAMP => metadata.timestamp = Some(read_u64_value(value)?),
+                    tlv_type::SOURCE => metadata.source = Some(String::from_utf8(value.to_vec())?),
+                    tlv_type::TRACE_ID => {
+                        metadata.trace_id = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::SEQUENCE => metadata.sequence = Some(read_u64_value(value)?),
+                    tlv_type::CONTENT_TYPE => {
+                        metadata.content_type = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::SCHEMA_VERSION => {
+                        metadata.schema_version = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::CORRELATION_ID => {
+                        metadata.correlation_id = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::CAUSATION_ID => {
+                        metadata.causation_id = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::PARTITION_KEY => {
+                        metadata.partition_key = Some(String::from_utf8(value.to_vec())?)
+                    }
+                    tlv_type::EXPIRES_AT => metadata.expires_at = Some(read_u64_value(value)?),
+                    tlv_type::SIGNATURE => metadata.signature = Some(decode_signature(value)?),
+                    tlv_type::LABEL => {
+                        let (key, value) = decode_label(value)?;
+                        insert_label(&mut metadata, key, value)?;
+                    }
+                    batch_tlv_type::TIMESTAMP_DELTA => timestamp_delta = read_delta(value)?,
+                    batch_tlv_type::SEQUENCE_DELTA => sequence_delta = read_delta(value)?,
+                    _ => {}
+                }
+            }
+
+            if metadata.timestamp.is_none() {
+                metadata.timestamp = prev_timestamp.map(|prev| prev.wrapping_add(timestamp_delta));
+                prev_timestamp = metadata.timestamp;
+            }
+            if metadata.sequence.is_none() {
+                metadata.sequence = prev_sequence.map(|prev| prev.wrapping_add(sequence_delta));
+                prev_sequence = metadata.sequence;
+            }
+            batch.push(metadata);
+        }
+
+        if offset != data.len() {
+            return Err(EnvelopeError::InvalidTlvLength(data.len() - offset));
+        }
+        Ok(batch)
+    }
+}
+
+fn write_block(buf: &mut Vec<u8>, block: &[u8]) -> Result<()> {
+    let len =
+        u32::try_from(block.len()).map_err(|_| EnvelopeError::InvalidTlvLength(block.len()))?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(block);
+    Ok(())
+}
+
+fn write_tlv(buf: &mut Vec<u8>, tlv_type: u8, value: &[u8]) -> Result<()> {
+    let len = u16::try_from(value.len()).map_err(|_| {
+        let field = match tlv_type {
+            tlv_type::SOURCE => "source",
+            tlv_type::CONTENT_TYPE => "content_type",
+            tlv_type::SCHEMA_VERSION => "schema_version",
+            tlv_type::CORRELATION_ID => "correlation_id",
+            tlv_type::CAUSATION_ID => "causation_id",
+            tlv_type::PARTITION_KEY => "partition_key",
+            tlv_type::LABEL => "labels",
+            _ => "trace_id",
+        };
+        EnvelopeError::StringTooLong(field.to_string(), u16::MAX as usize)
+    })?;
+    buf.push(tlv_type);
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(value);
+    Ok(())
+}
+
+/// Writes `current - prev` as a zigzag varint, omitting zero deltas
+fn write_delta(buf: &mut Vec<u8>, tlv_type: u8, prev: u64, current: u64) -> Result<()> {
+    let delta = current.wrapping_sub(prev) as i64;
+    if delta == 0 {
+        return Ok(());
+    }
+    let mut zigzag = ((delta << 1) ^ (delta >> 63)) as u64;
+    let mut value = Vec::with_capacity(10);
+    loop {
+        let byte = (zigzag & 0x7F) as u8;
+        zigzag >>= 7;
+        if zigzag == 0 {
+            value.push(byte);
+            break;
+        }
+        value.push(byte | 0x80);
+    }
+    write_tlv(buf, tlv_type, &value)
+}
+
+/// Reads a zigzag varint delta as the wrapping offset to add to the previous value
+fn read_delta(value: &[u8]) -> Result<u64> {
+    if value.is_empty() || value.len() > 10 || value[value.len() - 1] & 0x80 != 0 {
+        return Err(EnvelopeError::InvalidTlvLength(value.len()));
+    }
+    let mut zigzag = 0u64;
+    for (i, byte) in value.iter().enumerate() {
+        zigzag |= ((byte & 0x7F) as u64) << (7 * i);
+    }
+    let delta = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+    Ok(delta as u64)
+}
+
+fn read_u64_value(value: &[u8]) -> Result<u64> {
+    let bytes: [u8; 8] = value
+        .try_into()
+        .map_err(|_| EnvelopeError::InvalidTlvLength(value.len()))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn read_u16_be(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or(EnvelopeError::UnexpectedEof(offset))?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32_be(data: &[u8], offset: &mut usize) -> Result<u32> {
+    let bytes = data
+        .get(*offset..*offset + 4)
+        .ok_or(EnvelopeError::UnexpectedEof(*offset))?;
+    *offset += 4;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_block<'a>(data: &'a [u8], offset: &mut usize) -> Result<&'a [u8]> {
+    let len = read_u32_be(data, offset)? as usize;
+    let block = data
+        .get(*offset..*offset + len)
+        .ok_or(EnvelopeError::UnexpectedEof(*offset))?;
+    *offset += len;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(ts: Option<u64>, source: Option<&str>, seq: Option<u64>) -> EnvelopeMetadata {
+        EnvelopeMetadata {
+            timestamp: ts,
+            source: source.map(str::to_string),
+            trace_id: Some("trace-abc".to_string()),
+            sequence: seq,
+            ..EnvelopeMetadata::new()
+        }
+    }
+
+    #[test]
+    fn test_shared_metadata_written_once() {
+        let batch: Vec<_> = (0..100)
+            .map(|i| metadata(Some(1_732_373_147_000 + i * 5), Some("sensor-12"), Some(i)))
+            .collect();
+
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+        assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+
+        let individual: usize = batch
+            .iter()
+            .map(|m| TlvEncoder::encode(m).unwrap().len())
+            .sum();
+        assert!(bytes.len() * 3 < individual);
+        assert_eq!(
+            bytes
+                .windows(b"sensor-12".len())
+                .filter(|w| *w == b"sensor-12".as_slice())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_per_record_overrides() {
+        let batch = vec![
+            metadata(Some(2_000), Some("a"), Some(7)),
+            metadata(None, Some("b"), Some(9)),
+            metadata(Some(1_000), None, Some(8)),
+        ];
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+        assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_labels_kept_per_record() {
+        let mut batch = vec![
+            metadata(Some(1_000), Some("a"), Some(1)),
+            metadata(Some(1_010), Some("a"), Some(2)),
+        ];
+        batch[0].set_label("tenant", "acme");
+        batch[0].set_label("env", "prod");
+        batch[1].set_label("x-unknown", "kept");
+        batch[0].content_type = Some(crate::CONTENT_TYPE_BINARY.to_string());
+        batch[1].content_type = Some(crate::CONTENT_TYPE_DELTA.to_string());
+        for (i, metadata) in batch.iter_mut().enumerate() {
+            metadata.schema_version = Some("3.0.0".to_string());
+            metadata.correlation_id = Some("saga-9".to_string());
+            metadata.causation_id = Some(format!("step-{i}"));
+            metadata.partition_key = Some("user-42".to_string());
+        }
+        batch[1].expires_at = Some(2_000);
+
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+        assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_signatures_kept_per_record() {
+        let mut batch = vec![
+            metadata(Some(1_000), Some("a"), Some(1)),
+            metadata(Some(1_010), Some("a"), Some(2)),
+        ];
+        batch[1].signature = Some(crate::EnvelopeSignature {
+            algorithm: "ed25519".to_string(),
+            key_id: "sensor-key-1".to_string(),
+            signature: vec![0xAB; 64],
+        });
+        batch[1].set_label("tenant", "acme");
+
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+        assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_deltas_handle_decreases_and_extremes() {
+        let batch = vec![
+            metadata(Some(u64::MAX), None, Some(0)),
+            metadata(Some(0), None, Some(u64::MAX)),
+            metadata(Some(0), None, Some(u64::MAX - 1)),
+        ];
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+        assert_eq!(BatchTlvDecoder::decode(&bytes).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let bytes = BatchTlvEncoder::encode(&[]).unwrap();
+        assert_eq!(bytes, vec![0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(BatchTlvDecoder::decode(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_malformed_batches() {
+        let batch = vec![metadata(Some(1), None, None), metadata(Some(2), None, None)];
+        let bytes = BatchTlvEncoder::encode(&batch).unwrap();
+
+        assert!(BatchTlvDecoder::decode(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(BatchTlvDecoder::decode(&trailing).is_err());
+
+        // Inflated record count
+        let mut inflated = bytes.clone();
+        inflated[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            BatchTlvDecoder::decode(&inflated),
+            Err(EnvelopeError::UnexpectedEof(_))
+        ));
+    }
+}
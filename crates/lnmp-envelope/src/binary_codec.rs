@@ -16,11 +16,21 @@
 //! - `0x12`: TraceID (UTF-8 string)
 //! - `0x13`: Sequence (u64 big-endian)
 //! - `0x14`: Labels (reserved)
+//! - `0x15`: ContentType (UTF-8 media type)
+//! - `0x16`: SchemaVersion (UTF-8 string)
+//! - `0x17`: CorrelationID (UTF-8 string)
+//! - `0x18`: CausationID (UTF-8 string)
+//! - `0x19`: PartitionKey (UTF-8 string)
+//! - `0x1A`: ExpiresAt (u64 big-endian)
+//! - `0x1E`: Signature (`ALG_LEN (1 byte) | ALG | KEY_ID_LEN (1 byte) | KEY_ID | SIGNATURE`)
+//! - `0x1F`: Label (`KEY_LEN (1 byte) | KEY | VALUE`, UTF-8)
 //!
 //! ## Canonical Ordering
 //!
-//! TLV entries MUST appear in ascending type order for determinism.
+//! TLV entries MUST appear in ascending type order for determinism. Label is
+//! the only repeatable type: one entry per label, in ascending key order.
 
+use crate::signature::{decode_signature, encode_signature};
 use crate::{EnvelopeError, EnvelopeMetadata, Result};
 use std::io::{Read, Write};
 
@@ -36,6 +46,70 @@ pub mod tlv_type {
     pub const SEQUENCE: u8 = 0x13;
     /// Labels field (reserved for future use)
     pub const LABELS: u8 = 0x14;
+    /// Payload content type field (UTF-8 media type)
+    pub const CONTENT_TYPE: u8 = 0x15;
+    /// FID schema version field (UTF-8 string)
+    pub const SCHEMA_VERSION: u8 = 0x16;
+    /// Correlation ID field (UTF-8 string)
+    pub const CORRELATION_ID: u8 = 0x17;
+    /// Causation ID field (UTF-8 string)
+    pub const CAUSATION_ID: u8 = 0x18;
+    /// Partition key field (UTF-8 string)
+    pub const PARTITION_KEY: u8 = 0x19;
+    /// Expiry time field (u64 big-endian, Unix epoch milliseconds)
+    pub const EXPIRES_AT: u8 = 0x1A;
+    /// Detached signature (algorithm, key ID, signature bytes)
+    pub const SIGNATURE: u8 = 0x1E;
+    /// Single key-value label (repeatable, ascending key order)
+    pub const LABEL: u8 = 0x1F;
+}
+
+/// Encodes a label as the value of a [`tlv_type::LABEL`] entry
+pub(crate) fn encode_label(key: &str, value: &str) -> Result<Vec<u8>> {
+    crate::metadata::check_label_key(key)?;
+    let len = 1 + key.len() + value.len();
+    if len > u16::MAX as usize {
+        return Err(EnvelopeError::StringTooLong(
+            format!("labels.{}", key),
+            u16::MAX as usize,
+        ));
+    }
+    let mut buf = Vec::with_capacity(len);
+    buf.push(key.len() as u8);
+    buf.extend_from_slice(key.as_bytes());
+    buf.extend_from_slice(value.as_bytes());
+    Ok(buf)
+}
+
+/// Decodes the value of a [`tlv_type::LABEL`] entry
+pub(crate) fn decode_label(value: &[u8]) -> Result<(String, String)> {
+    let key_len = *value.first().ok_or(EnvelopeError::InvalidTlvLength(0))? as usize;
+    if value.len() < 1 + key_len {
+        return Err(EnvelopeError::InvalidTlvLength(value.len()));
+    }
+    let key = String::from_utf8(value[1..1 + key_len].to_vec())?;
+    let label = String::from_utf8(value[1 + key_len..].to_vec())?;
+    Ok((key, label))
+}
+
+/// Inserts a decoded label, enforcing ascending key order
+pub(crate) fn insert_label(
+    metadata: &mut EnvelopeMetadata,
+    key: String,
+    value: String,
+) -> Result<()> {
+    if metadata
+        .labels
+        .last_key_value()
+        .is_some_and(|(last, _)| *last >= key)
+    {
+        return Err(EnvelopeError::NonCanonicalOrder(
+            tlv_type::LABEL,
+            tlv_type::LABEL,
+        ));
+    }
+    metadata.labels.insert(key, value);
+    Ok(())
 }
 
 /// Binary TLV encoder for envelope metadata
@@ -49,6 +123,14 @@ impl TlvEncoder {
     /// 2. Source (0x11)
     /// 3. TraceID (0x12)
     /// 4. Sequence (0x13)
+    /// 5. ContentType (0x15)
+    /// 6. SchemaVersion (0x16)
+    /// 7. CorrelationID (0x17)
+    /// 8. CausationID (0x18)
+    /// 9. PartitionKey (0x19)
+    /// 10. ExpiresAt (0x1A)
+    /// 11. Signature (0x1E)
+    /// 12. Labels (0x1F), one entry per label in key order
     ///
     /// # Example
     ///
@@ -65,7 +147,9 @@ impl TlvEncoder {
     pub fn encode(metadata: &EnvelopeMetadata) -> Result<Vec<u8>> {
         let mut buf = Vec::new();
 
-        // Canonical order: timestamp, source, trace_id, sequence
+        // Canonical order: timestamp, source, trace_id, sequence, content_type,
+        // schema_version, correlation_id, causation_id, partition_key, expires_at,
+        // signature, labels
 
         if let Some(ts) = metadata.timestamp {
             Self::write_timestamp(&mut buf, ts)?;
@@ -83,7 +167,52 @@ impl TlvEncoder {
             Self::write_sequence(&mut buf, seq)?;
         }
 
-        // Labels reserved for future
+        if let Some(ref content_type) = metadata.content_type {
+            Self::write_string(
+                &mut buf,
+                tlv_type::CONTENT_TYPE,
+                "content_type",
+                content_type,
+            )?;
+        }
+
+        if let Some(ref version) = metadata.schema_version {
+            Self::write_string(
+                &mut buf,
+                tlv_type::SCHEMA_VERSION,
+                "schema_version",
+                version,
+            )?;
+        }
+
+        if let Some(ref id) = metadata.correlation_id {
+            Self::write_string(&mut buf, tlv_type::CORRELATION_ID, "correlation_id", id)?;
+        }
+
+        if let Some(ref id) = metadata.causation_id {
+            Self::write_string(&mut buf, tlv_type::CAUSATION_ID, "causation_id", id)?;
+        }
+
+        if let Some(ref key) = metadata.partition_key {
+            Self::write_string(&mut buf, tlv_type::PARTITION_KEY, "partition_key", key)?;
+        }
+
+        if let Some(exp) = metadata.expires_at {
+            buf.push(tlv_type::EXPIRES_AT);
+            buf.extend_from_slice(&8u16.to_be_bytes());
+            buf.extend_from_slice(&exp.to_be_bytes());
+        }
+
+        if let Some(ref sig) = metadata.signature {
+            let value = encode_signature(sig)?;
+            buf.push(tlv_type::SIGNATURE);
+            buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            buf.extend_from_slice(&value);
+        }
+
+        for (key, value) in &metadata.labels {
+            Self::write_label(&mut buf, &encode_label(key, value)?)?;
+        }
 
         Ok(buf)
     }
@@ -131,6 +260,28 @@ impl TlvEncoder {
         w.write_all(&seq.to_be_bytes())?;
         Ok(())
     }
+
+    fn write_string<W: Write>(w: &mut W, tlv_type: u8, field: &str, value: &str) -> Result<()> {
+        let bytes = value.as_bytes();
+        if bytes.len() > u16::MAX as usize {
+            return Err(EnvelopeError::StringTooLong(
+                field.to_string(),
+                u16::MAX as usize,
+            ));
+        }
+
+        w.write_all(&[tlv_type])?;
+        w.write_all(&(bytes.len() as u16).to_be_bytes())?;
+        w.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn write_label<W: Write>(w: &mut W, label: &[u8]) -> Result<()> {
+        w.write_all(&[tlv_type::LABEL])?;
+        w.write_all(&(label.len() as u16).to_be_bytes())?;
+        w.write_all(label)?;
+        Ok(())
+    }
 }
 
 /// Binary TLV decoder for envelope metadata
@@ -140,6 +291,7 @@ impl TlvDecoder {
     /// Decodes metadata from TLV binary format
     ///
     /// Unknown TRV types are skipped gracefully for forward compatibility.
+    /// Labels are kept whatever their key, so re-encoding forwards them.
     ///
     /// # Example
     ///
@@ -165,9 +317,9 @@ impl TlvDecoder {
             let tlv_type = Self::read_u8(&mut cursor)?;
             let length = Self::read_u16_be(&mut cursor)?;
 
-            // Check canonical ordering
+            // Check canonical ordering (labels repeat, ordered by key)
             if let Some(prev) = last_type {
-                if tlv_type <= prev {
+                if tlv_type < prev || (tlv_type == prev && tlv_type != tlv_type::LABEL) {
                     return Err(EnvelopeError::NonCanonicalOrder(tlv_type, prev));
                 }
             }
@@ -202,6 +354,39 @@ impl TlvDecoder {
                     }
                     metadata.sequence = Some(Self::read_sequence(&mut cursor, length)?);
                 }
+                tlv_type::CONTENT_TYPE => {
+                    metadata.content_type = Some(Self::read_string(&mut cursor, length)?);
+                }
+                tlv_type::SCHEMA_VERSION => {
+                    metadata.schema_version = Some(Self::read_string(&mut cursor, length)?);
+                }
+                tlv_type::CORRELATION_ID => {
+                    metadata.correlation_id = Some(Self::read_string(&mut cursor, length)?);
+                }
+                tlv_type::CAUSATION_ID => {
+                    metadata.causation_id = Some(Self::read_string(&mut cursor, length)?);
+                }
+                tlv_type::PARTITION_KEY => {
+                    metadata.partition_key = Some(Self::read_string(&mut cursor, length)?);
+                }
+                tlv_type::EXPIRES_AT => {
+                    metadata.expires_at = Some(Self::read_timestamp(&mut cursor, length)?);
+                }
+                tlv_type::SIGNATURE => {
+                    let mut value = vec![0u8; length as usize];
+                    cursor
+                        .read_exact(&mut value)
+                        .map_err(|_| EnvelopeError::UnexpectedEof(0))?;
+                    metadata.signature = Some(decode_signature(&value)?);
+                }
+                tlv_type::LABEL => {
+                    let mut value = vec![0u8; length as usize];
+                    cursor
+                        .read_exact(&mut value)
+                        .map_err(|_| EnvelopeError::UnexpectedEof(0))?;
+                    let (key, value) = decode_label(&value)?;
+                    insert_label(&mut metadata, key, value)?;
+                }
                 _ => {
                     // Unknown type - skip gracefully
                     Self::skip(&mut cursor, length as usize)?;
@@ -284,6 +469,12 @@ mod tests {
         metadata.source = Some("auth-service".to_string());
         metadata.trace_id = Some("abc-123-xyz".to_string());
         metadata.sequence = Some(42);
+        metadata.content_type = Some(crate::CONTENT_TYPE_SHORTFORM.to_string());
+        metadata.schema_version = Some("2.1.0".to_string());
+        metadata.correlation_id = Some("order-77".to_string());
+        metadata.causation_id = Some("cmd-5".to_string());
+        metadata.partition_key = Some("user-42".to_string());
+        metadata.expires_at = Some(1732373207000);
 
         let bytes = TlvEncoder::encode(&metadata).unwrap();
         let decoded = TlvDecoder::decode(&bytes).unwrap();
@@ -364,4 +555,57 @@ mod tests {
         assert_eq!(decoded.timestamp, Some(123));
         assert!(decoded.source.is_none());
     }
+
+    #[test]
+    fn test_encode_decode_labels() {
+        let mut metadata = EnvelopeMetadata::new();
+        metadata.sequence = Some(7);
+        metadata.set_label("tenant", "acme");
+        metadata.set_label("env", "prod");
+        metadata.set_label("x-vendor-hint", "");
+
+        let bytes = TlvEncoder::encode(&metadata).unwrap();
+        // Sequence first, then labels sorted by key
+        assert_eq!(bytes[0], tlv_type::SEQUENCE);
+        assert_eq!(
+            &bytes[11..18],
+            &[tlv_type::LABEL, 0, 8, 3, b'e', b'n', b'v']
+        );
+
+        let decoded = TlvDecoder::decode(&bytes).unwrap();
+        assert_eq!(decoded, metadata);
+        // Unknown labels survive a decode/encode hop unchanged
+        assert_eq!(TlvEncoder::encode(&decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_encode_rejects_invalid_label_key() {
+        let mut metadata = EnvelopeMetadata::new();
+        metadata.set_label("trace_id", "shadow");
+        assert!(matches!(
+            TlvEncoder::encode(&metadata),
+            Err(EnvelopeError::InvalidLabel(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_unordered_labels() {
+        let mut buf = Vec::new();
+        for label in [&b"\x01bB"[..], &b"\x01aA"[..]] {
+            buf.write_all(&[tlv_type::LABEL]).unwrap();
+            buf.write_all(&(label.len() as u16).to_be_bytes()).unwrap();
+            buf.write_all(label).unwrap();
+        }
+        assert!(matches!(
+            TlvDecoder::decode(&buf),
+            Err(EnvelopeError::NonCanonicalOrder(_, _))
+        ));
+
+        // Key length past the end of the entry
+        let buf = [tlv_type::LABEL, 0, 2, 5, b'k'];
+        assert!(matches!(
+            TlvDecoder::decode(&buf),
+            Err(EnvelopeError::InvalidTlvLength(_))
+        ));
+    }
 }
@@ -0,0 +1,941 @@
+//! CloudEvents v1.0 binding for LNMP envelopes
+//!
+//! Maps envelope metadata to CloudEvents context attributes and the record to
+//! the event `data`, encoded by a [`RecordCodec`] in the negotiated
+//! `datacontenttype`. This crate does not depend on the record codecs;
+//! `lnmp-transport` implements [`RecordCodec`] for its `SerializerConfig`.
+//!
+//! ## Attribute Mapping
+//!
+//! | CloudEvents       | LNMP Envelope                                  |
+//! |-------------------|------------------------------------------------|
+//! | `id`              | `trace_id`-`sequence` (data hash if neither)   |
+//! | `source`          | `source`                                       |
+//! | `type`            | [`CloudEventOptions::with_event_type`]         |
+//! | `time`            | `timestamp` (RFC 3339, UTC)                    |
+//! | `datacontenttype` | `content_type`                                 |
+//! | `traceparent`     | `trace_id`                                     |
+//! | `sequence`        | `sequence`                                     |
+//! | `partitionkey`    | `partition_key`                                |
+//! | `correlationid`   | `correlation_id`                               |
+//! | `causationid`     | `causation_id`                                 |
+//! | `schemaversion`   | `schema_version`                               |
+//! | `expiresat`       | `expires_at` (Unix milliseconds)               |
+//! | other extensions  | `labels`                                       |
+//!
+//! Both content modes are supported: [`CloudEvent::to_binary_mode`] yields
+//! `ce-*` headers plus the raw data as body, [`CloudEvent::to_structured`]
+//! yields an `application/cloudevents+json` document.
+//!
+//! ```
+//! use lnmp_envelope::cloudevents::{
+//!     from_cloudevent, to_cloudevent, CloudEvent, CloudEventOptions, RecordCodec,
+//! };
+//! use lnmp_envelope::{EnvelopeBuilder, LnmpField, LnmpRecord, LnmpValue, Result};
+//!
+//! // Stand-in for a real LNMP codec
+//! struct IntTextCodec;
+//!
+//! impl RecordCodec for IntTextCodec {
+//!     fn content_types(&self) -> &[&'static str] {
+//!         &["application/lnmp-text"]
+//!     }
+//!
+//!     fn encode(&self, record: &LnmpRecord, _content_type: &str) -> Result<Vec<u8>> {
+//!         let field = &record.fields()[0];
+//!         Ok(format!("F{}={:?}", field.fid, field.value).into_bytes())
+//!     }
+//!
+//!     fn decode(&self, _data: &[u8], _content_type: &str) -> Result<LnmpRecord> {
+//!         let mut record = LnmpRecord::new();
+//!         record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(14532) });
+//!         Ok(record)
+//!     }
+//! }
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(14532) });
+//! let envelope = EnvelopeBuilder::new(record)
+//!     .timestamp(1732373147000)
+//!     .source("auth-service")
+//!     .trace_id("abc-123")
+//!     .build();
+//!
+//! let options = CloudEventOptions::new().with_accept(["application/lnmp-text"]);
+//! let event = to_cloudevent(&envelope, &IntTextCodec, &options).unwrap();
+//! assert_eq!(event.time.as_deref(), Some("2024-11-23T14:45:47.000Z"));
+//!
+//! let json = event.to_structured().unwrap();
+//! let received = CloudEvent::from_structured(&json).unwrap();
+//! let decoded = from_cloudevent(&received, &IntTextCodec).unwrap();
+//! assert_eq!(decoded.record, envelope.record);
+//! assert_eq!(decoded.metadata.trace_id.as_deref(), Some("abc-123"));
+//! ```
+//!
+//! Requires the `cloudevents` feature.
+
+use crate::{EnvelopeError, EnvelopeMetadata, LnmpEnvelope, Result, CONTENT_TYPE_BINARY};
+use lnmp_core::LnmpRecord;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// CloudEvents specification version produced and accepted
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Media type of structured-mode events
+pub const STRUCTURED_CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// Default `type` attribute of events built from envelopes
+pub const DEFAULT_EVENT_TYPE: &str = "org.lnmp.record";
+
+/// Default `source` attribute for envelopes without a source
+pub const DEFAULT_SOURCE: &str = "urn:lnmp";
+
+/// Prefix of binary-mode headers carrying context attributes
+pub const HEADER_PREFIX: &str = "ce-";
+
+/// Attributes that are not copied into labels on decode
+const KNOWN_ATTRIBUTES: [&str; 16] = [
+    "specversion",
+    "id",
+    "source",
+    "type",
+    "time",
+    "datacontenttype",
+    "dataschema",
+    "subject",
+    "traceparent",
+    "sequence",
+    "partitionkey",
+    "correlationid",
+    "causationid",
+    "schemaversion",
+    "expiresat",
+    "data",
+];
+
+/// Encodes records as CloudEvent data and back
+pub trait RecordCodec {
+    /// Content types this codec produces, most preferred first
+    fn content_types(&self) -> &[&'static str];
+
+    /// Encodes `record` as `content_type`
+    fn encode(&self, record: &LnmpRecord, content_type: &str) -> Result<Vec<u8>>;
+
+    /// Decodes data received with `content_type`
+    fn decode(&self, data: &[u8], content_type: &str) -> Result<LnmpRecord>;
+}
+
+/// Payload of a CloudEvent
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloudEventData {
+    /// Raw bytes (`data_base64` in structured mode)
+    Binary(Vec<u8>),
+    /// UTF-8 text (`data` string in structured mode)
+    Text(String),
+}
+
+impl CloudEventData {
+    /// Returns the payload bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            CloudEventData::Binary(bytes) => bytes,
+            CloudEventData::Text(text) => text.as_bytes(),
+        }
+    }
+}
+
+/// A CloudEvents v1.0 event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudEvent {
+    /// `id` attribute
+    pub id: String,
+    /// `source` attribute (URI-reference)
+    pub source: String,
+    /// `specversion` attribute
+    pub specversion: String,
+    /// `type` attribute
+    pub event_type: String,
+    /// `time` attribute (RFC 3339)
+    pub time: Option<String>,
+    /// `datacontenttype` attribute
+    pub datacontenttype: Option<String>,
+    /// `dataschema` attribute
+    pub dataschema: Option<String>,
+    /// `subject` attribute
+    pub subject: Option<String>,
+    /// Extension attributes, by lowercase name
+    pub extensions: BTreeMap<String, String>,
+    /// Event payload
+    pub data: Option<CloudEventData>,
+}
+
+/// Options for [`to_cloudevent`]
+#[derive(Debug, Clone)]
+pub struct CloudEventOptions {
+    /// `type` attribute of produced events
+    pub event_type: String,
+    /// `source` used when the envelope has none
+    pub default_source: String,
+    /// Media types the receiver accepts, most preferred first (empty = any)
+    pub accept: Vec<String>,
+}
+
+impl Default for CloudEventOptions {
+    fn default() -> Self {
+        Self {
+            event_type: DEFAULT_EVENT_TYPE.to_string(),
+            default_source: DEFAULT_SOURCE.to_string(),
+            accept: Vec::new(),
+        }
+    }
+}
+
+impl CloudEventOptions {
+    /// Creates options with the default event type and source
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `type` attribute of produced events
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = event_type.into();
+        self
+    }
+
+    /// Sets the `source` used when the envelope has none
+    pub fn with_default_source(mut self, source: impl Into<String>) -> Self {
+        self.default_source = source.into();
+        self
+    }
+
+    /// Sets the media types the receiver accepts, most preferred first
+    ///
+    /// Entries may carry parameters (`;q=0.5`, ignored) and wildcards
+    /// (`*/*`, `application/*`).
+    pub fn with_accept<I, S>(mut self, accept: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.accept = accept.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// Picks the data content type for a record
+///
+/// Returns the first entry of `accept` that names one of `supported`;
+/// wildcards resolve to `preferred` when it is supported, otherwise to the
+/// first supported type. An empty `accept` means any type. Returns `None`
+/// when nothing in `accept` is supported.
+pub fn negotiate_content_type(
+    accept: &[String],
+    supported: &[&'static str],
+    preferred: Option<&str>,
+) -> Option<&'static str> {
+    let preferred = preferred
+        .and_then(|p| supported.iter().find(|s| **s == p))
+        .or_else(|| supported.first())
+        .copied();
+    if accept.is_empty() {
+        return preferred;
+    }
+    accept.iter().find_map(|entry| {
+        let media = entry.split(';').next().unwrap_or("").trim();
+        match media {
+            "*/*" | "application/*" => preferred,
+            _ => supported
+                .iter()
+                .find(|s| s.eq_ignore_ascii_case(media))
+                .copied(),
+        }
+    })
+}
+
+/// Converts an envelope to a CloudEvent
+///
+/// The record is encoded by `codec` in the content type negotiated from
+/// `options` and the envelope's own `content_type`.
+pub fn to_cloudevent(
+    env: &LnmpEnvelope,
+    codec: &impl RecordCodec,
+    options: &CloudEventOptions,
+) -> Result<CloudEvent> {
+    let meta = &env.metadata;
+    let content_type = negotiate_content_type(
+        &options.accept,
+        codec.content_types(),
+        meta.content_type.as_deref(),
+    )
+    .ok_or_else(|| {
+        EnvelopeError::CloudEvent(format!(
+            "no supported data content type in {:?}",
+            options.accept
+        ))
+    })?;
+    let bytes = codec.encode(&env.record, content_type)?;
+
+    let id = match (&meta.trace_id, meta.sequence) {
+        (Some(trace_id), Some(seq)) => format!("{}-{}", trace_id, seq),
+        (Some(trace_id), None) => trace_id.clone(),
+        (None, Some(seq)) => seq.to_string(),
+        (None, None) => data_id(&bytes),
+    };
+
+    let mut extensions = BTreeMap::new();
+    for (key, value) in &meta.labels {
+        if !is_attribute_name(key) || KNOWN_ATTRIBUTES.contains(&key.as_str()) {
+            return Err(EnvelopeError::CloudEvent(format!(
+                "label {:?} is not a valid extension attribute name",
+                key
+            )));
+        }
+        extensions.insert(key.clone(), value.clone());
+    }
+    let mapped = [
+        ("traceparent", meta.trace_id.clone()),
+        ("sequence", meta.sequence.map(|s| s.to_string())),
+        ("partitionkey", meta.partition_key.clone()),
+        ("correlationid", meta.correlation_id.clone()),
+        ("causationid", meta.causation_id.clone()),
+        ("schemaversion", meta.schema_version.clone()),
+        ("expiresat", meta.expires_at.map(|t| t.to_string())),
+    ];
+    for (name, value) in mapped {
+        if let Some(value) = value {
+            extensions.insert(name.to_string(), value);
+        }
+    }
+
+    Ok(CloudEvent {
+        id,
+        source: meta
+            .source
+            .clone()
+            .unwrap_or_else(|| options.default_source.clone()),
+        specversion: SPEC_VERSION.to_string(),
+        event_type: options.event_type.clone(),
+        time: meta.timestamp.map(format_rfc3339),
+        datacontenttype: Some(content_type.to_string()),
+        dataschema: None,
+        subject: None,
+        extensions,
+        data: Some(into_data(bytes, content_type)),
+    })
+}
+
+/// Converts a CloudEvent back to an envelope
+///
+/// The data is decoded by `codec` according to `datacontenttype`; extensions
+/// without an envelope field become labels.
+pub fn from_cloudevent(event: &CloudEvent, codec: &impl RecordCodec) -> Result<LnmpEnvelope> {
+    if event.specversion != SPEC_VERSION {
+        return Err(EnvelopeError::CloudEvent(format!(
+            "unsupported specversion {}",
+            event.specversion
+        )));
+    }
+
+    let record = match &event.data {
+        None => LnmpRecord::new(),
+        Some(data) => {
+            let content_type = event
+                .datacontenttype
+                .as_deref()
+                .unwrap_or(CONTENT_TYPE_BINARY);
+            codec.decode(data.as_bytes(), content_type)?
+        }
+    };
+
+    let mut metadata = EnvelopeMetadata {
+        source: Some(event.source.clone()),
+        content_type: event.datacontenttype.clone(),
+        ..EnvelopeMetadata::new()
+    };
+    if let Some(time) = &event.time {
+        metadata.timestamp = Some(parse_rfc3339(time).ok_or_else(|| {
+            EnvelopeError::CloudEvent(format!("invalid time attribute {:?}", time))
+        })?);
+    }
+    for (name, value) in &event.extensions {
+        match name.as_str() {
+            "traceparent" => metadata.trace_id = Some(value.clone()),
+            "sequence" => {
+                metadata.sequence = Some(value.parse().map_err(|_| {
+                    EnvelopeError::CloudEvent(format!("invalid sequence attribute {:?}", value))
+                })?)
+            }
+            "partitionkey" => metadata.partition_key = Some(value.clone()),
+            "correlationid" => metadata.correlation_id = Some(value.clone()),
+            "causationid" => metadata.causation_id = Some(value.clone()),
+            "schemaversion" => metadata.schema_version = Some(value.clone()),
+            "expiresat" => {
+                metadata.expires_at = Some(value.parse().map_err(|_| {
+                    EnvelopeError::CloudEvent(format!("invalid expiresat attribute {:?}", value))
+                })?)
+            }
+            _ => {
+                metadata.labels.insert(name.clone(), value.clone());
+            }
+        }
+    }
+
+    Ok(LnmpEnvelope { record, metadata })
+}
+
+impl CloudEvent {
+    /// Encodes the event in binary content mode
+    ///
+    /// Returns the `ce-*` headers (plus `content-type` when set) and the body.
+    pub fn to_binary_mode(&self) -> (Vec<(String, String)>, Vec<u8>) {
+        let mut headers = Vec::with_capacity(6 + self.extensions.len());
+        for (name, value) in self.attributes() {
+            if name == "datacontenttype" {
+                headers.push(("content-type".to_string(), value.to_string()));
+            } else {
+                headers.push((format!("{}{}", HEADER_PREFIX, name), value.to_string()));
+            }
+        }
+        let body = self
+            .data
+            .as_ref()
+            .map(|data| data.as_bytes().to_vec())
+            .unwrap_or_default();
+        (headers, body)
+    }
+
+    /// Decodes an event received in binary content mode
+    ///
+    /// Header names are matched case-insensitively; headers that are neither
+    /// `ce-*` nor `content-type` are ignored. A body is kept as text when the
+    /// content type is textual and the body is UTF-8, as bytes otherwise.
+    pub fn from_binary_mode<'a, I>(headers: I, body: &[u8]) -> Result<Self>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut attributes = BTreeMap::new();
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            if name == "content-type" {
+                attributes.insert("datacontenttype".to_string(), value.to_string());
+            } else if let Some(attribute) = name.strip_prefix(HEADER_PREFIX) {
+                attributes.insert(attribute.to_string(), value.to_string());
+            }
+        }
+        let data = if body.is_empty() {
+            None
+        } else {
+            let content_type = attributes
+                .get("datacontenttype")
+                .map_or(CONTENT_TYPE_BINARY, String::as_str);
+            Some(into_data(body.to_vec(), content_type))
+        };
+        Self::from_attributes(attributes, data)
+    }
+
+    /// Encodes the event in structured content mode (JSON)
+    pub fn to_structured(&self) -> Result<Vec<u8>> {
+        let mut object = Map::new();
+        for (name, value) in self.attributes() {
+            object.insert(name.to_string(), Value::String(value.to_string()));
+        }
+        match &self.data {
+            Some(CloudEventData::Text(text)) => {
+                object.insert("data".to_string(), Value::String(text.clone()));
+            }
+            Some(CloudEventData::Binary(bytes)) => {
+                object.insert(
+                    "data_base64".to_string(),
+                    Value::String(base64_encode(bytes)),
+                );
+            }
+            None => {}
+        }
+        serde_json::to_vec(&Value::Object(object))
+            .map_err(|e| EnvelopeError::CloudEvent(e.to_string()))
+    }
+
+    /// Decodes an event received in structured content mode (JSON)
+    pub fn from_structured(json: &[u8]) -> Result<Self> {
+        let object = match serde_json::from_slice(json) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => {
+                return Err(EnvelopeError::CloudEvent(
+                    "structured event is not a JSON object".to_string(),
+                ))
+            }
+            Err(e) => return Err(EnvelopeError::CloudEvent(e.to_string())),
+        };
+
+        let mut attributes = BTreeMap::new();
+        let mut data = None;
+        for (name, value) in object {
+            match (name.as_str(), value) {
+                ("data", Value::String(text)) => data = Some(CloudEventData::Text(text)),
+                ("data", other) => data = Some(CloudEventData::Text(other.to_string())),
+                ("data_base64", Value::String(encoded)) => {
+                    data = Some(CloudEventData::Binary(base64_decode(&encoded).ok_or_else(
+                        || EnvelopeError::CloudEvent("invalid data_base64".to_string()),
+                    )?))
+                }
+                (_, Value::String(value)) => {
+                    attributes.insert(name, value);
+                }
+                (_, Value::Null) => {}
+                (_, other) => {
+                    attributes.insert(name, other.to_string());
+                }
+            }
+        }
+        Self::from_attributes(attributes, data)
+    }
+
+    /// Context attributes in canonical order, required ones first
+    fn attributes(&self) -> Vec<(&str, &str)> {
+        let mut attributes = vec![
+            ("specversion", self.specversion.as_str()),
+            ("id", self.id.as_str()),
+            ("source", self.source.as_str()),
+            ("type", self.event_type.as_str()),
+        ];
+        let optional = [
+            ("time", &self.time),
+            ("datacontenttype", &self.datacontenttype),
+            ("dataschema", &self.dataschema),
+            ("subject", &self.subject),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                attributes.push((name, value.as_str()));
+            }
+        }
+        for (name, value) in &self.extensions {
+            attributes.push((name.as_str(), value.as_str()));
+        }
+        attributes
+    }
+
+    fn from_attributes(
+        mut attributes: BTreeMap<String, String>,
+        data: Option<CloudEventData>,
+    ) -> Result<Self> {
+        let mut required = |name: &str| {
+            attributes
+                .remove(name)
+                .ok_or_else(|| EnvelopeError::CloudEvent(format!("missing {} attribute", name)))
+        };
+        let specversion = required("specversion")?;
+        let id = required("id")?;
+        let source = required("source")?;
+        let event_type = required("type")?;
+        Ok(Self {
+            id,
+            source,
+            specversion,
+            event_type,
+            time: attributes.remove("time"),
+            datacontenttype: attributes.remove("datacontenttype"),
+            dataschema: attributes.remove("dataschema"),
+            subject: attributes.remove("subject"),
+            extensions: attributes,
+            data,
+        })
+    }
+}
+
+/// Event ID derived from the encoded data (64-bit FNV-1a, hex)
+fn data_id(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Wraps encoded data as text when the content type is textual and the bytes
+/// are UTF-8
+fn into_data(bytes: Vec<u8>, content_type: &str) -> CloudEventData {
+    if !is_text_content_type(content_type) {
+        return CloudEventData::Binary(bytes);
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => CloudEventData::Text(text),
+        Err(err) => CloudEventData::Binary(err.into_bytes()),
+    }
+}
+
+/// Whether data of `content_type` is carried as text rather than bytes
+fn is_text_content_type(content_type: &str) -> bool {
+    let media = content_type.split(';').next().unwrap_or("").trim();
+    media != CONTENT_TYPE_BINARY && media != "application/octet-stream"
+}
+
+/// CloudEvents attribute names: lowercase ASCII letters and digits
+fn is_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+}
+
+/// Formats Unix epoch milliseconds as `YYYY-MM-DDTHH:MM:SS.mmmZ`
+fn format_rfc3339(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days (Howard Hinnant), valid for all u64 millisecond inputs
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+/// Parses an RFC 3339 timestamp to Unix epoch milliseconds
+///
+/// Accepts `Z` or `±HH:MM` offsets and any number of fraction digits
+/// (truncated to milliseconds). Times before 1970 are rejected.
+fn parse_rfc3339(s: &str) -> Option<u64> {
+    let b = s.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = s.get(range)?;
+        if digits.bytes().all(|c| c.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if b[13] != b':' || b[16] != b':' || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut pos = 19;
+    let mut millis = 0i64;
+    if b.get(pos) == Some(&b'.') {
+        pos += 1;
+        let start = pos;
+        while pos < b.len() && b[pos].is_ascii_digit() {
+            if pos - start < 3 {
+                millis = millis * 10 + i64::from(b[pos] - b'0');
+            }
+            pos += 1;
+        }
+        if pos == start {
+            return None;
+        }
+        for _ in (pos - start)..3 {
+            millis *= 10;
+        }
+    }
+    let offset_minutes = match &s[pos..] {
+        "Z" | "z" => 0,
+        tz if tz.len() == 6
+            && matches!(tz.as_bytes()[0], b'+' | b'-')
+            && tz.as_bytes()[3] == b':' =>
+        {
+            let minutes = num(pos + 1..pos + 3)? * 60 + num(pos + 4..pos + 6)?;
+            if tz.starts_with('-') {
+                -minutes
+            } else {
+                minutes
+            }
+        }
+        _ => return None,
+    };
+
+    // Days-from-civil (Howard Hinnant)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_minutes * 60;
+    u64::try_from(secs * 1000 + millis).ok()
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = BASE64_ALPHABET.iter().position(|a| *a == c)? as u32;
+        n = n << 6 | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnvelopeBuilder, CONTENT_TYPE_TEXT};
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    /// Integer-only stand-in for the LNMP codecs
+    struct IntCodec;
+
+    impl RecordCodec for IntCodec {
+        fn content_types(&self) -> &[&'static str] {
+            &[CONTENT_TYPE_BINARY, CONTENT_TYPE_TEXT]
+        }
+
+        fn encode(&self, record: &LnmpRecord, content_type: &str) -> Result<Vec<u8>> {
+            let fields = record.fields().iter().map(|f| match f.value {
+                LnmpValue::Int(v) => (f.fid, v),
+                _ => unreachable!(),
+            });
+            Ok(if content_type == CONTENT_TYPE_TEXT {
+                let lines: Vec<_> = fields.map(|(fid, v)| format!("F{}={}", fid, v)).collect();
+                lines.join("\n").into_bytes()
+            } else {
+                fields
+                    .flat_map(|(fid, v)| [&fid.to_be_bytes()[..], &v.to_be_bytes()].concat())
+                    .collect()
+            })
+        }
+
+        fn decode(&self, data: &[u8], content_type: &str) -> Result<LnmpRecord> {
+            let mut record = LnmpRecord::new();
+            if content_type == CONTENT_TYPE_TEXT {
+                for line in std::str::from_utf8(data).unwrap().lines() {
+                    let (fid, v) = line[1..].split_once('=').unwrap();
+                    record.add_field(LnmpField {
+                        fid: fid.parse().unwrap(),
+                        value: LnmpValue::Int(v.parse().unwrap()),
+                    });
+                }
+            } else {
+                for chunk in data.chunks(10) {
+                    record.add_field(LnmpField {
+                        fid: u16::from_be_bytes([chunk[0], chunk[1]]),
+                        value: LnmpValue::Int(i64::from_be_bytes(chunk[2..].try_into().unwrap())),
+                    });
+                }
+            }
+            Ok(record)
+        }
+    }
+
+    fn envelope() -> LnmpEnvelope {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Int(1),
+        });
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        EnvelopeBuilder::new(record)
+            .timestamp(1_732_373_147_123)
+            .source("auth-service")
+            .trace_id("abc-123")
+            .sequence(42)
+            .correlation_id("order-77")
+            .partition_key("user-42")
+            .schema_version("1.0.0")
+            .expires_at(1_732_373_207_123)
+            .label("tenant", "acme")
+            .build()
+    }
+
+    #[test]
+    fn test_attribute_mapping() {
+        let event = to_cloudevent(&envelope(), &IntCodec, &CloudEventOptions::new()).unwrap();
+        assert_eq!(event.id, "abc-123-42");
+        assert_eq!(event.source, "auth-service");
+        assert_eq!(event.event_type, DEFAULT_EVENT_TYPE);
+        assert_eq!(event.time.as_deref(), Some("2024-11-23T14:45:47.123Z"));
+        assert_eq!(event.datacontenttype.as_deref(), Some(CONTENT_TYPE_BINARY));
+        assert_eq!(event.extensions["sequence"], "42");
+        assert_eq!(event.extensions["partitionkey"], "user-42");
+        assert_eq!(event.extensions["expiresat"], "1732373207123");
+        assert_eq!(event.extensions["tenant"], "acme");
+        assert!(matches!(event.data, Some(CloudEventData::Binary(_))));
+    }
+
+    #[test]
+    fn test_binary_mode_round_trip() {
+        let env = envelope();
+        let event = to_cloudevent(&env, &IntCodec, &CloudEventOptions::new()).unwrap();
+        let (headers, body) = event.to_binary_mode();
+        assert!(headers.contains(&("ce-id".to_string(), "abc-123-42".to_string())));
+        assert!(headers.contains(&("content-type".to_string(), CONTENT_TYPE_BINARY.to_string())));
+
+        let received = CloudEvent::from_binary_mode(
+            headers.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            &body,
+        )
+        .unwrap();
+        assert_eq!(received, event);
+
+        let decoded = from_cloudevent(&received, &IntCodec).unwrap();
+        let mut expected = env.metadata.clone();
+        expected.content_type = Some(CONTENT_TYPE_BINARY.to_string());
+        assert_eq!(decoded.metadata, expected);
+        assert_eq!(decoded.record, env.record);
+    }
+
+    #[test]
+    fn test_structured_mode_round_trip() {
+        let env = envelope();
+        let options = CloudEventOptions::new().with_accept(["text/html", "application/lnmp-text"]);
+        let event = to_cloudevent(&env, &IntCodec, &options).unwrap();
+        assert_eq!(
+            event.data,
+            Some(CloudEventData::Text("F7=1\nF12=14532".into()))
+        );
+
+        let json: Value = serde_json::from_slice(&event.to_structured().unwrap()).unwrap();
+        assert_eq!(json["specversion"], "1.0");
+        assert_eq!(json["data"], "F7=1\nF12=14532");
+        let received = CloudEvent::from_structured(&event.to_structured().unwrap()).unwrap();
+        assert_eq!(
+            from_cloudevent(&received, &IntCodec).unwrap().record,
+            env.record
+        );
+
+        let binary = to_cloudevent(&env, &IntCodec, &CloudEventOptions::new()).unwrap();
+        let bytes = binary.to_structured().unwrap();
+        assert!(serde_json::from_slice::<Value>(&bytes).unwrap()["data_base64"].is_string());
+        assert_eq!(CloudEvent::from_structured(&bytes).unwrap(), binary);
+    }
+
+    #[test]
+    fn test_content_type_negotiation() {
+        let supported = IntCodec.content_types();
+        let accept = |types: &[&str]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            negotiate_content_type(&[], supported, None),
+            Some(CONTENT_TYPE_BINARY)
+        );
+        assert_eq!(
+            negotiate_content_type(&[], supported, Some(CONTENT_TYPE_TEXT)),
+            Some(CONTENT_TYPE_TEXT)
+        );
+        assert_eq!(
+            negotiate_content_type(&accept(&["application/lnmp-text;q=0.9"]), supported, None),
+            Some(CONTENT_TYPE_TEXT)
+        );
+        assert_eq!(
+            negotiate_content_type(&accept(&["*/*"]), supported, Some(CONTENT_TYPE_TEXT)),
+            Some(CONTENT_TYPE_TEXT)
+        );
+        assert_eq!(
+            negotiate_content_type(&accept(&["application/json"]), supported, None),
+            None
+        );
+
+        let options = CloudEventOptions::new().with_accept(["application/json"]);
+        assert!(matches!(
+            to_cloudevent(&envelope(), &IntCodec, &options),
+            Err(EnvelopeError::CloudEvent(_))
+        ));
+    }
+
+    #[test]
+    fn test_id_falls_back_to_data_hash() {
+        let mut env = envelope();
+        env.metadata.trace_id = None;
+        env.metadata.sequence = None;
+        let options = CloudEventOptions::new();
+        let first = to_cloudevent(&env, &IntCodec, &options).unwrap();
+        assert_eq!(first.id.len(), 16);
+        assert_eq!(
+            to_cloudevent(&env, &IntCodec, &options).unwrap().id,
+            first.id
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_events() {
+        let options = CloudEventOptions::new();
+        let mut env = envelope();
+        env.metadata.set_label("x-vendor", "v");
+        assert!(to_cloudevent(&env, &IntCodec, &options).is_err());
+
+        assert!(CloudEvent::from_structured(br#"{"specversion":"1.0","id":"1"}"#).is_err());
+        let mut event = to_cloudevent(&envelope(), &IntCodec, &options).unwrap();
+        event.time = Some("yesterday".to_string());
+        assert!(from_cloudevent(&event, &IntCodec).is_err());
+        event.specversion = "0.3".to_string();
+        assert!(from_cloudevent(&event, &IntCodec).is_err());
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        for ms in [0, 951_782_400_000, 1_732_373_147_123, 4_102_444_799_999] {
+            assert_eq!(parse_rfc3339(&format_rfc3339(ms)), Some(ms));
+        }
+        assert_eq!(format_rfc3339(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(
+            parse_rfc3339("2024-11-23T16:45:47.1234+02:00"),
+            Some(1_732_373_147_123)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-11-23T14:45:47Z"),
+            Some(1_732_373_147_000)
+        );
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_rfc3339("2024-13-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_base64() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\x00\xfe"] {
+            assert_eq!(base64_decode(&base64_encode(input)).unwrap(), input);
+        }
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+        assert_eq!(base64_decode("Zm9v!"), None);
+    }
+}
@@ -114,12 +114,48 @@ impl EnvelopeBuilder {
         self
     }
 
+    /// Sets the correlation ID shared by a request/response or saga
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.metadata.correlation_id = Some(id.into());
+        self
+    }
+
+    /// Sets the ID of the message that caused this one
+    pub fn causation_id(mut self, id: impl Into<String>) -> Self {
+        self.metadata.causation_id = Some(id.into());
+        self
+    }
+
+    /// Sets the absolute expiry time (Unix epoch milliseconds, UTC)
+    pub fn expires_at(mut self, ts: u64) -> Self {
+        self.metadata.expires_at = Some(ts);
+        self
+    }
+
+    /// Sets the partition key (e.g. the entity ID the record belongs to)
+    pub fn partition_key(mut self, key: impl Into<String>) -> Self {
+        self.metadata.partition_key = Some(key.into());
+        self
+    }
+
     /// Sets the sequence number
     pub fn sequence(mut self, seq: u64) -> Self {
         self.metadata.sequence = Some(seq);
         self
     }
 
+    /// Sets the payload content type (see the `CONTENT_TYPE_*` constants)
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.metadata.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the FID schema version of the payload
+    pub fn schema_version(mut self, version: impl Into<String>) -> Self {
+        self.metadata.schema_version = Some(version.into());
+        self
+    }
+
     /// Adds a label (key-value pair)
     pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.labels.insert(key.into(), value.into());
@@ -168,17 +204,35 @@ mod tests {
     fn test_builder_all_fields() {
         let envelope = EnvelopeBuilder::new(sample_record())
             .timestamp(1732373147000)
+            .expires_at(1732373207000)
             .source("auth-service")
             .trace_id("abc-123-xyz")
+            .correlation_id("order-77")
+            .causation_id("cmd-5")
+            .partition_key("user-42")
             .sequence(42)
+            .content_type(crate::CONTENT_TYPE_BINARY)
+            .schema_version("1.0.0")
             .label("tenant", "acme")
             .label("env", "prod")
             .build();
 
         assert_eq!(envelope.metadata.timestamp, Some(1732373147000));
+        assert_eq!(envelope.metadata.expires_at, Some(1732373207000));
         assert_eq!(envelope.metadata.source, Some("auth-service".to_string()));
         assert_eq!(envelope.metadata.trace_id, Some("abc-123-xyz".to_string()));
         assert_eq!(envelope.metadata.sequence, Some(42));
+        assert_eq!(
+            envelope.metadata.content_type.as_deref(),
+            Some("application/lnmp-binary")
+        );
+        assert_eq!(envelope.metadata.schema_version.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            envelope.metadata.correlation_id.as_deref(),
+            Some("order-77")
+        );
+        assert_eq!(envelope.metadata.causation_id.as_deref(), Some("cmd-5"));
+        assert_eq!(envelope.metadata.partition_key.as_deref(), Some("user-42"));
         assert_eq!(envelope.metadata.labels.len(), 2);
     }
 
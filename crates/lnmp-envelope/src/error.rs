@@ -45,6 +45,22 @@ pub enum EnvelopeError {
     #[error("TLV entries not in canonical order: {0:#x} after {1:#x}")]
     NonCanonicalOrder(u8, u8),
 
+    /// Label key that cannot be encoded
+    #[error("Invalid label key: {0:?}")]
+    InvalidLabel(String),
+
+    /// Envelope carries no signature
+    #[error("Envelope is not signed")]
+    MissingSignature,
+
+    /// Signature rejected by the verifier
+    #[error("Invalid signature for key {0:?}")]
+    InvalidSignature(String),
+
+    /// CloudEvents mapping error
+    #[error("CloudEvents error: {0}")]
+    CloudEvent(String),
+
     /// IO error
     #[error("IO error: {0}")]
     Io(String),
@@ -6,7 +6,8 @@
 //! Operational metadata envelope for LNMP records.
 //!
 //! This crate provides a way to attach operational context (timestamp, source,
-//! trace ID, sequence) to LNMP records without affecting their deterministic
+//! trace ID, correlation/causation IDs, partition key, sequence, content type,
+//! schema version) to LNMP records without affecting their deterministic
 //! properties or semantic checksums.
 //!
 //! ## Alignment with Industry Standards
@@ -57,12 +58,20 @@
 //! Type: 0x11 (Source)    | Length: N | Value: UTF-8 string
 //! Type: 0x12 (TraceID)   | Length: M | Value: UTF-8 string
 //! Type: 0x13 (Sequence)  | Length: 8 | Value: u64 BE
+//! Type: 0x15 (ContentType)   | Length: N | Value: UTF-8 media type
+//! Type: 0x16 (SchemaVersion) | Length: N | Value: UTF-8 string
+//! Type: 0x17 (CorrelationID) | Length: N | Value: UTF-8 string
+//! Type: 0x18 (CausationID)   | Length: N | Value: UTF-8 string
+//! Type: 0x19 (PartitionKey)  | Length: N | Value: UTF-8 string
+//! Type: 0x1A (ExpiresAt)     | Length: 8 | Value: u64 big-endian
+//! Type: 0x1E (Signature)     | Length: N | Value: algorithm + key ID + signature
+//! Type: 0x1F (Label)     | Length: K | Value: key length (u8) + key + value
 //! ```
 //!
 //! ### Text (Header Comment)
 //!
 //! ```text
-//! #ENVELOPE timestamp=1732373147000 source=auth-service trace_id="abc-123"
+//! #ENVELOPE timestamp=1732373147000 source=auth-service trace_id="abc-123" tenant=acme
 //! F12=14532
 //! F7=1
 //! ```
@@ -88,19 +97,33 @@
 //!   lnmp.sequence: "42"
 //! ```
 //!
+//! ### CloudEvents
+//!
+//! The [`cloudevents`] module maps envelopes to CloudEvents v1.0 events in
+//! binary or structured content mode.
+//!
 //! ## Features
 //!
 //! - `serde`: Enable serde serialization support (optional)
+//! - `cloudevents`: CloudEvents v1.0 binding (optional)
 
+pub mod batch_codec;
 pub mod binary_codec;
+#[cfg(feature = "cloudevents")]
+pub mod cloudevents;
 mod envelope;
 mod error;
 mod metadata;
+pub mod signature;
 pub mod text_codec;
 
 pub use envelope::{EnvelopeBuilder, LnmpEnvelope};
 pub use error::{EnvelopeError, Result};
-pub use metadata::EnvelopeMetadata;
+pub use metadata::{
+    EnvelopeMetadata, CONTENT_TYPE_BINARY, CONTENT_TYPE_DELTA, CONTENT_TYPE_EXPLAIN,
+    CONTENT_TYPE_SHORTFORM, CONTENT_TYPE_TEXT, MAX_LABEL_KEY_LEN, RESERVED_LABEL_KEYS,
+};
+pub use signature::EnvelopeSignature;
 
 // Re-export for convenience
 pub use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
@@ -1,6 +1,7 @@
 //! Operational metadata for LNMP records
 
-use std::collections::HashMap;
+use crate::signature::EnvelopeSignature;
+use std::collections::BTreeMap;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -10,9 +11,13 @@ use serde::{Deserialize, Serialize};
 /// All fields are optional to provide flexibility. Applications should
 /// set fields based on their requirements:
 /// - Timestamp: For temporal reasoning and freshness
+/// - ExpiresAt: For dropping stale records anywhere along the path
 /// - Source: For routing, multi-tenant, and trust scoring
 /// - TraceID: For distributed tracing integration
+/// - CorrelationID/CausationID: For request/response and saga workflows
+/// - PartitionKey: For keeping one entity's records on one partition
 /// - Sequence: For conflict resolution and ordering
+/// - ContentType/SchemaVersion: For routing on payload format and FID schema
 #[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnvelopeMetadata {
@@ -24,6 +29,12 @@ pub struct EnvelopeMetadata {
     /// - Event replay
     pub timestamp: Option<u64>,
 
+    /// Absolute expiry time in milliseconds since Unix epoch (UTC)
+    ///
+    /// The record is stale from this instant on; routers drop it instead of
+    /// delivering it. Unlike a TTL it does not depend on `timestamp`.
+    pub expires_at: Option<u64>,
+
     /// Source service/device/tenant identifier
     ///
     /// Examples: "auth-service", "sensor-12", "tenant-acme"
@@ -39,17 +50,103 @@ pub struct EnvelopeMetadata {
     /// Recommendation: Keep ≤ 128 characters
     pub trace_id: Option<String>,
 
+    /// Identifier shared by every message of one conversation or saga
+    ///
+    /// A reply copies the request's correlation ID so the requester can
+    /// match them up.
+    pub correlation_id: Option<String>,
+
+    /// Identifier of the message that caused this one
+    ///
+    /// Usually the ID (e.g. trace or correlation ID) of the triggering
+    /// command or event; chains of causation IDs rebuild a saga's history.
+    pub causation_id: Option<String>,
+
+    /// Key that brokers partition on (Kafka record key)
+    ///
+    /// Records sharing a key keep their relative order. Typically the value
+    /// of an entity FID such as a user ID.
+    pub partition_key: Option<String>,
+
     /// Monotonically increasing sequence number
     ///
     /// Used for ordering and conflict resolution.
     /// Should increment for each version of the same entity.
     pub sequence: Option<u64>,
 
-    /// Extensibility labels (reserved for future use)
+    /// Payload format as a media type
+    ///
+    /// One of the `CONTENT_TYPE_*` constants for LNMP payloads, e.g.
+    /// [`CONTENT_TYPE_BINARY`]. Lets routers pick a decoder without
+    /// sniffing the payload.
+    pub content_type: Option<String>,
+
+    /// Version of the FID schema (registry) the payload was written against
     ///
-    /// V1: Optional, implementations may ignore
-    /// Future: tenant, environment, region, priority, etc.
-    pub labels: HashMap<String, String>,
+    /// Examples: "1.0.0", "2024-11"
+    pub schema_version: Option<String>,
+
+    /// Custom extension labels (tenant, environment, region, priority, ...)
+    ///
+    /// Labels are opaque to this crate: every codec carries them through
+    /// unchanged, so intermediaries forward labels they don't understand.
+    /// Kept sorted by key so encodings stay deterministic.
+    pub labels: BTreeMap<String, String>,
+
+    /// Detached signature over the other fields and the record hash
+    ///
+    /// Set by [`signature::sign`](crate::signature::sign). Carried by the
+    /// binary and batch codecs only.
+    pub signature: Option<EnvelopeSignature>,
+}
+
+/// Content type of canonical LNMP text payloads
+pub const CONTENT_TYPE_TEXT: &str = "application/lnmp-text";
+
+/// Content type of LNMP binary payloads
+pub const CONTENT_TYPE_BINARY: &str = "application/lnmp-binary";
+
+/// Content type of LNMP text annotated with `# field_name` comments
+pub const CONTENT_TYPE_EXPLAIN: &str = "application/lnmp-explain";
+
+/// Content type of ShortForm payloads (LLM input only, not canonical)
+pub const CONTENT_TYPE_SHORTFORM: &str = "application/lnmp-shortform";
+
+/// Content type of binary delta packets
+pub const CONTENT_TYPE_DELTA: &str = "application/lnmp-delta";
+
+/// Header keys that cannot be used as label keys
+pub const RESERVED_LABEL_KEYS: [&str; 10] = [
+    "timestamp",
+    "expires_at",
+    "source",
+    "trace_id",
+    "sequence",
+    "content_type",
+    "schema_version",
+    "correlation_id",
+    "causation_id",
+    "partition_key",
+];
+
+/// Maximum length of a label key in bytes
+pub const MAX_LABEL_KEY_LEN: usize = 255;
+
+/// Checks that a label key survives both the binary and the text codec
+///
+/// Keys must be non-empty, at most [`MAX_LABEL_KEY_LEN`] bytes, free of
+/// whitespace, `=` and `"`, and must not shadow a header field.
+pub(crate) fn check_label_key(key: &str) -> crate::Result<()> {
+    if key.is_empty()
+        || key.len() > MAX_LABEL_KEY_LEN
+        || key
+            .chars()
+            .any(|c| c.is_whitespace() || c == '=' || c == '"')
+        || RESERVED_LABEL_KEYS.contains(&key)
+    {
+        return Err(crate::EnvelopeError::InvalidLabel(key.to_string()));
+    }
+    Ok(())
 }
 
 impl EnvelopeMetadata {
@@ -58,13 +155,37 @@ impl EnvelopeMetadata {
         Self::default()
     }
 
+    /// Sets a label, replacing any previous value for the key
+    pub fn set_label(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.labels.insert(key.into(), value.into());
+    }
+
+    /// Returns the value of a label
+    pub fn label(&self, key: &str) -> Option<&str> {
+        self.labels.get(key).map(String::as_str)
+    }
+
+    /// Returns true if `expires_at` is set and not after `now_ms`
+    ///
+    /// Metadata without an expiry never expires.
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at.is_some_and(|exp| exp <= now_ms)
+    }
+
     /// Returns true if all fields are None/empty
     pub fn is_empty(&self) -> bool {
         self.timestamp.is_none()
+            && self.expires_at.is_none()
             && self.source.is_none()
             && self.trace_id.is_none()
+            && self.correlation_id.is_none()
+            && self.causation_id.is_none()
+            && self.partition_key.is_none()
             && self.sequence.is_none()
+            && self.content_type.is_none()
+            && self.schema_version.is_none()
             && self.labels.is_empty()
+            && self.signature.is_none()
     }
 
     /// Validates metadata constraints
@@ -72,6 +193,10 @@ impl EnvelopeMetadata {
     /// Checks:
     /// - Source length ≤ 64 characters (warning threshold)
     /// - TraceID length ≤ 128 characters (warning threshold)
+    /// - ContentType/SchemaVersion/CorrelationID/CausationID/PartitionKey
+    ///   length ≤ 256 bytes
+    /// - Label keys are well-formed and label values ≤ 256 bytes
+    /// - Signature algorithm and key ID ≤ 255 bytes
     pub fn validate(&self) -> crate::Result<()> {
         if let Some(ref source) = self.source {
             if source.len() > 256 {
@@ -91,6 +216,39 @@ impl EnvelopeMetadata {
             }
         }
 
+        for (field, value) in [
+            ("content_type", &self.content_type),
+            ("schema_version", &self.schema_version),
+            ("correlation_id", &self.correlation_id),
+            ("causation_id", &self.causation_id),
+            ("partition_key", &self.partition_key),
+        ] {
+            if value.as_ref().is_some_and(|v| v.len() > 256) {
+                return Err(crate::EnvelopeError::StringTooLong(field.to_string(), 256));
+            }
+        }
+
+        for (key, value) in &self.labels {
+            check_label_key(key)?;
+            if value.len() > 256 {
+                return Err(crate::EnvelopeError::StringTooLong(
+                    format!("labels.{}", key),
+                    256,
+                ));
+            }
+        }
+
+        if let Some(ref sig) = self.signature {
+            for (field, value) in [
+                ("signature.algorithm", &sig.algorithm),
+                ("signature.key_id", &sig.key_id),
+            ] {
+                if value.len() > 255 {
+                    return Err(crate::EnvelopeError::StringTooLong(field.to_string(), 255));
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -133,4 +291,73 @@ mod tests {
         meta.trace_id = Some("y".repeat(257));
         assert!(meta.validate().is_err());
     }
+
+    #[test]
+    fn test_routing_fields() {
+        let mut meta = EnvelopeMetadata::new();
+        meta.content_type = Some(CONTENT_TYPE_DELTA.to_string());
+        assert!(!meta.is_empty());
+        meta.schema_version = Some("v".repeat(257));
+        assert!(meta.validate().is_err());
+        meta.schema_version = Some("1.2.0".to_string());
+        assert!(meta.validate().is_ok());
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let mut meta = EnvelopeMetadata::new();
+        assert!(!meta.is_expired(u64::MAX));
+        meta.expires_at = Some(5000);
+        assert!(!meta.is_empty());
+        assert!(!meta.is_expired(4999));
+        assert!(meta.is_expired(5000));
+        assert!(meta.is_expired(6000));
+    }
+
+    #[test]
+    fn test_validate_rejects_too_long_correlation_ids() {
+        let mut meta = EnvelopeMetadata::new();
+        meta.correlation_id = Some("req-1".to_string());
+        assert!(!meta.is_empty());
+        assert!(meta.validate().is_ok());
+        meta.causation_id = Some("c".repeat(257));
+        assert!(meta.validate().is_err());
+        meta.causation_id = None;
+        meta.partition_key = Some("p".repeat(257));
+        assert!(meta.validate().is_err());
+    }
+
+    #[test]
+    fn test_labels_accessors() {
+        let mut meta = EnvelopeMetadata::new();
+        meta.set_label("tenant", "acme");
+        assert!(!meta.is_empty());
+        assert_eq!(meta.label("tenant"), Some("acme"));
+        assert_eq!(meta.label("region"), None);
+        assert!(meta.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_label_keys() {
+        for key in [
+            "",
+            "has space",
+            "a=b",
+            "q\"",
+            "source",
+            "content_type",
+            &"k".repeat(256),
+        ] {
+            let mut meta = EnvelopeMetadata::new();
+            meta.set_label(key, "v");
+            assert!(
+                matches!(meta.validate(), Err(crate::EnvelopeError::InvalidLabel(_))),
+                "{key:?}"
+            );
+        }
+
+        let mut meta = EnvelopeMetadata::new();
+        meta.set_label("note", "x".repeat(257));
+        assert!(meta.validate().is_err());
+    }
 }
@@ -0,0 +1,330 @@
+//! Detached envelope signatures
+//!
+//! A signature covers the envelope metadata (every field except the signature
+//! itself, in canonical TLV encoding) and a canonical hash of the record, so a
+//! consumer can check which source produced an envelope after any number of
+//! hops that forward it unchanged.
+//!
+//! This crate does not pick a hash or signature algorithm. The record hash is
+//! supplied by the caller (e.g. `BinaryEncoder::canonical_hash` from
+//! `lnmp-codec`), and keys live behind the [`Signer`] and [`Verifier`] traits.
+//!
+//! ## Signing Input
+//!
+//! ```text
+//! "LNMP-ENVELOPE-SIG-v1" | ALG_LEN (1 byte) | ALG | KEY_ID_LEN (1 byte) | KEY_ID
+//!   | META_LEN (4 bytes, BE) | META (TLV, without signature) | RECORD_HASH
+//! ```
+//!
+//! The signature travels as TLV type `0x1E` (see
+//! [`binary_codec`](crate::binary_codec)). Intermediaries that change any
+//! metadata field, including labels, invalidate it.
+//!
+//! # Example
+//!
+//! ```
+//! use lnmp_envelope::signature::{sign, verify, Signer, Verifier};
+//! use lnmp_envelope::{EnvelopeMetadata, Result};
+//!
+//! // Stand-in for a real signature scheme
+//! struct Checksum;
+//!
+//! impl Signer for Checksum {
+//!     fn algorithm(&self) -> &str { "sum8" }
+//!     fn key_id(&self) -> &str { "k1" }
+//!     fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+//!         Ok(vec![message.iter().fold(0u8, |a, b| a.wrapping_add(*b))])
+//!     }
+//! }
+//!
+//! impl Verifier for Checksum {
+//!     fn verify(&self, _algorithm: &str, _key_id: &str, message: &[u8], signature: &[u8]) -> bool {
+//!         self.sign(message).is_ok_and(|expected| expected == signature)
+//!     }
+//! }
+//!
+//! let record_hash = [7u8; 32];
+//! let mut metadata = EnvelopeMetadata::new();
+//! metadata.source = Some("auth-service".to_string());
+//!
+//! sign(&mut metadata, &record_hash, &Checksum).unwrap();
+//! assert!(verify(&metadata, &record_hash, &Checksum).is_ok());
+//! assert!(verify(&metadata, &[8u8; 32], &Checksum).is_err());
+//! ```
+
+use crate::binary_codec::TlvEncoder;
+use crate::{EnvelopeError, EnvelopeMetadata, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Domain separation prefix of the signing input
+pub const SIGNING_CONTEXT: &[u8] = b"LNMP-ENVELOPE-SIG-v1";
+
+/// Detached signature over envelope metadata and record hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnvelopeSignature {
+    /// Signature algorithm name (e.g. "ed25519"), at most 255 bytes
+    pub algorithm: String,
+    /// Identifier of the signing key, at most 255 bytes
+    pub key_id: String,
+    /// Raw signature bytes
+    pub signature: Vec<u8>,
+}
+
+/// Produces signatures with one key
+pub trait Signer {
+    /// Algorithm name recorded in the signature
+    fn algorithm(&self) -> &str;
+
+    /// Key identifier recorded in the signature
+    fn key_id(&self) -> &str;
+
+    /// Signs `message`
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Checks signatures, usually against a set of trusted keys
+pub trait Verifier {
+    /// Returns true if `signature` is a valid signature of `message` by the
+    /// key `key_id` under `algorithm`; unknown keys or algorithms are invalid
+    fn verify(&self, algorithm: &str, key_id: &str, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Builds the bytes that are signed for `metadata` and `record_hash`
+///
+/// Any signature already present in `metadata` is left out.
+pub fn signing_input(
+    metadata: &EnvelopeMetadata,
+    record_hash: &[u8],
+    algorithm: &str,
+    key_id: &str,
+) -> Result<Vec<u8>> {
+    let unsigned = EnvelopeMetadata {
+        signature: None,
+        ..metadata.clone()
+    };
+    let meta = TlvEncoder::encode(&unsigned)?;
+    let meta_len =
+        u32::try_from(meta.len()).map_err(|_| EnvelopeError::InvalidTlvLength(meta.len()))?;
+
+    let mut buf = Vec::with_capacity(
+        SIGNING_CONTEXT.len()
+            + 2
+            + algorithm.len()
+            + key_id.len()
+            + 4
+            + meta.len()
+            + record_hash.len(),
+    );
+    buf.extend_from_slice(SIGNING_CONTEXT);
+    write_short_string(&mut buf, "signature.algorithm", algorithm)?;
+    write_short_string(&mut buf, "signature.key_id", key_id)?;
+    buf.extend_from_slice(&meta_len.to_be_bytes());
+    buf.extend_from_slice(&meta);
+    buf.extend_from_slice(record_hash);
+    Ok(buf)
+}
+
+/// Signs `metadata` and `record_hash`, replacing any previous signature
+pub fn sign(
+    metadata: &mut EnvelopeMetadata,
+    record_hash: &[u8],
+    signer: &impl Signer,
+) -> Result<()> {
+    let input = signing_input(metadata, record_hash, signer.algorithm(), signer.key_id())?;
+    metadata.signature = Some(EnvelopeSignature {
+        algorithm: signer.algorithm().to_string(),
+        key_id: signer.key_id().to_string(),
+        signature: signer.sign(&input)?,
+    });
+    Ok(())
+}
+
+/// Verifies the signature carried by `metadata` against `record_hash`
+///
+/// Returns [`EnvelopeError::MissingSignature`] for unsigned metadata and
+/// [`EnvelopeError::InvalidSignature`] when `verifier` rejects it.
+pub fn verify(
+    metadata: &EnvelopeMetadata,
+    record_hash: &[u8],
+    verifier: &impl Verifier,
+) -> Result<()> {
+    let sig = metadata
+        .signature
+        .as_ref()
+        .ok_or(EnvelopeError::MissingSignature)?;
+    let input = signing_input(metadata, record_hash, &sig.algorithm, &sig.key_id)?;
+    if verifier.verify(&sig.algorithm, &sig.key_id, &input, &sig.signature) {
+        Ok(())
+    } else {
+        Err(EnvelopeError::InvalidSignature(sig.key_id.clone()))
+    }
+}
+
+/// Encodes a signature as the value of a `SIGNATURE` TLV entry
+pub(crate) fn encode_signature(sig: &EnvelopeSignature) -> Result<Vec<u8>> {
+    let mut buf =
+        Vec::with_capacity(2 + sig.algorithm.len() + sig.key_id.len() + sig.signature.len());
+    write_short_string(&mut buf, "signature.algorithm", &sig.algorithm)?;
+    write_short_string(&mut buf, "signature.key_id", &sig.key_id)?;
+    buf.extend_from_slice(&sig.signature);
+    if buf.len() > u16::MAX as usize {
+        return Err(EnvelopeError::StringTooLong(
+            "signature".to_string(),
+            u16::MAX as usize,
+        ));
+    }
+    Ok(buf)
+}
+
+/// Decodes the value of a `SIGNATURE` TLV entry
+pub(crate) fn decode_signature(value: &[u8]) -> Result<EnvelopeSignature> {
+    let mut pos = 0;
+    let algorithm = read_short_string(value, &mut pos)?;
+    let key_id = read_short_string(value, &mut pos)?;
+    Ok(EnvelopeSignature {
+        algorithm,
+        key_id,
+        signature: value[pos..].to_vec(),
+    })
+}
+
+fn write_short_string(buf: &mut Vec<u8>, field: &str, value: &str) -> Result<()> {
+    let len = u8::try_from(value.len())
+        .map_err(|_| EnvelopeError::StringTooLong(field.to_string(), u8::MAX as usize))?;
+    buf.push(len);
+    buf.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn read_short_string(value: &[u8], pos: &mut usize) -> Result<String> {
+    let len = *value
+        .get(*pos)
+        .ok_or(EnvelopeError::InvalidTlvLength(value.len()))? as usize;
+    let bytes = value
+        .get(*pos + 1..*pos + 1 + len)
+        .ok_or(EnvelopeError::InvalidTlvLength(value.len()))?;
+    *pos += 1 + len;
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_codec::TlvDecoder;
+
+    /// Keyed FNV-1a stand-in for a real signature scheme
+    struct KeyedHash {
+        key_id: &'static str,
+        key: u64,
+    }
+
+    impl KeyedHash {
+        fn mac(&self, message: &[u8]) -> Vec<u8> {
+            message
+                .iter()
+                .fold(self.key ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+                    (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+                })
+                .to_be_bytes()
+                .to_vec()
+        }
+    }
+
+    impl Signer for KeyedHash {
+        fn algorithm(&self) -> &str {
+            "fnv-mac"
+        }
+
+        fn key_id(&self) -> &str {
+            self.key_id
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+            Ok(self.mac(message))
+        }
+    }
+
+    impl Verifier for KeyedHash {
+        fn verify(&self, algorithm: &str, key_id: &str, message: &[u8], signature: &[u8]) -> bool {
+            algorithm == "fnv-mac" && key_id == self.key_id && self.mac(message) == signature
+        }
+    }
+
+    const KEY: KeyedHash = KeyedHash {
+        key_id: "auth-2024",
+        key: 42,
+    };
+
+    fn metadata() -> EnvelopeMetadata {
+        let mut metadata = EnvelopeMetadata::new();
+        metadata.timestamp = Some(1732373147000);
+        metadata.source = Some("auth-service".to_string());
+        metadata.set_label("tenant", "acme");
+        metadata
+    }
+
+    #[test]
+    fn test_sign_verify_round_trip_through_tlv() {
+        let hash = [1u8; 32];
+        let mut metadata = metadata();
+        sign(&mut metadata, &hash, &KEY).unwrap();
+        let sig = metadata.signature.as_ref().unwrap();
+        assert_eq!(sig.algorithm, "fnv-mac");
+        assert_eq!(sig.key_id, "auth-2024");
+
+        let forwarded = TlvDecoder::decode(&TlvEncoder::encode(&metadata).unwrap()).unwrap();
+        assert_eq!(forwarded, metadata);
+        assert_eq!(verify(&forwarded, &hash, &KEY), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let hash = [1u8; 32];
+        let mut metadata = metadata();
+        sign(&mut metadata, &hash, &KEY).unwrap();
+
+        assert!(verify(&metadata, &[2u8; 32], &KEY).is_err());
+
+        let mut relabeled = metadata.clone();
+        relabeled.set_label("tenant", "other");
+        assert_eq!(
+            verify(&relabeled, &hash, &KEY),
+            Err(EnvelopeError::InvalidSignature("auth-2024".to_string()))
+        );
+
+        let other_key = KeyedHash {
+            key_id: "auth-2024",
+            key: 7,
+        };
+        assert!(verify(&metadata, &hash, &other_key).is_err());
+    }
+
+    #[test]
+    fn test_verify_requires_signature() {
+        assert_eq!(
+            verify(&metadata(), &[0u8; 32], &KEY),
+            Err(EnvelopeError::MissingSignature)
+        );
+    }
+
+    #[test]
+    fn test_signing_input_ignores_existing_signature() {
+        let hash = [3u8; 32];
+        let mut metadata = metadata();
+        let before = signing_input(&metadata, &hash, "fnv-mac", "auth-2024").unwrap();
+        sign(&mut metadata, &hash, &KEY).unwrap();
+        let after = signing_input(&metadata, &hash, "fnv-mac", "auth-2024").unwrap();
+        assert_eq!(before, after);
+        assert!(before.starts_with(SIGNING_CONTEXT));
+    }
+
+    #[test]
+    fn test_decode_signature_rejects_truncated_value() {
+        assert!(decode_signature(&[]).is_err());
+        assert!(decode_signature(&[5, b'a']).is_err());
+        assert!(decode_signature(&[1, b'a', 0]).is_ok());
+    }
+}
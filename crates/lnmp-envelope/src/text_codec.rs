@@ -5,7 +5,7 @@
 //! ## Format
 //!
 //! ```text
-//! #ENVELOPE timestamp=1732373147000 source=auth-service trace_id="abc-123"
+//! #ENVELOPE timestamp=1732373147000 source=auth-service trace_id="abc-123" content_type=application/lnmp-text schema_version=1.0.0
 //! F12=14532
 //! F7=1
 //! ```
@@ -15,6 +15,8 @@
 //! - `#ENVELOPE` keyword must be first line (if present)
 //! - Space-separated key=value pairs
 //! - Values without spaces unquoted, otherwise double-quoted
+//! - Keys other than the header fields are labels, written after them in key
+//!   order and kept on decode whether or not the reader knows them
 //! - Envelope is optional (backward compatible)
 
 use crate::{EnvelopeError, EnvelopeMetadata, Result};
@@ -44,7 +46,9 @@ impl TextEncoder {
 
         let mut parts = vec!["#ENVELOPE".to_string()];
 
-        // Canonical order: timestamp, source, trace_id, sequence
+        // Canonical order: timestamp, source, trace_id, sequence, content_type,
+        // schema_version, correlation_id, causation_id, partition_key, expires_at,
+        // labels
         if let Some(ts) = metadata.timestamp {
             parts.push(format!("timestamp={}", ts));
         }
@@ -61,8 +65,35 @@ impl TextEncoder {
             parts.push(format!("sequence={}", seq));
         }
 
-        // Labels (future)
+        if let Some(ref content_type) = metadata.content_type {
+            parts.push(format!(
+                "content_type={}",
+                Self::quote_if_needed(content_type)
+            ));
+        }
+
+        if let Some(ref version) = metadata.schema_version {
+            parts.push(format!("schema_version={}", Self::quote_if_needed(version)));
+        }
+
+        if let Some(ref id) = metadata.correlation_id {
+            parts.push(format!("correlation_id={}", Self::quote_if_needed(id)));
+        }
+
+        if let Some(ref id) = metadata.causation_id {
+            parts.push(format!("causation_id={}", Self::quote_if_needed(id)));
+        }
+
+        if let Some(ref key) = metadata.partition_key {
+            parts.push(format!("partition_key={}", Self::quote_if_needed(key)));
+        }
+
+        if let Some(exp) = metadata.expires_at {
+            parts.push(format!("expires_at={}", exp));
+        }
+
         for (key, value) in &metadata.labels {
+            crate::metadata::check_label_key(key)?;
             parts.push(format!("{}={}", key, Self::quote_if_needed(value)));
         }
 
@@ -130,6 +161,26 @@ impl TextDecoder {
                         EnvelopeError::MalformedHeader(format!("Invalid sequence: {}", value))
                     })?);
                 }
+                "content_type" => {
+                    metadata.content_type = Some(value);
+                }
+                "schema_version" => {
+                    metadata.schema_version = Some(value);
+                }
+                "correlation_id" => {
+                    metadata.correlation_id = Some(value);
+                }
+                "causation_id" => {
+                    metadata.causation_id = Some(value);
+                }
+                "partition_key" => {
+                    metadata.partition_key = Some(value);
+                }
+                "expires_at" => {
+                    metadata.expires_at = Some(value.parse().map_err(|_| {
+                        EnvelopeError::MalformedHeader(format!("Invalid expires_at: {}", value))
+                    })?);
+                }
                 _ => {
                     // Unknown key - store in labels
                     metadata.labels.insert(key, value);
@@ -309,9 +360,21 @@ mod tests {
         original.source = Some("test-service".to_string());
         original.trace_id = Some("trace-abc".to_string());
         original.sequence = Some(99);
+        original.content_type = Some(crate::CONTENT_TYPE_DELTA.to_string());
+        original.schema_version = Some("1.0.0".to_string());
+        original.correlation_id = Some("order 77".to_string());
+        original.causation_id = Some("cmd-5".to_string());
+        original.partition_key = Some("user-42".to_string());
+        original.expires_at = Some(1732373207000);
 
         let encoded = TextEncoder::encode(&original).unwrap();
+        assert!(encoded.ends_with(
+            "sequence=99 content_type=application/lnmp-delta schema_version=1.0.0 \
+             correlation_id=\"order 77\" causation_id=cmd-5 partition_key=user-42 \
+             expires_at=1732373207000"
+        ));
         let decoded = TextDecoder::decode(&encoded).unwrap().unwrap();
+        assert_eq!(original, decoded);
 
         assert_eq!(original.timestamp, decoded.timestamp);
         assert_eq!(original.source, decoded.source);
@@ -331,4 +394,25 @@ mod tests {
             Some(&"custom_value".to_string())
         );
     }
+
+    #[test]
+    fn test_labels_round_trip_in_key_order() {
+        let input = "#ENVELOPE sequence=3 zone=\"eu west\" app=billing";
+        let decoded = TextDecoder::decode(input).unwrap().unwrap();
+        assert_eq!(decoded.label("zone"), Some("eu west"));
+
+        let encoded = TextEncoder::encode(&decoded).unwrap();
+        assert_eq!(encoded, "#ENVELOPE sequence=3 app=billing zone=\"eu west\"");
+        assert_eq!(TextDecoder::decode(&encoded).unwrap().unwrap(), decoded);
+    }
+
+    #[test]
+    fn test_encode_rejects_invalid_label_key() {
+        let mut metadata = EnvelopeMetadata::new();
+        metadata.set_label("bad key", "v");
+        assert!(matches!(
+            TextEncoder::encode(&metadata),
+            Err(EnvelopeError::InvalidLabel(_))
+        ));
+    }
 }
@@ -3,6 +3,7 @@
 //! This module provides human-readable annotations for LNMP data by appending
 //! inline comments with field names and descriptions.
 
+use lnmp_core::registry::StaleField;
 use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpValue, TypeHint};
 use lnmp_sfe;
 use std::collections::HashMap;
@@ -185,35 +186,59 @@ impl ExplainEncoder {
     /// // F12:i=14532         # user_id
     /// ```
     pub fn encode_with_explanation(&self, record: &LnmpRecord) -> String {
+        self.encode_with_staleness(record, &[])
+    }
+
+    /// Encodes a record with inline explanations, flagging stale fields
+    ///
+    /// Fields listed in `stale` (see
+    /// [`FidRegistry::stale_fields`](lnmp_core::registry::FidRegistry::stale_fields))
+    /// get a comment stating their age and TTL, so an LLM does not read them as
+    /// current values:
+    ///
+    /// ```text
+    /// F256:fa=[1.5,2.5]   # position (stale: 45000ms old, ttl 30000ms)
+    /// ```
+    pub fn encode_with_staleness(&self, record: &LnmpRecord, stale: &[StaleField]) -> String {
         // Canonicalize the record (sort fields)
         let canonical = self.canonicalize_record(record);
 
         let lines: Vec<String> = canonical
             .fields()
             .iter()
-            .map(|field| self.encode_field_with_explanation(field))
+            .map(|field| {
+                let stale = stale.iter().find(|s| s.fid == field.fid);
+                self.encode_field_with_explanation(field, stale)
+            })
             .collect();
 
         lines.join("\n")
     }
 
     /// Encodes a single field with explanation
-    fn encode_field_with_explanation(&self, field: &LnmpField) -> String {
+    fn encode_field_with_explanation(
+        &self,
+        field: &LnmpField,
+        stale: Option<&StaleField>,
+    ) -> String {
         let base = self.encode_field(field);
 
-        // Add comment if field name is available
-        if let Some(field_name) = self.dictionary.get_field_name(field.fid) {
-            // Calculate padding to align comment
-            let padding = if base.len() < self.comment_column {
-                self.comment_column - base.len()
-            } else {
-                2 // Minimum 2 spaces before comment
-            };
-
-            format!("{}{}# {}", base, " ".repeat(padding), field_name)
+        let stale_note = stale.map(|s| format!("stale: {}ms old, ttl {}ms", s.age_ms, s.ttl_ms));
+        let comment = match (self.dictionary.get_field_name(field.fid), stale_note) {
+            (Some(name), Some(note)) => format!("{} ({})", name, note),
+            (Some(name), None) => name.to_string(),
+            (None, Some(note)) => note,
+            (None, None) => return base,
+        };
+
+        // Calculate padding to align comment
+        let padding = if base.len() < self.comment_column {
+            self.comment_column - base.len()
         } else {
-            base
-        }
+            2 // Minimum 2 spaces before comment
+        };
+
+        format!("{}{}# {}", base, " ".repeat(padding), comment)
     }
 
     /// Encodes a single field in canonical format
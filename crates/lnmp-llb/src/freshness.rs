@@ -0,0 +1,171 @@
+//! Freshness filtering for LLM contexts
+//!
+//! Sensor readings and other time-sensitive fields can outlive their
+//! usefulness. The FID registry declares a TTL (`ttl_ms`) per field; this module
+//! uses it to strip or mark stale fields before a record is rendered into a
+//! prompt, so outdated readings are never presented as current.
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_core::registry::FidRegistry;
+//! use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+//! use lnmp_llb::{FreshnessFilter, StalePolicy};
+//! use std::sync::Arc;
+//!
+//! let registry = FidRegistry::from_yaml_str(
+//!     "core:\n  - fid: 40\n    name: temperature\n    type: Float\n    ttl_ms: 30000\n",
+//! )
+//! .unwrap();
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 40, value: LnmpValue::Float(21.5) });
+//!
+//! let filter = FreshnessFilter::new(Arc::new(registry)).with_policy(StalePolicy::Strip);
+//! let fresh = filter.apply(&record, 1_000, 60_000);
+//! assert!(fresh.record.fields().is_empty());
+//! assert_eq!(fresh.stale[0].fid, 40);
+//! ```
+
+use crate::explain::ExplainEncoder;
+use lnmp_core::registry::{FidRegistry, StaleField};
+use lnmp_core::LnmpRecord;
+use std::sync::Arc;
+
+/// What to do with fields whose readings have outlived their TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StalePolicy {
+    /// Keep stale fields and report them so they can be annotated (default)
+    #[default]
+    Mark,
+    /// Remove stale fields from the record
+    Strip,
+}
+
+/// Result of applying a [`FreshnessFilter`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreshRecord {
+    /// The record, without stale fields under [`StalePolicy::Strip`]
+    pub record: LnmpRecord,
+    /// Fields found to be stale, whether or not they were removed
+    pub stale: Vec<StaleField>,
+}
+
+impl FreshRecord {
+    /// Renders the record in explain mode, annotating fields that remain stale
+    pub fn explain(&self, encoder: &ExplainEncoder) -> String {
+        encoder.encode_with_staleness(&self.record, &self.stale)
+    }
+}
+
+/// Strips or marks stale fields using TTLs from the FID registry
+#[derive(Debug, Clone)]
+pub struct FreshnessFilter {
+    registry: Arc<FidRegistry>,
+    policy: StalePolicy,
+}
+
+impl FreshnessFilter {
+    /// Creates a filter that marks stale fields
+    pub fn new(registry: Arc<FidRegistry>) -> Self {
+        Self {
+            registry,
+            policy: StalePolicy::default(),
+        }
+    }
+
+    /// Sets what happens to stale fields
+    pub fn with_policy(mut self, policy: StalePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Checks `record` produced at `timestamp_ms` against the registry TTLs at `now_ms`
+    ///
+    /// Both times are Unix milliseconds; `timestamp_ms` is usually the envelope
+    /// timestamp. Only top-level fields are checked.
+    pub fn apply(&self, record: &LnmpRecord, timestamp_ms: u64, now_ms: u64) -> FreshRecord {
+        let stale = self.registry.stale_fields(record, timestamp_ms, now_ms);
+        let mut record = record.clone();
+        if self.policy == StalePolicy::Strip {
+            for field in &stale {
+                record.remove_field(field.fid);
+            }
+        }
+        FreshRecord { record, stale }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explain::SemanticDictionary;
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    const REGISTRY: &str = r#"
+core:
+  - fid: 1
+    name: entity_id
+    type: Int
+  - fid: 40
+    name: temperature
+    type: Float
+    ttl_ms: 30000
+"#;
+
+    fn filter() -> FreshnessFilter {
+        FreshnessFilter::new(Arc::new(FidRegistry::from_yaml_str(REGISTRY).unwrap()))
+    }
+
+    fn record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(7),
+        });
+        record.add_field(LnmpField {
+            fid: 40,
+            value: LnmpValue::Float(21.5),
+        });
+        record
+    }
+
+    #[test]
+    fn test_fresh_fields_are_untouched() {
+        let fresh = filter()
+            .with_policy(StalePolicy::Strip)
+            .apply(&record(), 100_000, 110_000);
+        assert!(fresh.stale.is_empty());
+        assert_eq!(fresh.record, record());
+    }
+
+    #[test]
+    fn test_strip_removes_stale_fields() {
+        let fresh = filter()
+            .with_policy(StalePolicy::Strip)
+            .apply(&record(), 100_000, 145_000);
+        assert_eq!(fresh.record.fields().len(), 1);
+        assert!(fresh.record.get_field(40).is_none());
+        assert_eq!(
+            fresh.stale,
+            vec![StaleField {
+                fid: 40,
+                age_ms: 45_000,
+                ttl_ms: 30_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mark_annotates_explain_output() {
+        let fresh = filter().apply(&record(), 100_000, 145_000);
+        assert_eq!(fresh.record, record());
+
+        let dict = SemanticDictionary::from_pairs(vec![(40, "temperature")]);
+        let output = fresh.explain(&ExplainEncoder::new(dict));
+        assert_eq!(
+            output,
+            "F1:i=7\nF40:f=21.5          # temperature (stale: 45000ms old, ttl 30000ms)"
+        );
+    }
+}
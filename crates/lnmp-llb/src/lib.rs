@@ -8,8 +8,10 @@
 //! # Modules
 //!
 //! - `explain`: Explain mode encoding with human-readable annotations
+//! - `freshness`: Stripping or marking fields that outlived their registry TTL
 //! - `prompt_opt`: Prompt visibility optimization for tokenization efficiency
 //! - `shortform`: ShortForm encoding for extreme token reduction (planned)
+//! - `tokens`: Token estimation of records for budgeting
 //!
 //! # Examples
 //!
@@ -42,11 +44,15 @@
 //! ```
 
 pub mod explain;
+pub mod freshness;
 pub mod llb2;
 pub mod prompt_opt;
 pub mod shortform;
+pub mod tokens;
 
 // Re-export main types for convenience
 pub use explain::{ExplainEncoder, SemanticDictionary};
+pub use freshness::{FreshRecord, FreshnessFilter, StalePolicy};
 pub use llb2::{LlbConfig, LlbConverter, LlbError};
 pub use prompt_opt::{PromptOptConfig, PromptOptimizer};
+pub use tokens::{TokenEstimator, TokenFormat};
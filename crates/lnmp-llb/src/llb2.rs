@@ -153,7 +153,7 @@ impl LlbConverter {
     }
 
     /// Converts a record to ShortForm representation
-    fn record_to_shortform(&self, record: &LnmpRecord) -> String {
+    pub fn record_to_shortform(&self, record: &LnmpRecord) -> String {
         let fields: Vec<String> = record
             .sorted_fields()
             .iter()
@@ -263,7 +263,7 @@ impl LlbConverter {
     }
 
     /// Converts ShortForm text to a record
-    fn shortform_to_record(&self, shortform: &str) -> Result<LnmpRecord, LlbError> {
+    pub fn shortform_to_record(&self, shortform: &str) -> Result<LnmpRecord, LlbError> {
         // Convert ShortForm to FullText by adding 'F' prefixes
         let fulltext = self.shortform_to_fulltext(shortform);
 
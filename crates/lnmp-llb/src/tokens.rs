@@ -0,0 +1,135 @@
+//! Token estimation for LLM contexts
+//!
+//! Budgeting and routing need to know what a record costs before it is sent.
+//! [`TokenEstimator`] renders the record the way the model will see it
+//! (canonical LNMP text or ShortForm) and estimates tokens from the rendered
+//! length. The default of 4 characters per token is close to common BPE
+//! tokenizers on LNMP's ASCII-heavy text; calibrate it with
+//! [`TokenEstimator::with_chars_per_token`] for a specific model.
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+//! use lnmp_llb::{TokenEstimator, TokenFormat};
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(14532) });
+//! record.add_field(LnmpField { fid: 7, value: LnmpValue::Bool(true) });
+//!
+//! let text = TokenEstimator::new();
+//! let short = TokenEstimator::new().with_format(TokenFormat::ShortForm);
+//! assert_eq!(text.estimate_record(&record), 4); // "F7=1\nF12=14532"
+//! assert!(short.estimate_record(&record) <= text.estimate_record(&record));
+//! ```
+
+use crate::llb2::LlbConverter;
+use lnmp_codec::Encoder;
+use lnmp_core::LnmpRecord;
+
+/// Rendering a record is estimated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenFormat {
+    /// Canonical LNMP text (`F12=14532`)
+    #[default]
+    Text,
+    /// ShortForm (`12=14532`)
+    ShortForm,
+}
+
+/// Estimates the LLM tokens of records and text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenEstimator {
+    format: TokenFormat,
+    chars_per_token: f64,
+}
+
+impl TokenEstimator {
+    /// Creates an estimator for canonical text at 4 characters per token
+    pub fn new() -> Self {
+        Self {
+            format: TokenFormat::Text,
+            chars_per_token: 4.0,
+        }
+    }
+
+    /// Sets the rendering records are estimated in
+    pub fn with_format(mut self, format: TokenFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the average characters per token (values below 1.0 are raised to 1.0)
+    pub fn with_chars_per_token(mut self, chars_per_token: f64) -> Self {
+        self.chars_per_token = chars_per_token.max(1.0);
+        self
+    }
+
+    /// Returns the rendering records are estimated in
+    pub fn format(&self) -> TokenFormat {
+        self.format
+    }
+
+    /// Estimates the tokens of `text`
+    pub fn estimate_text(&self, text: &str) -> usize {
+        (text.chars().count() as f64 / self.chars_per_token).ceil() as usize
+    }
+
+    /// Estimates the tokens of `record` in the configured rendering
+    pub fn estimate_record(&self, record: &LnmpRecord) -> usize {
+        let rendered = match self.format {
+            TokenFormat::Text => Encoder::new().encode(record),
+            TokenFormat::ShortForm => LlbConverter::default().record_to_shortform(record),
+        };
+        self.estimate_text(&rendered)
+    }
+}
+
+impl Default for TokenEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    #[test]
+    fn test_estimate_text() {
+        let estimator = TokenEstimator::new();
+        assert_eq!(estimator.estimate_text(""), 0);
+        assert_eq!(estimator.estimate_text("abcd"), 1);
+        assert_eq!(estimator.estimate_text("abcde"), 2);
+        assert_eq!(
+            estimator.with_chars_per_token(2.5).estimate_text("abcde"),
+            2
+        );
+        assert_eq!(estimator.with_chars_per_token(0.0).estimate_text("abc"), 3);
+    }
+
+    #[test]
+    fn test_estimate_grows_with_record() {
+        let estimator = TokenEstimator::new();
+        let mut record = LnmpRecord::new();
+        assert_eq!(estimator.estimate_record(&record), 0);
+
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("a fairly long status message".to_string()),
+        });
+        let one = estimator.estimate_record(&record);
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::IntArray((0..50).collect()),
+        });
+        assert!(estimator.estimate_record(&record) > one);
+        assert!(
+            estimator
+                .with_format(TokenFormat::ShortForm)
+                .estimate_record(&record)
+                < estimator.estimate_record(&record)
+        );
+    }
+}
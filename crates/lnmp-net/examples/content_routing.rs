@@ -13,7 +13,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let policy = ContentAwarePolicy::new()
         .with_threshold(0.7)
         .with_rule(ContentRule::critical_status_to_llm(50)) // F50: status
-        .with_rule(ContentRule::drop_spam(24)) // F24: tags
+        .with_rule(ContentRule::new(
+            24, // F24: tags
+            FieldCondition::ArrayContains("spam".to_string()),
+            RoutingDecision::Drop,
+        ))
         .with_rule(ContentRule::new(
             52, // F52: error_code
             FieldCondition::IntGreaterThan(5000),
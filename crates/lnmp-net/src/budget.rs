@@ -0,0 +1,316 @@
+//! Token budgets for LLM routing
+//!
+//! A [`TokenBudget`] caps the LLM tokens spent over a rolling window. A
+//! [`RoutingPolicy`](crate::RoutingPolicy) configured with one charges the
+//! estimated cost of every message it would send to the LLM. Once the budget
+//! is nearly exhausted (the last `reserve` share is left), only important
+//! messages may spend it and low-importance ones are processed locally; once it
+//! is exhausted, everything is.
+//!
+//! Costs come from a [`TokenEstimator`]. The default estimate is
+//! [`RecordComplexity::estimated_tokens`]; with the `llb` feature,
+//! [`lnmp_llb::TokenEstimator`] estimates from the text the LLM actually sees.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use lnmp_core::{LnmpRecord, LnmpRecordView};
+
+use crate::complexity::RecordComplexity;
+
+/// Estimates the LLM tokens a record costs
+pub trait TokenEstimator: fmt::Debug + Send + Sync {
+    /// Estimates the tokens of `record`
+    fn estimate_tokens(&self, record: &LnmpRecord) -> usize;
+
+    /// Estimates the tokens of a zero-copy record view
+    ///
+    /// The default converts the view to an owned record.
+    fn estimate_tokens_view(&self, record_view: &LnmpRecordView) -> usize {
+        self.estimate_tokens(&record_view.to_lnmp_record())
+    }
+}
+
+/// Token estimate from record shape ([`RecordComplexity::estimated_tokens`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComplexityTokenEstimator;
+
+impl TokenEstimator for ComplexityTokenEstimator {
+    fn estimate_tokens(&self, record: &LnmpRecord) -> usize {
+        RecordComplexity::of(record).estimated_tokens
+    }
+
+    fn estimate_tokens_view(&self, record_view: &LnmpRecordView) -> usize {
+        RecordComplexity::of_view(record_view).estimated_tokens
+    }
+}
+
+#[cfg(feature = "llb")]
+impl TokenEstimator for lnmp_llb::TokenEstimator {
+    fn estimate_tokens(&self, record: &LnmpRecord) -> usize {
+        self.estimate_record(record)
+    }
+}
+
+/// Counters collected by a [`TokenBudget`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenBudgetMetrics {
+    /// Messages allowed to spend
+    pub allowed: u64,
+    /// Messages refused because only the reserve was left
+    pub demoted: u64,
+    /// Messages refused because the budget was exhausted
+    pub exhausted: u64,
+    /// Tokens spent (refunds deducted)
+    pub spent_tokens: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    tokens_per_window: u64,
+    window_ms: u64,
+    reserve: f64,
+    protected_importance: f64,
+}
+
+#[derive(Debug)]
+struct State {
+    /// Spending in time order: (when, tokens)
+    spent: VecDeque<(u64, u64)>,
+    used: u64,
+    metrics: TokenBudgetMetrics,
+}
+
+/// Rolling-window LLM token budget
+///
+/// Spending older than `window_ms` no longer counts. The last `reserve` share
+/// of the budget (default 20%) is kept for messages whose importance reaches
+/// `protected_importance` (default 0.9) and for messages routed without an
+/// importance score (critical alerts, complex commands and queries). Clones
+/// share spending and metrics.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_net::TokenBudget;
+///
+/// let budget = TokenBudget::new(1000, 60_000);
+///
+/// assert!(budget.try_spend(700, Some(0.5), 0));
+/// // 850 would pass the 80% mark: only important messages may go on
+/// assert!(!budget.try_spend(150, Some(0.5), 1_000));
+/// assert!(budget.try_spend(150, Some(0.95), 1_000));
+/// assert!(budget.try_spend(150, None, 2_000));
+/// // Exhausted for everyone
+/// assert!(!budget.try_spend(1, None, 3_000));
+/// // The first spending has left the window
+/// assert!(budget.try_spend(100, Some(0.5), 60_001));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenBudget {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+impl TokenBudget {
+    /// Creates a budget of `tokens_per_window` tokens over a rolling `window_ms`
+    pub fn new(tokens_per_window: u64, window_ms: u64) -> Self {
+        Self {
+            config: Arc::new(Config {
+                tokens_per_window,
+                window_ms,
+                reserve: 0.2,
+                protected_importance: 0.9,
+            }),
+            state: Arc::new(Mutex::new(State {
+                spent: VecDeque::new(),
+                used: 0,
+                metrics: TokenBudgetMetrics::default(),
+            })),
+        }
+    }
+
+    /// Sets the share of the budget (0.0-1.0) kept for important messages
+    pub fn with_reserve(mut self, reserve: f64) -> Self {
+        Arc::make_mut(&mut self.config).reserve = reserve.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the importance at which messages may spend the reserve
+    pub fn with_protected_importance(mut self, importance: f64) -> Self {
+        Arc::make_mut(&mut self.config).protected_importance = importance;
+        self
+    }
+
+    /// Returns the tokens allowed per window
+    pub fn tokens_per_window(&self) -> u64 {
+        self.config.tokens_per_window
+    }
+
+    /// Returns the tokens spent within the window ending at `now_ms`
+    pub fn used(&self, now_ms: u64) -> u64 {
+        let mut state = self.lock();
+        self.expire(&mut state, now_ms);
+        state.used
+    }
+
+    /// Returns the tokens left within the window ending at `now_ms`
+    pub fn remaining(&self, now_ms: u64) -> u64 {
+        self.config
+            .tokens_per_window
+            .saturating_sub(self.used(now_ms))
+    }
+
+    /// Returns the spent share of the budget (0.0-1.0, above 1.0 if overspent)
+    pub fn load(&self, now_ms: u64) -> f64 {
+        let used = self.used(now_ms);
+        if self.config.tokens_per_window == 0 {
+            return if used == 0 { 0.0 } else { 1.0 };
+        }
+        used as f64 / self.config.tokens_per_window as f64
+    }
+
+    /// Spends `tokens` at `now_ms` for a message of the given importance
+    ///
+    /// Returns false, spending nothing, if the message would cross into the
+    /// reserve without being important enough, or exceed the budget.
+    pub fn try_spend(&self, tokens: usize, importance: Option<f64>, now_ms: u64) -> bool {
+        let tokens = tokens as u64;
+        let mut state = self.lock();
+        self.expire(&mut state, now_ms);
+
+        let after = state.used.saturating_add(tokens);
+        let limit = self.config.tokens_per_window;
+        let soft_limit = (limit as f64 * (1.0 - self.config.reserve)).floor() as u64;
+        let protected = importance.is_none_or(|i| i >= self.config.protected_importance);
+        if after > limit {
+            state.metrics.exhausted += 1;
+            return false;
+        }
+        if after > soft_limit && !protected {
+            state.metrics.demoted += 1;
+            return false;
+        }
+
+        state.spent.push_back((now_ms, tokens));
+        state.used = after;
+        state.metrics.allowed += 1;
+        state.metrics.spent_tokens += tokens;
+        true
+    }
+
+    /// Gives back `tokens` spent for a message that was not sent after all
+    pub fn refund(&self, tokens: usize) {
+        let mut tokens = tokens as u64;
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        state.metrics.spent_tokens = state.metrics.spent_tokens.saturating_sub(tokens);
+        // Newest spending first, so the refund leaves the window when it would have
+        while tokens > 0 {
+            let Some((_, spent)) = state.spent.back_mut() else {
+                break;
+            };
+            let taken = tokens.min(*spent);
+            *spent -= taken;
+            tokens -= taken;
+            state.used -= taken;
+            if *spent == 0 {
+                state.spent.pop_back();
+            }
+        }
+    }
+
+    /// Returns a snapshot of the counters
+    pub fn metrics(&self) -> TokenBudgetMetrics {
+        self.lock().metrics
+    }
+
+    /// Resets the counters, keeping the spending
+    pub fn reset_metrics(&self) {
+        self.lock().metrics = TokenBudgetMetrics::default();
+    }
+
+    fn expire(&self, state: &mut State, now_ms: u64) {
+        while let Some(&(at, tokens)) = state.spent.front() {
+            if at.saturating_add(self.config.window_ms) > now_ms {
+                break;
+            }
+            state.spent.pop_front();
+            state.used -= tokens;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Spending stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_window() {
+        let budget = TokenBudget::new(100, 1000).with_reserve(0.0);
+        assert!(budget.try_spend(60, Some(0.1), 0));
+        assert!(budget.try_spend(40, Some(0.1), 500));
+        assert!(!budget.try_spend(1, Some(0.1), 999));
+        assert_eq!(budget.remaining(999), 0);
+
+        // The spending at 0 leaves the window at 1000
+        assert_eq!(budget.used(1000), 40);
+        assert!(budget.try_spend(60, Some(0.1), 1000));
+        assert!((budget.load(1000) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reserve_and_metrics() {
+        let budget = TokenBudget::new(100, 1000)
+            .with_reserve(0.5)
+            .with_protected_importance(0.8);
+        let clone = budget.clone();
+        assert!(budget.try_spend(50, Some(0.3), 0));
+        assert!(!clone.try_spend(1, Some(0.79), 0));
+        assert!(clone.try_spend(30, Some(0.8), 0));
+        assert!(!budget.try_spend(30, None, 0));
+        assert!(budget.try_spend(20, None, 0));
+
+        let metrics = budget.metrics();
+        assert_eq!(metrics.allowed, 3);
+        assert_eq!(metrics.demoted, 1);
+        assert_eq!(metrics.exhausted, 1);
+        assert_eq!(metrics.spent_tokens, 100);
+    }
+
+    #[test]
+    fn test_refund() {
+        let budget = TokenBudget::new(100, 1000).with_reserve(0.0);
+        assert!(budget.try_spend(30, None, 0));
+        assert!(budget.try_spend(50, None, 500));
+        budget.refund(60);
+        assert_eq!(budget.used(500), 20);
+        assert_eq!(budget.metrics().spent_tokens, 20);
+        // What is left of the first spending still expires with it
+        assert_eq!(budget.used(1000), 0);
+    }
+
+    #[test]
+    fn test_complexity_estimator_matches_view() {
+        use lnmp_core::{LnmpField, LnmpFieldView, LnmpValue, LnmpValueView};
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("overheating".to_string()),
+        });
+        let view = LnmpRecordView::from_fields(vec![LnmpFieldView {
+            fid: 1,
+            value: LnmpValueView::String("overheating"),
+        }]);
+        let estimator = ComplexityTokenEstimator;
+        assert_eq!(estimator.estimate_tokens(&record), 5);
+        assert_eq!(estimator.estimate_tokens_view(&view), 5);
+    }
+}
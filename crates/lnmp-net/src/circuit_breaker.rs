@@ -0,0 +1,388 @@
+//! Circuit breaking around the LLM destination
+//!
+//! A [`CircuitBreaker`] watches the outcome of LLM dispatches. When too many of
+//! the recent ones failed it opens, and a
+//! [`RoutingPolicy`](crate::RoutingPolicy) configured with it processes
+//! messages locally instead of spending tokens on a provider that is down.
+//! After a cool-down the breaker lets a few probe messages through
+//! (half-open) and closes again once they succeed.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Externally visible breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Dispatches flow normally
+    Closed,
+    /// Dispatches are refused until the cool-down ends
+    Open,
+    /// A limited number of probe dispatches are allowed
+    HalfOpen,
+}
+
+/// Counters collected by a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CircuitMetrics {
+    /// Dispatches reported as successful
+    pub successes: u64,
+    /// Dispatches reported as failed
+    pub failures: u64,
+    /// Dispatches refused while open or out of probes
+    pub rejected: u64,
+    /// Times the breaker opened
+    pub opened: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    Closed,
+    Open {
+        until_ms: u64,
+    },
+    HalfOpen {
+        in_flight: u32,
+        successes: u32,
+        since_ms: u64,
+    },
+}
+
+#[derive(Debug)]
+struct State {
+    phase: Phase,
+    /// Recent outcomes while closed (true = success)
+    outcomes: VecDeque<bool>,
+    failures: usize,
+    metrics: CircuitMetrics,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    failure_rate: f64,
+    open_ms: u64,
+    window: usize,
+    min_requests: usize,
+    half_open_probes: u32,
+}
+
+/// Failure-rate circuit breaker shared by everything dispatching to one LLM
+///
+/// While closed, the last `window` outcomes are kept; once at least
+/// `min_requests` of them are known and the failed share reaches
+/// `failure_rate`, the breaker opens for `open_ms`. It then turns half-open and
+/// allows `half_open_probes` dispatches: if all succeed it closes, and any
+/// failure reopens it. Probes that never report back are given up after
+/// another `open_ms`. Clones share state and metrics.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_net::{CircuitBreaker, CircuitState};
+///
+/// let breaker = CircuitBreaker::new(0.5, 10_000).with_min_requests(4);
+///
+/// for ok in [true, true, false, false] {
+///     assert!(breaker.try_acquire(0));
+///     if ok {
+///         breaker.record_success(0);
+///     } else {
+///         breaker.record_failure(0);
+///     }
+/// }
+///
+/// // Half of the last 4 dispatches failed
+/// assert_eq!(breaker.state(0), CircuitState::Open);
+/// assert!(!breaker.try_acquire(5_000));
+///
+/// // After the cool-down one probe goes through and closes the circuit
+/// assert!(breaker.try_acquire(10_000));
+/// assert!(!breaker.try_acquire(10_000));
+/// breaker.record_success(10_100);
+/// assert_eq!(breaker.state(10_100), CircuitState::Closed);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    /// Creates a breaker opening at `failure_rate` (0.0-1.0) failed dispatches for `open_ms`
+    ///
+    /// Defaults: a window of 20 outcomes, at least 10 of them before tripping,
+    /// and 1 half-open probe.
+    pub fn new(failure_rate: f64, open_ms: u64) -> Self {
+        Self {
+            config: Arc::new(Config {
+                failure_rate,
+                open_ms,
+                window: 20,
+                min_requests: 10,
+                half_open_probes: 1,
+            }),
+            state: Arc::new(Mutex::new(State {
+                phase: Phase::Closed,
+                outcomes: VecDeque::new(),
+                failures: 0,
+                metrics: CircuitMetrics::default(),
+            })),
+        }
+    }
+
+    /// Sets how many recent outcomes the failure rate is computed over
+    pub fn with_window(mut self, window: usize) -> Self {
+        let config = Arc::make_mut(&mut self.config);
+        config.window = window.max(1);
+        config.min_requests = config.min_requests.min(config.window);
+        self
+    }
+
+    /// Sets how many outcomes must be known before the breaker can open
+    pub fn with_min_requests(mut self, min_requests: usize) -> Self {
+        let config = Arc::make_mut(&mut self.config);
+        config.min_requests = min_requests.clamp(1, config.window);
+        self
+    }
+
+    /// Sets how many probes are allowed, and must succeed, while half-open
+    pub fn with_half_open_probes(mut self, probes: u32) -> Self {
+        Arc::make_mut(&mut self.config).half_open_probes = probes.max(1);
+        self
+    }
+
+    /// Returns the state at `now_ms`
+    pub fn state(&self, now_ms: u64) -> CircuitState {
+        let mut state = self.lock();
+        self.advance(&mut state, now_ms);
+        match state.phase {
+            Phase::Closed => CircuitState::Closed,
+            Phase::Open { .. } => CircuitState::Open,
+            Phase::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Returns when the breaker turns half-open, if it is open at `now_ms`
+    ///
+    /// Callers that queue messages instead of processing them locally can use
+    /// this as the earliest delivery time.
+    pub fn next_probe_ms(&self, now_ms: u64) -> Option<u64> {
+        let mut state = self.lock();
+        self.advance(&mut state, now_ms);
+        match state.phase {
+            Phase::Open { until_ms } => Some(until_ms),
+            _ => None,
+        }
+    }
+
+    /// Asks permission to dispatch at `now_ms`
+    ///
+    /// Every granted dispatch should be followed by
+    /// [`record_success`](Self::record_success) or
+    /// [`record_failure`](Self::record_failure).
+    pub fn try_acquire(&self, now_ms: u64) -> bool {
+        let mut state = self.lock();
+        self.advance(&mut state, now_ms);
+        let allowed = match &mut state.phase {
+            Phase::Closed => true,
+            Phase::Open { .. } => false,
+            Phase::HalfOpen {
+                in_flight,
+                since_ms,
+                ..
+            } => {
+                if *in_flight < self.config.half_open_probes {
+                    if *in_flight == 0 {
+                        *since_ms = now_ms;
+                    }
+                    *in_flight += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if !allowed {
+            state.metrics.rejected += 1;
+        }
+        allowed
+    }
+
+    /// Records a successful dispatch at `now_ms`
+    pub fn record_success(&self, now_ms: u64) {
+        let mut state = self.lock();
+        self.advance(&mut state, now_ms);
+        state.metrics.successes += 1;
+        match state.phase {
+            Phase::Closed => self.push_outcome(&mut state, true, now_ms),
+            // Late result of a dispatch started before the breaker opened
+            Phase::Open { .. } => {}
+            Phase::HalfOpen {
+                in_flight,
+                successes,
+                since_ms,
+            } => {
+                let successes = successes + 1;
+                state.phase = if successes >= self.config.half_open_probes {
+                    Phase::Closed
+                } else {
+                    Phase::HalfOpen {
+                        in_flight: in_flight.saturating_sub(1),
+                        successes,
+                        since_ms,
+                    }
+                };
+            }
+        }
+    }
+
+    /// Records a failed dispatch at `now_ms`
+    pub fn record_failure(&self, now_ms: u64) {
+        let mut state = self.lock();
+        self.advance(&mut state, now_ms);
+        state.metrics.failures += 1;
+        match state.phase {
+            Phase::Closed => self.push_outcome(&mut state, false, now_ms),
+            Phase::Open { .. } => {}
+            Phase::HalfOpen { .. } => self.trip(&mut state, now_ms),
+        }
+    }
+
+    /// Returns a snapshot of the counters
+    pub fn metrics(&self) -> CircuitMetrics {
+        self.lock().metrics
+    }
+
+    /// Resets the counters, keeping the breaker state
+    pub fn reset_metrics(&self) {
+        self.lock().metrics = CircuitMetrics::default();
+    }
+
+    fn advance(&self, state: &mut State, now_ms: u64) {
+        match &mut state.phase {
+            Phase::Open { until_ms } if now_ms >= *until_ms => {
+                state.phase = Phase::HalfOpen {
+                    in_flight: 0,
+                    successes: 0,
+                    since_ms: now_ms,
+                };
+            }
+            Phase::HalfOpen {
+                in_flight,
+                since_ms,
+                ..
+            } if *in_flight > 0 && now_ms >= since_ms.saturating_add(self.config.open_ms) => {
+                // Probes that never reported back don't block the circuit forever
+                *in_flight = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn push_outcome(&self, state: &mut State, ok: bool, now_ms: u64) {
+        state.outcomes.push_back(ok);
+        if !ok {
+            state.failures += 1;
+        }
+        while state.outcomes.len() > self.config.window {
+            if state.outcomes.pop_front() == Some(false) {
+                state.failures -= 1;
+            }
+        }
+
+        let known = state.outcomes.len();
+        if known >= self.config.min_requests
+            && state.failures as f64 / known as f64 >= self.config.failure_rate
+        {
+            self.trip(state, now_ms);
+        }
+    }
+
+    fn trip(&self, state: &mut State, now_ms: u64) {
+        state.phase = Phase::Open {
+            until_ms: now_ms.saturating_add(self.config.open_ms),
+        };
+        state.outcomes.clear();
+        state.failures = 0;
+        state.metrics.opened += 1;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Breaker state stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail_n(breaker: &CircuitBreaker, n: usize, now_ms: u64) {
+        for _ in 0..n {
+            breaker.record_failure(now_ms);
+        }
+    }
+
+    #[test]
+    fn test_needs_min_requests() {
+        let breaker = CircuitBreaker::new(0.5, 1000).with_min_requests(5);
+        fail_n(&breaker, 4, 0);
+        assert_eq!(breaker.state(0), CircuitState::Closed);
+        fail_n(&breaker, 1, 0);
+        assert_eq!(breaker.state(0), CircuitState::Open);
+        assert_eq!(breaker.next_probe_ms(0), Some(1000));
+    }
+
+    #[test]
+    fn test_window_forgets_old_failures() {
+        let breaker = CircuitBreaker::new(0.5, 1000)
+            .with_window(4)
+            .with_min_requests(4);
+        fail_n(&breaker, 1, 0);
+        for _ in 0..4 {
+            breaker.record_success(0);
+        }
+        fail_n(&breaker, 1, 0);
+        // Window is [ok, ok, ok, fail]: 25% failed
+        assert_eq!(breaker.state(0), CircuitState::Closed);
+        fail_n(&breaker, 1, 0);
+        assert_eq!(breaker.state(0), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_half_open_failure_reopens() {
+        let breaker = CircuitBreaker::new(0.5, 1000)
+            .with_min_requests(1)
+            .with_half_open_probes(2);
+        fail_n(&breaker, 1, 0);
+        assert!(!breaker.try_acquire(999));
+
+        assert!(breaker.try_acquire(1000));
+        assert!(breaker.try_acquire(1000));
+        assert!(!breaker.try_acquire(1000));
+        assert_eq!(breaker.state(1000), CircuitState::HalfOpen);
+        breaker.record_success(1100);
+        breaker.record_failure(1200);
+        assert_eq!(breaker.state(1200), CircuitState::Open);
+        assert_eq!(breaker.next_probe_ms(1200), Some(2200));
+
+        let metrics = breaker.metrics();
+        assert_eq!(metrics.opened, 2);
+        assert_eq!(metrics.rejected, 2);
+        assert_eq!(metrics.successes, 1);
+        assert_eq!(metrics.failures, 2);
+    }
+
+    #[test]
+    fn test_lost_probe_released() {
+        let breaker = CircuitBreaker::new(0.5, 1000).with_min_requests(1);
+        let clone = breaker.clone();
+        fail_n(&breaker, 1, 0);
+
+        assert!(clone.try_acquire(1000));
+        assert!(!breaker.try_acquire(1500));
+        // The probe never reported back
+        assert!(breaker.try_acquire(2000));
+        clone.record_success(2100);
+        assert_eq!(breaker.state(2100), CircuitState::Closed);
+    }
+}
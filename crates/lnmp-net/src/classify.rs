@@ -0,0 +1,433 @@
+//! Message kind inference
+//!
+//! Producers do not always tag their messages. [`KindClassifier`] infers a
+//! [`MessageKind`] from the record itself:
+//!
+//! 1. **Rules**: application [`ContentMatch`] rules, checked in order; the first
+//!    match decides with full confidence.
+//! 2. **Field roles**: field names from a [`SemanticDictionary`] (or explicit
+//!    per-field hints) such as `command`, `status` or `severity` vote for a kind.
+//! 3. **Value patterns**: string values vote too, e.g. a trailing `?` for a
+//!    Query, a leading imperative verb for a Command, `critical` for an Alert.
+//!
+//! The kind with the most votes wins. Its confidence is its vote weight divided
+//! by the total weight plus one, so a single weak signal never reaches 1.0.
+//! Without any signal the default kind (Event) is returned with confidence 0.0.
+
+use std::collections::HashMap;
+
+use lnmp_core::{FieldId, LnmpRecord, LnmpRecordView, LnmpValue, LnmpValueView};
+use lnmp_sfe::SemanticDictionary;
+
+use crate::content_routing::ContentMatch;
+use crate::kind::MessageKind;
+
+/// Vote weight of a field role (name or hint)
+const ROLE_WEIGHT: f64 = 2.0;
+/// Vote weight of a string value pattern
+const VALUE_WEIGHT: f64 = 1.0;
+
+/// Field-name words that suggest a kind
+const ROLE_WORDS: &[(MessageKind, &[&str])] = &[
+    (
+        MessageKind::Alert,
+        &[
+            "alert",
+            "alarm",
+            "severity",
+            "fault",
+            "incident",
+            "emergency",
+        ],
+    ),
+    (
+        MessageKind::Command,
+        &[
+            "command",
+            "cmd",
+            "action",
+            "instruction",
+            "operation",
+            "setpoint",
+        ],
+    ),
+    (
+        MessageKind::Query,
+        &["query", "question", "request", "lookup", "search"],
+    ),
+    (
+        MessageKind::State,
+        &["state", "status", "health", "mode", "uptime", "snapshot"],
+    ),
+    (
+        MessageKind::Event,
+        &[
+            "event",
+            "reading",
+            "measurement",
+            "sensor",
+            "telemetry",
+            "sample",
+        ],
+    ),
+];
+
+/// Leading words of an imperative value ("restart service")
+const COMMAND_VERBS: &[&str] = &[
+    "start", "stop", "restart", "set", "deploy", "enable", "disable", "open", "close", "reset",
+    "run", "execute", "shutdown", "move", "turn",
+];
+
+/// Leading words of a request for information ("get temperature")
+const QUERY_WORDS: &[&str] = &[
+    "what", "which", "when", "where", "who", "why", "how", "get", "list", "find", "show",
+];
+
+/// Values reporting a critical condition
+const ALERT_VALUES: &[&str] = &["critical", "emergency", "fatal", "alarm", "panic"];
+
+/// Values describing a steady state
+const STATE_VALUES: &[&str] = &[
+    "online", "offline", "ok", "healthy", "degraded", "idle", "running", "active", "inactive",
+    "ready", "up", "down",
+];
+
+/// Outcome of [`KindClassifier::classify`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    /// The inferred kind
+    pub kind: MessageKind,
+    /// Confidence in `kind` (0.0-1.0)
+    pub confidence: f64,
+    /// Name of the rule that decided, if one matched
+    pub rule: Option<String>,
+}
+
+/// A named classification rule: records matching `matcher` are of `kind`
+#[derive(Debug, Clone)]
+pub struct KindRule {
+    /// Rule name reported in [`Classification::rule`]
+    pub name: String,
+    /// Conditions of the rule
+    pub matcher: ContentMatch,
+    /// Kind assigned when the rule matches
+    pub kind: MessageKind,
+}
+
+/// Infers the [`MessageKind`] of untagged records
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+/// use lnmp_net::{KindClassifier, MessageKind};
+/// use lnmp_sfe::SemanticDictionary;
+///
+/// let mut dictionary = SemanticDictionary::new();
+/// dictionary.add_field_name(20, "command".to_string());
+/// let classifier = KindClassifier::new().with_dictionary(dictionary);
+///
+/// let mut record = LnmpRecord::new();
+/// record.add_field(LnmpField {
+///     fid: 20,
+///     value: LnmpValue::String("restart pump-3".to_string()),
+/// });
+///
+/// let classification = classifier.classify(&record);
+/// assert_eq!(classification.kind, MessageKind::Command);
+/// assert!(classification.confidence > 0.7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KindClassifier {
+    dictionary: Option<SemanticDictionary>,
+    field_kinds: HashMap<FieldId, MessageKind>,
+    rules: Vec<KindRule>,
+    default_kind: MessageKind,
+}
+
+impl KindClassifier {
+    /// Creates a classifier using value patterns only
+    pub fn new() -> Self {
+        Self {
+            dictionary: None,
+            field_kinds: HashMap::new(),
+            rules: Vec::new(),
+            default_kind: MessageKind::Event,
+        }
+    }
+
+    /// Uses the field names of `dictionary` as field roles
+    pub fn with_dictionary(mut self, dictionary: SemanticDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Declares that field `fid` suggests `kind`, overriding its dictionary name
+    pub fn with_field_kind(mut self, fid: FieldId, kind: MessageKind) -> Self {
+        self.field_kinds.insert(fid, kind);
+        self
+    }
+
+    /// Adds a rule checked before the heuristics (rules are checked in order)
+    pub fn with_rule(
+        mut self,
+        name: impl Into<String>,
+        matcher: ContentMatch,
+        kind: MessageKind,
+    ) -> Self {
+        self.rules.push(KindRule {
+            name: name.into(),
+            matcher,
+            kind,
+        });
+        self
+    }
+
+    /// Sets the kind returned when no signal is found (default: Event)
+    pub fn with_default_kind(mut self, kind: MessageKind) -> Self {
+        self.default_kind = kind;
+        self
+    }
+
+    /// Classifies a record
+    pub fn classify(&self, record: &LnmpRecord) -> Classification {
+        if let Some(rule) = self.rules.iter().find(|rule| rule.matcher.matches(record)) {
+            return Self::from_rule(rule);
+        }
+        self.score(record.fields().iter().map(|field| {
+            let text = match &field.value {
+                LnmpValue::String(s) => Some(s.as_str()),
+                _ => None,
+            };
+            (field.fid, text)
+        }))
+    }
+
+    /// Classifies a zero-copy record view
+    pub fn classify_view(&self, record_view: &LnmpRecordView) -> Classification {
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.matcher.matches_view(record_view))
+        {
+            return Self::from_rule(rule);
+        }
+        self.score(record_view.fields().iter().map(|field| {
+            let text = match &field.value {
+                LnmpValueView::String(s) => Some(*s),
+                _ => None,
+            };
+            (field.fid, text)
+        }))
+    }
+
+    fn from_rule(rule: &KindRule) -> Classification {
+        Classification {
+            kind: rule.kind,
+            confidence: 1.0,
+            rule: Some(rule.name.clone()),
+        }
+    }
+
+    /// Tallies role and value votes over `(fid, string value)` pairs
+    fn score<'a>(
+        &self,
+        fields: impl Iterator<Item = (FieldId, Option<&'a str>)>,
+    ) -> Classification {
+        let mut votes: HashMap<MessageKind, f64> = HashMap::new();
+        for (fid, text) in fields {
+            if let Some(kind) = self.field_role(fid) {
+                *votes.entry(kind).or_default() += ROLE_WEIGHT;
+            }
+            if let Some(kind) = text.and_then(value_pattern) {
+                *votes.entry(kind).or_default() += VALUE_WEIGHT;
+            }
+        }
+
+        let total: f64 = votes.values().sum();
+        // Ties go to the kind listed first in `MessageKind::all`
+        let winner = MessageKind::all()
+            .into_iter()
+            .filter_map(|kind| votes.get(&kind).map(|weight| (kind, *weight)))
+            .fold(
+                None,
+                |best: Option<(MessageKind, f64)>, (kind, weight)| match best {
+                    Some((_, best_weight)) if best_weight >= weight => best,
+                    _ => Some((kind, weight)),
+                },
+            );
+        match winner {
+            Some((kind, weight)) => Classification {
+                kind,
+                confidence: weight / (total + 1.0),
+                rule: None,
+            },
+            None => Classification {
+                kind: self.default_kind,
+                confidence: 0.0,
+                rule: None,
+            },
+        }
+    }
+
+    fn field_role(&self, fid: FieldId) -> Option<MessageKind> {
+        if let Some(kind) = self.field_kinds.get(&fid) {
+            return Some(*kind);
+        }
+        let name = self.dictionary.as_ref()?.get_field_name(fid)?;
+        let words: Vec<String> = name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect();
+        ROLE_WORDS
+            .iter()
+            .find(|(_, role_words)| words.iter().any(|word| role_words.contains(&word.as_str())))
+            .map(|(kind, _)| *kind)
+    }
+}
+
+impl Default for KindClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kind suggested by a string value, if any
+fn value_pattern(text: &str) -> Option<MessageKind> {
+    let text = text.trim();
+    let lower = text.to_ascii_lowercase();
+    if ALERT_VALUES.contains(&lower.as_str()) {
+        return Some(MessageKind::Alert);
+    }
+    if STATE_VALUES.contains(&lower.as_str()) {
+        return Some(MessageKind::State);
+    }
+    if text.ends_with('?') {
+        return Some(MessageKind::Query);
+    }
+    let first = lower.split_whitespace().next()?;
+    // A lone verb ("stop") is as likely a label as an instruction
+    let has_object = lower.split_whitespace().nth(1).is_some();
+    if QUERY_WORDS.contains(&first) && has_object {
+        Some(MessageKind::Query)
+    } else if COMMAND_VERBS.contains(&first) && has_object {
+        Some(MessageKind::Command)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_routing::FieldCondition;
+    use lnmp_core::{LnmpField, LnmpFieldView};
+
+    fn record(fields: &[(FieldId, LnmpValue)]) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        for (fid, value) in fields {
+            record.add_field(LnmpField {
+                fid: *fid,
+                value: value.clone(),
+            });
+        }
+        record
+    }
+
+    fn text(s: &str) -> LnmpValue {
+        LnmpValue::String(s.to_string())
+    }
+
+    fn dictionary() -> SemanticDictionary {
+        let mut dictionary = SemanticDictionary::new();
+        dictionary.add_field_name(1, "device_status".to_string());
+        dictionary.add_field_name(2, "alarm-level".to_string());
+        dictionary.add_field_name(3, "temperature".to_string());
+        dictionary
+    }
+
+    #[test]
+    fn test_value_patterns() {
+        let classifier = KindClassifier::new();
+        for (value, expected) in [
+            ("What is the pump pressure?", MessageKind::Query),
+            ("get temperature", MessageKind::Query),
+            ("restart pump-3", MessageKind::Command),
+            ("CRITICAL", MessageKind::Alert),
+            ("degraded", MessageKind::State),
+        ] {
+            let classification = classifier.classify(&record(&[(9, text(value))]));
+            assert_eq!(classification.kind, expected, "{value}");
+            assert!((classification.confidence - 0.5).abs() < 1e-9);
+        }
+
+        // No signal: the default kind with no confidence
+        let classification =
+            classifier.classify(&record(&[(9, text("stop")), (10, LnmpValue::Int(4))]));
+        assert_eq!(classification.kind, MessageKind::Event);
+        assert_eq!(classification.confidence, 0.0);
+        let state_default = KindClassifier::new().with_default_kind(MessageKind::State);
+        assert_eq!(
+            state_default.classify(&LnmpRecord::new()).kind,
+            MessageKind::State
+        );
+    }
+
+    #[test]
+    fn test_dictionary_roles_outweigh_values() {
+        let classifier = KindClassifier::new().with_dictionary(dictionary());
+
+        // Status field (2.0) beats a query-looking value (1.0)
+        let status = classifier.classify(&record(&[(1, text("which one?"))]));
+        assert_eq!(status.kind, MessageKind::State);
+        assert!((status.confidence - 0.5).abs() < 1e-9);
+
+        // Agreeing signals raise confidence: alarm field + critical value
+        let alarm = classifier.classify(&record(&[
+            (2, text("critical")),
+            (3, LnmpValue::Float(91.5)),
+        ]));
+        assert_eq!(alarm.kind, MessageKind::Alert);
+        assert!((alarm.confidence - 0.75).abs() < 1e-9);
+
+        // Explicit hints override dictionary names
+        let hinted = classifier.with_field_kind(1, MessageKind::Event);
+        assert_eq!(
+            hinted.classify(&record(&[(1, LnmpValue::Int(1))])).kind,
+            MessageKind::Event
+        );
+    }
+
+    #[test]
+    fn test_rules_take_precedence() {
+        let classifier = KindClassifier::new()
+            .with_dictionary(dictionary())
+            .with_rule(
+                "overheat",
+                ContentMatch::Field(3, FieldCondition::NumberGreaterThan(90.0)),
+                MessageKind::Alert,
+            );
+
+        let hot = record(&[(1, text("running")), (3, LnmpValue::Float(95.0))]);
+        let classification = classifier.classify(&hot);
+        assert_eq!(classification.kind, MessageKind::Alert);
+        assert_eq!(classification.confidence, 1.0);
+        assert_eq!(classification.rule.as_deref(), Some("overheat"));
+
+        let view = LnmpRecordView::from_fields(vec![
+            LnmpFieldView {
+                fid: 1,
+                value: LnmpValueView::String("running"),
+            },
+            LnmpFieldView {
+                fid: 3,
+                value: LnmpValueView::Float(20.0),
+            },
+        ]);
+        let classification = classifier.classify_view(&view);
+        assert_eq!(classification.kind, MessageKind::State);
+        assert_eq!(classification.rule, None);
+        assert_eq!(classification, classifier.classify(&view.to_lnmp_record()));
+    }
+}
@@ -0,0 +1,264 @@
+//! Record complexity scoring for routing decisions
+//!
+//! Commands and Queries are processed locally unless their payload is complex.
+//! [`RecordComplexity`] measures a record along four dimensions and
+//! [`RecordComplexity::score`] maps them to a 0.0-1.0 score against a
+//! [`ComplexityConfig`] budget. The score is the largest of the per-dimension
+//! ratios, so one oversized dimension (e.g. a 500-element array) is enough to
+//! make a record complex.
+
+use lnmp_core::{LnmpRecord, LnmpRecordView, LnmpValue, LnmpValueView};
+
+/// Budgets at which each complexity dimension reaches a score of 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplexityConfig {
+    /// Total field count, including fields of nested records
+    pub max_fields: usize,
+    /// Nesting depth (0 for a flat record)
+    pub max_depth: usize,
+    /// Element count of the largest array
+    pub max_array_len: usize,
+    /// Estimated LLM tokens for the record in text form
+    pub max_tokens: usize,
+}
+
+impl Default for ComplexityConfig {
+    fn default() -> Self {
+        Self {
+            max_fields: 32,
+            max_depth: 3,
+            max_array_len: 64,
+            max_tokens: 512,
+        }
+    }
+}
+
+/// Size and shape measurements of a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RecordComplexity {
+    /// Total field count, including fields of nested records
+    pub field_count: usize,
+    /// Nesting depth (0 for a flat record)
+    pub nesting_depth: usize,
+    /// Element count of the largest array (nested arrays count records)
+    pub largest_array: usize,
+    /// Estimated LLM tokens for the record in text form
+    pub estimated_tokens: usize,
+}
+
+impl RecordComplexity {
+    /// Measures a record
+    pub fn of(record: &LnmpRecord) -> Self {
+        let mut complexity = Self::default();
+        for field in record.fields() {
+            complexity.add_value(&field.value, 0);
+        }
+        complexity
+    }
+
+    /// Measures a zero-copy record view
+    pub fn of_view(record: &LnmpRecordView<'_>) -> Self {
+        let mut complexity = Self::default();
+        for field in record.fields() {
+            complexity.add_value_view(&field.value, 0);
+        }
+        complexity
+    }
+
+    /// Computes the complexity score (0.0-1.0) against `config`
+    ///
+    /// # Formula
+    ///
+    /// ```text
+    /// score = max(fields / max_fields, depth / max_depth,
+    ///             largest_array / max_array_len, tokens / max_tokens), capped at 1.0
+    /// ```
+    pub fn score(&self, config: &ComplexityConfig) -> f64 {
+        [
+            ratio(self.field_count, config.max_fields),
+            ratio(self.nesting_depth, config.max_depth),
+            ratio(self.largest_array, config.max_array_len),
+            ratio(self.estimated_tokens, config.max_tokens),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max)
+    }
+
+    fn add_field(&mut self, depth: usize, value_tokens: usize) {
+        self.field_count += 1;
+        self.nesting_depth = self.nesting_depth.max(depth);
+        // `F<fid>=` plus a separator
+        self.estimated_tokens += 2 + value_tokens;
+    }
+
+    fn add_array(&mut self, len: usize) {
+        self.largest_array = self.largest_array.max(len);
+    }
+
+    fn add_value(&mut self, value: &LnmpValue, depth: usize) {
+        let tokens = match value {
+            LnmpValue::Int(_) | LnmpValue::Float(_) | LnmpValue::Bool(_) => 1,
+            LnmpValue::String(s) => string_tokens(s),
+            LnmpValue::StringArray(items) => {
+                self.add_array(items.len());
+                items.iter().map(|s| string_tokens(s)).sum::<usize>() + 1
+            }
+            LnmpValue::IntArray(items) => self.array_tokens(items.len()),
+            LnmpValue::FloatArray(items) => self.array_tokens(items.len()),
+            LnmpValue::BoolArray(items) => self.array_tokens(items.len()),
+            LnmpValue::NestedRecord(record) => {
+                for field in record.fields() {
+                    self.add_value(&field.value, depth + 1);
+                }
+                1
+            }
+            LnmpValue::NestedArray(records) => {
+                self.add_array(records.len());
+                for record in records {
+                    for field in record.fields() {
+                        self.add_value(&field.value, depth + 1);
+                    }
+                }
+                records.len() + 1
+            }
+            LnmpValue::Embedding(vector) => vector.dim as usize,
+            _ => 1,
+        };
+        self.add_field(depth, tokens);
+    }
+
+    fn add_value_view(&mut self, value: &LnmpValueView<'_>, depth: usize) {
+        let tokens = match value {
+            LnmpValueView::Int(_) | LnmpValueView::Float(_) | LnmpValueView::Bool(_) => 1,
+            LnmpValueView::String(s) => string_tokens(s),
+            LnmpValueView::StringArray(items) => {
+                self.add_array(items.len());
+                items.iter().map(|s| string_tokens(s)).sum::<usize>() + 1
+            }
+            LnmpValueView::IntArray(items) => self.array_tokens(items.len()),
+            LnmpValueView::FloatArray(items) => self.array_tokens(items.len()),
+            LnmpValueView::BoolArray(items) => self.array_tokens(items.len()),
+            LnmpValueView::NestedRecord(record) => {
+                for field in record.fields() {
+                    self.add_value_view(&field.value, depth + 1);
+                }
+                1
+            }
+            LnmpValueView::NestedArray(records) => {
+                self.add_array(records.len());
+                for record in records {
+                    for field in record.fields() {
+                        self.add_value_view(&field.value, depth + 1);
+                    }
+                }
+                records.len() + 1
+            }
+            // Raw encoded bytes; roughly one token per 4-byte element
+            LnmpValueView::Embedding(bytes) => bytes.len() / 4,
+        };
+        self.add_field(depth, tokens);
+    }
+
+    /// Records a numeric/boolean array and returns its token estimate
+    fn array_tokens(&mut self, len: usize) -> usize {
+        self.add_array(len);
+        len + 1
+    }
+}
+
+/// Computes the complexity score of `record` against `config`
+pub fn complexity_score(record: &LnmpRecord, config: &ComplexityConfig) -> f64 {
+    RecordComplexity::of(record).score(config)
+}
+
+/// Token estimate for a string value (about 4 characters per token)
+fn string_tokens(s: &str) -> usize {
+    s.chars().count().div_ceil(4).max(1)
+}
+
+fn ratio(value: usize, budget: usize) -> f64 {
+    if budget == 0 {
+        return if value == 0 { 0.0 } else { 1.0 };
+    }
+    (value as f64 / budget as f64).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::LnmpField;
+
+    fn field(fid: u16, value: LnmpValue) -> LnmpField {
+        LnmpField { fid, value }
+    }
+
+    #[test]
+    fn test_measure_flat_record() {
+        let mut record = LnmpRecord::new();
+        record.add_field(field(1, LnmpValue::Int(42)));
+        record.add_field(field(2, LnmpValue::String("hello world!".to_string())));
+        record.add_field(field(3, LnmpValue::IntArray(vec![1, 2, 3])));
+
+        let complexity = RecordComplexity::of(&record);
+        assert_eq!(complexity.field_count, 3);
+        assert_eq!(complexity.nesting_depth, 0);
+        assert_eq!(complexity.largest_array, 3);
+        // (2 + 1) + (2 + 3) + (2 + 4)
+        assert_eq!(complexity.estimated_tokens, 14);
+    }
+
+    #[test]
+    fn test_measure_nested_record() {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(field(1, LnmpValue::Bool(true)));
+        let mut middle = LnmpRecord::new();
+        middle.add_field(field(2, LnmpValue::NestedArray(vec![inner.clone(), inner])));
+        let mut record = LnmpRecord::new();
+        record.add_field(field(3, LnmpValue::NestedRecord(Box::new(middle))));
+
+        let complexity = RecordComplexity::of(&record);
+        assert_eq!(complexity.field_count, 4);
+        assert_eq!(complexity.nesting_depth, 2);
+        assert_eq!(complexity.largest_array, 2);
+    }
+
+    #[test]
+    fn test_score_is_largest_dimension() {
+        let config = ComplexityConfig::default();
+        let small = RecordComplexity {
+            field_count: 8,
+            nesting_depth: 0,
+            largest_array: 0,
+            estimated_tokens: 24,
+        };
+        assert!((small.score(&config) - 0.25).abs() < 1e-9);
+
+        let big_array = RecordComplexity {
+            largest_array: 1000,
+            ..small
+        };
+        assert_eq!(big_array.score(&config), 1.0);
+    }
+
+    #[test]
+    fn test_view_matches_owned_record() {
+        let mut record = LnmpRecord::new();
+        record.add_field(field(1, LnmpValue::String("abc".to_string())));
+        record.add_field(field(2, LnmpValue::FloatArray(vec![1.0, 2.0])));
+        let view = LnmpRecordView::from_fields(vec![
+            lnmp_core::LnmpFieldView {
+                fid: 1,
+                value: LnmpValueView::String("abc"),
+            },
+            lnmp_core::LnmpFieldView {
+                fid: 2,
+                value: LnmpValueView::FloatArray(vec![1.0, 2.0]),
+            },
+        ]);
+
+        assert_eq!(
+            RecordComplexity::of(&record),
+            RecordComplexity::of_view(&view)
+        );
+    }
+}
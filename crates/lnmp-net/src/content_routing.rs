@@ -3,24 +3,38 @@
 //! This module extends the base routing policy with content-based decision making
 //! using zero-copy record views.
 
+use crate::error::NetError;
+use crate::hashing::{self, HashAlgorithm, Hasher};
 use crate::{Result, RoutingDecision};
-use lnmp_core::{FieldId, LnmpRecordView, LnmpValueView};
+use lnmp_core::{FieldId, LnmpRecord, LnmpRecordView, LnmpValue, LnmpValueView};
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::sync::Arc;
 
 /// A content-based routing rule.
 ///
 /// Inspects specific fields in the record to make routing decisions.
 #[derive(Debug, Clone)]
 pub struct ContentRule {
-    /// Field ID to inspect
-    pub field_id: FieldId,
-    /// Condition to check
-    pub condition: FieldCondition,
-    /// Routing decision if condition matches
+    /// Conditions to check
+    pub matcher: ContentMatch,
+    /// Routing decision if the conditions match
     pub on_match: RoutingDecision,
     /// Description of this rule (for debugging)
     pub description: String,
 }
 
+/// Conditions a [`ContentRule`] checks, composed with AND/OR.
+#[derive(Debug, Clone)]
+pub enum ContentMatch {
+    /// Condition on one top-level field
+    Field(FieldId, FieldCondition),
+    /// All conditions match (AND)
+    All(Vec<ContentMatch>),
+    /// At least one condition matches (OR)
+    Any(Vec<ContentMatch>),
+}
+
 /// Field condition for content-based routing.
 #[derive(Debug, Clone)]
 pub enum FieldCondition {
@@ -30,30 +44,208 @@ pub enum FieldCondition {
     StringContains(String),
     /// String field matches any of the values
     StringIn(Vec<String>),
+    /// String field matches a regular expression (see [`FieldCondition::regex`])
+    #[cfg(feature = "regex")]
+    Regex(Regex),
     /// Integer field in range [min, max] (inclusive)
     IntInRange(i64, i64),
     /// Integer field greater than threshold
     IntGreaterThan(i64),
     /// Integer field less than threshold
     IntLessThan(i64),
+    /// Integer or float field in range [min, max] (inclusive)
+    NumberInRange(f64, f64),
+    /// Integer or float field greater than threshold
+    NumberGreaterThan(f64),
+    /// Integer or float field less than threshold
+    NumberLessThan(f64),
+    /// String array field contains the value
+    ArrayContains(String),
+    /// Integer array field contains the value
+    IntArrayContains(i64),
+    /// Condition on a field of the nested record, following the path of field IDs
+    ///
+    /// Nested arrays match when any of their records does.
+    Nested(Vec<FieldId>, Box<FieldCondition>),
     /// Field exists (any value)
     Exists,
     /// Field does not exist
     NotExists,
 }
 
+impl FieldCondition {
+    /// Creates a regular-expression condition, failing on an invalid pattern.
+    ///
+    /// Without the `regex` feature every pattern is rejected.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        #[cfg(feature = "regex")]
+        {
+            Regex::new(pattern)
+                .map(FieldCondition::Regex)
+                .map_err(|e| NetError::InvalidCondition(e.to_string()))
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            let _ = pattern;
+            Err(NetError::InvalidCondition(
+                "regular expressions require the `regex` feature".to_string(),
+            ))
+        }
+    }
+
+    /// Creates a condition on the nested field at `path`.
+    pub fn nested(path: Vec<FieldId>, condition: FieldCondition) -> Self {
+        FieldCondition::Nested(path, Box::new(condition))
+    }
+
+    /// Checks the condition against a field value (`None` when the field is absent).
+    pub fn matches(&self, value: Option<&LnmpValue>) -> bool {
+        let value = match (self, value) {
+            (FieldCondition::NotExists, value) => return value.is_none(),
+            (FieldCondition::Exists, value) => return value.is_some(),
+            (FieldCondition::Nested(path, condition), value) => {
+                return nested_matches(path, condition, value)
+            }
+            (_, Some(value)) => value,
+            (_, None) => return false,
+        };
+        match (self, value) {
+            (FieldCondition::StringEquals(target), LnmpValue::String(s)) => s == target,
+            (FieldCondition::StringContains(substr), LnmpValue::String(s)) => {
+                s.contains(substr.as_str())
+            }
+            (FieldCondition::StringIn(values), LnmpValue::String(s)) => values.contains(s),
+            #[cfg(feature = "regex")]
+            (FieldCondition::Regex(re), LnmpValue::String(s)) => re.is_match(s),
+            (FieldCondition::ArrayContains(target), LnmpValue::StringArray(items)) => {
+                items.contains(target)
+            }
+            (FieldCondition::IntArrayContains(target), LnmpValue::IntArray(items)) => {
+                items.contains(target)
+            }
+            (_, LnmpValue::Int(i)) => self.matches_number(Some(*i), *i as f64),
+            (_, LnmpValue::Float(f)) => self.matches_number(None, *f),
+            _ => false, // Type mismatch or condition doesn't apply
+        }
+    }
+
+    /// Checks the condition against a field value view (zero-copy).
+    pub fn matches_view(&self, value: Option<&LnmpValueView>) -> bool {
+        let value = match (self, value) {
+            (FieldCondition::NotExists, value) => return value.is_none(),
+            (FieldCondition::Exists, value) => return value.is_some(),
+            (FieldCondition::Nested(path, condition), value) => {
+                return nested_view_matches(path, condition, value)
+            }
+            (_, Some(value)) => value,
+            (_, None) => return false,
+        };
+        match (self, value) {
+            (FieldCondition::StringEquals(target), LnmpValueView::String(s)) => {
+                *s == target.as_str()
+            }
+            (FieldCondition::StringContains(substr), LnmpValueView::String(s)) => {
+                s.contains(substr.as_str())
+            }
+            (FieldCondition::StringIn(values), LnmpValueView::String(s)) => {
+                values.iter().any(|v| v.as_str() == *s)
+            }
+            #[cfg(feature = "regex")]
+            (FieldCondition::Regex(re), LnmpValueView::String(s)) => re.is_match(s),
+            (FieldCondition::ArrayContains(target), LnmpValueView::StringArray(items)) => {
+                items.contains(&target.as_str())
+            }
+            (FieldCondition::IntArrayContains(target), LnmpValueView::IntArray(items)) => {
+                items.contains(target)
+            }
+            (_, LnmpValueView::Int(i)) => self.matches_number(Some(*i), *i as f64),
+            (_, LnmpValueView::Float(f)) => self.matches_number(None, *f),
+            _ => false, // Type mismatch or condition doesn't apply
+        }
+    }
+
+    /// Numeric conditions; integer conditions only apply to integer values.
+    fn matches_number(&self, int: Option<i64>, number: f64) -> bool {
+        match (self, int) {
+            (FieldCondition::IntInRange(min, max), Some(i)) => i >= *min && i <= *max,
+            (FieldCondition::IntGreaterThan(threshold), Some(i)) => i > *threshold,
+            (FieldCondition::IntLessThan(threshold), Some(i)) => i < *threshold,
+            (FieldCondition::NumberInRange(min, max), _) => number >= *min && number <= *max,
+            (FieldCondition::NumberGreaterThan(threshold), _) => number > *threshold,
+            (FieldCondition::NumberLessThan(threshold), _) => number < *threshold,
+            _ => false,
+        }
+    }
+}
+
+impl ContentMatch {
+    /// Checks the conditions against a record.
+    pub fn matches(&self, record: &LnmpRecord) -> bool {
+        match self {
+            ContentMatch::Field(fid, condition) => {
+                condition.matches(record.get_field(*fid).map(|field| &field.value))
+            }
+            ContentMatch::All(matches) => matches.iter().all(|m| m.matches(record)),
+            ContentMatch::Any(matches) => matches.iter().any(|m| m.matches(record)),
+        }
+    }
+
+    /// Checks the conditions against a record view (zero-copy).
+    pub fn matches_view(&self, record_view: &LnmpRecordView) -> bool {
+        match self {
+            ContentMatch::Field(fid, condition) => {
+                condition.matches_view(record_view.get_field(*fid).map(|field| &field.value))
+            }
+            ContentMatch::All(matches) => matches.iter().all(|m| m.matches_view(record_view)),
+            ContentMatch::Any(matches) => matches.iter().any(|m| m.matches_view(record_view)),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let join = |matches: &[ContentMatch], op: &str| {
+            let parts: Vec<String> = matches.iter().map(ContentMatch::describe).collect();
+            format!("({})", parts.join(op))
+        };
+        match self {
+            ContentMatch::Field(fid, condition) => format!("F{}: {:?}", fid, condition),
+            ContentMatch::All(matches) => join(matches, " AND "),
+            ContentMatch::Any(matches) => join(matches, " OR "),
+        }
+    }
+}
+
 impl ContentRule {
     /// Creates a new content rule.
     pub fn new(field_id: FieldId, condition: FieldCondition, on_match: RoutingDecision) -> Self {
-        let description = format!("F{}: {:?} → {:?}", field_id, condition, on_match);
+        Self::matching(ContentMatch::Field(field_id, condition), on_match)
+    }
+
+    /// Creates a rule from composed conditions.
+    pub fn matching(matcher: ContentMatch, on_match: RoutingDecision) -> Self {
+        let description = format!("{} → {:?}", matcher.describe(), on_match);
         Self {
-            field_id,
-            condition,
+            matcher,
             on_match,
             description,
         }
     }
 
+    /// Creates a rule matching when all `rules` match; their own decisions are ignored.
+    pub fn all(rules: Vec<ContentRule>, on_match: RoutingDecision) -> Self {
+        Self::matching(
+            ContentMatch::All(rules.into_iter().map(|rule| rule.matcher).collect()),
+            on_match,
+        )
+    }
+
+    /// Creates a rule matching when any of `rules` matches; their own decisions are ignored.
+    pub fn any(rules: Vec<ContentRule>, on_match: RoutingDecision) -> Self {
+        Self::matching(
+            ContentMatch::Any(rules.into_iter().map(|rule| rule.matcher).collect()),
+            on_match,
+        )
+    }
+
     /// Creates a rule that routes critical status to LLM.
     pub fn critical_status_to_llm(field_id: FieldId) -> Self {
         Self::new(
@@ -74,51 +266,55 @@ impl ContentRule {
 
     /// Checks if this rule matches the given record view.
     ///
-    /// Returns `Some(decision)` if the condition matches, `None` otherwise.
+    /// Returns `Some(decision)` if the conditions match, `None` otherwise.
     pub fn check(&self, record_view: &LnmpRecordView) -> Result<Option<RoutingDecision>> {
-        match record_view.get_field(self.field_id) {
-            Some(field) => {
-                if self.check_condition(&field.value)? {
-                    Ok(Some(self.on_match))
-                } else {
-                    Ok(None)
-                }
-            }
-            None => {
-                // Field doesn't exist
-                if matches!(self.condition, FieldCondition::NotExists) {
-                    Ok(Some(self.on_match))
-                } else {
-                    Ok(None)
-                }
-            }
-        }
+        Ok(self
+            .matcher
+            .matches_view(record_view)
+            .then_some(self.on_match))
     }
+}
 
-    fn check_condition(&self, value: &LnmpValueView) -> Result<bool> {
-        match (&self.condition, value) {
-            (FieldCondition::StringEquals(target), LnmpValueView::String(s)) => {
-                Ok(*s == target.as_str())
-            }
-            (FieldCondition::StringContains(substr), LnmpValueView::String(s)) => {
-                Ok(s.contains(substr.as_str()))
-            }
-            (FieldCondition::StringIn(values), LnmpValueView::String(s)) => {
-                Ok(values.iter().any(|v| v.as_str() == *s))
-            }
-            (FieldCondition::IntInRange(min, max), LnmpValueView::Int(i)) => {
-                Ok(*i >= *min && *i <= *max)
-            }
-            (FieldCondition::IntGreaterThan(threshold), LnmpValueView::Int(i)) => {
-                Ok(*i > *threshold)
-            }
-            (FieldCondition::IntLessThan(threshold), LnmpValueView::Int(i)) => Ok(*i < *threshold),
-            (FieldCondition::Exists, _) => Ok(true),
-            _ => Ok(false), // Type mismatch or condition doesn't apply
-        }
+/// Follows `path` through nested records; a missing field ends the walk with `None`.
+fn nested_matches(path: &[FieldId], condition: &FieldCondition, value: Option<&LnmpValue>) -> bool {
+    let Some((fid, rest)) = path.split_first() else {
+        return condition.matches(value);
+    };
+    let field = |record: &LnmpRecord| {
+        record
+            .get_field(*fid)
+            .map(|f| nested_matches(rest, condition, Some(&f.value)))
+            .unwrap_or_else(|| condition.matches(None))
+    };
+    match value {
+        None => condition.matches(None),
+        Some(LnmpValue::NestedRecord(record)) => field(record),
+        Some(LnmpValue::NestedArray(records)) => records.iter().any(field),
+        Some(_) => false,
     }
 }
 
+fn nested_view_matches(
+    path: &[FieldId],
+    condition: &FieldCondition,
+    value: Option<&LnmpValueView>,
+) -> bool {
+    let Some((fid, rest)) = path.split_first() else {
+        return condition.matches_view(value);
+    };
+    let field = |record: &LnmpRecordView| {
+        record
+            .get_field(*fid)
+            .map(|f| nested_view_matches(rest, condition, Some(&f.value)))
+            .unwrap_or_else(|| condition.matches_view(None))
+    };
+    match value {
+        None => condition.matches_view(None),
+        Some(LnmpValueView::NestedRecord(record)) => field(record),
+        Some(LnmpValueView::NestedArray(records)) => records.iter().any(field),
+        Some(_) => false,
+    }
+}
 /// Content-aware routing policy builder.
 ///
 /// Extends the base routing policy with content-based rules.
@@ -132,6 +328,10 @@ pub struct ContentAwarePolicy {
     pub drop_expired: bool,
     /// Whether to always route high-priority alerts
     pub always_route_alerts: bool,
+    /// Hash function for routing keys (default: [`HashAlgorithm::Fnv1a`])
+    pub hasher: Arc<dyn Hasher>,
+    /// Fields that make up the routing key (all fields when empty)
+    pub key_fields: Vec<FieldId>,
 }
 
 impl ContentAwarePolicy {
@@ -142,6 +342,8 @@ impl ContentAwarePolicy {
             content_rules: Vec::new(),
             drop_expired: true,
             always_route_alerts: true,
+            hasher: Arc::new(HashAlgorithm::default()),
+            key_fields: Vec::new(),
         }
     }
 
@@ -163,6 +365,30 @@ impl ContentAwarePolicy {
         self
     }
 
+    /// Sets the hash function used for routing keys.
+    ///
+    /// Keys from the default [`HashAlgorithm::Fnv1a`] and from `Blake3` can be
+    /// stored or shared across versions; `FxHash` trades that for throughput when
+    /// all nodes run the same build.
+    pub fn with_hasher(mut self, hasher: impl Hasher + 'static) -> Self {
+        self.hasher = Arc::new(hasher);
+        self
+    }
+
+    /// Restricts the routing key to the given fields.
+    pub fn with_key_fields(mut self, key_fields: Vec<FieldId>) -> Self {
+        self.key_fields = key_fields;
+        self
+    }
+
+    /// Computes the routing key of a record (zero-copy).
+    ///
+    /// Records with equal key fields get equal keys, so the key can be used for
+    /// sticky routing and deduplication.
+    pub fn routing_key(&self, record_view: &LnmpRecordView) -> u64 {
+        hashing::routing_key(self.hasher.as_ref(), record_view, &self.key_fields)
+    }
+
     /// Decides routing based on content inspection (zero-copy).
     ///
     /// Decision flow:
@@ -219,6 +445,15 @@ mod tests {
     use super::*;
     use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 
+    #[derive(Debug)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn hash(&self, _data: &[u8]) -> u64 {
+            7
+        }
+    }
+
     // Helper: Create a view from a record using binary encode/decode
     fn encode_decode_view<'a>(
         record: &LnmpRecord,
@@ -288,6 +523,155 @@ mod tests {
         assert_eq!(decision, Some(RoutingDecision::SendToLLM));
     }
 
+    #[test]
+    fn test_regex_and_number_conditions() {
+        let mut record = sample_record_with_status("disk-full:sda1");
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Float(0.93),
+        });
+        let mut buffer = Vec::new();
+        let view = encode_decode_view(&record, &mut buffer);
+
+        #[cfg(feature = "regex")]
+        {
+            let regex = FieldCondition::regex(r"^disk-\w+:sd[a-z]\d$").unwrap();
+            assert!(ContentRule::new(50, regex, RoutingDecision::Drop)
+                .check(&view)
+                .unwrap()
+                .is_some());
+        }
+        assert!(FieldCondition::regex("(").is_err());
+        #[cfg(not(feature = "regex"))]
+        assert!(FieldCondition::regex("^disk-").is_err());
+
+        for (condition, expected) in [
+            (FieldCondition::NumberGreaterThan(0.9), true),
+            (FieldCondition::NumberInRange(0.0, 0.5), false),
+            (FieldCondition::NumberLessThan(1.0), true),
+            // Integer conditions don't apply to floats
+            (FieldCondition::IntLessThan(1), false),
+        ] {
+            let rule = ContentRule::new(12, condition, RoutingDecision::SendToLLM);
+            assert_eq!(
+                rule.check(&view).unwrap().is_some(),
+                expected,
+                "{}",
+                rule.description
+            );
+        }
+    }
+
+    #[test]
+    fn test_array_contains_and_nested_path() {
+        use lnmp_core::LnmpFieldView;
+
+        // The v0.4 binary view decoder has no nested types, so build the view directly
+        let sensor = LnmpRecordView::from_fields(vec![LnmpFieldView {
+            fid: 3,
+            value: LnmpValueView::String("overheat"),
+        }]);
+        let view = LnmpRecordView::from_fields(vec![
+            LnmpFieldView {
+                fid: 24,
+                value: LnmpValueView::StringArray(vec!["spam", "junk"]),
+            },
+            LnmpFieldView {
+                fid: 30,
+                value: LnmpValueView::NestedArray(vec![LnmpRecordView::new(), sensor]),
+            },
+            LnmpFieldView {
+                fid: 31,
+                value: LnmpValueView::IntArray(vec![1, 2, 3]),
+            },
+        ]);
+        let owned = view.to_lnmp_record();
+
+        for (fid, condition, expected) in [
+            (24, FieldCondition::ArrayContains("spam".to_string()), true),
+            (
+                24,
+                FieldCondition::StringContains("spam".to_string()),
+                false,
+            ),
+            (31, FieldCondition::IntArrayContains(2), true),
+            (31, FieldCondition::IntArrayContains(4), false),
+            (
+                30,
+                FieldCondition::nested(vec![3], FieldCondition::StringEquals("overheat".into())),
+                true,
+            ),
+            (
+                30,
+                FieldCondition::nested(vec![3, 1], FieldCondition::Exists),
+                false,
+            ),
+            (
+                32,
+                FieldCondition::nested(vec![3], FieldCondition::NotExists),
+                true,
+            ),
+        ] {
+            let value = owned.get_field(fid).map(|f| &f.value);
+            assert_eq!(condition.matches(value), expected, "F{fid}: {condition:?}");
+            let rule = ContentRule::new(fid, condition, RoutingDecision::Drop);
+            assert_eq!(
+                rule.check(&view).unwrap().is_some(),
+                expected,
+                "{}",
+                rule.description
+            );
+        }
+    }
+
+    #[test]
+    fn test_and_or_composition() {
+        let mut record = sample_record_with_status("warning");
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(8000),
+        });
+        let mut buffer = Vec::new();
+        let view = encode_decode_view(&record, &mut buffer);
+
+        let warning = ContentRule::new(
+            50,
+            FieldCondition::StringEquals("warning".into()),
+            RoutingDecision::ProcessLocally,
+        );
+        let high_code = ContentRule::new(
+            12,
+            FieldCondition::IntGreaterThan(5000),
+            RoutingDecision::ProcessLocally,
+        );
+        let both = ContentRule::all(
+            vec![warning.clone(), high_code.clone()],
+            RoutingDecision::SendToLLM,
+        );
+        assert_eq!(both.check(&view).unwrap(), Some(RoutingDecision::SendToLLM));
+        assert!(both.description.contains(" AND "));
+
+        let critical_and_high = ContentRule::all(
+            vec![ContentRule::critical_status_to_llm(50), high_code],
+            RoutingDecision::SendToLLM,
+        );
+        assert_eq!(critical_and_high.check(&view).unwrap(), None);
+
+        let either = ContentRule::any(
+            vec![ContentRule::critical_status_to_llm(50), warning],
+            RoutingDecision::Drop,
+        );
+        assert_eq!(either.check(&view).unwrap(), Some(RoutingDecision::Drop));
+
+        let policy = ContentAwarePolicy::new()
+            .with_rule(critical_and_high)
+            .with_rule(either);
+        let decision = policy
+            .decide_content_aware(crate::kind::MessageKind::Event, 250, None, &view, 1000)
+            .unwrap();
+        assert_eq!(decision, RoutingDecision::Drop);
+    }
+
     #[test]
     fn test_content_aware_policy() {
         let policy = ContentAwarePolicy::new()
@@ -305,6 +689,30 @@ mod tests {
         assert_eq!(decision, RoutingDecision::SendToLLM);
     }
 
+    #[test]
+    fn test_routing_key_uses_selected_hasher_and_fields() {
+        let mut buffer = Vec::new();
+        let mut record = sample_record_with_status("critical");
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(42),
+        });
+        let view = encode_decode_view(&record, &mut buffer);
+
+        let fnv = ContentAwarePolicy::new().with_key_fields(vec![50]);
+        let constant = fnv.clone().with_hasher(ConstantHasher);
+        assert_ne!(fnv.routing_key(&view), constant.routing_key(&view));
+        assert_eq!(constant.routing_key(&view), 7);
+
+        let mut other_buffer = Vec::new();
+        let other = encode_decode_view(&sample_record_with_status("critical"), &mut other_buffer);
+        assert_eq!(fnv.routing_key(&view), fnv.routing_key(&other));
+        assert_ne!(
+            ContentAwarePolicy::new().routing_key(&view),
+            ContentAwarePolicy::new().routing_key(&other)
+        );
+    }
+
     #[test]
     fn test_fallback_to_priority() {
         let policy = ContentAwarePolicy::new(); // No content rules
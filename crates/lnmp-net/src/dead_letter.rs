@@ -0,0 +1,259 @@
+//! Dead-letter capture of dropped messages
+//!
+//! A [`RoutingPolicy`](crate::RoutingPolicy) configured with a
+//! [`DeadLetterSink`] hands every message it drops to the sink together with a
+//! [`DropReason`], instead of losing it silently. Pipelines can send their own
+//! losses (full queues, exhausted retries) to the same sink.
+//!
+//! With the `dlq` feature, [`FileDeadLetterSink`] appends dead letters to a
+//! JSON Lines file that [`read_dead_letters`] loads back for inspection or
+//! replay; the `dlq` example is a small CLI doing both.
+
+use std::fmt;
+
+use crate::error::Result;
+use crate::message::NetMessage;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Why a message was dead-lettered
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DropReason {
+    /// Expired before it could be routed
+    Expired,
+    /// Repeated a recent message
+    Duplicate,
+    /// The router decided to drop it
+    Policy,
+    /// A full scheduler queue refused or displaced it
+    QueueFull,
+    /// Delivery failed and retrying gave up
+    RetriesExhausted,
+    /// Application-defined reason
+    Other(String),
+}
+
+impl fmt::Display for DropReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropReason::Expired => write!(f, "expired"),
+            DropReason::Duplicate => write!(f, "duplicate"),
+            DropReason::Policy => write!(f, "policy"),
+            DropReason::QueueFull => write!(f, "queue_full"),
+            DropReason::RetriesExhausted => write!(f, "retries_exhausted"),
+            DropReason::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// A dropped message and why it was dropped
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = ""))
+)]
+pub struct DeadLetter {
+    /// Why the message was dropped
+    pub reason: DropReason,
+    /// When it was dropped (epoch milliseconds)
+    pub dropped_at_ms: u64,
+    /// The message itself
+    pub message: NetMessage,
+}
+
+impl DeadLetter {
+    /// Creates a dead letter for `message` dropped at `dropped_at_ms`
+    pub fn new(message: NetMessage, reason: DropReason, dropped_at_ms: u64) -> Self {
+        Self {
+            reason,
+            dropped_at_ms,
+            message,
+        }
+    }
+}
+
+/// Destination for dropped messages
+///
+/// Implementations must be cheap to share: a policy holds its sink behind an
+/// `Arc` and calls it from whichever thread routes.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{
+///     DeadLetter, DeadLetterSink, DropReason, MessageKind, NetMessage, Result, RoutingDecision,
+///     RoutingPolicy,
+/// };
+///
+/// #[derive(Debug, Default)]
+/// struct Collect(Mutex<Vec<DeadLetter>>);
+///
+/// impl DeadLetterSink for Collect {
+///     fn send(&self, letter: DeadLetter) -> Result<()> {
+///         self.0.lock().unwrap().push(letter);
+///         Ok(())
+///     }
+/// }
+///
+/// let sink = Arc::new(Collect::default());
+/// let policy = RoutingPolicy::default().with_dead_letter_sink(sink.clone());
+///
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(1000).build();
+/// let msg = NetMessage::with_qos(envelope, MessageKind::Event, 100, 500);
+/// assert_eq!(policy.decide(&msg, 5000).unwrap(), RoutingDecision::Drop);
+/// assert_eq!(sink.0.lock().unwrap()[0].reason, DropReason::Expired);
+/// ```
+pub trait DeadLetterSink: fmt::Debug + Send + Sync {
+    /// Stores `letter`
+    fn send(&self, letter: DeadLetter) -> Result<()>;
+}
+
+#[cfg(feature = "dlq")]
+pub use file::{read_dead_letters, FileDeadLetterSink};
+
+#[cfg(feature = "dlq")]
+mod file {
+    use std::fs::{File, OpenOptions};
+    use std::io::{BufRead, BufReader, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    use super::{DeadLetter, DeadLetterSink};
+    use crate::error::{NetError, Result};
+
+    /// Appends dead letters to a JSON Lines file, one letter per line
+    ///
+    /// Each line is flushed as it is written, so letters survive a crash of
+    /// the process that dropped them.
+    #[derive(Debug)]
+    pub struct FileDeadLetterSink {
+        path: PathBuf,
+        file: Mutex<File>,
+    }
+
+    impl FileDeadLetterSink {
+        /// Opens `path` for appending, creating it if needed
+        pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+            let path = path.as_ref().to_path_buf();
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| io_error(&path, e))?;
+            Ok(Self {
+                path,
+                file: Mutex::new(file),
+            })
+        }
+
+        /// Returns the file path
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl DeadLetterSink for FileDeadLetterSink {
+        fn send(&self, letter: DeadLetter) -> Result<()> {
+            let mut line = serde_json::to_vec(&letter)
+                .map_err(|e| NetError::DeadLetter(format!("cannot encode letter: {}", e)))?;
+            line.push(b'\n');
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.write_all(&line)
+                .and_then(|_| file.flush())
+                .map_err(|e| io_error(&self.path, e))
+        }
+    }
+
+    /// Reads every dead letter stored in `path` by a [`FileDeadLetterSink`]
+    ///
+    /// Blank lines are skipped; a malformed line is an error naming its number.
+    pub fn read_dead_letters<P: AsRef<Path>>(path: P) -> Result<Vec<DeadLetter>> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        let mut letters = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| io_error(path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let letter = serde_json::from_str(&line).map_err(|e| {
+                NetError::DeadLetter(format!("{}:{}: {}", path.display(), index + 1, e))
+            })?;
+            letters.push(letter);
+        }
+        Ok(letters)
+    }
+
+    fn io_error(path: &Path, e: std::io::Error) -> NetError {
+        NetError::DeadLetter(format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(all(test, feature = "dlq"))]
+mod tests {
+    use super::*;
+    use crate::kind::MessageKind;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    #[test]
+    fn test_file_sink_round_trip() {
+        let path = std::env::temp_dir().join(format!("lnmp-dlq-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::String("overheat".into()),
+        });
+        let envelope = EnvelopeBuilder::new(record)
+            .timestamp(1000)
+            .source("sensor-7")
+            .build();
+        let msg = NetMessage::with_qos(envelope, MessageKind::Alert, 250, 5000);
+
+        let sink = FileDeadLetterSink::open(&path).unwrap();
+        sink.send(DeadLetter::new(msg.clone(), DropReason::Expired, 7000))
+            .unwrap();
+        sink.send(DeadLetter::new(
+            msg,
+            DropReason::Other("manual".into()),
+            8000,
+        ))
+        .unwrap();
+        drop(sink);
+
+        let letters = read_dead_letters(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(letters.len(), 2);
+        assert_eq!(letters[0].reason, DropReason::Expired);
+        assert_eq!(letters[0].dropped_at_ms, 7000);
+        assert_eq!(letters[0].message.source(), Some("sensor-7"));
+        assert_eq!(letters[0].message.priority, 250);
+        assert_eq!(letters[1].reason, DropReason::Other("manual".into()));
+        assert_eq!(
+            letters[1].message.record().get_field(12).unwrap().value,
+            LnmpValue::String("overheat".into())
+        );
+    }
+
+    #[test]
+    fn test_malformed_line_reports_position() {
+        let path = std::env::temp_dir().join(format!("lnmp-dlq-bad-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "\n{not json}\n").unwrap();
+        let err = read_dead_letters(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains(":2:"));
+    }
+}
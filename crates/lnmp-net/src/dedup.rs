@@ -0,0 +1,330 @@
+//! Suppression of repeated messages within a time window
+//!
+//! Bursty sensors often send the same record many times in a row. A
+//! [`DedupFilter`] remembers the canonical hash of every message it lets
+//! through and reports an equivalent message arriving within the window as a
+//! duplicate. A [`RoutingPolicy`](crate::RoutingPolicy) configured with one
+//! drops duplicates before any other routing step.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use lnmp_core::{FieldId, LnmpField, LnmpRecord, LnmpRecordView};
+
+use crate::kind::MessageKind;
+use crate::message::NetMessage;
+
+/// Which part of a record decides whether two messages are equivalent
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DedupSimilarity {
+    /// The whole record, compared by its canonical hash
+    #[default]
+    Exact,
+    /// Only the listed fields; a listed field that is missing counts as a value
+    /// of its own
+    Fields(Vec<FieldId>),
+}
+
+/// Counters collected by a [`DedupFilter`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupMetrics {
+    /// Messages seen for the first time within the window
+    pub passed: u64,
+    /// Messages reported as duplicates
+    pub duplicates: u64,
+    /// Number of keys currently remembered
+    pub tracked: usize,
+}
+
+#[derive(Debug)]
+struct State {
+    /// Key -> time the key last passed
+    seen: HashMap<u64, u64>,
+    /// (passed at, key) in arrival order, for expiring `seen`
+    order: VecDeque<(u64, u64)>,
+    metrics: DedupMetrics,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    window_ms: u64,
+    similarity: DedupSimilarity,
+    per_source: bool,
+}
+
+/// Sliding-window duplicate detector keyed by canonical record hash
+///
+/// A message is a duplicate if an equivalent one (same kind, same source
+/// unless disabled with [`with_per_source`](Self::with_per_source), and same
+/// record under the configured [`DedupSimilarity`]) passed less than
+/// `window_ms` ago. Duplicates do not restart the window, so a record repeated
+/// forever still passes once per window. Clones share state and metrics.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{DedupFilter, MessageKind, NetMessage};
+///
+/// let mut record = LnmpRecord::new();
+/// record.add_field(LnmpField { fid: 1, value: LnmpValue::Float(21.5) });
+/// let envelope = EnvelopeBuilder::new(record).source("sensor-7").build();
+/// let msg = NetMessage::new(envelope, MessageKind::Event);
+///
+/// let filter = DedupFilter::new(5000);
+/// assert!(!filter.is_duplicate(&msg, 0));
+/// assert!(filter.is_duplicate(&msg, 1000));
+/// // The window is measured from the first message, not the last duplicate
+/// assert!(!filter.is_duplicate(&msg, 5000));
+/// assert_eq!(filter.metrics().duplicates, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DedupFilter {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+impl DedupFilter {
+    /// Creates a filter comparing whole records per source within `window_ms`
+    pub fn new(window_ms: u64) -> Self {
+        Self {
+            config: Arc::new(Config {
+                window_ms,
+                similarity: DedupSimilarity::Exact,
+                per_source: true,
+            }),
+            state: Arc::new(Mutex::new(State {
+                seen: HashMap::new(),
+                order: VecDeque::new(),
+                metrics: DedupMetrics::default(),
+            })),
+        }
+    }
+
+    /// Sets which part of the record is compared
+    pub fn with_similarity(mut self, similarity: DedupSimilarity) -> Self {
+        Arc::make_mut(&mut self.config).similarity = similarity;
+        self
+    }
+
+    /// Sets whether messages from different sources are kept apart (default true)
+    pub fn with_per_source(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).per_source = enabled;
+        self
+    }
+
+    /// Returns the window length in milliseconds
+    pub fn window_ms(&self) -> u64 {
+        self.config.window_ms
+    }
+
+    /// Returns the configured similarity
+    pub fn similarity(&self) -> &DedupSimilarity {
+        &self.config.similarity
+    }
+
+    /// Records `msg` at `now_ms` and returns true if it duplicates an earlier message
+    pub fn is_duplicate(&self, msg: &NetMessage, now_ms: u64) -> bool {
+        let source = msg.envelope.metadata.source.as_deref();
+        self.is_duplicate_key(self.key(msg.kind, source, msg.record()), now_ms)
+    }
+
+    /// Same as [`is_duplicate`](Self::is_duplicate) for a zero-copy record view
+    ///
+    /// Only the compared fields are copied out of the view.
+    pub fn is_duplicate_view(
+        &self,
+        kind: MessageKind,
+        source: Option<&str>,
+        record: &LnmpRecordView,
+        now_ms: u64,
+    ) -> bool {
+        let owned = match &self.config.similarity {
+            DedupSimilarity::Exact => record.to_lnmp_record(),
+            DedupSimilarity::Fields(fids) => LnmpRecord::from_fields(
+                fids.iter()
+                    .filter_map(|&fid| record.get_field(fid))
+                    .map(|field| LnmpField {
+                        fid: field.fid,
+                        value: field.value.to_owned_value(),
+                    })
+                    .collect(),
+            ),
+        };
+        self.is_duplicate_key(self.key(kind, source, &owned), now_ms)
+    }
+
+    /// Computes the key two messages must share to be equivalent
+    pub fn key(&self, kind: MessageKind, source: Option<&str>, record: &LnmpRecord) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        kind.hash(&mut hasher);
+        if self.config.per_source {
+            source.hash(&mut hasher);
+        }
+        match &self.config.similarity {
+            DedupSimilarity::Exact => record.canonical_hash(&mut hasher),
+            DedupSimilarity::Fields(fids) => {
+                let mut fids = fids.clone();
+                fids.sort_unstable();
+                fids.dedup();
+                let subset: Vec<_> = fids
+                    .iter()
+                    .filter_map(|&fid| record.get_field(fid).cloned())
+                    .collect();
+                LnmpRecord::from_sorted_fields(subset).canonical_hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Records `key` at `now_ms` and returns true if it passed within the window
+    pub fn is_duplicate_key(&self, key: u64, now_ms: u64) -> bool {
+        let window_ms = self.config.window_ms;
+        let mut state = self.lock();
+        Self::expire(&mut state, window_ms, now_ms);
+
+        let duplicate = state
+            .seen
+            .get(&key)
+            .is_some_and(|&passed_ms| now_ms < passed_ms.saturating_add(window_ms));
+        if duplicate {
+            state.metrics.duplicates += 1;
+        } else {
+            state.seen.insert(key, now_ms);
+            state.order.push_back((now_ms, key));
+            state.metrics.passed += 1;
+        }
+        state.metrics.tracked = state.seen.len();
+        duplicate
+    }
+
+    /// Forgets keys whose window has closed by `now_ms`
+    ///
+    /// Expiry also happens on every check, so this is only needed to release
+    /// memory after traffic stops.
+    pub fn prune(&self, now_ms: u64) {
+        let mut state = self.lock();
+        Self::expire(&mut state, self.config.window_ms, now_ms);
+        state.metrics.tracked = state.seen.len();
+    }
+
+    /// Returns a snapshot of the counters
+    pub fn metrics(&self) -> DedupMetrics {
+        self.lock().metrics
+    }
+
+    /// Resets the counters, keeping remembered keys
+    pub fn reset_metrics(&self) {
+        let mut state = self.lock();
+        state.metrics = DedupMetrics {
+            tracked: state.seen.len(),
+            ..DedupMetrics::default()
+        };
+    }
+
+    fn expire(state: &mut State, window_ms: u64, now_ms: u64) {
+        while let Some(&(passed_ms, key)) = state.order.front() {
+            if passed_ms.saturating_add(window_ms) > now_ms {
+                break;
+            }
+            state.order.pop_front();
+            // The key may have passed again since; only the latest entry counts
+            if state.seen.get(&key) == Some(&passed_ms) {
+                state.seen.remove(&key);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Remembered keys stay consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpFieldView, LnmpValue, LnmpValueView};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    fn reading(source: &str, seq: i64, temp: f64) -> NetMessage {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Int(seq),
+        });
+        record.add_field(LnmpField {
+            fid: 2,
+            value: LnmpValue::Float(temp),
+        });
+        let envelope = EnvelopeBuilder::new(record).source(source).build();
+        NetMessage::new(envelope, MessageKind::Event)
+    }
+
+    #[test]
+    fn test_exact_window() {
+        let filter = DedupFilter::new(1000);
+        assert!(!filter.is_duplicate(&reading("a", 1, 20.0), 0));
+        assert!(filter.is_duplicate(&reading("a", 1, 20.0), 999));
+        assert!(!filter.is_duplicate(&reading("a", 2, 20.0), 999));
+        assert!(!filter.is_duplicate(&reading("a", 1, 20.0), 1000));
+
+        let metrics = filter.metrics();
+        assert_eq!(metrics.passed, 3);
+        assert_eq!(metrics.duplicates, 1);
+        assert_eq!(metrics.tracked, 2);
+    }
+
+    #[test]
+    fn test_field_subset_and_sources() {
+        let filter = DedupFilter::new(1000).with_similarity(DedupSimilarity::Fields(vec![2]));
+        assert!(!filter.is_duplicate(&reading("a", 1, 20.0), 0));
+        // Sequence number differs, compared field does not
+        assert!(filter.is_duplicate(&reading("a", 2, 20.0), 10));
+        assert!(!filter.is_duplicate(&reading("b", 3, 20.0), 20));
+
+        let shared = DedupFilter::new(1000)
+            .with_similarity(DedupSimilarity::Fields(vec![2]))
+            .with_per_source(false);
+        assert!(!shared.is_duplicate(&reading("a", 1, 20.0), 0));
+        assert!(shared.is_duplicate(&reading("b", 2, 20.0), 10));
+    }
+
+    #[test]
+    fn test_view_matches_owned() {
+        let filter = DedupFilter::new(1000);
+        let view = LnmpRecordView::from_fields(vec![
+            LnmpFieldView {
+                fid: 2,
+                value: LnmpValueView::Float(20.0),
+            },
+            LnmpFieldView {
+                fid: 1,
+                value: LnmpValueView::Int(1),
+            },
+        ]);
+        assert!(!filter.is_duplicate(&reading("a", 1, 20.0), 0));
+        assert!(filter.is_duplicate_view(MessageKind::Event, Some("a"), &view, 10));
+        assert!(!filter.is_duplicate_view(MessageKind::State, Some("a"), &view, 10));
+    }
+
+    #[test]
+    fn test_prune_and_shared_state() {
+        let filter = DedupFilter::new(100);
+        let clone = filter.clone();
+        assert!(!filter.is_duplicate(&reading("a", 1, 20.0), 0));
+        assert!(clone.is_duplicate(&reading("a", 1, 20.0), 50));
+        assert!(!clone.is_duplicate(&reading("a", 2, 20.0), 50));
+
+        filter.prune(100);
+        assert_eq!(filter.metrics().tracked, 1);
+        filter.prune(150);
+        assert_eq!(clone.metrics().tracked, 0);
+
+        filter.reset_metrics();
+        assert_eq!(clone.metrics(), DedupMetrics::default());
+    }
+}
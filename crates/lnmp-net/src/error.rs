@@ -24,6 +24,30 @@ pub enum NetError {
     #[error("Envelope error: {0}")]
     EnvelopeError(#[from] lnmp_envelope::EnvelopeError),
 
+    /// A field condition could not be built
+    #[error("Invalid field condition: {0}")]
+    InvalidCondition(String),
+
+    /// Routing rules could not be loaded
+    #[error("Invalid routing rules: {0}")]
+    InvalidRules(String),
+
+    /// Topology configuration could not be loaded
+    #[error("Invalid topology: {0}")]
+    InvalidTopology(String),
+
+    /// A node is not registered in the topology
+    #[error("Unknown node: {0}")]
+    UnknownNode(String),
+
+    /// A dead letter could not be stored or read
+    #[error("Dead-letter error: {0}")]
+    DeadLetter(String),
+
+    /// A spilled message could not be stored or read
+    #[error("Spill queue error: {0}")]
+    Spill(String),
+
     /// Generic error
     #[error("{0}")]
     Other(String),
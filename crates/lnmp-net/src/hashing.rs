@@ -0,0 +1,268 @@
+//! Pluggable hashing for content routing keys
+//!
+//! Routing keys identify records with the same content, e.g. for sticky routing
+//! or deduplication. They are computed by feeding a canonical byte form of the
+//! selected fields to a [`Hasher`]. Three algorithms are built in:
+//!
+//! - [`HashAlgorithm::Fnv1a`] (default): 64-bit FNV-1a, needs no dependency and
+//!   its output is fixed, so keys can be stored or compared across deployments.
+//! - `HashAlgorithm::FxHash` (`fxhash` feature): fastest, but its output is not
+//!   guaranteed to stay the same across crate versions, so only use it when every
+//!   node runs the same build.
+//! - `HashAlgorithm::Blake3` (`blake3` feature): slower, but its output is fixed
+//!   and well distributed even for adversarial inputs.
+//!
+//! Custom algorithms can be plugged in by implementing [`Hasher`].
+
+use lnmp_core::{FieldId, LnmpRecordView, LnmpValueView};
+use std::fmt;
+
+/// Hash function used to derive routing keys
+pub trait Hasher: fmt::Debug + Send + Sync {
+    /// Hashes `data` to a 64-bit key
+    fn hash(&self, data: &[u8]) -> u64;
+}
+
+/// Built-in hash algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HashAlgorithm {
+    /// 64-bit FNV-1a: stable across versions and platforms
+    #[default]
+    Fnv1a,
+    /// FxHash: high throughput, output may change between versions
+    #[cfg(feature = "fxhash")]
+    FxHash,
+    /// BLAKE3 truncated to 64 bits: stable across versions and platforms
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Returns true if keys are guaranteed to be identical across versions
+    pub fn is_stable(&self) -> bool {
+        match self {
+            HashAlgorithm::Fnv1a => true,
+            #[cfg(feature = "fxhash")]
+            HashAlgorithm::FxHash => false,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => true,
+        }
+    }
+}
+
+impl Hasher for HashAlgorithm {
+    fn hash(&self, data: &[u8]) -> u64 {
+        match self {
+            HashAlgorithm::Fnv1a => data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+            }),
+            #[cfg(feature = "fxhash")]
+            HashAlgorithm::FxHash => fxhash::hash64(data),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => {
+                let digest = blake3::hash(data);
+                let mut first = [0u8; 8];
+                first.copy_from_slice(&digest.as_bytes()[..8]);
+                u64::from_le_bytes(first)
+            }
+        }
+    }
+}
+
+/// Computes the routing key of `record` with `hasher`
+///
+/// Only `key_fields` are hashed (all fields when empty). A listed field that is
+/// missing hashes differently from any value it could hold, and field order in
+/// the record does not affect the key.
+pub fn routing_key(hasher: &dyn Hasher, record: &LnmpRecordView, key_fields: &[FieldId]) -> u64 {
+    let mut bytes = Vec::new();
+    if key_fields.is_empty() {
+        write_record(&mut bytes, record);
+    } else {
+        let mut fids = key_fields.to_vec();
+        fids.sort_unstable();
+        fids.dedup();
+        for fid in fids {
+            bytes.extend_from_slice(&fid.to_le_bytes());
+            match record.get_field(fid) {
+                Some(field) => write_value(&mut bytes, &field.value),
+                None => bytes.push(TAG_MISSING),
+            }
+        }
+    }
+    hasher.hash(&bytes)
+}
+
+const TAG_MISSING: u8 = 0x00;
+
+/// Writes `FIELD_COUNT | (FID | TAG | VALUE)*` with fields in FID order
+fn write_record(bytes: &mut Vec<u8>, record: &LnmpRecordView) {
+    let mut fields: Vec<_> = record.fields().iter().collect();
+    fields.sort_by_key(|field| field.fid);
+    write_len(bytes, fields.len());
+    for field in fields {
+        bytes.extend_from_slice(&field.fid.to_le_bytes());
+        write_value(bytes, &field.value);
+    }
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &LnmpValueView) {
+    match value {
+        LnmpValueView::Int(i) => {
+            bytes.push(0x01);
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        LnmpValueView::Float(f) => {
+            bytes.push(0x02);
+            bytes.extend_from_slice(&f.to_bits().to_le_bytes());
+        }
+        LnmpValueView::Bool(b) => bytes.extend_from_slice(&[0x03, *b as u8]),
+        LnmpValueView::String(s) => {
+            bytes.push(0x04);
+            write_str(bytes, s);
+        }
+        LnmpValueView::StringArray(items) => {
+            bytes.push(0x05);
+            write_len(bytes, items.len());
+            for s in items {
+                write_str(bytes, s);
+            }
+        }
+        LnmpValueView::NestedRecord(record) => {
+            bytes.push(0x06);
+            write_record(bytes, record);
+        }
+        LnmpValueView::NestedArray(records) => {
+            bytes.push(0x07);
+            write_len(bytes, records.len());
+            for record in records {
+                write_record(bytes, record);
+            }
+        }
+        LnmpValueView::Embedding(data) => {
+            bytes.push(0x08);
+            write_len(bytes, data.len());
+            bytes.extend_from_slice(data);
+        }
+        LnmpValueView::IntArray(items) => {
+            bytes.push(0x0B);
+            write_len(bytes, items.len());
+            for i in items {
+                bytes.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+        LnmpValueView::FloatArray(items) => {
+            bytes.push(0x0C);
+            write_len(bytes, items.len());
+            for f in items {
+                bytes.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+        }
+        LnmpValueView::BoolArray(items) => {
+            bytes.push(0x0D);
+            write_len(bytes, items.len());
+            bytes.extend(items.iter().map(|b| *b as u8));
+        }
+    }
+}
+
+fn write_str(bytes: &mut Vec<u8>, s: &str) {
+    write_len(bytes, s.len());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::LnmpFieldView;
+
+    fn view<'a>(fields: Vec<(FieldId, LnmpValueView<'a>)>) -> LnmpRecordView<'a> {
+        LnmpRecordView::from_fields(
+            fields
+                .into_iter()
+                .map(|(fid, value)| LnmpFieldView { fid, value })
+                .collect(),
+        )
+    }
+
+    fn algorithms() -> Vec<HashAlgorithm> {
+        vec![
+            HashAlgorithm::Fnv1a,
+            #[cfg(feature = "fxhash")]
+            HashAlgorithm::FxHash,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3,
+        ]
+    }
+
+    #[test]
+    fn test_fnv1a_keys_are_pinned() {
+        // Changing these values breaks keys stored by existing deployments
+        assert_eq!(HashAlgorithm::Fnv1a.hash(b""), 0xCBF2_9CE4_8422_2325);
+        let record = view(vec![(1, LnmpValueView::String("critical"))]);
+        assert_eq!(
+            routing_key(&HashAlgorithm::Fnv1a, &record, &[]),
+            0x5894_2C49_E0EB_ABD2
+        );
+        assert!(HashAlgorithm::Fnv1a.is_stable());
+    }
+
+    #[test]
+    #[cfg(feature = "blake3")]
+    fn test_blake3_keys_are_pinned() {
+        // Changing these values breaks keys stored by existing deployments
+        assert_eq!(HashAlgorithm::Blake3.hash(b""), 0xA6A1_F9F5_B949_13AF);
+        let record = view(vec![(1, LnmpValueView::String("critical"))]);
+        assert_eq!(
+            routing_key(&HashAlgorithm::Blake3, &record, &[]),
+            0xD87B_210A_1522_A5D5
+        );
+        assert!(HashAlgorithm::Blake3.is_stable());
+    }
+
+    #[test]
+    #[cfg(feature = "fxhash")]
+    fn test_fxhash_is_not_stable() {
+        assert!(!HashAlgorithm::FxHash.is_stable());
+    }
+
+    #[test]
+    fn test_key_ignores_field_order_and_unselected_fields() {
+        let a = view(vec![
+            (1, LnmpValueView::Int(7)),
+            (2, LnmpValueView::String("eu")),
+            (3, LnmpValueView::Bool(true)),
+        ]);
+        let b = view(vec![
+            (3, LnmpValueView::Bool(false)),
+            (2, LnmpValueView::String("eu")),
+            (1, LnmpValueView::Int(7)),
+        ]);
+        for algorithm in algorithms() {
+            assert_eq!(
+                routing_key(&algorithm, &a, &[2, 1]),
+                routing_key(&algorithm, &b, &[1, 2])
+            );
+            assert_ne!(
+                routing_key(&algorithm, &a, &[]),
+                routing_key(&algorithm, &b, &[])
+            );
+        }
+    }
+
+    #[test]
+    fn test_missing_field_differs_from_present_values() {
+        let missing = view(vec![]);
+        let empty = view(vec![(5, LnmpValueView::String(""))]);
+        let algorithm = HashAlgorithm::default();
+        assert_ne!(
+            routing_key(&algorithm, &missing, &[5]),
+            routing_key(&algorithm, &empty, &[5])
+        );
+    }
+}
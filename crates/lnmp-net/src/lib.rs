@@ -61,30 +61,79 @@
 //! The `RoutingPolicy` implements Energy/Token Optimization:
 //!
 //! 1. **Expired messages** → Drop (wasteful to process)
+//!    (repeats within a [`DedupFilter`] window are dropped too)
 //! 2. **Alerts** with high priority → Always send to LLM
 //! 3. **Events/State**: Compute importance score (priority + SFE) → threshold check
-//! 4. **Commands/Queries** → Process locally (unless complex)
+//! 4. **Commands/Queries** → Process locally unless complex (see [`complexity`])
 //!
 //! This reduces LLM API calls by 90%+ while maintaining decision quality.
+//! Custom policies implement [`Router`] (or [`AsyncRouter`] when deciding
+//! needs I/O) and can be used wherever a routing policy is accepted.
+//! [`KindClassifier`] infers the kind of untagged records.
+//! [`RoutingRules`] evaluates ordered routing rules (loadable from YAML with
+//! the `yaml` feature), [`FanOutPolicy`] plans deliveries to several
+//! destinations at once, and [`NetScheduler`] orders the deliveries a policy
+//! decides on, retried under a [`RetryPolicy`].
+//! A [`Topology`] resolves plan destinations to the peers of an agent mesh.
 //!
 //! ## Features
 //!
 //! - `serde`: Enable serde serialization support (optional)
+//! - `dlq`: File-backed dead-letter sink ([`FileDeadLetterSink`]), implies `serde`
+//! - `llb`: Token estimates from rendered LNMP text (`lnmp_llb::TokenEstimator`)
+//! - `spill`: Disk-backed overflow queue for the scheduler (`SpillQueue`)
+//! - `yaml`: Load [`RoutingRules`] and [`Topology`] from YAML
+//! - `regex`: Regular-expression field conditions ([`FieldCondition::regex`])
+//! - `fxhash` / `blake3`: Extra routing key hashers ([`HashAlgorithm`])
 
+pub mod budget;
+pub mod circuit_breaker;
+pub mod classify;
+pub mod complexity;
 pub mod content_routing;
+pub mod dead_letter;
+pub mod dedup;
 pub mod error;
+pub mod hashing;
 pub mod kind;
 pub mod message;
+pub mod plan;
+pub mod rate_limit;
+pub mod retry;
 pub mod routing;
+pub mod rules;
+pub mod scheduler;
+#[cfg(feature = "spill")]
+pub mod spill;
+pub mod stats;
+pub mod topology;
 
 #[cfg(feature = "transport")]
 pub mod transport;
 
-pub use content_routing::{ContentAwarePolicy, ContentRule, FieldCondition};
+pub use budget::{ComplexityTokenEstimator, TokenBudget, TokenBudgetMetrics, TokenEstimator};
+pub use circuit_breaker::{CircuitBreaker, CircuitMetrics, CircuitState};
+pub use classify::{Classification, KindClassifier, KindRule};
+pub use complexity::{complexity_score, ComplexityConfig, RecordComplexity};
+pub use content_routing::{ContentAwarePolicy, ContentMatch, ContentRule, FieldCondition};
+#[cfg(feature = "dlq")]
+pub use dead_letter::{read_dead_letters, FileDeadLetterSink};
+pub use dead_letter::{DeadLetter, DeadLetterSink, DropReason};
+pub use dedup::{DedupFilter, DedupMetrics, DedupSimilarity};
 pub use error::{NetError, Result};
+pub use hashing::{HashAlgorithm, Hasher};
 pub use kind::MessageKind;
 pub use message::{NetMessage, NetMessageBuilder};
-pub use routing::{RoutingDecision, RoutingPolicy};
+pub use plan::{Delivery, Destination, FanOutPolicy, Planner, RoutingPlan, Transform};
+pub use rate_limit::{RateLimit, RateLimitMetrics, RateLimiter};
+pub use retry::{GiveUpReason, RetryBudget, RetryDecision, RetryPolicy, RetryState};
+pub use routing::{AsyncRouter, Router, RoutingDecision, RoutingPolicy};
+pub use rules::{RoutingRule, RoutingRules, RuleCondition, RuleMatch};
+pub use scheduler::{Backpressure, Enqueue, NetScheduler, SchedulerMetrics};
+#[cfg(feature = "spill")]
+pub use spill::SpillQueue;
+pub use stats::{RouteReason, RoutingStats, RoutingStatsSnapshot, ScoreDistribution};
+pub use topology::{Node, NodeHealth, Topology};
 
 // Re-export commonly used types for convenience
 pub use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
@@ -4,6 +4,7 @@ use lnmp_envelope::LnmpEnvelope;
 
 use crate::error::{NetError, Result};
 use crate::kind::MessageKind;
+use crate::retry::RetryState;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -59,6 +60,10 @@ pub struct NetMessage {
 
     /// Optional domain classification (e.g., "health", "safety", "traffic")
     pub class: Option<String>,
+
+    /// Failed delivery attempts, maintained by [`RetryPolicy`](crate::RetryPolicy)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retry: RetryState,
 }
 
 impl NetMessage {
@@ -72,6 +77,7 @@ impl NetMessage {
             priority: kind.default_priority(),
             ttl_ms: kind.default_ttl_ms(),
             class: None,
+            retry: RetryState::default(),
         }
     }
 
@@ -83,12 +89,15 @@ impl NetMessage {
             priority,
             ttl_ms,
             class: None,
+            retry: RetryState::default(),
         }
     }
 
     /// Checks if the message has expired based on current time
     ///
-    /// Returns `Err(NetError::MissingTimestamp)` if envelope has no timestamp.
+    /// An absolute `expires_at` in the envelope metadata takes precedence over
+    /// `ttl_ms`. Otherwise returns `Err(NetError::MissingTimestamp)` if envelope
+    /// has no timestamp.
     ///
     /// # Arguments
     ///
@@ -111,6 +120,10 @@ impl NetMessage {
     /// assert!(msg.is_expired(7000).unwrap());  // Age = 6000ms > 5000ms
     /// ```
     pub fn is_expired(&self, now_ms: u64) -> Result<bool> {
+        if self.envelope.metadata.expires_at.is_some() {
+            return Ok(self.envelope.metadata.is_expired(now_ms));
+        }
+
         let timestamp = self
             .envelope
             .metadata
@@ -121,6 +134,19 @@ impl NetMessage {
         Ok(age_ms > self.ttl_ms as u64)
     }
 
+    /// Returns the time (epoch milliseconds) after which the message is stale
+    ///
+    /// This is `expires_at` when the envelope sets it, otherwise timestamp + TTL.
+    /// Returns `None` if neither an expiry nor a timestamp is known.
+    pub fn deadline_ms(&self) -> Option<u64> {
+        let metadata = &self.envelope.metadata;
+        metadata.expires_at.or_else(|| {
+            metadata
+                .timestamp
+                .map(|ts| ts.saturating_add(self.ttl_ms as u64))
+        })
+    }
+
     /// Returns the age of the message in milliseconds
     ///
     /// Returns `None` if envelope has no timestamp.
@@ -230,6 +256,7 @@ impl NetMessageBuilder {
             priority: self.priority,
             ttl_ms: self.ttl_ms,
             class: self.class,
+            retry: RetryState::default(),
         }
     }
 }
@@ -284,6 +311,26 @@ mod tests {
         assert!(msg.is_expired(7000).unwrap());
     }
 
+    #[test]
+    fn test_is_expired_prefers_envelope_expiry() {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .expires_at(10_000)
+            .build();
+        let msg = NetMessage::with_qos(envelope, MessageKind::Event, 100, 5000);
+
+        // Age = 6000ms exceeds TTL, but expires_at has not passed
+        assert!(!msg.is_expired(7000).unwrap());
+        assert!(msg.is_expired(10_000).unwrap());
+
+        // No timestamp needed when expires_at is set
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .expires_at(10_000)
+            .build();
+        let msg = NetMessage::new(envelope, MessageKind::Event);
+        assert!(!msg.is_expired(5000).unwrap());
+    }
+
     #[test]
     fn test_is_expired_missing_timestamp() {
         let envelope = LnmpEnvelope::new(sample_record());
@@ -292,6 +339,22 @@ mod tests {
         assert!(msg.is_expired(5000).is_err());
     }
 
+    #[test]
+    fn test_deadline_ms() {
+        let msg = NetMessage::with_qos(sample_envelope(1000), MessageKind::Event, 100, 5000);
+        assert_eq!(msg.deadline_ms(), Some(6000));
+
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .expires_at(3000)
+            .build();
+        let msg = NetMessage::with_qos(envelope, MessageKind::Event, 100, 5000);
+        assert_eq!(msg.deadline_ms(), Some(3000));
+
+        let msg = NetMessage::new(LnmpEnvelope::new(sample_record()), MessageKind::Event);
+        assert_eq!(msg.deadline_ms(), None);
+    }
+
     #[test]
     fn test_age_ms() {
         let envelope = sample_envelope(1000);
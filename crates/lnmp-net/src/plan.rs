@@ -0,0 +1,416 @@
+//! Multi-destination routing plans
+//!
+//! A [`RoutingDecision`] picks one destination. A [`RoutingPlan`] lists every
+//! destination a message goes to (the LLM, local processing, named channels
+//! such as an archive or an alert feed) together with the [`Transform`] applied
+//! to the record for each one, e.g. the full record for the archive but only a
+//! summary for the LLM.
+//!
+//! [`Planner`] produces plans; every [`Router`] is a planner whose plan holds
+//! its single decision, and [`FanOutPolicy`] adds extra destinations on top of
+//! a router when [`RuleCondition`]s match.
+
+use lnmp_core::{FieldId, LnmpRecord};
+use lnmp_sfe::SemanticDictionary;
+
+use crate::error::Result;
+use crate::message::NetMessage;
+use crate::routing::{Router, RoutingDecision, RoutingPolicy};
+use crate::rules::RuleCondition;
+
+/// Where a delivery goes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Destination {
+    /// The LLM
+    Llm,
+    /// Local processing at the edge or service
+    Local,
+    /// An application-defined channel (e.g. "archive", "alerts")
+    Channel(String),
+}
+
+impl Destination {
+    /// Creates a named channel destination
+    pub fn channel(name: impl Into<String>) -> Self {
+        Destination::Channel(name.into())
+    }
+}
+
+/// How the record is shaped for one destination
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Transform {
+    /// The record as is
+    #[default]
+    Full,
+    /// Only the listed fields, in FID order
+    Fields(Vec<FieldId>),
+    /// The `max_fields` most important fields, in FID order
+    ///
+    /// Importance comes from the [`SemanticDictionary`] passed when rendering;
+    /// fields it doesn't rate (or all fields, without a dictionary) rank below
+    /// rated ones and by ascending FID among themselves.
+    Summary {
+        /// Number of fields kept
+        max_fields: usize,
+    },
+}
+
+impl Transform {
+    /// Applies the transform to `record`
+    pub fn apply(
+        &self,
+        record: &LnmpRecord,
+        dictionary: Option<&SemanticDictionary>,
+    ) -> LnmpRecord {
+        match self {
+            Transform::Full => record.clone(),
+            Transform::Fields(fids) => LnmpRecord::from_fields(
+                record
+                    .fields()
+                    .iter()
+                    .filter(|field| fids.contains(&field.fid))
+                    .cloned()
+                    .collect(),
+            ),
+            Transform::Summary { max_fields } => {
+                let importance = |fid| dictionary.and_then(|dict| dict.get_importance(fid));
+                let mut ranked = record.sorted_fields();
+                // Stable sort: FID order breaks ties
+                ranked.sort_by_key(|field| std::cmp::Reverse(importance(field.fid)));
+                ranked.truncate(*max_fields);
+                LnmpRecord::from_fields(ranked)
+            }
+        }
+    }
+}
+
+/// One destination of a plan and the record shape it receives
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    /// Where the message goes
+    pub destination: Destination,
+    /// How its record is shaped
+    pub transform: Transform,
+}
+
+impl Delivery {
+    /// Creates a delivery of the full record to `destination`
+    pub fn new(destination: Destination) -> Self {
+        Self {
+            destination,
+            transform: Transform::Full,
+        }
+    }
+
+    /// Sets the record transform
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+/// Every destination a message is delivered to
+///
+/// Each destination appears at most once; adding one that is already planned
+/// keeps the earlier delivery. An empty plan drops the message.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{Delivery, Destination, MessageKind, NetMessage, RoutingPlan, Transform};
+///
+/// let mut record = LnmpRecord::new();
+/// record.add_field(LnmpField { fid: 1, value: LnmpValue::Int(42) });
+/// record.add_field(LnmpField { fid: 2, value: LnmpValue::String("raw dump".into()) });
+/// let msg = NetMessage::new(EnvelopeBuilder::new(record).build(), MessageKind::Alert);
+///
+/// let plan = RoutingPlan::new()
+///     .with_delivery(Delivery::new(Destination::Llm).with_transform(Transform::Fields(vec![1])))
+///     .with_delivery(Delivery::new(Destination::channel("archive")));
+///
+/// let rendered = plan.render(&msg, None);
+/// assert_eq!(rendered[0].1.record().fields().len(), 1);
+/// assert_eq!(rendered[1].0, &Destination::channel("archive"));
+/// assert_eq!(rendered[1].1.record().fields().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RoutingPlan {
+    /// Deliveries in the order they were planned
+    pub deliveries: Vec<Delivery>,
+}
+
+impl RoutingPlan {
+    /// Creates an empty plan (drops the message)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a delivery unless its destination is already planned
+    pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+        self.add(delivery);
+        self
+    }
+
+    /// Adds a delivery unless its destination is already planned
+    pub fn add(&mut self, delivery: Delivery) {
+        if !self.includes(&delivery.destination) {
+            self.deliveries.push(delivery);
+        }
+    }
+
+    /// Returns true if the message goes nowhere
+    pub fn is_drop(&self) -> bool {
+        self.deliveries.is_empty()
+    }
+
+    /// Returns true if `destination` is planned
+    pub fn includes(&self, destination: &Destination) -> bool {
+        self.deliveries
+            .iter()
+            .any(|delivery| &delivery.destination == destination)
+    }
+
+    /// Returns the delivery planned for `destination`
+    pub fn delivery(&self, destination: &Destination) -> Option<&Delivery> {
+        self.deliveries
+            .iter()
+            .find(|delivery| &delivery.destination == destination)
+    }
+
+    /// Returns one message per delivery, with its transform applied
+    ///
+    /// Envelope metadata and QoS fields are kept; only the record changes, so an
+    /// envelope signature no longer verifies on transformed copies.
+    pub fn render<'a>(
+        &'a self,
+        msg: &NetMessage,
+        dictionary: Option<&SemanticDictionary>,
+    ) -> Vec<(&'a Destination, NetMessage)> {
+        self.deliveries
+            .iter()
+            .map(|delivery| {
+                let mut out = msg.clone();
+                if delivery.transform != Transform::Full {
+                    out.envelope.record = delivery.transform.apply(msg.record(), dictionary);
+                }
+                (&delivery.destination, out)
+            })
+            .collect()
+    }
+}
+
+impl From<RoutingDecision> for RoutingPlan {
+    fn from(decision: RoutingDecision) -> Self {
+        match decision {
+            RoutingDecision::SendToLLM => {
+                Self::new().with_delivery(Delivery::new(Destination::Llm))
+            }
+            RoutingDecision::ProcessLocally => {
+                Self::new().with_delivery(Delivery::new(Destination::Local))
+            }
+            RoutingDecision::Drop => Self::new(),
+        }
+    }
+}
+
+/// Produces a [`RoutingPlan`] for a message
+///
+/// Every [`Router`] is a planner whose plan delivers the full record to the
+/// single destination it decides on.
+pub trait Planner {
+    /// Plans the deliveries of `msg` at `now_ms` (epoch milliseconds)
+    fn plan(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingPlan>;
+}
+
+impl<R: Router> Planner for R {
+    fn plan(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingPlan> {
+        self.route(msg, now_ms).map(RoutingPlan::from)
+    }
+}
+
+/// A router's decision plus extra deliveries for matching messages
+///
+/// The router decides between the LLM and local processing as usual. Each tap
+/// whose condition matches then adds its delivery, even when the router drops
+/// the message (add `expired: Some(false)` to a condition to skip stale
+/// messages).
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{
+///     Delivery, Destination, FanOutPolicy, MessageKind, NetMessage, Planner, RuleCondition,
+///     Transform,
+/// };
+///
+/// let alerts = RuleCondition {
+///     kinds: vec![MessageKind::Alert],
+///     ..RuleCondition::default()
+/// };
+/// let planner = FanOutPolicy::default()
+///     .with_llm_transform(Transform::Summary { max_fields: 8 })
+///     .with_tap(RuleCondition::default(), Delivery::new(Destination::channel("archive")))
+///     .with_tap(alerts, Delivery::new(Destination::channel("alerts")));
+///
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(1000).build();
+/// let msg = NetMessage::with_qos(envelope, MessageKind::Alert, 255, 5000);
+/// let plan = planner.plan(&msg, 2000).unwrap();
+///
+/// assert_eq!(plan.deliveries.len(), 3);
+/// assert_eq!(
+///     plan.delivery(&Destination::Llm).unwrap().transform,
+///     Transform::Summary { max_fields: 8 }
+/// );
+/// ```
+pub struct FanOutPolicy {
+    router: Box<dyn Router + Send + Sync>,
+    llm_transform: Transform,
+    taps: Vec<(RuleCondition, Delivery)>,
+}
+
+impl FanOutPolicy {
+    /// Creates a fan-out policy on top of `router`
+    pub fn new(router: impl Router + Send + Sync + 'static) -> Self {
+        Self {
+            router: Box::new(router),
+            llm_transform: Transform::Full,
+            taps: Vec::new(),
+        }
+    }
+
+    /// Sets the transform applied when the router sends to the LLM
+    pub fn with_llm_transform(mut self, transform: Transform) -> Self {
+        self.llm_transform = transform;
+        self
+    }
+
+    /// Adds `delivery` to the plan of every message matching `condition`
+    pub fn with_tap(mut self, condition: RuleCondition, delivery: Delivery) -> Self {
+        self.taps.push((condition, delivery));
+        self
+    }
+}
+
+impl Default for FanOutPolicy {
+    fn default() -> Self {
+        Self::new(RoutingPolicy::default())
+    }
+}
+
+impl std::fmt::Debug for FanOutPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FanOutPolicy")
+            .field("llm_transform", &self.llm_transform)
+            .field("taps", &self.taps)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Planner for FanOutPolicy {
+    fn plan(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingPlan> {
+        let mut plan = match self.router.route(msg, now_ms)? {
+            RoutingDecision::SendToLLM => RoutingPlan::new().with_delivery(
+                Delivery::new(Destination::Llm).with_transform(self.llm_transform.clone()),
+            ),
+            decision => RoutingPlan::from(decision),
+        };
+        for (condition, delivery) in &self.taps {
+            if condition.matches(msg, now_ms)? {
+                plan.add(delivery.clone());
+            }
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::MessageKind;
+    use lnmp_core::{LnmpField, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    fn record(fids: &[FieldId]) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        for &fid in fids {
+            record.add_field(LnmpField {
+                fid,
+                value: LnmpValue::Int(fid as i64),
+            });
+        }
+        record
+    }
+
+    fn fids(record: &LnmpRecord) -> Vec<FieldId> {
+        record.fields().iter().map(|field| field.fid).collect()
+    }
+
+    #[test]
+    fn test_transforms() {
+        let full = record(&[7, 3, 12, 1]);
+        assert_eq!(Transform::Full.apply(&full, None), full);
+        assert_eq!(
+            fids(&Transform::Fields(vec![12, 3, 99]).apply(&full, None)),
+            [3, 12]
+        );
+
+        let summary = Transform::Summary { max_fields: 2 };
+        assert_eq!(fids(&summary.apply(&full, None)), [1, 3]);
+
+        let mut dictionary = SemanticDictionary::new();
+        dictionary.add_importance(12, 200);
+        dictionary.add_importance(7, 100);
+        assert_eq!(fids(&summary.apply(&full, Some(&dictionary))), [7, 12]);
+    }
+
+    #[test]
+    fn test_plan_from_decision_and_dedup() {
+        assert!(RoutingPlan::from(RoutingDecision::Drop).is_drop());
+        let plan = RoutingPlan::from(RoutingDecision::ProcessLocally).with_delivery(
+            Delivery::new(Destination::Local).with_transform(Transform::Fields(vec![1])),
+        );
+        assert_eq!(plan.deliveries, [Delivery::new(Destination::Local)]);
+    }
+
+    #[test]
+    fn test_fan_out_taps() {
+        let live = RuleCondition {
+            expired: Some(false),
+            ..RuleCondition::default()
+        };
+        let planner = FanOutPolicy::default()
+            .with_tap(live, Delivery::new(Destination::channel("archive")))
+            .with_tap(
+                RuleCondition::default(),
+                Delivery::new(Destination::channel("audit"))
+                    .with_transform(Transform::Fields(vec![1])),
+            );
+
+        let envelope = EnvelopeBuilder::new(record(&[1, 2]))
+            .timestamp(1000)
+            .build();
+        let msg = NetMessage::with_qos(envelope, MessageKind::Command, 100, 5000);
+
+        let plan = planner.plan(&msg, 2000).unwrap();
+        let destinations: Vec<_> = plan.deliveries.iter().map(|d| &d.destination).collect();
+        assert_eq!(
+            destinations,
+            [
+                &Destination::Local,
+                &Destination::channel("archive"),
+                &Destination::channel("audit")
+            ]
+        );
+
+        // Expired: the router drops it, only the unconditional tap remains
+        let plan = planner.plan(&msg, 10_000).unwrap();
+        let rendered = plan.render(&msg, None);
+        assert_eq!(rendered.len(), 1);
+        assert_eq!(rendered[0].0, &Destination::channel("audit"));
+        assert_eq!(fids(rendered[0].1.record()), [1]);
+    }
+}
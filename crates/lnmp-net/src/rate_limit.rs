@@ -0,0 +1,311 @@
+//! Token-bucket rate limiting for LLM routing
+//!
+//! A [`RateLimiter`] keeps one token bucket per (source, kind) pair. A
+//! [`RoutingPolicy`](crate::RoutingPolicy) configured with one spends a token
+//! for every message it would send to the LLM and routes the message locally
+//! instead when the bucket is empty, so a flooding source cannot exhaust the
+//! LLM budget of everyone else.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::kind::MessageKind;
+
+/// Burst size and steady refill rate of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Bucket capacity: messages allowed back to back after a quiet period
+    pub burst: u32,
+    /// Tokens added per second
+    pub per_second: f64,
+}
+
+impl RateLimit {
+    /// Creates a limit of `burst` messages refilled at `per_second`
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        Self { burst, per_second }
+    }
+}
+
+/// Counters collected by a [`RateLimiter`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitMetrics {
+    /// Messages that got a token
+    pub allowed: u64,
+    /// Messages refused a token
+    pub limited: u64,
+    /// Refusals per source (`""` for messages without a source)
+    pub limited_by_source: HashMap<String, u64>,
+    /// Number of buckets currently tracked
+    pub buckets: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_ms: u64,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: &RateLimit, now_ms: u64) {
+        let elapsed_ms = now_ms.saturating_sub(self.updated_ms);
+        self.tokens =
+            (self.tokens + elapsed_ms as f64 * limit.per_second / 1000.0).min(limit.burst as f64);
+        self.updated_ms = self.updated_ms.max(now_ms);
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    buckets: HashMap<(String, MessageKind), Bucket>,
+    metrics: RateLimitMetrics,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    default: RateLimit,
+    by_source: HashMap<String, RateLimit>,
+    by_kind: HashMap<MessageKind, RateLimit>,
+}
+
+/// Per-source, per-kind token buckets
+///
+/// Clones share buckets and metrics. The limit of a bucket is the source
+/// override if one is set, else the kind override, else the default.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_net::{MessageKind, RateLimit, RateLimiter};
+///
+/// let limiter = RateLimiter::new(RateLimit::new(2, 1.0));
+///
+/// assert!(limiter.try_acquire(Some("sensor-7"), MessageKind::Alert, 0));
+/// assert!(limiter.try_acquire(Some("sensor-7"), MessageKind::Alert, 0));
+/// assert!(!limiter.try_acquire(Some("sensor-7"), MessageKind::Alert, 0));
+///
+/// // Other sources have their own bucket; one token is back after a second
+/// assert!(limiter.try_acquire(Some("sensor-8"), MessageKind::Alert, 0));
+/// assert!(limiter.try_acquire(Some("sensor-7"), MessageKind::Alert, 1000));
+/// assert_eq!(limiter.metrics().limited, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter applying `default` to every (source, kind) pair
+    pub fn new(default: RateLimit) -> Self {
+        Self {
+            config: Arc::new(Config {
+                default,
+                by_source: HashMap::new(),
+                by_kind: HashMap::new(),
+            }),
+            state: Arc::new(Mutex::new(State {
+                buckets: HashMap::new(),
+                metrics: RateLimitMetrics::default(),
+            })),
+        }
+    }
+
+    /// Overrides the limit for one source
+    pub fn with_source_limit(mut self, source: impl Into<String>, limit: RateLimit) -> Self {
+        Arc::make_mut(&mut self.config)
+            .by_source
+            .insert(source.into(), limit);
+        self
+    }
+
+    /// Overrides the limit for one message kind
+    pub fn with_kind_limit(mut self, kind: MessageKind, limit: RateLimit) -> Self {
+        Arc::make_mut(&mut self.config).by_kind.insert(kind, limit);
+        self
+    }
+
+    /// Returns the limit applied to `source` and `kind`
+    pub fn limit_for(&self, source: Option<&str>, kind: MessageKind) -> RateLimit {
+        source
+            .and_then(|source| self.config.by_source.get(source))
+            .or_else(|| self.config.by_kind.get(&kind))
+            .copied()
+            .unwrap_or(self.config.default)
+    }
+
+    /// Takes a token for `source` and `kind` at `now_ms`; false if none is left
+    pub fn try_acquire(&self, source: Option<&str>, kind: MessageKind, now_ms: u64) -> bool {
+        let limit = self.limit_for(source, kind);
+        let source = source.unwrap_or_default();
+        let mut state = self.lock();
+
+        let bucket = state
+            .buckets
+            .entry((source.to_string(), kind))
+            .or_insert(Bucket {
+                tokens: limit.burst as f64,
+                updated_ms: now_ms,
+            });
+        bucket.refill(&limit, now_ms);
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        let buckets = state.buckets.len();
+        let metrics = &mut state.metrics;
+        metrics.buckets = buckets;
+        if allowed {
+            metrics.allowed += 1;
+        } else {
+            metrics.limited += 1;
+            *metrics
+                .limited_by_source
+                .entry(source.to_string())
+                .or_default() += 1;
+        }
+        allowed
+    }
+
+    /// Gives back the token taken for a message that was not sent after all
+    ///
+    /// The bucket never grows past its burst size.
+    pub fn release(&self, source: Option<&str>, kind: MessageKind) {
+        let limit = self.limit_for(source, kind);
+        let source = source.unwrap_or_default();
+        let mut state = self.lock();
+        if let Some(bucket) = state.buckets.get_mut(&(source.to_string(), kind)) {
+            bucket.tokens = (bucket.tokens + 1.0).min(limit.burst as f64);
+        }
+    }
+
+    /// Forgets buckets that have refilled completely by `now_ms`
+    ///
+    /// A full bucket behaves like a fresh one, so pruning only bounds memory
+    /// when many short-lived sources appear.
+    pub fn prune(&self, now_ms: u64) {
+        let mut state = self.lock();
+        state.buckets.retain(|(source, kind), bucket| {
+            let limit = self.limit_for(Some(source.as_str()), *kind);
+            bucket.refill(&limit, now_ms);
+            bucket.tokens < limit.burst as f64
+        });
+        state.metrics.buckets = state.buckets.len();
+    }
+
+    /// Returns a snapshot of the counters
+    pub fn metrics(&self) -> RateLimitMetrics {
+        self.lock().metrics.clone()
+    }
+
+    /// Resets the counters, keeping bucket state
+    pub fn reset_metrics(&self) {
+        let mut state = self.lock();
+        let buckets = state.buckets.len();
+        state.metrics = RateLimitMetrics {
+            buckets,
+            ..RateLimitMetrics::default()
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Bucket state stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steady_rate_refills() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 2.0));
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 100));
+        // 2 tokens/s -> one token after 500ms
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 500));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 500));
+        // Refill is capped at the burst size
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 60_000));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 60_000));
+    }
+
+    #[test]
+    fn test_release_returns_token() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 0.0));
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        limiter.release(Some("a"), MessageKind::Event);
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+
+        // Capped at the burst size
+        limiter.release(Some("a"), MessageKind::Event);
+        limiter.release(Some("a"), MessageKind::Event);
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+    }
+
+    #[test]
+    fn test_buckets_keyed_by_source_and_kind() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 0.0));
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Alert, 0));
+        assert!(limiter.try_acquire(Some("b"), MessageKind::Event, 0));
+        assert!(limiter.try_acquire(None, MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(None, MessageKind::Event, 0));
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.allowed, 4);
+        assert_eq!(metrics.limited, 2);
+        assert_eq!(metrics.buckets, 4);
+        assert_eq!(metrics.limited_by_source["a"], 1);
+        assert_eq!(metrics.limited_by_source[""], 1);
+    }
+
+    #[test]
+    fn test_limit_overrides() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 1.0))
+            .with_kind_limit(MessageKind::Alert, RateLimit::new(5, 1.0))
+            .with_source_limit("gateway", RateLimit::new(100, 50.0));
+
+        assert_eq!(
+            limiter.limit_for(Some("sensor"), MessageKind::Event),
+            RateLimit::new(1, 1.0)
+        );
+        assert_eq!(
+            limiter.limit_for(Some("sensor"), MessageKind::Alert),
+            RateLimit::new(5, 1.0)
+        );
+        assert_eq!(
+            limiter.limit_for(Some("gateway"), MessageKind::Alert),
+            RateLimit::new(100, 50.0)
+        );
+        assert_eq!(
+            limiter.limit_for(None, MessageKind::Query),
+            RateLimit::new(1, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_clones_share_state_and_prune() {
+        let limiter = RateLimiter::new(RateLimit::new(2, 1.0));
+        let clone = limiter.clone();
+        assert!(limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(clone.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(!limiter.try_acquire(Some("a"), MessageKind::Event, 0));
+        assert!(clone.try_acquire(Some("b"), MessageKind::Event, 0));
+        assert_eq!(clone.metrics().limited, 1);
+
+        // "a" is full again after 2s, "b" after 1s
+        limiter.prune(1000);
+        assert_eq!(limiter.metrics().buckets, 1);
+        limiter.prune(2000);
+        assert_eq!(limiter.metrics().buckets, 0);
+
+        limiter.reset_metrics();
+        assert_eq!(clone.metrics(), RateLimitMetrics::default());
+    }
+}
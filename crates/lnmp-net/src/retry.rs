@@ -0,0 +1,386 @@
+//! Retry and backoff for failed LLM deliveries
+//!
+//! A [`RetryPolicy`] decides what happens when delivering a
+//! [`SendToLLM`](crate::RoutingDecision::SendToLLM) message fails: retry after
+//! an exponential, jittered backoff, or give up. Attempt counts live in the
+//! message's [`RetryState`], so they survive re-queuing in a
+//! [`NetScheduler`](crate::NetScheduler), which holds a message back until its
+//! next attempt is due. An optional [`RetryBudget`] caps retries relative to
+//! successful deliveries, so an outage doesn't turn into a retry storm.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::message::NetMessage;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Delivery attempts recorded on a message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RetryState {
+    /// Failed delivery attempts so far
+    pub attempts: u32,
+    /// Earliest time (epoch milliseconds) of the next attempt
+    pub next_attempt_ms: Option<u64>,
+}
+
+impl RetryState {
+    /// Returns true if the message may be delivered at `now_ms`
+    pub fn is_due(&self, now_ms: u64) -> bool {
+        self.next_attempt_ms.is_none_or(|at| at <= now_ms)
+    }
+}
+
+/// Why a [`RetryPolicy`] gave up on a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GiveUpReason {
+    /// `max_attempts` deliveries failed
+    MaxAttempts,
+    /// The message would expire before the next attempt
+    Expired,
+    /// The shared retry budget is spent
+    BudgetExhausted,
+}
+
+/// Outcome of [`RetryPolicy::on_failure`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Deliver again at `at_ms`; this will be attempt number `attempt`
+    Retry {
+        /// Time (epoch milliseconds) of the next attempt
+        at_ms: u64,
+        /// 1-based number of the next attempt
+        attempt: u32,
+    },
+    /// Stop retrying; the caller should dead-letter or report the message
+    GiveUp {
+        /// Why retrying stopped
+        reason: GiveUpReason,
+        /// Failed attempts, including the last one
+        attempts: u32,
+    },
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    tokens: f64,
+}
+
+/// Retry allowance shared by all messages using a policy
+///
+/// Starts with `max_tokens`; every retry spends one token and every successful
+/// delivery earns back `token_ratio`. Clones share the same tokens.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    max_tokens: f64,
+    token_ratio: f64,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+impl RetryBudget {
+    /// Creates a full budget of `max_tokens` refilled by `token_ratio` per success
+    pub fn new(max_tokens: f64, token_ratio: f64) -> Self {
+        Self {
+            max_tokens,
+            token_ratio,
+            state: Arc::new(Mutex::new(BudgetState { tokens: max_tokens })),
+        }
+    }
+
+    /// Returns the tokens currently available
+    pub fn tokens(&self) -> f64 {
+        self.lock().tokens
+    }
+
+    /// Spends a token for one retry; false if the budget is spent
+    pub fn try_withdraw(&self) -> bool {
+        let mut state = self.lock();
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Earns back `token_ratio` tokens after a successful delivery
+    pub fn deposit(&self) {
+        let mut state = self.lock();
+        state.tokens = (state.tokens + self.token_ratio).min(self.max_tokens);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        // The token count stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Exponential backoff with jitter, attempt limit and optional budget
+///
+/// The backoff before attempt `n + 1` is
+/// `min(initial_backoff_ms * multiplier^(n - 1), max_backoff_ms)`, reduced by
+/// up to `jitter` (0.0-1.0) of itself. Jitter is derived from the message and
+/// attempt number rather than a random source, so it spreads retries of
+/// different messages while staying reproducible.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{GiveUpReason, MessageKind, NetMessage, RetryDecision, RetryPolicy};
+///
+/// let policy = RetryPolicy::new(3).with_backoff(100, 1000).with_jitter(0.0);
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(0).build();
+/// let mut msg = NetMessage::with_qos(envelope, MessageKind::Alert, 255, 60_000);
+///
+/// // Delivery failed twice: back off 100ms, then 200ms
+/// assert_eq!(
+///     policy.on_failure(&mut msg, 0),
+///     RetryDecision::Retry { at_ms: 100, attempt: 2 }
+/// );
+/// assert_eq!(
+///     policy.on_failure(&mut msg, 100),
+///     RetryDecision::Retry { at_ms: 300, attempt: 3 }
+/// );
+///
+/// // Third failure exhausts the attempts
+/// assert_eq!(
+///     policy.on_failure(&mut msg, 300),
+///     RetryDecision::GiveUp { reason: GiveUpReason::MaxAttempts, attempts: 3 }
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total delivery attempts, including the first
+    pub max_attempts: u32,
+    /// Backoff before the first retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound on any backoff
+    pub max_backoff_ms: u64,
+    /// Backoff growth factor per attempt
+    pub multiplier: f64,
+    /// Fraction (0.0-1.0) of each backoff that may be shaved off pseudo-randomly
+    pub jitter: f64,
+    /// Shared allowance of retries, if any
+    pub budget: Option<RetryBudget>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy allowing `max_attempts` deliveries in total
+    ///
+    /// Backoff starts at 100ms, doubles per attempt up to 30s, with 20% jitter.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 30_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+            budget: None,
+        }
+    }
+
+    /// Sets the first and the largest backoff
+    pub fn with_backoff(mut self, initial_ms: u64, max_ms: u64) -> Self {
+        self.initial_backoff_ms = initial_ms;
+        self.max_backoff_ms = max_ms;
+        self
+    }
+
+    /// Sets the backoff growth factor
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the jitter fraction (clamped to 0.0-1.0)
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the retry budget; clones of the budget share its tokens
+    pub fn with_budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Returns the backoff after `attempts` failures, before jitter
+    pub fn base_backoff_ms(&self, attempts: u32) -> u64 {
+        let exponent = attempts.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff_ms as f64 * self.multiplier.powi(exponent);
+        if backoff.is_finite() {
+            (backoff as u64).min(self.max_backoff_ms)
+        } else {
+            self.max_backoff_ms
+        }
+    }
+
+    /// Records a failed delivery of `msg` at `now_ms` and decides what's next
+    ///
+    /// On [`RetryDecision::Retry`] the message's [`RetryState`] holds the time
+    /// of the next attempt; re-queue it as is.
+    pub fn on_failure(&self, msg: &mut NetMessage, now_ms: u64) -> RetryDecision {
+        msg.retry.attempts = msg.retry.attempts.saturating_add(1);
+        let attempts = msg.retry.attempts;
+        let give_up = |reason| RetryDecision::GiveUp { reason, attempts };
+
+        if attempts >= self.max_attempts {
+            return give_up(GiveUpReason::MaxAttempts);
+        }
+
+        let at_ms = now_ms.saturating_add(self.backoff_ms(msg, attempts));
+        if msg.deadline_ms().is_some_and(|deadline| deadline <= at_ms) {
+            return give_up(GiveUpReason::Expired);
+        }
+        if let Some(budget) = &self.budget {
+            if !budget.try_withdraw() {
+                return give_up(GiveUpReason::BudgetExhausted);
+            }
+        }
+
+        msg.retry.next_attempt_ms = Some(at_ms);
+        RetryDecision::Retry {
+            at_ms,
+            attempt: attempts + 1,
+        }
+    }
+
+    /// Records a successful delivery of `msg`
+    ///
+    /// Clears its retry state and refills the budget.
+    pub fn on_success(&self, msg: &mut NetMessage) {
+        msg.retry = RetryState::default();
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+    }
+
+    fn backoff_ms(&self, msg: &NetMessage, attempts: u32) -> u64 {
+        let base = self.base_backoff_ms(attempts);
+        if self.jitter <= 0.0 {
+            return base;
+        }
+        let metadata = &msg.envelope.metadata;
+        let mut hasher = DefaultHasher::new();
+        (
+            metadata.source.as_deref(),
+            metadata.timestamp,
+            metadata.sequence,
+            attempts,
+        )
+            .hash(&mut hasher);
+        let seed = hasher.finish();
+        let unit = (seed >> 11) as f64 / (1u64 << 53) as f64;
+        base - (base as f64 * self.jitter * unit) as u64
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::MessageKind;
+    use lnmp_core::LnmpRecord;
+    use lnmp_envelope::EnvelopeBuilder;
+
+    fn message(source: &str, ttl_ms: u32) -> NetMessage {
+        let envelope = EnvelopeBuilder::new(LnmpRecord::new())
+            .timestamp(0)
+            .source(source)
+            .build();
+        NetMessage::with_qos(envelope, MessageKind::Alert, 255, ttl_ms)
+    }
+
+    #[test]
+    fn test_base_backoff_grows_and_caps() {
+        let policy = RetryPolicy::new(10).with_backoff(100, 1000);
+        let backoffs: Vec<_> = (1..=6).map(|n| policy.base_backoff_ms(n)).collect();
+        assert_eq!(backoffs, [100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.base_backoff_ms(u32::MAX), 1000);
+    }
+
+    #[test]
+    fn test_jitter_bounded_and_reproducible() {
+        let policy = RetryPolicy::new(10)
+            .with_backoff(1000, 1000)
+            .with_jitter(0.5);
+        let delays: Vec<_> = ["a", "b", "c", "d"]
+            .iter()
+            .map(
+                |source| match policy.on_failure(&mut message(source, 60_000), 0) {
+                    RetryDecision::Retry { at_ms, .. } => at_ms,
+                    other => panic!("expected retry, got {:?}", other),
+                },
+            )
+            .collect();
+        assert!(delays.iter().all(|d| (500..=1000).contains(d)));
+        assert!(delays.iter().any(|d| *d != delays[0]));
+
+        let again = policy.on_failure(&mut message("a", 60_000), 0);
+        assert_eq!(
+            again,
+            RetryDecision::Retry {
+                at_ms: delays[0],
+                attempt: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_gives_up_before_expiry() {
+        let policy = RetryPolicy::new(10)
+            .with_backoff(1000, 1000)
+            .with_jitter(0.0);
+        let mut msg = message("a", 1500);
+        assert!(matches!(
+            policy.on_failure(&mut msg, 0),
+            RetryDecision::Retry { at_ms: 1000, .. }
+        ));
+        assert!(!msg.retry.is_due(999));
+        assert!(msg.retry.is_due(1000));
+        assert_eq!(
+            policy.on_failure(&mut msg, 1000),
+            RetryDecision::GiveUp {
+                reason: GiveUpReason::Expired,
+                attempts: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_budget_shared_and_refilled() {
+        let budget = RetryBudget::new(2.0, 0.5);
+        let policy = RetryPolicy::new(10).with_budget(budget.clone());
+        let mut msg = message("a", 60_000);
+
+        assert!(matches!(
+            policy.on_failure(&mut msg, 0),
+            RetryDecision::Retry { .. }
+        ));
+        assert!(matches!(
+            policy.clone().on_failure(&mut message("b", 60_000), 0),
+            RetryDecision::Retry { .. }
+        ));
+        assert_eq!(
+            policy.on_failure(&mut msg, 0),
+            RetryDecision::GiveUp {
+                reason: GiveUpReason::BudgetExhausted,
+                attempts: 2
+            }
+        );
+
+        policy.on_success(&mut msg);
+        assert_eq!(msg.retry, RetryState::default());
+        policy.on_success(&mut message("c", 60_000));
+        assert_eq!(budget.tokens(), 1.0);
+    }
+}
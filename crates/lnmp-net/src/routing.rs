@@ -1,12 +1,27 @@
 //! Routing logic for LNMP-Net messages
+//!
+//! [`Router`] and [`AsyncRouter`] are the extension points for custom policies
+//! (ML-scored, table-driven, remote); [`RoutingPolicy`] is the built-in ECO
+//! implementation.
+
+use std::future::Future;
+use std::sync::Arc;
 
 use lnmp_sfe::{ContextScorer, ContextScorerConfig};
 
+use crate::budget::{ComplexityTokenEstimator, TokenBudget, TokenEstimator};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::complexity::{ComplexityConfig, RecordComplexity};
+use crate::dead_letter::{DeadLetter, DeadLetterSink, DropReason};
+use crate::dedup::DedupFilter;
 use crate::error::Result;
+use crate::kind::MessageKind;
 use crate::message::NetMessage;
+use crate::rate_limit::RateLimiter;
+use crate::stats::{RouteReason, RoutingStats};
 
 /// Routing decision for a message
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RoutingDecision {
     /// Send message to LLM for processing
     SendToLLM,
@@ -16,13 +31,87 @@ pub enum RoutingDecision {
     Drop,
 }
 
+/// Decides where a message goes
+///
+/// Implementations see the whole [`NetMessage`], so they can build on its QoS
+/// helpers ([`NetMessage::is_expired`], [`NetMessage::age_ms`], priority and
+/// kind) and fall back to [`RoutingPolicy`] for cases they don't handle.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{MessageKind, NetMessage, Result, Router, RoutingDecision, RoutingPolicy};
+///
+/// /// Fixed decision per source, ECO policy for everything else
+/// struct SourceTable {
+///     table: HashMap<String, RoutingDecision>,
+///     fallback: RoutingPolicy,
+/// }
+///
+/// impl Router for SourceTable {
+///     fn route(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision> {
+///         let source = msg.envelope.metadata.source.as_deref().unwrap_or_default();
+///         match self.table.get(source) {
+///             Some(decision) => Ok(*decision),
+///             None => self.fallback.route(msg, now_ms),
+///         }
+///     }
+/// }
+///
+/// let router = SourceTable {
+///     table: HashMap::from([("noisy-sensor".to_string(), RoutingDecision::Drop)]),
+///     fallback: RoutingPolicy::default(),
+/// };
+///
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new())
+///     .timestamp(1000)
+///     .source("noisy-sensor")
+///     .build();
+/// let msg = NetMessage::new(envelope, MessageKind::Alert);
+/// assert_eq!(router.route(&msg, 2000).unwrap(), RoutingDecision::Drop);
+/// ```
+pub trait Router {
+    /// Decides how to route `msg` at `now_ms` (epoch milliseconds)
+    fn route(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision>;
+}
+
+/// Router whose decision may wait on I/O, e.g. a model server or remote table
+///
+/// Every [`Router`] that is `Sync` is also an `AsyncRouter` whose future is
+/// immediately ready, so async pipelines can accept either.
+pub trait AsyncRouter {
+    /// Decides how to route `msg` at `now_ms` (epoch milliseconds)
+    fn route_async(
+        &self,
+        msg: &NetMessage,
+        now_ms: u64,
+    ) -> impl Future<Output = Result<RoutingDecision>> + Send;
+}
+
+impl<R: Router + Sync> AsyncRouter for R {
+    fn route_async(
+        &self,
+        msg: &NetMessage,
+        now_ms: u64,
+    ) -> impl Future<Output = Result<RoutingDecision>> + Send {
+        let decision = self.route(msg, now_ms);
+        async move { decision }
+    }
+}
+
 /// Policy for routing messages to LLM vs local processing
 ///
 /// Implements the ECO (Energy/Token Optimization) profile logic:
 /// - Alerts with high priority always routed to LLM
 /// - Expired messages dropped
+/// - Repeats of a recent message dropped (if a [`DedupFilter`] is set)
 /// - Event/State messages scored using SFE and routed based on threshold
-/// - Commands/Queries typically processed locally
+/// - Commands/Queries processed locally unless their record's complexity score
+///   reaches `complexity_threshold`
 ///
 /// # Examples
 ///
@@ -51,6 +140,35 @@ pub struct RoutingPolicy {
     /// Automatically drop expired messages
     pub drop_expired: bool,
 
+    /// Minimum complexity score (0.0-1.0) for a Command/Query to be routed to LLM
+    pub complexity_threshold: f64,
+
+    /// Budgets used to compute record complexity
+    pub complexity_config: ComplexityConfig,
+
+    /// Drops messages equivalent to one seen within its window
+    pub dedup: Option<DedupFilter>,
+
+    /// Limits LLM routing per source and kind; over-limit messages are
+    /// processed locally instead
+    pub rate_limiter: Option<RateLimiter>,
+
+    /// Caps LLM tokens per window; low-importance messages are processed
+    /// locally once it runs low
+    pub token_budget: Option<TokenBudget>,
+
+    /// Estimates the LLM tokens of a record for the budget and statistics
+    pub token_estimator: Arc<dyn TokenEstimator>,
+
+    /// Guards the LLM destination; while open, messages are processed locally
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    /// Receives every message this policy drops, with the reason
+    pub dead_letters: Option<Arc<dyn DeadLetterSink>>,
+
+    /// Counts every decision this policy makes
+    pub stats: Option<RoutingStats>,
+
     /// SFE scorer for computing importance/freshness
     scorer_config: ContextScorerConfig,
 }
@@ -62,6 +180,15 @@ impl RoutingPolicy {
             llm_threshold,
             always_route_alerts: true,
             drop_expired: true,
+            complexity_threshold: 1.0,
+            complexity_config: ComplexityConfig::default(),
+            dedup: None,
+            rate_limiter: None,
+            token_budget: None,
+            token_estimator: Arc::new(ComplexityTokenEstimator),
+            circuit_breaker: None,
+            dead_letters: None,
+            stats: None,
             scorer_config: ContextScorerConfig::default(),
         }
     }
@@ -78,6 +205,78 @@ impl RoutingPolicy {
         self
     }
 
+    /// Sets the complexity score at which Commands/Queries are routed to LLM
+    pub fn with_complexity_threshold(mut self, threshold: f64) -> Self {
+        self.complexity_threshold = threshold;
+        self
+    }
+
+    /// Sets the budgets used to compute record complexity
+    pub fn with_complexity_config(mut self, config: ComplexityConfig) -> Self {
+        self.complexity_config = config;
+        self
+    }
+
+    /// Sets the duplicate filter applied before any other routing step
+    ///
+    /// Like the rate limiter, the filter is shared with its clones.
+    pub fn with_dedup(mut self, filter: DedupFilter) -> Self {
+        self.dedup = Some(filter);
+        self
+    }
+
+    /// Sets the rate limiter consulted before routing to LLM
+    ///
+    /// The limiter is shared with its clones, so one limiter can cap several
+    /// policies together.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Sets the token budget charged before routing to LLM
+    ///
+    /// The budget is shared with its clones, so one budget can cap several
+    /// policies together.
+    pub fn with_token_budget(mut self, budget: TokenBudget) -> Self {
+        self.token_budget = Some(budget);
+        self
+    }
+
+    /// Sets how the LLM tokens of a record are estimated
+    ///
+    /// Defaults to [`ComplexityTokenEstimator`]; with the `llb` feature,
+    /// `lnmp_llb::TokenEstimator` estimates from the rendered text.
+    pub fn with_token_estimator(mut self, estimator: impl TokenEstimator + 'static) -> Self {
+        self.token_estimator = Arc::new(estimator);
+        self
+    }
+
+    /// Sets the circuit breaker consulted last before routing to LLM
+    ///
+    /// Every SendToLLM decision takes a dispatch permit from the breaker, so
+    /// the caller must report the outcome of the delivery with
+    /// [`CircuitBreaker::record_success`] or [`CircuitBreaker::record_failure`].
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Sets the sink that captures dropped messages
+    ///
+    /// Routing fails if the sink fails, so a message is never dropped without
+    /// either being captured or the caller knowing.
+    pub fn with_dead_letter_sink(mut self, sink: Arc<dyn DeadLetterSink>) -> Self {
+        self.dead_letters = Some(sink);
+        self
+    }
+
+    /// Sets the statistics every decision is recorded in
+    pub fn with_stats(mut self, stats: RoutingStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
     /// Sets custom SFE scorer configuration
     pub fn with_scorer_config(mut self, config: ContextScorerConfig) -> Self {
         self.scorer_config = config;
@@ -88,38 +287,26 @@ impl RoutingPolicy {
     ///
     /// Decision flow:
     /// 1. Check expiry (if enabled) -> Drop
-    /// 2. Check if Alert + high priority -> SendToLLM
-    /// 3. For Event/State: compute importance score -> threshold check
-    /// 4. Commands/Queries -> ProcessLocally
+    /// 2. Duplicate of a recent message (if a filter is set) -> Drop
+    /// 3. Check if Alert + high priority -> SendToLLM
+    /// 4. For Event/State: compute importance score -> threshold check
+    /// 5. Commands/Queries -> SendToLLM if complex, otherwise ProcessLocally
+    /// 6. SendToLLM over the source's rate limit (if set) -> ProcessLocally
+    /// 7. SendToLLM the token budget (if set) can't afford -> ProcessLocally
+    /// 8. SendToLLM while the circuit breaker (if set) refuses -> ProcessLocally
     ///
     /// # Arguments
     ///
     /// * `msg` - The message to route
     /// * `now_ms` - Current time in epoch milliseconds
     pub fn decide(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision> {
-        // 1. Check expiry
-        if self.drop_expired && msg.is_expired(now_ms)? {
-            return Ok(RoutingDecision::Drop);
+        let (decision, reason, importance, tokens) = self.evaluate(msg, now_ms)?;
+        if let Some(stats) = &self.stats {
+            let tokens =
+                tokens.unwrap_or_else(|| self.token_estimator.estimate_tokens(msg.record()));
+            stats.record(msg.kind, decision, reason, importance, tokens);
         }
-
-        // 2. Always route high-priority alerts
-        if self.always_route_alerts && msg.kind.is_alert() && msg.priority > 200 {
-            return Ok(RoutingDecision::SendToLLM);
-        }
-
-        // 3. For Event/State: compute importance and check threshold
-        if msg.kind.is_event() || msg.kind.is_state() {
-            let importance = self.base_importance(msg, now_ms)?;
-            return if importance >= self.llm_threshold {
-                Ok(RoutingDecision::SendToLLM)
-            } else {
-                Ok(RoutingDecision::ProcessLocally)
-            };
-        }
-
-        // 4. Commands and Queries: local processing by default
-        // (Future: complex queries could be routed to LLM)
-        Ok(RoutingDecision::ProcessLocally)
+        Ok(decision)
     }
 
     /// Decides how to route a message (Zero-Copy View)
@@ -129,8 +316,9 @@ impl RoutingPolicy {
     /// * `kind` - Message kind
     /// * `priority` - Message priority
     /// * `metadata` - Envelope metadata
-    /// * `expires_at` - Expiration timestamp (if any)
-    /// * `record_view` - The record view (currently unused by default policy but available for extensions)
+    /// * `expires_at` - Expiration timestamp (if any); `metadata.expires_at` wins when set
+    /// * `record_view` - The record view, scored for complexity when routing Commands/Queries
+    ///   and compared by the duplicate filter
     /// * `now_ms` - Current time in epoch milliseconds
     pub fn decide_view(
         &self,
@@ -138,35 +326,234 @@ impl RoutingPolicy {
         priority: u8,
         metadata: &lnmp_envelope::EnvelopeMetadata,
         expires_at: Option<u64>,
-        _record_view: &lnmp_core::LnmpRecordView,
+        record_view: &lnmp_core::LnmpRecordView,
         now_ms: u64,
     ) -> Result<RoutingDecision> {
+        let (decision, reason, importance, tokens) =
+            self.evaluate_view(kind, priority, metadata, expires_at, record_view, now_ms)?;
+        if let Some(stats) = &self.stats {
+            let tokens =
+                tokens.unwrap_or_else(|| self.token_estimator.estimate_tokens_view(record_view));
+            stats.record(kind, decision, reason, importance, tokens);
+        }
+        Ok(decision)
+    }
+
+    fn evaluate(&self, msg: &NetMessage, now_ms: u64) -> Result<Evaluation> {
+        // 1. Check expiry
+        if self.drop_expired && msg.is_expired(now_ms)? {
+            self.dead_letter(msg, DropReason::Expired, now_ms)?;
+            return Ok((RoutingDecision::Drop, RouteReason::Expired, None, None));
+        }
+
+        // 2. Drop repeats
+        if let Some(dedup) = &self.dedup {
+            if dedup.is_duplicate(msg, now_ms) {
+                self.dead_letter(msg, DropReason::Duplicate, now_ms)?;
+                return Ok((RoutingDecision::Drop, RouteReason::Duplicate, None, None));
+            }
+        }
+
+        let (decision, reason, importance) =
+            if self.always_route_alerts && msg.kind.is_alert() && msg.priority > 200 {
+                // 3. Always route high-priority alerts
+                (RoutingDecision::SendToLLM, RouteReason::CriticalAlert, None)
+            } else if msg.kind.is_event() || msg.kind.is_state() {
+                // 4. For Event/State: compute importance and check threshold
+                let importance = self.base_importance(msg, now_ms)?;
+                let decision = self.route_by_importance(importance);
+                (decision, RouteReason::Importance, Some(importance))
+            } else {
+                // 5. Commands and Queries: local processing unless complex
+                let decision = self.route_by_complexity(&RecordComplexity::of(msg.record()));
+                (decision, RouteReason::Complexity, None)
+            };
+
+        let source = msg.envelope.metadata.source.as_deref();
+        let tokens = || self.token_estimator.estimate_tokens(msg.record());
+        Ok(self.apply_guards(
+            (decision, reason, importance),
+            tokens,
+            source,
+            msg.kind,
+            now_ms,
+        ))
+    }
+
+    fn evaluate_view(
+        &self,
+        kind: MessageKind,
+        priority: u8,
+        metadata: &lnmp_envelope::EnvelopeMetadata,
+        expires_at: Option<u64>,
+        record_view: &lnmp_core::LnmpRecordView,
+        now_ms: u64,
+    ) -> Result<Evaluation> {
+        let drop_as = |reason: DropReason| {
+            let route_reason = match reason {
+                DropReason::Duplicate => RouteReason::Duplicate,
+                _ => RouteReason::Expired,
+            };
+            if self.dead_letters.is_some() {
+                let mut metadata = metadata.clone();
+                metadata.expires_at = metadata.expires_at.or(expires_at);
+                let envelope = lnmp_envelope::LnmpEnvelope {
+                    record: record_view.to_lnmp_record(),
+                    metadata,
+                };
+                let mut msg = NetMessage::new(envelope, kind);
+                msg.priority = priority;
+                self.dead_letter(&msg, reason, now_ms)?;
+            }
+            Ok((RoutingDecision::Drop, route_reason, None, None))
+        };
+
         // 1. Check expiry
         if self.drop_expired {
-            if let Some(exp) = expires_at {
+            if let Some(exp) = metadata.expires_at.or(expires_at) {
                 if exp <= now_ms {
-                    return Ok(RoutingDecision::Drop);
+                    return drop_as(DropReason::Expired);
                 }
             }
         }
 
-        // 2. Always route high-priority alerts
-        if self.always_route_alerts && kind.is_alert() && priority > 200 {
-            return Ok(RoutingDecision::SendToLLM);
+        // 2. Drop repeats
+        if let Some(dedup) = &self.dedup {
+            let source = metadata.source.as_deref();
+            if dedup.is_duplicate_view(kind, source, record_view, now_ms) {
+                return drop_as(DropReason::Duplicate);
+            }
         }
 
-        // 3. For Event/State: compute importance and check threshold
-        if kind.is_event() || kind.is_state() {
-            let importance = self.base_importance_view(priority, metadata, now_ms)?;
-            return if importance >= self.llm_threshold {
-                Ok(RoutingDecision::SendToLLM)
+        let (decision, reason, importance) =
+            if self.always_route_alerts && kind.is_alert() && priority > 200 {
+                // 3. Always route high-priority alerts
+                (RoutingDecision::SendToLLM, RouteReason::CriticalAlert, None)
+            } else if kind.is_event() || kind.is_state() {
+                // 4. For Event/State: compute importance and check threshold
+                let importance = self.base_importance_view(priority, metadata, now_ms)?;
+                let decision = self.route_by_importance(importance);
+                (decision, RouteReason::Importance, Some(importance))
             } else {
-                Ok(RoutingDecision::ProcessLocally)
+                // 5. Commands and Queries: local processing unless complex
+                let decision = self.route_by_complexity(&RecordComplexity::of_view(record_view));
+                (decision, RouteReason::Complexity, None)
             };
+
+        let source = metadata.source.as_deref();
+        let tokens = || self.token_estimator.estimate_tokens_view(record_view);
+        Ok(self.apply_guards((decision, reason, importance), tokens, source, kind, now_ms))
+    }
+
+    /// Steps 6 to 8: downgrades SendToLLM when the source floods, the token
+    /// budget runs low or the LLM fails
+    ///
+    /// Tokens are only estimated for LLM-bound messages when a budget is set.
+    /// A later guard that refuses the message gives back what the earlier ones
+    /// took.
+    fn apply_guards(
+        &self,
+        (decision, reason, importance): (RoutingDecision, RouteReason, Option<f64>),
+        estimate_tokens: impl FnOnce() -> usize,
+        source: Option<&str>,
+        kind: MessageKind,
+        now_ms: u64,
+    ) -> Evaluation {
+        let limited = self.apply_rate_limit(decision, source, kind, now_ms);
+        if limited != decision {
+            return (limited, RouteReason::RateLimited, importance, None);
         }
+        // Nothing is sent when a later guard refuses, so nothing is spent
+        let release_rate_token = || {
+            if let Some(limiter) = &self.rate_limiter {
+                if decision == RoutingDecision::SendToLLM {
+                    limiter.release(source, kind);
+                }
+            }
+        };
+        let (budget, tokens) = match &self.token_budget {
+            Some(budget) if decision == RoutingDecision::SendToLLM => {
+                let tokens = estimate_tokens();
+                if !budget.try_spend(tokens, importance, now_ms) {
+                    release_rate_token();
+                    let local = RoutingDecision::ProcessLocally;
+                    return (local, RouteReason::TokenBudget, importance, Some(tokens));
+                }
+                (Some(budget), Some(tokens))
+            }
+            _ => (None, None),
+        };
+        let guarded = self.apply_circuit_breaker(decision, now_ms);
+        if guarded != decision {
+            release_rate_token();
+            if let (Some(budget), Some(tokens)) = (budget, tokens) {
+                budget.refund(tokens);
+            }
+            return (guarded, RouteReason::CircuitOpen, importance, tokens);
+        }
+        (decision, reason, importance, tokens)
+    }
 
-        // 4. Commands and Queries: local processing by default
-        Ok(RoutingDecision::ProcessLocally)
+    /// Estimates the LLM tokens of a message's record
+    pub fn estimate_tokens(&self, msg: &NetMessage) -> usize {
+        self.token_estimator.estimate_tokens(msg.record())
+    }
+
+    /// Computes the complexity score of a message's record (0.0-1.0)
+    pub fn complexity_score(&self, msg: &NetMessage) -> f64 {
+        RecordComplexity::of(msg.record()).score(&self.complexity_config)
+    }
+
+    fn dead_letter(&self, msg: &NetMessage, reason: DropReason, now_ms: u64) -> Result<()> {
+        match &self.dead_letters {
+            Some(sink) => sink.send(DeadLetter::new(msg.clone(), reason, now_ms)),
+            None => Ok(()),
+        }
+    }
+
+    fn route_by_importance(&self, importance: f64) -> RoutingDecision {
+        if importance >= self.llm_threshold {
+            RoutingDecision::SendToLLM
+        } else {
+            RoutingDecision::ProcessLocally
+        }
+    }
+
+    fn apply_rate_limit(
+        &self,
+        decision: RoutingDecision,
+        source: Option<&str>,
+        kind: MessageKind,
+        now_ms: u64,
+    ) -> RoutingDecision {
+        match &self.rate_limiter {
+            Some(limiter)
+                if decision == RoutingDecision::SendToLLM
+                    && !limiter.try_acquire(source, kind, now_ms) =>
+            {
+                RoutingDecision::ProcessLocally
+            }
+            _ => decision,
+        }
+    }
+
+    fn apply_circuit_breaker(&self, decision: RoutingDecision, now_ms: u64) -> RoutingDecision {
+        match &self.circuit_breaker {
+            Some(breaker)
+                if decision == RoutingDecision::SendToLLM && !breaker.try_acquire(now_ms) =>
+            {
+                RoutingDecision::ProcessLocally
+            }
+            _ => decision,
+        }
+    }
+
+    fn route_by_complexity(&self, complexity: &RecordComplexity) -> RoutingDecision {
+        if complexity.score(&self.complexity_config) >= self.complexity_threshold {
+            RoutingDecision::SendToLLM
+        } else {
+            RoutingDecision::ProcessLocally
+        }
     }
 
     /// Computes base importance score for a message (0.0-1.0)
@@ -223,12 +610,22 @@ impl RoutingPolicy {
     }
 }
 
+/// Decision, the reason for it, the importance score it was based on, and the
+/// token estimate if one was made
+type Evaluation = (RoutingDecision, RouteReason, Option<f64>, Option<usize>);
+
 impl Default for RoutingPolicy {
     fn default() -> Self {
         Self::new(0.7)
     }
 }
 
+impl Router for RoutingPolicy {
+    fn route(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision> {
+        self.decide(msg, now_ms)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -479,5 +876,83 @@ mod tests {
             )
             .unwrap();
         assert_eq!(decision_expired, RoutingDecision::Drop);
+
+        // 3. Envelope expiry overrides the explicit value
+        let mut metadata = metadata;
+        metadata.expires_at = Some(1800);
+        let decision_envelope = policy
+            .decide_view(
+                MessageKind::Alert,
+                250,
+                &metadata,
+                Some(5000),
+                &empty_view,
+                2000,
+            )
+            .unwrap();
+        assert_eq!(decision_envelope, RoutingDecision::Drop);
+    }
+
+    #[test]
+    fn test_complex_command_sent_to_llm() {
+        let policy = RoutingPolicy::default();
+        let mut record = sample_record();
+        record.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::IntArray((0..100).collect()),
+        });
+        let envelope = EnvelopeBuilder::new(record).timestamp(1000).build();
+        let msg = NetMessage::new(envelope, MessageKind::Command);
+
+        assert_eq!(policy.complexity_score(&msg), 1.0);
+        assert_eq!(
+            policy.decide(&msg, 2000).unwrap(),
+            RoutingDecision::SendToLLM
+        );
+    }
+
+    #[test]
+    fn test_complexity_threshold_for_queries() {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .build();
+        let msg = NetMessage::new(envelope, MessageKind::Query);
+
+        let default_policy = RoutingPolicy::default();
+        assert!(default_policy.complexity_score(&msg) < 0.1);
+        assert_eq!(
+            default_policy.decide(&msg, 2000).unwrap(),
+            RoutingDecision::ProcessLocally
+        );
+
+        let eager_policy = RoutingPolicy::default().with_complexity_threshold(0.0);
+        assert_eq!(
+            eager_policy.decide(&msg, 2000).unwrap(),
+            RoutingDecision::SendToLLM
+        );
+    }
+
+    #[test]
+    fn test_decide_view_scores_query_complexity() {
+        use lnmp_core::{LnmpFieldView, LnmpRecordView, LnmpValueView};
+
+        let policy = RoutingPolicy::default().with_complexity_config(ComplexityConfig {
+            max_fields: 2,
+            ..ComplexityConfig::default()
+        });
+        let metadata = lnmp_envelope::EnvelopeMetadata::default();
+        let view = LnmpRecordView::from_fields(
+            (1..=2)
+                .map(|fid| LnmpFieldView {
+                    fid,
+                    value: LnmpValueView::Int(1),
+                })
+                .collect(),
+        );
+
+        let decision = policy
+            .decide_view(MessageKind::Query, 100, &metadata, None, &view, 2000)
+            .unwrap();
+        assert_eq!(decision, RoutingDecision::SendToLLM);
     }
 }
@@ -0,0 +1,782 @@
+//! Declarative routing rules
+//!
+//! [`RoutingRules`] evaluates an ordered list of rules built in code or, with
+//! the `yaml` feature, loaded from YAML so operators can change routing without
+//! recompiling. The first rule whose
+//! conditions all hold decides; if none matches, the configured `default`
+//! decision applies, or the ECO [`RoutingPolicy`] when there is none.
+//!
+//! ## Format
+//!
+//! ```yaml
+//! default: process_locally       # optional: send_to_llm | process_locally | drop
+//! rules:
+//!   - name: drop-expired
+//!     when:
+//!       expired: true
+//!     then: drop
+//!   - name: critical-alerts
+//!     when:
+//!       kind: [alert, command]     # one kind or a list
+//!       priority: { min: 200 }     # min and/or max, inclusive
+//!     then: send_to_llm
+//!   - name: noisy-sensor
+//!     when:
+//!       source: [sensor-7, sensor-9]
+//!       ttl_ms: { max: 1000 }
+//!       fields:
+//!         - { fid: 50, equals: ok }   # equals | contains | in | matches | range | gt | lt
+//!                                     # | array_contains | exists
+//!         - { fid: 30, path: [2], matches: "^temp-" }
+//!     then: drop
+//! ```
+//!
+//! A rule without `when` matches every message. Field conditions reuse
+//! [`FieldCondition`]; `exists: false` matches when the field is absent.
+//! `matches` takes a regular expression (`regex` feature), `range`, `gt` and
+//! `lt` compare floats as well when given a non-integer bound, and `path`
+//! applies the condition to a field of the nested record held by `fid`.
+
+#[cfg(feature = "yaml")]
+use std::fs;
+#[cfg(feature = "yaml")]
+use std::path::Path;
+
+use lnmp_core::FieldId;
+#[cfg(feature = "yaml")]
+use serde_norway::Value;
+
+use crate::content_routing::FieldCondition;
+#[cfg(feature = "yaml")]
+use crate::error::NetError;
+use crate::error::Result;
+use crate::kind::MessageKind;
+use crate::message::NetMessage;
+use crate::routing::{Router, RoutingDecision, RoutingPolicy};
+
+/// Conditions of one rule; all present conditions must hold
+#[derive(Debug, Clone, Default)]
+pub struct RuleCondition {
+    /// Accepted message kinds (any kind when empty)
+    pub kinds: Vec<MessageKind>,
+    /// Minimum priority (inclusive)
+    pub min_priority: Option<u8>,
+    /// Maximum priority (inclusive)
+    pub max_priority: Option<u8>,
+    /// Minimum TTL in milliseconds (inclusive)
+    pub min_ttl_ms: Option<u32>,
+    /// Maximum TTL in milliseconds (inclusive)
+    pub max_ttl_ms: Option<u32>,
+    /// Accepted envelope sources (any source when empty)
+    pub sources: Vec<String>,
+    /// Whether the message must be expired (`true`) or live (`false`)
+    pub expired: Option<bool>,
+    /// Conditions on top-level record fields
+    pub fields: Vec<(FieldId, FieldCondition)>,
+}
+
+impl RuleCondition {
+    /// Returns true if `msg` satisfies every condition at `now_ms`
+    pub fn matches(&self, msg: &NetMessage, now_ms: u64) -> Result<bool> {
+        if !self.kinds.is_empty() && !self.kinds.contains(&msg.kind) {
+            return Ok(false);
+        }
+        if self.min_priority.is_some_and(|min| msg.priority < min)
+            || self.max_priority.is_some_and(|max| msg.priority > max)
+        {
+            return Ok(false);
+        }
+        if self.min_ttl_ms.is_some_and(|min| msg.ttl_ms < min)
+            || self.max_ttl_ms.is_some_and(|max| msg.ttl_ms > max)
+        {
+            return Ok(false);
+        }
+        if !self.sources.is_empty() {
+            let source = msg.envelope.metadata.source.as_deref();
+            if !self.sources.iter().any(|s| Some(s.as_str()) == source) {
+                return Ok(false);
+            }
+        }
+        let record = msg.record();
+        for (fid, condition) in &self.fields {
+            let value = record.get_field(*fid).map(|field| &field.value);
+            if !condition.matches(value) {
+                return Ok(false);
+            }
+        }
+        // Checked last: it is the only condition that can fail with an error
+        if let Some(expired) = self.expired {
+            if msg.is_expired(now_ms)? != expired {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// A named rule: when `condition` holds, route with `decision`
+#[derive(Debug, Clone)]
+pub struct RoutingRule {
+    /// Rule name reported by [`RoutingRules::decide`]
+    pub name: String,
+    /// Conditions of the rule
+    pub condition: RuleCondition,
+    /// Decision taken when the rule fires
+    pub decision: RoutingDecision,
+}
+
+/// Outcome of [`RoutingRules::decide`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    /// The routing decision
+    pub decision: RoutingDecision,
+    /// Name of the rule that fired, or `None` if the default applied
+    pub rule: Option<String>,
+}
+
+/// Ordered routing rules with a default decision
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{
+///     MessageKind, NetMessage, RoutingDecision, RoutingRule, RoutingRules, RuleCondition,
+/// };
+///
+/// let rules = RoutingRules::new()
+///     .with_rule(RoutingRule {
+///         name: "urgent".into(),
+///         condition: RuleCondition {
+///             kinds: vec![MessageKind::Alert],
+///             min_priority: Some(200),
+///             ..Default::default()
+///         },
+///         decision: RoutingDecision::SendToLLM,
+///     })
+///     .with_default(RoutingDecision::ProcessLocally);
+///
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(1000).build();
+/// let alert = NetMessage::new(envelope, MessageKind::Alert);
+///
+/// let outcome = rules.decide(&alert, 2000).unwrap();
+/// assert_eq!(outcome.decision, RoutingDecision::SendToLLM);
+/// assert_eq!(outcome.rule.as_deref(), Some("urgent"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoutingRules {
+    /// Rules in evaluation order
+    pub rules: Vec<RoutingRule>,
+    /// Decision when no rule matches; `None` defers to `fallback`
+    pub default: Option<RoutingDecision>,
+    /// Policy consulted when no rule matches and there is no default
+    pub fallback: RoutingPolicy,
+}
+
+impl RoutingRules {
+    /// Creates an empty rule set that defers to the ECO policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a rule
+    pub fn with_rule(mut self, rule: RoutingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Sets the decision used when no rule matches
+    pub fn with_default(mut self, decision: RoutingDecision) -> Self {
+        self.default = Some(decision);
+        self
+    }
+
+    /// Sets the policy consulted when no rule matches and there is no default
+    pub fn with_fallback(mut self, policy: RoutingPolicy) -> Self {
+        self.fallback = policy;
+        self
+    }
+
+    /// Parses rules from YAML (see the [module docs](self) for the format)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lnmp_core::LnmpRecord;
+    /// use lnmp_envelope::EnvelopeBuilder;
+    /// use lnmp_net::{MessageKind, NetMessage, RoutingDecision, RoutingRules};
+    ///
+    /// let rules = RoutingRules::from_yaml(
+    ///     r#"
+    /// default: process_locally
+    /// rules:
+    ///   - name: urgent
+    ///     when: { kind: alert, priority: { min: 200 } }
+    ///     then: send_to_llm
+    /// "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(1000).build();
+    /// let alert = NetMessage::new(envelope, MessageKind::Alert);
+    ///
+    /// let outcome = rules.decide(&alert, 2000).unwrap();
+    /// assert_eq!(outcome.decision, RoutingDecision::SendToLLM);
+    /// assert_eq!(outcome.rule.as_deref(), Some("urgent"));
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let root: Value = serde_norway::from_str(yaml).map_err(|e| invalid(e.to_string()))?;
+        let mut rules = Self::new();
+
+        if let Some(default) = root.get("default") {
+            rules.default = Some(parse_decision(default)?);
+        }
+
+        let entries = match root.get("rules") {
+            Some(Value::Sequence(entries)) => entries.as_slice(),
+            Some(Value::Null) | None => &[],
+            Some(_) => return Err(invalid("'rules' must be a list")),
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            rules.rules.push(parse_rule(index, entry)?);
+        }
+        Ok(rules)
+    }
+
+    /// Reads and parses a YAML rules file
+    #[cfg(feature = "yaml")]
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())
+            .map_err(|e| invalid(format!("cannot read {}: {}", path.as_ref().display(), e)))?;
+        Self::from_yaml(&content)
+    }
+
+    /// Routes `msg`, reporting which rule fired
+    pub fn decide(&self, msg: &NetMessage, now_ms: u64) -> Result<RuleMatch> {
+        for rule in &self.rules {
+            if rule.condition.matches(msg, now_ms)? {
+                return Ok(RuleMatch {
+                    decision: rule.decision,
+                    rule: Some(rule.name.clone()),
+                });
+            }
+        }
+        let decision = match self.default {
+            Some(decision) => decision,
+            None => self.fallback.decide(msg, now_ms)?,
+        };
+        Ok(RuleMatch {
+            decision,
+            rule: None,
+        })
+    }
+}
+
+impl Router for RoutingRules {
+    fn route(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision> {
+        self.decide(msg, now_ms).map(|outcome| outcome.decision)
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn invalid(reason: impl Into<String>) -> NetError {
+    NetError::InvalidRules(reason.into())
+}
+
+#[cfg(feature = "yaml")]
+fn parse_rule(index: usize, entry: &Value) -> Result<RoutingRule> {
+    if !entry.is_mapping() {
+        return Err(invalid(format!("rule {} must be a mapping", index)));
+    }
+    let name = match entry.get("name") {
+        Some(name) => name
+            .as_str()
+            .ok_or_else(|| invalid(format!("rule {}: 'name' must be a string", index)))?
+            .to_string(),
+        None => format!("rule-{}", index),
+    };
+    let context = |e: NetError| match e {
+        NetError::InvalidRules(reason) => invalid(format!("rule '{}': {}", name, reason)),
+        other => other,
+    };
+
+    let decision = entry
+        .get("then")
+        .ok_or_else(|| invalid("missing 'then'"))
+        .and_then(parse_decision)
+        .map_err(context)?;
+    let condition = match entry.get("when") {
+        Some(when) => parse_condition(when).map_err(context)?,
+        None => RuleCondition::default(),
+    };
+
+    Ok(RoutingRule {
+        name,
+        condition,
+        decision,
+    })
+}
+
+#[cfg(feature = "yaml")]
+fn parse_decision(value: &Value) -> Result<RoutingDecision> {
+    match value.as_str().map(str::to_ascii_lowercase).as_deref() {
+        Some("send_to_llm" | "sendtollm" | "llm") => Ok(RoutingDecision::SendToLLM),
+        Some("process_locally" | "processlocally" | "local") => Ok(RoutingDecision::ProcessLocally),
+        Some("drop") => Ok(RoutingDecision::Drop),
+        _ => Err(invalid(format!(
+            "invalid decision {:?} (expected send_to_llm, process_locally or drop)",
+            value
+        ))),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn parse_condition(when: &Value) -> Result<RuleCondition> {
+    let mapping = when
+        .as_mapping()
+        .ok_or_else(|| invalid("'when' must be a mapping"))?;
+    let mut condition = RuleCondition::default();
+
+    for (key, value) in mapping {
+        match key.as_str() {
+            Some("kind") => {
+                for kind in one_or_many(value) {
+                    let kind = kind
+                        .as_str()
+                        .ok_or_else(|| invalid("'kind' must be a string or list of strings"))?;
+                    condition.kinds.push(kind.parse().map_err(invalid)?);
+                }
+            }
+            Some("priority") => {
+                condition.min_priority = parse_bound(value, "priority", "min")?;
+                condition.max_priority = parse_bound(value, "priority", "max")?;
+            }
+            Some("ttl_ms") => {
+                condition.min_ttl_ms = parse_bound(value, "ttl_ms", "min")?;
+                condition.max_ttl_ms = parse_bound(value, "ttl_ms", "max")?;
+            }
+            Some("source") => {
+                for source in one_or_many(value) {
+                    let source = source
+                        .as_str()
+                        .ok_or_else(|| invalid("'source' must be a string or list of strings"))?;
+                    condition.sources.push(source.to_string());
+                }
+            }
+            Some("expired") => {
+                condition.expired = Some(
+                    value
+                        .as_bool()
+                        .ok_or_else(|| invalid("'expired' must be a boolean"))?,
+                );
+            }
+            Some("fields") => {
+                let entries = value
+                    .as_sequence()
+                    .ok_or_else(|| invalid("'fields' must be a list"))?;
+                for entry in entries {
+                    condition.fields.push(parse_field_condition(entry)?);
+                }
+            }
+            _ => return Err(invalid(format!("unknown condition {:?}", key))),
+        }
+    }
+    Ok(condition)
+}
+
+#[cfg(feature = "yaml")]
+fn one_or_many(value: &Value) -> &[Value] {
+    match value {
+        Value::Sequence(values) => values,
+        value => std::slice::from_ref(value),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn parse_bound<T: TryFrom<u64>>(value: &Value, name: &str, bound: &str) -> Result<Option<T>> {
+    if !value.is_mapping() {
+        return Err(invalid(format!(
+            "'{}' must be a mapping with min/max",
+            name
+        )));
+    }
+    value
+        .get(bound)
+        .map(|v| {
+            v.as_u64()
+                .and_then(|n| T::try_from(n).ok())
+                .ok_or_else(|| invalid(format!("'{}.{}' is out of range", name, bound)))
+        })
+        .transpose()
+}
+
+#[cfg(feature = "yaml")]
+fn parse_field_condition(entry: &Value) -> Result<(FieldId, FieldCondition)> {
+    let mapping = entry
+        .as_mapping()
+        .ok_or_else(|| invalid("field condition must be a mapping"))?;
+    let fid = entry
+        .get("fid")
+        .and_then(Value::as_u64)
+        .and_then(|fid| FieldId::try_from(fid).ok())
+        .ok_or_else(|| invalid("field condition needs an integer 'fid' (0-65535)"))?;
+
+    let mut path = None;
+    let mut condition = None;
+    for (key, value) in mapping {
+        let parsed = match key.as_str() {
+            Some("fid") => continue,
+            Some("path") => {
+                path = Some(
+                    one_or_many(value)
+                        .iter()
+                        .map(|fid| {
+                            fid.as_u64()
+                                .and_then(|fid| FieldId::try_from(fid).ok())
+                                .ok_or_else(|| invalid("'path' must list field IDs (0-65535)"))
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                );
+                continue;
+            }
+            Some("equals") => FieldCondition::StringEquals(string_value(value, "equals")?),
+            Some("contains") => FieldCondition::StringContains(string_value(value, "contains")?),
+            Some("in") => FieldCondition::StringIn(
+                one_or_many(value)
+                    .iter()
+                    .map(|v| string_value(v, "in"))
+                    .collect::<Result<_>>()?,
+            ),
+            Some("matches") => FieldCondition::regex(&string_value(value, "matches")?)
+                .map_err(|e| invalid(format!("F{}: {}", fid, e)))?,
+            Some("range") => match value.as_sequence().map(Vec::as_slice) {
+                Some([min, max]) if min.is_i64() && max.is_i64() => {
+                    FieldCondition::IntInRange(int_value(min, "range")?, int_value(max, "range")?)
+                }
+                Some([min, max]) => FieldCondition::NumberInRange(
+                    number_value(min, "range")?,
+                    number_value(max, "range")?,
+                ),
+                _ => return Err(invalid("'range' must be [min, max]")),
+            },
+            Some("gt") if value.is_i64() => FieldCondition::IntGreaterThan(int_value(value, "gt")?),
+            Some("gt") => FieldCondition::NumberGreaterThan(number_value(value, "gt")?),
+            Some("lt") if value.is_i64() => FieldCondition::IntLessThan(int_value(value, "lt")?),
+            Some("lt") => FieldCondition::NumberLessThan(number_value(value, "lt")?),
+            Some("array_contains") => match value.as_i64() {
+                Some(i) => FieldCondition::IntArrayContains(i),
+                None => FieldCondition::ArrayContains(string_value(value, "array_contains")?),
+            },
+            Some("exists") => match value.as_bool() {
+                Some(true) => FieldCondition::Exists,
+                Some(false) => FieldCondition::NotExists,
+                None => return Err(invalid("'exists' must be a boolean")),
+            },
+            _ => return Err(invalid(format!("unknown field condition {:?}", key))),
+        };
+        if condition.replace(parsed).is_some() {
+            return Err(invalid(format!("F{}: one condition per field entry", fid)));
+        }
+    }
+
+    condition
+        .map(|condition| match path {
+            Some(path) => (fid, FieldCondition::nested(path, condition)),
+            None => (fid, condition),
+        })
+        .ok_or_else(|| invalid(format!("F{}: missing condition", fid)))
+}
+
+#[cfg(feature = "yaml")]
+fn string_value(value: &Value, name: &str) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(invalid(format!("'{}' must be a scalar", name))),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn int_value(value: &Value, name: &str) -> Result<i64> {
+    value
+        .as_i64()
+        .ok_or_else(|| invalid(format!("'{}' must be an integer", name)))
+}
+
+#[cfg(feature = "yaml")]
+fn number_value(value: &Value, name: &str) -> Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| invalid(format!("'{}' must be a number", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    #[cfg(feature = "yaml")]
+    const RULES: &str = r#"
+default: process_locally
+rules:
+  - name: drop-expired
+    when:
+      expired: true
+    then: drop
+  - name: critical-status
+    when:
+      fields:
+        - { fid: 50, equals: critical }
+    then: send_to_llm
+  - name: noisy-sensor
+    when:
+      source: [sensor-7, sensor-9]
+      kind: event
+      priority: { max: 100 }
+    then: drop
+  - when:
+      kind: [command, query]
+      ttl_ms: { min: 5000 }
+      fields:
+        - { fid: 12, range: [100, 200] }
+        - { fid: 7, exists: false }
+    then: llm
+"#;
+
+    fn message(kind: MessageKind, source: &str, fields: &[(FieldId, LnmpValue)]) -> NetMessage {
+        let mut record = LnmpRecord::new();
+        for (fid, value) in fields {
+            record.add_field(LnmpField {
+                fid: *fid,
+                value: value.clone(),
+            });
+        }
+        let envelope = EnvelopeBuilder::new(record)
+            .timestamp(1000)
+            .source(source)
+            .build();
+        NetMessage::with_qos(envelope, kind, 50, 10_000)
+    }
+
+    #[cfg(feature = "yaml")]
+    fn fired(
+        rules: &RoutingRules,
+        msg: &NetMessage,
+        now_ms: u64,
+    ) -> (RoutingDecision, Option<String>) {
+        let outcome = rules.decide(msg, now_ms).unwrap();
+        (outcome.decision, outcome.rule)
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_ordered_match_semantics() {
+        let rules = RoutingRules::from_yaml(RULES).unwrap();
+        assert_eq!(rules.rules.len(), 4);
+
+        // Expiry comes first even for critical status
+        let critical = message(
+            MessageKind::Event,
+            "sensor-7",
+            &[(50, LnmpValue::String("critical".into()))],
+        );
+        assert_eq!(
+            fired(&rules, &critical, 20_000),
+            (RoutingDecision::Drop, Some("drop-expired".into()))
+        );
+        assert_eq!(
+            fired(&rules, &critical, 2000),
+            (RoutingDecision::SendToLLM, Some("critical-status".into()))
+        );
+
+        let noisy = message(MessageKind::Event, "sensor-9", &[]);
+        assert_eq!(
+            fired(&rules, &noisy, 2000),
+            (RoutingDecision::Drop, Some("noisy-sensor".into()))
+        );
+
+        let other = message(MessageKind::Event, "sensor-1", &[]);
+        assert_eq!(
+            fired(&rules, &other, 2000),
+            (RoutingDecision::ProcessLocally, None)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_unnamed_rule_and_field_conditions() {
+        let rules = RoutingRules::from_yaml(RULES).unwrap();
+
+        let command = message(
+            MessageKind::Command,
+            "planner",
+            &[(12, LnmpValue::Int(150))],
+        );
+        assert_eq!(
+            fired(&rules, &command, 2000),
+            (RoutingDecision::SendToLLM, Some("rule-3".into()))
+        );
+
+        let with_f7 = message(
+            MessageKind::Command,
+            "planner",
+            &[(12, LnmpValue::Int(150)), (7, LnmpValue::Bool(true))],
+        );
+        assert_eq!(fired(&rules, &with_f7, 2000).1, None);
+
+        let out_of_range = message(MessageKind::Query, "planner", &[(12, LnmpValue::Int(201))]);
+        assert_eq!(fired(&rules, &out_of_range, 2000).1, None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml", feature = "regex"))]
+    fn test_extended_field_conditions() {
+        let yaml = r#"
+rules:
+  - name: hot
+    when:
+      fields:
+        - { fid: 1, matches: "^temp-[0-9]+$" }
+        - { fid: 2, gt: 40.5 }
+    then: send_to_llm
+  - name: tagged
+    when:
+      fields:
+        - { fid: 3, array_contains: urgent }
+        - { fid: 4, path: [7], range: [0.0, 1.5] }
+    then: drop
+"#;
+        let rules = RoutingRules::from_yaml(yaml).unwrap();
+
+        let hot = message(
+            MessageKind::Event,
+            "node",
+            &[
+                (1, LnmpValue::String("temp-3".into())),
+                (2, LnmpValue::Float(41.0)),
+            ],
+        );
+        assert_eq!(fired(&rules, &hot, 2000).1, Some("hot".into()));
+        let mild = message(
+            MessageKind::Event,
+            "node",
+            &[
+                (1, LnmpValue::String("temp-3".into())),
+                (2, LnmpValue::Int(40)),
+            ],
+        );
+        assert_eq!(fired(&rules, &mild, 2000).1, None);
+
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 7,
+            value: LnmpValue::Int(1),
+        });
+        let tagged = message(
+            MessageKind::Event,
+            "node",
+            &[
+                (
+                    3,
+                    LnmpValue::StringArray(vec!["low".into(), "urgent".into()]),
+                ),
+                (4, LnmpValue::NestedRecord(Box::new(inner))),
+            ],
+        );
+        assert_eq!(fired(&rules, &tagged, 2000).1, Some("tagged".into()));
+    }
+
+    #[test]
+    fn test_fallback_to_eco_policy() {
+        let rules = RoutingRules::new();
+        let policy = RoutingPolicy::default();
+        let alert = message(MessageKind::Alert, "node", &[]);
+        let mut urgent = alert.clone();
+        urgent.priority = 255;
+
+        for msg in [alert, urgent] {
+            assert_eq!(
+                rules.route(&msg, 2000).unwrap(),
+                policy.decide(&msg, 2000).unwrap()
+            );
+            assert_eq!(rules.decide(&msg, 2000).unwrap().rule, None);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let rules = RoutingRules::new()
+            .with_rule(RoutingRule {
+                name: "queries".into(),
+                condition: RuleCondition {
+                    kinds: vec![MessageKind::Query],
+                    ..Default::default()
+                },
+                decision: RoutingDecision::SendToLLM,
+            })
+            .with_default(RoutingDecision::Drop);
+
+        let query = message(MessageKind::Query, "node", &[]);
+        let event = message(MessageKind::Event, "node", &[]);
+        assert_eq!(
+            rules.route(&query, 2000).unwrap(),
+            RoutingDecision::SendToLLM
+        );
+        assert_eq!(rules.route(&event, 2000).unwrap(), RoutingDecision::Drop);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_invalid_rules() {
+        let cases = [
+            ("rules: 3", "'rules' must be a list"),
+            ("rules:\n  - when: {}\n", "rule 'rule-0': missing 'then'"),
+            ("rules:\n  - then: maybe\n", "invalid decision"),
+            (
+                "rules:\n  - name: x\n    when: { colour: red }\n    then: drop\n",
+                "unknown condition",
+            ),
+            (
+                "rules:\n  - when: { kind: gossip }\n    then: drop\n",
+                "Invalid MessageKind",
+            ),
+            (
+                "rules:\n  - when: { priority: { min: 300 } }\n    then: drop\n",
+                "out of range",
+            ),
+            (
+                "rules:\n  - when: { fields: [{ fid: 1 }] }\n    then: drop\n",
+                "missing condition",
+            ),
+            (
+                "rules:\n  - when: { fields: [{ fid: 1, gt: 1, lt: 5 }] }\n    then: drop\n",
+                "one condition per field entry",
+            ),
+            (
+                "rules:\n  - when: { fields: [{ fid: 1, matches: \"(\" }] }\n    then: drop\n",
+                "F1: Invalid field condition",
+            ),
+            ("default: later", "invalid decision"),
+        ];
+        for (yaml, expected) in cases {
+            let err = RoutingRules::from_yaml(yaml).unwrap_err().to_string();
+            assert!(err.contains(expected), "{yaml:?}: {err}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_load_from_file() {
+        let path = std::env::temp_dir().join(format!("lnmp-net-rules-{}.yaml", std::process::id()));
+        fs::write(&path, RULES).unwrap();
+        let rules = RoutingRules::load_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rules.default, Some(RoutingDecision::ProcessLocally));
+
+        assert!(RoutingRules::load_from_file("/nonexistent/rules.yaml").is_err());
+    }
+}
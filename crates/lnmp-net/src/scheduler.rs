@@ -0,0 +1,514 @@
+//! Priority scheduling of messages awaiting delivery
+//!
+//! A [`RoutingDecision`](crate::RoutingDecision) says where a message goes; a
+//! [`NetScheduler`] decides in which order deliveries happen. Messages are
+//! dequeued by priority (highest first), then by deadline (soonest first), then
+//! in arrival order. Messages that expire while queued are dropped instead of
+//! delivered, messages waiting out a retry backoff are held back until due, and
+//! [`Backpressure`] tells producers when to slow down.
+//!
+//! With the `spill` feature, a scheduler given a `SpillQueue` moves messages it
+//! would displace or reject to disk instead, and takes them back as room frees
+//! up, also after a restart.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::message::NetMessage;
+
+/// Queue fill level as seen by producers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Backpressure {
+    /// Below the high watermark
+    Normal,
+    /// At or above the high watermark; producers should slow down
+    High,
+    /// At capacity; only messages outranking the lowest queued one get in
+    Full,
+}
+
+/// Result of [`NetScheduler::push`]
+#[derive(Debug, Clone)]
+#[must_use]
+pub enum Enqueue {
+    /// The message was queued
+    Queued,
+    /// The message was queued and displaced this lower-ranked one
+    Displaced(NetMessage),
+    /// The queue is full of messages ranked at least as high; not queued
+    Rejected(NetMessage),
+    /// The queue is full; the message was moved to the spill queue
+    Spilled,
+    /// The message had already expired; not queued
+    Expired(NetMessage),
+}
+
+impl Enqueue {
+    /// Returns true if the pushed message is now in the queue
+    pub fn is_queued(&self) -> bool {
+        matches!(self, Enqueue::Queued | Enqueue::Displaced(_))
+    }
+
+    /// Returns true if the scheduler took the pushed message, queued or spilled
+    pub fn is_accepted(&self) -> bool {
+        self.is_queued() || matches!(self, Enqueue::Spilled)
+    }
+}
+
+/// Counters collected by a [`NetScheduler`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerMetrics {
+    /// Messages accepted by `push`
+    pub queued: u64,
+    /// Messages handed out by `pop`
+    pub dequeued: u64,
+    /// Messages dropped because they expired, on push or while queued
+    pub expired: u64,
+    /// Queued messages displaced by higher-ranked ones
+    pub displaced: u64,
+    /// Messages refused because the queue was full
+    pub rejected: u64,
+    /// Messages moved to the spill queue, pushed or displaced
+    pub spilled: u64,
+    /// Spilled messages taken back into the queue
+    pub replayed: u64,
+}
+
+/// Highest priority first, then soonest deadline, then arrival order
+type Rank = (Reverse<u8>, u64, u64);
+
+/// Bounded priority queue of messages with TTL-aware dequeueing
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{Backpressure, MessageKind, NetMessage, NetScheduler};
+///
+/// let message = |priority, ttl_ms| {
+///     let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(0).build();
+///     NetMessage::with_qos(envelope, MessageKind::Event, priority, ttl_ms)
+/// };
+///
+/// let mut scheduler = NetScheduler::new(3);
+/// assert!(scheduler.push(message(100, 60_000), 0).is_queued());
+/// assert!(scheduler.push(message(200, 60_000), 0).is_queued());
+/// assert!(scheduler.push(message(100, 500), 0).is_queued());
+/// assert_eq!(scheduler.backpressure(), Backpressure::Full);
+///
+/// // Highest priority first, then the message closest to expiry
+/// assert_eq!(scheduler.pop(100).unwrap().priority, 200);
+/// assert_eq!(scheduler.pop(100).unwrap().ttl_ms, 500);
+///
+/// // Nothing is delivered past its deadline
+/// assert!(scheduler.push(message(100, 500), 0).is_queued());
+/// assert_eq!(scheduler.pop(1000).unwrap().ttl_ms, 60_000);
+/// assert!(scheduler.pop(1000).is_none());
+/// assert_eq!(scheduler.metrics().expired, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NetScheduler {
+    capacity: usize,
+    high_watermark: f64,
+    queue: BTreeMap<Rank, NetMessage>,
+    next_seq: u64,
+    metrics: SchedulerMetrics,
+    #[cfg(feature = "spill")]
+    spill: Option<crate::spill::SpillQueue>,
+}
+
+impl NetScheduler {
+    /// Creates a scheduler holding at most `capacity` messages
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            high_watermark: 0.8,
+            queue: BTreeMap::new(),
+            next_seq: 0,
+            metrics: SchedulerMetrics::default(),
+            #[cfg(feature = "spill")]
+            spill: None,
+        }
+    }
+
+    /// Overflows to `spill` instead of displacing or rejecting messages
+    ///
+    /// Spilled messages come back in FIFO order whenever `pop` finds room,
+    /// including messages left in the spill queue by a previous run. If the
+    /// spill queue is full or fails, messages are displaced or rejected as
+    /// without one.
+    #[cfg(feature = "spill")]
+    pub fn with_spill(mut self, spill: crate::spill::SpillQueue) -> Self {
+        self.spill = Some(spill);
+        self
+    }
+
+    /// Returns the spill queue, if any
+    #[cfg(feature = "spill")]
+    pub fn spill(&self) -> Option<&crate::spill::SpillQueue> {
+        self.spill.as_ref()
+    }
+
+    /// Sets the fill ratio (0.0-1.0) at which backpressure turns `High`
+    pub fn with_high_watermark(mut self, ratio: f64) -> Self {
+        self.high_watermark = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the maximum number of queued messages
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of queued messages, not counting spilled ones
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the fill ratio (0.0-1.0); an empty-capacity queue is always full
+    pub fn load(&self) -> f64 {
+        if self.capacity == 0 {
+            1.0
+        } else {
+            self.queue.len() as f64 / self.capacity as f64
+        }
+    }
+
+    /// Returns the current backpressure signal
+    pub fn backpressure(&self) -> Backpressure {
+        if self.queue.len() >= self.capacity {
+            Backpressure::Full
+        } else if self.load() >= self.high_watermark {
+            Backpressure::High
+        } else {
+            Backpressure::Normal
+        }
+    }
+
+    /// Queues `msg` at `now_ms`
+    ///
+    /// When the queue is full, the lowest-ranked message is displaced if `msg`
+    /// outranks it; otherwise `msg` is rejected. Either way the loser goes to
+    /// the spill queue if there is one. Messages without a timestamp or expiry
+    /// never expire.
+    pub fn push(&mut self, msg: NetMessage, now_ms: u64) -> Enqueue {
+        if is_expired(&msg, now_ms) {
+            self.metrics.expired += 1;
+            return Enqueue::Expired(msg);
+        }
+
+        let rank = self.rank(&msg);
+        let mut displaced = None;
+        if self.queue.len() >= self.capacity {
+            // Make room by expiring first, then by displacing the lowest rank
+            self.drop_expired(now_ms);
+        }
+        if self.queue.len() >= self.capacity {
+            match self.queue.last_key_value() {
+                Some((lowest, _)) if rank < *lowest => {
+                    displaced = self.queue.pop_last().map(|(_, msg)| msg);
+                }
+                _ => {
+                    return match self.try_spill(msg) {
+                        None => Enqueue::Spilled,
+                        Some(msg) => {
+                            self.metrics.rejected += 1;
+                            Enqueue::Rejected(msg)
+                        }
+                    };
+                }
+            }
+        }
+
+        self.next_seq += 1;
+        self.queue.insert(rank, msg);
+        self.metrics.queued += 1;
+        match displaced.and_then(|msg| self.try_spill(msg)) {
+            Some(msg) => {
+                self.metrics.displaced += 1;
+                Enqueue::Displaced(msg)
+            }
+            None => Enqueue::Queued,
+        }
+    }
+
+    /// Removes and returns the next message due for delivery at `now_ms`
+    ///
+    /// Expired messages ahead of it are dropped; messages whose retry backoff
+    /// hasn't elapsed stay queued.
+    pub fn pop(&mut self, now_ms: u64) -> Option<NetMessage> {
+        self.refill(now_ms);
+        let mut expired = Vec::new();
+        let mut due = None;
+        for (rank, msg) in &self.queue {
+            if is_expired(msg, now_ms) {
+                expired.push(*rank);
+            } else if msg.retry.is_due(now_ms) {
+                due = Some(*rank);
+                break;
+            }
+        }
+
+        self.metrics.expired += expired.len() as u64;
+        for rank in expired {
+            self.queue.remove(&rank);
+        }
+        let msg = self.queue.remove(&due?)?;
+        self.metrics.dequeued += 1;
+        Some(msg)
+    }
+
+    /// Returns the earliest retry time among queued messages not yet due
+    ///
+    /// Useful to decide how long to sleep when `pop` returns `None`.
+    pub fn next_due_ms(&self, now_ms: u64) -> Option<u64> {
+        self.queue
+            .values()
+            .filter_map(|msg| msg.retry.next_attempt_ms)
+            .filter(|at| *at > now_ms)
+            .min()
+    }
+
+    /// Returns the highest-ranked queued message, ignoring expiry and backoff
+    pub fn peek(&self) -> Option<&NetMessage> {
+        self.queue.first_key_value().map(|(_, msg)| msg)
+    }
+
+    /// Drops every queued message expired at `now_ms`; returns how many
+    pub fn drop_expired(&mut self, now_ms: u64) -> usize {
+        let before = self.queue.len();
+        self.queue.retain(|_, msg| !is_expired(msg, now_ms));
+        let dropped = before - self.queue.len();
+        self.metrics.expired += dropped as u64;
+        dropped
+    }
+
+    /// Returns a snapshot of the counters
+    pub fn metrics(&self) -> SchedulerMetrics {
+        self.metrics
+    }
+}
+
+impl NetScheduler {
+    fn rank(&self, msg: &NetMessage) -> Rank {
+        (
+            Reverse(msg.priority),
+            msg.deadline_ms().unwrap_or(u64::MAX),
+            self.next_seq,
+        )
+    }
+
+    /// Moves `msg` to the spill queue; hands it back if that isn't possible
+    #[cfg(feature = "spill")]
+    fn try_spill(&mut self, msg: NetMessage) -> Option<NetMessage> {
+        match &self.spill {
+            Some(spill) if matches!(spill.push(&msg), Ok(true)) => {
+                self.metrics.spilled += 1;
+                None
+            }
+            _ => Some(msg),
+        }
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn try_spill(&mut self, msg: NetMessage) -> Option<NetMessage> {
+        Some(msg)
+    }
+
+    /// Takes spilled messages back while there is room, oldest first
+    #[cfg(feature = "spill")]
+    fn refill(&mut self, now_ms: u64) {
+        let Some(spill) = self.spill.clone() else {
+            return;
+        };
+        while self.queue.len() < self.capacity {
+            // Read errors leave the remaining messages spilled until the next pop
+            let Ok(Some(msg)) = spill.pop() else {
+                break;
+            };
+            if is_expired(&msg, now_ms) {
+                self.metrics.expired += 1;
+                continue;
+            }
+            let rank = self.rank(&msg);
+            self.next_seq += 1;
+            self.queue.insert(rank, msg);
+            self.metrics.replayed += 1;
+        }
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn refill(&mut self, _now_ms: u64) {}
+}
+
+fn is_expired(msg: &NetMessage, now_ms: u64) -> bool {
+    msg.is_expired(now_ms).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::MessageKind;
+    use lnmp_core::LnmpRecord;
+    use lnmp_envelope::{EnvelopeBuilder, LnmpEnvelope};
+
+    fn message(priority: u8, timestamp: u64, ttl_ms: u32) -> NetMessage {
+        let envelope = EnvelopeBuilder::new(LnmpRecord::new())
+            .timestamp(timestamp)
+            .build();
+        NetMessage::with_qos(envelope, MessageKind::Event, priority, ttl_ms)
+    }
+
+    #[test]
+    fn test_order_priority_deadline_fifo() {
+        let mut scheduler = NetScheduler::new(10);
+        for msg in [
+            message(50, 0, 1000),
+            message(50, 0, 500),
+            message(150, 0, 9000),
+            message(50, 0, 500),
+        ] {
+            assert!(matches!(scheduler.push(msg, 0), Enqueue::Queued));
+        }
+        let mut no_deadline =
+            NetMessage::new(LnmpEnvelope::new(LnmpRecord::new()), MessageKind::Command);
+        no_deadline.priority = 50;
+        assert!(scheduler.push(no_deadline, 0).is_queued());
+
+        let order: Vec<_> = std::iter::from_fn(|| scheduler.pop(0))
+            .map(|msg| (msg.priority, msg.kind))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (150, MessageKind::Event),
+                (50, MessageKind::Event),
+                (50, MessageKind::Event),
+                (50, MessageKind::Event),
+                (50, MessageKind::Command),
+            ]
+        );
+        assert_eq!(scheduler.metrics().dequeued, 5);
+    }
+
+    #[test]
+    fn test_expired_entries_dropped() {
+        let mut scheduler = NetScheduler::new(10);
+        assert!(matches!(
+            scheduler.push(message(100, 0, 100), 500),
+            Enqueue::Expired(_)
+        ));
+        assert!(scheduler.push(message(200, 0, 100), 0).is_queued());
+        assert!(scheduler.push(message(100, 0, 1000), 0).is_queued());
+        assert!(scheduler.push(message(100, 0, 200), 0).is_queued());
+
+        assert_eq!(scheduler.drop_expired(150), 1);
+        assert_eq!(scheduler.pop(250).unwrap().ttl_ms, 1000);
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.metrics().expired, 3);
+    }
+
+    #[test]
+    fn test_full_queue_displaces_or_rejects() {
+        let mut scheduler = NetScheduler::new(2);
+        assert!(scheduler.push(message(100, 0, 1000), 0).is_queued());
+        assert!(scheduler.push(message(50, 0, 1000), 0).is_queued());
+
+        match scheduler.push(message(200, 0, 1000), 0) {
+            Enqueue::Displaced(msg) => assert_eq!(msg.priority, 50),
+            other => panic!("expected displacement, got {:?}", other),
+        }
+        assert!(matches!(
+            scheduler.push(message(100, 0, 1000), 0),
+            Enqueue::Rejected(_)
+        ));
+
+        // Expired entries make room before anything is displaced
+        assert!(scheduler.push(message(10, 0, 5000), 2000).is_queued());
+        assert_eq!(scheduler.len(), 1);
+
+        let metrics = scheduler.metrics();
+        assert_eq!(metrics.displaced, 1);
+        assert_eq!(metrics.rejected, 1);
+        assert_eq!(metrics.expired, 2);
+    }
+
+    #[test]
+    fn test_backpressure_levels() {
+        let mut scheduler = NetScheduler::new(4).with_high_watermark(0.5);
+        assert_eq!(scheduler.backpressure(), Backpressure::Normal);
+        let _ = scheduler.push(message(1, 0, 1000), 0);
+        assert_eq!(scheduler.backpressure(), Backpressure::Normal);
+        let _ = scheduler.push(message(1, 0, 1000), 0);
+        assert_eq!(scheduler.backpressure(), Backpressure::High);
+        let _ = scheduler.push(message(1, 0, 1000), 0);
+        let _ = scheduler.push(message(1, 0, 1000), 0);
+        assert_eq!(scheduler.backpressure(), Backpressure::Full);
+        assert_eq!(scheduler.load(), 1.0);
+
+        assert_eq!(NetScheduler::new(0).backpressure(), Backpressure::Full);
+    }
+
+    #[test]
+    fn test_retry_backoff_holds_message() {
+        let mut scheduler = NetScheduler::new(10);
+        let mut retried = message(200, 0, 10_000);
+        retried.retry.attempts = 1;
+        retried.retry.next_attempt_ms = Some(500);
+        assert!(scheduler.push(retried, 0).is_queued());
+        assert!(scheduler.push(message(100, 0, 10_000), 0).is_queued());
+
+        // The higher-priority retry waits out its backoff
+        assert_eq!(scheduler.pop(100).unwrap().priority, 100);
+        assert!(scheduler.pop(100).is_none());
+        assert_eq!(scheduler.next_due_ms(100), Some(500));
+
+        let msg = scheduler.pop(500).unwrap();
+        assert_eq!(msg.retry.attempts, 1);
+        assert!(scheduler.is_empty());
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_overflow_and_replay() {
+        use crate::spill::SpillQueue;
+
+        let dir = std::env::temp_dir().join(format!("lnmp-sched-spill-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let spill = SpillQueue::open(&dir).unwrap();
+        let mut scheduler = NetScheduler::new(2).with_spill(spill);
+        assert!(scheduler.push(message(100, 0, 10_000), 0).is_queued());
+        assert!(scheduler.push(message(50, 0, 10_000), 0).is_queued());
+
+        // The displaced message and the rejected one both go to disk
+        assert!(matches!(
+            scheduler.push(message(200, 0, 10_000), 0),
+            Enqueue::Queued
+        ));
+        assert!(matches!(
+            scheduler.push(message(10, 0, 100), 0),
+            Enqueue::Spilled
+        ));
+        assert_eq!(scheduler.spill().unwrap().len(), 2);
+        drop(scheduler);
+
+        // A restarted scheduler replays what was spilled; expired ones are dropped
+        let mut scheduler = NetScheduler::new(2).with_spill(SpillQueue::open(&dir).unwrap());
+        let order: Vec<_> = std::iter::from_fn(|| scheduler.pop(500))
+            .map(|msg| msg.priority)
+            .collect();
+        assert_eq!(order, [50]);
+
+        let metrics = scheduler.metrics();
+        assert_eq!(metrics.replayed, 1);
+        assert_eq!(metrics.expired, 1);
+        assert!(scheduler.spill().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
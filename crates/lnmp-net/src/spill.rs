@@ -0,0 +1,747 @@
+//! Disk-backed overflow queue for messages
+//!
+//! When the LLM or a downstream peer is slow, a bounded in-memory
+//! [`NetScheduler`](crate::NetScheduler) fills up and starts displacing or
+//! rejecting messages. A [`SpillQueue`] takes those messages instead: it appends
+//! them to segment files in a directory and hands them back in FIFO order once
+//! the scheduler has room again, including after a restart.
+//!
+//! Segments are named after their sequence number (`00000000000000000003.lnq`)
+//! and use the framing of the binary record log:
+//!
+//! ```text
+//! SEGMENT: MAGIC "LNSQ" | VERSION (1 byte) | FRAME*
+//! FRAME:   LEN (u32 LE) | CRC32C (u32 LE) | PAYLOAD (LEN bytes)
+//! PAYLOAD: KIND (u8) | PRIORITY (u8) | TTL_MS (u32 LE) | ATTEMPTS (u32 LE)
+//!          | FLAGS (u8) | NEXT_ATTEMPT_MS (u64 LE, if flagged)
+//!          | CLASS_LEN (u16 LE) + CLASS (if flagged)
+//!          | META_LEN (u32 LE) | META (envelope TLV) | RECORD (binary record frame)
+//! ```
+//!
+//! A `cursor` file records how far the oldest segment has been consumed, so
+//! messages handed out before a restart are not replayed. Fully consumed
+//! segments are deleted. Records come back in canonical field order. As in the
+//! record log, a torn frame at the end of the newest segment is truncated on
+//! open and corruption elsewhere is an error.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crc::{Crc, CRC_32_ISCSI};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder, EncoderConfig, FsyncPolicy};
+use lnmp_envelope::binary_codec::{TlvDecoder, TlvEncoder};
+use lnmp_envelope::LnmpEnvelope;
+
+use crate::error::{NetError, Result};
+use crate::kind::MessageKind;
+use crate::message::NetMessage;
+use crate::retry::RetryState;
+
+/// Magic bytes at the start of every segment
+pub const SPILL_MAGIC: [u8; 4] = *b"LNSQ";
+
+/// Current segment format version
+pub const SPILL_VERSION: u8 = 0x01;
+
+/// File extension of segment files
+pub const SPILL_EXTENSION: &str = "lnq";
+
+const CURSOR_FILE: &str = "cursor";
+const SEGMENT_HEADER_LEN: u64 = 5;
+const FRAME_HEADER_LEN: usize = 8;
+const FLAG_NEXT_ATTEMPT: u8 = 0x01;
+const FLAG_CLASS: u8 = 0x02;
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+#[derive(Debug, Clone)]
+struct Config {
+    max_bytes: u64,
+    max_segment_bytes: u64,
+    fsync: FsyncPolicy,
+}
+
+#[derive(Debug)]
+struct Segment {
+    seq: u64,
+    path: PathBuf,
+    /// Bytes on disk, header included
+    bytes: u64,
+}
+
+/// Unconsumed frames of the oldest segment
+#[derive(Debug)]
+struct Head {
+    seq: u64,
+    bytes: Vec<u8>,
+    /// Payload ranges of the frames not handed out yet
+    frames: VecDeque<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct State {
+    dir: PathBuf,
+    segments: VecDeque<Segment>,
+    head: Option<Head>,
+    writer: Option<File>,
+    /// Offset of the first unconsumed frame in the oldest segment
+    cursor: u64,
+    len: usize,
+    unsynced: u32,
+}
+
+/// Bounded FIFO of messages persisted in segment files
+///
+/// The queue holds at most `max_bytes` of segment data (default 64 MiB);
+/// pushing beyond that is refused. Appends are not fsynced by default, so
+/// spilled messages survive a crash of the process but not necessarily of the
+/// machine; see [`SpillQueue::with_fsync`]. Clones share the same queue.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{MessageKind, NetMessage, SpillQueue};
+///
+/// let dir = std::env::temp_dir().join(format!("lnmp-spill-doc-{}", std::process::id()));
+/// let message = |priority| {
+///     let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(0).build();
+///     NetMessage::with_qos(envelope, MessageKind::Event, priority, 60_000)
+/// };
+///
+/// let queue = SpillQueue::open(&dir).unwrap();
+/// assert!(queue.push(&message(10)).unwrap());
+/// assert!(queue.push(&message(20)).unwrap());
+/// assert_eq!(queue.pop().unwrap().unwrap().priority, 10);
+/// drop(queue);
+///
+/// // What was not handed out is still there after a restart
+/// let queue = SpillQueue::open(&dir).unwrap();
+/// assert_eq!(queue.len(), 1);
+/// assert_eq!(queue.pop().unwrap().unwrap().priority, 20);
+/// assert!(queue.pop().unwrap().is_none());
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpillQueue {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+impl SpillQueue {
+    /// Opens the queue in `dir`, creating the directory if needed
+    ///
+    /// Messages left by a previous run are queued again, oldest first.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        let (cursor_seq, cursor) = read_cursor(&dir)?;
+
+        let listed = list_segments(&dir)?;
+        let newest = listed.last().map(|(seq, _)| *seq);
+        let mut state = State {
+            dir,
+            segments: VecDeque::new(),
+            head: None,
+            writer: None,
+            cursor: 0,
+            len: 0,
+            unsynced: 0,
+        };
+        for (seq, path) in listed {
+            if seq < cursor_seq {
+                // Consumed before the restart, but not deleted yet
+                fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+                continue;
+            }
+            let bytes = fs::read(&path).map_err(|e| io_error(&path, e))?;
+            if bytes.len() < SEGMENT_HEADER_LEN as usize {
+                // Crash before the header was fully written; it holds nothing
+                fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+                continue;
+            }
+            let start = if seq == cursor_seq { cursor } else { 0 };
+            let frames = scan_segment(&path, &bytes, start)?;
+            let valid_len = frames.last().map_or(start, |&(_, end)| end as u64);
+            let valid_len = valid_len.max(SEGMENT_HEADER_LEN);
+            if Some(seq) == newest && valid_len < bytes.len() as u64 {
+                // Torn tail: cut it off before appending
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .map_err(|e| io_error(&path, e))?;
+                file.set_len(valid_len).map_err(|e| io_error(&path, e))?;
+            }
+            state.len += frames.len();
+            if state.segments.is_empty() {
+                state.cursor = start.max(SEGMENT_HEADER_LEN);
+            }
+            state.segments.push_back(Segment {
+                seq,
+                path,
+                bytes: valid_len,
+            });
+        }
+
+        Ok(Self {
+            config: Arc::new(Config {
+                max_bytes: 64 * 1024 * 1024,
+                max_segment_bytes: 4 * 1024 * 1024,
+                fsync: FsyncPolicy::Never,
+            }),
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Sets the total segment size beyond which pushes are refused
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        Arc::make_mut(&mut self.config).max_bytes = bytes;
+        self
+    }
+
+    /// Sets the segment size after which a new segment is started (default: 4 MiB)
+    pub fn with_max_segment_bytes(mut self, bytes: u64) -> Self {
+        Arc::make_mut(&mut self.config).max_segment_bytes = bytes;
+        self
+    }
+
+    /// Sets when appended messages are fsynced (default: never)
+    pub fn with_fsync(mut self, policy: FsyncPolicy) -> Self {
+        Arc::make_mut(&mut self.config).fsync = policy;
+        self
+    }
+
+    /// Returns the queue directory
+    pub fn dir(&self) -> PathBuf {
+        self.lock().dir.clone()
+    }
+
+    /// Returns the number of queued messages
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    /// Returns true if no message is queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the segment bytes on disk, consumed frames of the oldest segment included
+    pub fn bytes(&self) -> u64 {
+        self.lock().segments.iter().map(|s| s.bytes).sum()
+    }
+
+    /// Appends `msg` to the queue
+    ///
+    /// Returns `Ok(false)`, writing nothing, if the queue is full.
+    pub fn push(&self, msg: &NetMessage) -> Result<bool> {
+        let payload = encode_message(msg)?;
+        let len = u32::try_from(payload.len())
+            .map_err(|_| NetError::Spill(format!("message too large: {} bytes", payload.len())))?;
+        let frame_len = (FRAME_HEADER_LEN + payload.len()) as u64;
+
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let total: u64 = state.segments.iter().map(|s| s.bytes).sum();
+        let rotate = state.segments.back().is_none_or(|s| {
+            s.bytes > SEGMENT_HEADER_LEN && s.bytes + frame_len > self.config.max_segment_bytes
+        });
+        let added = frame_len + if rotate { SEGMENT_HEADER_LEN } else { 0 };
+        if total + added > self.config.max_bytes {
+            return Ok(false);
+        }
+
+        if rotate {
+            self.rotate(state)?;
+        } else if state.writer.is_none() {
+            let path = &state.segments.back().expect("segment").path;
+            let file = OpenOptions::new()
+                .append(true)
+                .open(path)
+                .map_err(|e| io_error(path, e))?;
+            state.writer = Some(file);
+        }
+
+        let mut frame = Vec::with_capacity(frame_len as usize);
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(&CRC32C.checksum(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        let segment = state.segments.back_mut().expect("segment");
+        let writer = state.writer.as_mut().expect("writer");
+        writer
+            .write_all(&frame)
+            .map_err(|e| io_error(&segment.path, e))?;
+        segment.bytes += frame_len;
+        state.len += 1;
+
+        state.unsynced += 1;
+        let sync = match self.config.fsync {
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EveryN(n) => state.unsynced >= n,
+            FsyncPolicy::Never => false,
+        };
+        if sync {
+            writer.sync_data().map_err(|e| io_error(&segment.path, e))?;
+            state.unsynced = 0;
+        }
+        Ok(true)
+    }
+
+    /// Removes and returns the oldest queued message
+    pub fn pop(&self) -> Result<Option<NetMessage>> {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        loop {
+            let Some(segment) = state.segments.front() else {
+                return Ok(None);
+            };
+            let stale = state.head.as_ref().is_none_or(|head| {
+                head.seq != segment.seq || head.bytes.len() as u64 != segment.bytes
+            });
+            if stale {
+                // Load, or reload after appends to the segment being read
+                let bytes = fs::read(&segment.path).map_err(|e| io_error(&segment.path, e))?;
+                let frames = scan_segment(&segment.path, &bytes, state.cursor)?;
+                state.head = Some(Head {
+                    seq: segment.seq,
+                    bytes,
+                    frames: frames.into(),
+                });
+            }
+
+            let head = state.head.as_mut().expect("head");
+            if let Some((start, end)) = head.frames.pop_front() {
+                let msg = decode_message(&head.bytes[start..end]);
+                let seq = head.seq;
+                // A frame that passed its checksum but doesn't decode is skipped
+                state.cursor = end as u64;
+                state.len -= 1;
+                if state.len == 0 {
+                    self.clear(state)?;
+                } else {
+                    write_cursor(&state.dir, seq, state.cursor)?;
+                }
+                return msg.map(Some);
+            }
+
+            if state.segments.len() == 1 {
+                return Ok(None);
+            }
+            // Oldest segment fully consumed
+            let segment = state.segments.pop_front().expect("segment");
+            fs::remove_file(&segment.path).map_err(|e| io_error(&segment.path, e))?;
+            state.head = None;
+            state.cursor = SEGMENT_HEADER_LEN;
+        }
+    }
+
+    /// Flushes appended messages to stable storage
+    pub fn sync(&self) -> Result<()> {
+        let mut state = self.lock();
+        if let (Some(writer), Some(segment)) = (&state.writer, state.segments.back()) {
+            writer.sync_data().map_err(|e| io_error(&segment.path, e))?;
+        }
+        state.unsynced = 0;
+        Ok(())
+    }
+
+    fn rotate(&self, state: &mut State) -> Result<()> {
+        if let (Some(writer), Some(segment)) = (&state.writer, state.segments.back()) {
+            writer.sync_data().map_err(|e| io_error(&segment.path, e))?;
+        }
+        let seq = state.segments.back().map_or(0, |s| s.seq + 1);
+        let path = state.dir.join(format!("{:020}.{}", seq, SPILL_EXTENSION));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        file.write_all(&SPILL_MAGIC)
+            .and_then(|_| file.write_all(&[SPILL_VERSION]))
+            .map_err(|e| io_error(&path, e))?;
+        if state.segments.is_empty() {
+            state.cursor = SEGMENT_HEADER_LEN;
+        }
+        state.segments.push_back(Segment {
+            seq,
+            path,
+            bytes: SEGMENT_HEADER_LEN,
+        });
+        state.writer = Some(file);
+        state.unsynced = 0;
+        Ok(())
+    }
+
+    /// Deletes every segment once the last message has been handed out
+    fn clear(&self, state: &mut State) -> Result<()> {
+        state.writer = None;
+        state.head = None;
+        for segment in state.segments.drain(..) {
+            fs::remove_file(&segment.path).map_err(|e| io_error(&segment.path, e))?;
+        }
+        state.cursor = SEGMENT_HEADER_LEN;
+        let path = state.dir.join(CURSOR_FILE);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&path, e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Segment bookkeeping is updated after each write, so it stays usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn encode_message(msg: &NetMessage) -> Result<Vec<u8>> {
+    let meta = TlvEncoder::encode(&msg.envelope.metadata)?;
+    let encoder = BinaryEncoder::with_config(EncoderConfig::new().with_nested_binary(true));
+    let record = encoder
+        .encode(&msg.envelope.record)
+        .map_err(|e| NetError::Spill(format!("cannot encode record: {}", e)))?;
+
+    let mut payload = Vec::with_capacity(32 + meta.len() + record.len());
+    let kind = MessageKind::all()
+        .iter()
+        .position(|k| *k == msg.kind)
+        .expect("every kind is listed") as u8;
+    payload.push(kind);
+    payload.push(msg.priority);
+    payload.extend_from_slice(&msg.ttl_ms.to_le_bytes());
+    payload.extend_from_slice(&msg.retry.attempts.to_le_bytes());
+    let mut flags = 0;
+    if msg.retry.next_attempt_ms.is_some() {
+        flags |= FLAG_NEXT_ATTEMPT;
+    }
+    if msg.class.is_some() {
+        flags |= FLAG_CLASS;
+    }
+    payload.push(flags);
+    if let Some(at) = msg.retry.next_attempt_ms {
+        payload.extend_from_slice(&at.to_le_bytes());
+    }
+    if let Some(class) = &msg.class {
+        let len = u16::try_from(class.len())
+            .map_err(|_| NetError::Spill(format!("class too long: {} bytes", class.len())))?;
+        payload.extend_from_slice(&len.to_le_bytes());
+        payload.extend_from_slice(class.as_bytes());
+    }
+    payload.extend_from_slice(&(meta.len() as u32).to_le_bytes());
+    payload.extend_from_slice(&meta);
+    payload.extend_from_slice(&record);
+    Ok(payload)
+}
+
+fn decode_message(payload: &[u8]) -> Result<NetMessage> {
+    let mut reader = Reader(payload);
+    let kind = *MessageKind::all()
+        .get(reader.u8()? as usize)
+        .ok_or_else(|| NetError::Spill("unknown message kind".to_string()))?;
+    let priority = reader.u8()?;
+    let ttl_ms = u32::from_le_bytes(reader.array()?);
+    let attempts = u32::from_le_bytes(reader.array()?);
+    let flags = reader.u8()?;
+    let next_attempt_ms = if flags & FLAG_NEXT_ATTEMPT != 0 {
+        Some(u64::from_le_bytes(reader.array()?))
+    } else {
+        None
+    };
+    let class = if flags & FLAG_CLASS != 0 {
+        let len = u16::from_le_bytes(reader.array()?) as usize;
+        let bytes = reader.take(len)?;
+        Some(
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| NetError::Spill("class is not UTF-8".to_string()))?,
+        )
+    } else {
+        None
+    };
+    let meta_len = u32::from_le_bytes(reader.array()?) as usize;
+    let metadata = TlvDecoder::decode(reader.take(meta_len)?)?;
+    let record = BinaryDecoder::new()
+        .decode(reader.0)
+        .map_err(|e| NetError::Spill(format!("cannot decode record: {}", e)))?;
+
+    let mut envelope = LnmpEnvelope::new(record);
+    envelope.metadata = metadata;
+    let mut msg = NetMessage::with_qos(envelope, kind, priority, ttl_ms);
+    msg.class = class;
+    msg.retry = RetryState {
+        attempts,
+        next_attempt_ms,
+    };
+    Ok(msg)
+}
+
+/// Cursor over a message payload
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(NetError::Spill("truncated message".to_string()));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("length checked"))
+    }
+}
+
+/// Returns the payload ranges of the valid frames at or after `start`
+///
+/// Stops at a torn tail; a bad frame followed by more data is an error.
+fn scan_segment(path: &Path, bytes: &[u8], start: u64) -> Result<Vec<(usize, usize)>> {
+    if bytes.len() < SEGMENT_HEADER_LEN as usize {
+        // Crash before the header was fully written
+        return Ok(Vec::new());
+    }
+    if bytes[..4] != SPILL_MAGIC {
+        return Err(NetError::Spill(format!(
+            "{}: invalid magic",
+            path.display()
+        )));
+    }
+    if bytes[4] != SPILL_VERSION {
+        return Err(NetError::Spill(format!(
+            "{}: unsupported version 0x{:02X}",
+            path.display(),
+            bytes[4]
+        )));
+    }
+
+    let mut frames = Vec::new();
+    let mut offset = start.max(SEGMENT_HEADER_LEN) as usize;
+    while offset < bytes.len() {
+        let Some(header) = bytes.get(offset..offset + FRAME_HEADER_LEN) else {
+            break;
+        };
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let expected_crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let payload_start = offset + FRAME_HEADER_LEN;
+        let Some(payload) = bytes.get(payload_start..payload_start + len) else {
+            break;
+        };
+        if CRC32C.checksum(payload) != expected_crc {
+            if payload_start + len == bytes.len() {
+                break;
+            }
+            return Err(NetError::Spill(format!(
+                "{}: corrupt frame at offset {}",
+                path.display(),
+                offset
+            )));
+        }
+        frames.push((payload_start, payload_start + len));
+        offset = payload_start + len;
+    }
+    Ok(frames)
+}
+
+fn list_segments(dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SPILL_EXTENSION) {
+            continue;
+        }
+        if let Some(seq) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok())
+        {
+            segments.push((seq, path));
+        }
+    }
+    segments.sort_unstable_by_key(|(seq, _)| *seq);
+    Ok(segments)
+}
+
+/// Reads `(segment, offset)` of the first unconsumed frame; `(0, 0)` if unset
+fn read_cursor(dir: &Path) -> Result<(u64, u64)> {
+    let path = dir.join(CURSOR_FILE);
+    match fs::read(&path) {
+        Ok(bytes) if bytes.len() == 16 => Ok((
+            u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        )),
+        Ok(_) => Err(NetError::Spill(format!(
+            "{}: invalid cursor",
+            path.display()
+        ))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok((0, 0)),
+        Err(e) => Err(io_error(&path, e)),
+    }
+}
+
+fn write_cursor(dir: &Path, seq: u64, offset: u64) -> Result<()> {
+    let mut bytes = [0u8; 16];
+    bytes[0..8].copy_from_slice(&seq.to_le_bytes());
+    bytes[8..16].copy_from_slice(&offset.to_le_bytes());
+    // Replace atomically so a crash never leaves half a cursor
+    let tmp = dir.join(format!("{}.tmp", CURSOR_FILE));
+    fs::write(&tmp, bytes).map_err(|e| io_error(&tmp, e))?;
+    let path = dir.join(CURSOR_FILE);
+    fs::rename(&tmp, &path).map_err(|e| io_error(&path, e))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> NetError {
+    NetError::Spill(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lnmp-spill-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn message(priority: u8) -> NetMessage {
+        let mut inner = LnmpRecord::new();
+        inner.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Float(21.5),
+        });
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 3,
+            value: LnmpValue::NestedRecord(Box::new(inner)),
+        });
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::String("overheat".into()),
+        });
+        let envelope = EnvelopeBuilder::new(record)
+            .timestamp(1000)
+            .source("sensor-7")
+            .trace_id("trace-1")
+            .label("site", "berlin")
+            .build();
+        NetMessage::with_qos(envelope, MessageKind::Alert, priority, 5000)
+    }
+
+    fn assert_same(a: &NetMessage, b: &NetMessage) {
+        assert_eq!(a.envelope, b.envelope);
+        assert_eq!(
+            (a.kind, a.priority, a.ttl_ms, &a.class, a.retry),
+            (b.kind, b.priority, b.ttl_ms, &b.class, b.retry)
+        );
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut msg = message(250);
+        msg.class = Some("safety".into());
+        msg.retry = RetryState {
+            attempts: 2,
+            next_attempt_ms: Some(7000),
+        };
+        assert_same(
+            &decode_message(&encode_message(&msg).unwrap()).unwrap(),
+            &msg,
+        );
+
+        let plain = message(10);
+        assert_same(
+            &decode_message(&encode_message(&plain).unwrap()).unwrap(),
+            &plain,
+        );
+        assert!(decode_message(&encode_message(&plain).unwrap()[..6]).is_err());
+    }
+
+    #[test]
+    fn test_fifo_across_segments_and_restart() {
+        let dir = temp_dir("segments");
+        let queue = SpillQueue::open(&dir).unwrap().with_max_segment_bytes(300);
+        for priority in 0..10 {
+            assert!(queue.push(&message(priority)).unwrap());
+        }
+        assert!(list_segments(&dir).unwrap().len() > 1);
+        for expected in 0..4 {
+            assert_eq!(queue.pop().unwrap().unwrap().priority, expected);
+        }
+        drop(queue);
+
+        let queue = SpillQueue::open(&dir).unwrap();
+        assert_eq!(queue.len(), 6);
+        assert!(queue.push(&message(10)).unwrap());
+        let rest: Vec<_> = std::iter::from_fn(|| queue.pop().unwrap())
+            .map(|msg| msg.priority)
+            .collect();
+        assert_eq!(rest, (4..=10).collect::<Vec<_>>());
+
+        // Drained queues leave no files behind
+        assert!(fs::read_dir(&dir).unwrap().next().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bounded() {
+        let dir = temp_dir("bounded");
+        let frame = encode_message(&message(1)).unwrap().len() as u64 + 8;
+        let queue = SpillQueue::open(&dir)
+            .unwrap()
+            .with_max_bytes(SEGMENT_HEADER_LEN + 2 * frame);
+        assert!(queue.push(&message(1)).unwrap());
+        assert!(queue.push(&message(2)).unwrap());
+        assert!(!queue.push(&message(3)).unwrap());
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.bytes(), SEGMENT_HEADER_LEN + 2 * frame);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torn_tail_truncated() {
+        let dir = temp_dir("torn");
+        let queue = SpillQueue::open(&dir).unwrap();
+        assert!(queue.push(&message(1)).unwrap());
+        assert!(queue.push(&message(2)).unwrap());
+        drop(queue);
+
+        let (_, path) = list_segments(&dir).unwrap().pop().unwrap();
+        let len = fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 3).unwrap();
+
+        let queue = SpillQueue::open(&dir).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(queue.push(&message(3)).unwrap());
+        assert_eq!(queue.pop().unwrap().unwrap().priority, 1);
+        assert_eq!(queue.pop().unwrap().unwrap().priority, 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corruption_reported() {
+        let dir = temp_dir("corrupt");
+        let queue = SpillQueue::open(&dir).unwrap();
+        assert!(queue.push(&message(1)).unwrap());
+        assert!(queue.push(&message(2)).unwrap());
+        drop(queue);
+
+        let (_, path) = list_segments(&dir).unwrap().pop().unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[SEGMENT_HEADER_LEN as usize + FRAME_HEADER_LEN] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+
+        let err = SpillQueue::open(&dir).unwrap_err();
+        assert!(err.to_string().contains("corrupt frame"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
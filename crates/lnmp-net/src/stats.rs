@@ -0,0 +1,446 @@
+//! Routing statistics
+//!
+//! [`RoutingStats`] aggregates what a [`RoutingPolicy`](crate::RoutingPolicy)
+//! decided: counts per message kind, decision and [`RouteReason`], the
+//! distribution of importance scores, and an estimate of the LLM tokens that
+//! local processing and drops saved. Take a [`RoutingStatsSnapshot`] to read
+//! the numbers or export them in the Prometheus text format.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use crate::kind::MessageKind;
+use crate::routing::RoutingDecision;
+
+/// Why a policy reached its decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RouteReason {
+    /// Dropped because it expired
+    Expired,
+    /// Dropped as a repeat of a recent message
+    Duplicate,
+    /// High-priority alert, always sent to the LLM
+    CriticalAlert,
+    /// Event/State importance compared with the LLM threshold
+    Importance,
+    /// Command/Query complexity compared with the complexity threshold
+    Complexity,
+    /// Kept local because the source is over its rate limit
+    RateLimited,
+    /// Kept local because the circuit breaker is open
+    CircuitOpen,
+    /// Kept local because the token budget is (nearly) exhausted
+    TokenBudget,
+}
+
+impl RouteReason {
+    /// Returns the snake_case name used in exports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteReason::Expired => "expired",
+            RouteReason::Duplicate => "duplicate",
+            RouteReason::CriticalAlert => "critical_alert",
+            RouteReason::Importance => "importance",
+            RouteReason::Complexity => "complexity",
+            RouteReason::RateLimited => "rate_limited",
+            RouteReason::CircuitOpen => "circuit_open",
+            RouteReason::TokenBudget => "token_budget",
+        }
+    }
+}
+
+impl fmt::Display for RouteReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Number of equal-width buckets in a [`ScoreDistribution`]
+pub const SCORE_BUCKETS: usize = 10;
+
+/// Histogram of scores in 0.0-1.0, in buckets of width 0.1
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScoreDistribution {
+    buckets: [u64; SCORE_BUCKETS],
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl ScoreDistribution {
+    /// Adds a score (clamped to 0.0-1.0)
+    pub fn record(&mut self, score: f64) {
+        let score = if score.is_nan() {
+            0.0
+        } else {
+            score.clamp(0.0, 1.0)
+        };
+        let bucket = ((score * SCORE_BUCKETS as f64) as usize).min(SCORE_BUCKETS - 1);
+        self.buckets[bucket] += 1;
+        if self.count == 0 {
+            self.min = score;
+            self.max = score;
+        } else {
+            self.min = self.min.min(score);
+            self.max = self.max.max(score);
+        }
+        self.count += 1;
+        self.sum += score;
+    }
+
+    /// Returns the number of scores recorded
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the per-bucket counts; bucket `i` holds scores in `[i/10, (i+1)/10)`
+    pub fn buckets(&self) -> &[u64; SCORE_BUCKETS] {
+        &self.buckets
+    }
+
+    /// Returns the sum of all scores
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the mean score
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    /// Returns the lowest score
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Returns the highest score
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Returns an upper bound of the `q`-quantile (0.0-1.0), to bucket precision
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (q.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(((i + 1) as f64 / SCORE_BUCKETS as f64).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// Point-in-time copy of the counters of a [`RoutingStats`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoutingStatsSnapshot {
+    /// Messages routed
+    pub total: u64,
+    /// Messages per (kind, decision)
+    pub by_kind: HashMap<(MessageKind, RoutingDecision), u64>,
+    /// Messages per reason
+    pub by_reason: HashMap<RouteReason, u64>,
+    /// Importance scores of scored Event/State messages
+    pub importance: ScoreDistribution,
+    /// Estimated tokens of records sent to the LLM
+    pub llm_tokens: u64,
+    /// Estimated tokens of records processed locally or dropped
+    pub tokens_saved: u64,
+}
+
+impl RoutingStatsSnapshot {
+    /// Returns how many messages got `decision`
+    pub fn decision_count(&self, decision: RoutingDecision) -> u64 {
+        self.by_kind
+            .iter()
+            .filter(|((_, d), _)| *d == decision)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Returns how many messages of `kind` got `decision`
+    pub fn count(&self, kind: MessageKind, decision: RoutingDecision) -> u64 {
+        self.by_kind.get(&(kind, decision)).copied().unwrap_or(0)
+    }
+
+    /// Returns how many messages were routed for `reason`
+    pub fn reason_count(&self, reason: RouteReason) -> u64 {
+        self.by_reason.get(&reason).copied().unwrap_or(0)
+    }
+
+    /// Returns the share of messages sent to the LLM (0.0 when none was routed)
+    pub fn llm_ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.decision_count(RoutingDecision::SendToLLM) as f64 / self.total as f64
+        }
+    }
+
+    /// Renders the counters in the Prometheus text exposition format
+    ///
+    /// Metric names start with `prefix` (e.g. `lnmp_routing`); series are
+    /// sorted so the output is stable.
+    pub fn to_prometheus(&self, prefix: &str) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_decisions_total Routing decisions by message kind"
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_decisions_total counter");
+        let mut by_kind: Vec<_> = self
+            .by_kind
+            .iter()
+            .map(|((kind, decision), count)| {
+                (
+                    kind.to_string().to_lowercase(),
+                    decision_label(*decision),
+                    count,
+                )
+            })
+            .collect();
+        by_kind.sort();
+        for (kind, decision, count) in by_kind {
+            let _ = writeln!(
+                out,
+                "{prefix}_decisions_total{{kind=\"{kind}\",decision=\"{decision}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_reasons_total Routing decisions by reason"
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_reasons_total counter");
+        let mut by_reason: Vec<_> = self.by_reason.iter().collect();
+        by_reason.sort();
+        for (reason, count) in by_reason {
+            let _ = writeln!(out, "{prefix}_reasons_total{{reason=\"{reason}\"}} {count}");
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_importance Importance scores of Event/State messages"
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_importance histogram");
+        let mut cumulative = 0;
+        for (i, count) in self.importance.buckets().iter().enumerate() {
+            cumulative += count;
+            let le = (i + 1) as f64 / SCORE_BUCKETS as f64;
+            let _ = writeln!(
+                out,
+                "{prefix}_importance_bucket{{le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let count = self.importance.count();
+        let _ = writeln!(out, "{prefix}_importance_bucket{{le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{prefix}_importance_sum {}", self.importance.sum());
+        let _ = writeln!(out, "{prefix}_importance_count {count}");
+
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_llm_tokens_total Estimated tokens sent to the LLM"
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_llm_tokens_total counter");
+        let _ = writeln!(out, "{prefix}_llm_tokens_total {}", self.llm_tokens);
+        let _ = writeln!(
+            out,
+            "# HELP {prefix}_tokens_saved_total Estimated tokens kept off the LLM"
+        );
+        let _ = writeln!(out, "# TYPE {prefix}_tokens_saved_total counter");
+        let _ = writeln!(out, "{prefix}_tokens_saved_total {}", self.tokens_saved);
+        out
+    }
+}
+
+fn decision_label(decision: RoutingDecision) -> &'static str {
+    match decision {
+        RoutingDecision::SendToLLM => "send_to_llm",
+        RoutingDecision::ProcessLocally => "process_locally",
+        RoutingDecision::Drop => "drop",
+    }
+}
+
+/// Shared routing counters
+///
+/// Attach to a policy with
+/// [`RoutingPolicy::with_stats`](crate::RoutingPolicy::with_stats); clones
+/// share counters, so one `RoutingStats` can aggregate several policies.
+/// Custom routers can feed it through [`record`](Self::record).
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_core::LnmpRecord;
+/// use lnmp_envelope::EnvelopeBuilder;
+/// use lnmp_net::{MessageKind, NetMessage, RouteReason, RoutingDecision, RoutingPolicy, RoutingStats};
+///
+/// let stats = RoutingStats::new();
+/// let policy = RoutingPolicy::default().with_stats(stats.clone());
+///
+/// let envelope = EnvelopeBuilder::new(LnmpRecord::new()).timestamp(1000).build();
+/// let alert = NetMessage::with_qos(envelope, MessageKind::Alert, 255, 5000);
+/// policy.decide(&alert, 2000).unwrap();
+/// policy.decide(&alert, 9000).unwrap();
+///
+/// let snapshot = stats.snapshot();
+/// assert_eq!(snapshot.total, 2);
+/// assert_eq!(snapshot.count(MessageKind::Alert, RoutingDecision::SendToLLM), 1);
+/// assert_eq!(snapshot.reason_count(RouteReason::Expired), 1);
+/// assert_eq!(snapshot.llm_ratio(), 0.5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoutingStats {
+    inner: Arc<Mutex<RoutingStatsSnapshot>>,
+}
+
+impl RoutingStats {
+    /// Creates empty counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one routed message
+    ///
+    /// `importance` is the score the decision was based on, if any, and
+    /// `estimated_tokens` the size of its record as the LLM would see it.
+    pub fn record(
+        &self,
+        kind: MessageKind,
+        decision: RoutingDecision,
+        reason: RouteReason,
+        importance: Option<f64>,
+        estimated_tokens: usize,
+    ) {
+        let mut stats = self.lock();
+        stats.total += 1;
+        *stats.by_kind.entry((kind, decision)).or_default() += 1;
+        *stats.by_reason.entry(reason).or_default() += 1;
+        if let Some(score) = importance {
+            stats.importance.record(score);
+        }
+        if decision == RoutingDecision::SendToLLM {
+            stats.llm_tokens += estimated_tokens as u64;
+        } else {
+            stats.tokens_saved += estimated_tokens as u64;
+        }
+    }
+
+    /// Returns a copy of the counters
+    pub fn snapshot(&self) -> RoutingStatsSnapshot {
+        self.lock().clone()
+    }
+
+    /// Returns a copy of the counters and resets them
+    pub fn take(&self) -> RoutingStatsSnapshot {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RoutingStatsSnapshot> {
+        // Counters stay consistent even if a holder panicked
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_distribution() {
+        let mut dist = ScoreDistribution::default();
+        assert_eq!(dist.mean(), None);
+        assert_eq!(dist.quantile(0.5), None);
+
+        for score in [0.05, 0.15, 0.15, 0.95, 1.0, 1.7, f64::NAN] {
+            dist.record(score);
+        }
+        assert_eq!(dist.count(), 7);
+        assert_eq!(dist.buckets()[0], 2);
+        assert_eq!(dist.buckets()[1], 2);
+        assert_eq!(dist.buckets()[9], 3);
+        assert_eq!(dist.min(), Some(0.0));
+        assert_eq!(dist.max(), Some(1.0));
+        assert_eq!(dist.quantile(0.5), Some(0.2));
+        assert_eq!(dist.quantile(1.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_record_and_take() {
+        let stats = RoutingStats::new();
+        stats.record(
+            MessageKind::Event,
+            RoutingDecision::SendToLLM,
+            RouteReason::Importance,
+            Some(0.9),
+            120,
+        );
+        stats.record(
+            MessageKind::Event,
+            RoutingDecision::ProcessLocally,
+            RouteReason::Importance,
+            Some(0.2),
+            80,
+        );
+        stats.clone().record(
+            MessageKind::Command,
+            RoutingDecision::ProcessLocally,
+            RouteReason::RateLimited,
+            None,
+            40,
+        );
+
+        let snapshot = stats.take();
+        assert_eq!(snapshot.total, 3);
+        assert_eq!(snapshot.decision_count(RoutingDecision::ProcessLocally), 2);
+        assert_eq!(
+            snapshot.count(MessageKind::Event, RoutingDecision::SendToLLM),
+            1
+        );
+        assert_eq!(snapshot.reason_count(RouteReason::Importance), 2);
+        assert_eq!(snapshot.importance.count(), 2);
+        assert_eq!(snapshot.llm_tokens, 120);
+        assert_eq!(snapshot.tokens_saved, 120);
+        assert_eq!(stats.snapshot(), RoutingStatsSnapshot::default());
+    }
+
+    #[test]
+    fn test_prometheus_export() {
+        let stats = RoutingStats::new();
+        stats.record(
+            MessageKind::State,
+            RoutingDecision::Drop,
+            RouteReason::Duplicate,
+            None,
+            10,
+        );
+        stats.record(
+            MessageKind::Event,
+            RoutingDecision::SendToLLM,
+            RouteReason::Importance,
+            Some(0.75),
+            30,
+        );
+        let text = stats.snapshot().to_prometheus("lnmp_routing");
+
+        assert!(text.contains("# TYPE lnmp_routing_decisions_total counter\n"));
+        assert!(text
+            .contains("lnmp_routing_decisions_total{kind=\"event\",decision=\"send_to_llm\"} 1\n"));
+        assert!(text.contains("lnmp_routing_decisions_total{kind=\"state\",decision=\"drop\"} 1\n"));
+        assert!(text.contains("lnmp_routing_reasons_total{reason=\"duplicate\"} 1\n"));
+        assert!(text.contains("lnmp_routing_importance_bucket{le=\"0.7\"} 0\n"));
+        assert!(text.contains("lnmp_routing_importance_bucket{le=\"0.8\"} 1\n"));
+        assert!(text.contains("lnmp_routing_importance_count 1\n"));
+        assert!(text.contains("lnmp_routing_tokens_saved_total 10\n"));
+        // Event sorts before State
+        assert!(text.find("kind=\"event\"").unwrap() < text.find("kind=\"state\"").unwrap());
+    }
+}
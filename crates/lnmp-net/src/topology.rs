@@ -0,0 +1,544 @@
+//! Agent topology and destination resolution
+//!
+//! A [`RoutingPlan`] names abstract destinations (the LLM, local processing,
+//! channels). A [`Topology`] knows the concrete nodes of an agent mesh, what
+//! each of them can do and whether it is alive, and resolves destinations to
+//! the peers that can take a delivery.
+//!
+//! Nodes declare capabilities: `llm` serves [`Destination::Llm`], `local`
+//! serves [`Destination::Local`] and `channel:<name>` serves
+//! [`Destination::Channel`]. Other capabilities are free-form and can be
+//! looked up with [`Topology::by_capability`].
+//!
+//! Nodes are registered at runtime or, with the `yaml` feature, loaded from a
+//! static YAML file:
+//!
+//! ```yaml
+//! heartbeat_timeout_ms: 15000   # optional, default 15000
+//! nodes:
+//!   - id: llm-gateway
+//!     address: http://10.0.0.5:8080
+//!     capabilities: [llm]
+//!   - id: edge-1
+//!     address: 10.0.0.7:7000
+//!     capabilities: [local, "channel:telemetry"]
+//!     health: degraded          # optional: healthy | degraded | down
+//! ```
+//!
+//! A node that sent a heartbeat is considered down once it has been silent for
+//! longer than the heartbeat timeout. Statically configured nodes that never
+//! sent one keep their configured health.
+
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "yaml")]
+use std::fs;
+#[cfg(feature = "yaml")]
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "yaml")]
+use serde_norway::Value;
+
+use crate::error::{NetError, Result};
+use crate::plan::{Delivery, Destination, RoutingPlan};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Capability of nodes serving [`Destination::Llm`]
+pub const LLM_CAPABILITY: &str = "llm";
+/// Capability of nodes serving [`Destination::Local`]
+pub const LOCAL_CAPABILITY: &str = "local";
+/// Prefix of capabilities serving [`Destination::Channel`]
+pub const CHANNEL_PREFIX: &str = "channel:";
+
+/// Health of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NodeHealth {
+    /// Fully available
+    #[default]
+    Healthy,
+    /// Available, but preferred only when no healthy node is
+    Degraded,
+    /// Not available
+    Down,
+}
+
+impl NodeHealth {
+    /// Returns true unless the node is down
+    pub fn is_available(&self) -> bool {
+        !matches!(self, NodeHealth::Down)
+    }
+}
+
+impl fmt::Display for NodeHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeHealth::Healthy => write!(f, "healthy"),
+            NodeHealth::Degraded => write!(f, "degraded"),
+            NodeHealth::Down => write!(f, "down"),
+        }
+    }
+}
+
+impl FromStr for NodeHealth {
+    type Err = NetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "healthy" => Ok(NodeHealth::Healthy),
+            "degraded" => Ok(NodeHealth::Degraded),
+            "down" => Ok(NodeHealth::Down),
+            _ => Err(NetError::InvalidTopology(format!(
+                "invalid health {:?} (expected healthy, degraded or down)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A node of the agent mesh
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Node {
+    /// Unique node identifier
+    pub id: String,
+    /// Transport address (URL, host:port, ...)
+    pub address: String,
+    /// What the node can do (see the [module docs](self))
+    pub capabilities: Vec<String>,
+    /// Last reported health
+    pub health: NodeHealth,
+    /// Time of the last registration or heartbeat (epoch milliseconds)
+    pub last_seen_ms: Option<u64>,
+}
+
+impl Node {
+    /// Creates a healthy node without capabilities
+    pub fn new(id: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            address: address.into(),
+            capabilities: Vec::new(),
+            health: NodeHealth::Healthy,
+            last_seen_ms: None,
+        }
+    }
+
+    /// Adds a capability
+    pub fn with_capability(mut self, capability: impl Into<String>) -> Self {
+        self.capabilities.push(capability.into());
+        self
+    }
+
+    /// Sets the health
+    pub fn with_health(mut self, health: NodeHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Returns true if the node has `capability`
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Returns true if the node can take deliveries to `destination`
+    pub fn serves(&self, destination: &Destination) -> bool {
+        match destination {
+            Destination::Llm => self.has_capability(LLM_CAPABILITY),
+            Destination::Local => self.has_capability(LOCAL_CAPABILITY),
+            Destination::Channel(name) => self.capabilities.iter().any(|c| {
+                c.strip_prefix(CHANNEL_PREFIX)
+                    .is_some_and(|channel| channel == name)
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Config {
+    heartbeat_timeout_ms: u64,
+}
+
+/// Registry of the nodes of an agent mesh
+///
+/// Clones share the registry, so one handle can take heartbeats while
+/// another resolves destinations.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_net::{Destination, Node, NodeHealth, Topology};
+///
+/// let topology = Topology::new(10_000);
+/// topology.register(Node::new("gw-1", "http://10.0.0.5").with_capability("llm"), 0);
+/// topology.register(Node::new("gw-2", "http://10.0.0.6").with_capability("llm"), 0);
+/// topology.heartbeat("gw-1", NodeHealth::Degraded, 5_000).unwrap();
+///
+/// // Healthy peers come first
+/// let peers = topology.resolve(&Destination::Llm, 5_000);
+/// assert_eq!(peers[0].id, "gw-2");
+///
+/// // gw-2 has been silent for longer than the timeout
+/// let peers = topology.resolve(&Destination::Llm, 12_000);
+/// assert_eq!(peers.len(), 1);
+/// assert_eq!(peers[0].id, "gw-1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Topology {
+    config: Arc<Config>,
+    nodes: Arc<Mutex<HashMap<String, Node>>>,
+}
+
+impl Topology {
+    /// Creates an empty topology where nodes go down after `heartbeat_timeout_ms` of silence
+    pub fn new(heartbeat_timeout_ms: u64) -> Self {
+        Self {
+            config: Arc::new(Config {
+                heartbeat_timeout_ms,
+            }),
+            nodes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Parses a static topology from YAML (see the [module docs](self) for the format)
+    ///
+    /// Loaded nodes have not been seen yet and keep their configured health
+    /// until they send a heartbeat.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let root: Value = serde_norway::from_str(yaml).map_err(|e| invalid(e.to_string()))?;
+        let timeout = match root.get("heartbeat_timeout_ms") {
+            Some(value) => value
+                .as_u64()
+                .ok_or_else(|| invalid("'heartbeat_timeout_ms' must be an integer"))?,
+            None => 15_000,
+        };
+        let topology = Self::new(timeout);
+
+        let entries = match root.get("nodes") {
+            Some(Value::Sequence(entries)) => entries.as_slice(),
+            Some(Value::Null) | None => &[],
+            Some(_) => return Err(invalid("'nodes' must be a list")),
+        };
+        {
+            let mut nodes = topology.lock();
+            for (index, entry) in entries.iter().enumerate() {
+                let node = parse_node(index, entry)?;
+                if nodes.contains_key(&node.id) {
+                    return Err(invalid(format!("duplicate node '{}'", node.id)));
+                }
+                nodes.insert(node.id.clone(), node);
+            }
+        }
+        Ok(topology)
+    }
+
+    /// Reads and parses a YAML topology file
+    #[cfg(feature = "yaml")]
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())
+            .map_err(|e| invalid(format!("cannot read {}: {}", path.as_ref().display(), e)))?;
+        Self::from_yaml(&content)
+    }
+
+    /// Returns the heartbeat timeout in milliseconds
+    pub fn heartbeat_timeout_ms(&self) -> u64 {
+        self.config.heartbeat_timeout_ms
+    }
+
+    /// Registers `node` as seen at `now_ms`, returning the node it replaces
+    pub fn register(&self, mut node: Node, now_ms: u64) -> Option<Node> {
+        node.last_seen_ms = Some(now_ms);
+        self.lock().insert(node.id.clone(), node)
+    }
+
+    /// Removes a node, returning it if it was registered
+    pub fn deregister(&self, id: &str) -> Option<Node> {
+        self.lock().remove(id)
+    }
+
+    /// Records a heartbeat of node `id` reporting `health` at `now_ms`
+    pub fn heartbeat(&self, id: &str, health: NodeHealth, now_ms: u64) -> Result<()> {
+        let mut nodes = self.lock();
+        let node = nodes
+            .get_mut(id)
+            .ok_or_else(|| NetError::UnknownNode(id.to_string()))?;
+        node.health = health;
+        node.last_seen_ms = Some(node.last_seen_ms.map_or(now_ms, |seen| seen.max(now_ms)));
+        Ok(())
+    }
+
+    /// Returns node `id` with its health at `now_ms`
+    pub fn node(&self, id: &str, now_ms: u64) -> Option<Node> {
+        self.lock().get(id).map(|node| self.at(node, now_ms))
+    }
+
+    /// Returns every node with its health at `now_ms`, ordered by id
+    pub fn nodes(&self, now_ms: u64) -> Vec<Node> {
+        let mut nodes: Vec<Node> = self
+            .lock()
+            .values()
+            .map(|node| self.at(node, now_ms))
+            .collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes
+    }
+
+    /// Returns the number of registered nodes
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no node is registered
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the available nodes with `capability`, healthy ones first
+    pub fn by_capability(&self, capability: &str, now_ms: u64) -> Vec<Node> {
+        self.available(now_ms, |node| node.has_capability(capability))
+    }
+
+    /// Returns the available nodes serving `destination`, healthy ones first
+    ///
+    /// Ties are ordered by id, so every caller sees the same preference order.
+    pub fn resolve(&self, destination: &Destination, now_ms: u64) -> Vec<Node> {
+        self.available(now_ms, |node| node.serves(destination))
+    }
+
+    /// Resolves every delivery of `plan`
+    ///
+    /// Deliveries no available node serves get an empty peer list.
+    pub fn resolve_plan<'p>(
+        &self,
+        plan: &'p RoutingPlan,
+        now_ms: u64,
+    ) -> Vec<(&'p Delivery, Vec<Node>)> {
+        plan.deliveries
+            .iter()
+            .map(|delivery| (delivery, self.resolve(&delivery.destination, now_ms)))
+            .collect()
+    }
+
+    /// Removes nodes whose heartbeats timed out at `now_ms`, returning their ids
+    pub fn prune(&self, now_ms: u64) -> Vec<String> {
+        let mut nodes = self.lock();
+        let mut removed: Vec<String> = nodes
+            .values()
+            .filter(|node| self.is_stale(node, now_ms))
+            .map(|node| node.id.clone())
+            .collect();
+        for id in &removed {
+            nodes.remove(id);
+        }
+        removed.sort();
+        removed
+    }
+
+    fn available(&self, now_ms: u64, filter: impl Fn(&Node) -> bool) -> Vec<Node> {
+        let mut nodes: Vec<Node> = self
+            .lock()
+            .values()
+            .filter(|node| filter(node))
+            .map(|node| self.at(node, now_ms))
+            .filter(|node| node.health.is_available())
+            .collect();
+        nodes.sort_by(|a, b| a.health.cmp(&b.health).then_with(|| a.id.cmp(&b.id)));
+        nodes
+    }
+
+    /// Copy of `node` with its effective health at `now_ms`
+    fn at(&self, node: &Node, now_ms: u64) -> Node {
+        let mut node = node.clone();
+        if self.is_stale(&node, now_ms) {
+            node.health = NodeHealth::Down;
+        }
+        node
+    }
+
+    fn is_stale(&self, node: &Node, now_ms: u64) -> bool {
+        node.last_seen_ms
+            .is_some_and(|seen| now_ms > seen.saturating_add(self.config.heartbeat_timeout_ms))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Node>> {
+        // The registry stays usable even if a holder panicked
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self::new(15_000)
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn invalid(reason: impl Into<String>) -> NetError {
+    NetError::InvalidTopology(reason.into())
+}
+
+#[cfg(feature = "yaml")]
+fn parse_node(index: usize, entry: &Value) -> Result<Node> {
+    if !entry.is_mapping() {
+        return Err(invalid(format!("node {} must be a mapping", index)));
+    }
+    let string = |key: &str| -> Result<Option<String>> {
+        entry
+            .get(key)
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid(format!("node {}: '{}' must be a string", index, key)))
+            })
+            .transpose()
+    };
+
+    let id = string("id")?.ok_or_else(|| invalid(format!("node {}: missing 'id'", index)))?;
+    let address =
+        string("address")?.ok_or_else(|| invalid(format!("node '{}': missing 'address'", id)))?;
+    let mut node = Node::new(id, address);
+
+    match entry.get("capabilities") {
+        Some(Value::Sequence(capabilities)) => {
+            for capability in capabilities {
+                let capability = capability.as_str().ok_or_else(|| {
+                    invalid(format!("node '{}': capabilities must be strings", node.id))
+                })?;
+                node.capabilities.push(capability.to_string());
+            }
+        }
+        Some(Value::Null) | None => {}
+        Some(_) => {
+            return Err(invalid(format!(
+                "node '{}': 'capabilities' must be a list",
+                node.id
+            )))
+        }
+    }
+    if let Some(health) = string("health")? {
+        node.health = health.parse().map_err(|e| match e {
+            NetError::InvalidTopology(reason) => invalid(format!("node '{}': {}", node.id, reason)),
+            other => other,
+        })?;
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "yaml")]
+    const TOPOLOGY: &str = r#"
+heartbeat_timeout_ms: 1000
+nodes:
+  - id: llm-gateway
+    address: http://10.0.0.5:8080
+    capabilities: [llm]
+  - id: edge-2
+    address: 10.0.0.8:7000
+    capabilities: [local, "channel:telemetry"]
+  - id: edge-1
+    address: 10.0.0.7:7000
+    capabilities: [local, "channel:telemetry", gpu]
+    health: degraded
+"#;
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_load_and_resolve() {
+        let topology = Topology::from_yaml(TOPOLOGY).unwrap();
+        assert_eq!(topology.len(), 3);
+        assert_eq!(topology.heartbeat_timeout_ms(), 1000);
+
+        let ids = |nodes: Vec<Node>| nodes.into_iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(ids(topology.resolve(&Destination::Llm, 0)), ["llm-gateway"]);
+        assert_eq!(
+            ids(topology.resolve(&Destination::channel("telemetry"), 0)),
+            ["edge-2", "edge-1"]
+        );
+        assert!(topology
+            .resolve(&Destination::channel("archive"), 0)
+            .is_empty());
+        assert_eq!(ids(topology.by_capability("gpu", 0)), ["edge-1"]);
+
+        // Never-seen static nodes don't time out
+        assert_eq!(topology.resolve(&Destination::Local, 1_000_000).len(), 2);
+    }
+
+    #[test]
+    fn test_heartbeats_and_pruning() {
+        let topology = Topology::new(1000);
+        let handle = topology.clone();
+        assert!(handle
+            .register(Node::new("a", "a:1").with_capability("local"), 0)
+            .is_none());
+        handle.register(Node::new("b", "b:1").with_capability("local"), 0);
+        assert!(matches!(
+            topology.heartbeat("c", NodeHealth::Healthy, 0),
+            Err(NetError::UnknownNode(id)) if id == "c"
+        ));
+
+        topology.heartbeat("a", NodeHealth::Down, 500).unwrap();
+        topology.heartbeat("b", NodeHealth::Healthy, 900).unwrap();
+        assert_eq!(topology.node("a", 500).unwrap().health, NodeHealth::Down);
+        assert!(topology
+            .resolve(&Destination::Local, 1000)
+            .iter()
+            .all(|node| node.id == "b"));
+
+        // a recovers; b is silent past the timeout
+        topology.heartbeat("a", NodeHealth::Healthy, 1500).unwrap();
+        assert_eq!(topology.node("b", 1901).unwrap().health, NodeHealth::Down);
+        assert_eq!(topology.prune(1901), ["b"]);
+        assert_eq!(topology.nodes(1901).len(), 1);
+        assert!(topology.deregister("a").is_some());
+        assert!(topology.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_resolve_plan() {
+        let topology = Topology::from_yaml(TOPOLOGY).unwrap();
+        let plan = RoutingPlan::new()
+            .with_delivery(Delivery::new(Destination::Llm))
+            .with_delivery(Delivery::new(Destination::channel("archive")));
+        let resolved = topology.resolve_plan(&plan, 0);
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved[0].1[0].address, "http://10.0.0.5:8080");
+        assert!(resolved[1].1.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_invalid_topology() {
+        let cases = [
+            ("nodes: 3", "'nodes' must be a list"),
+            ("nodes:\n  - address: x\n", "node 0: missing 'id'"),
+            ("nodes:\n  - id: a\n", "node 'a': missing 'address'"),
+            (
+                "nodes:\n  - { id: a, address: x, health: sleepy }\n",
+                "node 'a': invalid health",
+            ),
+            (
+                "nodes:\n  - { id: a, address: x }\n  - { id: a, address: y }\n",
+                "duplicate node 'a'",
+            ),
+            ("heartbeat_timeout_ms: soon", "must be an integer"),
+        ];
+        for (yaml, expected) in cases {
+            let err = Topology::from_yaml(yaml).unwrap_err().to_string();
+            assert!(err.contains(expected), "{yaml:?}: {err}");
+        }
+    }
+}
@@ -2,7 +2,23 @@
 
 use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 use lnmp_envelope::EnvelopeBuilder;
-use lnmp_net::{MessageKind, NetMessage, RoutingDecision, RoutingPolicy};
+use lnmp_net::{
+    AsyncRouter, CircuitBreaker, CircuitState, DeadLetter, DeadLetterSink, DedupFilter, DropReason,
+    MessageKind, NetMessage, RateLimit, RateLimiter, Result, RouteReason, Router, RoutingDecision,
+    RoutingPolicy, RoutingStats, TokenBudget,
+};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Polls a future that must complete without waiting
+fn ready<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("future was not ready"),
+    }
+}
 
 fn sample_record() -> LnmpRecord {
     let mut record = LnmpRecord::new();
@@ -266,3 +282,411 @@ fn test_mixed_scenario() {
         RoutingDecision::ProcessLocally
     );
 }
+
+/// Routes by priority only, never consulting the ECO scorer
+struct PriorityRouter {
+    cutoff: u8,
+}
+
+impl Router for PriorityRouter {
+    fn route(&self, msg: &NetMessage, now_ms: u64) -> Result<RoutingDecision> {
+        if msg.is_expired(now_ms)? {
+            Ok(RoutingDecision::Drop)
+        } else if msg.priority >= self.cutoff {
+            Ok(RoutingDecision::SendToLLM)
+        } else {
+            Ok(RoutingDecision::ProcessLocally)
+        }
+    }
+}
+
+/// Stand-in for a router that awaits a remote model
+struct RemoteRouter;
+
+impl AsyncRouter for RemoteRouter {
+    async fn route_async(&self, msg: &NetMessage, _now_ms: u64) -> Result<RoutingDecision> {
+        Ok(if msg.kind.is_command() {
+            RoutingDecision::SendToLLM
+        } else {
+            RoutingDecision::ProcessLocally
+        })
+    }
+}
+
+fn route_all(router: &dyn Router, msgs: &[NetMessage], now_ms: u64) -> Vec<RoutingDecision> {
+    msgs.iter()
+        .map(|msg| router.route(msg, now_ms).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_router_trait_objects() {
+    let envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .build();
+    let msgs = [
+        NetMessage::with_qos(envelope.clone(), MessageKind::Command, 100, 5000),
+        NetMessage::with_qos(envelope.clone(), MessageKind::Event, 20, 5000),
+        NetMessage::with_qos(envelope, MessageKind::Event, 20, 500),
+    ];
+
+    assert_eq!(
+        route_all(&PriorityRouter { cutoff: 50 }, &msgs, 2000),
+        [
+            RoutingDecision::SendToLLM,
+            RoutingDecision::ProcessLocally,
+            RoutingDecision::Drop
+        ]
+    );
+
+    let policy = RoutingPolicy::default();
+    let expected: Vec<_> = msgs
+        .iter()
+        .map(|msg| policy.decide(msg, 2000).unwrap())
+        .collect();
+    assert_eq!(route_all(&policy, &msgs, 2000), expected);
+}
+
+#[test]
+fn test_async_router() {
+    let envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .build();
+    let command = NetMessage::new(envelope.clone(), MessageKind::Command);
+    let alert = NetMessage::new(envelope, MessageKind::Alert);
+
+    assert_eq!(
+        ready(RemoteRouter.route_async(&command, 2000)).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+    assert_eq!(
+        ready(RemoteRouter.route_async(&alert, 2000)).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+
+    // Sync routers are async routers too
+    assert_eq!(
+        ready(RoutingPolicy::default().route_async(&alert, 2000)).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+}
+
+#[test]
+fn test_rate_limiter_downgrades_flooding_source() {
+    let limiter = RateLimiter::new(RateLimit::new(2, 1.0));
+    let policy = RoutingPolicy::default().with_rate_limiter(limiter.clone());
+    let alert = |source: &str| {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .source(source)
+            .build();
+        NetMessage::with_qos(envelope, MessageKind::Alert, 255, 10_000)
+    };
+
+    let flood: Vec<_> = (0..4)
+        .map(|_| policy.decide(&alert("sensor-7"), 2000).unwrap())
+        .collect();
+    assert_eq!(
+        flood,
+        [
+            RoutingDecision::SendToLLM,
+            RoutingDecision::SendToLLM,
+            RoutingDecision::ProcessLocally,
+            RoutingDecision::ProcessLocally
+        ]
+    );
+
+    // Other sources keep their budget, and the flooder recovers over time
+    assert_eq!(
+        policy.decide(&alert("sensor-8"), 2000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+    assert_eq!(
+        policy.decide(&alert("sensor-7"), 3000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+
+    // Local decisions don't spend tokens
+    let low_envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .source("sensor-9")
+        .build();
+    let low = NetMessage::with_qos(low_envelope, MessageKind::Event, 10, 10_000);
+    assert_eq!(
+        policy.decide(&low, 2000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+
+    let metrics = limiter.metrics();
+    assert_eq!(metrics.allowed, 4);
+    assert_eq!(metrics.limited, 2);
+    assert_eq!(metrics.limited_by_source["sensor-7"], 2);
+}
+
+#[test]
+fn test_rate_token_returned_when_later_guard_refuses() {
+    let limiter = RateLimiter::new(RateLimit::new(1, 0.0));
+    let envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .source("sensor-7")
+        .build();
+    let alert = NetMessage::with_qos(envelope, MessageKind::Alert, 255, 60_000);
+
+    // An exhausted budget keeps the alert local without using the rate token
+    let budget = TokenBudget::new(2, 60_000);
+    let starved = RoutingPolicy::default()
+        .with_rate_limiter(limiter.clone())
+        .with_token_budget(budget);
+    for _ in 0..3 {
+        assert_eq!(
+            starved.decide(&alert, 2000).unwrap(),
+            RoutingDecision::ProcessLocally
+        );
+    }
+
+    // So does an open circuit
+    let breaker = CircuitBreaker::new(0.5, 10_000).with_min_requests(1);
+    breaker.record_failure(2000);
+    let broken = RoutingPolicy::default()
+        .with_rate_limiter(limiter.clone())
+        .with_circuit_breaker(breaker);
+    assert_eq!(
+        broken.decide(&alert, 2000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+
+    let policy = RoutingPolicy::default().with_rate_limiter(limiter.clone());
+    assert_eq!(
+        policy.decide(&alert, 2000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+    assert_eq!(
+        policy.decide(&alert, 2000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+}
+
+#[test]
+fn test_dedup_drops_repeats_before_routing() {
+    let dedup = DedupFilter::new(5000);
+    let limiter = RateLimiter::new(RateLimit::new(10, 0.0));
+    let policy = RoutingPolicy::default()
+        .with_dedup(dedup.clone())
+        .with_rate_limiter(limiter.clone());
+    let alert = || {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .source("sensor-7")
+            .build();
+        NetMessage::with_qos(envelope, MessageKind::Alert, 255, 60_000)
+    };
+
+    let decisions: Vec<_> = [2000, 2500, 6999, 7000]
+        .into_iter()
+        .map(|now| policy.decide(&alert(), now).unwrap())
+        .collect();
+    assert_eq!(
+        decisions,
+        [
+            RoutingDecision::SendToLLM,
+            RoutingDecision::Drop,
+            RoutingDecision::Drop,
+            RoutingDecision::SendToLLM
+        ]
+    );
+
+    // Dropped repeats never reach the rate limiter
+    assert_eq!(dedup.metrics().duplicates, 2);
+    assert_eq!(limiter.metrics().allowed, 2);
+}
+
+#[test]
+fn test_open_circuit_routes_locally() {
+    let breaker = CircuitBreaker::new(0.5, 10_000).with_min_requests(2);
+    let policy = RoutingPolicy::default().with_circuit_breaker(breaker.clone());
+    let envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .build();
+    let alert = NetMessage::with_qos(envelope, MessageKind::Alert, 255, 60_000);
+
+    for _ in 0..2 {
+        assert_eq!(
+            policy.decide(&alert, 2000).unwrap(),
+            RoutingDecision::SendToLLM
+        );
+        breaker.record_failure(2000);
+    }
+    assert_eq!(breaker.state(2000), CircuitState::Open);
+    assert_eq!(
+        policy.decide(&alert, 3000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+
+    // Half-open: one probe goes to the LLM, the rest stay local until it succeeds
+    assert_eq!(
+        policy.decide(&alert, 12_000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+    assert_eq!(
+        policy.decide(&alert, 12_000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+    breaker.record_success(12_500);
+    assert_eq!(
+        policy.decide(&alert, 13_000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+}
+
+#[test]
+fn test_token_budget_demotes_low_importance() {
+    // The sample record is estimated at 3 tokens; no event score is protected
+    let budget = TokenBudget::new(10, 60_000)
+        .with_reserve(0.5)
+        .with_protected_importance(1.1);
+    let stats = RoutingStats::new();
+    let policy = RoutingPolicy::new(0.0)
+        .with_token_budget(budget.clone())
+        .with_stats(stats.clone());
+    let message = |kind, priority| {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .build();
+        NetMessage::with_qos(envelope, kind, priority, 120_000)
+    };
+    let event = message(MessageKind::Event, 100);
+    let alert = message(MessageKind::Alert, 255);
+
+    assert_eq!(policy.estimate_tokens(&event), 3);
+    assert_eq!(
+        policy.decide(&event, 2000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+    // 6 of 10 tokens would eat into the reserve
+    assert_eq!(
+        policy.decide(&event, 2000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+    // Critical alerts may spend the reserve, but not more
+    for expected in [RoutingDecision::SendToLLM, RoutingDecision::SendToLLM] {
+        assert_eq!(policy.decide(&alert, 2000).unwrap(), expected);
+    }
+    assert_eq!(
+        policy.decide(&alert, 2000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+    assert_eq!(budget.used(2000), 9);
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.reason_count(RouteReason::TokenBudget), 2);
+    assert_eq!(budget.metrics().demoted, 1);
+    assert_eq!(budget.metrics().exhausted, 1);
+
+    // The window rolls over
+    assert_eq!(
+        policy.decide(&event, 62_000).unwrap(),
+        RoutingDecision::SendToLLM
+    );
+
+    // Messages an open circuit keeps local don't spend the budget
+    let breaker = CircuitBreaker::new(0.5, 10_000).with_min_requests(1);
+    breaker.record_failure(62_000);
+    let guarded = policy.with_circuit_breaker(breaker);
+    assert_eq!(
+        guarded.decide(&alert, 62_000).unwrap(),
+        RoutingDecision::ProcessLocally
+    );
+    assert_eq!(budget.used(62_000), 3);
+}
+
+#[derive(Debug, Default)]
+struct CollectDeadLetters(Mutex<Vec<DeadLetter>>);
+
+impl DeadLetterSink for CollectDeadLetters {
+    fn send(&self, letter: DeadLetter) -> Result<()> {
+        self.0.lock().unwrap().push(letter);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_dropped_messages_dead_lettered() {
+    let sink = Arc::new(CollectDeadLetters::default());
+    let policy = RoutingPolicy::default()
+        .with_dedup(DedupFilter::new(10_000))
+        .with_dead_letter_sink(sink.clone());
+    let envelope = EnvelopeBuilder::new(sample_record())
+        .timestamp(1000)
+        .source("sensor-7")
+        .build();
+    let msg = NetMessage::with_qos(envelope, MessageKind::Event, 100, 5000);
+
+    assert_ne!(policy.decide(&msg, 2000).unwrap(), RoutingDecision::Drop);
+    assert_eq!(policy.decide(&msg, 2500).unwrap(), RoutingDecision::Drop);
+    assert_eq!(policy.decide(&msg, 9000).unwrap(), RoutingDecision::Drop);
+
+    // Zero-copy path captures too, keeping the effective expiry
+    let view = lnmp_core::LnmpRecordView::new();
+    let metadata = msg.envelope.metadata.clone();
+    assert_eq!(
+        policy
+            .decide_view(MessageKind::State, 80, &metadata, Some(1500), &view, 3000)
+            .unwrap(),
+        RoutingDecision::Drop
+    );
+
+    let letters = sink.0.lock().unwrap();
+    let reasons: Vec<_> = letters.iter().map(|l| l.reason.clone()).collect();
+    assert_eq!(
+        reasons,
+        [
+            DropReason::Duplicate,
+            DropReason::Expired,
+            DropReason::Expired
+        ]
+    );
+    assert_eq!(letters[1].dropped_at_ms, 9000);
+    assert_eq!(letters[1].message.source(), Some("sensor-7"));
+    assert_eq!(letters[2].message.kind, MessageKind::State);
+    assert_eq!(letters[2].message.priority, 80);
+    assert_eq!(letters[2].message.envelope.metadata.expires_at, Some(1500));
+}
+
+#[test]
+fn test_stats_record_reasons_and_tokens() {
+    let stats = RoutingStats::new();
+    let policy = RoutingPolicy::default()
+        .with_rate_limiter(RateLimiter::new(RateLimit::new(1, 0.0)))
+        .with_stats(stats.clone());
+    let message = |kind, priority| {
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1000)
+            .source("sensor-7")
+            .build();
+        NetMessage::with_qos(envelope, kind, priority, 60_000)
+    };
+
+    policy
+        .decide(&message(MessageKind::Alert, 255), 2000)
+        .unwrap();
+    policy
+        .decide(&message(MessageKind::Alert, 255), 2000)
+        .unwrap();
+    policy
+        .decide(&message(MessageKind::Event, 10), 2000)
+        .unwrap();
+    policy
+        .decide(&message(MessageKind::Command, 100), 2000)
+        .unwrap();
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total, 4);
+    assert_eq!(snapshot.reason_count(RouteReason::CriticalAlert), 1);
+    assert_eq!(snapshot.reason_count(RouteReason::RateLimited), 1);
+    assert_eq!(snapshot.reason_count(RouteReason::Importance), 1);
+    assert_eq!(snapshot.reason_count(RouteReason::Complexity), 1);
+    assert_eq!(snapshot.decision_count(RoutingDecision::ProcessLocally), 3);
+    assert_eq!(snapshot.importance.count(), 1);
+    assert!(snapshot.llm_tokens > 0);
+    assert_eq!(snapshot.tokens_saved, 3 * snapshot.llm_tokens);
+}
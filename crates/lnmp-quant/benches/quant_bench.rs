@@ -1,6 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use lnmp_embedding::Vector;
-use lnmp_quant::{dequantize_embedding, quantize_embedding, QuantScheme};
+use lnmp_embedding::{DeltaChange, SimilarityMetric, Vector, VectorDelta};
+use lnmp_quant::{
+    dequantize_embedding, dequantize_into, quantize_embedding, quantized_similarity, QuantScheme,
+};
 
 fn bench_quantize_512dim(c: &mut Criterion) {
     let values: Vec<f32> = (0..512).map(|i| (i as f32 / 512.0) - 0.5).collect();
@@ -23,6 +25,13 @@ fn bench_dequantize_512dim(c: &mut Criterion) {
             let _ = dequantize_embedding(black_box(&quantized)).unwrap();
         });
     });
+
+    let mut buffer = vec![0.0f32; 512];
+    c.bench_function("dequantize_into_512dim", |b| {
+        b.iter(|| {
+            dequantize_into(black_box(&quantized), &mut buffer).unwrap();
+        });
+    });
 }
 
 fn bench_roundtrip_512dim(c: &mut Criterion) {
@@ -37,6 +46,61 @@ fn bench_roundtrip_512dim(c: &mut Criterion) {
     });
 }
 
+fn bench_similarity_512dim(c: &mut Criterion) {
+    let a = Vector::from_f32((0..512).map(|i| (i as f32 / 512.0) - 0.5).collect());
+    let b = Vector::from_f32(
+        (0..512)
+            .map(|i| ((i * 7 % 512) as f32 / 512.0) - 0.5)
+            .collect(),
+    );
+    let qa = quantize_embedding(&a, QuantScheme::QInt8).unwrap();
+    let qb = quantize_embedding(&b, QuantScheme::QInt8).unwrap();
+
+    c.bench_function("quantized_similarity_512dim", |bench| {
+        bench.iter(|| {
+            let _ = quantized_similarity(black_box(&qa), black_box(&qb), SimilarityMetric::Cosine)
+                .unwrap();
+        });
+    });
+
+    c.bench_function("dequantized_similarity_512dim", |bench| {
+        bench.iter(|| {
+            let da = dequantize_embedding(black_box(&qa)).unwrap();
+            let db = dequantize_embedding(black_box(&qb)).unwrap();
+            let _ = da.similarity(&db, SimilarityMetric::Cosine).unwrap();
+        });
+    });
+}
+
+fn bench_apply_delta_1536dim(c: &mut Criterion) {
+    let embedding = Vector::from_f32((0..1536).map(|i| (i as f32 / 1536.0) - 0.5).collect());
+    let quantized = quantize_embedding(&embedding, QuantScheme::QInt8).unwrap();
+    let delta = VectorDelta::new(
+        0,
+        (0..16)
+            .map(|i| DeltaChange {
+                index: i * 96,
+                delta: 0.01,
+            })
+            .collect(),
+    );
+
+    c.bench_function("apply_delta_quantized_1536dim", |b| {
+        b.iter(|| {
+            let mut q = quantized.clone();
+            q.apply_delta(black_box(&delta)).unwrap();
+        });
+    });
+
+    c.bench_function("apply_delta_requantize_1536dim", |b| {
+        b.iter(|| {
+            let restored = dequantize_embedding(black_box(&quantized)).unwrap();
+            let updated = delta.apply(&restored).unwrap();
+            let _ = quantize_embedding(&updated, QuantScheme::QInt8).unwrap();
+        });
+    });
+}
+
 fn bench_quantize_128dim(c: &mut Criterion) {
     let values: Vec<f32> = (0..128).map(|i| (i as f32 / 128.0) - 0.5).collect();
     let embedding = Vector::from_f32(values);
@@ -65,6 +129,8 @@ criterion_group!(
     bench_quantize_512dim,
     bench_quantize_1536dim,
     bench_dequantize_512dim,
-    bench_roundtrip_512dim
+    bench_roundtrip_512dim,
+    bench_similarity_512dim,
+    bench_apply_delta_1536dim
 );
 criterion_main!(benches);
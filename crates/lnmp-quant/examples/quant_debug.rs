@@ -1,5 +1,5 @@
 use lnmp_embedding::{SimilarityMetric, Vector};
-use lnmp_quant::{dequantize_embedding, quantize_embedding, QuantScheme};
+use lnmp_quant::{dequantize_embedding, quantize_embedding, QuantMetrics, QuantScheme};
 
 fn main() {
     let original = Vector::from_f32(vec![0.1, 0.2, 0.3, 0.4, 0.5]);
@@ -31,4 +31,18 @@ fn main() {
 
     println!("\nWith negative values:");
     println!("Cosine similarity: {}", similarity2);
+
+    // Per-dimension breakdown under the lossier schemes
+    for scheme in [QuantScheme::QInt4, QuantScheme::Binary] {
+        let quantized = quantize_embedding(&original2, scheme).unwrap();
+        let report = QuantMetrics::error_report(&original2, &quantized, 4, 3).unwrap();
+
+        println!("\n{:?} error report:", scheme);
+        println!("  Cosine degradation: {:.4}", report.cosine_degradation);
+        println!(
+            "  Histogram (bin width {:.4}): {:?}",
+            report.bin_width, report.histogram
+        );
+        println!("  Worst dimensions: {:?}", report.worst_dims);
+    }
 }
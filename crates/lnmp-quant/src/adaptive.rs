@@ -12,7 +12,20 @@ use crate::encode::quantize_embedding;
 use crate::error::QuantError;
 use crate::scheme::QuantScheme;
 use crate::vector::QuantizedVector;
-use lnmp_embedding::Vector;
+use half::f16;
+use lnmp_embedding::{EmbeddingType, Vector};
+
+/// Maximum number of dimensions reconstructed when estimating a scheme's quality
+const SAMPLE_DIMS: usize = 256;
+
+/// Candidate schemes, most compressed first
+const CANDIDATES: [QuantScheme; 5] = [
+    QuantScheme::Binary,
+    QuantScheme::QInt2,
+    QuantScheme::QInt4,
+    QuantScheme::QInt8,
+    QuantScheme::FP16Passthrough,
+];
 
 /// Target accuracy levels for adaptive quantization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,6 +40,18 @@ pub enum AccuracyTarget {
     Compact,
 }
 
+impl AccuracyTarget {
+    /// The scheme used for this target
+    pub fn scheme(self) -> QuantScheme {
+        match self {
+            AccuracyTarget::Maximum => QuantScheme::FP16Passthrough,
+            AccuracyTarget::High => QuantScheme::QInt8,
+            AccuracyTarget::Balanced => QuantScheme::QInt4,
+            AccuracyTarget::Compact => QuantScheme::Binary,
+        }
+    }
+}
+
 /// Target compression levels for adaptive quantization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompressionTarget {
@@ -62,14 +87,7 @@ pub fn quantize_adaptive(
     emb: &Vector,
     target: AccuracyTarget,
 ) -> Result<QuantizedVector, QuantError> {
-    let scheme = match target {
-        AccuracyTarget::Maximum => QuantScheme::FP16Passthrough,
-        AccuracyTarget::High => QuantScheme::QInt8,
-        AccuracyTarget::Balanced => QuantScheme::QInt4,
-        AccuracyTarget::Compact => QuantScheme::Binary,
-    };
-
-    quantize_embedding(emb, scheme)
+    quantize_embedding(emb, target.scheme())
 }
 
 /// Quantize an embedding based on a compression target
@@ -95,6 +113,130 @@ pub fn quantize_with_target(
     quantize_embedding(emb, scheme)
 }
 
+/// Select the most compressed scheme whose estimated cosine similarity between
+/// the original and the reconstructed vector is at least `min_cosine`
+///
+/// Quantization parameters (range, mean) come from the whole vector, but only up
+/// to 256 evenly spaced dimensions are reconstructed, so the cost stays flat for
+/// large vectors. Falls back to FP16 when no scheme meets the budget or the vector
+/// cannot be estimated (non-F32 or empty).
+///
+/// # Example
+/// ```
+/// use lnmp_quant::adaptive::select_scheme;
+/// use lnmp_quant::QuantScheme;
+/// use lnmp_embedding::Vector;
+///
+/// let vec = Vector::from_f32((0..768).map(|i| (i as f32 * 0.37).sin()).collect());
+/// assert_eq!(select_scheme(&vec, 0.999), QuantScheme::QInt8);
+/// assert_eq!(select_scheme(&vec, 0.5), QuantScheme::Binary);
+/// ```
+pub fn select_scheme(emb: &Vector, min_cosine: f32) -> QuantScheme {
+    select_scheme_batch(std::slice::from_ref(emb), min_cosine)
+}
+
+/// Select one scheme for a whole batch: the most compressed scheme whose
+/// estimated cosine similarity meets `min_cosine` for every vector
+///
+/// Useful when a collection must share a scheme (e.g. one index or column). An
+/// empty batch selects FP16.
+pub fn select_scheme_batch(embs: &[Vector], min_cosine: f32) -> QuantScheme {
+    let Some(samples) = embs.iter().map(Sample::new).collect::<Option<Vec<_>>>() else {
+        return QuantScheme::FP16Passthrough;
+    };
+    if samples.is_empty() {
+        return QuantScheme::FP16Passthrough;
+    }
+
+    CANDIDATES
+        .into_iter()
+        .find(|&scheme| {
+            samples
+                .iter()
+                .all(|sample| sample.estimate_cosine(scheme) >= min_cosine)
+        })
+        .unwrap_or(QuantScheme::FP16Passthrough)
+}
+
+/// Sampled values of a vector plus the whole-vector statistics the schemes use
+struct Sample {
+    values: Vec<f32>,
+    min: f32,
+    max: f32,
+    mean: f32,
+}
+
+impl Sample {
+    fn new(emb: &Vector) -> Option<Self> {
+        if emb.dtype != EmbeddingType::F32 || emb.dim == 0 {
+            return None;
+        }
+        let values = emb.as_f32().ok()?;
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+
+        let n = values.len().min(SAMPLE_DIMS);
+        let values = (0..n).map(|i| values[i * values.len() / n]).collect();
+        Some(Self {
+            values,
+            min,
+            max,
+            mean,
+        })
+    }
+
+    /// Cosine similarity between the sampled values and their reconstruction
+    fn estimate_cosine(&self, scheme: QuantScheme) -> f32 {
+        let levels = match scheme {
+            QuantScheme::QInt8 => 255.0,
+            QuantScheme::QInt4 | QuantScheme::MixedPrecision => 15.0,
+            QuantScheme::QInt2 => 3.0,
+            _ => 0.0,
+        };
+        let scale = if (self.max - self.min).abs() < 1e-10 {
+            1.0
+        } else {
+            (self.max - self.min) / levels
+        };
+
+        let reconstruct = |v: f32| match scheme {
+            // QInt8 truncates, the packed schemes round
+            QuantScheme::QInt8 => {
+                ((v - self.min) / scale).floor().clamp(0.0, levels) * scale + self.min
+            }
+            // MixedPrecision is not a candidate; QInt4 bounds it from below
+            QuantScheme::QInt4 | QuantScheme::QInt2 | QuantScheme::MixedPrecision => {
+                ((v - self.min) / scale).round().clamp(0.0, levels) * scale + self.min
+            }
+            QuantScheme::Binary => {
+                if v >= self.mean {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            QuantScheme::FP16Passthrough => f16::from_f32(v).to_f32(),
+        };
+
+        let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+        for &v in &self.values {
+            let r = reconstruct(v);
+            dot += v as f64 * r as f64;
+            norm_a += v as f64 * v as f64;
+            norm_b += r as f64 * r as f64;
+        }
+        if norm_a == 0.0 {
+            // A zero vector has no direction to lose
+            1.0
+        } else if norm_b == 0.0 {
+            0.0
+        } else {
+            (dot / (norm_a.sqrt() * norm_b.sqrt())) as f32
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +263,61 @@ mod tests {
         assert_eq!(q.scheme, QuantScheme::Binary);
     }
 
+    /// Position in `CANDIDATES` (higher is less compressed)
+    fn rank(scheme: QuantScheme) -> usize {
+        CANDIDATES.iter().position(|&s| s == scheme).unwrap()
+    }
+
+    #[test]
+    fn test_select_scheme_matches_actual_quality() {
+        use crate::decode::dequantize_embedding;
+        use lnmp_embedding::SimilarityMetric;
+
+        // Smooth vector: dims are sampled, but every scheme's estimate should be
+        // close to the real reconstruction quality
+        let vec = Vector::from_f32((0..2048).map(|i| (i as f32 * 0.013).sin()).collect());
+        let samples = Sample::new(&vec).unwrap();
+        assert_eq!(samples.values.len(), SAMPLE_DIMS);
+        for scheme in CANDIDATES {
+            let restored =
+                dequantize_embedding(&quantize_embedding(&vec, scheme).unwrap()).unwrap();
+            let actual = vec.similarity(&restored, SimilarityMetric::Cosine).unwrap();
+            let estimate = samples.estimate_cosine(scheme);
+            assert!(
+                (actual - estimate).abs() < 0.02,
+                "{scheme:?}: {actual} vs {estimate}"
+            );
+        }
+
+        // Tighter budgets never pick a more compressed scheme
+        let ranks: Vec<usize> = [0.5, 0.9, 0.97, 0.999, 0.999999]
+            .into_iter()
+            .map(|budget| rank(select_scheme(&vec, budget)))
+            .collect();
+        assert!(ranks.windows(2).all(|w| w[0] <= w[1]), "{ranks:?}");
+        assert_eq!(select_scheme(&vec, 1.5), QuantScheme::FP16Passthrough);
+    }
+
+    #[test]
+    fn test_select_scheme_batch() {
+        let smooth = Vector::from_f32((0..512).map(|i| (i as f32 * 0.02).sin()).collect());
+        // One outlier stretches the range and hurts the low-bit schemes
+        let mut values: Vec<f32> = (0..512).map(|i| (i as f32 * 0.02).cos() * 0.1).collect();
+        values[7] = 5.0;
+        let spiky = Vector::from_f32(values);
+
+        let budget = 0.95;
+        let alone = rank(select_scheme(&smooth, budget));
+        let spiky_alone = rank(select_scheme(&spiky, budget));
+        assert!(spiky_alone > alone);
+        let together = select_scheme_batch(&[smooth.clone(), spiky], budget);
+        assert_eq!(rank(together), alone.max(spiky_alone));
+
+        assert_eq!(select_scheme_batch(&[], 0.9), QuantScheme::FP16Passthrough);
+        let f16 = smooth.to_f16().unwrap();
+        assert_eq!(select_scheme(&f16, 0.5), QuantScheme::FP16Passthrough);
+    }
+
     #[test]
     fn test_adaptive_compression_selection() {
         let vec = Vector::from_f32(vec![0.1, 0.2, 0.3, 0.4]);
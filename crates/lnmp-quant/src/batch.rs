@@ -9,12 +9,13 @@
 //! 150ns per vector. For maximum raw throughput in tight loops, consider using
 //! `quantize_embedding` directly.
 
-use crate::adaptive::{quantize_adaptive, AccuracyTarget};
-use crate::encode::quantize_embedding;
+use crate::adaptive::AccuracyTarget;
+use crate::decode::dequantize_into;
+use crate::encode::{quantize_embedding, quantize_into};
 use crate::error::QuantError;
 use crate::scheme::QuantScheme;
 use crate::vector::QuantizedVector;
-use lnmp_embedding::Vector;
+use lnmp_embedding::{EmbeddingType, Vector};
 use std::time::{Duration, Instant};
 
 /// Statistics for a batch quantization operation
@@ -65,8 +66,21 @@ pub fn quantize_batch(embeddings: &[Vector], scheme: QuantScheme) -> BatchResult
     let mut succeeded = 0;
     let mut failed = 0;
 
+    // F32 values are decoded into one scratch buffer shared by the whole batch
+    let mut scratch = Vec::new();
     for emb in embeddings {
-        let result = quantize_embedding(emb, scheme);
+        let result = if emb.dtype == EmbeddingType::F32 && emb.data.len() == emb.dim as usize * 4 {
+            scratch.clear();
+            scratch.extend(
+                emb.data
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            );
+            let mut quantized = QuantizedVector::default();
+            quantize_into(&scratch, scheme, &mut quantized).map(|_| quantized)
+        } else {
+            quantize_embedding(emb, scheme)
+        };
         if result.is_ok() {
             succeeded += 1;
         } else {
@@ -95,30 +109,50 @@ pub fn quantize_batch(embeddings: &[Vector], scheme: QuantScheme) -> BatchResult
 /// # Returns
 /// * `BatchResult` - Contains results and statistics
 pub fn quantize_batch_adaptive(embeddings: &[Vector], target: AccuracyTarget) -> BatchResult {
-    let start_time = Instant::now();
-    let mut results = Vec::with_capacity(embeddings.len());
-    let mut succeeded = 0;
-    let mut failed = 0;
+    quantize_batch(embeddings, target.scheme())
+}
 
-    for emb in embeddings {
-        let result = quantize_adaptive(emb, target);
-        if result.is_ok() {
-            succeeded += 1;
-        } else {
-            failed += 1;
-        }
-        results.push(result);
+/// Dequantize a batch into one contiguous buffer, rows back to back
+///
+/// `out` must hold exactly the sum of the vectors' dimensions. No allocation is
+/// made, so a retrieval loop can score a whole page of candidates from one buffer.
+///
+/// # Example
+/// ```
+/// use lnmp_quant::batch::{dequantize_batch_into, quantize_batch};
+/// use lnmp_quant::QuantScheme;
+/// use lnmp_embedding::Vector;
+///
+/// let vecs = vec![Vector::from_f32(vec![0.1, 0.2]), Vector::from_f32(vec![0.3, 0.4])];
+/// let quantized: Vec<_> = quantize_batch(&vecs, QuantScheme::QInt8)
+///     .results
+///     .into_iter()
+///     .map(Result::unwrap)
+///     .collect();
+///
+/// let mut rows = vec![0.0f32; 4];
+/// dequantize_batch_into(&quantized, &mut rows).unwrap();
+/// ```
+pub fn dequantize_batch_into(
+    quantized: &[QuantizedVector],
+    out: &mut [f32],
+) -> Result<(), QuantError> {
+    let total: usize = quantized.iter().map(|q| q.dim as usize).sum();
+    if out.len() != total {
+        return Err(QuantError::InvalidDimension(format!(
+            "Output buffer holds {} values, batch has {}",
+            out.len(),
+            total
+        )));
     }
 
-    BatchResult {
-        results,
-        stats: BatchStats {
-            total: embeddings.len(),
-            succeeded,
-            failed,
-            total_time: start_time.elapsed(),
-        },
+    let mut offset = 0;
+    for q in quantized {
+        let dim = q.dim as usize;
+        dequantize_into(q, &mut out[offset..offset + dim])?;
+        offset += dim;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -147,6 +181,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_batch_matches_single_and_dequantizes_into() {
+        let vecs = vec![
+            Vector::from_f32(vec![0.1, -0.2, 0.3]),
+            Vector::from_f32(vec![0.9, 0.4, -0.5, 0.2]),
+            Vector::from_f32(vec![0.5]).to_f16().unwrap(),
+        ];
+
+        let result = quantize_batch(&vecs, QuantScheme::QInt4);
+        assert_eq!(result.stats.failed, 1);
+        let quantized: Vec<QuantizedVector> = result.results.into_iter().flatten().collect();
+        for (q, v) in quantized.iter().zip(&vecs) {
+            assert_eq!(*q, quantize_embedding(v, QuantScheme::QInt4).unwrap());
+        }
+
+        let mut rows = vec![0.0f32; 7];
+        dequantize_batch_into(&quantized, &mut rows).unwrap();
+        let expected: Vec<f32> = quantized
+            .iter()
+            .flat_map(|q| crate::dequantize_embedding(q).unwrap().as_f32().unwrap())
+            .collect();
+        assert_eq!(rows, expected);
+
+        assert!(matches!(
+            dequantize_batch_into(&quantized, &mut rows[..6]),
+            Err(QuantError::InvalidDimension(_))
+        ));
+    }
+
     #[test]
     fn test_batch_adaptive() {
         let vecs = vec![
@@ -46,6 +46,16 @@ pub fn quantize_binary(emb: &Vector) -> Result<QuantizedVector, QuantError> {
         .as_f32()
         .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
 
+    let mut quantized = QuantizedVector::default();
+    quantize_binary_into(&values, &mut quantized)?;
+    Ok(quantized)
+}
+
+/// Quantizes `values` to Binary into `out`, reusing its data buffer
+pub(crate) fn quantize_binary_into(
+    values: &[f32],
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
     if values.is_empty() {
         return Err(QuantError::InvalidDimension(
             "Cannot quantize empty vector".to_string(),
@@ -57,7 +67,8 @@ pub fn quantize_binary(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 
     // Pack bits: 8 values per byte
     let num_bytes = values.len().div_ceil(8); // Round up
-    let mut data = Vec::with_capacity(num_bytes);
+    out.data.clear();
+    out.data.reserve(num_bytes);
 
     for chunk in values.chunks(8) {
         let mut byte = 0u8;
@@ -67,19 +78,17 @@ pub fn quantize_binary(emb: &Vector) -> Result<QuantizedVector, QuantError> {
             }
             // Otherwise bit remains 0
         }
-        data.push(byte);
+        out.data.push(byte);
     }
 
     // Store mean in min_val field for use during dequantization
     // Scale and zero_point are not really used for binary quantization
-    Ok(QuantizedVector::new(
-        emb.dim as u32,
-        QuantScheme::Binary,
-        1.0,  // Not used for binary
-        0,    // Not used for binary
-        mean, // Store mean as min_val for reconstruction
-        data,
-    ))
+    out.dim = values.len() as u32;
+    out.scheme = QuantScheme::Binary;
+    out.scale = 1.0; // Not used for binary
+    out.zero_point = 0; // Not used for binary
+    out.min_val = mean; // Store mean as min_val for reconstruction
+    Ok(())
 }
 
 /// Dequantizes a binary quantized vector back to f32
@@ -97,6 +106,16 @@ pub fn quantize_binary(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 /// * `Ok(Vector)` - Restored f32 vector with normalized values
 /// * `Err(QuantError)` - If dequantization fails
 pub fn dequantize_binary(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; qv.dim as usize];
+    dequantize_binary_into(qv, &mut values)?;
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes a Binary vector into `out` (exactly `qv.dim` values)
+pub(crate) fn dequantize_binary_into(
+    qv: &QuantizedVector,
+    out: &mut [f32],
+) -> Result<(), QuantError> {
     if qv.scheme != QuantScheme::Binary {
         return Err(QuantError::InvalidScheme(format!(
             "Expected Binary, got {:?}",
@@ -105,25 +124,21 @@ pub fn dequantize_binary(qv: &QuantizedVector) -> Result<Vector, QuantError> {
     }
 
     let dim = qv.dim as usize;
-    let mut values = Vec::with_capacity(dim);
-
-    for &byte in &qv.data {
-        for i in 0..8 {
-            if values.len() >= dim {
-                break; // Stop if we've reached the target dimension
-            }
-
-            let bit = (byte >> i) & 1;
-            // Convert to +1 or -1 for normalized representation
-            let value = if bit == 1 { 1.0 } else { -1.0 };
-            values.push(value);
-        }
+    if qv.data.len() != dim.div_ceil(8) {
+        return Err(QuantError::DataCorrupted(format!(
+            "Data length mismatch: expected {} bytes, got {}",
+            dim.div_ceil(8),
+            qv.data.len()
+        )));
     }
 
-    // Truncate to exact dimension
-    values.truncate(dim);
+    for (i, value) in out.iter_mut().enumerate() {
+        let bit = (qv.data[i / 8] >> (i % 8)) & 1;
+        // Convert to +1 or -1 for normalized representation
+        *value = if bit == 1 { 1.0 } else { -1.0 };
+    }
 
-    Ok(Vector::from_f32(values))
+    Ok(())
 }
 
 #[cfg(test)]
@@ -33,11 +33,61 @@ pub fn dequantize_embedding(q: &QuantizedVector) -> Result<Vector, QuantError> {
         QuantScheme::QInt4 => crate::qint4::dequantize_qint4(q),
         QuantScheme::Binary => crate::binary::dequantize_binary(q),
         QuantScheme::FP16Passthrough => crate::fp16::dequantize_fp16(q),
+        QuantScheme::QInt2 => crate::qint2::dequantize_qint2(q),
+        QuantScheme::MixedPrecision => crate::mixed::dequantize_mixed(q),
+    }
+}
+
+/// Dequantizes into a caller-provided buffer of exactly `q.dim` values
+///
+/// Unlike [`dequantize_embedding`] this does not allocate, so hot retrieval loops
+/// can reuse one buffer across vectors.
+///
+/// # Example
+/// ```
+/// use lnmp_quant::{dequantize_into, quantize_embedding, QuantScheme};
+/// use lnmp_embedding::Vector;
+///
+/// let quantized = quantize_embedding(&Vector::from_f32(vec![0.1, 0.2, 0.3]), QuantScheme::QInt8).unwrap();
+/// let mut buffer = [0.0f32; 3];
+/// dequantize_into(&quantized, &mut buffer).unwrap();
+/// assert!((buffer[2] - 0.3).abs() < 0.01);
+/// ```
+pub fn dequantize_into(q: &QuantizedVector, out: &mut [f32]) -> Result<(), QuantError> {
+    if q.dim == 0 {
+        return Err(QuantError::InvalidDimension(
+            "Cannot dequantize zero-dimensional vector".to_string(),
+        ));
+    }
+    if out.len() != q.dim as usize {
+        return Err(QuantError::InvalidDimension(format!(
+            "Output buffer holds {} values, expected {}",
+            out.len(),
+            q.dim
+        )));
+    }
+
+    match q.scheme {
+        QuantScheme::QInt8 => dequantize_qint8_into(q, out),
+        QuantScheme::QInt4 => crate::qint4::dequantize_qint4_into(q, out),
+        QuantScheme::Binary => crate::binary::dequantize_binary_into(q, out),
+        QuantScheme::FP16Passthrough => crate::fp16::dequantize_fp16_into(q, out),
+        QuantScheme::QInt2 => crate::qint2::dequantize_qint2_into(q, out),
+        QuantScheme::MixedPrecision => crate::mixed::dequantize_mixed_into(q, out),
     }
 }
 
 /// Dequantizes a QInt8 quantized vector
 fn dequantize_qint8(q: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; q.dim as usize];
+    dequantize_qint8_into(q, &mut values)?;
+
+    // Convert to embedding vector
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes a QInt8 quantized vector into `out` (exactly `q.dim` values)
+fn dequantize_qint8_into(q: &QuantizedVector, out: &mut [f32]) -> Result<(), QuantError> {
     // Validate data length
     if q.data.len() != q.dim as usize {
         return Err(QuantError::DataCorrupted(format!(
@@ -48,17 +98,13 @@ fn dequantize_qint8(q: &QuantizedVector) -> Result<Vector, QuantError> {
     }
 
     // Dequantize each value using the stored min_val
-    let mut values = Vec::with_capacity(q.dim as usize);
-
-    for &quantized_byte in &q.data {
+    for (value, &quantized_byte) in out.iter_mut().zip(&q.data) {
         let quantized = quantized_byte as i8;
         // Reverse the quantization: value = (quantized + 128) * scale + min_val
-        let value = ((quantized as i32 + 128) as f32 * q.scale) + q.min_val;
-        values.push(value);
+        *value = ((quantized as i32 + 128) as f32 * q.scale) + q.min_val;
     }
 
-    // Convert to embedding vector
-    Ok(Vector::from_f32(values))
+    Ok(())
 }
 
 #[cfg(test)]
@@ -111,6 +157,37 @@ mod tests {
         assert!(similarity > 0.99, "Cosine similarity: {}", similarity);
     }
 
+    #[test]
+    fn test_dequantize_into_matches_all_schemes() {
+        let original = Vector::from_f32((0..37).map(|i| (i as f32 * 0.3).sin()).collect());
+        let mut buffer = vec![0.0f32; 37];
+        for scheme in [
+            QuantScheme::QInt8,
+            QuantScheme::QInt4,
+            QuantScheme::QInt2,
+            QuantScheme::MixedPrecision,
+            QuantScheme::Binary,
+            QuantScheme::FP16Passthrough,
+        ] {
+            let quantized = quantize_embedding(&original, scheme).unwrap();
+            dequantize_into(&quantized, &mut buffer).unwrap();
+            let expected = dequantize_embedding(&quantized).unwrap().as_f32().unwrap();
+            assert_eq!(buffer, expected, "{scheme:?}");
+        }
+
+        let quantized = quantize_embedding(&original, QuantScheme::QInt4).unwrap();
+        assert!(matches!(
+            dequantize_into(&quantized, &mut buffer[..36]),
+            Err(QuantError::InvalidDimension(_))
+        ));
+        let mut truncated = quantized.clone();
+        truncated.data.pop();
+        assert!(matches!(
+            dequantize_into(&truncated, &mut buffer),
+            Err(QuantError::DataCorrupted(_))
+        ));
+    }
+
     #[test]
     fn test_dequantize_corrupted_data() {
         // Create a quantized vector with mismatched dimensions
@@ -0,0 +1,313 @@
+//! Delta updates applied directly to quantized vectors.
+//!
+//! Updating a quantized embedding with a [`VectorDelta`] normally means
+//! dequantizing the whole vector, applying the delta and quantizing it again.
+//! [`QuantizedVector::apply_delta`] instead rewrites only the codes of the changed
+//! dimensions, keeping the vector's scale and offset, so the cost depends on the
+//! size of the delta rather than the dimension.
+//!
+//! Because the quantization range is not recomputed, an updated value outside the
+//! range saturates at its nearest edge. The returned count lets callers decide
+//! when a full requantization is worthwhile.
+
+use crate::error::QuantError;
+use crate::scheme::QuantScheme;
+use crate::vector::QuantizedVector;
+use half::f16;
+use lnmp_embedding::VectorDelta;
+use std::collections::BTreeMap;
+
+impl QuantizedVector {
+    /// Applies `delta` in place, re-quantizing only the changed dimensions.
+    ///
+    /// Each changed value is dequantized, shifted by its delta and rounded to the
+    /// nearest code with the vector's existing parameters. Binary vectors take the
+    /// sign of `±1 + delta`. FP16 vectors are updated exactly (to f16 precision).
+    ///
+    /// Returns the number of updated values that fell outside the representable
+    /// range and were clamped. The vector is left unchanged on error.
+    ///
+    /// # Example
+    /// ```
+    /// use lnmp_quant::{dequantize_embedding, quantize_embedding, QuantScheme};
+    /// use lnmp_embedding::{DeltaChange, Vector, VectorDelta};
+    ///
+    /// let emb = Vector::from_f32(vec![0.0, 0.25, 0.5, 1.0]);
+    /// let mut q = quantize_embedding(&emb, QuantScheme::QInt8).unwrap();
+    ///
+    /// let delta = VectorDelta::new(0, vec![DeltaChange { index: 1, delta: 0.5 }]);
+    /// let clamped = q.apply_delta(&delta).unwrap();
+    /// assert_eq!(clamped, 0);
+    ///
+    /// let restored = dequantize_embedding(&q).unwrap().as_f32().unwrap();
+    /// assert!((restored[1] - 0.75).abs() < 0.01);
+    /// ```
+    pub fn apply_delta(&mut self, delta: &VectorDelta) -> Result<usize, QuantError> {
+        if let Some(change) = delta
+            .changes
+            .iter()
+            .find(|change| change.index as u32 >= self.dim)
+        {
+            return Err(QuantError::InvalidDimension(format!(
+                "Delta index {} out of range for dimension {}",
+                change.index, self.dim
+            )));
+        }
+
+        let dim = self.dim as usize;
+        let changes = merged_changes(delta);
+        match self.scheme {
+            QuantScheme::QInt8 => {
+                check_len(self, dim)?;
+                let (scale, min) = (self.scale, self.min_val);
+                Ok(update_codes(&changes, |index, shift| {
+                    let code = (self.data[index] as i8 as i32 + 128) as u8;
+                    let (new_code, clamped) = requantize(code, shift, scale, min, 255);
+                    self.data[index] = (new_code as i32 - 128) as i8 as u8;
+                    clamped
+                }))
+            }
+            QuantScheme::QInt4 => {
+                check_len(self, dim.div_ceil(2))?;
+                let (scale, min) = (self.scale, self.min_val);
+                Ok(update_codes(&changes, |index, shift| {
+                    let offset = if index % 2 == 0 { 4 } else { 0 };
+                    let byte = &mut self.data[index / 2];
+                    let code = (*byte >> offset) & 0x0F;
+                    let (new_code, clamped) = requantize(code, shift, scale, min, 15);
+                    *byte = (*byte & !(0x0F << offset)) | (new_code << offset);
+                    clamped
+                }))
+            }
+            QuantScheme::QInt2 => {
+                let (group_size, params) = crate::qint2::group_params_of(self)?;
+                Ok(update_codes(&changes, |index, shift| {
+                    let (scale, min) = params[index / group_size];
+                    let offset = 6 - 2 * (index % 4);
+                    let byte = &mut self.data[index / 4];
+                    let code = (*byte >> offset) & 0b11;
+                    let (new_code, clamped) = requantize(code, shift, scale, min, 3);
+                    *byte = (*byte & !(0b11 << offset)) | (new_code << offset);
+                    clamped
+                }))
+            }
+            QuantScheme::MixedPrecision => {
+                let layout = crate::mixed::Layout::of(self)?;
+                let (scale, min) = (self.scale, self.min_val);
+                Ok(update_codes(&changes, |index, shift| {
+                    match layout.slot(&self.data, index) {
+                        crate::mixed::Slot::Fp16(at) => {
+                            update_fp16(&mut self.data[at..at + 2], shift)
+                        }
+                        crate::mixed::Slot::Code(position) => {
+                            let code = layout.code(&self.data, position);
+                            let (new_code, clamped) = requantize(code, shift, scale, min, 15);
+                            layout.set_code(&mut self.data, position, new_code);
+                            clamped
+                        }
+                    }
+                }))
+            }
+            QuantScheme::Binary => {
+                check_len(self, dim.div_ceil(8))?;
+                for (&index, &shift) in &changes {
+                    let byte = &mut self.data[index / 8];
+                    let value = if (*byte >> (index % 8)) & 1 == 1 {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    if value + shift >= 0.0 {
+                        *byte |= 1 << (index % 8);
+                    } else {
+                        *byte &= !(1 << (index % 8));
+                    }
+                }
+                Ok(0)
+            }
+            QuantScheme::FP16Passthrough => {
+                check_len(self, dim * 2)?;
+                Ok(update_codes(&changes, |index, shift| {
+                    update_fp16(&mut self.data[index * 2..index * 2 + 2], shift)
+                }))
+            }
+        }
+    }
+}
+
+/// Delta per changed index; repeated indices are summed so they round only once.
+fn merged_changes(delta: &VectorDelta) -> BTreeMap<usize, f32> {
+    let mut merged = BTreeMap::new();
+    for change in &delta.changes {
+        *merged.entry(change.index as usize).or_insert(0.0) += change.delta;
+    }
+    merged
+}
+
+/// Runs `update(index, shift)` for every change and counts clamped results.
+fn update_codes<F>(changes: &BTreeMap<usize, f32>, mut update: F) -> usize
+where
+    F: FnMut(usize, f32) -> bool,
+{
+    changes
+        .iter()
+        .filter(|&(&index, &shift)| update(index, shift))
+        .count()
+}
+
+/// Adds `shift` to the FP16 value in `bytes`; true if it overflowed to infinity.
+fn update_fp16(bytes: &mut [u8], shift: f32) -> bool {
+    let value = f16::from_le_bytes([bytes[0], bytes[1]]).to_f32();
+    let updated = f16::from_f32(value + shift);
+    bytes.copy_from_slice(&updated.to_le_bytes());
+    updated.is_infinite() && (value + shift).is_finite()
+}
+
+/// New code for `code * scale + min + shift`, and whether it was clamped.
+fn requantize(code: u8, shift: f32, scale: f32, min: f32, max_code: u8) -> (u8, bool) {
+    let value = code as f32 * scale + min + shift;
+    let normalized = ((value - min) / scale).round();
+    if normalized.is_nan() {
+        return (code, false);
+    }
+    let clamped = normalized.clamp(0.0, max_code as f32);
+    (clamped as u8, clamped != normalized)
+}
+
+fn check_len(q: &QuantizedVector, expected: usize) -> Result<(), QuantError> {
+    if q.data.len() != expected {
+        return Err(QuantError::DataCorrupted(format!(
+            "Data length mismatch: expected {} bytes, got {}",
+            expected,
+            q.data.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::dequantize_embedding;
+    use crate::encode::quantize_embedding;
+    use crate::qint2::{quantize_qint2_with, QInt2Config};
+    use lnmp_embedding::{DeltaChange, Vector};
+
+    fn vector(dim: usize) -> Vector {
+        Vector::from_f32(
+            (0..dim)
+                .map(|i| ((i * 37) % 29) as f32 / 14.0 - 1.0)
+                .collect(),
+        )
+    }
+
+    fn delta(changes: &[(u16, f32)]) -> VectorDelta {
+        VectorDelta::new(
+            0,
+            changes
+                .iter()
+                .map(|&(index, delta)| DeltaChange { index, delta })
+                .collect(),
+        )
+    }
+
+    /// Applies `changes` in the quantized domain and checks every value against
+    /// dequantize → apply, within half a quantization step.
+    fn assert_matches_dequantized(mut q: QuantizedVector, changes: &[(u16, f32)], step: f32) {
+        let delta = delta(changes);
+        let expected = delta
+            .apply(&dequantize_embedding(&q).unwrap())
+            .unwrap()
+            .as_f32()
+            .unwrap();
+        assert_eq!(q.apply_delta(&delta).unwrap(), 0);
+        let actual = dequantize_embedding(&q).unwrap().as_f32().unwrap();
+        for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+            assert!((e - a).abs() <= step / 2.0 + 1e-5, "dim {i}: {e} vs {a}");
+        }
+    }
+
+    #[test]
+    fn test_matches_dequantized_update() {
+        let emb = vector(101);
+        let changes = [(0, 0.3), (7, -0.25), (100, 0.1), (7, 0.05)];
+
+        for scheme in [QuantScheme::QInt8, QuantScheme::QInt4, QuantScheme::QInt2] {
+            let q = quantize_embedding(&emb, scheme).unwrap();
+            let step = q.scale;
+            assert_matches_dequantized(q, &changes, step);
+        }
+
+        let grouped = quantize_qint2_with(&emb, &QInt2Config::new().with_group_size(16)).unwrap();
+        let step = crate::qint2::group_params_of(&grouped)
+            .unwrap()
+            .1
+            .iter()
+            .map(|p| p.0)
+            .fold(0.0, f32::max);
+        assert_matches_dequantized(grouped, &changes, step);
+
+        let fp16 = quantize_embedding(&emb, QuantScheme::FP16Passthrough).unwrap();
+        assert_matches_dequantized(fp16, &changes, 1e-3);
+
+        // Dim 0 is the largest-magnitude value and stays at FP16
+        let mixed = quantize_embedding(&emb, QuantScheme::MixedPrecision).unwrap();
+        assert!(crate::mixed::critical_dims(&mixed).unwrap().contains(&0));
+        let step = mixed.scale;
+        assert_matches_dequantized(mixed, &changes, step);
+    }
+
+    #[test]
+    fn test_only_changed_codes_are_rewritten() {
+        let emb = vector(64);
+        let original = quantize_embedding(&emb, QuantScheme::QInt4).unwrap();
+        let mut q = original.clone();
+        q.apply_delta(&delta(&[(10, 0.5)])).unwrap();
+
+        for (i, (a, b)) in original.data.iter().zip(&q.data).enumerate() {
+            if i != 5 {
+                assert_eq!(a, b);
+            }
+        }
+        // The other nibble of byte 5 is untouched
+        assert_eq!(original.data[5] & 0x0F, q.data[5] & 0x0F);
+        assert_eq!((q.scale, q.min_val), (original.scale, original.min_val));
+    }
+
+    #[test]
+    fn test_saturation_and_binary() {
+        let emb = vector(16);
+        let mut q = quantize_embedding(&emb, QuantScheme::QInt8).unwrap();
+        assert_eq!(q.apply_delta(&delta(&[(0, 10.0), (1, -10.0)])).unwrap(), 2);
+        let restored = dequantize_embedding(&q).unwrap().as_f32().unwrap();
+        assert!((restored[0] - (q.min_val + 255.0 * q.scale)).abs() < 1e-5);
+        assert!((restored[1] - q.min_val).abs() < 1e-5);
+
+        let mut binary = quantize_embedding(&emb, QuantScheme::Binary).unwrap();
+        let before = dequantize_embedding(&binary).unwrap().as_f32().unwrap();
+        let flip = if before[3] > 0.0 { -3.0 } else { 3.0 };
+        binary
+            .apply_delta(&delta(&[(3, flip), (4, 0.5 * before[4])]))
+            .unwrap();
+        let after = dequantize_embedding(&binary).unwrap().as_f32().unwrap();
+        assert_eq!(after[3], -before[3]);
+        assert_eq!(after[4], before[4]);
+    }
+
+    #[test]
+    fn test_errors_leave_vector_unchanged() {
+        let mut q = quantize_embedding(&vector(8), QuantScheme::QInt8).unwrap();
+        let original = q.clone();
+        assert!(matches!(
+            q.apply_delta(&delta(&[(0, 0.1), (8, 0.1)])),
+            Err(QuantError::InvalidDimension(_))
+        ));
+        assert_eq!(q, original);
+
+        q.data.pop();
+        assert!(matches!(
+            q.apply_delta(&delta(&[(0, 0.1)])),
+            Err(QuantError::DataCorrupted(_))
+        ));
+    }
+}
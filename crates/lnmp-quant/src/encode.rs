@@ -7,7 +7,7 @@ use lnmp_embedding::Vector;
 /// Quantizes an embedding vector using the specified quantization scheme
 ///
 /// # Arguments
-/// * `emb` - The embedding vector to quantize (must be F32 type)
+/// * `emb` - The embedding vector to quantize (F32, or F16 for `FP16Passthrough`)
 /// * `scheme` - The quantization scheme to use
 ///
 /// # Returns
@@ -26,8 +26,10 @@ pub fn quantize_embedding(
     emb: &Vector,
     scheme: QuantScheme,
 ) -> Result<QuantizedVector, QuantError> {
-    // Validate input
-    if emb.dtype != lnmp_embedding::EmbeddingType::F32 {
+    // Validate input; F16 embeddings are already in the FP16 scheme's layout
+    let f16_passthrough =
+        emb.dtype == lnmp_embedding::EmbeddingType::F16 && scheme == QuantScheme::FP16Passthrough;
+    if emb.dtype != lnmp_embedding::EmbeddingType::F32 && !f16_passthrough {
         return Err(QuantError::EncodingFailed(
             "Only F32 embeddings are currently supported for quantization".to_string(),
         ));
@@ -44,6 +46,47 @@ pub fn quantize_embedding(
         QuantScheme::QInt4 => crate::qint4::quantize_qint4(emb),
         QuantScheme::Binary => crate::binary::quantize_binary(emb),
         QuantScheme::FP16Passthrough => crate::fp16::quantize_fp16(emb),
+        QuantScheme::QInt2 => crate::qint2::quantize_qint2(emb),
+        QuantScheme::MixedPrecision => crate::mixed::quantize_mixed(emb),
+    }
+}
+
+/// Quantizes `values` into `out`, reusing its data buffer
+///
+/// Once `out.data` has grown to the required capacity this does not allocate, so
+/// hot loops can quantize many vectors through one output. QInt2 and
+/// MixedPrecision use their default configurations.
+///
+/// # Example
+/// ```
+/// use lnmp_quant::{quantize_into, QuantScheme, QuantizedVector};
+///
+/// let mut out = QuantizedVector::default();
+/// for values in [[0.1, -0.2, 0.3], [0.4, 0.5, -0.6]] {
+///     quantize_into(&values, QuantScheme::QInt8, &mut out).unwrap();
+///     assert_eq!(out.data.len(), 3);
+/// }
+/// ```
+pub fn quantize_into(
+    values: &[f32],
+    scheme: QuantScheme,
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
+    if values.is_empty() {
+        return Err(QuantError::InvalidDimension(
+            "Cannot quantize zero-dimensional vector".to_string(),
+        ));
+    }
+
+    match scheme {
+        QuantScheme::QInt8 => quantize_qint8_into(values, out),
+        QuantScheme::QInt4 => crate::qint4::quantize_qint4_into(values, out),
+        QuantScheme::Binary => crate::binary::quantize_binary_into(values, out),
+        QuantScheme::FP16Passthrough => crate::fp16::quantize_fp16_into(values, out),
+        QuantScheme::QInt2 => crate::qint2::quantize_qint2_into(values, &Default::default(), out),
+        QuantScheme::MixedPrecision => {
+            crate::mixed::quantize_mixed_into(values, &Default::default(), out)
+        }
     }
 }
 
@@ -54,6 +97,13 @@ fn quantize_qint8(emb: &Vector) -> Result<QuantizedVector, QuantError> {
         .as_f32()
         .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
 
+    let mut quantized = QuantizedVector::default();
+    quantize_qint8_into(&values, &mut quantized)?;
+    Ok(quantized)
+}
+
+/// Quantizes `values` to QInt8 into `out`, reusing its data buffer
+fn quantize_qint8_into(values: &[f32], out: &mut QuantizedVector) -> Result<(), QuantError> {
     if values.is_empty() {
         return Err(QuantError::InvalidDimension(
             "Empty embedding vector".to_string(),
@@ -86,8 +136,9 @@ fn quantize_qint8(emb: &Vector) -> Result<QuantizedVector, QuantError> {
         (scale, zero_point)
     };
 
-    // Pre-allocate output vector with exact capacity (memory optimization)
-    let mut quantized_data = Vec::with_capacity(values.len());
+    // Reuse the output buffer with exact capacity (memory optimization)
+    out.data.clear();
+    out.data.reserve(values.len());
 
     // Cache inverse scale to avoid repeated division (performance optimization)
     let inv_scale = if scale.abs() > 1e-10 {
@@ -97,26 +148,24 @@ fn quantize_qint8(emb: &Vector) -> Result<QuantizedVector, QuantError> {
     };
 
     // Quantize each value
-    for &value in &values {
+    for &value in values {
         // Optimized: multiply by inv_scale instead of dividing by scale
         let normalized = (value - min_val) * inv_scale;
         let quantized = (normalized as i32 - 128).clamp(-128, 127) as i8;
-        quantized_data.push(quantized as u8);
+        out.data.push(quantized as u8);
     }
 
     // Calculate metrics (approximate loss ratio based on quantization error)
-    let loss_ratio = calculate_loss_ratio(&values, &quantized_data, scale, min_val);
+    let loss_ratio = calculate_loss_ratio(values, &out.data, scale, min_val);
 
     let _metrics = QuantMetrics::new(min_val, max_val, loss_ratio);
 
-    Ok(QuantizedVector::new(
-        emb.dim as u32,
-        QuantScheme::QInt8,
-        scale,
-        zero_point,
-        min_val,
-        quantized_data,
-    ))
+    out.dim = values.len() as u32;
+    out.scheme = QuantScheme::QInt8;
+    out.scale = scale;
+    out.zero_point = zero_point;
+    out.min_val = min_val;
+    Ok(())
 }
 
 /// Calculates approximate information loss ratio
@@ -185,6 +234,33 @@ mod tests {
         // All values should be quantized to the same value
     }
 
+    #[test]
+    fn test_quantize_into_reuses_buffer() {
+        let first: Vec<f32> = (0..64).map(|i| (i as f32 * 0.1).cos()).collect();
+        let second: Vec<f32> = (0..64).map(|i| (i as f32 * 0.2).sin()).collect();
+
+        for scheme in [
+            QuantScheme::QInt8,
+            QuantScheme::QInt4,
+            QuantScheme::QInt2,
+            QuantScheme::MixedPrecision,
+            QuantScheme::Binary,
+            QuantScheme::FP16Passthrough,
+        ] {
+            let mut out = QuantizedVector::default();
+            quantize_into(&first, scheme, &mut out).unwrap();
+            let buffer = out.data.as_ptr();
+            quantize_into(&second, scheme, &mut out).unwrap();
+
+            assert_eq!(out.data.as_ptr(), buffer, "{scheme:?} reallocated");
+            let expected = quantize_embedding(&Vector::from_f32(second.clone()), scheme).unwrap();
+            assert_eq!(out, expected, "{scheme:?}");
+        }
+
+        let mut out = QuantizedVector::default();
+        assert!(quantize_into(&[], QuantScheme::QInt8, &mut out).is_err());
+    }
+
     #[test]
     fn test_quantize_empty_fails() {
         let emb = Vector::from_f32(vec![]);
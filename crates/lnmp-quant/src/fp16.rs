@@ -16,6 +16,9 @@ use lnmp_embedding::Vector;
 /// with minimal accuracy loss. This is ideal when high accuracy is needed
 /// but storage/bandwidth is constrained.
 ///
+/// The data has the same layout as an F16 [`Vector`]: F16 embeddings are copied
+/// as is, and [`fp16_to_embedding`] converts back without upcasting.
+///
 /// # Arguments
 /// * `emb` - The embedding vector to quantize
 ///
@@ -34,31 +37,26 @@ use lnmp_embedding::Vector;
 /// ```
 pub fn quantize_fp16(emb: &Vector) -> Result<QuantizedVector, QuantError> {
     // Validate input
-    if emb.dtype != lnmp_embedding::EmbeddingType::F32 {
+    if !matches!(
+        emb.dtype,
+        lnmp_embedding::EmbeddingType::F32 | lnmp_embedding::EmbeddingType::F16
+    ) {
         return Err(QuantError::EncodingFailed(
-            "Only F32 embeddings are supported for FP16 quantization".to_string(),
+            "Only F32 and F16 embeddings are supported for FP16 quantization".to_string(),
         ));
     }
 
-    let values = emb
-        .as_f32()
-        .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
-
-    if values.is_empty() {
+    if emb.dim == 0 {
         return Err(QuantError::InvalidDimension(
             "Cannot quantize empty vector".to_string(),
         ));
     }
 
     // Convert each f32 to f16 (2 bytes each)
-    // Pre-allocate for performance
-    let mut data = Vec::with_capacity(values.len() * 2);
-
-    for val in values.iter() {
-        let fp16 = f16::from_f32(*val);
-        let bytes = fp16.to_le_bytes();
-        data.extend_from_slice(&bytes);
-    }
+    let data = emb
+        .to_f16()
+        .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F16: {}", e)))?
+        .data;
 
     // FP16 doesn't use scale/zero_point/min_val the same way
     // We set them to defaults since the data is self-contained
@@ -72,6 +70,32 @@ pub fn quantize_fp16(emb: &Vector) -> Result<QuantizedVector, QuantError> {
     ))
 }
 
+/// Quantizes `values` to FP16 into `out`, reusing its data buffer
+pub(crate) fn quantize_fp16_into(
+    values: &[f32],
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
+    if values.is_empty() {
+        return Err(QuantError::InvalidDimension(
+            "Cannot quantize empty vector".to_string(),
+        ));
+    }
+
+    out.data.clear();
+    out.data.reserve(values.len() * 2);
+    for &value in values {
+        out.data
+            .extend_from_slice(&f16::from_f32(value).to_le_bytes());
+    }
+
+    out.dim = values.len() as u32;
+    out.scheme = QuantScheme::FP16Passthrough;
+    out.scale = 1.0; // Not used for FP16
+    out.zero_point = 0; // Not used for FP16
+    out.min_val = 0.0; // Not used for FP16
+    Ok(())
+}
+
 /// Dequantizes an FP16 quantized vector back to f32
 ///
 /// Converts each 16-bit half-precision float back to 32-bit float.
@@ -83,6 +107,16 @@ pub fn quantize_fp16(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 /// * `Ok(Vector)` - Restored f32 vector
 /// * `Err(QuantError)` - If dequantization fails
 pub fn dequantize_fp16(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; qv.dim as usize];
+    dequantize_fp16_into(qv, &mut values)?;
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes an FP16 vector into `out` (exactly `qv.dim` values)
+pub(crate) fn dequantize_fp16_into(
+    qv: &QuantizedVector,
+    out: &mut [f32],
+) -> Result<(), QuantError> {
     if qv.scheme != QuantScheme::FP16Passthrough {
         return Err(QuantError::InvalidScheme(format!(
             "Expected FP16Passthrough, got {:?}",
@@ -102,16 +136,40 @@ pub fn dequantize_fp16(qv: &QuantizedVector) -> Result<Vector, QuantError> {
         )));
     }
 
-    let mut values = Vec::with_capacity(dim);
-
     // Read 2-byte chunks and convert from f16 to f32
-    for chunk in qv.data.chunks_exact(2) {
-        let bytes = [chunk[0], chunk[1]];
-        let fp16 = f16::from_le_bytes(bytes);
-        values.push(fp16.to_f32());
+    for (value, chunk) in out.iter_mut().zip(qv.data.chunks_exact(2)) {
+        *value = f16::from_le_bytes([chunk[0], chunk[1]]).to_f32();
     }
 
-    Ok(Vector::from_f32(values))
+    Ok(())
+}
+
+/// Converts an FP16 quantized vector to an F16 embedding, keeping its bytes
+///
+/// Unlike [`dequantize_fp16`], the result stays at 2 bytes per value; F16
+/// similarity upcasts element by element.
+pub fn fp16_to_embedding(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    if qv.scheme != QuantScheme::FP16Passthrough {
+        return Err(QuantError::InvalidScheme(format!(
+            "Expected FP16Passthrough, got {:?}",
+            qv.scheme
+        )));
+    }
+    if qv.data.len() != qv.dim as usize * 2 {
+        return Err(QuantError::DataCorrupted(format!(
+            "Expected {} bytes for {} dimensions, got {}",
+            qv.dim as usize * 2,
+            qv.dim,
+            qv.data.len()
+        )));
+    }
+    let dim = u16::try_from(qv.dim)
+        .map_err(|_| QuantError::InvalidDimension(format!("{} exceeds u16", qv.dim)))?;
+    Ok(Vector::new(
+        lnmp_embedding::EmbeddingType::F16,
+        dim,
+        qv.data.clone(),
+    ))
 }
 
 #[cfg(test)]
@@ -119,6 +177,21 @@ mod tests {
     use super::*;
     use lnmp_embedding::SimilarityMetric;
 
+    #[test]
+    fn test_fp16_matches_f16_embeddings() {
+        let vec = Vector::from_f32(vec![0.1, -0.2, 0.3, -0.4]);
+        let half = vec.to_f16().unwrap();
+
+        let quantized = quantize_fp16(&vec).unwrap();
+        assert_eq!(quantized.data, half.data);
+        assert_eq!(fp16_to_embedding(&quantized).unwrap(), half);
+
+        // F16 embeddings pass straight through
+        let from_half = crate::quantize_embedding(&half, QuantScheme::FP16Passthrough).unwrap();
+        assert_eq!(from_half, quantized);
+        assert!(crate::quantize_embedding(&half, QuantScheme::QInt8).is_err());
+    }
+
     #[test]
     fn test_fp16_basic() {
         let vec = Vector::from_f32(vec![0.1, -0.2, 0.3, -0.4]);
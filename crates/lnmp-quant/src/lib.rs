@@ -6,6 +6,7 @@
 //! while maintaining high semantic accuracy. Multiple schemes available:
 //!   - QInt8: 4x compression with ~99% accuracy
 //!   - QInt4: 8x compression with ~95-97% accuracy  
+//!   - QInt2: 16x compression with ~90-95% accuracy
 //!   - Binary: 32x compression with ~85-90% similarity
 //!
 //! ## Quick Start
@@ -31,6 +32,8 @@
 //!
 //! - **QInt8**: 8-bit signed integer quantization (4x compression, ~99% accuracy)
 //! - **QInt4**: 4-bit packed quantization (8x compression, ~95-97% accuracy)
+//! - **QInt2**: 2-bit packed quantization (16x compression, ~90-95% accuracy)
+//! - **MixedPrecision**: critical dimensions at FP16, the rest at QInt4
 //! - **Binary**: 1-bit sign-based quantization (32x compression, ~85-90% similarity)
 //! - **FP16**: Half-precision float (2x compression, ~99.9% accuracy, near-lossless)
 
@@ -38,20 +41,27 @@ pub mod adaptive;
 pub mod batch;
 pub mod binary;
 pub mod decode;
+pub mod delta;
 pub mod encode;
 pub mod error;
 pub mod fp16;
 pub mod metrics;
+pub mod mixed;
+pub mod qint2;
 pub mod qint4;
 pub mod scheme;
+pub mod similarity;
 pub mod vector;
 
 // Re-export main types and functions
-pub use decode::dequantize_embedding;
-pub use encode::quantize_embedding;
+pub use decode::{dequantize_embedding, dequantize_into};
+pub use encode::{quantize_embedding, quantize_into};
 pub use error::QuantError;
-pub use metrics::QuantMetrics;
+pub use metrics::{AccuracyMetrics, ErrorReport, QuantMetrics};
+pub use mixed::{CriticalDims, MixedPrecisionConfig};
+pub use qint2::{QInt2Config, RoundingMode};
 pub use scheme::QuantScheme;
+pub use similarity::{hamming_distance, quantized_similarity};
 pub use vector::QuantizedVector;
 
 #[cfg(test)]
@@ -1,3 +1,7 @@
+use crate::decode::dequantize_embedding;
+use crate::error::QuantError;
+use crate::vector::QuantizedVector;
+use lnmp_embedding::{SimilarityMetric, Vector};
 use serde::{Deserialize, Serialize};
 
 /// Metrics and debug information for quantization operations
@@ -28,6 +32,155 @@ impl QuantMetrics {
     pub fn dynamic_range(&self) -> f32 {
         self.original_max - self.original_min
     }
+
+    /// Per-dimension error report of `quantized` against `original`
+    ///
+    /// Absolute errors are bucketed into `bins` equal-width bins over
+    /// `[0, max error]`, and the `worst_k` dimensions with the largest error are
+    /// listed, worst first.
+    ///
+    /// # Example
+    /// ```
+    /// use lnmp_quant::{quantize_embedding, QuantMetrics, QuantScheme};
+    /// use lnmp_embedding::Vector;
+    ///
+    /// let original = Vector::from_f32((0..64).map(|i| (i as f32 * 0.2).sin()).collect());
+    /// let quantized = quantize_embedding(&original, QuantScheme::QInt4).unwrap();
+    /// let report = QuantMetrics::error_report(&original, &quantized, 8, 3).unwrap();
+    ///
+    /// assert_eq!(report.histogram.iter().sum::<usize>(), 64);
+    /// assert_eq!(report.worst_dims.len(), 3);
+    /// assert!(report.cosine_degradation < 0.05);
+    /// ```
+    pub fn error_report(
+        original: &Vector,
+        quantized: &QuantizedVector,
+        bins: usize,
+        worst_k: usize,
+    ) -> Result<ErrorReport, QuantError> {
+        if bins == 0 {
+            return Err(QuantError::InvalidDimension(
+                "Error histogram needs at least one bin".to_string(),
+            ));
+        }
+        if original.dim as u32 != quantized.dim {
+            return Err(QuantError::InvalidDimension(format!(
+                "Dimension mismatch: {} vs {}",
+                original.dim, quantized.dim
+            )));
+        }
+
+        let accuracy = AccuracyMetrics::measure(original, quantized)?;
+        let original = original.as_f32().map_err(QuantError::DecodingFailed)?;
+        let restored = dequantize_embedding(quantized)?
+            .as_f32()
+            .map_err(QuantError::DecodingFailed)?;
+        let errors: Vec<f32> = original
+            .iter()
+            .zip(&restored)
+            .map(|(a, b)| (a - b).abs())
+            .collect();
+
+        let max_error = accuracy.max_abs_error;
+        let bin_width = max_error / bins as f32;
+        let mut histogram = vec![0; bins];
+        for &error in &errors {
+            let bin = if bin_width > 0.0 {
+                ((error / bin_width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            histogram[bin] += 1;
+        }
+
+        let mut worst_dims: Vec<(usize, f32)> = errors.into_iter().enumerate().collect();
+        worst_dims.sort_by(|a, b| b.1.total_cmp(&a.1));
+        worst_dims.truncate(worst_k);
+
+        Ok(ErrorReport {
+            histogram,
+            bin_width,
+            worst_dims,
+            cosine_degradation: 1.0 - accuracy.cosine_similarity,
+        })
+    }
+}
+
+/// Per-dimension error breakdown produced by [`QuantMetrics::error_report`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Number of dimensions per absolute-error bin; bin `i` covers
+    /// `[i * bin_width, (i + 1) * bin_width)`, the last bin includes the maximum
+    pub histogram: Vec<usize>,
+
+    /// Width of each histogram bin (0.0 for a lossless reconstruction)
+    pub bin_width: f32,
+
+    /// `(dimension, absolute error)` of the worst dimensions, largest error first
+    pub worst_dims: Vec<(usize, f32)>,
+
+    /// `1 - cosine similarity` between original and dequantized vectors
+    pub cosine_degradation: f32,
+}
+
+/// Reconstruction accuracy of a quantized vector against its original
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccuracyMetrics {
+    /// Cosine similarity between original and dequantized vectors
+    pub cosine_similarity: f32,
+
+    /// Mean squared error per value
+    pub mse: f32,
+
+    /// Largest absolute error of any single value
+    pub max_abs_error: f32,
+
+    /// Sum of absolute errors relative to the sum of absolute original values
+    pub loss_ratio: f32,
+}
+
+impl AccuracyMetrics {
+    /// Dequantizes `quantized` and compares it with `original`
+    ///
+    /// # Example
+    /// ```
+    /// use lnmp_quant::{quantize_embedding, AccuracyMetrics, QuantScheme};
+    /// use lnmp_embedding::Vector;
+    ///
+    /// let original = Vector::from_f32(vec![0.12, -0.45, 0.33, 0.8]);
+    /// let quantized = quantize_embedding(&original, QuantScheme::QInt2).unwrap();
+    /// let metrics = AccuracyMetrics::measure(&original, &quantized).unwrap();
+    /// assert!(metrics.cosine_similarity > 0.9);
+    /// ```
+    pub fn measure(original: &Vector, quantized: &QuantizedVector) -> Result<Self, QuantError> {
+        let restored = dequantize_embedding(quantized)?;
+        let cosine_similarity = original
+            .similarity(&restored, SimilarityMetric::Cosine)
+            .map_err(QuantError::DecodingFailed)?;
+        let original = original.as_f32().map_err(QuantError::DecodingFailed)?;
+        let restored = restored.as_f32().map_err(QuantError::DecodingFailed)?;
+
+        let (mut squared, mut max_abs_error, mut abs_error, mut abs_total) =
+            (0.0, 0.0f32, 0.0, 0.0);
+        for (a, b) in original.iter().zip(&restored) {
+            let error = (a - b).abs();
+            squared += error * error;
+            max_abs_error = max_abs_error.max(error);
+            abs_error += error;
+            abs_total += a.abs();
+        }
+
+        Ok(Self {
+            cosine_similarity,
+            mse: squared / original.len() as f32,
+            max_abs_error,
+            loss_ratio: if abs_total > 0.0 {
+                abs_error / abs_total
+            } else {
+                0.0
+            },
+        })
+    }
 }
 
 #[cfg(test)]
@@ -43,4 +196,44 @@ mod tests {
         assert_eq!(metrics.loss_ratio, 0.05);
         assert_eq!(metrics.dynamic_range(), 2.0);
     }
+
+    #[test]
+    fn test_error_report() {
+        use crate::encode::quantize_embedding;
+        use crate::scheme::QuantScheme;
+
+        // A single outlier stretches the QInt4 range; it lands in the worst list
+        let mut values: Vec<f32> = (0..100).map(|i| (i as f32 * 0.1).sin() * 0.1).collect();
+        values[42] = 3.0;
+        let original = Vector::from_f32(values);
+
+        let qint4 = quantize_embedding(&original, QuantScheme::QInt4).unwrap();
+        let report = QuantMetrics::error_report(&original, &qint4, 4, 5).unwrap();
+        assert_eq!(report.histogram.len(), 4);
+        assert_eq!(report.histogram.iter().sum::<usize>(), 100);
+        assert_eq!(report.worst_dims.len(), 5);
+        assert!(report.worst_dims.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!((report.bin_width * 4.0 - report.worst_dims[0].1).abs() < 1e-6);
+
+        let binary = quantize_embedding(&original, QuantScheme::Binary).unwrap();
+        let binary_report = QuantMetrics::error_report(&original, &binary, 4, 1).unwrap();
+        assert!(binary_report.cosine_degradation > report.cosine_degradation);
+        // Binary reconstructs ±1, so the outlier has the largest error
+        assert_eq!(binary_report.worst_dims[0].0, 42);
+
+        // Lossless reconstruction puts everything in the first bin
+        let exact = Vector::from_f32(vec![0.0, 1.0, 2.0, 3.0]);
+        let qint2 = quantize_embedding(&exact, QuantScheme::QInt2).unwrap();
+        let report = QuantMetrics::error_report(&exact, &qint2, 3, 10).unwrap();
+        assert_eq!(report.histogram, vec![4, 0, 0]);
+        assert_eq!(report.bin_width, 0.0);
+        assert_eq!(report.worst_dims.len(), 4);
+
+        assert!(QuantMetrics::error_report(&exact, &qint2, 0, 1).is_err());
+        let shorter = Vector::from_f32(vec![0.0, 1.0]);
+        assert!(matches!(
+            QuantMetrics::error_report(&shorter, &qint2, 2, 1),
+            Err(QuantError::InvalidDimension(_))
+        ));
+    }
 }
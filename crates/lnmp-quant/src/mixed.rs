@@ -0,0 +1,490 @@
+//! Mixed precision: critical dimensions in FP16, the rest in QInt4
+//!
+//! A few high-magnitude or high-variance dimensions often carry most of an
+//! embedding's signal, and a single outlier stretches the QInt4 range for all
+//! others. This scheme keeps a configurable set of critical dimensions at FP16
+//! and quantizes the remaining ones to 4 bits, using a range computed over the
+//! non-critical dimensions only.
+//!
+//! ## Data layout
+//!
+//! `data` holds a mask of `dim.div_ceil(8)` bytes (bit `i % 8` of byte `i / 8`
+//! set for critical dimensions), then one little-endian FP16 value per critical
+//! dimension, then the remaining dimensions as nibble-packed QInt4 codes (first
+//! value in the high nibble). `scale` and `min_val` describe the QInt4 part.
+
+use crate::error::QuantError;
+use crate::scheme::QuantScheme;
+use crate::vector::QuantizedVector;
+use half::f16;
+use lnmp_embedding::Vector;
+
+/// Share of dimensions kept at FP16 by default
+const DEFAULT_FRACTION: f32 = 1.0 / 16.0;
+
+/// How the critical (FP16) dimensions are chosen
+#[derive(Debug, Clone, PartialEq)]
+pub enum CriticalDims {
+    /// The given fraction of dimensions with the largest absolute value, chosen
+    /// per vector (rounded up)
+    TopFraction(f32),
+    /// An explicit mask, `true` for critical dimensions; its length must match the
+    /// vector dimension
+    Mask(Vec<bool>),
+}
+
+impl Default for CriticalDims {
+    fn default() -> Self {
+        CriticalDims::TopFraction(DEFAULT_FRACTION)
+    }
+}
+
+/// Configuration for mixed-precision quantization
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MixedPrecisionConfig {
+    /// Selection of the dimensions kept at FP16
+    pub critical: CriticalDims,
+}
+
+impl MixedPrecisionConfig {
+    /// Creates the default configuration (top 1/16 of dimensions by magnitude)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the given fraction of largest-magnitude dimensions at FP16
+    pub fn with_top_fraction(mut self, fraction: f32) -> Self {
+        self.critical = CriticalDims::TopFraction(fraction);
+        self
+    }
+
+    /// Keeps the dimensions set in `mask` at FP16
+    pub fn with_mask(mut self, mask: Vec<bool>) -> Self {
+        self.critical = CriticalDims::Mask(mask);
+        self
+    }
+
+    /// Marks the `count` dimensions with the highest variance across `samples`
+    /// as critical
+    ///
+    /// # Example
+    /// ```
+    /// use lnmp_quant::mixed::{quantize_mixed_with, MixedPrecisionConfig};
+    /// use lnmp_embedding::Vector;
+    ///
+    /// let samples: Vec<Vector> = (0..8)
+    ///     .map(|i| Vector::from_f32(vec![0.1, i as f32, 0.2, -0.1]))
+    ///     .collect();
+    /// let config = MixedPrecisionConfig::from_variance(&samples, 1).unwrap();
+    /// let quantized = quantize_mixed_with(&samples[3], &config).unwrap();
+    /// assert_eq!(lnmp_quant::mixed::critical_dims(&quantized).unwrap(), vec![1]);
+    /// ```
+    pub fn from_variance(samples: &[Vector], count: usize) -> Result<Self, QuantError> {
+        let Some(first) = samples.first() else {
+            return Err(QuantError::InvalidDimension(
+                "Variance needs at least one sample".to_string(),
+            ));
+        };
+        let dim = first.dim as usize;
+        let mut sum = vec![0.0f64; dim];
+        let mut sum_sq = vec![0.0f64; dim];
+        for sample in samples {
+            if sample.dim as usize != dim {
+                return Err(QuantError::InvalidDimension(format!(
+                    "Sample dimension mismatch: {} vs {}",
+                    sample.dim, dim
+                )));
+            }
+            let values = sample.as_f32().map_err(|e| {
+                QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e))
+            })?;
+            for (i, &v) in values.iter().enumerate() {
+                sum[i] += v as f64;
+                sum_sq[i] += v as f64 * v as f64;
+            }
+        }
+
+        let n = samples.len() as f64;
+        let variance: Vec<f32> = sum
+            .iter()
+            .zip(&sum_sq)
+            .map(|(s, sq)| (sq / n - (s / n) * (s / n)) as f32)
+            .collect();
+        Ok(Self::new().with_mask(top_k_mask(&variance, count)))
+    }
+}
+
+/// Quantizes an embedding with the default mixed-precision configuration
+///
+/// # Example
+/// ```
+/// use lnmp_quant::mixed::quantize_mixed;
+/// use lnmp_embedding::Vector;
+///
+/// let emb = Vector::from_f32((0..64).map(|i| (i as f32 * 0.3).sin()).collect());
+/// let quantized = quantize_mixed(&emb).unwrap();
+/// // mask (8) + 4 FP16 values (8) + 60 nibbles (30)
+/// assert_eq!(quantized.data.len(), 8 + 8 + 30);
+/// ```
+pub fn quantize_mixed(emb: &Vector) -> Result<QuantizedVector, QuantError> {
+    quantize_mixed_with(emb, &MixedPrecisionConfig::default())
+}
+
+/// Quantizes an embedding with the given mixed-precision configuration
+pub fn quantize_mixed_with(
+    emb: &Vector,
+    config: &MixedPrecisionConfig,
+) -> Result<QuantizedVector, QuantError> {
+    if emb.dtype != lnmp_embedding::EmbeddingType::F32 {
+        return Err(QuantError::EncodingFailed(
+            "Only F32 embeddings are supported for mixed-precision quantization".to_string(),
+        ));
+    }
+
+    let values = emb
+        .as_f32()
+        .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
+
+    let mut quantized = QuantizedVector::default();
+    quantize_mixed_into(&values, config, &mut quantized)?;
+    Ok(quantized)
+}
+
+/// Quantizes `values` into `out`, reusing its data buffer
+///
+/// Choosing the critical dimensions by magnitude allocates a scratch index list.
+pub(crate) fn quantize_mixed_into(
+    values: &[f32],
+    config: &MixedPrecisionConfig,
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
+    if values.is_empty() {
+        return Err(QuantError::InvalidDimension(
+            "Cannot quantize empty vector".to_string(),
+        ));
+    }
+
+    let dim = values.len();
+    let mask_bytes = dim.div_ceil(8);
+    out.data.clear();
+    out.data.resize(mask_bytes, 0);
+    match &config.critical {
+        CriticalDims::Mask(mask) => {
+            if mask.len() != dim {
+                return Err(QuantError::InvalidDimension(format!(
+                    "Mask length {} does not match dimension {}",
+                    mask.len(),
+                    dim
+                )));
+            }
+            for (i, _) in mask.iter().enumerate().filter(|(_, &critical)| critical) {
+                out.data[i / 8] |= 1 << (i % 8);
+            }
+        }
+        CriticalDims::TopFraction(fraction) => {
+            if !(0.0..=1.0).contains(fraction) {
+                return Err(QuantError::EncodingFailed(format!(
+                    "Critical fraction {} outside [0, 1]",
+                    fraction
+                )));
+            }
+            let count = (fraction * dim as f32).ceil() as usize;
+            let magnitudes: Vec<f32> = values.iter().map(|v| v.abs()).collect();
+            for (i, critical) in top_k_mask(&magnitudes, count).into_iter().enumerate() {
+                if critical {
+                    out.data[i / 8] |= 1 << (i % 8);
+                }
+            }
+        }
+    }
+
+    let is_critical = |data: &[u8], i: usize| (data[i / 8] >> (i % 8)) & 1 == 1;
+
+    // QInt4 range over the non-critical dimensions only
+    let (mut min_val, mut max_val) = (f32::INFINITY, f32::NEG_INFINITY);
+    for (i, &v) in values.iter().enumerate() {
+        if !is_critical(&out.data, i) {
+            min_val = min_val.min(v);
+            max_val = max_val.max(v);
+        }
+    }
+    let (scale, min_val) = if min_val > max_val {
+        // Every dimension is critical
+        (1.0, 0.0)
+    } else if (max_val - min_val).abs() < 1e-10 {
+        (1.0, min_val)
+    } else {
+        ((max_val - min_val) / 15.0, min_val)
+    };
+
+    for (i, &v) in values.iter().enumerate() {
+        if is_critical(&out.data, i) {
+            out.data.extend_from_slice(&f16::from_f32(v).to_le_bytes());
+        }
+    }
+
+    let mut pending: Option<u8> = None;
+    for (i, &v) in values.iter().enumerate() {
+        if is_critical(&out.data, i) {
+            continue;
+        }
+        let code = ((v - min_val) / scale).round().clamp(0.0, 15.0) as u8;
+        match pending.take() {
+            Some(high) => out.data.push((high << 4) | code),
+            None => pending = Some(code),
+        }
+    }
+    if let Some(high) = pending {
+        out.data.push(high << 4);
+    }
+
+    out.dim = dim as u32;
+    out.scheme = QuantScheme::MixedPrecision;
+    out.scale = scale;
+    out.zero_point = 0;
+    out.min_val = min_val;
+    Ok(())
+}
+
+/// Dequantizes a mixed-precision vector back to f32
+///
+/// # Arguments
+/// * `qv` - The quantized vector to dequantize
+///
+/// # Returns
+/// * `Ok(Vector)` - Restored f32 vector
+/// * `Err(QuantError)` - If dequantization fails
+pub fn dequantize_mixed(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; qv.dim as usize];
+    dequantize_mixed_into(qv, &mut values)?;
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes a mixed-precision vector into `out` (exactly `qv.dim` values)
+pub(crate) fn dequantize_mixed_into(
+    qv: &QuantizedVector,
+    out: &mut [f32],
+) -> Result<(), QuantError> {
+    let layout = Layout::of(qv)?;
+    let (mut fp16_at, mut code) = (layout.mask_bytes, 0);
+    for (i, value) in out.iter_mut().enumerate() {
+        if layout.is_critical(&qv.data, i) {
+            *value = f16::from_le_bytes([qv.data[fp16_at], qv.data[fp16_at + 1]]).to_f32();
+            fp16_at += 2;
+        } else {
+            *value = layout.code(&qv.data, code) as f32 * qv.scale + qv.min_val;
+            code += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Indices of the dimensions stored at FP16, in ascending order
+pub fn critical_dims(qv: &QuantizedVector) -> Result<Vec<usize>, QuantError> {
+    let layout = Layout::of(qv)?;
+    Ok((0..qv.dim as usize)
+        .filter(|&i| layout.is_critical(&qv.data, i))
+        .collect())
+}
+
+/// Where one dimension of a mixed-precision vector is stored
+pub(crate) enum Slot {
+    /// Byte offset of its FP16 value
+    Fp16(usize),
+    /// Position among the QInt4 codes
+    Code(usize),
+}
+
+/// Validated section offsets of a mixed-precision vector
+pub(crate) struct Layout {
+    mask_bytes: usize,
+    codes_offset: usize,
+}
+
+impl Layout {
+    pub(crate) fn of(qv: &QuantizedVector) -> Result<Self, QuantError> {
+        if qv.scheme != QuantScheme::MixedPrecision {
+            return Err(QuantError::InvalidScheme(format!(
+                "Expected MixedPrecision, got {:?}",
+                qv.scheme
+            )));
+        }
+
+        let dim = qv.dim as usize;
+        let mask_bytes = dim.div_ceil(8);
+        if qv.data.len() < mask_bytes {
+            return Err(QuantError::DataCorrupted(format!(
+                "Data length mismatch: expected at least {} mask bytes, got {}",
+                mask_bytes,
+                qv.data.len()
+            )));
+        }
+
+        let mut critical: usize = qv.data[..mask_bytes]
+            .iter()
+            .map(|b| b.count_ones() as usize)
+            .sum();
+        let tail = dim % 8;
+        if tail != 0 {
+            critical -= (qv.data[mask_bytes - 1] >> tail).count_ones() as usize;
+        }
+
+        let codes_offset = mask_bytes + critical * 2;
+        let expected = codes_offset + (dim - critical).div_ceil(2);
+        if qv.data.len() != expected {
+            return Err(QuantError::DataCorrupted(format!(
+                "Data length mismatch: expected {} bytes, got {}",
+                expected,
+                qv.data.len()
+            )));
+        }
+        Ok(Self {
+            mask_bytes,
+            codes_offset,
+        })
+    }
+
+    pub(crate) fn is_critical(&self, data: &[u8], i: usize) -> bool {
+        (data[i / 8] >> (i % 8)) & 1 == 1
+    }
+
+    /// Storage of dimension `i`; counts the critical dimensions before it.
+    pub(crate) fn slot(&self, data: &[u8], i: usize) -> Slot {
+        let below = data[..i / 8]
+            .iter()
+            .map(|b| b.count_ones() as usize)
+            .sum::<usize>()
+            + (data[i / 8] & ((1u16 << (i % 8)) - 1) as u8).count_ones() as usize;
+        if self.is_critical(data, i) {
+            Slot::Fp16(self.mask_bytes + below * 2)
+        } else {
+            Slot::Code(i - below)
+        }
+    }
+
+    /// Reads the QInt4 code at `position`.
+    pub(crate) fn code(&self, data: &[u8], position: usize) -> u8 {
+        let byte = data[self.codes_offset + position / 2];
+        if position.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0F
+        }
+    }
+
+    /// Overwrites the QInt4 code at `position`.
+    pub(crate) fn set_code(&self, data: &mut [u8], position: usize, code: u8) {
+        let byte = &mut data[self.codes_offset + position / 2];
+        *byte = if position.is_multiple_of(2) {
+            (*byte & 0x0F) | (code << 4)
+        } else {
+            (*byte & 0xF0) | code
+        };
+    }
+}
+
+/// Mask of the `count` largest scores (ties keep the lower index).
+fn top_k_mask(scores: &[f32], count: usize) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    let mut mask = vec![false; scores.len()];
+    for &i in order.iter().take(count) {
+        mask[i] = true;
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::AccuracyMetrics;
+
+    fn with_outliers() -> Vector {
+        let mut values: Vec<f32> = (0..128).map(|i| (i as f32 * 0.37).sin() * 0.1).collect();
+        values[5] = 4.0;
+        values[77] = -3.0;
+        Vector::from_f32(values)
+    }
+
+    #[test]
+    fn test_outliers_kept_at_fp16() {
+        let emb = with_outliers();
+        let config = MixedPrecisionConfig::new().with_top_fraction(2.0 / 128.0);
+        let quantized = quantize_mixed_with(&emb, &config).unwrap();
+        assert_eq!(quantized.scheme, QuantScheme::MixedPrecision);
+        assert_eq!(critical_dims(&quantized).unwrap(), vec![5, 77]);
+        assert_eq!(quantized.data.len(), 16 + 2 * 2 + 63);
+
+        let restored = dequantize_mixed(&quantized).unwrap().as_f32().unwrap();
+        assert_eq!(restored[5], 4.0);
+        assert_eq!(restored[77], -3.0);
+
+        // Excluding the outliers from the QInt4 range beats plain QInt4
+        let mixed = AccuracyMetrics::measure(&emb, &quantized).unwrap();
+        let qint4 = crate::qint4::quantize_qint4(&emb).unwrap();
+        let plain = AccuracyMetrics::measure(&emb, &qint4).unwrap();
+        assert!(mixed.max_abs_error < plain.max_abs_error / 4.0);
+        assert!(mixed.cosine_similarity > plain.cosine_similarity);
+    }
+
+    #[test]
+    fn test_explicit_mask_and_variance() {
+        let emb = Vector::from_f32(vec![0.5, 0.1, -0.25, 0.3, 0.9]);
+        let mask = vec![false, true, false, false, true];
+        let config = MixedPrecisionConfig::new().with_mask(mask);
+        let quantized = quantize_mixed_with(&emb, &config).unwrap();
+        assert_eq!(critical_dims(&quantized).unwrap(), vec![1, 4]);
+
+        let restored = crate::dequantize_embedding(&quantized).unwrap();
+        let restored = restored.as_f32().unwrap();
+        assert_eq!(restored[4], f16::from_f32(0.9).to_f32());
+        assert!((restored[0] - 0.5).abs() < 0.05);
+
+        let bad = MixedPrecisionConfig::new().with_mask(vec![true]);
+        assert!(quantize_mixed_with(&emb, &bad).is_err());
+        let bad = MixedPrecisionConfig::new().with_top_fraction(1.5);
+        assert!(quantize_mixed_with(&emb, &bad).is_err());
+
+        let samples = vec![
+            Vector::from_f32(vec![0.0, 1.0, 0.0]),
+            Vector::from_f32(vec![0.1, -1.0, 5.0]),
+        ];
+        let config = MixedPrecisionConfig::from_variance(&samples, 2).unwrap();
+        assert_eq!(config.critical, CriticalDims::Mask(vec![false, true, true]));
+        assert!(MixedPrecisionConfig::from_variance(&[], 1).is_err());
+    }
+
+    #[test]
+    fn test_all_or_no_critical_dims() {
+        let emb = Vector::from_f32(vec![0.25, -0.5, 1.0]);
+        let all = MixedPrecisionConfig::new().with_top_fraction(1.0);
+        let quantized = quantize_mixed_with(&emb, &all).unwrap();
+        let restored = dequantize_mixed(&quantized).unwrap().as_f32().unwrap();
+        assert_eq!(restored, vec![0.25, -0.5, 1.0]);
+
+        let none = MixedPrecisionConfig::new().with_top_fraction(0.0);
+        let quantized = quantize_mixed_with(&emb, &none).unwrap();
+        assert!(critical_dims(&quantized).unwrap().is_empty());
+        assert_eq!(quantized.data.len(), 1 + 2);
+    }
+
+    #[test]
+    fn test_corrupted_data() {
+        let quantized = quantize_mixed(&with_outliers()).unwrap();
+        let mut truncated = quantized.clone();
+        truncated.data.pop();
+        assert!(matches!(
+            dequantize_mixed(&truncated),
+            Err(QuantError::DataCorrupted(_))
+        ));
+
+        // Padding bits in the mask are ignored
+        let short = quantize_mixed(&Vector::from_f32(vec![1.0, 2.0, 3.0])).unwrap();
+        let mut padded = short.clone();
+        padded.data[0] |= 0x80;
+        assert_eq!(
+            dequantize_mixed(&padded).unwrap(),
+            dequantize_mixed(&short).unwrap()
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! QInt2: 2-bit quantization with crumb packing
+//!
+//! This module implements 2-bit integer quantization where four values are packed
+//! into each byte. This provides 16x compression compared to FP32, filling the gap
+//! between QInt4 (8x) and Binary (32x).
+//!
+//! With only four levels per value, a single min/scale pair over the whole vector
+//! wastes most of the range on outliers. [`QInt2Config::with_group_size`] gives
+//! every group of consecutive values its own min/scale pair instead.
+//!
+//! ## Data layout
+//!
+//! `data` holds the packed codes, `dim.div_ceil(4)` bytes with the first value in
+//! the two highest bits. Grouped vectors append `group_size (u32 LE)` followed by
+//! one `scale (f32 LE) | min (f32 LE)` pair per group; ungrouped vectors use the
+//! `scale` and `min_val` fields.
+
+use crate::error::QuantError;
+use crate::scheme::QuantScheme;
+use crate::vector::QuantizedVector;
+use lnmp_embedding::Vector;
+
+/// Highest 2-bit code.
+const MAX_CODE: f32 = 3.0;
+
+/// How values are rounded to the nearest quantization levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Round to the nearest level (lowest error per value)
+    #[default]
+    Nearest,
+    /// Round down to the level below
+    Floor,
+    /// Round up or down with probability proportional to the distance, so the
+    /// expected reconstruction equals the input. The seed makes it reproducible.
+    Stochastic {
+        /// Seed of the random generator
+        seed: u64,
+    },
+}
+
+/// Configuration for QInt2 quantization
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QInt2Config {
+    /// Values per min/scale group (`None`: one pair for the whole vector)
+    pub group_size: Option<u32>,
+    /// Rounding mode
+    pub rounding: RoundingMode,
+}
+
+impl QInt2Config {
+    /// Creates the default configuration (ungrouped, round to nearest)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives every `group_size` consecutive values their own min/scale pair
+    pub fn with_group_size(mut self, group_size: u32) -> Self {
+        self.group_size = Some(group_size);
+        self
+    }
+
+    /// Sets the rounding mode
+    pub fn with_rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+}
+
+/// Quantizes an embedding vector to 2-bit integers (packed 4 per byte) with the
+/// default configuration
+///
+/// # Example
+/// ```
+/// use lnmp_quant::qint2::quantize_qint2;
+/// use lnmp_embedding::Vector;
+///
+/// let emb = Vector::from_f32(vec![0.0, 0.25, 0.5, 1.0]);
+/// let quantized = quantize_qint2(&emb).unwrap();
+/// assert_eq!(quantized.scheme, lnmp_quant::QuantScheme::QInt2);
+/// assert_eq!(quantized.data.len(), 1);
+/// ```
+pub fn quantize_qint2(emb: &Vector) -> Result<QuantizedVector, QuantError> {
+    quantize_qint2_with(emb, &QInt2Config::default())
+}
+
+/// Quantizes an embedding vector to 2-bit integers with the given configuration
+///
+/// # Example
+/// ```
+/// use lnmp_quant::qint2::{quantize_qint2_with, QInt2Config, RoundingMode};
+/// use lnmp_embedding::Vector;
+///
+/// let emb = Vector::from_f32((0..256).map(|i| (i as f32 * 0.1).sin()).collect());
+/// let config = QInt2Config::new()
+///     .with_group_size(64)
+///     .with_rounding(RoundingMode::Stochastic { seed: 7 });
+/// let quantized = quantize_qint2_with(&emb, &config).unwrap();
+/// assert_eq!(quantized.data.len(), 64 + 4 + 4 * 8);
+/// ```
+pub fn quantize_qint2_with(
+    emb: &Vector,
+    config: &QInt2Config,
+) -> Result<QuantizedVector, QuantError> {
+    if emb.dtype != lnmp_embedding::EmbeddingType::F32 {
+        return Err(QuantError::EncodingFailed(
+            "Only F32 embeddings are supported for QInt2 quantization".to_string(),
+        ));
+    }
+
+    let values = emb
+        .as_f32()
+        .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
+
+    let mut quantized = QuantizedVector::default();
+    quantize_qint2_into(&values, config, &mut quantized)?;
+    Ok(quantized)
+}
+
+/// Quantizes `values` to QInt2 into `out`, reusing its data buffer
+pub(crate) fn quantize_qint2_into(
+    values: &[f32],
+    config: &QInt2Config,
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
+    if values.is_empty() {
+        return Err(QuantError::InvalidDimension(
+            "Cannot quantize empty vector".to_string(),
+        ));
+    }
+    if config.group_size == Some(0) {
+        return Err(QuantError::EncodingFailed(
+            "Group size must be positive".to_string(),
+        ));
+    }
+
+    let group_size = config.group_size.map_or(values.len(), |g| g as usize);
+    let mut rng = match config.rounding {
+        RoundingMode::Stochastic { seed } => seed.max(1),
+        _ => 0,
+    };
+
+    // Codes first, then the group trailer (if any) appended behind them
+    out.data.clear();
+    out.data.resize(values.len().div_ceil(4), 0);
+    if config.group_size.is_some() {
+        out.data
+            .extend_from_slice(&(group_size as u32).to_le_bytes());
+    }
+
+    let mut last_params = (1.0, 0.0);
+    for (group, chunk) in values.chunks(group_size).enumerate() {
+        let (scale, min) = group_params(chunk);
+        for (j, &value) in chunk.iter().enumerate() {
+            let normalized = (value - min) / scale;
+            let rounded = match config.rounding {
+                RoundingMode::Nearest => normalized.round(),
+                RoundingMode::Floor => normalized.floor(),
+                RoundingMode::Stochastic { .. } => (normalized + uniform(&mut rng)).floor(),
+            };
+            let code = rounded.clamp(0.0, MAX_CODE) as u8;
+            let i = group * group_size + j;
+            out.data[i / 4] |= code << (6 - 2 * (i % 4));
+        }
+        if config.group_size.is_some() {
+            out.data.extend_from_slice(&scale.to_le_bytes());
+            out.data.extend_from_slice(&min.to_le_bytes());
+        }
+        last_params = (scale, min);
+    }
+
+    let (scale, min_val) = if config.group_size.is_some() {
+        (1.0, 0.0)
+    } else {
+        last_params
+    };
+
+    out.dim = values.len() as u32;
+    out.scheme = QuantScheme::QInt2;
+    out.scale = scale;
+    out.zero_point = 0;
+    out.min_val = min_val;
+    Ok(())
+}
+
+/// Dequantizes a 2-bit quantized vector back to f32
+///
+/// # Arguments
+/// * `qv` - The quantized vector to dequantize
+///
+/// # Returns
+/// * `Ok(Vector)` - Restored f32 vector
+/// * `Err(QuantError)` - If dequantization fails
+pub fn dequantize_qint2(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; qv.dim as usize];
+    dequantize_qint2_into(qv, &mut values)?;
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes a QInt2 vector into `out` (exactly `qv.dim` values)
+pub(crate) fn dequantize_qint2_into(
+    qv: &QuantizedVector,
+    out: &mut [f32],
+) -> Result<(), QuantError> {
+    if qv.scheme != QuantScheme::QInt2 {
+        return Err(QuantError::InvalidScheme(format!(
+            "Expected QInt2, got {:?}",
+            qv.scheme
+        )));
+    }
+
+    let (group_size, params) = group_layout(qv)?;
+    for (group, chunk) in out.chunks_mut(group_size).enumerate() {
+        let (scale, min) = param_at(qv, params, group);
+        for (j, value) in chunk.iter_mut().enumerate() {
+            let i = group * group_size + j;
+            let code = (qv.data[i / 4] >> (6 - 2 * (i % 4))) & 0b11;
+            *value = code as f32 * scale + min;
+        }
+    }
+
+    Ok(())
+}
+
+/// Group size and per-group `(scale, min)` pairs of a QInt2 vector.
+pub(crate) fn group_params_of(
+    qv: &QuantizedVector,
+) -> Result<(usize, Vec<(f32, f32)>), QuantError> {
+    let (group_size, params) = group_layout(qv)?;
+    let groups = (qv.dim as usize).div_ceil(group_size);
+    Ok((
+        group_size,
+        (0..groups).map(|g| param_at(qv, params, g)).collect(),
+    ))
+}
+
+/// Validates the layout and returns the group size and, for grouped vectors,
+/// the raw `(scale, min)` pairs of the trailer.
+fn group_layout(qv: &QuantizedVector) -> Result<(usize, Option<&[u8]>), QuantError> {
+    let dim = qv.dim as usize;
+    let code_bytes = dim.div_ceil(4);
+    if qv.data.len() < code_bytes {
+        return Err(QuantError::DataCorrupted(format!(
+            "Data length mismatch: expected at least {} bytes, got {}",
+            code_bytes,
+            qv.data.len()
+        )));
+    }
+    if qv.data.len() == code_bytes {
+        return Ok((dim, None));
+    }
+
+    let corrupted = || QuantError::DataCorrupted("Invalid QInt2 group trailer".to_string());
+    let trailer = &qv.data[code_bytes..];
+    if trailer.len() < 4 {
+        return Err(corrupted());
+    }
+    let group_size = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) as usize;
+    if group_size == 0 || trailer.len() != 4 + dim.div_ceil(group_size) * 8 {
+        return Err(corrupted());
+    }
+    Ok((group_size, Some(&trailer[4..])))
+}
+
+/// `(scale, min)` of one group, from the trailer or the vector's own fields.
+fn param_at(qv: &QuantizedVector, params: Option<&[u8]>, group: usize) -> (f32, f32) {
+    match params {
+        Some(params) => {
+            let p = &params[group * 8..group * 8 + 8];
+            (
+                f32::from_le_bytes([p[0], p[1], p[2], p[3]]),
+                f32::from_le_bytes([p[4], p[5], p[6], p[7]]),
+            )
+        }
+        None => (qv.scale, qv.min_val),
+    }
+}
+
+/// Scale and minimum of one group; constant groups use scale 1.0.
+fn group_params(values: &[f32]) -> (f32, f32) {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if (max - min).abs() < 1e-10 {
+        (1.0, min)
+    } else {
+        ((max - min) / MAX_CODE, min)
+    }
+}
+
+/// Uniform value in `[0, 1)` from a xorshift64 state.
+fn uniform(state: &mut u64) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::AccuracyMetrics;
+
+    #[test]
+    fn test_qint2_packing() {
+        // min 0, scale 1: codes 0, 1, 2, 3, 2
+        let vec = Vector::from_f32(vec![0.0, 1.0, 2.0, 3.0, 2.0]);
+        let quantized = quantize_qint2(&vec).unwrap();
+
+        assert_eq!(quantized.data, vec![0b00_01_10_11, 0b10_00_00_00]);
+        assert_eq!((quantized.scale, quantized.min_val), (1.0, 0.0));
+
+        let restored = dequantize_qint2(&quantized).unwrap();
+        assert_eq!(restored.as_f32().unwrap(), vec![0.0, 1.0, 2.0, 3.0, 2.0]);
+    }
+
+    #[test]
+    fn test_qint2_rounding_modes() {
+        let vec = Vector::from_f32(vec![0.0, 0.4, 0.6, 3.0]);
+        let restore = |rounding| {
+            let config = QInt2Config::new().with_rounding(rounding);
+            dequantize_qint2(&quantize_qint2_with(&vec, &config).unwrap())
+                .unwrap()
+                .as_f32()
+                .unwrap()
+        };
+
+        assert_eq!(restore(RoundingMode::Nearest), vec![0.0, 0.0, 1.0, 3.0]);
+        assert_eq!(restore(RoundingMode::Floor), vec![0.0, 0.0, 0.0, 3.0]);
+
+        // Stochastic rounding is unbiased: 0.4 averages to ~0.4
+        let mut values = vec![0.0, 3.0];
+        values.extend([0.4; 2000]);
+        let constant = Vector::from_f32(values);
+        let config = QInt2Config::new().with_rounding(RoundingMode::Stochastic { seed: 3 });
+        let quantized = quantize_qint2_with(&constant, &config).unwrap();
+        let restored = dequantize_qint2(&quantized).unwrap().as_f32().unwrap();
+        let mean = restored[2..].iter().sum::<f32>() / 2000.0;
+        assert!((mean - 0.4).abs() < 0.05, "mean {}", mean);
+        assert_eq!(quantize_qint2_with(&constant, &config).unwrap(), quantized);
+    }
+
+    #[test]
+    fn test_qint2_grouped_scales() {
+        // Two very different ranges: per-group scales keep both precise
+        let mut values: Vec<f32> = (0..32).map(|i| (i % 4) as f32 * 0.01).collect();
+        values.extend((0..32).map(|i| (i % 4) as f32 * 100.0));
+        let vec = Vector::from_f32(values.clone());
+
+        let grouped = quantize_qint2_with(&vec, &QInt2Config::new().with_group_size(32)).unwrap();
+        assert_eq!(grouped.data.len(), 16 + 4 + 2 * 8);
+        let restored = dequantize_qint2(&grouped).unwrap().as_f32().unwrap();
+        for (a, b) in values.iter().zip(&restored) {
+            assert!((a - b).abs() < 1e-3, "{} vs {}", a, b);
+        }
+
+        let ungrouped = quantize_qint2(&vec).unwrap();
+        let grouped_loss = AccuracyMetrics::measure(&vec, &grouped).unwrap().loss_ratio;
+        let ungrouped_loss = AccuracyMetrics::measure(&vec, &ungrouped)
+            .unwrap()
+            .loss_ratio;
+        assert!(grouped_loss < ungrouped_loss);
+
+        // Odd trailing group
+        let odd = Vector::from_f32((0..10).map(|i| i as f32).collect());
+        let q = quantize_qint2_with(&odd, &QInt2Config::new().with_group_size(4)).unwrap();
+        assert_eq!(dequantize_qint2(&q).unwrap().dim, 10);
+
+        let mut corrupted = q.clone();
+        corrupted.data.pop();
+        assert!(dequantize_qint2(&corrupted).is_err());
+        assert!(quantize_qint2_with(&odd, &QInt2Config::new().with_group_size(0)).is_err());
+    }
+
+    #[test]
+    fn test_qint2_compression_and_accuracy() {
+        let original: Vec<f32> = (0..512)
+            .map(|i| ((i * 7 % 97) as f32 / 97.0) - 0.5)
+            .collect();
+        let vec = Vector::from_f32(original);
+        let quantized = quantize_qint2(&vec).unwrap();
+
+        assert_eq!(quantized.data.len(), 128);
+        assert_eq!(quantized.compression_ratio(), 16.0);
+
+        let metrics = AccuracyMetrics::measure(&vec, &quantized).unwrap();
+        assert!(metrics.cosine_similarity > 0.9, "{:?}", metrics);
+    }
+}
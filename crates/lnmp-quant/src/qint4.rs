@@ -38,6 +38,16 @@ pub fn quantize_qint4(emb: &Vector) -> Result<QuantizedVector, QuantError> {
         .as_f32()
         .map_err(|e| QuantError::EncodingFailed(format!("Failed to convert to F32: {}", e)))?;
 
+    let mut quantized = QuantizedVector::default();
+    quantize_qint4_into(&values, &mut quantized)?;
+    Ok(quantized)
+}
+
+/// Quantizes `values` to QInt4 into `out`, reusing its data buffer
+pub(crate) fn quantize_qint4_into(
+    values: &[f32],
+    out: &mut QuantizedVector,
+) -> Result<(), QuantError> {
     if values.is_empty() {
         return Err(QuantError::InvalidDimension(
             "Cannot quantize empty vector".to_string(),
@@ -75,7 +85,8 @@ pub fn quantize_qint4(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 
     // Quantize and pack: 2 values per byte
     let num_bytes = values.len().div_ceil(2); // Round up for odd dimensions
-    let mut data = Vec::with_capacity(num_bytes);
+    out.data.clear();
+    out.data.reserve(num_bytes);
 
     for chunk in values.chunks(2) {
         // Quantize first value
@@ -92,17 +103,15 @@ pub fn quantize_qint4(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 
         // Pack: high nibble = val1, low nibble = val2
         let packed = (val1 << 4) | val2;
-        data.push(packed);
+        out.data.push(packed);
     }
 
-    Ok(QuantizedVector::new(
-        emb.dim as u32,
-        QuantScheme::QInt4,
-        scale,
-        zero_point,
-        min_val,
-        data,
-    ))
+    out.dim = values.len() as u32;
+    out.scheme = QuantScheme::QInt4;
+    out.scale = scale;
+    out.zero_point = zero_point;
+    out.min_val = min_val;
+    Ok(())
 }
 
 /// Dequantizes a 4-bit quantized vector back to f32
@@ -114,6 +123,16 @@ pub fn quantize_qint4(emb: &Vector) -> Result<QuantizedVector, QuantError> {
 /// * `Ok(Vector)` - Restored f32 vector
 /// * `Err(QuantError)` - If dequantization fails
 pub fn dequantize_qint4(qv: &QuantizedVector) -> Result<Vector, QuantError> {
+    let mut values = vec![0.0; qv.dim as usize];
+    dequantize_qint4_into(qv, &mut values)?;
+    Ok(Vector::from_f32(values))
+}
+
+/// Dequantizes a QInt4 vector into `out` (exactly `qv.dim` values)
+pub(crate) fn dequantize_qint4_into(
+    qv: &QuantizedVector,
+    out: &mut [f32],
+) -> Result<(), QuantError> {
     if qv.scheme != QuantScheme::QInt4 {
         return Err(QuantError::InvalidScheme(format!(
             "Expected QInt4, got {:?}",
@@ -122,26 +141,26 @@ pub fn dequantize_qint4(qv: &QuantizedVector) -> Result<Vector, QuantError> {
     }
 
     let dim = qv.dim as usize;
-    let mut values = Vec::with_capacity(dim);
-
-    for &packed_byte in &qv.data {
-        // Extract high nibble (first value)
-        let val1 = (packed_byte >> 4) & 0x0F;
-        let restored1 = (val1 as f32) * qv.scale + qv.min_val;
-        values.push(restored1);
-
-        // Extract low nibble (second value) if we haven't reached dimension limit
-        if values.len() < dim {
-            let val2 = packed_byte & 0x0F;
-            let restored2 = (val2 as f32) * qv.scale + qv.min_val;
-            values.push(restored2);
-        }
+    if qv.data.len() != dim.div_ceil(2) {
+        return Err(QuantError::DataCorrupted(format!(
+            "Data length mismatch: expected {} bytes, got {}",
+            dim.div_ceil(2),
+            qv.data.len()
+        )));
     }
 
-    // Truncate to exact dimension (in case we overshot due to padding)
-    values.truncate(dim);
+    for (i, value) in out.iter_mut().enumerate() {
+        // High nibble holds the first value of each pair
+        let packed_byte = qv.data[i / 2];
+        let code = if i % 2 == 0 {
+            packed_byte >> 4
+        } else {
+            packed_byte & 0x0F
+        };
+        *value = (code as f32) * qv.scale + qv.min_val;
+    }
 
-    Ok(Vector::from_f32(values))
+    Ok(())
 }
 
 #[cfg(test)]
@@ -30,6 +30,18 @@ pub enum QuantScheme {
     /// - Accuracy: Very high
     #[allow(dead_code)]
     FP16Passthrough = 0x04,
+
+    /// 2-bit packed quantization
+    /// - Range: 0 to 3 (4 levels, optional per-group scales)
+    /// - Size reduction: 16x (F32 → 2-bit)
+    /// - Accuracy: Moderate-high
+    QInt2 = 0x05,
+
+    /// Mixed precision: critical dimensions at FP16, the rest at QInt4
+    /// - Range: Half-precision float / 0 to 15, plus a dimension mask
+    /// - Size reduction: up to 6.4x, depending on the share of critical dimensions
+    /// - Accuracy: High, robust to outlier dimensions
+    MixedPrecision = 0x06,
 }
 
 impl QuantScheme {
@@ -40,6 +52,8 @@ impl QuantScheme {
             QuantScheme::QInt4 => 1,  // packed, 2 values per byte
             QuantScheme::Binary => 1, // packed, 8 values per byte
             QuantScheme::FP16Passthrough => 2,
+            QuantScheme::QInt2 => 1,          // packed, 4 values per byte
+            QuantScheme::MixedPrecision => 1, // mostly nibble-packed; varies with the mask
         }
     }
 
@@ -59,6 +73,8 @@ mod tests {
         assert_eq!(QuantScheme::QInt4.bytes_per_value(), 1);
         assert_eq!(QuantScheme::Binary.bytes_per_value(), 1);
         assert_eq!(QuantScheme::FP16Passthrough.bytes_per_value(), 2);
+        assert_eq!(QuantScheme::QInt2.bytes_per_value(), 1);
+        assert_eq!(QuantScheme::MixedPrecision.bytes_per_value(), 1);
     }
 
     #[test]
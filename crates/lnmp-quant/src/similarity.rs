@@ -0,0 +1,252 @@
+//! Similarity computed directly on quantized vectors.
+//!
+//! Comparing two quantized embeddings by dequantizing both allocates and converts
+//! every value first. For QInt8 pairs, this module instead accumulates integer
+//! dot products over the raw codes and applies the scale/offset correction once;
+//! for Binary pairs it counts differing bits (Hamming distance). Results match
+//! [`Vector::similarity`](lnmp_embedding::Vector::similarity) on the dequantized
+//! vectors up to floating-point rounding.
+
+use crate::decode::dequantize_embedding;
+use crate::error::QuantError;
+use crate::scheme::QuantScheme;
+use crate::vector::QuantizedVector;
+use lnmp_embedding::SimilarityMetric;
+
+/// Computes the similarity between two quantized vectors.
+///
+/// QInt8 and Binary pairs are scored without dequantization. Other schemes, and
+/// pairs with different schemes, fall back to dequantizing both vectors.
+///
+/// # Example
+/// ```
+/// use lnmp_quant::{quantize_embedding, quantized_similarity, QuantScheme};
+/// use lnmp_embedding::{SimilarityMetric, Vector};
+///
+/// let a = quantize_embedding(&Vector::from_f32(vec![0.1, 0.9, -0.3]), QuantScheme::QInt8).unwrap();
+/// let b = quantize_embedding(&Vector::from_f32(vec![0.2, 0.8, -0.2]), QuantScheme::QInt8).unwrap();
+/// let cosine = quantized_similarity(&a, &b, SimilarityMetric::Cosine).unwrap();
+/// assert!(cosine > 0.95);
+/// ```
+pub fn quantized_similarity(
+    a: &QuantizedVector,
+    b: &QuantizedVector,
+    metric: SimilarityMetric,
+) -> Result<f32, QuantError> {
+    if a.dim != b.dim {
+        return Err(QuantError::InvalidDimension(format!(
+            "Dimension mismatch: {} vs {}",
+            a.dim, b.dim
+        )));
+    }
+
+    match (a.scheme, b.scheme) {
+        (QuantScheme::QInt8, QuantScheme::QInt8) => qint8_similarity(a, b, metric),
+        (QuantScheme::Binary, QuantScheme::Binary) => binary_similarity(a, b, metric),
+        _ => {
+            let a = dequantize_embedding(a)?;
+            let b = dequantize_embedding(b)?;
+            a.similarity(&b, metric).map_err(QuantError::DecodingFailed)
+        }
+    }
+}
+
+/// Number of differing bits between two Binary quantized vectors.
+pub fn hamming_distance(a: &QuantizedVector, b: &QuantizedVector) -> Result<u32, QuantError> {
+    if a.scheme != QuantScheme::Binary || b.scheme != QuantScheme::Binary {
+        return Err(QuantError::InvalidScheme(format!(
+            "Hamming distance requires Binary vectors, got {:?} and {:?}",
+            a.scheme, b.scheme
+        )));
+    }
+    if a.dim != b.dim {
+        return Err(QuantError::InvalidDimension(format!(
+            "Dimension mismatch: {} vs {}",
+            a.dim, b.dim
+        )));
+    }
+    let bytes = (a.dim as usize).div_ceil(8);
+    check_len(a, bytes)?;
+    check_len(b, bytes)?;
+
+    let mut distance: u32 = a.data[..bytes]
+        .iter()
+        .zip(&b.data[..bytes])
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum();
+    // Ignore padding bits past `dim` in the last byte
+    let tail = a.dim % 8;
+    if tail != 0 {
+        let padding = (a.data[bytes - 1] ^ b.data[bytes - 1]) >> tail;
+        distance -= padding.count_ones();
+    }
+    Ok(distance)
+}
+
+/// Binary vectors dequantize to ±1, so the dot product is `dim - 2 * hamming`
+/// and both norms are `√dim`.
+fn binary_similarity(
+    a: &QuantizedVector,
+    b: &QuantizedVector,
+    metric: SimilarityMetric,
+) -> Result<f32, QuantError> {
+    let hamming = hamming_distance(a, b)? as f32;
+    let dim = a.dim as f32;
+    Ok(match metric {
+        SimilarityMetric::Cosine => 1.0 - 2.0 * hamming / dim,
+        SimilarityMetric::DotProduct => dim - 2.0 * hamming,
+        SimilarityMetric::Euclidean => (4.0 * hamming).sqrt(),
+    })
+}
+
+/// QInt8 values dequantize to `scale * u + min_val` with the unsigned code
+/// `u = q + 128`, so every metric follows from the integer sums `Σuv`, `Σu`,
+/// `Σv`, `Σu²` and `Σv²`.
+fn qint8_similarity(
+    a: &QuantizedVector,
+    b: &QuantizedVector,
+    metric: SimilarityMetric,
+) -> Result<f32, QuantError> {
+    check_len(a, a.dim as usize)?;
+    check_len(b, b.dim as usize)?;
+
+    let (mut sum_uv, mut sum_u, mut sum_v, mut sum_uu, mut sum_vv) = (0u64, 0u64, 0u64, 0u64, 0u64);
+    for (&x, &y) in a.data.iter().zip(&b.data) {
+        let u = (x as i8 as i32 + 128) as u64;
+        let v = (y as i8 as i32 + 128) as u64;
+        sum_uv += u * v;
+        sum_u += u;
+        sum_v += v;
+        sum_uu += u * u;
+        sum_vv += v * v;
+    }
+
+    let n = a.dim as f64;
+    let (sa, ma) = (a.scale as f64, a.min_val as f64);
+    let (sb, mb) = (b.scale as f64, b.min_val as f64);
+    let dot =
+        sa * sb * sum_uv as f64 + sa * mb * sum_u as f64 + ma * sb * sum_v as f64 + n * ma * mb;
+    let norm_a_sq = sa * sa * sum_uu as f64 + 2.0 * sa * ma * sum_u as f64 + n * ma * ma;
+    let norm_b_sq = sb * sb * sum_vv as f64 + 2.0 * sb * mb * sum_v as f64 + n * mb * mb;
+
+    Ok(match metric {
+        SimilarityMetric::DotProduct => dot as f32,
+        SimilarityMetric::Cosine => {
+            let norms = norm_a_sq.max(0.0).sqrt() * norm_b_sq.max(0.0).sqrt();
+            if norms == 0.0 {
+                0.0
+            } else {
+                (dot / norms) as f32
+            }
+        }
+        SimilarityMetric::Euclidean => (norm_a_sq + norm_b_sq - 2.0 * dot).max(0.0).sqrt() as f32,
+    })
+}
+
+fn check_len(q: &QuantizedVector, expected: usize) -> Result<(), QuantError> {
+    if q.data.len() != expected {
+        return Err(QuantError::DataCorrupted(format!(
+            "Data length mismatch: expected {} bytes, got {}",
+            expected,
+            q.data.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::quantize_embedding;
+    use lnmp_embedding::Vector;
+
+    const METRICS: [SimilarityMetric; 3] = [
+        SimilarityMetric::Cosine,
+        SimilarityMetric::DotProduct,
+        SimilarityMetric::Euclidean,
+    ];
+
+    fn vector(seed: usize, dim: usize) -> Vector {
+        Vector::from_f32(
+            (0..dim)
+                .map(|i| ((i * 37 + seed * 101) % 29) as f32 / 14.0 - 0.9)
+                .collect(),
+        )
+    }
+
+    fn assert_matches_dequantized(a: &QuantizedVector, b: &QuantizedVector) {
+        let (da, db) = (
+            dequantize_embedding(a).unwrap(),
+            dequantize_embedding(b).unwrap(),
+        );
+        for metric in METRICS {
+            let expected = da.similarity(&db, metric).unwrap();
+            let actual = quantized_similarity(a, b, metric).unwrap();
+            assert!(
+                (expected - actual).abs() <= 1e-4 * expected.abs().max(1.0),
+                "{metric:?}: {expected} vs {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_qint8_matches_dequantized() {
+        let a = quantize_embedding(&vector(1, 300), QuantScheme::QInt8).unwrap();
+        let b = quantize_embedding(&vector(2, 300), QuantScheme::QInt8).unwrap();
+        assert_matches_dequantized(&a, &b);
+        assert_matches_dequantized(&a, &a);
+
+        // Constant vectors use scale 1.0
+        let flat =
+            quantize_embedding(&Vector::from_f32(vec![0.5; 300]), QuantScheme::QInt8).unwrap();
+        assert_matches_dequantized(&a, &flat);
+    }
+
+    #[test]
+    fn test_binary_matches_dequantized() {
+        // 21 dims leaves padding bits in the last byte
+        let a = quantize_embedding(&vector(3, 21), QuantScheme::Binary).unwrap();
+        let mut b = quantize_embedding(&vector(4, 21), QuantScheme::Binary).unwrap();
+        b.data[2] |= 0xE0;
+        assert_matches_dequantized(&a, &b);
+
+        let expected = dequantize_embedding(&a)
+            .unwrap()
+            .as_f32()
+            .unwrap()
+            .iter()
+            .zip(dequantize_embedding(&b).unwrap().as_f32().unwrap())
+            .filter(|(x, y)| **x != *y)
+            .count();
+        assert_eq!(hamming_distance(&a, &b).unwrap(), expected as u32);
+        assert_eq!(hamming_distance(&a, &a).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_other_schemes_fall_back() {
+        let a = quantize_embedding(&vector(5, 64), QuantScheme::QInt4).unwrap();
+        let b = quantize_embedding(&vector(6, 64), QuantScheme::QInt8).unwrap();
+        assert_matches_dequantized(&a, &b);
+    }
+
+    #[test]
+    fn test_errors() {
+        let a = quantize_embedding(&vector(1, 16), QuantScheme::QInt8).unwrap();
+        let b = quantize_embedding(&vector(1, 8), QuantScheme::QInt8).unwrap();
+        assert!(matches!(
+            quantized_similarity(&a, &b, SimilarityMetric::Cosine),
+            Err(QuantError::InvalidDimension(_))
+        ));
+        assert!(matches!(
+            hamming_distance(&a, &a),
+            Err(QuantError::InvalidScheme(_))
+        ));
+
+        let mut corrupted = a.clone();
+        corrupted.data.pop();
+        assert!(matches!(
+            quantized_similarity(&a, &corrupted, SimilarityMetric::Cosine),
+            Err(QuantError::DataCorrupted(_))
+        ));
+    }
+}
@@ -23,6 +23,13 @@ pub struct QuantizedVector {
     pub data: Vec<u8>,
 }
 
+/// An empty QInt8 vector, to be filled by [`quantize_into`](crate::quantize_into)
+impl Default for QuantizedVector {
+    fn default() -> Self {
+        Self::new(0, QuantScheme::QInt8, 1.0, 0, 0.0, Vec::new())
+    }
+}
+
 impl QuantizedVector {
     /// Creates a new quantized vector
     pub fn new(
@@ -56,6 +63,16 @@ impl QuantizedVector {
     }
 }
 
+/// Quantized vectors are dequantized when inserted into or queried against an
+/// [`HnswIndex`](lnmp_embedding::HnswIndex).
+impl lnmp_embedding::IndexVector for QuantizedVector {
+    fn to_f32_vec(&self) -> Result<Vec<f32>, String> {
+        crate::decode::dequantize_embedding(self)
+            .map_err(|e| e.to_string())?
+            .as_f32()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,4 +98,28 @@ mod tests {
         // Ratio: 2048 / 512 = 4.0
         assert_eq!(qv.compression_ratio(), 4.0);
     }
+
+    #[test]
+    fn test_index_quantized_vectors() {
+        use crate::encode::quantize_embedding;
+        use lnmp_embedding::{HnswIndex, SimilarityMetric, Vector};
+
+        let vectors = [
+            vec![1.0, 0.1, 0.0, 0.2],
+            vec![0.0, 1.0, 0.3, 0.0],
+            vec![0.2, 0.0, 1.0, 0.5],
+        ];
+        let mut index = HnswIndex::new(4, SimilarityMetric::Cosine);
+        for (id, v) in vectors.iter().enumerate() {
+            let q = quantize_embedding(&Vector::from_f32(v.clone()), QuantScheme::QInt8).unwrap();
+            index.insert(id as u64, &q).unwrap();
+        }
+
+        let query = quantize_embedding(
+            &Vector::from_f32(vec![0.1, 0.9, 0.2, 0.0]),
+            QuantScheme::QInt8,
+        )
+        .unwrap();
+        assert_eq!(index.query(&query, 1).unwrap()[0].id, 1);
+    }
 }
@@ -53,6 +53,10 @@ fn main() {
         auto_escape_quotes: true,
         normalize_booleans: true,
         normalize_numbers: true,
+        normalize_locale_numbers: true,
+        truncation: Default::default(),
+        extract_payload: true,
+        rules: Default::default(),
     };
     let input = "  F12 = +042  ; F7=TRUE ; F20=User Name  ";
     let sanitized = sanitize_lnmp_text(input, &config);
@@ -60,5 +64,14 @@ fn main() {
     println!("  Output: '{}'", sanitized);
     println!();
 
+    // Example 5: LLM output wrapped in prose and a markdown fence
+    println!("Example 5: Payload Extraction");
+    let config = SanitizationConfig::default();
+    let input = "Here is the record:\n```lnmp\nF12=14532;F7=1\n```\nLet me know!";
+    let sanitized = sanitize_lnmp_text(input, &config);
+    println!("  Input:  {:?}", input);
+    println!("  Output: '{}'", sanitized);
+    println!();
+
     println!("✅ All sanitization examples completed!");
 }
@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// A user-defined repair that runs as part of a [`RuleChain`].
+///
+/// ```
+/// use std::borrow::Cow;
+/// use lnmp_sanitize::{sanitize_lnmp_text, RuleChain, SanitizationConfig, SanitizeRule};
+///
+/// /// Maps `user_id=` keys to `F12=`
+/// struct UserIdAlias;
+///
+/// impl SanitizeRule for UserIdAlias {
+///     fn name(&self) -> &str {
+///         "user_id_alias"
+///     }
+///
+///     fn apply<'a>(&self, input: &'a str) -> Cow<'a, str> {
+///         if input.contains("user_id=") {
+///             Cow::Owned(input.replace("user_id=", "F12="))
+///         } else {
+///             Cow::Borrowed(input)
+///         }
+///     }
+/// }
+///
+/// let config = SanitizationConfig {
+///     rules: RuleChain::default().with_rule_before("extract_payload", UserIdAlias),
+///     ..Default::default()
+/// };
+/// assert_eq!(sanitize_lnmp_text("user_id=42;F7=1", &config), "F12=42;F7=1");
+/// ```
+pub trait SanitizeRule: Send + Sync {
+    /// Name used to order, enable and disable the rule; must not clash with
+    /// [`BUILTIN_RULES`]
+    fn name(&self) -> &str;
+
+    /// Applies the repair, returning the input by reference when nothing changed
+    fn apply<'a>(&self, input: &'a str) -> Cow<'a, str>;
+}
+
+/// Names of the built-in rules, in their default order.
+///
+/// - `extract_payload`: strips markdown fences and surrounding prose
+/// - `truncation`: repairs a record cut off mid-field (opt-in)
+/// - `structural`: whitespace cleanup, escape repair and unterminated quotes
+/// - `locale_numbers`: locale decimal/thousands separators to dot-decimal (opt-in)
+/// - `quote_repair`: second quote/escape repair pass (skipped at the minimal level)
+/// - `auto_quote`: quotes unquoted values containing spaces or quotes
+/// - `normalize`: boolean and number canonicalization
+pub const BUILTIN_RULES: [&str; 7] = [
+    "extract_payload",
+    "truncation",
+    "structural",
+    "locale_numbers",
+    "quote_repair",
+    "auto_quote",
+    "normalize",
+];
+
+/// Built-in rules, in [`BUILTIN_RULES`] order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Builtin {
+    ExtractPayload,
+    Truncation,
+    Structural,
+    LocaleNumbers,
+    QuoteRepair,
+    AutoQuote,
+    Normalize,
+}
+
+impl Builtin {
+    const ALL: [Builtin; 7] = [
+        Builtin::ExtractPayload,
+        Builtin::Truncation,
+        Builtin::Structural,
+        Builtin::LocaleNumbers,
+        Builtin::QuoteRepair,
+        Builtin::AutoQuote,
+        Builtin::Normalize,
+    ];
+
+    fn name(self) -> &'static str {
+        BUILTIN_RULES[self as usize]
+    }
+}
+
+/// A step of a [`RuleChain`].
+#[derive(Clone)]
+pub(crate) enum Stage {
+    Builtin(Builtin),
+    Custom(Arc<dyn SanitizeRule>),
+}
+
+impl Stage {
+    fn name(&self) -> &str {
+        match self {
+            Stage::Builtin(builtin) => builtin.name(),
+            Stage::Custom(rule) => rule.name(),
+        }
+    }
+}
+
+/// Ordered list of built-in and custom rules applied by the sanitizer.
+///
+/// The default chain holds the built-in rules in [`BUILTIN_RULES`] order. Built-in
+/// rules still honour the [`SanitizationConfig`](crate::SanitizationConfig) level and
+/// flags; disabling one by name skips it regardless of the config.
+#[derive(Clone)]
+pub struct RuleChain {
+    stages: Vec<Stage>,
+    disabled: BTreeSet<String>,
+}
+
+impl Default for RuleChain {
+    fn default() -> Self {
+        Self {
+            stages: Builtin::ALL.into_iter().map(Stage::Builtin).collect(),
+            disabled: BTreeSet::new(),
+        }
+    }
+}
+
+impl RuleChain {
+    /// Creates the default chain of built-in rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `rule` to the end of the chain
+    pub fn with_rule(mut self, rule: impl SanitizeRule + 'static) -> Self {
+        self.stages.push(Stage::Custom(Arc::new(rule)));
+        self
+    }
+
+    /// Inserts `rule` before the rule named `name`, or appends it if there is none
+    pub fn with_rule_before(mut self, name: &str, rule: impl SanitizeRule + 'static) -> Self {
+        let at = self.position(name).unwrap_or(self.stages.len());
+        self.stages.insert(at, Stage::Custom(Arc::new(rule)));
+        self
+    }
+
+    /// Inserts `rule` after the rule named `name`, or appends it if there is none
+    pub fn with_rule_after(mut self, name: &str, rule: impl SanitizeRule + 'static) -> Self {
+        let at = self.position(name).map_or(self.stages.len(), |idx| idx + 1);
+        self.stages.insert(at, Stage::Custom(Arc::new(rule)));
+        self
+    }
+
+    /// Disables the rule named `name`
+    pub fn with_disabled(mut self, name: &str) -> Self {
+        self.disabled.insert(name.to_string());
+        self
+    }
+
+    /// Re-enables the rule named `name`
+    pub fn with_enabled(mut self, name: &str) -> Self {
+        self.disabled.remove(name);
+        self
+    }
+
+    /// Returns true if the rule named `name` will run
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// Rule names in execution order, including disabled ones
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(Stage::name)
+    }
+
+    /// Enabled stages in execution order
+    pub(crate) fn enabled(&self) -> impl Iterator<Item = &Stage> {
+        self.stages
+            .iter()
+            .filter(|stage| self.is_enabled(stage.name()))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.name() == name)
+    }
+}
+
+impl fmt::Debug for RuleChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuleChain")
+            .field("stages", &self.names().collect::<Vec<_>>())
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
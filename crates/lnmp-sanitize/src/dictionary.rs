@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use lnmp_sfe::SemanticDictionary;
+
+use crate::chain::SanitizeRule;
+use crate::rule::{SanitizationFix, SanitizationRule};
+use crate::sanitize::SanitizationReport;
+
+/// Repairs keys written as field names (`user_id=14532`) into field IDs (`F12=14532`)
+/// using the names of a [`SemanticDictionary`].
+///
+/// Names are matched case-insensitively at key positions only: the start of the text
+/// or a line, after `;` or inside `{`, outside quoted strings, and followed by `=` or a
+/// `:` type hint. Unknown names are left alone.
+///
+/// ```
+/// use lnmp_sanitize::FieldNameRepair;
+/// use lnmp_sfe::SemanticDictionary;
+///
+/// let mut dict = SemanticDictionary::new();
+/// dict.add_field_name(12, "user_id".to_string());
+/// dict.add_field_name(7, "is_active".to_string());
+///
+/// let report = FieldNameRepair::new(&dict)
+///     .repair("user_id=14532;is_active=1")
+///     .unwrap();
+/// assert_eq!(report.text, "F12=14532;F7=1");
+/// assert_eq!(report.fixes[0].original, "user_id");
+/// assert_eq!(report.fixes[0].fid, Some(12));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FieldNameRepair {
+    /// Lowercased name to every FID that uses it
+    fids: HashMap<String, Vec<u16>>,
+}
+
+/// A key name that maps to more than one field ID in the dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmbiguousFieldName {
+    /// The key as written in the input
+    pub name: String,
+    /// Field IDs sharing the name, in ascending order
+    pub candidates: Vec<u16>,
+}
+
+impl fmt::Display for AmbiguousFieldName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let candidates: Vec<String> = self
+            .candidates
+            .iter()
+            .map(|fid| format!("F{}", fid))
+            .collect();
+        write!(
+            f,
+            "Ambiguous field name '{}': matches {}",
+            self.name,
+            candidates.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for AmbiguousFieldName {}
+
+impl FieldNameRepair {
+    /// Builds the name lookup from the field names of `dict`
+    pub fn new(dict: &SemanticDictionary) -> Self {
+        let mut fids: HashMap<String, Vec<u16>> = HashMap::new();
+        for (fid, name) in dict.field_name_entries() {
+            fids.entry(name.trim().to_ascii_lowercase())
+                .or_default()
+                .push(fid);
+        }
+        for candidates in fids.values_mut() {
+            candidates.sort_unstable();
+        }
+        Self { fids }
+    }
+
+    /// Replaces known key names with their field IDs, reporting each substitution as a
+    /// [`SanitizationRule::FieldName`] fix
+    ///
+    /// Fails on the first name shared by several field IDs.
+    pub fn repair<'a>(&self, input: &'a str) -> Result<SanitizationReport<'a>, AmbiguousFieldName> {
+        let mut fixes = Vec::new();
+        let text = self.rewrite(input, &mut fixes, true)?;
+        Ok(SanitizationReport { text, fixes })
+    }
+
+    /// Rewrites keys; ambiguous names fail when `strict`, otherwise they are left alone.
+    fn rewrite<'a>(
+        &self,
+        input: &'a str,
+        fixes: &mut Vec<SanitizationFix>,
+        strict: bool,
+    ) -> Result<Cow<'a, str>, AmbiguousFieldName> {
+        let mut output = String::new();
+        // End of the input already copied to `output`
+        let mut copied = 0;
+        let mut key_start = true;
+        let mut in_quotes = false;
+        let mut escape_next = false;
+
+        for (idx, ch) in input.char_indices() {
+            if escape_next {
+                escape_next = false;
+                continue;
+            }
+            match ch {
+                '\\' if in_quotes => escape_next = true,
+                '"' => in_quotes = !in_quotes,
+                _ if in_quotes => {}
+                ';' | '\n' | '{' => key_start = true,
+                ' ' | '\t' | '\r' => {}
+                _ if key_start => {
+                    key_start = false;
+                    let Some(name) = key_name(&input[idx..]) else {
+                        continue;
+                    };
+                    let candidates = self.fids.get(&name.to_ascii_lowercase());
+                    let fid = match candidates.map(Vec::as_slice) {
+                        Some(&[fid]) => fid,
+                        Some(candidates) if strict => {
+                            return Err(AmbiguousFieldName {
+                                name: name.to_string(),
+                                candidates: candidates.to_vec(),
+                            });
+                        }
+                        _ => continue,
+                    };
+
+                    let replacement = format!("F{}", fid);
+                    output.push_str(&input[copied..idx]);
+                    output.push_str(&replacement);
+                    copied = idx + name.len();
+                    fixes.push(SanitizationFix {
+                        kind: SanitizationRule::FieldName,
+                        custom_rule: None,
+                        fid: Some(fid),
+                        original: name.to_string(),
+                        replacement,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if copied == 0 {
+            return Ok(Cow::Borrowed(input));
+        }
+        output.push_str(&input[copied..]);
+        Ok(Cow::Owned(output))
+    }
+}
+
+/// The key name at the start of `text` if it is followed by `=` or `:`, unless it is
+/// already a field ID (`F12`).
+fn key_name(text: &str) -> Option<&str> {
+    if !text.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+        return None;
+    }
+    let len = text
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.')))
+        .unwrap_or(text.len());
+    let name = &text[..len];
+    let is_fid =
+        name.len() > 1 && name.starts_with('F') && name[1..].bytes().all(|b| b.is_ascii_digit());
+    match text[len..].chars().next() {
+        Some('=' | ':') if !is_fid => Some(name),
+        _ => None,
+    }
+}
+
+/// In a [`RuleChain`](crate::RuleChain) the repair runs as the `field_names` rule;
+/// ambiguous names are left unchanged so the strict parser still rejects them.
+impl SanitizeRule for FieldNameRepair {
+    fn name(&self) -> &str {
+        "field_names"
+    }
+
+    fn apply<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        self.rewrite(input, &mut Vec::new(), false)
+            .unwrap_or(Cow::Borrowed(input))
+    }
+}
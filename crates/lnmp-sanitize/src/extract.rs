@@ -0,0 +1,173 @@
+/// Locates the LNMP payload inside LLM output that mixes it with prose or markdown.
+///
+/// A fenced block (```` ``` ```` or `~~~`) tagged `lnmp`, or else the first fenced block
+/// containing a field assignment, is unwrapped first. Inside it (or the whole input when
+/// there is no such block) the payload runs from the first field assignment
+/// (`F<fid>=` or `F<fid>:<hint>=`) to the end of the line holding the last one, extended
+/// until open quotes and brackets are closed. `#` comment lines directly above the first
+/// field are kept. Leading prose such as
+/// `Here is the record:` and trailing commentary are dropped.
+///
+/// Returns `input` unchanged when no field assignment is found or only whitespace
+/// surrounds the payload.
+///
+/// ```
+/// use lnmp_sanitize::extract_lnmp_payload;
+///
+/// let reply = "Here is the record:\n```lnmp\nF1=1\nF2=\"ok\"\n```\nLet me know!";
+/// assert_eq!(extract_lnmp_payload(reply), "F1=1\nF2=\"ok\"");
+/// assert_eq!(extract_lnmp_payload("Sure: F7=1;F12=14532"), "F7=1;F12=14532");
+/// ```
+pub fn extract_lnmp_payload(input: &str) -> &str {
+    let body = fenced_block(input).unwrap_or(input);
+    let Some(start) = field_starts(body).next() else {
+        return if body.len() == input.len() {
+            input
+        } else {
+            body
+        };
+    };
+    let start = include_leading_comments(body, start);
+    let last = field_starts(body).last().unwrap_or(start);
+    let last_line_end = body[last..].find('\n').map_or(body.len(), |pos| last + pos);
+    let end = payload_end(body, start, last_line_end);
+
+    let payload = &body[start..end];
+    let prefix = &input[..payload.as_ptr() as usize - input.as_ptr() as usize];
+    let suffix = &input[prefix.len() + payload.len()..];
+    if prefix.trim().is_empty() && suffix.trim().is_empty() {
+        input
+    } else {
+        payload
+    }
+}
+
+/// Contents of the preferred fenced block, without the fence lines.
+fn fenced_block(input: &str) -> Option<&str> {
+    let mut fallback = None;
+    let mut open: Option<(&str, &str, usize)> = None;
+    let mut offset = 0;
+
+    for line in input.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        match open {
+            None => {
+                let marker = ["```", "~~~"]
+                    .into_iter()
+                    .find(|marker| trimmed.starts_with(marker));
+                if let Some(marker) = marker {
+                    let info = trimmed.trim_start_matches(marker.chars().next().unwrap());
+                    open = Some((marker, info.trim(), offset));
+                }
+            }
+            Some((marker, info, content_start)) if trimmed.starts_with(marker) => {
+                let content = trim_newlines(&input[content_start..line_start]);
+                if info.eq_ignore_ascii_case("lnmp") {
+                    return Some(content);
+                }
+                if fallback.is_none() && field_starts(content).next().is_some() {
+                    fallback = Some(content);
+                }
+                open = None;
+            }
+            Some(_) => {}
+        }
+    }
+
+    // An unterminated fence (truncated output) runs to the end of the input
+    if let Some((_, info, content_start)) = open {
+        let content = trim_newlines(&input[content_start..]);
+        if info.eq_ignore_ascii_case("lnmp")
+            || (fallback.is_none() && field_starts(content).next().is_some())
+        {
+            return Some(content);
+        }
+    }
+    fallback
+}
+
+fn trim_newlines(text: &str) -> &str {
+    text.trim_matches(['\n', '\r'])
+}
+
+/// Byte offsets of field assignments that start a word (`F12=`, `F7:b=`).
+pub(crate) fn field_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).filter(move |&i| {
+        bytes[i] == b'F'
+            && (i == 0 || bytes[i - 1].is_ascii_whitespace())
+            && is_assignment(&bytes[i + 1..])
+    })
+}
+
+/// Returns true if `rest` (the text after `F`) is `<digits>=` or `<digits>:<hint>=`.
+fn is_assignment(rest: &[u8]) -> bool {
+    let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+        return false;
+    }
+    match rest.get(digits) {
+        Some(b'=') => true,
+        Some(b':') => {
+            let hint = &rest[digits + 1..];
+            let len = hint
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric())
+                .count();
+            len > 0 && hint.get(len) == Some(&b'=')
+        }
+        _ => false,
+    }
+}
+
+/// Moves `start` back over `#` comment lines directly above the first field.
+fn include_leading_comments(body: &str, start: usize) -> usize {
+    let line_start = body[..start].rfind('\n').map_or(0, |pos| pos + 1);
+    if !body[line_start..start].trim().is_empty() {
+        return start;
+    }
+
+    let mut start = line_start;
+    while start > 0 {
+        let prev_start = body[..start - 1].rfind('\n').map_or(0, |pos| pos + 1);
+        if !body[prev_start..start].trim_start().starts_with('#') {
+            break;
+        }
+        start = prev_start;
+    }
+    start
+}
+
+/// End of the payload: the first line end at or after `min_end` where all quotes and
+/// brackets opened since `start` are closed.
+fn payload_end(body: &str, start: usize, min_end: usize) -> usize {
+    let mut depth = 0i32;
+    let mut in_quotes = false;
+    let mut escape_next = false;
+
+    for (idx, ch) in body[start..].char_indices() {
+        let idx = start + idx;
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_quotes => escape_next = true,
+            '"' => in_quotes = !in_quotes,
+            '{' | '[' if !in_quotes => depth += 1,
+            '}' | ']' if !in_quotes => depth -= 1,
+            '\n' if idx >= min_end && depth <= 0 && !in_quotes => {
+                return trim_payload_end(body, start, idx);
+            }
+            _ => {}
+        }
+    }
+    trim_payload_end(body, start, body.len())
+}
+
+fn trim_payload_end(body: &str, start: usize, end: usize) -> usize {
+    start + body[start..end].trim_end().len()
+}
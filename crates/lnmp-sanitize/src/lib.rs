@@ -1,13 +1,39 @@
 //! Lenient sanitization layer for LNMP text inputs.
 //!
-//! The sanitizer performs lightweight whitespace normalization, quote/escape repair,
-//! and optional boolean/number canonicalization before handing text to the strict
-//! LNMP parser.
+//! The sanitizer extracts the LNMP payload from markdown fences or surrounding prose,
+//! then performs lightweight whitespace normalization, quote/escape repair, and optional
+//! boolean/number canonicalization before handing text to the strict LNMP parser.
+//!
+//! These repairs run as a [`RuleChain`] of named rules that custom [`SanitizeRule`]s can
+//! be inserted into, and any rule can be disabled by name. [`StreamingSanitizer`] applies
+//! the same repairs incrementally to chunked input.
+//!
+//! With the `dictionary` feature, [`FieldNameRepair`] maps keys written as field names
+//! (`user_id=`) back to field IDs using an `lnmp-sfe` semantic dictionary.
+//!
+//! With the `log` feature each applied repair is logged with its
+//! [`SanitizationRule`] id, the affected field ID and the text before and after.
 
+mod chain;
+#[cfg(feature = "dictionary")]
+mod dictionary;
+mod extract;
+mod locale;
 mod mode;
+mod rule;
 mod sanitize;
+mod stream;
 #[cfg(test)]
 mod tests;
 
-pub use crate::mode::SanitizationLevel;
-pub use crate::sanitize::{sanitize_lnmp_text, SanitizationConfig};
+pub use crate::chain::{RuleChain, SanitizeRule, BUILTIN_RULES};
+#[cfg(feature = "dictionary")]
+pub use crate::dictionary::{AmbiguousFieldName, FieldNameRepair};
+pub use crate::extract::extract_lnmp_payload;
+pub use crate::locale::normalize_number;
+pub use crate::mode::{SanitizationLevel, TruncationRepair};
+pub use crate::rule::{SanitizationFix, SanitizationRule};
+pub use crate::sanitize::{
+    sanitize_lnmp_text, sanitize_with_report, SanitizationConfig, SanitizationReport,
+};
+pub use crate::stream::StreamingSanitizer;
@@ -0,0 +1,79 @@
+/// Dot-decimal form of a locale-formatted number such as `3,14`, `1.234,56`,
+/// `1,234.56`, `1 234,56` or `1'234.56`.
+///
+/// Interpretation rules:
+/// - When both `.` and `,` appear, the last one is the decimal separator.
+/// - A single `,` is a decimal separator unless it is followed by exactly three
+///   digits (`1,234` is read as one thousand two hundred thirty-four).
+/// - A single `.` is a decimal separator.
+/// - Repeated `.` or `,`, spaces, no-break spaces and `'` are group separators;
+///   the first group has 1-3 digits, later groups exactly three, all with the
+///   same separator.
+///
+/// Returns `None` if `literal` is not a number or is already canonical.
+pub fn normalize_number(literal: &str) -> Option<String> {
+    let (sign, body) = match literal.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", literal.strip_prefix('+').unwrap_or(literal)),
+    };
+    if body.is_empty()
+        || !body.starts_with(|c: char| c.is_ascii_digit())
+        || !body.ends_with(|c: char| c.is_ascii_digit())
+        || !body.chars().all(|c| c.is_ascii_digit() || is_separator(c))
+        || !body.chars().any(is_separator)
+    {
+        return None;
+    }
+
+    let last_dot = body.rfind('.');
+    let last_comma = body.rfind(',');
+    let decimal = match (last_dot, last_comma) {
+        (Some(d), Some(c)) => Some(d.max(c)),
+        (None, Some(c)) if body.matches(',').count() == 1 && digits_after(body, c) != 3 => Some(c),
+        (Some(d), None) if body.matches('.').count() == 1 => Some(d),
+        _ => None,
+    };
+
+    let (int_part, frac_part) = match decimal {
+        Some(pos) => (&body[..pos], Some(&body[pos + 1..])),
+        None => (body, None),
+    };
+    if frac_part.is_some_and(|f| !f.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+
+    // Validate digit grouping of the integer part.
+    let groups: Vec<&str> = int_part.split(is_separator).collect();
+    if groups.len() > 1 {
+        let first_ok = (1..=3).contains(&groups[0].len());
+        if !first_ok || groups[1..].iter().any(|g| g.len() != 3) {
+            return None;
+        }
+        // Group separators must be consistent within the integer part.
+        let mut seps = int_part.chars().filter(|c| is_separator(*c));
+        let first = seps.next();
+        if seps.any(|c| Some(c) != first) {
+            return None;
+        }
+    }
+
+    let mut normalized = String::from(sign);
+    normalized.extend(groups.iter().copied());
+    if let Some(frac) = frac_part {
+        normalized.push('.');
+        normalized.push_str(frac);
+    }
+    let canonical_input = format!("{}{}", sign, body);
+    (normalized != canonical_input).then_some(normalized)
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '.' | ',' | ' ' | '\'' | '\u{a0}' | '\u{202f}')
+}
+
+fn digits_after(body: &str, pos: usize) -> usize {
+    body[pos + 1..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .count()
+}
@@ -7,3 +7,16 @@ pub enum SanitizationLevel {
     /// Aggressive best-effort repair for LLM outputs
     Aggressive,
 }
+
+/// How to repair a record cut off mid-field, e.g. `F23=["admin","de` from a
+/// truncated LLM stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TruncationRepair {
+    /// Leave truncated input unchanged
+    #[default]
+    Off,
+    /// Close the dangling quote and any open brackets/braces, keeping the partial value
+    Close,
+    /// Drop the incomplete trailing field
+    DropIncomplete,
+}
@@ -0,0 +1,166 @@
+/// Identifies a lenient-mode transformation applied by the sanitizer.
+///
+/// With the `log` feature every applied rule is logged at `debug` level under the
+/// `lnmp::sanitize` target as `rule=<id> fid=<F..|-> before=".." after=".."`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SanitizationRule {
+    /// Redundant whitespace was trimmed or collapsed
+    Whitespace,
+    /// A dangling or unknown escape sequence was repaired
+    EscapeRepair,
+    /// An unterminated quoted string was closed
+    CloseQuote,
+    /// An unquoted value containing spaces or quotes was wrapped in quotes
+    AutoQuote,
+    /// A boolean literal (`true`/`yes`/`false`/`no`) was rewritten to `1`/`0`
+    BooleanLiteral,
+    /// Leading zeros were stripped from an integer
+    LeadingZeros,
+    /// Markdown fences or surrounding prose were stripped from the payload
+    ExtractPayload,
+    /// A locale-formatted number (`3,14`, `1.234,56`, `1 234`) was rewritten to
+    /// dot-decimal form
+    LocaleNumber,
+    /// A record cut off mid-field was closed or its incomplete trailing field dropped
+    Truncation,
+    /// A dictionary field name used as a key was replaced by its field ID
+    FieldName,
+    /// A user-registered [`SanitizeRule`](crate::SanitizeRule) changed the text
+    Custom,
+}
+
+impl SanitizationRule {
+    /// Stable identifier used in log records
+    pub fn id(&self) -> &'static str {
+        match self {
+            SanitizationRule::Whitespace => "sanitize.whitespace",
+            SanitizationRule::EscapeRepair => "sanitize.escape_repair",
+            SanitizationRule::CloseQuote => "sanitize.close_quote",
+            SanitizationRule::AutoQuote => "sanitize.auto_quote",
+            SanitizationRule::BooleanLiteral => "sanitize.boolean_literal",
+            SanitizationRule::LeadingZeros => "sanitize.leading_zeros",
+            SanitizationRule::ExtractPayload => "sanitize.extract_payload",
+            SanitizationRule::LocaleNumber => "sanitize.locale_number",
+            SanitizationRule::Truncation => "sanitize.truncation",
+            SanitizationRule::FieldName => "sanitize.field_name",
+            SanitizationRule::Custom => "sanitize.custom",
+        }
+    }
+}
+
+/// A repair applied by the sanitizer, as returned by
+/// [`sanitize_with_report`](crate::sanitize_with_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizationFix {
+    /// Rule that applied the repair
+    pub kind: SanitizationRule,
+    /// Name of the custom rule, for [`SanitizationRule::Custom`] fixes
+    pub custom_rule: Option<String>,
+    /// Field ID of the repaired value, when known
+    pub fid: Option<u16>,
+    /// Text before the repair (the whole text for whitespace cleanup)
+    pub original: String,
+    /// Text after the repair
+    pub replacement: String,
+}
+
+/// Receives every applied rule: logs it and, when collecting, records it.
+pub(crate) struct Repairs {
+    fixes: Option<Vec<SanitizationFix>>,
+}
+
+impl Repairs {
+    /// Only logs applied rules.
+    pub(crate) fn logging_only() -> Self {
+        Self { fixes: None }
+    }
+
+    /// Logs and records applied rules.
+    pub(crate) fn collecting() -> Self {
+        Self {
+            fixes: Some(Vec::new()),
+        }
+    }
+
+    /// Recorded fixes, in the order they were applied.
+    pub(crate) fn into_fixes(self) -> Vec<SanitizationFix> {
+        self.fixes.unwrap_or_default()
+    }
+
+    /// Reports an applied rule; `fid` is only evaluated when logging is enabled
+    /// or fixes are collected.
+    #[inline]
+    pub(crate) fn report(
+        &mut self,
+        rule: SanitizationRule,
+        fid: impl FnOnce() -> Option<u16>,
+        before: &str,
+        after: &str,
+    ) {
+        self.record(rule, None, fid, before, after);
+    }
+
+    /// Reports a change made by the custom rule `name`.
+    pub(crate) fn report_custom(&mut self, name: &str, before: &str, after: &str) {
+        self.record(SanitizationRule::Custom, Some(name), || None, before, after);
+    }
+
+    fn record(
+        &mut self,
+        rule: SanitizationRule,
+        custom_rule: Option<&str>,
+        fid: impl FnOnce() -> Option<u16>,
+        before: &str,
+        after: &str,
+    ) {
+        #[cfg(feature = "log")]
+        let log_enabled = log::log_enabled!(target: "lnmp::sanitize", log::Level::Debug);
+        #[cfg(not(feature = "log"))]
+        let log_enabled = false;
+
+        if !log_enabled && self.fixes.is_none() {
+            return;
+        }
+        let fid = fid();
+
+        #[cfg(feature = "log")]
+        if log_enabled {
+            let fid = fid.map_or_else(|| "-".to_string(), |fid| format!("F{}", fid));
+            log::debug!(
+                target: "lnmp::sanitize",
+                "rule={} fid={} before={:?} after={:?}",
+                custom_rule.unwrap_or(rule.id()),
+                fid,
+                before,
+                after
+            );
+        }
+
+        if let Some(fixes) = &mut self.fixes {
+            fixes.push(SanitizationFix {
+                kind: rule,
+                custom_rule: custom_rule.map(str::to_string),
+                fid,
+                original: before.to_string(),
+                replacement: after.to_string(),
+            });
+        }
+    }
+}
+
+/// Field ID of the value being written at the end of `text` (e.g. `F1=1;F12="abc`).
+pub(crate) fn value_fid(text: &str) -> Option<u16> {
+    let pos = text.rfind('=')?;
+    trailing_fid(&text[..pos])
+}
+
+/// Parses the field ID of the key at the end of `text` (e.g. `F1=1;F12` or `F12:i`).
+fn trailing_fid(text: &str) -> Option<u16> {
+    let key = text
+        .rsplit([';', '\n', '{', '[', ','])
+        .next()
+        .unwrap_or(text)
+        .trim();
+    let key = key.split(':').next().unwrap_or(key);
+    key.strip_prefix('F')?.parse().ok()
+}
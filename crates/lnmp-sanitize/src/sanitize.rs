@@ -1,6 +1,12 @@
 use std::borrow::Cow;
 
-use crate::mode::SanitizationLevel;
+use lnmp_core::coercion::CoercionRules;
+
+use crate::chain::{Builtin, RuleChain, Stage};
+use crate::extract::extract_lnmp_payload;
+use crate::locale::normalize_number;
+use crate::mode::{SanitizationLevel, TruncationRepair};
+use crate::rule::{value_fid, Repairs, SanitizationFix, SanitizationRule};
 
 /// Configuration options for sanitization.
 #[derive(Debug, Clone)]
@@ -15,6 +21,16 @@ pub struct SanitizationConfig {
     pub normalize_booleans: bool,
     /// Normalize simple numeric forms (e.g., remove leading zeros)
     pub normalize_numbers: bool,
+    /// Rewrite locale-formatted numeric values (`3,14`, `1.234,56`, `1 234`) to
+    /// dot-decimal form; never touches quoted strings or arrays
+    pub normalize_locale_numbers: bool,
+    /// Repair of records cut off mid-field (off by default)
+    pub truncation: TruncationRepair,
+    /// Strip markdown fences and surrounding prose before sanitizing
+    /// (see [`extract_lnmp_payload`])
+    pub extract_payload: bool,
+    /// Ordered built-in and custom rules to run
+    pub rules: RuleChain,
 }
 
 impl Default for SanitizationConfig {
@@ -25,6 +41,10 @@ impl Default for SanitizationConfig {
             auto_escape_quotes: true,
             normalize_booleans: true,
             normalize_numbers: false,
+            normalize_locale_numbers: false,
+            truncation: TruncationRepair::Off,
+            extract_payload: true,
+            rules: RuleChain::default(),
         }
     }
 }
@@ -32,39 +52,159 @@ impl Default for SanitizationConfig {
 /// Leniently sanitizes LNMP-like text. When no changes are required the input is returned
 /// by reference to avoid allocations.
 pub fn sanitize_lnmp_text<'a>(input: &'a str, config: &SanitizationConfig) -> Cow<'a, str> {
-    let mut changed = false;
+    sanitize(input, config, &mut Repairs::logging_only())
+}
 
-    // Pass 1: whitespace/structural cleanup
-    let pass1 = structural_cleanup(input, config, &mut changed);
+/// Sanitized text together with the repairs that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizationReport<'a> {
+    /// The sanitized text, borrowed when no repair was needed
+    pub text: Cow<'a, str>,
+    /// Applied repairs, in the order they were applied
+    pub fixes: Vec<SanitizationFix>,
+}
 
-    // Pass 2: quote/escape repair + optional auto-quoting
-    let pass2 = if config.level == SanitizationLevel::Minimal {
-        pass1
-    } else {
-        let quote_fixed = quote_and_escape_repair(&pass1, config, &mut changed);
-        if config.auto_quote_strings {
-            Cow::Owned(auto_quote_unquoted_values(
-                quote_fixed.as_ref(),
-                &mut changed,
-            ))
-        } else {
-            quote_fixed
-        }
-    };
+impl SanitizationReport<'_> {
+    /// Returns true if the input needed no repair
+    pub fn is_clean(&self) -> bool {
+        self.fixes.is_empty()
+    }
 
-    // Pass 3: semantic normalization (Aggressive only)
-    let pass3 = if config.level == SanitizationLevel::Aggressive
-        && (config.normalize_booleans || config.normalize_numbers)
-    {
-        Cow::Owned(normalize_tokens(&pass2, config, &mut changed))
-    } else {
-        pass2
-    };
+    /// Number of repairs applied by `rule`
+    pub fn count(&self, rule: SanitizationRule) -> usize {
+        self.fixes.iter().filter(|fix| fix.kind == rule).count()
+    }
+}
 
-    if changed {
-        Cow::Owned(pass3.into_owned())
-    } else {
-        Cow::Borrowed(input)
+/// Like [`sanitize_lnmp_text`], but also returns the list of applied repairs so
+/// callers can log, audit, or reject inputs that needed aggressive repair.
+///
+/// ```
+/// use lnmp_sanitize::{sanitize_with_report, SanitizationConfig, SanitizationRule};
+///
+/// let report = sanitize_with_report("F1=\"hello", &SanitizationConfig::default());
+/// assert_eq!(report.text, "F1=\"hello\"");
+/// assert_eq!(report.fixes[0].kind, SanitizationRule::CloseQuote);
+/// assert_eq!(report.fixes[0].fid, Some(1));
+/// assert_eq!(report.fixes[0].original, "\"hello");
+/// assert_eq!(report.fixes[0].replacement, "\"hello\"");
+/// ```
+pub fn sanitize_with_report<'a>(
+    input: &'a str,
+    config: &SanitizationConfig,
+) -> SanitizationReport<'a> {
+    let mut repairs = Repairs::collecting();
+    let text = sanitize(input, config, &mut repairs);
+    SanitizationReport {
+        text,
+        fixes: repairs.into_fixes(),
+    }
+}
+
+pub(crate) fn sanitize<'a>(
+    input: &'a str,
+    config: &SanitizationConfig,
+    repairs: &mut Repairs,
+) -> Cow<'a, str> {
+    let mut text = Cow::Borrowed(input);
+    for stage in config.rules.enabled() {
+        text = match stage {
+            Stage::Builtin(builtin) => apply_builtin(*builtin, text, config, repairs),
+            Stage::Custom(rule) => chain(text, |input: &str| {
+                let output = rule.apply(input);
+                if output != input {
+                    repairs.report_custom(rule.name(), input, &output);
+                }
+                output
+            }),
+        };
+    }
+    text
+}
+
+/// Applies `pass` to `text`, keeping the result borrowed while nothing has changed.
+fn chain<'a>(
+    text: Cow<'a, str>,
+    pass: impl for<'b> FnOnce(&'b str) -> Cow<'b, str>,
+) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(input) => pass(input),
+        Cow::Owned(input) => match pass(&input) {
+            // Borrowed output is either the whole input or a slice of it
+            Cow::Borrowed(output) if output.len() == input.len() => Cow::Owned(input),
+            output => Cow::Owned(output.into_owned()),
+        },
+    }
+}
+
+/// Runs a built-in rule if the config level and flags allow it.
+fn apply_builtin<'a>(
+    builtin: Builtin,
+    text: Cow<'a, str>,
+    config: &SanitizationConfig,
+    repairs: &mut Repairs,
+) -> Cow<'a, str> {
+    let minimal = config.level == SanitizationLevel::Minimal;
+    match builtin {
+        // Payload extraction from fenced/prose-wrapped LLM output
+        Builtin::ExtractPayload if config.extract_payload => chain(text, |input: &str| {
+            let payload = extract_lnmp_payload(input);
+            if payload.len() != input.len() {
+                repairs.report(SanitizationRule::ExtractPayload, || None, input, payload);
+            }
+            Cow::Borrowed(payload)
+        }),
+        // Truncated-record repair, before the structural pass closes open quotes
+        Builtin::Truncation if config.truncation != TruncationRepair::Off => {
+            chain(text, |input: &str| {
+                repair_truncation(input, config.truncation, repairs)
+            })
+        }
+        // Whitespace/structural cleanup
+        Builtin::Structural => chain(text, |input: &str| {
+            structural_cleanup(input, config, &mut false, repairs)
+        }),
+        // Locale number normalization (opt-in), before auto-quoting sees the spaces
+        Builtin::LocaleNumbers if config.normalize_locale_numbers => chain(text, |input: &str| {
+            let mut changed = false;
+            let output = normalize_locale_numbers(input, &mut changed, repairs);
+            if changed {
+                Cow::Owned(output)
+            } else {
+                Cow::Borrowed(input)
+            }
+        }),
+        // Quote/escape repair + optional auto-quoting
+        Builtin::QuoteRepair if !minimal => chain(text, |input: &str| {
+            quote_and_escape_repair(input, config, &mut false, repairs)
+        }),
+        Builtin::AutoQuote if !minimal && config.auto_quote_strings => {
+            chain(text, |input: &str| {
+                let mut changed = false;
+                let output = auto_quote_unquoted_values(input, &mut changed, repairs);
+                if changed {
+                    Cow::Owned(output)
+                } else {
+                    Cow::Borrowed(input)
+                }
+            })
+        }
+        // Semantic normalization (Aggressive only)
+        Builtin::Normalize
+            if config.level == SanitizationLevel::Aggressive
+                && (config.normalize_booleans || config.normalize_numbers) =>
+        {
+            chain(text, |input: &str| {
+                let mut changed = false;
+                let output = normalize_tokens(input, config, &mut changed, repairs);
+                if changed {
+                    Cow::Owned(output)
+                } else {
+                    Cow::Borrowed(input)
+                }
+            })
+        }
+        _ => text,
     }
 }
 
@@ -72,6 +212,7 @@ fn structural_cleanup<'a>(
     input: &'a str,
     config: &SanitizationConfig,
     changed: &mut bool,
+    repairs: &mut Repairs,
 ) -> Cow<'a, str> {
     // Minimal mode: only newline normalization and trailing space trim.
     if config.level == SanitizationLevel::Minimal {
@@ -89,12 +230,14 @@ fn structural_cleanup<'a>(
         }
 
         if *changed {
+            repairs.report(SanitizationRule::Whitespace, || None, input, &output);
             return Cow::Owned(output);
         }
         return Cow::Borrowed(input);
     }
 
     let mut output = String::with_capacity(input.len());
+    let mut whitespace = false;
     let mut in_quotes = false;
     let mut escape_next = false;
     let mut last_emitted: Option<char> = None;
@@ -121,6 +264,12 @@ fn structural_cleanup<'a>(
                     }
                     None => {
                         output.push('\\');
+                        repairs.report(
+                            SanitizationRule::EscapeRepair,
+                            || value_fid(&output),
+                            "\\",
+                            "\\\\",
+                        );
                         *changed = true;
                     }
                     _ => {}
@@ -137,7 +286,7 @@ fn structural_cleanup<'a>(
                 last_emitted = Some(';');
                 while matches!(chars.peek(), Some(c) if c.is_whitespace()) {
                     chars.next();
-                    *changed = true;
+                    whitespace = true;
                 }
             }
             ',' if !in_quotes => {
@@ -145,19 +294,19 @@ fn structural_cleanup<'a>(
                 last_emitted = Some(',');
                 while matches!(chars.peek(), Some(c) if c.is_whitespace()) {
                     chars.next();
-                    *changed = true;
+                    whitespace = true;
                 }
             }
             '\n' => {
                 while output.ends_with(' ') || output.ends_with('\t') {
                     output.pop();
-                    *changed = true;
+                    whitespace = true;
                 }
                 output.push('\n');
                 last_emitted = Some('\n');
             }
             '\r' => {
-                *changed = true;
+                whitespace = true;
                 output.push('\n');
                 last_emitted = Some('\n');
             }
@@ -177,12 +326,12 @@ fn structural_cleanup<'a>(
                 );
 
                 if prev_is_boundary || next_is_boundary {
-                    *changed = true;
+                    whitespace = true;
                     continue;
                 }
 
                 if last_emitted == Some(' ') {
-                    *changed = true;
+                    whitespace = true;
                     continue;
                 }
 
@@ -197,7 +346,12 @@ fn structural_cleanup<'a>(
     }
 
     if in_quotes && config.auto_escape_quotes {
-        output.push('"');
+        close_quote(&mut output, repairs);
+        *changed = true;
+    }
+
+    if whitespace {
+        repairs.report(SanitizationRule::Whitespace, || None, input, &output);
         *changed = true;
     }
 
@@ -208,10 +362,23 @@ fn structural_cleanup<'a>(
     }
 }
 
+/// Closes the unterminated quoted string at the end of `output`.
+fn close_quote(output: &mut String, repairs: &mut Repairs) {
+    let start = output.rfind('"').unwrap_or(0);
+    output.push('"');
+    repairs.report(
+        SanitizationRule::CloseQuote,
+        || value_fid(&output[..start]),
+        &output[start..output.len() - 1],
+        &output[start..],
+    );
+}
+
 fn quote_and_escape_repair<'a>(
     input: &'a str,
     config: &SanitizationConfig,
     changed: &mut bool,
+    repairs: &mut Repairs,
 ) -> Cow<'a, str> {
     let mut output = String::with_capacity(input.len());
     let mut in_quotes = false;
@@ -240,7 +407,7 @@ fn quote_and_escape_repair<'a>(
     }
 
     if in_quotes && config.auto_escape_quotes {
-        output.push('"');
+        close_quote(&mut output, repairs);
         *changed = true;
     }
 
@@ -251,7 +418,135 @@ fn quote_and_escape_repair<'a>(
     }
 }
 
-fn auto_quote_unquoted_values(input: &str, changed: &mut bool) -> String {
+/// Repairs a record cut off mid-field: closes the dangling quote and open
+/// brackets/braces, or drops the incomplete trailing top-level field.
+fn repair_truncation<'a>(
+    input: &'a str,
+    mode: TruncationRepair,
+    repairs: &mut Repairs,
+) -> Cow<'a, str> {
+    let mut in_quotes = false;
+    let mut escape_next = false;
+    let mut closers = Vec::new();
+    // Start of the current top-level field
+    let mut field_start = 0;
+
+    for (idx, ch) in input.char_indices() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_quotes => escape_next = true,
+            '"' => in_quotes = !in_quotes,
+            _ if in_quotes => {}
+            '[' => closers.push(']'),
+            '{' => closers.push('}'),
+            ']' | '}' if closers.last() == Some(&ch) => {
+                closers.pop();
+            }
+            ';' | '\n' if closers.is_empty() => field_start = idx + 1,
+            _ => {}
+        }
+    }
+
+    if !in_quotes && closers.is_empty() {
+        return Cow::Borrowed(input);
+    }
+
+    let field = &input[field_start..];
+    let (output, replacement) = match mode {
+        TruncationRepair::Off => return Cow::Borrowed(input),
+        TruncationRepair::Close => {
+            let mut output = input.to_string();
+            if escape_next {
+                // Dangling escape at the cut
+                output.pop();
+            }
+            if in_quotes {
+                output.push('"');
+            }
+            for closer in closers.into_iter().rev() {
+                let kept = output
+                    .trim_end_matches([',', ';', ' ', '\t', '\r', '\n'])
+                    .len();
+                output.truncate(kept);
+                output.push(closer);
+            }
+            let replacement = output[field_start..].to_string();
+            (output, replacement)
+        }
+        TruncationRepair::DropIncomplete => {
+            let kept = input[..field_start].trim_end_matches([';', ' ', '\t', '\r', '\n']);
+            (kept.to_string(), String::new())
+        }
+    };
+
+    repairs.report(
+        SanitizationRule::Truncation,
+        || value_fid(&field[..=field.find('=')?]),
+        field,
+        &replacement,
+    );
+    Cow::Owned(output)
+}
+
+/// Rewrites locale-formatted numeric values to dot-decimal form with
+/// [`normalize_number`]. Quoted strings and array items are left untouched, since
+/// `,` separates array items.
+fn normalize_locale_numbers(input: &str, changed: &mut bool, repairs: &mut Repairs) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut in_quotes = false;
+    let mut escape_next = false;
+    let mut array_depth = 0usize;
+    let mut idx = 0;
+
+    while let Some(ch) = input[idx..].chars().next() {
+        idx += ch.len_utf8();
+        output.push(ch);
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+
+        match ch {
+            '\\' if in_quotes => escape_next = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => array_depth += 1,
+            ']' if !in_quotes => array_depth = array_depth.saturating_sub(1),
+            '=' if !in_quotes && array_depth == 0 => {
+                let len = input[idx..]
+                    .find([';', '\n', '}', ']'])
+                    .unwrap_or(input.len() - idx);
+                let value = &input[idx..idx + len];
+                if value.contains(['"', '[', '{']) {
+                    continue;
+                }
+                let trimmed = value.trim();
+                if let Some(canonical) = normalize_number(trimmed) {
+                    let leading = value.len() - value.trim_start().len();
+                    output.push_str(&value[..leading]);
+                    let field_end = output.len();
+                    output.push_str(&canonical);
+                    output.push_str(&value[leading + trimmed.len()..]);
+                    repairs.report(
+                        SanitizationRule::LocaleNumber,
+                        || value_fid(&output[..field_end]),
+                        trimmed,
+                        &canonical,
+                    );
+                    idx += len;
+                    *changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    output
+}
+
+fn auto_quote_unquoted_values(input: &str, changed: &mut bool, repairs: &mut Repairs) -> String {
     let mut output = String::with_capacity(input.len());
     let mut iter = input.char_indices().peekable();
     while let Some((idx, ch)) = iter.next() {
@@ -291,7 +586,9 @@ fn auto_quote_unquoted_values(input: &str, changed: &mut bool) -> String {
 
             let value = &input[value_start..value_end];
             let trimmed = value.trim();
-            let starts_structural = trimmed.starts_with('[') || trimmed.starts_with('{');
+            // Arrays, nested records and `E[dim,dtype,base64]` embeddings
+            let starts_structural =
+                trimmed.starts_with('[') || trimmed.starts_with('{') || trimmed.starts_with("E[");
             let needs_quotes = !trimmed.is_empty()
                 && !trimmed.starts_with('"')
                 && !starts_structural
@@ -310,9 +607,16 @@ fn auto_quote_unquoted_values(input: &str, changed: &mut bool) -> String {
                         _ => escaped.push(ch),
                     }
                 }
+                let field_end = output.len();
                 output.push('"');
                 output.push_str(escaped.trim());
                 output.push('"');
+                repairs.report(
+                    SanitizationRule::AutoQuote,
+                    || value_fid(&output[..field_end]),
+                    value,
+                    &output[field_end..],
+                );
                 *changed = true;
             } else {
                 output.push_str(value);
@@ -332,7 +636,12 @@ fn auto_quote_unquoted_values(input: &str, changed: &mut bool) -> String {
     output
 }
 
-fn normalize_tokens(input: &str, config: &SanitizationConfig, changed: &mut bool) -> String {
+fn normalize_tokens(
+    input: &str,
+    config: &SanitizationConfig,
+    changed: &mut bool,
+    repairs: &mut Repairs,
+) -> String {
     let mut out = String::with_capacity(input.len());
     let mut token = String::new();
     let mut in_quotes = false;
@@ -352,7 +661,7 @@ fn normalize_tokens(input: &str, config: &SanitizationConfig, changed: &mut bool
         }
 
         if ch == '"' {
-            flush_token(&mut token, &mut out, config, changed);
+            flush_token(&mut token, &mut out, config, changed, repairs);
             in_quotes = !in_quotes;
             out.push('"');
             continue;
@@ -366,12 +675,12 @@ fn normalize_tokens(input: &str, config: &SanitizationConfig, changed: &mut bool
         if ch.is_ascii_alphanumeric() || ch == '-' {
             token.push(ch);
         } else {
-            flush_token(&mut token, &mut out, config, changed);
+            flush_token(&mut token, &mut out, config, changed, repairs);
             out.push(ch);
         }
     }
 
-    flush_token(&mut token, &mut out, config, changed);
+    flush_token(&mut token, &mut out, config, changed, repairs);
     out
 }
 
@@ -380,18 +689,19 @@ fn flush_token(
     out: &mut String,
     config: &SanitizationConfig,
     changed: &mut bool,
+    repairs: &mut Repairs,
 ) {
     if token.is_empty() {
         return;
     }
 
-    let mut replacement: Option<String> = None;
+    let mut replacement: Option<(SanitizationRule, String)> = None;
 
     if config.normalize_booleans {
-        match token.to_ascii_lowercase().as_str() {
-            "true" | "yes" => replacement = Some("1".to_string()),
-            "false" | "no" => replacement = Some("0".to_string()),
-            _ => {}
+        // Same truth table as the parser's loose mode and the JSON bridge
+        if let Some(b) = CoercionRules::lenient().bool_from_text(token) {
+            let canonical = if b { "1" } else { "0" };
+            replacement = Some((SanitizationRule::BooleanLiteral, canonical.to_string()));
         }
     }
 
@@ -403,11 +713,14 @@ fn flush_token(
     {
         let trimmed = token.trim_start_matches('0');
         let normalized = if trimmed.is_empty() { "0" } else { trimmed };
-        replacement = Some(normalized.to_string());
+        replacement = Some((SanitizationRule::LeadingZeros, normalized.to_string()));
     }
 
-    if let Some(ref value) = replacement {
-        *changed |= value != token;
+    if let Some((rule, ref value)) = replacement {
+        if value != token {
+            repairs.report(rule, || value_fid(out), token, value);
+            *changed = true;
+        }
         out.push_str(value);
     } else {
         out.push_str(token);
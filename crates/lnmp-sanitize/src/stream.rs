@@ -0,0 +1,174 @@
+use crate::extract::field_starts;
+use crate::rule::Repairs;
+use crate::sanitize::{sanitize, SanitizationConfig};
+
+/// Where the stream is relative to the LNMP payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    /// No field seen yet; prose and fence lines are dropped
+    Before,
+    /// Inside the payload
+    Payload,
+    /// The payload's closing fence was seen; the rest is dropped
+    After,
+}
+
+/// Incremental sanitizer for text that arrives in chunks, e.g. tokens streamed from
+/// an LLM.
+///
+/// Input is split into logical lines: newlines inside quoted strings or open
+/// brackets do not end a line, so quote/escape state carries across chunk
+/// boundaries. Each completed line is sanitized like [`sanitize_lnmp_text`] would
+/// and emitted with a trailing newline, ready for a line-oriented parser.
+///
+/// With payload extraction enabled, prose and fence lines before the first field are
+/// dropped, lines without fields are held back until another field line confirms
+/// they are part of the payload, and everything after the closing fence is dropped.
+/// Custom [`SanitizeRule`](crate::SanitizeRule)s see one logical line at a time.
+///
+/// ```
+/// use lnmp_sanitize::{SanitizationConfig, StreamingSanitizer};
+///
+/// let mut stream = StreamingSanitizer::new(SanitizationConfig::default());
+/// let mut output = String::new();
+/// for chunk in ["Here is the record:\n```lnmp\nF1=\"hel", "lo\" ;  F2=", "1\n```\nBye"] {
+///     output.push_str(&stream.push(chunk));
+/// }
+/// output.push_str(&stream.finish());
+/// assert_eq!(output, "F1=\"hello\";F2=1\n");
+/// ```
+///
+/// [`sanitize_lnmp_text`]: crate::sanitize_lnmp_text
+#[derive(Debug, Clone)]
+pub struct StreamingSanitizer {
+    config: SanitizationConfig,
+    extract: bool,
+    position: Position,
+    /// Current, incomplete logical line
+    line: String,
+    /// Sanitized lines waiting for a later field line
+    held: String,
+    in_quotes: bool,
+    escape_next: bool,
+    depth: i32,
+}
+
+impl Default for StreamingSanitizer {
+    fn default() -> Self {
+        Self::new(SanitizationConfig::default())
+    }
+}
+
+impl StreamingSanitizer {
+    /// Creates a streaming sanitizer using `config`
+    pub fn new(config: SanitizationConfig) -> Self {
+        let extract = config.extract_payload && config.rules.is_enabled("extract_payload");
+        Self {
+            // Extraction is done line by line here, not per sanitized line
+            config: SanitizationConfig {
+                extract_payload: false,
+                ..config
+            },
+            extract,
+            position: Position::Before,
+            line: String::new(),
+            held: String::new(),
+            in_quotes: false,
+            escape_next: false,
+            depth: 0,
+        }
+    }
+
+    /// Feeds the next chunk and returns the sanitized output of every logical line
+    /// it completed (empty while a line is still open)
+    pub fn push(&mut self, chunk: &str) -> String {
+        let mut output = String::new();
+        for ch in chunk.chars() {
+            if self.escape_next {
+                self.escape_next = false;
+            } else {
+                match ch {
+                    '\\' if self.in_quotes => self.escape_next = true,
+                    '"' => self.in_quotes = !self.in_quotes,
+                    '{' | '[' if !self.in_quotes => self.depth += 1,
+                    '}' | ']' if !self.in_quotes => self.depth -= 1,
+                    '\n' if !self.in_quotes && self.depth <= 0 => {
+                        let line = std::mem::take(&mut self.line);
+                        self.emit_line(&line, &mut output);
+                        self.depth = 0;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            self.line.push(ch);
+        }
+        output
+    }
+
+    /// Sanitizes the last, unterminated line (closing open quotes) and resets the
+    /// sanitizer for a new stream
+    pub fn finish(&mut self) -> String {
+        let mut output = String::new();
+        let line = std::mem::take(&mut self.line);
+        if !line.trim().is_empty() {
+            self.emit_line(&line, &mut output);
+        }
+        // Lines held back after the last field are trailing commentary
+        self.held.clear();
+        self.position = Position::Before;
+        self.in_quotes = false;
+        self.escape_next = false;
+        self.depth = 0;
+        output
+    }
+
+    fn emit_line(&mut self, line: &str, output: &mut String) {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if !self.extract {
+            write_sanitized(&self.config, line, output);
+            return;
+        }
+
+        let is_fence = {
+            let trimmed = line.trim_start();
+            trimmed.starts_with("```") || trimmed.starts_with("~~~")
+        };
+        match self.position {
+            Position::After => {}
+            Position::Before if is_fence => {}
+            Position::Before => match field_starts(line).next() {
+                Some(start) => {
+                    let start = if line[..start].trim().is_empty() {
+                        0
+                    } else {
+                        start
+                    };
+                    output.push_str(&std::mem::take(&mut self.held));
+                    write_sanitized(&self.config, &line[start..], output);
+                    self.position = Position::Payload;
+                }
+                // `#` comment lines directly above the first field are kept
+                None if line.trim_start().starts_with('#') => {
+                    write_sanitized(&self.config, line, &mut self.held);
+                }
+                None => self.held.clear(),
+            },
+            Position::Payload if is_fence => {
+                self.held.clear();
+                self.position = Position::After;
+            }
+            Position::Payload if field_starts(line).next().is_some() => {
+                output.push_str(&std::mem::take(&mut self.held));
+                write_sanitized(&self.config, line, output);
+            }
+            Position::Payload => write_sanitized(&self.config, line, &mut self.held),
+        }
+    }
+}
+
+/// Sanitizes one logical line and appends it to `output` with a trailing newline.
+fn write_sanitized(config: &SanitizationConfig, line: &str, output: &mut String) {
+    output.push_str(&sanitize(line, config, &mut Repairs::logging_only()));
+    output.push('\n');
+}
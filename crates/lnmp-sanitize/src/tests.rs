@@ -1,4 +1,8 @@
-use crate::{sanitize_lnmp_text, SanitizationConfig, SanitizationLevel};
+use crate::{
+    extract_lnmp_payload, normalize_number, sanitize_lnmp_text, sanitize_with_report, RuleChain,
+    SanitizationConfig, SanitizationLevel, SanitizationRule, SanitizeRule, StreamingSanitizer,
+    TruncationRepair, BUILTIN_RULES,
+};
 use proptest::prelude::*;
 
 #[test]
@@ -28,14 +32,14 @@ fn repairs_unterminated_quote() {
 
 #[test]
 fn normalizes_booleans() {
-    let input = "F1=true;F2=no";
+    let input = "F1=true;F2=no;F3=YES";
     let config = SanitizationConfig {
         normalize_numbers: true,
         level: SanitizationLevel::Aggressive,
         ..Default::default()
     };
     let sanitized = sanitize_lnmp_text(input, &config);
-    assert_eq!(sanitized, "F1=1;F2=0");
+    assert_eq!(sanitized, "F1=1;F2=0;F3=1");
 }
 
 #[test]
@@ -68,6 +72,418 @@ fn auto_quotes_unquoted_value_with_quotes() {
     assert_eq!(sanitized, r#"F1="Hello \"world\"";F2=ok"#);
 }
 
+#[test]
+fn leaves_embedding_literal_unquoted() {
+    let input = r#"F1=E[3,f32,"AACAPwAAAEAAAEBA"];F2=ok"#;
+    let sanitized = sanitize_lnmp_text(input, &SanitizationConfig::default());
+    assert_eq!(sanitized, input);
+}
+
+#[test]
+fn resolves_field_id_of_repaired_value() {
+    use crate::rule::value_fid;
+
+    assert_eq!(value_fid("F1=1;F12="), Some(12));
+    assert_eq!(value_fid("F7:b="), Some(7));
+    assert_eq!(value_fid("F3=[yes,"), Some(3));
+    assert_eq!(value_fid("F2={F9="), Some(9));
+    assert_eq!(value_fid("no key"), None);
+}
+
+#[test]
+fn report_lists_applied_fixes_in_order() {
+    let input = "F1=yes;F2=007;F3=\"open";
+    let config = SanitizationConfig {
+        level: SanitizationLevel::Aggressive,
+        normalize_numbers: true,
+        ..Default::default()
+    };
+    let report = sanitize_with_report(input, &config);
+    assert_eq!(report.text, sanitize_lnmp_text(input, &config));
+
+    let fixes: Vec<_> = report
+        .fixes
+        .iter()
+        .map(|fix| {
+            (
+                fix.kind,
+                fix.fid,
+                fix.original.as_str(),
+                fix.replacement.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fixes,
+        vec![
+            (SanitizationRule::CloseQuote, Some(3), "\"open", "\"open\""),
+            (SanitizationRule::BooleanLiteral, Some(1), "yes", "1"),
+            (SanitizationRule::LeadingZeros, Some(2), "007", "7"),
+        ]
+    );
+    assert!(!report.is_clean());
+    assert_eq!(report.count(SanitizationRule::BooleanLiteral), 1);
+    assert_eq!(report.count(SanitizationRule::Whitespace), 0);
+}
+
+#[test]
+fn report_records_auto_quote() {
+    let config = SanitizationConfig {
+        auto_quote_strings: true,
+        ..Default::default()
+    };
+    let report = sanitize_with_report("F4=hello world", &config);
+    assert_eq!(report.text, "F4=\"hello world\"");
+    assert_eq!(report.fixes.len(), 1);
+    assert_eq!(report.fixes[0].kind, SanitizationRule::AutoQuote);
+    assert_eq!(report.fixes[0].fid, Some(4));
+}
+
+#[test]
+fn report_is_clean_for_valid_input() {
+    let input = "F1=1;F2=\"ok\"";
+    let report = sanitize_with_report(input, &SanitizationConfig::default());
+    assert!(report.is_clean());
+    assert!(matches!(report.text, std::borrow::Cow::Borrowed(_)));
+}
+
+#[test]
+fn extracts_payload_from_fenced_block() {
+    let reply =
+        "Here is the record:\n\n```lnmp\nF12=14532\nF7=1\n```\n\nLet me know if you need changes.";
+    assert_eq!(extract_lnmp_payload(reply), "F12=14532\nF7=1");
+
+    // Untagged fences are used when they contain fields
+    let reply = "```text\nnot it\n```\n```\nF1=1\n```";
+    assert_eq!(extract_lnmp_payload(reply), "F1=1");
+
+    // Truncated output without a closing fence
+    assert_eq!(extract_lnmp_payload("```lnmp\nF1=1;F2=2\n"), "F1=1;F2=2");
+}
+
+#[test]
+fn extracts_payload_from_prose() {
+    assert_eq!(
+        extract_lnmp_payload("Here is the record: F1=1;F2:s=\"x\""),
+        "F1=1;F2:s=\"x\""
+    );
+    assert_eq!(
+        extract_lnmp_payload("Sure!\nF1=1\n# note\nF2=2\nHope this helps, F3 is omitted."),
+        "F1=1\n# note\nF2=2"
+    );
+
+    // Open quotes and brackets keep the payload going past the last field line
+    let reply = "Result:\nF1=\"multi\nline\"\nF2={F3=1\n}\nDone.";
+    assert_eq!(
+        extract_lnmp_payload(reply),
+        "F1=\"multi\nline\"\nF2={F3=1\n}"
+    );
+
+    // Nothing to extract
+    let plain = "F1=1\nF2=2\n";
+    assert!(std::ptr::eq(extract_lnmp_payload(plain), plain));
+    assert_eq!(extract_lnmp_payload("no record here"), "no record here");
+}
+
+#[test]
+fn sanitizer_strips_fences_before_parsing() {
+    let reply = "Here is the record:\n```lnmp\nF1=yes;F2=\"ok\"\n```";
+    let config = SanitizationConfig::default();
+    let report = sanitize_with_report(reply, &config);
+    assert_eq!(report.text, "F1=yes;F2=\"ok\"");
+    assert!(matches!(report.text, std::borrow::Cow::Borrowed(_)));
+    assert_eq!(report.fixes[0].kind, SanitizationRule::ExtractPayload);
+    assert_eq!(report.fixes[0].replacement, "F1=yes;F2=\"ok\"");
+
+    let config = SanitizationConfig {
+        extract_payload: false,
+        ..Default::default()
+    };
+    assert_eq!(report.count(SanitizationRule::ExtractPayload), 1);
+    assert!(sanitize_with_report(reply, &config)
+        .fixes
+        .iter()
+        .all(|fix| fix.kind != SanitizationRule::ExtractPayload));
+}
+
+/// Maps `user_id=` keys to `F12=`
+struct UserIdAlias;
+
+impl SanitizeRule for UserIdAlias {
+    fn name(&self) -> &str {
+        "user_id_alias"
+    }
+
+    fn apply<'a>(&self, input: &'a str) -> std::borrow::Cow<'a, str> {
+        if input.contains("user_id=") {
+            input.replace("user_id=", "F12=").into()
+        } else {
+            input.into()
+        }
+    }
+}
+
+#[test]
+fn custom_rules_run_in_chain_order() {
+    let rules = RuleChain::new().with_rule_before("extract_payload", UserIdAlias);
+    assert_eq!(
+        rules.names().collect::<Vec<_>>(),
+        ["user_id_alias"]
+            .into_iter()
+            .chain(BUILTIN_RULES)
+            .collect::<Vec<_>>()
+    );
+
+    // Aliasing first lets payload extraction find the field
+    let config = SanitizationConfig {
+        rules,
+        ..Default::default()
+    };
+    let report = sanitize_with_report("Here you go: user_id=42;F7=1", &config);
+    assert_eq!(report.text, "F12=42;F7=1");
+    assert_eq!(report.fixes[0].kind, SanitizationRule::Custom);
+    assert_eq!(
+        report.fixes[0].custom_rule.as_deref(),
+        Some("user_id_alias")
+    );
+    assert_eq!(report.fixes[1].kind, SanitizationRule::ExtractPayload);
+
+    // Appended last, extraction ran before the key was recognized as a field
+    let config = SanitizationConfig {
+        rules: RuleChain::new().with_rule(UserIdAlias),
+        ..Default::default()
+    };
+    assert_eq!(
+        sanitize_lnmp_text("Here you go: user_id=42;F7=1", &config),
+        "Here you go: F12=42;F7=1"
+    );
+
+    // Clean input stays borrowed through custom rules
+    let input = "F1=1";
+    assert!(matches!(
+        sanitize_lnmp_text(input, &config),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn rules_can_be_disabled_by_name() {
+    let input = "Result: F1=hello world";
+    let config = SanitizationConfig {
+        rules: RuleChain::new()
+            .with_disabled("auto_quote")
+            .with_disabled("extract_payload"),
+        ..Default::default()
+    };
+    assert!(!config.rules.is_enabled("auto_quote"));
+    assert_eq!(sanitize_lnmp_text(input, &config), input);
+
+    let config = SanitizationConfig {
+        rules: config.rules.with_enabled("auto_quote"),
+        ..Default::default()
+    };
+    assert_eq!(
+        sanitize_lnmp_text(input, &config),
+        "Result: F1=\"hello world\""
+    );
+
+    let rules = RuleChain::new()
+        .with_rule_after("structural", UserIdAlias)
+        .with_disabled("user_id_alias");
+    let names: Vec<_> = rules.names().collect();
+    let structural = names.iter().position(|&name| name == "structural").unwrap();
+    assert_eq!(names[structural + 1], "user_id_alias");
+    let config = SanitizationConfig {
+        rules,
+        ..Default::default()
+    };
+    assert_eq!(sanitize_lnmp_text("user_id=42", &config), "user_id=42");
+}
+
+#[test]
+fn normalizes_locale_number_literals() {
+    assert_eq!(normalize_number("1.234,56").as_deref(), Some("1234.56"));
+    assert_eq!(normalize_number("1 234,56").as_deref(), Some("1234.56"));
+    assert_eq!(normalize_number("1,234.56").as_deref(), Some("1234.56"));
+    assert_eq!(normalize_number("1'234.56").as_deref(), Some("1234.56"));
+    assert_eq!(normalize_number("3,14").as_deref(), Some("3.14"));
+    assert_eq!(normalize_number("-1.234.567").as_deref(), Some("-1234567"));
+    // A comma followed by exactly three digits groups thousands
+    assert_eq!(normalize_number("1,234").as_deref(), Some("1234"));
+    assert_eq!(normalize_number("1,2345").as_deref(), Some("1.2345"));
+    assert_eq!(normalize_number("1\u{a0}000").as_deref(), Some("1000"));
+}
+
+#[test]
+fn rejects_non_locale_number_literals() {
+    assert_eq!(normalize_number("3.14"), None); // already canonical
+    assert_eq!(normalize_number("42"), None);
+    assert_eq!(normalize_number("1.2.3,4"), None); // bad grouping
+    assert_eq!(normalize_number("12 34"), None);
+    assert_eq!(normalize_number("1.234 567"), None); // mixed group separators
+    assert_eq!(normalize_number("hello world"), None);
+    assert_eq!(normalize_number("v1,2"), None);
+}
+
+#[test]
+fn normalizes_locale_numbers_in_values() {
+    let config = SanitizationConfig {
+        normalize_locale_numbers: true,
+        ..Default::default()
+    };
+    let cases = [
+        ("F1=3,14", "F1=3.14"),
+        ("F1=-1.234,56", "F1=-1234.56"),
+        ("F1=1,234.56", "F1=1234.56"),
+        ("F1=1 234", "F1=1234"),
+        ("F1=1\u{a0}234,5", "F1=1234.5"),
+        ("F1=1.234.567;F2=7", "F1=1234567;F2=7"),
+        ("F1=1,234", "F1=1234"),
+        ("F1=1'234.5", "F1=1234.5"),
+        ("F2={F3=2,5}", "F2={F3=2.5}"),
+        // Canonical or non-numeric values are left alone
+        ("F1=3.14", "F1=3.14"),
+        ("F1=1.2.3", "F1=1.2.3"),
+        ("F1=12 34", "F1=\"12 34\""),
+        ("F1=\"3,14\"", "F1=\"3,14\""),
+        ("F1=[1,234]", "F1=[1,234]"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(sanitize_lnmp_text(input, &config), expected, "{input:?}");
+    }
+
+    let report = sanitize_with_report("F1=x;F7=1.234,56", &config);
+    assert_eq!(report.text, "F1=x;F7=1234.56");
+    assert_eq!(report.fixes.len(), 1);
+    assert_eq!(report.fixes[0].kind, SanitizationRule::LocaleNumber);
+    assert_eq!(report.fixes[0].fid, Some(7));
+    assert_eq!(report.fixes[0].original, "1.234,56");
+    assert_eq!(report.fixes[0].replacement, "1234.56");
+
+    // Opt-in: the default config leaves `3,14` alone
+    assert_eq!(
+        sanitize_lnmp_text("F1=3,14", &SanitizationConfig::default()),
+        "F1=3,14"
+    );
+}
+
+#[test]
+fn closes_truncated_records() {
+    let config = SanitizationConfig {
+        truncation: TruncationRepair::Close,
+        ..Default::default()
+    };
+    let cases = [
+        ("F7=1;F23=[\"admin\",\"de", "F7=1;F23=[\"admin\",\"de\"]"),
+        ("F7=1;F23=[\"admin\",", "F7=1;F23=[\"admin\"]"),
+        ("F50={F1=1;F2={F3=\"x\\", "F50={F1=1;F2={F3=\"x\"}}"),
+        ("F50={F1=1;", "F50={F1=1}"),
+        ("F7=1;F12=14532", "F7=1;F12=14532"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(sanitize_lnmp_text(input, &config), expected, "{input:?}");
+    }
+
+    let report = sanitize_with_report("F7=1;F23=[\"admin\",\"de", &config);
+    assert_eq!(report.fixes.len(), 1);
+    assert_eq!(report.fixes[0].kind, SanitizationRule::Truncation);
+    assert_eq!(report.fixes[0].fid, Some(23));
+    assert_eq!(report.fixes[0].original, "F23=[\"admin\",\"de");
+    assert_eq!(report.fixes[0].replacement, "F23=[\"admin\",\"de\"]");
+
+    // A stream cut off mid-field is repaired when finished
+    let mut stream = StreamingSanitizer::new(config);
+    assert_eq!(stream.push("F7=1\nF23=[\"admin\",\"de"), "F7=1\n");
+    assert_eq!(stream.finish(), "F23=[\"admin\",\"de\"]\n");
+}
+
+#[test]
+fn drops_incomplete_trailing_field() {
+    let config = SanitizationConfig {
+        truncation: TruncationRepair::DropIncomplete,
+        ..Default::default()
+    };
+    let cases = [
+        ("F7=1;F23=[\"admin\",\"de", "F7=1"),
+        ("F7=1\nF50={F1=1;F2=\"x", "F7=1"),
+        ("F23=[\"admin\"", ""),
+        ("F7=1;F12=14532", "F7=1;F12=14532"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(sanitize_lnmp_text(input, &config), expected, "{input:?}");
+    }
+
+    let report = sanitize_with_report("F7=1;F23=[\"ad", &config);
+    assert_eq!(report.fixes[0].kind, SanitizationRule::Truncation);
+    assert_eq!(report.fixes[0].fid, Some(23));
+    assert_eq!(report.fixes[0].replacement, "");
+
+    // Off by default: the structural pass only closes the quote
+    assert_eq!(
+        sanitize_lnmp_text("F23=[\"ad", &SanitizationConfig::default()),
+        "F23=[\"ad\""
+    );
+}
+
+/// Streams `input` in `size`-char chunks and collects the output
+fn stream_in_chunks(input: &str, size: usize, config: &SanitizationConfig) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut stream = StreamingSanitizer::new(config.clone());
+    let mut output = String::new();
+    for chunk in chars.chunks(size) {
+        output.push_str(&stream.push(&chunk.iter().collect::<String>()));
+    }
+    output.push_str(&stream.finish());
+    output
+}
+
+#[test]
+fn streaming_matches_batch_sanitization() {
+    let inputs = [
+        "F1=1 ;  F2=\"hi\"\nF3=yes\n",
+        "Here is the record:\n```lnmp\nF12=14532\nF7=hello world\n```\nLet me know!",
+        "Sure: F1=1\n# note\nF2={F3=1\n}\nThanks",
+        "F1=\"multi\nline \\\" quote\"\nF2=2",
+        "F1=2;F2=\"open",
+    ];
+    let configs = [
+        SanitizationConfig::default(),
+        SanitizationConfig {
+            level: SanitizationLevel::Aggressive,
+            normalize_numbers: true,
+            extract_payload: false,
+            ..Default::default()
+        },
+    ];
+
+    for config in &configs {
+        for input in inputs {
+            let batch = sanitize_lnmp_text(input, config);
+            for size in 1..=8 {
+                let streamed = stream_in_chunks(input, size, config);
+                assert_eq!(
+                    streamed.trim_end(),
+                    batch.trim_end(),
+                    "{input:?} in {size}-char chunks"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn streaming_emits_completed_lines_only() {
+    let mut stream = StreamingSanitizer::default();
+    assert_eq!(stream.push("F1=\"a;"), "");
+    // The newline is inside the open string
+    assert_eq!(stream.push("\nb\";F2=yes"), "");
+    assert_eq!(stream.push(" \nF3="), "F1=\"a;\nb\";F2=yes\n");
+    assert_eq!(stream.finish(), "F3=\n");
+
+    // `finish` resets the stream
+    assert_eq!(stream.push("Hi!\nF4=1\n"), "F4=1\n");
+}
+
 proptest! {
     #[test]
     fn sanitized_output_normalizes_whitespace(input in prop::collection::vec(any::<char>(), 0..128)) {
@@ -0,0 +1,92 @@
+#![cfg(feature = "dictionary")]
+
+//! Key-name to FID repair
+//!
+//! LLMs sometimes write keys as dictionary field names (`user_id=14532`); these must
+//! be mapped back to field IDs, with ambiguous names rejected.
+
+use lnmp_sanitize::{
+    sanitize_with_report, AmbiguousFieldName, FieldNameRepair, RuleChain, SanitizationConfig,
+    SanitizationRule,
+};
+use lnmp_sfe::SemanticDictionary;
+
+fn dictionary() -> SemanticDictionary {
+    let mut dict = SemanticDictionary::new();
+    dict.add_field_name(12, "user_id".to_string());
+    dict.add_field_name(7, "is_active".to_string());
+    dict.add_field_name(23, "roles".to_string());
+    dict.add_field_name(50, "profile".to_string());
+    dict
+}
+
+#[test]
+fn maps_known_names_to_fids() {
+    let repair = FieldNameRepair::new(&dictionary());
+    let input = "User_ID=14532;is_active:b=1\nroles=[\"admin\"];F5=1;profile={user_id=1}";
+    let report = repair.repair(input).unwrap();
+    assert_eq!(
+        report.text,
+        "F12=14532;F7:b=1\nF23=[\"admin\"];F5=1;F50={F12=1}"
+    );
+
+    let substitutions: Vec<_> = report
+        .fixes
+        .iter()
+        .map(|fix| (fix.kind, fix.fid, fix.original.as_str()))
+        .collect();
+    assert_eq!(
+        substitutions,
+        vec![
+            (SanitizationRule::FieldName, Some(12), "User_ID"),
+            (SanitizationRule::FieldName, Some(7), "is_active"),
+            (SanitizationRule::FieldName, Some(23), "roles"),
+            (SanitizationRule::FieldName, Some(50), "profile"),
+            (SanitizationRule::FieldName, Some(12), "user_id"),
+        ]
+    );
+}
+
+#[test]
+fn leaves_values_and_unknown_names_alone() {
+    let repair = FieldNameRepair::new(&dictionary());
+    let input = "F1=\"user_id=3\";unknown=2;F2=roles";
+    let report = repair.repair(input).unwrap();
+    assert!(report.is_clean());
+    assert_eq!(report.text, input);
+}
+
+#[test]
+fn rejects_ambiguous_names() {
+    let mut dict = dictionary();
+    dict.add_field_name(3, "ID".to_string());
+    dict.add_field_name(1, "id".to_string());
+    let repair = FieldNameRepair::new(&dict);
+
+    let err = repair.repair("user_id=1;id=2").unwrap_err();
+    assert_eq!(
+        err,
+        AmbiguousFieldName {
+            name: "id".to_string(),
+            candidates: vec![1, 3],
+        }
+    );
+    assert_eq!(err.to_string(), "Ambiguous field name 'id': matches F1, F3");
+}
+
+#[test]
+fn runs_as_rule_in_chain() {
+    let mut dict = dictionary();
+    dict.add_field_name(1, "id".to_string());
+    dict.add_field_name(3, "id".to_string());
+
+    let config = SanitizationConfig {
+        rules: RuleChain::new().with_rule_before("extract_payload", FieldNameRepair::new(&dict)),
+        ..Default::default()
+    };
+    // Ambiguous names are left for the strict parser to reject
+    let report = sanitize_with_report("user_id=14532;id=2", &config);
+    assert_eq!(report.text, "F12=14532;id=2");
+    assert_eq!(report.fixes[0].kind, SanitizationRule::Custom);
+    assert_eq!(report.fixes[0].custom_rule.as_deref(), Some("field_names"));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "log")]
+
+//! Logging of sanitizer repairs
+//!
+//! Each applied repair must be logged with its rule id, field ID and the text
+//! before and after, so lenient-mode rewrites can be traced in production.
+
+use lnmp_sanitize::{sanitize_lnmp_text, SanitizationConfig, SanitizationLevel, SanitizationRule};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::cell::RefCell;
+use std::sync::Once;
+
+thread_local! {
+    static RECORDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "lnmp::sanitize"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.with(|records| records.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger;
+static INIT: Once = Once::new();
+
+/// Sanitizes `input` and returns the log records emitted on this thread
+fn sanitize_and_capture(input: &str, config: &SanitizationConfig) -> (String, Vec<String>) {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Debug);
+    });
+    RECORDS.with(|records| records.borrow_mut().clear());
+    let output = sanitize_lnmp_text(input, config).into_owned();
+    (output, RECORDS.with(|records| records.take()))
+}
+
+#[test]
+fn logs_boolean_and_number_rewrites_with_field_ids() {
+    let config = SanitizationConfig {
+        level: SanitizationLevel::Aggressive,
+        normalize_numbers: true,
+        ..Default::default()
+    };
+    let (output, records) = sanitize_and_capture("F1=yes;F12=007", &config);
+    assert_eq!(output, "F1=1;F12=7");
+    assert_eq!(
+        records,
+        vec![
+            r#"rule=sanitize.boolean_literal fid=F1 before="yes" after="1""#,
+            r#"rule=sanitize.leading_zeros fid=F12 before="007" after="7""#,
+        ]
+    );
+}
+
+#[test]
+fn logs_quote_repairs() {
+    let config = SanitizationConfig::default();
+    let (output, records) = sanitize_and_capture("F1=hello world;F2=\"open", &config);
+    assert_eq!(output, "F1=\"hello world\";F2=\"open\"");
+    assert_eq!(
+        records,
+        vec![
+            format!(
+                r#"rule={} fid=F2 before="\"open" after="\"open\"""#,
+                SanitizationRule::CloseQuote.id()
+            ),
+            format!(
+                r#"rule={} fid=F1 before="hello world" after="\"hello world\"""#,
+                SanitizationRule::AutoQuote.id()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn logs_whitespace_cleanup_once_per_input() {
+    let config = SanitizationConfig::default();
+    let (_, records) = sanitize_and_capture("F1=1 ;  F2=2  \n", &config);
+    assert_eq!(records.len(), 1);
+    assert!(records[0].starts_with("rule=sanitize.whitespace fid=- "));
+}
+
+#[test]
+fn clean_input_logs_nothing() {
+    let (_, records) = sanitize_and_capture("F1=1;F2=\"ok\"", &SanitizationConfig::default());
+    assert!(records.is_empty());
+}
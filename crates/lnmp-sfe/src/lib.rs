@@ -1,8 +1,10 @@
 pub mod context;
 pub mod dictionary;
+pub mod shared;
 
 pub use context::{
     ContextPrioritizer, ContextProfile, ContextScorer, ContextScorerConfig, ContextStats,
     RiskLevel, ScoringWeights,
 };
 pub use dictionary::SemanticDictionary;
+pub use shared::{DictionaryUpdate, SharedDictionary};
@@ -0,0 +1,361 @@
+//! Shared, hot-reloadable semantic dictionary handle.
+//!
+//! [`SharedDictionary`] wraps a [`SemanticDictionary`] in an `Arc` so parsers, encoders
+//! and normalizers can hold a cheap handle instead of a private copy. Readers take an
+//! immutable [`snapshot`](SharedDictionary::snapshot); writers publish a new version with
+//! copy-on-write semantics, so in-flight readers keep a consistent view while new lookups
+//! see the update immediately.
+//!
+//! # Examples
+//!
+//! ```
+//! use lnmp_sfe::SharedDictionary;
+//!
+//! let shared = SharedDictionary::default();
+//! let updates = shared.subscribe();
+//!
+//! let handle = shared.clone();
+//! handle.update(|dict| dict.add_field_name(12, "user_id".to_string()));
+//!
+//! assert_eq!(shared.snapshot().get_field_name(12), Some("user_id"));
+//! assert_eq!(updates.try_recv().unwrap().version, 1);
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+use crate::dictionary::{DictionaryError, SemanticDictionary};
+
+/// Notification sent to subscribers when a new dictionary version is published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryUpdate {
+    /// Version number of the published dictionary (starts at 0, incremented per update)
+    pub version: u64,
+    /// File the dictionary was reloaded from, if the update came from a reload
+    pub source: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct State {
+    dictionary: Arc<SemanticDictionary>,
+    version: u64,
+    source: Option<PathBuf>,
+    loaded_at: Option<SystemTime>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: RwLock<State>,
+    subscribers: Mutex<Vec<Sender<DictionaryUpdate>>>,
+}
+
+/// Cloneable handle to a semantic dictionary shared across threads.
+///
+/// Clones refer to the same dictionary; an update through any handle is visible to all.
+#[derive(Debug, Clone)]
+pub struct SharedDictionary {
+    inner: Arc<Inner>,
+}
+
+impl Default for SharedDictionary {
+    fn default() -> Self {
+        Self::new(SemanticDictionary::new())
+    }
+}
+
+impl From<SemanticDictionary> for SharedDictionary {
+    fn from(dictionary: SemanticDictionary) -> Self {
+        Self::new(dictionary)
+    }
+}
+
+impl SharedDictionary {
+    /// Creates a shared handle around `dictionary`.
+    pub fn new(dictionary: SemanticDictionary) -> Self {
+        Self::with_state(State {
+            dictionary: Arc::new(dictionary),
+            version: 0,
+            source: None,
+            loaded_at: None,
+        })
+    }
+
+    /// Loads a dictionary from a YAML file and remembers the path for [`reload`](Self::reload).
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, DictionaryError> {
+        let path = path.as_ref().to_path_buf();
+        let loaded_at = modified_time(&path);
+        let dictionary = SemanticDictionary::load_from_file(&path)?;
+        Ok(Self::with_state(State {
+            dictionary: Arc::new(dictionary),
+            version: 0,
+            source: Some(path),
+            loaded_at,
+        }))
+    }
+
+    fn with_state(state: State) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: RwLock::new(state),
+                subscribers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Returns the current dictionary.
+    ///
+    /// The snapshot is immutable; later updates do not affect it.
+    pub fn snapshot(&self) -> Arc<SemanticDictionary> {
+        self.read(|state| state.dictionary.clone())
+    }
+
+    /// Returns the current version number.
+    pub fn version(&self) -> u64 {
+        self.read(|state| state.version)
+    }
+
+    /// Returns the file the dictionary is reloaded from, if any.
+    pub fn source(&self) -> Option<PathBuf> {
+        self.read(|state| state.source.clone())
+    }
+
+    /// Returns true if both handles refer to the same shared dictionary.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Applies `edit` to a copy of the current dictionary and publishes the result.
+    ///
+    /// Returns the new version number.
+    pub fn update<F>(&self, edit: F) -> u64
+    where
+        F: FnOnce(&mut SemanticDictionary),
+    {
+        let mut state = self.write();
+        edit(Arc::make_mut(&mut state.dictionary));
+        state.version += 1;
+        self.notify(DictionaryUpdate {
+            version: state.version,
+            source: None,
+        })
+    }
+
+    /// Replaces the dictionary with `dictionary` and publishes it.
+    ///
+    /// Returns the new version number.
+    pub fn replace(&self, dictionary: SemanticDictionary) -> u64 {
+        let mut state = self.write();
+        state.dictionary = Arc::new(dictionary);
+        state.version += 1;
+        self.notify(DictionaryUpdate {
+            version: state.version,
+            source: None,
+        })
+    }
+
+    /// Reloads the dictionary from `path` and uses it as the source for later reloads.
+    ///
+    /// On error the current dictionary is kept unchanged.
+    pub fn reload_from_file<P: AsRef<Path>>(&self, path: P) -> Result<u64, DictionaryError> {
+        let path = path.as_ref().to_path_buf();
+        let loaded_at = modified_time(&path);
+        let dictionary = SemanticDictionary::load_from_file(&path)?;
+        let mut state = self.write();
+        state.dictionary = Arc::new(dictionary);
+        state.version += 1;
+        state.source = Some(path.clone());
+        state.loaded_at = loaded_at;
+        Ok(self.notify(DictionaryUpdate {
+            version: state.version,
+            source: Some(path),
+        }))
+    }
+
+    /// Reloads the dictionary from its source file.
+    ///
+    /// Returns an I/O error if the handle was not loaded from a file.
+    pub fn reload(&self) -> Result<u64, DictionaryError> {
+        let path = self.source().ok_or_else(|| {
+            DictionaryError::IoError("dictionary has no source file to reload".to_string())
+        })?;
+        self.reload_from_file(path)
+    }
+
+    /// Reloads the dictionary if its source file changed since it was last loaded.
+    ///
+    /// Returns `Ok(true)` if a new version was published. Intended to be polled by
+    /// long-running services.
+    pub fn reload_if_modified(&self) -> Result<bool, DictionaryError> {
+        let (path, loaded_at) = self.read(|state| (state.source.clone(), state.loaded_at));
+        let path = path.ok_or_else(|| {
+            DictionaryError::IoError("dictionary has no source file to reload".to_string())
+        })?;
+        let modified = modified_time(&path);
+        if modified.is_some() && modified == loaded_at {
+            return Ok(false);
+        }
+        self.reload_from_file(path)?;
+        Ok(true)
+    }
+
+    /// Subscribes to change notifications.
+    ///
+    /// Each published version sends one [`DictionaryUpdate`]. Dropping the receiver
+    /// unsubscribes.
+    pub fn subscribe(&self) -> Receiver<DictionaryUpdate> {
+        let (tx, rx) = channel();
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tx);
+        rx
+    }
+
+    /// Sends `update` to all live subscribers; called with the state lock held so
+    /// notifications arrive in version order.
+    fn notify(&self, update: DictionaryUpdate) -> u64 {
+        let version = update.version;
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|tx| tx.send(update.clone()).is_ok());
+        version
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&State) -> T) -> T {
+        f(&self.inner.state.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, State> {
+        self.inner.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn write_dictionary(file: &NamedTempFile, name: &str) {
+        let yaml = format!("fields:\n  12:\n    name: {}\n    type: integer\n", name);
+        std::fs::write(file.path(), yaml).unwrap();
+    }
+
+    #[test]
+    fn test_update_is_copy_on_write() {
+        let shared = SharedDictionary::default();
+        let before = shared.snapshot();
+
+        let version = shared.update(|dict| dict.add_field_name(1, "id".to_string()));
+
+        assert_eq!(version, 1);
+        assert_eq!(before.get_field_name(1), None);
+        assert_eq!(shared.snapshot().get_field_name(1), Some("id"));
+    }
+
+    #[test]
+    fn test_clones_share_updates() {
+        let shared = SharedDictionary::default();
+        let other = shared.clone();
+        assert!(shared.ptr_eq(&other));
+
+        other.replace({
+            let mut dict = SemanticDictionary::new();
+            dict.add_equivalence(7, "yes".to_string(), "1".to_string());
+            dict
+        });
+
+        assert_eq!(shared.version(), 1);
+        assert_eq!(shared.snapshot().get_equivalence(7, "yes"), Some("1"));
+    }
+
+    #[test]
+    fn test_subscribers_receive_updates() {
+        let shared = SharedDictionary::default();
+        let rx = shared.subscribe();
+        let dropped = shared.subscribe();
+        drop(dropped);
+
+        shared.update(|dict| dict.add_field_name(1, "a".to_string()));
+        shared.update(|dict| dict.add_field_name(2, "b".to_string()));
+
+        let versions: Vec<u64> = rx.try_iter().map(|u| u.version).collect();
+        assert_eq!(versions, vec![1, 2]);
+        assert_eq!(shared.inner.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reload_from_source_file() {
+        let file = NamedTempFile::new().unwrap();
+        write_dictionary(&file, "user_id");
+
+        let shared = SharedDictionary::load_from_file(file.path()).unwrap();
+        let rx = shared.subscribe();
+        assert_eq!(shared.snapshot().get_field_name(12), Some("user_id"));
+
+        write_dictionary(&file, "account_id");
+        assert_eq!(shared.reload().unwrap(), 1);
+        assert_eq!(shared.snapshot().get_field_name(12), Some("account_id"));
+
+        let update = rx.try_recv().unwrap();
+        assert_eq!(update.source.as_deref(), Some(file.path()));
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_current_dictionary() {
+        let file = NamedTempFile::new().unwrap();
+        write_dictionary(&file, "user_id");
+        let shared = SharedDictionary::load_from_file(file.path()).unwrap();
+
+        std::fs::write(file.path(), "fields: [not: valid").unwrap();
+        assert!(shared.reload().is_err());
+        assert_eq!(shared.version(), 0);
+        assert_eq!(shared.snapshot().get_field_name(12), Some("user_id"));
+    }
+
+    #[test]
+    fn test_reload_without_source_is_an_error() {
+        let shared = SharedDictionary::default();
+        assert!(shared.reload().is_err());
+        assert!(shared.reload_if_modified().is_err());
+    }
+
+    #[test]
+    fn test_concurrent_readers_and_writer() {
+        let shared = SharedDictionary::default();
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = shared.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        let snapshot = handle.snapshot();
+                        // A snapshot is internally consistent: both entries or neither.
+                        assert_eq!(
+                            snapshot.get_field_name(1).is_some(),
+                            snapshot.get_field_name(2).is_some()
+                        );
+                    }
+                })
+            })
+            .collect();
+
+        for i in 0..50u16 {
+            shared.update(|dict| {
+                dict.add_field_name(1, format!("a{}", i));
+                dict.add_field_name(2, format!("b{}", i));
+            });
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(shared.version(), 50);
+    }
+}
@@ -84,6 +84,7 @@ impl SpatialStreamer {
 
         let force_abs = seq.is_multiple_of(self.config.abs_interval);
 
+        #[allow(clippy::unnecessary_unwrap)]
         let (mode, payload) = if force_abs || self.last_sent_state.is_none() {
             (FrameMode::Absolute, SpatialValue::S10(new_state.clone()))
         } else {
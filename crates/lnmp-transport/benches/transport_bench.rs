@@ -11,16 +11,23 @@ use lnmp_transport::kafka;
 
 #[cfg(any(feature = "http", feature = "kafka"))]
 fn create_bench_envelope() -> LnmpEnvelope {
-    let mut labels = std::collections::HashMap::new();
+    let mut labels = std::collections::BTreeMap::new();
     labels.insert("env".to_string(), "production".to_string());
     labels.insert("region".to_string(), "us-east-1".to_string());
 
     let meta = EnvelopeMetadata {
         timestamp: Some(1627849200000),
+        expires_at: None,
         source: Some("bench-source".to_string()),
         trace_id: Some("bench-trace-id-123456789".to_string()),
         sequence: Some(987654321),
+        correlation_id: None,
+        causation_id: None,
+        partition_key: None,
+        content_type: Some(lnmp_envelope::CONTENT_TYPE_BINARY.to_string()),
+        schema_version: Some("1.0.0".to_string()),
         labels,
+        signature: None,
     };
 
     let mut record = LnmpRecord::new();
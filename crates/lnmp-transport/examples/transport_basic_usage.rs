@@ -14,10 +14,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 1. Create an Envelope
     let meta = EnvelopeMetadata {
         timestamp: Some(1732373147000),
+        expires_at: None,
         source: Some("example-service".to_string()),
         trace_id: Some("abc-123-xyz".to_string()),
         sequence: None,
-        labels: std::collections::HashMap::new(),
+        correlation_id: None,
+        causation_id: None,
+        partition_key: None,
+        content_type: None,
+        schema_version: None,
+        labels: std::collections::BTreeMap::new(),
+        signature: None,
     };
     let mut record = LnmpRecord::new();
     record.add_field(LnmpField {
@@ -0,0 +1,376 @@
+//! AMQP 0.9.1 (RabbitMQ) transport bindings for LNMP.
+//!
+//! This module provides helpers to map LNMP Envelope metadata to/from AMQP basic
+//! properties and headers, derive routing keys for topic exchanges, and encode/decode
+//! LNMP message bodies.
+//!
+//! [`AmqpProperties`] mirrors the AMQP `basic` properties used by LNMP with plain
+//! types, so it converts field by field into `lapin::BasicProperties` (header values
+//! are `LongString`s) without this crate depending on an AMQP client.
+//!
+//! Standard properties are used where AMQP has them:
+//!
+//! | Envelope / NetMessage | AMQP |
+//! |-----------------------|------|
+//! | `timestamp` | `timestamp` (seconds) + `lnmp-timestamp` header (ms) |
+//! | `expires_at` | `expiration` (TTL in ms) + `lnmp-expires-at` header |
+//! | `source` | `app_id` |
+//! | `correlation_id` | `correlation_id` |
+//! | `content_type` / body format | `content_type` |
+//! | `MessageKind` | `type` |
+//! | priority (0-255) | `priority` (0-9) + `lnmp-priority` header |
+
+use crate::serializer::{self, SerializerConfig, CONTENT_TYPE_LNMP_BINARY};
+use crate::{Result, TransportError};
+use lnmp_codec::binary::BinaryEncoder;
+use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::{MessageKind, NetMessage};
+use std::collections::BTreeMap;
+
+/// AMQP header name for LNMP timestamp in milliseconds.
+pub const HEADER_TIMESTAMP: &str = "lnmp-timestamp";
+
+/// AMQP header name for LNMP expiry time (Unix epoch milliseconds).
+pub const HEADER_EXPIRES_AT: &str = "lnmp-expires-at";
+
+/// AMQP header name for LNMP trace ID.
+pub const HEADER_TRACE_ID: &str = "lnmp-trace-id";
+
+/// AMQP header name for LNMP causation ID.
+pub const HEADER_CAUSATION_ID: &str = "lnmp-causation-id";
+
+/// AMQP header name for LNMP partition key (for consistent-hash exchanges).
+pub const HEADER_PARTITION_KEY: &str = "lnmp-partition-key";
+
+/// AMQP header name for LNMP sequence number.
+pub const HEADER_SEQUENCE: &str = "lnmp-sequence";
+
+/// AMQP header name for LNMP FID schema version.
+pub const HEADER_SCHEMA_VERSION: &str = "lnmp-schema-version";
+
+/// AMQP header name for the full-range LNMP-Net priority (0-255).
+pub const HEADER_PRIORITY: &str = "lnmp-priority";
+
+/// AMQP header name for the LNMP-Net TTL in milliseconds.
+pub const HEADER_TTL_MS: &str = "lnmp-ttl-ms";
+
+/// AMQP header name for the LNMP-Net domain class.
+pub const HEADER_CLASS: &str = "lnmp-class";
+
+/// AMQP header name prefix for LNMP labels.
+pub const HEADER_LABEL_PREFIX: &str = "lnmp-label-";
+
+/// Highest AMQP message priority LNMP priorities are scaled to.
+///
+/// RabbitMQ recommends priority queues with at most 10 levels (`x-max-priority: 9`).
+pub const MAX_AMQP_PRIORITY: u8 = 9;
+
+/// Routing key prefix used by [`routing_key`].
+pub const ROUTING_KEY_PREFIX: &str = "lnmp";
+
+/// Type alias for AMQP headers (all values sent as `LongString`).
+pub type AmqpHeaders = BTreeMap<String, String>;
+
+/// AMQP basic properties carried by LNMP messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmqpProperties {
+    /// `content_type`: the body format.
+    pub content_type: Option<String>,
+    /// `correlation_id`.
+    pub correlation_id: Option<String>,
+    /// `timestamp`, in seconds since the Unix epoch.
+    pub timestamp: Option<u64>,
+    /// `expiration`: per-message TTL in milliseconds, as a decimal string.
+    pub expiration: Option<String>,
+    /// `priority` (0-9).
+    pub priority: Option<u8>,
+    /// `app_id`: the sending service.
+    pub app_id: Option<String>,
+    /// `type`: the message kind.
+    pub kind: Option<String>,
+    /// `headers` table.
+    pub headers: AmqpHeaders,
+}
+
+/// A message ready to publish: routing key, properties and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmqpMessage {
+    /// Routing key to publish with.
+    pub routing_key: String,
+    /// Basic properties.
+    pub properties: AmqpProperties,
+    /// Encoded record.
+    pub body: Vec<u8>,
+}
+
+/// Converts an LNMP Envelope's metadata to AMQP properties.
+///
+/// The body format is not known here, so `content_type` carries the envelope's
+/// `content_type`; the message helpers replace it with the body format.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::amqp;
+/// let properties = amqp::envelope_to_amqp_properties(&envelope)?;
+/// ```
+pub fn envelope_to_amqp_properties(env: &LnmpEnvelope) -> Result<AmqpProperties> {
+    let meta = &env.metadata;
+    let mut properties = AmqpProperties {
+        content_type: meta.content_type.clone(),
+        correlation_id: meta.correlation_id.clone(),
+        timestamp: meta.timestamp.map(|ts| ts / 1000),
+        app_id: meta.source.clone(),
+        ..AmqpProperties::default()
+    };
+    let headers = &mut properties.headers;
+
+    if let Some(ts) = meta.timestamp {
+        headers.insert(HEADER_TIMESTAMP.to_string(), ts.to_string());
+    }
+
+    if let Some(exp) = meta.expires_at {
+        headers.insert(HEADER_EXPIRES_AT.to_string(), exp.to_string());
+        if let Some(ts) = meta.timestamp {
+            properties.expiration = Some(exp.saturating_sub(ts).to_string());
+        }
+    }
+
+    if let Some(trace_id) = &meta.trace_id {
+        headers.insert(HEADER_TRACE_ID.to_string(), trace_id.clone());
+    }
+
+    if let Some(id) = &meta.causation_id {
+        headers.insert(HEADER_CAUSATION_ID.to_string(), id.clone());
+    }
+
+    if let Some(key) = &meta.partition_key {
+        headers.insert(HEADER_PARTITION_KEY.to_string(), key.clone());
+    }
+
+    if let Some(seq) = meta.sequence {
+        headers.insert(HEADER_SEQUENCE.to_string(), seq.to_string());
+    }
+
+    if let Some(version) = &meta.schema_version {
+        headers.insert(HEADER_SCHEMA_VERSION.to_string(), version.clone());
+    }
+
+    for (k, v) in &meta.labels {
+        let header_name = format!("{}{}", HEADER_LABEL_PREFIX, k);
+        headers.insert(header_name, v.clone());
+    }
+
+    Ok(properties)
+}
+
+/// Converts AMQP properties to LNMP Envelope metadata.
+///
+/// The millisecond `lnmp-timestamp` header wins over the second-precision
+/// `timestamp` property.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::amqp;
+/// let meta = amqp::amqp_properties_to_envelope_metadata(&properties)?;
+/// ```
+pub fn amqp_properties_to_envelope_metadata(
+    properties: &AmqpProperties,
+) -> Result<EnvelopeMetadata> {
+    let headers = &properties.headers;
+    let mut meta = EnvelopeMetadata {
+        content_type: properties.content_type.clone(),
+        correlation_id: properties.correlation_id.clone(),
+        source: properties.app_id.clone(),
+        ..EnvelopeMetadata::default()
+    };
+
+    meta.timestamp = match headers.get(HEADER_TIMESTAMP) {
+        Some(val) => Some(parse_header("timestamp", val)?),
+        None => properties.timestamp.map(|ts| ts * 1000),
+    };
+
+    if let Some(val) = headers.get(HEADER_EXPIRES_AT) {
+        meta.expires_at = Some(parse_header("expires_at", val)?);
+    }
+
+    if let Some(val) = headers.get(HEADER_TRACE_ID) {
+        meta.trace_id = Some(val.clone());
+    }
+
+    if let Some(val) = headers.get(HEADER_CAUSATION_ID) {
+        meta.causation_id = Some(val.clone());
+    }
+
+    if let Some(val) = headers.get(HEADER_PARTITION_KEY) {
+        meta.partition_key = Some(val.clone());
+    }
+
+    if let Some(val) = headers.get(HEADER_SEQUENCE) {
+        meta.sequence = Some(parse_header("sequence", val)?);
+    }
+
+    if let Some(val) = headers.get(HEADER_SCHEMA_VERSION) {
+        meta.schema_version = Some(val.clone());
+    }
+
+    for (name, value) in headers {
+        if let Some(key) = name.strip_prefix(HEADER_LABEL_PREFIX) {
+            meta.labels.insert(key.to_string(), value.clone());
+        }
+    }
+
+    Ok(meta)
+}
+
+/// Derives a topic-exchange routing key from the message kind and source.
+///
+/// The key is `lnmp.<kind>.<source>` (e.g. `lnmp.alert.sensor-01`), so consumers can
+/// bind `lnmp.alert.#` or `lnmp.*.sensor-01`. Characters with a meaning in binding
+/// patterns (`.`, `*`, `#`) are replaced with `_` in the source, and a missing
+/// source becomes `unknown`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::amqp;
+/// let key = amqp::routing_key(MessageKind::Alert, envelope.metadata.source.as_deref());
+/// ```
+pub fn routing_key(kind: MessageKind, source: Option<&str>) -> String {
+    let source: String = source
+        .filter(|s| !s.is_empty())
+        .unwrap_or("unknown")
+        .chars()
+        .map(|c| if matches!(c, '.' | '*' | '#') { '_' } else { c })
+        .collect();
+    format!(
+        "{}.{}.{}",
+        ROUTING_KEY_PREFIX,
+        kind.to_string().to_lowercase(),
+        source
+    )
+}
+
+/// Scales an LNMP-Net priority (0-255) to an AMQP priority (0-[`MAX_AMQP_PRIORITY`]).
+pub fn amqp_priority(priority: u8) -> u8 {
+    (priority as u16 * MAX_AMQP_PRIORITY as u16 / u8::MAX as u16) as u8
+}
+
+/// Encodes an LNMP Envelope to an AMQP message with a binary body.
+///
+/// The routing key is derived with [`routing_key`] from `kind` and the envelope's
+/// source, and `content_type` announces the binary format.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::amqp;
+/// let message = amqp::envelope_to_amqp_message(&envelope, MessageKind::Event)?;
+/// channel.basic_publish("lnmp", &message.routing_key, options, &message.body, props);
+/// ```
+pub fn envelope_to_amqp_message(env: &LnmpEnvelope, kind: MessageKind) -> Result<AmqpMessage> {
+    let body = BinaryEncoder::new().encode(&env.record)?;
+    build_message(env, kind, body, CONTENT_TYPE_LNMP_BINARY)
+}
+
+/// Encodes an LNMP Envelope to an AMQP message in the format configured for `kind`.
+///
+/// The chosen format is announced in the `content_type` property.
+pub fn envelope_to_amqp_message_for_kind(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<AmqpMessage> {
+    let (body, content_type) = config.encode(kind, &env.record)?;
+    build_message(env, kind, body, content_type)
+}
+
+/// Encodes a network message, adding its kind, priority, TTL and class.
+///
+/// The body format is the one `config` selects for the message kind. The AMQP
+/// `priority` is scaled with [`amqp_priority`]; the exact priority travels in the
+/// `lnmp-priority` header. Without an envelope expiry, the message TTL becomes the
+/// AMQP `expiration`.
+pub fn net_message_to_amqp(msg: &NetMessage, config: &SerializerConfig) -> Result<AmqpMessage> {
+    let mut message = envelope_to_amqp_message_for_kind(&msg.envelope, msg.kind, config)?;
+    let properties = &mut message.properties;
+    properties.priority = Some(amqp_priority(msg.priority));
+    properties
+        .expiration
+        .get_or_insert_with(|| msg.ttl_ms.to_string());
+    let headers = &mut properties.headers;
+    headers.insert(HEADER_PRIORITY.to_string(), msg.priority.to_string());
+    headers.insert(HEADER_TTL_MS.to_string(), msg.ttl_ms.to_string());
+    if let Some(class) = &msg.class {
+        headers.insert(HEADER_CLASS.to_string(), class.clone());
+    }
+    Ok(message)
+}
+
+/// Decodes an LNMP Envelope from an AMQP message body and properties.
+///
+/// The body is decoded according to the `content_type` property, defaulting to binary.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::amqp;
+/// let envelope = amqp::amqp_message_to_envelope(&delivery.data, &properties)?;
+/// ```
+pub fn amqp_message_to_envelope(body: &[u8], properties: &AmqpProperties) -> Result<LnmpEnvelope> {
+    let metadata = amqp_properties_to_envelope_metadata(properties)?;
+    let content_type = properties
+        .content_type
+        .as_deref()
+        .unwrap_or(CONTENT_TYPE_LNMP_BINARY);
+    let record = serializer::decode_body(body, content_type)?;
+
+    Ok(LnmpEnvelope { metadata, record })
+}
+
+/// Decodes a network message from an AMQP message body and properties.
+///
+/// The kind comes from the `type` property (Event when absent). Priority and TTL
+/// come from the LNMP headers, falling back to the kind's defaults.
+pub fn amqp_to_net_message(body: &[u8], properties: &AmqpProperties) -> Result<NetMessage> {
+    let envelope = amqp_message_to_envelope(body, properties)?;
+    let kind = match &properties.kind {
+        Some(kind) => kind
+            .parse()
+            .map_err(|_| TransportError::InvalidHeaderValue("type".into(), kind.clone()))?,
+        None => MessageKind::default(),
+    };
+    let headers = &properties.headers;
+    let mut msg = NetMessage::new(envelope, kind);
+    if let Some(val) = headers.get(HEADER_PRIORITY) {
+        msg.priority = parse_header("priority", val)?;
+    }
+    if let Some(val) = headers.get(HEADER_TTL_MS) {
+        msg.ttl_ms = parse_header("ttl_ms", val)?;
+    }
+    msg.class = headers.get(HEADER_CLASS).cloned();
+    Ok(msg)
+}
+
+fn build_message(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    body: Vec<u8>,
+    content_type: &str,
+) -> Result<AmqpMessage> {
+    let mut properties = envelope_to_amqp_properties(env)?;
+    properties.content_type = Some(content_type.to_string());
+    properties.kind = Some(kind.to_string());
+    Ok(AmqpMessage {
+        routing_key: routing_key(kind, env.metadata.source.as_deref()),
+        properties,
+        body,
+    })
+}
+
+fn parse_header<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| TransportError::InvalidHeaderValue(name.into(), "parse error".into()))
+}
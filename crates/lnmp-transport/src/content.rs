@@ -0,0 +1,340 @@
+//! Content negotiation between LNMP wire formats.
+//!
+//! A [`ContentNegotiator`] lists the [`WireFormat`]s this side can produce, in order of
+//! preference, and picks the one to send from what the peer announces:
+//!
+//! - an HTTP `Accept` header (media ranges with `q` weights),
+//! - the [`HEADER_ACCEPT_FORMATS`] capability header (format names in the peer's order
+//!   of preference, for transports without `Accept`),
+//! - or the feature flags of a schema [`NegotiationMessage`] (ShortForm needs LLB
+//!   support).
+//!
+//! The result comes back as a [`SerializerConfig`] whose default format is the one
+//! selected, ready for the `*_for_kind` send helpers.
+
+use crate::serializer::{
+    SerializerConfig, WireFormat, CONTENT_TYPE_LNMP_BINARY, CONTENT_TYPE_LNMP_EXPLAIN,
+    CONTENT_TYPE_LNMP_SHORTFORM, CONTENT_TYPE_LNMP_TEXT,
+};
+use lnmp_codec::binary::{FeatureFlags, NegotiationMessage};
+
+/// Capability header listing the formats a peer accepts, most preferred first.
+///
+/// Values are comma-separated format names: `binary`, `text`, `explain`, `shortform`.
+pub const HEADER_ACCEPT_FORMATS: &str = "X-LNMP-Accept-Formats";
+
+/// Returns the name of a format in [`HEADER_ACCEPT_FORMATS`].
+pub fn format_name(format: WireFormat) -> &'static str {
+    match format {
+        WireFormat::Binary => "binary",
+        WireFormat::Text => "text",
+        WireFormat::ExplainText => "explain",
+        WireFormat::ShortForm => "shortform",
+    }
+}
+
+/// Parses a format name of [`HEADER_ACCEPT_FORMATS`] (case-insensitive).
+pub fn parse_format_name(name: &str) -> Option<WireFormat> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "binary" => Some(WireFormat::Binary),
+        "text" => Some(WireFormat::Text),
+        "explain" => Some(WireFormat::ExplainText),
+        "shortform" => Some(WireFormat::ShortForm),
+        _ => None,
+    }
+}
+
+/// Builds a [`HEADER_ACCEPT_FORMATS`] value.
+pub fn accept_formats_header(formats: &[WireFormat]) -> String {
+    formats
+        .iter()
+        .map(|f| format_name(*f))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Selects the wire format to send to a peer.
+///
+/// # Examples
+///
+/// ```
+/// use lnmp_transport::content::ContentNegotiator;
+/// use lnmp_transport::WireFormat;
+///
+/// let negotiator = ContentNegotiator::new();
+/// assert_eq!(
+///     negotiator.select_from_accept("application/lnmp-text;q=0.5, application/lnmp-shortform"),
+///     Some(WireFormat::ShortForm)
+/// );
+/// assert_eq!(negotiator.select_from_accept("*/*"), Some(WireFormat::Binary));
+/// assert_eq!(negotiator.select_from_accept("application/json"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentNegotiator {
+    supported: Vec<WireFormat>,
+    config: SerializerConfig,
+}
+
+impl Default for ContentNegotiator {
+    fn default() -> Self {
+        Self {
+            supported: vec![
+                WireFormat::Binary,
+                WireFormat::Text,
+                WireFormat::ShortForm,
+                WireFormat::ExplainText,
+            ],
+            config: SerializerConfig::new(),
+        }
+    }
+}
+
+impl ContentNegotiator {
+    /// Creates a negotiator producing every format, preferring binary, then text,
+    /// ShortForm and explain text.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the formats this side can produce, most preferred first.
+    ///
+    /// The first one is sent to peers that accept anything.
+    pub fn with_supported(mut self, formats: &[WireFormat]) -> Self {
+        self.supported = formats.to_vec();
+        self
+    }
+
+    /// Sets the configuration the negotiated configurations start from (explain
+    /// dictionary, per-kind overrides).
+    pub fn with_config(mut self, config: SerializerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the formats this side can produce, most preferred first.
+    pub fn supported(&self) -> &[WireFormat] {
+        &self.supported
+    }
+
+    /// Returns the format sent when the peer states no preference.
+    pub fn default_format(&self) -> Option<WireFormat> {
+        self.supported.first().copied()
+    }
+
+    /// Returns the configuration encoding with `format` by default.
+    pub fn config_for(&self, format: WireFormat) -> SerializerConfig {
+        self.config.clone().with_default_format(format)
+    }
+
+    /// Selects a format from an HTTP `Accept` header value.
+    ///
+    /// Each format takes the `q` of the most specific range matching it (`*/*` and
+    /// `application/*` match every format). The highest `q` wins, ties going to this
+    /// side's preference. An empty header accepts anything. Returns `None` if nothing
+    /// acceptable is supported (`406 Not Acceptable`).
+    pub fn select_from_accept(&self, accept: &str) -> Option<WireFormat> {
+        if accept.trim().is_empty() {
+            return self.default_format();
+        }
+
+        let ranges: Vec<(String, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut parts = range.split(';');
+                let media = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+                let q = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (media, q)
+            })
+            .collect();
+
+        // (q, preference rank) of the best format so far
+        let mut best: Option<(f32, usize)> = None;
+        for (rank, format) in self.supported.iter().enumerate() {
+            let q = ranges
+                .iter()
+                .filter_map(|(media, q)| Some((specificity(media, *format)?, *q)))
+                .max_by_key(|(specificity, _)| *specificity)
+                .map_or(0.0, |(_, q)| q);
+            if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+                best = Some((q, rank));
+            }
+        }
+        best.map(|(_, rank)| self.supported[rank])
+    }
+
+    /// Selects a format from a [`HEADER_ACCEPT_FORMATS`] value, honoring the peer's
+    /// order of preference.
+    ///
+    /// Returns `None` if the peer lists no format this side supports.
+    pub fn select_from_accept_formats(&self, value: &str) -> Option<WireFormat> {
+        value
+            .split(',')
+            .filter_map(parse_format_name)
+            .find(|f| self.supported.contains(f))
+    }
+
+    /// Selects a format from the features a peer announced in a schema negotiation.
+    ///
+    /// Uses [`NegotiationMessage::Capabilities`] and
+    /// [`NegotiationMessage::CapabilitiesAck`]; other messages carry no features and
+    /// yield `None`.
+    pub fn select_from_negotiation(&self, message: &NegotiationMessage) -> Option<WireFormat> {
+        match message {
+            NegotiationMessage::Capabilities { features, .. }
+            | NegotiationMessage::CapabilitiesAck { features, .. } => {
+                self.select_from_features(features)
+            }
+            _ => None,
+        }
+    }
+
+    /// Selects this side's most preferred format that `features` allow.
+    ///
+    /// ShortForm requires `supports_llb`; the other formats are always understood.
+    pub fn select_from_features(&self, features: &FeatureFlags) -> Option<WireFormat> {
+        self.supported
+            .iter()
+            .copied()
+            .find(|f| *f != WireFormat::ShortForm || features.supports_llb)
+    }
+
+    /// Selects a format from HTTP request headers and returns its configuration.
+    ///
+    /// [`HEADER_ACCEPT_FORMATS`] takes precedence over `Accept`; without either, the
+    /// default format is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let Some(config) = negotiator.negotiate_headers(request.headers()) else {
+    ///     return StatusCode::NOT_ACCEPTABLE.into_response();
+    /// };
+    /// let (body, content_type) = http::record_to_http_body_for_kind(&record, kind, &config)?;
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn negotiate_headers(&self, headers: &http::HeaderMap) -> Option<SerializerConfig> {
+        let value = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        let format = if let Some(formats) = value(HEADER_ACCEPT_FORMATS) {
+            self.select_from_accept_formats(formats)
+        } else if let Some(accept) = value(http::header::ACCEPT.as_str()) {
+            self.select_from_accept(accept)
+        } else {
+            self.default_format()
+        }?;
+        Some(self.config_for(format))
+    }
+}
+
+/// Returns how specifically a media range matches a format (higher is more specific),
+/// or `None` if it does not match.
+fn specificity(media: &str, format: WireFormat) -> Option<u8> {
+    let exact = match format {
+        WireFormat::Binary => CONTENT_TYPE_LNMP_BINARY,
+        WireFormat::Text => CONTENT_TYPE_LNMP_TEXT,
+        WireFormat::ExplainText => CONTENT_TYPE_LNMP_EXPLAIN,
+        WireFormat::ShortForm => CONTENT_TYPE_LNMP_SHORTFORM,
+    };
+    match media {
+        "*/*" => Some(0),
+        "application/*" => Some(1),
+        "text/*" if format == WireFormat::Text => Some(1),
+        "text/plain" if format == WireFormat::Text => Some(2),
+        "application/octet-stream" if format == WireFormat::Binary => Some(2),
+        _ if media == exact => Some(2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_codec::binary::Capabilities;
+
+    #[test]
+    fn test_accept_weights_and_preference() {
+        let negotiator = ContentNegotiator::new();
+        assert_eq!(
+            negotiator.select_from_accept("application/lnmp-text, application/lnmp-binary"),
+            Some(WireFormat::Binary)
+        );
+        assert_eq!(
+            negotiator.select_from_accept("application/lnmp-binary;q=0, */*;q=0.1"),
+            Some(WireFormat::Text)
+        );
+        assert_eq!(
+            negotiator.select_from_accept("text/plain"),
+            Some(WireFormat::Text)
+        );
+        assert_eq!(negotiator.select_from_accept(""), Some(WireFormat::Binary));
+
+        let text_only = ContentNegotiator::new().with_supported(&[WireFormat::Text]);
+        assert_eq!(
+            text_only.select_from_accept("application/lnmp-binary"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_accept_formats_header() {
+        let negotiator = ContentNegotiator::new();
+        let header = accept_formats_header(&[WireFormat::ShortForm, WireFormat::Text]);
+        assert_eq!(header, "shortform, text");
+        assert_eq!(
+            negotiator.select_from_accept_formats(&header),
+            Some(WireFormat::ShortForm)
+        );
+        assert_eq!(
+            negotiator.select_from_accept_formats("cbor, TEXT"),
+            Some(WireFormat::Text)
+        );
+        assert_eq!(negotiator.select_from_accept_formats("cbor"), None);
+    }
+
+    #[test]
+    fn test_negotiation_features() {
+        let negotiator =
+            ContentNegotiator::new().with_supported(&[WireFormat::ShortForm, WireFormat::Text]);
+        let v5 = Capabilities::v0_5();
+        let message = NegotiationMessage::CapabilitiesAck {
+            version: v5.version,
+            features: v5.features,
+        };
+        assert_eq!(
+            negotiator.select_from_negotiation(&message),
+            Some(WireFormat::ShortForm)
+        );
+
+        let v4 = Capabilities::v0_4();
+        let message = NegotiationMessage::Capabilities {
+            version: v4.version,
+            features: v4.features,
+            supported_types: v4.supported_types,
+        };
+        assert_eq!(
+            negotiator.select_from_negotiation(&message),
+            Some(WireFormat::Text)
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_negotiate_headers() {
+        use lnmp_net::MessageKind;
+
+        let negotiator = ContentNegotiator::new();
+        let mut headers = http::HeaderMap::new();
+        headers.insert("accept", "application/lnmp-shortform".parse().unwrap());
+        let config = negotiator.negotiate_headers(&headers).unwrap();
+        assert_eq!(config.format_for(MessageKind::Event), WireFormat::ShortForm);
+
+        headers.insert(HEADER_ACCEPT_FORMATS, "text".parse().unwrap());
+        let config = negotiator.negotiate_headers(&headers).unwrap();
+        assert_eq!(config.format_for(MessageKind::Event), WireFormat::Text);
+
+        headers.insert(HEADER_ACCEPT_FORMATS, "cbor".parse().unwrap());
+        assert!(negotiator.negotiate_headers(&headers).is_none());
+    }
+}
@@ -5,6 +5,19 @@
 //! For gRPC payload handling, you can either:
 //! 1. Embed the LNMP binary record inside your Protobuf message as a `bytes` field, or
 //! 2. Use LNMP metadata only in the headers and send application data in the Protobuf message.
+//!
+//! With the `tonic` feature, the crate also ships the canonical service definition
+//! (`proto/lnmp/v1/lnmp.proto`): [`proto`] holds its message types, [`envelope_to_proto`]
+//! and [`proto_to_envelope`] convert envelopes, and [`LnmpCodec`] sends binary LNMP
+//! frames as raw gRPC message payloads.
+
+#[cfg(feature = "tonic")]
+mod codec;
+#[cfg(feature = "tonic")]
+pub mod proto;
+
+#[cfg(feature = "tonic")]
+pub use codec::{LnmpCodec, LnmpFrameDecoder, LnmpFrameEncoder};
 
 use crate::{Result, TransportError};
 use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
@@ -16,12 +29,27 @@ pub const META_TIMESTAMP: &str = "lnmp-timestamp";
 /// gRPC metadata key for LNMP source identifier.
 pub const META_SOURCE: &str = "lnmp-source";
 
+/// gRPC metadata key for LNMP expiry time (Unix epoch milliseconds).
+pub const META_EXPIRES_AT: &str = "lnmp-expires-at";
+
 /// gRPC metadata key for LNMP trace ID.
 pub const META_TRACE_ID: &str = "lnmp-trace-id";
 
+/// gRPC metadata key for LNMP correlation ID.
+pub const META_CORRELATION_ID: &str = "lnmp-correlation-id";
+
+/// gRPC metadata key for LNMP causation ID.
+pub const META_CAUSATION_ID: &str = "lnmp-causation-id";
+
 /// gRPC metadata key for LNMP sequence number.
 pub const META_SEQUENCE: &str = "lnmp-sequence";
 
+/// gRPC metadata key for the LNMP payload content type.
+pub const META_CONTENT_TYPE: &str = "lnmp-content-type";
+
+/// gRPC metadata key for LNMP FID schema version.
+pub const META_SCHEMA_VERSION: &str = "lnmp-schema-version";
+
 /// gRPC metadata key prefix for LNMP labels.
 pub const META_LABEL_PREFIX: &str = "lnmp-label-";
 
@@ -43,6 +71,10 @@ pub fn envelope_to_metadata(env: &LnmpEnvelope) -> Result<HashMap<String, String
         metadata.insert(META_TIMESTAMP.to_string(), ts.to_string());
     }
 
+    if let Some(exp) = meta.expires_at {
+        metadata.insert(META_EXPIRES_AT.to_string(), exp.to_string());
+    }
+
     if let Some(src) = &meta.source {
         metadata.insert(META_SOURCE.to_string(), src.clone());
     }
@@ -51,10 +83,26 @@ pub fn envelope_to_metadata(env: &LnmpEnvelope) -> Result<HashMap<String, String
         metadata.insert(META_TRACE_ID.to_string(), trace_id.clone());
     }
 
+    if let Some(id) = &meta.correlation_id {
+        metadata.insert(META_CORRELATION_ID.to_string(), id.clone());
+    }
+
+    if let Some(id) = &meta.causation_id {
+        metadata.insert(META_CAUSATION_ID.to_string(), id.clone());
+    }
+
     if let Some(seq) = meta.sequence {
         metadata.insert(META_SEQUENCE.to_string(), seq.to_string());
     }
 
+    if let Some(content_type) = &meta.content_type {
+        metadata.insert(META_CONTENT_TYPE.to_string(), content_type.clone());
+    }
+
+    if let Some(version) = &meta.schema_version {
+        metadata.insert(META_SCHEMA_VERSION.to_string(), version.clone());
+    }
+
     for (k, v) in &meta.labels {
         let key = format!("{}{}", META_LABEL_PREFIX, k);
         metadata.insert(key, v.clone());
@@ -82,6 +130,12 @@ pub fn metadata_to_envelope_metadata(map: &HashMap<String, String>) -> Result<En
         })?);
     }
 
+    if let Some(val) = map.get(META_EXPIRES_AT) {
+        meta.expires_at = Some(val.parse().map_err(|_e| {
+            TransportError::InvalidHeaderValue("expires_at".into(), "parse error".into())
+        })?);
+    }
+
     if let Some(val) = map.get(META_SOURCE) {
         meta.source = Some(val.clone());
     }
@@ -90,12 +144,28 @@ pub fn metadata_to_envelope_metadata(map: &HashMap<String, String>) -> Result<En
         meta.trace_id = Some(val.clone());
     }
 
+    if let Some(val) = map.get(META_CORRELATION_ID) {
+        meta.correlation_id = Some(val.clone());
+    }
+
+    if let Some(val) = map.get(META_CAUSATION_ID) {
+        meta.causation_id = Some(val.clone());
+    }
+
     if let Some(val) = map.get(META_SEQUENCE) {
         meta.sequence = Some(val.parse().map_err(|_e| {
             TransportError::InvalidHeaderValue("sequence".into(), "parse error".into())
         })?);
     }
 
+    if let Some(val) = map.get(META_CONTENT_TYPE) {
+        meta.content_type = Some(val.clone());
+    }
+
+    if let Some(val) = map.get(META_SCHEMA_VERSION) {
+        meta.schema_version = Some(val.clone());
+    }
+
     for (name, value) in map {
         if name.starts_with(META_LABEL_PREFIX) {
             let key = name.trim_start_matches(META_LABEL_PREFIX).to_string();
@@ -105,3 +175,88 @@ pub fn metadata_to_envelope_metadata(map: &HashMap<String, String>) -> Result<En
 
     Ok(meta)
 }
+
+/// Converts an LNMP Envelope to the Protobuf `lnmp.v1.Envelope`.
+///
+/// The record is carried as a binary LNMP frame.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::grpc;
+/// let message = grpc::envelope_to_proto(&envelope)?;
+/// client.publish(message).await?;
+/// ```
+#[cfg(feature = "tonic")]
+pub fn envelope_to_proto(env: &LnmpEnvelope) -> Result<proto::Envelope> {
+    let frame = lnmp_codec::binary::BinaryEncoder::new().encode(&env.record)?;
+    let meta = env.metadata.clone();
+    Ok(proto::Envelope {
+        timestamp: meta.timestamp,
+        expires_at: meta.expires_at,
+        source: meta.source,
+        trace_id: meta.trace_id,
+        correlation_id: meta.correlation_id,
+        causation_id: meta.causation_id,
+        partition_key: meta.partition_key,
+        sequence: meta.sequence,
+        schema_version: meta.schema_version,
+        labels: meta.labels,
+        signature: meta.signature.map(|sig| proto::Signature {
+            algorithm: sig.algorithm,
+            key_id: sig.key_id,
+            signature: sig.signature,
+        }),
+        record: Some(proto::LnmpFrame {
+            frame,
+            content_type: crate::serializer::CONTENT_TYPE_LNMP_BINARY.to_string(),
+        }),
+    })
+}
+
+/// Converts a Protobuf `lnmp.v1.Envelope` to an LNMP Envelope.
+///
+/// The record is decoded according to its frame's content type (binary when empty).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::grpc;
+/// let envelope = grpc::proto_to_envelope(&request.into_inner())?;
+/// ```
+#[cfg(feature = "tonic")]
+pub fn proto_to_envelope(message: &proto::Envelope) -> Result<LnmpEnvelope> {
+    let frame = message
+        .record
+        .as_ref()
+        .ok_or_else(|| TransportError::MissingHeader("record".into()))?;
+    let content_type = if frame.content_type.is_empty() {
+        crate::serializer::CONTENT_TYPE_LNMP_BINARY
+    } else {
+        frame.content_type.as_str()
+    };
+    let record = crate::serializer::decode_body(&frame.frame, content_type)?;
+
+    let metadata = EnvelopeMetadata {
+        timestamp: message.timestamp,
+        expires_at: message.expires_at,
+        source: message.source.clone(),
+        trace_id: message.trace_id.clone(),
+        correlation_id: message.correlation_id.clone(),
+        causation_id: message.causation_id.clone(),
+        partition_key: message.partition_key.clone(),
+        sequence: message.sequence,
+        content_type: Some(content_type.to_string()),
+        schema_version: message.schema_version.clone(),
+        labels: message.labels.clone(),
+        signature: message
+            .signature
+            .as_ref()
+            .map(|sig| lnmp_envelope::EnvelopeSignature {
+                algorithm: sig.algorithm.clone(),
+                key_id: sig.key_id.clone(),
+                signature: sig.signature.clone(),
+            }),
+    };
+    Ok(LnmpEnvelope { metadata, record })
+}
@@ -0,0 +1,83 @@
+//! tonic codec carrying binary LNMP frames as gRPC message payloads.
+
+use bytes::{Buf, BufMut};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_core::LnmpRecord;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::Status;
+
+/// A [`Codec`] whose messages are binary LNMP record frames.
+///
+/// No Protobuf wrapping is involved: each gRPC message is exactly the
+/// `BinaryEncoder` output of one record. Envelope metadata travels in request and
+/// response metadata (see [`envelope_to_metadata`](super::envelope_to_metadata)), or
+/// use the Protobuf [`Envelope`](super::proto::Envelope) for per-message metadata.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::grpc::LnmpCodec;
+///
+/// let mut grpc = tonic::client::Grpc::new(channel);
+/// grpc.ready().await?;
+/// let path = http::uri::PathAndQuery::from_static("/sensors.v1.Telemetry/Report");
+/// let reply = grpc.unary(tonic::Request::new(record), path, LnmpCodec::new()).await?;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LnmpCodec;
+
+impl LnmpCodec {
+    /// Creates a codec.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Codec for LnmpCodec {
+    type Encode = LnmpRecord;
+    type Decode = LnmpRecord;
+    type Encoder = LnmpFrameEncoder;
+    type Decoder = LnmpFrameDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        LnmpFrameEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        LnmpFrameDecoder
+    }
+}
+
+/// Encoder half of [`LnmpCodec`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LnmpFrameEncoder;
+
+impl Encoder for LnmpFrameEncoder {
+    type Item = LnmpRecord;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        let frame = BinaryEncoder::new()
+            .encode(&item)
+            .map_err(|e| Status::internal(format!("LNMP encode failed: {}", e)))?;
+        dst.put_slice(&frame);
+        Ok(())
+    }
+}
+
+/// Decoder half of [`LnmpCodec`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LnmpFrameDecoder;
+
+impl Decoder for LnmpFrameDecoder {
+    type Item = LnmpRecord;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let frame = src.copy_to_bytes(src.remaining());
+        BinaryDecoder::new()
+            .decode(&frame)
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(format!("invalid LNMP frame: {}", e)))
+    }
+}
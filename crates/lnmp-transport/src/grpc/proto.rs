@@ -0,0 +1,93 @@
+//! Protobuf types of `proto/lnmp/v1/lnmp.proto` (package `lnmp.v1`).
+//!
+//! These mirror what `prost-build` generates for the canonical `.proto`, so the crate
+//! builds without `protoc`. Keep them in sync with the `.proto` file.
+
+use std::collections::BTreeMap;
+
+/// Fully-qualified name of the `LnmpStream` service.
+pub const SERVICE_NAME: &str = "lnmp.v1.LnmpStream";
+
+/// gRPC path of `LnmpStream.Publish`.
+pub const METHOD_PUBLISH: &str = "/lnmp.v1.LnmpStream/Publish";
+
+/// gRPC path of `LnmpStream.Subscribe`.
+pub const METHOD_SUBSCRIBE: &str = "/lnmp.v1.LnmpStream/Subscribe";
+
+/// gRPC path of `LnmpStream.Exchange`.
+pub const METHOD_EXCHANGE: &str = "/lnmp.v1.LnmpStream/Exchange";
+
+/// An encoded LNMP record.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct LnmpFrame {
+    /// Record bytes: a binary LNMP frame, or text in one of the LNMP text formats.
+    #[prost(bytes = "vec", tag = "1")]
+    pub frame: Vec<u8>,
+    /// Media type of `frame`; empty means `application/lnmp-binary`.
+    #[prost(string, tag = "2")]
+    pub content_type: String,
+}
+
+/// Envelope signature over the canonical record and metadata.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Signature {
+    #[prost(string, tag = "1")]
+    pub algorithm: String,
+    #[prost(string, tag = "2")]
+    pub key_id: String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: Vec<u8>,
+}
+
+/// An LNMP record with its envelope metadata.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Envelope {
+    /// Unix epoch milliseconds.
+    #[prost(uint64, optional, tag = "1")]
+    pub timestamp: Option<u64>,
+    /// Unix epoch milliseconds after which the record is stale.
+    #[prost(uint64, optional, tag = "2")]
+    pub expires_at: Option<u64>,
+    #[prost(string, optional, tag = "3")]
+    pub source: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub trace_id: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub correlation_id: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub causation_id: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub partition_key: Option<String>,
+    #[prost(uint64, optional, tag = "8")]
+    pub sequence: Option<u64>,
+    #[prost(string, optional, tag = "9")]
+    pub schema_version: Option<String>,
+    #[prost(btree_map = "string, string", tag = "10")]
+    pub labels: BTreeMap<String, String>,
+    #[prost(message, optional, tag = "11")]
+    pub signature: Option<Signature>,
+    #[prost(message, optional, tag = "15")]
+    pub record: Option<LnmpFrame>,
+}
+
+/// Acknowledges a published envelope.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct PublishAck {
+    /// Sequence number assigned to (or carried by) the envelope.
+    #[prost(uint64, optional, tag = "1")]
+    pub sequence: Option<u64>,
+}
+
+/// Selects the envelopes a subscriber receives.
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct SubscribeRequest {
+    /// Only envelopes from this source; empty means all sources.
+    #[prost(string, tag = "1")]
+    pub source: String,
+    /// Resume after this sequence number.
+    #[prost(uint64, optional, tag = "2")]
+    pub after_sequence: Option<u64>,
+    /// Only envelopes carrying all of these labels.
+    #[prost(btree_map = "string, string", tag = "3")]
+    pub labels: BTreeMap<String, String>,
+}
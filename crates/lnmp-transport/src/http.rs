@@ -3,45 +3,76 @@
 //! This module provides helpers to map LNMP Envelope metadata to/from HTTP headers,
 //! encode/decode LNMP record bodies, and integrate with W3C Trace Context for distributed tracing.
 
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "tower")]
+pub mod tower;
+
+use crate::serializer::{self, SerializerConfig};
 use crate::{Result, TransportError};
 #[cfg(feature = "http")]
 use http::{HeaderMap, HeaderName, HeaderValue};
-use lnmp_codec::Parser;
 use lnmp_core::LnmpRecord;
 use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::MessageKind;
 use std::str::FromStr;
 
 /// HTTP header name for LNMP timestamp (Unix epoch milliseconds).
 pub const HEADER_TIMESTAMP: &str = "X-LNMP-Timestamp";
 
+/// HTTP header name for LNMP expiry time (Unix epoch milliseconds).
+pub const HEADER_EXPIRES_AT: &str = "X-LNMP-Expires-At";
+
 /// HTTP header name for LNMP source identifier.
 pub const HEADER_SOURCE: &str = "X-LNMP-Source";
 
 /// HTTP header name for LNMP trace ID.
 pub const HEADER_TRACE_ID: &str = "X-LNMP-Trace-Id";
 
+/// HTTP header name for LNMP correlation ID.
+pub const HEADER_CORRELATION_ID: &str = "X-LNMP-Correlation-Id";
+
+/// HTTP header name for LNMP causation ID.
+pub const HEADER_CAUSATION_ID: &str = "X-LNMP-Causation-Id";
+
 /// HTTP header name for LNMP sequence number.
 pub const HEADER_SEQUENCE: &str = "X-LNMP-Sequence";
 
+/// HTTP header name for the LNMP payload content type carried in the envelope.
+pub const HEADER_CONTENT_TYPE: &str = "X-LNMP-Content-Type";
+
+/// HTTP header name for LNMP FID schema version.
+pub const HEADER_SCHEMA_VERSION: &str = "X-LNMP-Schema-Version";
+
 /// HTTP header name prefix for LNMP labels.
 pub const HEADER_LABEL_PREFIX: &str = "X-LNMP-Label-";
 
+/// HTTP header (or trailer) name for the LNMP content digest: the lowercase hex
+/// BLAKE3 canonical hash of the record in the body.
+pub const HEADER_CONTENT_DIGEST: &str = "X-LNMP-Content-Digest";
+
 /// W3C Trace Context traceparent header name.
 pub const HEADER_TRACEPARENT: &str = "traceparent";
 
-/// Content-Type for LNMP binary format.
-pub const CONTENT_TYPE_LNMP_BINARY: &str = "application/lnmp-binary";
-
-/// Content-Type for LNMP text format.
-pub const CONTENT_TYPE_LNMP_TEXT: &str = "application/lnmp-text";
+pub use crate::serializer::{
+    CONTENT_TYPE_LNMP_BINARY, CONTENT_TYPE_LNMP_EXPLAIN, CONTENT_TYPE_LNMP_SHORTFORM,
+    CONTENT_TYPE_LNMP_TEXT,
+};
 
 /// Converts an LNMP Envelope's metadata to HTTP headers.
 ///
 /// This function maps envelope metadata fields to standard LNMP HTTP headers:
 /// - `timestamp` → `X-LNMP-Timestamp`
+/// - `expires_at` → `X-LNMP-Expires-At`
 /// - `source` → `X-LNMP-Source`
 /// - `trace_id` → `X-LNMP-Trace-Id` and `traceparent` (W3C Trace Context)
+/// - `correlation_id` → `X-LNMP-Correlation-Id`
+/// - `causation_id` → `X-LNMP-Causation-Id`
 /// - `sequence` → `X-LNMP-Sequence`
+/// - `content_type` → `X-LNMP-Content-Type`
+/// - `schema_version` → `X-LNMP-Schema-Version`
 /// - `labels["key"]` → `X-LNMP-Label-key`
 ///
 /// # Example
@@ -64,6 +95,15 @@ pub fn envelope_to_headers(env: &LnmpEnvelope) -> Result<HeaderMap> {
         );
     }
 
+    if let Some(exp) = meta.expires_at {
+        headers.insert(
+            HeaderName::from_static("x-lnmp-expires-at"),
+            HeaderValue::from_str(&exp.to_string()).map_err(|e| {
+                TransportError::InvalidHeaderValue("expires_at".into(), e.to_string())
+            })?,
+        );
+    }
+
     if let Some(src) = &meta.source {
         headers.insert(
             HeaderName::from_static("x-lnmp-source"),
@@ -80,8 +120,12 @@ pub fn envelope_to_headers(env: &LnmpEnvelope) -> Result<HeaderMap> {
             })?,
         );
 
-        // Generate W3C traceparent header
-        let traceparent = trace_id_to_traceparent(trace_id, None, 0x01);
+        // Generate W3C traceparent header, keeping one the trace_id already holds
+        let traceparent = if is_traceparent(trace_id) {
+            trace_id.clone()
+        } else {
+            trace_id_to_traceparent(trace_id, None, 0x01)
+        };
         headers.insert(
             HeaderName::from_static("traceparent"),
             HeaderValue::from_str(&traceparent).map_err(|e| {
@@ -90,6 +134,24 @@ pub fn envelope_to_headers(env: &LnmpEnvelope) -> Result<HeaderMap> {
         );
     }
 
+    if let Some(id) = &meta.correlation_id {
+        headers.insert(
+            HeaderName::from_static("x-lnmp-correlation-id"),
+            HeaderValue::from_str(id).map_err(|e| {
+                TransportError::InvalidHeaderValue("correlation_id".into(), e.to_string())
+            })?,
+        );
+    }
+
+    if let Some(id) = &meta.causation_id {
+        headers.insert(
+            HeaderName::from_static("x-lnmp-causation-id"),
+            HeaderValue::from_str(id).map_err(|e| {
+                TransportError::InvalidHeaderValue("causation_id".into(), e.to_string())
+            })?,
+        );
+    }
+
     if let Some(seq) = meta.sequence {
         headers.insert(
             HeaderName::from_static("x-lnmp-sequence"),
@@ -99,6 +161,24 @@ pub fn envelope_to_headers(env: &LnmpEnvelope) -> Result<HeaderMap> {
         );
     }
 
+    if let Some(content_type) = &meta.content_type {
+        headers.insert(
+            HeaderName::from_static("x-lnmp-content-type"),
+            HeaderValue::from_str(content_type).map_err(|e| {
+                TransportError::InvalidHeaderValue("content_type".into(), e.to_string())
+            })?,
+        );
+    }
+
+    if let Some(version) = &meta.schema_version {
+        headers.insert(
+            HeaderName::from_static("x-lnmp-schema-version"),
+            HeaderValue::from_str(version).map_err(|e| {
+                TransportError::InvalidHeaderValue("schema_version".into(), e.to_string())
+            })?,
+        );
+    }
+
     for (k, v) in &meta.labels {
         let header_name = format!("{}{}", HEADER_LABEL_PREFIX, k).to_lowercase();
         if let Ok(name) = HeaderName::from_str(&header_name) {
@@ -133,6 +213,12 @@ pub fn headers_to_envelope_metadata(headers: &HeaderMap) -> Result<EnvelopeMetad
         }
     }
 
+    if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-expires-at")) {
+        if let Ok(s) = val.to_str() {
+            meta.expires_at = s.parse().ok();
+        }
+    }
+
     if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-source")) {
         if let Ok(s) = val.to_str() {
             meta.source = Some(s.to_string());
@@ -152,12 +238,36 @@ pub fn headers_to_envelope_metadata(headers: &HeaderMap) -> Result<EnvelopeMetad
         }
     }
 
+    if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-correlation-id")) {
+        if let Ok(s) = val.to_str() {
+            meta.correlation_id = Some(s.to_string());
+        }
+    }
+
+    if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-causation-id")) {
+        if let Ok(s) = val.to_str() {
+            meta.causation_id = Some(s.to_string());
+        }
+    }
+
     if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-sequence")) {
         if let Ok(s) = val.to_str() {
             meta.sequence = s.parse().ok();
         }
     }
 
+    if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-content-type")) {
+        if let Ok(s) = val.to_str() {
+            meta.content_type = Some(s.to_string());
+        }
+    }
+
+    if let Some(val) = headers.get(HeaderName::from_static("x-lnmp-schema-version")) {
+        if let Ok(s) = val.to_str() {
+            meta.schema_version = Some(s.to_string());
+        }
+    }
+
     for (name, value) in headers {
         let name_str = name.as_str();
         if name_str.starts_with("x-lnmp-label-") {
@@ -238,9 +348,28 @@ pub fn record_to_http_body(record: &LnmpRecord) -> Result<(Vec<u8>, &'static str
     Ok((encoded, CONTENT_TYPE_LNMP_BINARY))
 }
 
+/// Encodes an LNMP record for HTTP body in the format configured for `kind`.
+///
+/// Returns the encoded bytes and the matching Content-Type header value.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let config = SerializerConfig::new().with_format(MessageKind::Alert, WireFormat::ExplainText);
+/// let (body, content_type) = record_to_http_body_for_kind(&record, MessageKind::Alert, &config)?;
+/// // content_type: "application/lnmp-explain"
+/// ```
+pub fn record_to_http_body_for_kind(
+    record: &LnmpRecord,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<(Vec<u8>, &'static str)> {
+    config.encode(kind, record)
+}
+
 /// Decodes an LNMP record from HTTP body bytes.
 ///
-/// Supports both binary and text formats based on Content-Type header.
+/// Supports binary, text, explain-annotated text and ShortForm formats based on Content-Type header.
 ///
 /// # Example
 ///
@@ -248,27 +377,105 @@ pub fn record_to_http_body(record: &LnmpRecord) -> Result<(Vec<u8>, &'static str
 /// let record = http_body_to_record(&body, "application/lnmp-binary")?;
 /// ```
 pub fn http_body_to_record(body: &[u8], content_type: &str) -> Result<LnmpRecord> {
-    if content_type.contains("lnmp-binary") || content_type.contains("octet-stream") {
-        // Binary format
-        use lnmp_codec::binary::BinaryDecoder;
-        let decoder = BinaryDecoder::new();
-        Ok(decoder.decode(body)?)
-    } else if content_type.contains("lnmp-text") || content_type.contains("text/plain") {
-        // Text format
-        let text = std::str::from_utf8(body)
-            .map_err(|_| TransportError::InvalidHeaderValue("body".into(), "not utf8".into()))?;
-        let mut parser = Parser::new(text)?;
-        Ok(parser.parse_record()?)
+    serializer::decode_body(body, content_type)
+}
+
+/// Computes the content digest of a record.
+///
+/// The digest is the record's canonical hash, so it does not depend on the body
+/// format or on field order: a proxy may re-encode the body without breaking it.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let digest = http::content_digest(&record)?;
+/// ```
+pub fn content_digest(record: &LnmpRecord) -> Result<String> {
+    let hash = lnmp_codec::binary::BinaryEncoder::new().canonical_hash(record)?;
+    Ok(hash.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Writes the `X-LNMP-Content-Digest` of a record.
+///
+/// Works on request/response headers and on trailers alike, which lets streaming
+/// senders append the digest once the body is written.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut headers = http::envelope_to_headers(&envelope)?;
+/// http::insert_content_digest(&mut headers, &envelope.record)?;
+/// ```
+#[cfg(feature = "http")]
+pub fn insert_content_digest(headers: &mut HeaderMap, record: &LnmpRecord) -> Result<()> {
+    let digest = content_digest(record)?;
+    headers.insert(
+        HeaderName::from_static("x-lnmp-content-digest"),
+        HeaderValue::from_str(&digest).map_err(|e| {
+            TransportError::InvalidHeaderValue("content_digest".into(), e.to_string())
+        })?,
+    );
+    Ok(())
+}
+
+/// Checks a decoded record against the `X-LNMP-Content-Digest` header or trailer.
+///
+/// Returns `Ok(true)` when the digest matches and `Ok(false)` when there is none.
+/// The comparison runs in constant time.
+///
+/// # Errors
+///
+/// [`TransportError::ContentDigestMismatch`] if the record does not match the digest,
+/// [`TransportError::InvalidHeaderValue`] if the header is not a hex digest.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let record = http::http_body_to_record(&body, content_type)?;
+/// http::verify_content_digest(&headers, &record)?;
+/// ```
+#[cfg(feature = "http")]
+pub fn verify_content_digest(headers: &HeaderMap, record: &LnmpRecord) -> Result<bool> {
+    let Some(value) = headers.get(HeaderName::from_static("x-lnmp-content-digest")) else {
+        return Ok(false);
+    };
+    let expected = value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|s| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            TransportError::InvalidHeaderValue(
+                "content_digest".into(),
+                "expected 64 hex digits".into(),
+            )
+        })?
+        .to_ascii_lowercase();
+    let actual = content_digest(record)?;
+    if constant_time_eq(expected.as_bytes(), actual.as_bytes()) {
+        Ok(true)
     } else {
-        Err(TransportError::InvalidHeaderValue(
-            "content-type".into(),
-            format!("unsupported: {}", content_type),
-        ))
+        Err(TransportError::ContentDigestMismatch)
     }
 }
 
 // Helper functions
 
+/// Compares two byte strings without exiting early on the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_traceparent(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let hex = |s: &str, len: usize| s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit());
+    parts.len() == 4
+        && parts[0] == "00"
+        && hex(parts[1], 32)
+        && hex(parts[2], 16)
+        && hex(parts[3], 2)
+}
+
 fn normalize_trace_id_for_w3c(trace_id: &str) -> String {
     let hex_only: String = trace_id.chars().filter(|c| c.is_ascii_hexdigit()).collect();
 
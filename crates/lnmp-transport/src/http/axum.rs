@@ -0,0 +1,156 @@
+//! [`axum`](::axum) integration for LNMP HTTP endpoints.
+//!
+//! [`LnmpBody`] is both an extractor and a responder: it decodes the request body
+//! according to its `Content-Type` (binary, text, explain or ShortForm) and reads the
+//! envelope metadata from the `X-LNMP-*` headers, checking `X-LNMP-Content-Digest`
+//! when the client sent one; returned from a handler, it encodes
+//! the record in its format and writes the metadata back as headers.
+//!
+//! ```rust,ignore
+//! use axum::{routing::post, Router};
+//! use lnmp_transport::http::axum::LnmpBody;
+//!
+//! async fn ingest(LnmpBody { envelope, .. }: LnmpBody) -> LnmpBody {
+//!     // handle envelope.record
+//!     LnmpBody::new(envelope)
+//! }
+//!
+//! let app = Router::new().route("/ingest", post(ingest));
+//! ```
+
+use super::{envelope_to_headers, headers_to_envelope_metadata, verify_content_digest};
+use crate::serializer::{self, SerializerConfig, WireFormat};
+use crate::TransportError;
+use ::axum::body::Bytes;
+use ::axum::extract::rejection::BytesRejection;
+use ::axum::extract::{FromRequest, Request};
+use ::axum::response::{IntoResponse, Response};
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, StatusCode};
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::LnmpEnvelope;
+
+/// An LNMP envelope carried in an HTTP request or response body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LnmpBody {
+    /// The record and its metadata from the `X-LNMP-*` headers.
+    pub envelope: LnmpEnvelope,
+    /// Body format; extracted bodies keep the request's format.
+    pub format: WireFormat,
+}
+
+impl LnmpBody {
+    /// Wraps an envelope, to be sent as a binary body.
+    pub fn new(envelope: LnmpEnvelope) -> Self {
+        Self {
+            envelope,
+            format: WireFormat::Binary,
+        }
+    }
+
+    /// Wraps a record without metadata, to be sent as a binary body.
+    pub fn from_record(record: LnmpRecord) -> Self {
+        Self::new(LnmpEnvelope::new(record))
+    }
+
+    /// Sets the body format.
+    pub fn with_format(mut self, format: WireFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the record.
+    pub fn record(&self) -> &LnmpRecord {
+        &self.envelope.record
+    }
+
+    /// Returns the envelope.
+    pub fn into_envelope(self) -> LnmpEnvelope {
+        self.envelope
+    }
+}
+
+/// Rejection returned when a request cannot be extracted as an [`LnmpBody`].
+#[derive(Debug, thiserror::Error)]
+pub enum LnmpRejection {
+    /// The `Content-Type` is missing or not an LNMP format (415).
+    #[error("Unsupported LNMP content type: {0}")]
+    UnsupportedContentType(String),
+    /// An `X-LNMP-*` header could not be parsed (400).
+    #[error("Invalid LNMP headers: {0}")]
+    InvalidHeaders(TransportError),
+    /// The body could not be decoded as an LNMP record, or does not match its
+    /// `X-LNMP-Content-Digest` (400).
+    #[error("Invalid LNMP body: {0}")]
+    InvalidBody(TransportError),
+    /// The body could not be read.
+    #[error(transparent)]
+    Body(#[from] BytesRejection),
+}
+
+impl LnmpRejection {
+    /// Returns the HTTP status of the rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            LnmpRejection::UnsupportedContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            LnmpRejection::InvalidHeaders(_) | LnmpRejection::InvalidBody(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            LnmpRejection::Body(rejection) => rejection.status(),
+        }
+    }
+}
+
+impl IntoResponse for LnmpRejection {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
+
+impl<S: Send + Sync> FromRequest<S> for LnmpBody {
+    type Rejection = LnmpRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let format = WireFormat::from_content_type(&content_type)
+            .ok_or_else(|| LnmpRejection::UnsupportedContentType(content_type.clone()))?;
+        let metadata =
+            headers_to_envelope_metadata(req.headers()).map_err(LnmpRejection::InvalidHeaders)?;
+        let headers = req.headers().clone();
+
+        let body = Bytes::from_request(req, state).await?;
+        let record =
+            serializer::decode_body(&body, &content_type).map_err(LnmpRejection::InvalidBody)?;
+        verify_content_digest(&headers, &record).map_err(LnmpRejection::InvalidBody)?;
+        Ok(Self {
+            envelope: LnmpEnvelope { metadata, record },
+            format,
+        })
+    }
+}
+
+/// Responds `200 OK` with the encoded record, its `Content-Type` and the
+/// `X-LNMP-*` metadata headers, or `500` if they cannot be encoded.
+impl IntoResponse for LnmpBody {
+    fn into_response(self) -> Response {
+        let encoded = envelope_to_headers(&self.envelope).and_then(|headers| {
+            let body = SerializerConfig::new().encode_as(self.format, &self.envelope.record)?;
+            Ok((headers, body))
+        });
+        match encoded {
+            Ok((mut headers, body)) => {
+                headers.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(self.format.content_type()),
+                );
+                (StatusCode::OK, headers, body).into_response()
+            }
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! [`reqwest`] client helpers for LNMP HTTP calls.
+//!
+//! [`post_record`] sends an envelope with the `X-LNMP-*` headers and the body in the
+//! requested format, and parses the response back into an envelope. `429` and `503`
+//! responses are retried after their `Retry-After` delay; other failures become
+//! [`TransportError::HttpStatus`] carrying the decoded error body. Responses carrying
+//! `X-LNMP-Content-Digest` are checked against it.
+//!
+//! ```rust,ignore
+//! use lnmp_transport::http::client;
+//! use lnmp_transport::WireFormat;
+//!
+//! let reply = client::post_record("http://planner/ingest", &envelope, WireFormat::Binary).await?;
+//! ```
+
+use super::{
+    envelope_to_headers, headers_to_envelope_metadata, insert_content_digest, verify_content_digest,
+};
+use crate::serializer::{self, SerializerConfig, WireFormat};
+use crate::{Result, TransportError};
+use http::header::{ACCEPT, CONTENT_TYPE, RETRY_AFTER};
+use http::{HeaderMap, StatusCode};
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::LnmpEnvelope;
+use std::time::Duration;
+
+/// Delay before retrying a `429`/`503` response without `Retry-After`.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// LNMP HTTP client over a [`reqwest::Client`].
+///
+/// Clones share the underlying connection pool.
+#[derive(Debug, Clone)]
+pub struct LnmpClient {
+    http: reqwest::Client,
+    max_retries: u32,
+    max_retry_after: Duration,
+    content_digest: bool,
+}
+
+impl Default for LnmpClient {
+    fn default() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            max_retries: 2,
+            max_retry_after: Duration::from_secs(30),
+            content_digest: false,
+        }
+    }
+}
+
+impl LnmpClient {
+    /// Creates a client retrying up to 2 times, waiting at most 30 s per retry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses a configured reqwest client (timeouts, TLS, proxies).
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Sets how many times `429`/`503` responses are retried.
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Sets the longest `Retry-After` delay waited for; longer ones fail at once.
+    pub fn with_max_retry_after(mut self, delay: Duration) -> Self {
+        self.max_retry_after = delay;
+        self
+    }
+
+    /// Sends `X-LNMP-Content-Digest` with each request, so the server can check the
+    /// body was not modified in transit.
+    pub fn with_content_digest(mut self, enabled: bool) -> Self {
+        self.content_digest = enabled;
+        self
+    }
+
+    /// POSTs an envelope and returns the response envelope.
+    ///
+    /// The request body is encoded in `format` and the same format is asked for in
+    /// `Accept`. The response is decoded by its `Content-Type`, with metadata from its
+    /// `X-LNMP-*` headers; an empty response body yields an empty record.
+    pub async fn post_record(
+        &self,
+        url: &str,
+        env: &LnmpEnvelope,
+        format: WireFormat,
+    ) -> Result<LnmpEnvelope> {
+        let body = SerializerConfig::new().encode_as(format, &env.record)?;
+        let mut headers = envelope_to_headers(env)?;
+        if self.content_digest {
+            insert_content_digest(&mut headers, &env.record)?;
+        }
+        headers.insert(
+            CONTENT_TYPE,
+            http::HeaderValue::from_static(format.content_type()),
+        );
+        headers.insert(
+            ACCEPT,
+            http::HeaderValue::from_static(format.content_type()),
+        );
+
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .http
+                .post(url)
+                .headers(headers.clone())
+                .body(body.clone())
+                .send()
+                .await
+                .map_err(|e| TransportError::Http(e.to_string()))?;
+            let status = response.status();
+            let response_headers = response.headers().clone();
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| TransportError::Http(e.to_string()))?;
+
+            if status.is_success() {
+                return response_to_envelope(&response_headers, &bytes);
+            }
+
+            let retry_after = retry_after(&response_headers);
+            let retryable = matches!(
+                status,
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+            );
+            let delay = retry_after.unwrap_or(DEFAULT_RETRY_DELAY);
+            if retryable && attempt < self.max_retries && delay <= self.max_retry_after {
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            return Err(error_response(
+                status,
+                retry_after,
+                &response_headers,
+                &bytes,
+            ));
+        }
+    }
+}
+
+/// POSTs an envelope with a default [`LnmpClient`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::http::client;
+/// let reply = client::post_record(url, &envelope, WireFormat::Text).await?;
+/// ```
+pub async fn post_record(
+    url: &str,
+    env: &LnmpEnvelope,
+    format: WireFormat,
+) -> Result<LnmpEnvelope> {
+    LnmpClient::new().post_record(url, env, format).await
+}
+
+fn response_to_envelope(headers: &HeaderMap, body: &[u8]) -> Result<LnmpEnvelope> {
+    let metadata = headers_to_envelope_metadata(headers)?;
+    let record = if body.is_empty() {
+        LnmpRecord::new()
+    } else {
+        serializer::decode_body(body, content_type(headers))?
+    };
+    verify_content_digest(headers, &record)?;
+    Ok(LnmpEnvelope { metadata, record })
+}
+
+/// Builds the error of a failed response, decoding LNMP error bodies.
+fn error_response(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> TransportError {
+    let record = WireFormat::from_content_type(content_type(headers))
+        .filter(|_| !body.is_empty())
+        .and_then(|_| serializer::decode_body(body, content_type(headers)).ok());
+    let message = match &record {
+        Some(record) => lnmp_codec::Encoder::new().encode(record),
+        None if body.is_empty() => status.canonical_reason().unwrap_or_default().to_string(),
+        None => String::from_utf8_lossy(body).into_owned(),
+    };
+    TransportError::HttpStatus {
+        status: status.as_u16(),
+        message,
+        retry_after,
+        record,
+    }
+}
+
+fn content_type(headers: &HeaderMap) -> &str {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Parses a `Retry-After` header given in seconds.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
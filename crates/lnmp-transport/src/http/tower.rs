@@ -0,0 +1,250 @@
+//! Tower middleware propagating LNMP envelope metadata over HTTP.
+//!
+//! - [`InjectEnvelopeLayer`] wraps outbound (client) services: it writes the
+//!   `X-LNMP-*` and `traceparent` headers of each request, taking the metadata from
+//!   the request's [`EnvelopeMetadata`] extension (or the headers already set),
+//!   recording the layer's source and stamping a fresh sequence number.
+//! - [`ExtractEnvelopeLayer`] wraps inbound (server) services: it parses those
+//!   headers into an [`EnvelopeMetadata`] request extension for handlers.
+//!
+//! ```rust,ignore
+//! use lnmp_transport::http::tower::{ExtractEnvelopeLayer, InjectEnvelopeLayer};
+//! use tower::ServiceBuilder;
+//!
+//! let client = ServiceBuilder::new()
+//!     .layer(InjectEnvelopeLayer::new().with_source("planner"))
+//!     .service(hyper_client);
+//!
+//! let app = Router::new()
+//!     .route("/ingest", post(ingest))
+//!     .layer(ExtractEnvelopeLayer::new());
+//!
+//! async fn ingest(Extension(meta): Extension<EnvelopeMetadata>) { /* ... */ }
+//! ```
+
+use super::{envelope_to_headers, headers_to_envelope_metadata};
+use crate::TransportError;
+use http::Request;
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Error type of [`InjectEnvelope`]: the inner service's error or a [`TransportError`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Layer writing envelope metadata headers on outbound requests.
+///
+/// Clones share the sequence counter, so every request sent through services built
+/// from one layer gets a distinct, increasing sequence number.
+#[derive(Debug, Clone)]
+pub struct InjectEnvelopeLayer {
+    source: Option<String>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl Default for InjectEnvelopeLayer {
+    fn default() -> Self {
+        Self {
+            source: None,
+            sequence: Arc::new(AtomicU64::new(1)),
+        }
+    }
+}
+
+impl InjectEnvelopeLayer {
+    /// Creates a layer numbering requests from 1, without a source.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the source recorded on requests that do not name one.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sets the sequence number of the next request.
+    pub fn with_next_sequence(self, sequence: u64) -> Self {
+        self.sequence.store(sequence, Ordering::Relaxed);
+        self
+    }
+
+    /// Returns the sequence number the next request will get.
+    pub fn next_sequence(&self) -> u64 {
+        self.sequence.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Layer<S> for InjectEnvelopeLayer {
+    type Service = InjectEnvelope<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InjectEnvelope {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service writing envelope metadata headers on outbound requests.
+///
+/// See [`InjectEnvelopeLayer`].
+#[derive(Debug, Clone)]
+pub struct InjectEnvelope<S> {
+    inner: S,
+    layer: InjectEnvelopeLayer,
+}
+
+impl<S> InjectEnvelope<S> {
+    /// Returns the wrapped service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the middleware, returning the wrapped service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn inject<B>(&self, req: &mut Request<B>) -> crate::Result<()> {
+        let mut meta = match req.extensions_mut().remove::<EnvelopeMetadata>() {
+            Some(meta) => meta,
+            None => headers_to_envelope_metadata(req.headers())?,
+        };
+        if meta.source.is_none() {
+            meta.source = self.layer.source.clone();
+        }
+        meta.sequence = Some(self.layer.sequence.fetch_add(1, Ordering::Relaxed));
+
+        let env = LnmpEnvelope {
+            metadata: meta,
+            record: LnmpRecord::new(),
+        };
+        let headers = envelope_to_headers(&env)?;
+        req.headers_mut().extend(headers);
+        req.extensions_mut().insert(env.metadata);
+        Ok(())
+    }
+}
+
+impl<S, B> Service<Request<B>> for InjectEnvelope<S>
+where
+    S: Service<Request<B>>,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let state = match self.inject(&mut req) {
+            Ok(()) => FutureState::Inner(Box::pin(self.inner.call(req))),
+            Err(e) => FutureState::Failed(Some(e)),
+        };
+        ResponseFuture { state }
+    }
+}
+
+/// Response future of [`InjectEnvelope`].
+pub struct ResponseFuture<F> {
+    state: FutureState<F>,
+}
+
+enum FutureState<F> {
+    Inner(Pin<Box<F>>),
+    Failed(Option<TransportError>),
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<BoxError>,
+{
+    type Output = Result<T, BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.get_mut().state {
+            FutureState::Inner(future) => future.as_mut().poll(cx).map_err(Into::into),
+            FutureState::Failed(error) => Poll::Ready(Err(error
+                .take()
+                .expect("ResponseFuture polled after completion")
+                .into())),
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseFuture").finish_non_exhaustive()
+    }
+}
+
+/// Layer parsing envelope metadata headers of inbound requests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractEnvelopeLayer;
+
+impl ExtractEnvelopeLayer {
+    /// Creates the layer.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for ExtractEnvelopeLayer {
+    type Service = ExtractEnvelope<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ExtractEnvelope { inner }
+    }
+}
+
+/// Service parsing envelope metadata headers of inbound requests.
+///
+/// Inserts the [`EnvelopeMetadata`] parsed from the headers as a request extension;
+/// values that do not parse are left unset.
+#[derive(Debug, Clone)]
+pub struct ExtractEnvelope<S> {
+    inner: S,
+}
+
+impl<S> ExtractEnvelope<S> {
+    /// Returns the wrapped service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the middleware, returning the wrapped service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, B> Service<Request<B>> for ExtractEnvelope<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if let Ok(meta) = headers_to_envelope_metadata(req.headers()) {
+            req.extensions_mut().insert(meta);
+        }
+        self.inner.call(req)
+    }
+}
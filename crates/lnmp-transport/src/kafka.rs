@@ -2,26 +2,58 @@
 //!
 //! This module provides helpers to map LNMP Envelope metadata to/from Kafka record headers,
 //! and encode/decode LNMP record values.
+//!
+//! The envelope's `partition_key` doubles as the Kafka record key (see
+//! [`envelope_to_kafka_key`]), so records of one entity land on one partition.
+//!
+//! With the `kafka-rdkafka` feature, [`rdkafka`] adapts these helpers to rdkafka
+//! producers and consumers.
+
+#[cfg(feature = "kafka-rdkafka")]
+pub mod rdkafka;
 
+use crate::serializer::{self, SerializerConfig, CONTENT_TYPE_LNMP_BINARY};
 use crate::{Result, TransportError};
+use lnmp_core::{FieldId, LnmpRecord, LnmpValue};
 use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::MessageKind;
 use std::collections::HashMap;
 
 /// Kafka header name for LNMP timestamp.
 pub const HEADER_TIMESTAMP: &str = "lnmp.timestamp";
 
+/// Kafka header name for LNMP expiry time (Unix epoch milliseconds).
+pub const HEADER_EXPIRES_AT: &str = "lnmp.expires_at";
+
 /// Kafka header name for LNMP source identifier.
 pub const HEADER_SOURCE: &str = "lnmp.source";
 
 /// Kafka header name for LNMP trace ID.
 pub const HEADER_TRACE_ID: &str = "lnmp.trace_id";
 
+/// Kafka header name for LNMP correlation ID.
+pub const HEADER_CORRELATION_ID: &str = "lnmp.correlation_id";
+
+/// Kafka header name for LNMP causation ID.
+pub const HEADER_CAUSATION_ID: &str = "lnmp.causation_id";
+
+/// Kafka header name for LNMP partition key (also sent as the record key).
+pub const HEADER_PARTITION_KEY: &str = "lnmp.partition_key";
+
 /// Kafka header name for LNMP sequence number.
 pub const HEADER_SEQUENCE: &str = "lnmp.sequence";
 
+/// Kafka header name for LNMP FID schema version.
+pub const HEADER_SCHEMA_VERSION: &str = "lnmp.schema_version";
+
 /// Kafka header name prefix for LNMP labels.
 pub const HEADER_LABEL_PREFIX: &str = "lnmp.label.";
 
+/// Kafka header name for the record value's Content-Type (binary when absent).
+///
+/// Also carries the envelope's `content_type`.
+pub const HEADER_CONTENT_TYPE: &str = "lnmp.content_type";
+
 /// Type alias for Kafka headers (key-value pairs as bytes).
 pub type KafkaHeaders = HashMap<String, Vec<u8>>;
 
@@ -43,6 +75,10 @@ pub fn envelope_to_kafka_headers(env: &LnmpEnvelope) -> Result<KafkaHeaders> {
         headers.insert(HEADER_TIMESTAMP.to_string(), ts.to_string().into_bytes());
     }
 
+    if let Some(exp) = meta.expires_at {
+        headers.insert(HEADER_EXPIRES_AT.to_string(), exp.to_string().into_bytes());
+    }
+
     if let Some(src) = &meta.source {
         headers.insert(HEADER_SOURCE.to_string(), src.as_bytes().to_vec());
     }
@@ -51,10 +87,36 @@ pub fn envelope_to_kafka_headers(env: &LnmpEnvelope) -> Result<KafkaHeaders> {
         headers.insert(HEADER_TRACE_ID.to_string(), trace_id.as_bytes().to_vec());
     }
 
+    if let Some(id) = &meta.correlation_id {
+        headers.insert(HEADER_CORRELATION_ID.to_string(), id.as_bytes().to_vec());
+    }
+
+    if let Some(id) = &meta.causation_id {
+        headers.insert(HEADER_CAUSATION_ID.to_string(), id.as_bytes().to_vec());
+    }
+
+    if let Some(key) = &meta.partition_key {
+        headers.insert(HEADER_PARTITION_KEY.to_string(), key.as_bytes().to_vec());
+    }
+
     if let Some(seq) = meta.sequence {
         headers.insert(HEADER_SEQUENCE.to_string(), seq.to_string().into_bytes());
     }
 
+    if let Some(content_type) = &meta.content_type {
+        headers.insert(
+            HEADER_CONTENT_TYPE.to_string(),
+            content_type.as_bytes().to_vec(),
+        );
+    }
+
+    if let Some(version) = &meta.schema_version {
+        headers.insert(
+            HEADER_SCHEMA_VERSION.to_string(),
+            version.as_bytes().to_vec(),
+        );
+    }
+
     for (k, v) in &meta.labels {
         let header_name = format!("{}{}", HEADER_LABEL_PREFIX, k);
         headers.insert(header_name, v.as_bytes().to_vec());
@@ -85,6 +147,15 @@ pub fn kafka_headers_to_envelope_metadata(headers: &KafkaHeaders) -> Result<Enve
         })?);
     }
 
+    if let Some(val) = headers.get(HEADER_EXPIRES_AT) {
+        let s = String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("expires_at".into(), "not utf8".into())
+        })?;
+        meta.expires_at = Some(s.parse().map_err(|_e| {
+            TransportError::InvalidHeaderValue("expires_at".into(), "parse error".into())
+        })?);
+    }
+
     if let Some(val) = headers.get(HEADER_SOURCE) {
         meta.source =
             Some(String::from_utf8(val.clone()).map_err(|_| {
@@ -98,6 +169,24 @@ pub fn kafka_headers_to_envelope_metadata(headers: &KafkaHeaders) -> Result<Enve
         })?);
     }
 
+    if let Some(val) = headers.get(HEADER_CORRELATION_ID) {
+        meta.correlation_id = Some(String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("correlation_id".into(), "not utf8".into())
+        })?);
+    }
+
+    if let Some(val) = headers.get(HEADER_CAUSATION_ID) {
+        meta.causation_id = Some(String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("causation_id".into(), "not utf8".into())
+        })?);
+    }
+
+    if let Some(val) = headers.get(HEADER_PARTITION_KEY) {
+        meta.partition_key = Some(String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("partition_key".into(), "not utf8".into())
+        })?);
+    }
+
     if let Some(val) = headers.get(HEADER_SEQUENCE) {
         let s = String::from_utf8(val.clone()).map_err(|_| {
             TransportError::InvalidHeaderValue("sequence".into(), "not utf8".into())
@@ -107,6 +196,18 @@ pub fn kafka_headers_to_envelope_metadata(headers: &KafkaHeaders) -> Result<Enve
         })?);
     }
 
+    if let Some(val) = headers.get(HEADER_CONTENT_TYPE) {
+        meta.content_type = Some(String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("content_type".into(), "not utf8".into())
+        })?);
+    }
+
+    if let Some(val) = headers.get(HEADER_SCHEMA_VERSION) {
+        meta.schema_version = Some(String::from_utf8(val.clone()).map_err(|_| {
+            TransportError::InvalidHeaderValue("schema_version".into(), "not utf8".into())
+        })?);
+    }
+
     for (name, value) in headers {
         if name.starts_with(HEADER_LABEL_PREFIX) {
             let key = name.trim_start_matches(HEADER_LABEL_PREFIX).to_string();
@@ -120,9 +221,51 @@ pub fn kafka_headers_to_envelope_metadata(headers: &KafkaHeaders) -> Result<Enve
     Ok(meta)
 }
 
+/// Returns the Kafka record key for an envelope: its partition key, if any.
+///
+/// Producers should pass this as the record key so the default partitioner
+/// keeps every record of one entity on the same partition.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::kafka;
+/// let key = kafka::envelope_to_kafka_key(&envelope);
+/// producer.send(FutureRecord::to("events").payload(&value).key(&key.unwrap_or_default()));
+/// ```
+pub fn envelope_to_kafka_key(env: &LnmpEnvelope) -> Option<Vec<u8>> {
+    env.metadata
+        .partition_key
+        .as_ref()
+        .map(|key| key.as_bytes().to_vec())
+}
+
+/// Derives a partition key from the value of `fid` (e.g. F12 `user_id`).
+///
+/// Integers, floats, booleans (`1`/`0`) and strings are rendered as text;
+/// the field being absent or holding an array, nested record or embedding
+/// yields `None`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::kafka;
+/// envelope.metadata.partition_key = kafka::partition_key_from_fid(&envelope.record, 12);
+/// ```
+pub fn partition_key_from_fid(record: &LnmpRecord, fid: FieldId) -> Option<String> {
+    match &record.get_field(fid)?.value {
+        LnmpValue::Int(i) => Some(i.to_string()),
+        LnmpValue::Float(f) => Some(f.to_string()),
+        LnmpValue::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+        LnmpValue::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
 /// Encodes an LNMP Envelope to a complete Kafka record (value + headers).
 ///
-/// Returns the encoded record value and headers as a tuple.
+/// Returns the encoded record value and headers as a tuple. The value is always
+/// binary, which [`HEADER_CONTENT_TYPE`] announces.
 ///
 /// # Example
 ///
@@ -133,15 +276,46 @@ pub fn kafka_headers_to_envelope_metadata(headers: &KafkaHeaders) -> Result<Enve
 pub fn envelope_to_kafka_record(env: &LnmpEnvelope) -> Result<(Vec<u8>, KafkaHeaders)> {
     use lnmp_codec::binary::BinaryEncoder;
 
-    let headers = envelope_to_kafka_headers(env)?;
+    let mut headers = envelope_to_kafka_headers(env)?;
     let encoder = BinaryEncoder::new();
     let value = encoder.encode(&env.record)?;
+    headers.insert(
+        HEADER_CONTENT_TYPE.to_string(),
+        CONTENT_TYPE_LNMP_BINARY.as_bytes().to_vec(),
+    );
+
+    Ok((value, headers))
+}
+
+/// Encodes an LNMP Envelope to a Kafka record in the format configured for `kind`.
+///
+/// The chosen format is announced in the [`HEADER_CONTENT_TYPE`] header.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::kafka;
+/// let (value, headers) = kafka::envelope_to_kafka_record_for_kind(&envelope, kind, &config)?;
+/// ```
+pub fn envelope_to_kafka_record_for_kind(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<(Vec<u8>, KafkaHeaders)> {
+    let mut headers = envelope_to_kafka_headers(env)?;
+    let (value, content_type) = config.encode(kind, &env.record)?;
+    headers.insert(
+        HEADER_CONTENT_TYPE.to_string(),
+        content_type.as_bytes().to_vec(),
+    );
 
     Ok((value, headers))
 }
 
 /// Decodes an LNMP Envelope from a Kafka record (value + headers).
 ///
+/// The value is decoded according to [`HEADER_CONTENT_TYPE`], defaulting to binary.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -149,11 +323,14 @@ pub fn envelope_to_kafka_record(env: &LnmpEnvelope) -> Result<(Vec<u8>, KafkaHea
 /// let envelope = kafka::kafka_record_to_envelope(&value, &headers)?;
 /// ```
 pub fn kafka_record_to_envelope(value: &[u8], headers: &KafkaHeaders) -> Result<LnmpEnvelope> {
-    use lnmp_codec::binary::BinaryDecoder;
-
     let metadata = kafka_headers_to_envelope_metadata(headers)?;
-    let decoder = BinaryDecoder::new();
-    let record = decoder.decode(value)?;
+    let content_type = match headers.get(HEADER_CONTENT_TYPE) {
+        Some(val) => std::str::from_utf8(val).map_err(|_| {
+            TransportError::InvalidHeaderValue("content_type".into(), "not utf8".into())
+        })?,
+        None => CONTENT_TYPE_LNMP_BINARY,
+    };
+    let record = serializer::decode_body(value, content_type)?;
 
     Ok(LnmpEnvelope { metadata, record })
 }
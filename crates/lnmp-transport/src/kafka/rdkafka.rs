@@ -0,0 +1,170 @@
+//! [`rdkafka`](::rdkafka) producer and consumer adapters for LNMP.
+//!
+//! [`LnmpSerializer`] turns envelopes into [`LnmpKafkaRecord`]s (value, key and
+//! `lnmp.*` headers) that borrow straight into rdkafka's `FutureRecord` and
+//! `BaseRecord`; [`LnmpDeserializer`] turns any rdkafka [`Message`] back into an
+//! envelope.
+//!
+//! ```rust,ignore
+//! use lnmp_transport::kafka::rdkafka::{LnmpDeserializer, LnmpSerializer};
+//!
+//! let serializer = LnmpSerializer::new().with_config(config);
+//! let record = serializer.serialize(&envelope, MessageKind::Event)?;
+//! producer.send(record.future_record("events"), Duration::from_secs(5)).await?;
+//!
+//! let message = consumer.recv().await?;
+//! let envelope = LnmpDeserializer::new().deserialize(&message)?;
+//! ```
+
+use super::{envelope_to_kafka_record_for_kind, kafka_record_to_envelope, KafkaHeaders};
+use crate::serializer::{SerializerConfig, WireFormat};
+use crate::{Result, TransportError};
+use ::rdkafka::message::{Header, Headers, Message, OwnedHeaders};
+use ::rdkafka::producer::{BaseRecord, FutureRecord};
+use lnmp_envelope::LnmpEnvelope;
+use lnmp_net::MessageKind;
+
+/// An encoded Kafka record, ready to be handed to an rdkafka producer.
+#[derive(Debug, Clone)]
+pub struct LnmpKafkaRecord {
+    /// Encoded record value.
+    pub payload: Vec<u8>,
+    /// Record key, from the envelope's `partition_key`.
+    pub key: Option<Vec<u8>>,
+    /// `lnmp.*` headers, including `lnmp.content_type`.
+    pub headers: OwnedHeaders,
+}
+
+impl LnmpKafkaRecord {
+    /// Borrows the record as a [`FutureRecord`] for `topic`.
+    pub fn future_record<'a>(&'a self, topic: &'a str) -> FutureRecord<'a, [u8], [u8]> {
+        let record = FutureRecord::to(topic)
+            .payload(self.payload.as_slice())
+            .headers(self.headers.clone());
+        match &self.key {
+            Some(key) => record.key(key.as_slice()),
+            None => record,
+        }
+    }
+
+    /// Borrows the record as a [`BaseRecord`] for `topic`.
+    pub fn base_record<'a>(&'a self, topic: &'a str) -> BaseRecord<'a, [u8], [u8]> {
+        let record = BaseRecord::to(topic)
+            .payload(self.payload.as_slice())
+            .headers(self.headers.clone());
+        match &self.key {
+            Some(key) => record.key(key.as_slice()),
+            None => record,
+        }
+    }
+}
+
+/// Encodes envelopes for rdkafka producers.
+///
+/// The value format is picked per [`MessageKind`] by the [`SerializerConfig`]
+/// (binary unless configured) and announced in `lnmp.content_type`.
+#[derive(Debug, Clone, Default)]
+pub struct LnmpSerializer {
+    config: SerializerConfig,
+}
+
+impl LnmpSerializer {
+    /// Creates a serializer encoding every kind as binary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the per-kind format configuration.
+    pub fn with_config(mut self, config: SerializerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Encodes an envelope of the given kind.
+    pub fn serialize(&self, env: &LnmpEnvelope, kind: MessageKind) -> Result<LnmpKafkaRecord> {
+        let (payload, headers) = envelope_to_kafka_record_for_kind(env, kind, &self.config)?;
+
+        // Sorted, so the header order is deterministic
+        let mut entries: Vec<_> = headers.into_iter().collect();
+        entries.sort();
+        let headers = entries.iter().fold(
+            OwnedHeaders::new_with_capacity(entries.len()),
+            |h, (k, v)| {
+                h.insert(Header {
+                    key: k,
+                    value: Some(v),
+                })
+            },
+        );
+
+        Ok(LnmpKafkaRecord {
+            payload,
+            key: super::envelope_to_kafka_key(env),
+            headers,
+        })
+    }
+}
+
+/// Decodes envelopes from rdkafka messages.
+///
+/// The value is decoded according to `lnmp.content_type` (binary when absent). The
+/// record key fills in a missing `partition_key` and the Kafka timestamp a missing
+/// `timestamp`.
+#[derive(Debug, Clone, Default)]
+pub struct LnmpDeserializer {
+    accepted: Option<Vec<WireFormat>>,
+}
+
+impl LnmpDeserializer {
+    /// Creates a deserializer accepting every LNMP format.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the formats accepted; messages in other formats are rejected.
+    pub fn with_accepted_formats(mut self, formats: &[WireFormat]) -> Self {
+        self.accepted = Some(formats.to_vec());
+        self
+    }
+
+    /// Decodes the envelope of a message.
+    pub fn deserialize<M: Message>(&self, message: &M) -> Result<LnmpEnvelope> {
+        let mut headers = KafkaHeaders::new();
+        if let Some(h) = message.headers() {
+            for header in h.iter() {
+                if let Some(value) = header.value {
+                    headers.insert(header.key.to_string(), value.to_vec());
+                }
+            }
+        }
+
+        if let Some(accepted) = &self.accepted {
+            let content_type = headers
+                .get(super::HEADER_CONTENT_TYPE)
+                .map(|v| String::from_utf8_lossy(v).into_owned())
+                .unwrap_or_else(|| WireFormat::Binary.content_type().to_string());
+            let format = WireFormat::from_content_type(&content_type);
+            if !format.is_some_and(|f| accepted.contains(&f)) {
+                return Err(TransportError::InvalidHeaderValue(
+                    "content_type".into(),
+                    format!("not accepted: {}", content_type),
+                ));
+            }
+        }
+
+        let mut env = kafka_record_to_envelope(message.payload().unwrap_or_default(), &headers)?;
+        if env.metadata.partition_key.is_none() {
+            env.metadata.partition_key = message
+                .key()
+                .and_then(|key| std::str::from_utf8(key).ok())
+                .map(str::to_string);
+        }
+        if env.metadata.timestamp.is_none() {
+            env.metadata.timestamp = message
+                .timestamp()
+                .to_millis()
+                .and_then(|ms| u64::try_from(ms).ok());
+        }
+        Ok(env)
+    }
+}
@@ -6,6 +6,9 @@
 //! This crate does NOT implement HTTP/Kafka/gRPC clients or servers - it only provides
 //! helpers to map LNMP data to/from transport-specific headers and bodies.
 
+#[cfg(feature = "amqp")]
+pub mod amqp;
+pub mod content;
 #[cfg(feature = "grpc")]
 pub mod grpc;
 #[cfg(feature = "http")]
@@ -14,6 +17,19 @@ pub mod http;
 pub mod kafka;
 #[cfg(feature = "nats")]
 pub mod nats;
+#[cfg(feature = "negotiation")]
+pub mod negotiation;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod serializer;
+#[cfg(feature = "sse")]
+pub mod sse;
+#[cfg(feature = "udp")]
+pub mod udp;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+pub use serializer::{SerializerConfig, WireFormat};
 
 use thiserror::Error;
 
@@ -29,6 +45,36 @@ pub enum TransportError {
     BinaryError(#[from] lnmp_codec::binary::BinaryError),
     #[error("Envelope error: {0}")]
     EnvelopeError(String),
+    #[error("ShortForm error: {0}")]
+    ShortFormError(#[from] lnmp_llb::LlbError),
+    #[error("Negotiation error: {0}")]
+    Negotiation(#[from] lnmp_codec::binary::NegotiationError),
+    #[error("Negotiation rejected by peer ({code:?}): {message}")]
+    NegotiationRejected {
+        code: lnmp_codec::binary::ErrorCode,
+        message: String,
+    },
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("HTTP error: {0}")]
+    Http(String),
+    #[error("HTTP status {status}: {message}")]
+    HttpStatus {
+        status: u16,
+        message: String,
+        retry_after: Option<std::time::Duration>,
+        record: Option<lnmp_core::LnmpRecord>,
+    },
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+    #[error("Content digest does not match the record")]
+    ContentDigestMismatch,
+    #[error("Datagram of {size} bytes exceeds the {max}-byte limit")]
+    DatagramTooLarge { size: usize, max: usize },
+    #[error("Invalid datagram: {0}")]
+    InvalidDatagram(String),
 }
 
 pub type Result<T> = std::result::Result<T, TransportError>;
@@ -4,10 +4,26 @@
 //! and encode/decode LNMP record payloads.
 //!
 //! NATS headers are similar to Kafka headers - key-value pairs attached to messages.
+//!
+//! For JetStream, subjects follow `lnmp.<kind>.<source>` (see [`subject`] and
+//! [`subject_filter`] for stream and consumer filters), `Nats-Msg-Id` is derived from
+//! the canonical record hash so the server drops duplicates within its dedup window,
+//! and [`AckKind::for_decision`] turns a routing decision into the ack to send.
 
+use crate::serializer::{self, SerializerConfig, CONTENT_TYPE_LNMP_BINARY};
 use crate::Result;
+use lnmp_codec::binary::BinaryEncoder;
+use lnmp_core::LnmpRecord;
 use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::{MessageKind, RoutingDecision};
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// First token of LNMP subjects.
+pub const SUBJECT_PREFIX: &str = "lnmp";
+
+/// JetStream header carrying the message ID used for deduplication.
+pub const HEADER_MSG_ID: &str = "Nats-Msg-Id";
 
 /// NATS header name for LNMP timestamp.
 pub const HEADER_TIMESTAMP: &str = "lnmp-timestamp";
@@ -15,15 +31,32 @@ pub const HEADER_TIMESTAMP: &str = "lnmp-timestamp";
 /// NATS header name for LNMP source identifier.
 pub const HEADER_SOURCE: &str = "lnmp-source";
 
+/// NATS header name for LNMP expiry time (Unix epoch milliseconds).
+pub const HEADER_EXPIRES_AT: &str = "lnmp-expires-at";
+
 /// NATS header name for LNMP trace ID.
 pub const HEADER_TRACE_ID: &str = "lnmp-trace-id";
 
+/// NATS header name for LNMP correlation ID.
+pub const HEADER_CORRELATION_ID: &str = "lnmp-correlation-id";
+
+/// NATS header name for LNMP causation ID.
+pub const HEADER_CAUSATION_ID: &str = "lnmp-causation-id";
+
 /// NATS header name for LNMP sequence number.
 pub const HEADER_SEQUENCE: &str = "lnmp-sequence";
 
+/// NATS header name for LNMP FID schema version.
+pub const HEADER_SCHEMA_VERSION: &str = "lnmp-schema-version";
+
 /// NATS header name prefix for LNMP labels.
 pub const HEADER_LABEL_PREFIX: &str = "lnmp-label-";
 
+/// NATS header name for the payload's Content-Type (binary when absent).
+///
+/// Also carries the envelope's `content_type`.
+pub const HEADER_CONTENT_TYPE: &str = "lnmp-content-type";
+
 /// Converts an LNMP Envelope's metadata to NATS headers.
 ///
 /// This function maps envelope metadata fields to standard LNMP NATS headers.
@@ -42,6 +75,10 @@ pub fn envelope_to_nats_headers(env: &LnmpEnvelope) -> Result<HashMap<String, St
         headers.insert(HEADER_TIMESTAMP.to_string(), ts.to_string());
     }
 
+    if let Some(exp) = meta.expires_at {
+        headers.insert(HEADER_EXPIRES_AT.to_string(), exp.to_string());
+    }
+
     if let Some(src) = &meta.source {
         headers.insert(HEADER_SOURCE.to_string(), src.clone());
     }
@@ -50,10 +87,26 @@ pub fn envelope_to_nats_headers(env: &LnmpEnvelope) -> Result<HashMap<String, St
         headers.insert(HEADER_TRACE_ID.to_string(), trace_id.clone());
     }
 
+    if let Some(id) = &meta.correlation_id {
+        headers.insert(HEADER_CORRELATION_ID.to_string(), id.clone());
+    }
+
+    if let Some(id) = &meta.causation_id {
+        headers.insert(HEADER_CAUSATION_ID.to_string(), id.clone());
+    }
+
     if let Some(seq) = meta.sequence {
         headers.insert(HEADER_SEQUENCE.to_string(), seq.to_string());
     }
 
+    if let Some(content_type) = &meta.content_type {
+        headers.insert(HEADER_CONTENT_TYPE.to_string(), content_type.clone());
+    }
+
+    if let Some(version) = &meta.schema_version {
+        headers.insert(HEADER_SCHEMA_VERSION.to_string(), version.clone());
+    }
+
     for (k, v) in &meta.labels {
         let header_name = format!("{}{}", HEADER_LABEL_PREFIX, k);
         headers.insert(header_name, v.clone());
@@ -81,6 +134,10 @@ pub fn nats_headers_to_envelope_metadata(
         meta.timestamp = val.parse().ok();
     }
 
+    if let Some(val) = headers.get(HEADER_EXPIRES_AT) {
+        meta.expires_at = val.parse().ok();
+    }
+
     if let Some(val) = headers.get(HEADER_SOURCE) {
         meta.source = Some(val.clone());
     }
@@ -89,10 +146,26 @@ pub fn nats_headers_to_envelope_metadata(
         meta.trace_id = Some(val.clone());
     }
 
+    if let Some(val) = headers.get(HEADER_CORRELATION_ID) {
+        meta.correlation_id = Some(val.clone());
+    }
+
+    if let Some(val) = headers.get(HEADER_CAUSATION_ID) {
+        meta.causation_id = Some(val.clone());
+    }
+
     if let Some(val) = headers.get(HEADER_SEQUENCE) {
         meta.sequence = val.parse().ok();
     }
 
+    if let Some(val) = headers.get(HEADER_CONTENT_TYPE) {
+        meta.content_type = Some(val.clone());
+    }
+
+    if let Some(val) = headers.get(HEADER_SCHEMA_VERSION) {
+        meta.schema_version = Some(val.clone());
+    }
+
     for (name, value) in headers {
         if name.starts_with(HEADER_LABEL_PREFIX) {
             let key = name.trim_start_matches(HEADER_LABEL_PREFIX).to_string();
@@ -105,7 +178,8 @@ pub fn nats_headers_to_envelope_metadata(
 
 /// Encodes an LNMP Envelope to a complete NATS message (payload + headers).
 ///
-/// Returns the encoded message payload and headers as a tuple.
+/// Returns the encoded message payload and headers as a tuple. The payload is
+/// always binary, which [`HEADER_CONTENT_TYPE`] announces.
 ///
 /// # Example
 ///
@@ -116,15 +190,43 @@ pub fn nats_headers_to_envelope_metadata(
 pub fn envelope_to_nats_message(env: &LnmpEnvelope) -> Result<(Vec<u8>, HashMap<String, String>)> {
     use lnmp_codec::binary::BinaryEncoder;
 
-    let headers = envelope_to_nats_headers(env)?;
+    let mut headers = envelope_to_nats_headers(env)?;
     let encoder = BinaryEncoder::new();
     let payload = encoder.encode(&env.record)?;
+    headers.insert(
+        HEADER_CONTENT_TYPE.to_string(),
+        CONTENT_TYPE_LNMP_BINARY.to_string(),
+    );
+
+    Ok((payload, headers))
+}
+
+/// Encodes an LNMP Envelope to a NATS message in the format configured for `kind`.
+///
+/// The chosen format is announced in the [`HEADER_CONTENT_TYPE`] header.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::nats;
+/// let (payload, headers) = nats::envelope_to_nats_message_for_kind(&envelope, kind, &config)?;
+/// ```
+pub fn envelope_to_nats_message_for_kind(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<(Vec<u8>, HashMap<String, String>)> {
+    let mut headers = envelope_to_nats_headers(env)?;
+    let (payload, content_type) = config.encode(kind, &env.record)?;
+    headers.insert(HEADER_CONTENT_TYPE.to_string(), content_type.to_string());
 
     Ok((payload, headers))
 }
 
 /// Decodes an LNMP Envelope from a NATS message (payload + headers).
 ///
+/// The payload is decoded according to [`HEADER_CONTENT_TYPE`], defaulting to binary.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -135,11 +237,150 @@ pub fn nats_message_to_envelope(
     payload: &[u8],
     headers: &HashMap<String, String>,
 ) -> Result<LnmpEnvelope> {
-    use lnmp_codec::binary::BinaryDecoder;
-
     let metadata = nats_headers_to_envelope_metadata(headers)?;
-    let decoder = BinaryDecoder::new();
-    let record = decoder.decode(payload)?;
+    let content_type = headers
+        .get(HEADER_CONTENT_TYPE)
+        .map_or(CONTENT_TYPE_LNMP_BINARY, String::as_str);
+    let record = serializer::decode_body(payload, content_type)?;
 
     Ok(LnmpEnvelope { metadata, record })
 }
+
+/// Builds the subject of a message: `lnmp.<kind>.<source>`.
+///
+/// The kind is lowercased. Characters of the source that NATS treats specially (`.`,
+/// `*`, `>` and whitespace) are replaced with `_`; a missing source becomes `unknown`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::nats;
+/// let subject = nats::subject(MessageKind::Event, Some("sensor-7"));
+/// // "lnmp.event.sensor-7"
+/// ```
+pub fn subject(kind: MessageKind, source: Option<&str>) -> String {
+    let source = source.filter(|s| !s.is_empty()).unwrap_or("unknown");
+    format!(
+        "{}.{}.{}",
+        SUBJECT_PREFIX,
+        kind.to_string().to_lowercase(),
+        subject_token(source)
+    )
+}
+
+/// Builds a subject filter for JetStream streams and consumers.
+///
+/// Missing parts are wildcards: `(None, None)` gives `lnmp.>`, a kind alone gives
+/// `lnmp.<kind>.*` and a source alone `lnmp.*.<source>`.
+pub fn subject_filter(kind: Option<MessageKind>, source: Option<&str>) -> String {
+    match (kind, source) {
+        (None, None) => format!("{}.>", SUBJECT_PREFIX),
+        (kind, source) => format!(
+            "{}.{}.{}",
+            SUBJECT_PREFIX,
+            kind.map_or("*".to_string(), |k| k.to_string().to_lowercase()),
+            source.map_or("*".to_string(), subject_token)
+        ),
+    }
+}
+
+fn subject_token(token: &str) -> String {
+    token
+        .chars()
+        .map(|c| {
+            if matches!(c, '.' | '*' | '>') || c.is_whitespace() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Derives the JetStream message ID of a record from its canonical hash.
+///
+/// Records with the same fields get the same ID whatever their field order, so
+/// republishing one within the stream's duplicate window is dropped by the server.
+pub fn message_id(record: &LnmpRecord) -> Result<String> {
+    let hash = BinaryEncoder::new().canonical_hash(record)?;
+    Ok(hash.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// A message ready to be published to JetStream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JetStreamMessage {
+    /// Subject from [`subject`].
+    pub subject: String,
+    /// Encoded record.
+    pub payload: Vec<u8>,
+    /// LNMP headers plus [`HEADER_MSG_ID`].
+    pub headers: HashMap<String, String>,
+}
+
+/// Encodes an LNMP Envelope for JetStream in the format configured for `kind`.
+///
+/// The subject is derived from `kind` and the envelope's source, and the message ID
+/// from the record (see [`message_id`]).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::nats;
+/// let msg = nats::envelope_to_jetstream_message(&envelope, kind, &config)?;
+/// jetstream.publish_with_headers(msg.subject, headers_from(msg.headers), msg.payload.into()).await?;
+/// ```
+pub fn envelope_to_jetstream_message(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<JetStreamMessage> {
+    let (payload, mut headers) = envelope_to_nats_message_for_kind(env, kind, config)?;
+    headers.insert(HEADER_MSG_ID.to_string(), message_id(&env.record)?);
+    Ok(JetStreamMessage {
+        subject: subject(kind, env.metadata.source.as_deref()),
+        payload,
+        headers,
+    })
+}
+
+/// Acknowledgement sent to the reply subject of a JetStream message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckKind {
+    /// Processed; do not redeliver.
+    Ack,
+    /// Not processed; redeliver, after `delay` if given.
+    Nak {
+        /// Delay before redelivery.
+        delay: Option<Duration>,
+    },
+    /// Still working; reset the ack timer.
+    InProgress,
+    /// Never redeliver, without processing.
+    Term,
+}
+
+impl AckKind {
+    /// Returns the ack for a routing decision.
+    ///
+    /// Messages sent to the LLM or processed locally are acked; dropped messages
+    /// (expired, low priority) are terminated so they are not redelivered.
+    pub fn for_decision(decision: RoutingDecision) -> Self {
+        match decision {
+            RoutingDecision::SendToLLM | RoutingDecision::ProcessLocally => AckKind::Ack,
+            RoutingDecision::Drop => AckKind::Term,
+        }
+    }
+
+    /// Returns the payload to publish to the message's reply subject.
+    pub fn payload(&self) -> Vec<u8> {
+        match self {
+            AckKind::Ack => b"+ACK".to_vec(),
+            AckKind::Nak { delay: None } => b"-NAK".to_vec(),
+            AckKind::Nak { delay: Some(delay) } => {
+                format!("-NAK {{\"delay\": {}}}", delay.as_nanos()).into_bytes()
+            }
+            AckKind::InProgress => b"+WPI".to_vec(),
+            AckKind::Term => b"+TERM".to_vec(),
+        }
+    }
+}
@@ -0,0 +1,568 @@
+//! Async schema negotiation over byte streams and HTTP.
+//!
+//! Runs the [`SchemaNegotiator`] message exchange over a `tokio`
+//! [`AsyncRead`]/[`AsyncWrite`] pair, or as HTTP request/response pairs, so two agents
+//! agree on [`FeatureFlags`](lnmp_codec::binary::FeatureFlags) before streaming records.
+//!
+//! ```text
+//! client                           server
+//!   Capabilities         ──────▶
+//!                        ◀──────   CapabilitiesAck | Error
+//!   SelectSchema         ──────▶
+//!                        ◀──────   Ready
+//!   Ready (confirmation) ──────▶
+//! ```
+//!
+//! On a stream every message is framed as a `u32` BE length followed by
+//! [`NegotiationMessage::encode`] bytes. Over HTTP each client message is POSTed with
+//! [`CONTENT_TYPE_LNMP_NEGOTIATION`] and the reply is the response body.
+//!
+//! If the server rejects the client's protocol version or a feature, the client
+//! downgrades to the next capabilities registered with [`HandshakeConfig::with_fallback`]
+//! and starts over on the same connection. Every message is subject to
+//! [`HandshakeConfig::timeout`]; timeouts and I/O failures are retried on a fresh
+//! connection by [`ClientHandshake::run_with_retry`] and [`ClientHandshake::run_http`].
+
+use crate::{Result, TransportError};
+use lnmp_codec::binary::{
+    BinaryError, Capabilities, ErrorCode, NegotiationMessage, NegotiationResponse,
+    NegotiationSession, SchemaNegotiator,
+};
+use std::future::Future;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Content type of negotiation messages sent over HTTP.
+pub const CONTENT_TYPE_LNMP_NEGOTIATION: &str = "application/lnmp-negotiation";
+
+/// Largest negotiation message accepted from a peer, in bytes.
+pub const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+/// Handshakes a server accepts on one connection before giving up on downgrades.
+const MAX_SERVER_ATTEMPTS: usize = 8;
+
+/// Timeouts, retries and downgrade options for a handshake.
+#[derive(Debug, Clone)]
+pub struct HandshakeConfig {
+    /// Maximum time to wait for each message to be sent or received
+    pub timeout: Duration,
+    /// Extra attempts after a timeout or I/O failure (client only)
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry
+    pub retry_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_retry_backoff: Duration,
+    /// Capabilities to fall back to, in order, when the server rejects the current ones
+    pub fallbacks: Vec<Capabilities>,
+}
+
+impl Default for HandshakeConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            max_retry_backoff: Duration::from_secs(30),
+            fallbacks: Vec::new(),
+        }
+    }
+}
+
+impl HandshakeConfig {
+    /// Creates the default configuration (5s timeout, 2 retries, no fallbacks).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the per-message timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many times a failed handshake is retried on a new connection.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Sets the upper bound on the delay between retries.
+    pub fn with_max_retry_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_retry_backoff = max_backoff;
+        self
+    }
+
+    /// Adds capabilities to offer if the server rejects the previous ones.
+    pub fn with_fallback(mut self, capabilities: Capabilities) -> Self {
+        self.fallbacks.push(capabilities);
+        self
+    }
+
+    /// Delay before retry number `retry` (0-based), capped at `max_retry_backoff`.
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        2u32.checked_pow(retry)
+            .and_then(|factor| self.retry_backoff.checked_mul(factor))
+            .map_or(self.max_retry_backoff, |delay| {
+                delay.min(self.max_retry_backoff)
+            })
+    }
+}
+
+/// Writes one length-prefixed negotiation message to `writer`.
+pub async fn write_message<W>(writer: &mut W, message: &NegotiationMessage) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let bytes = message.encode();
+    writer
+        .write_all(&(bytes.len() as u32).to_be_bytes())
+        .await?;
+    writer.write_all(&bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads one length-prefixed negotiation message from `reader`.
+pub async fn read_message<R>(reader: &mut R) -> Result<NegotiationMessage>
+where
+    R: AsyncRead + Unpin,
+{
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(BinaryError::RecordSizeExceeded {
+            size: len,
+            max: MAX_MESSAGE_LEN,
+        }
+        .into());
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes).await?;
+    Ok(NegotiationMessage::decode(&bytes)?)
+}
+
+/// Drives the initiating side of a negotiation.
+#[derive(Debug, Clone)]
+pub struct ClientHandshake {
+    negotiator: SchemaNegotiator,
+    config: HandshakeConfig,
+}
+
+impl ClientHandshake {
+    /// Creates a client handshake that starts from `negotiator`'s capabilities and FID mappings.
+    pub fn new(negotiator: SchemaNegotiator) -> Self {
+        Self {
+            negotiator,
+            config: HandshakeConfig::default(),
+        }
+    }
+
+    /// Sets timeouts, retries and fallbacks.
+    pub fn with_config(mut self, config: HandshakeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Negotiates over an established stream.
+    pub async fn run<S>(&self, stream: &mut S) -> Result<NegotiationSession>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        self.negotiate(&mut StreamExchange { stream }).await
+    }
+
+    /// Negotiates over a stream opened by `connect`, reconnecting after timeouts and
+    /// I/O failures up to [`HandshakeConfig::max_retries`] times.
+    pub async fn run_with_retry<S, F, Fut>(&self, mut connect: F) -> Result<NegotiationSession>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::io::Result<S>>,
+    {
+        let mut retries = 0;
+        loop {
+            let result = match self.timed(connect()).await {
+                Ok(Ok(mut stream)) => self.run(&mut stream).await,
+                Ok(Err(err)) => Err(err.into()),
+                Err(err) => Err(err),
+            };
+            match result {
+                Err(err) if self.backoff(&err, &mut retries).await => {}
+                result => return result,
+            }
+        }
+    }
+
+    /// Negotiates by POSTing each message to `uri` with `send`, retrying the whole
+    /// handshake after timeouts and I/O failures.
+    ///
+    /// `send` performs one HTTP exchange with the caller's client of choice.
+    #[cfg(feature = "http")]
+    pub async fn run_http<F, Fut>(&self, uri: http::Uri, mut send: F) -> Result<NegotiationSession>
+    where
+        F: FnMut(http::Request<Vec<u8>>) -> Fut,
+        Fut: Future<Output = std::io::Result<http::Response<Vec<u8>>>>,
+    {
+        let mut retries = 0;
+        loop {
+            let mut exchange = HttpExchange {
+                uri: uri.clone(),
+                send: &mut send,
+                reply: None,
+            };
+            match self.negotiate(&mut exchange).await {
+                Err(err) if self.backoff(&err, &mut retries).await => {}
+                result => return result,
+            }
+        }
+    }
+
+    /// Waits before retrying after `err`; returns `false` if it should not be retried.
+    async fn backoff(&self, err: &TransportError, retries: &mut u32) -> bool {
+        if !is_transient(err) || *retries >= self.config.max_retries {
+            return false;
+        }
+        tokio::time::sleep(self.config.retry_delay(*retries)).await;
+        *retries += 1;
+        true
+    }
+
+    async fn negotiate<X: Exchange>(&self, exchange: &mut X) -> Result<NegotiationSession> {
+        let candidates = std::iter::once(self.negotiator.local_capabilities().clone())
+            .chain(self.config.fallbacks.iter().cloned());
+        let mut rejection = None;
+
+        'attempts: for capabilities in candidates {
+            let mut negotiator = self
+                .negotiator
+                .clone()
+                .with_local_capabilities(capabilities);
+            self.timed(exchange.send(&negotiator.initiate()?)).await??;
+
+            loop {
+                let message = self.timed(exchange.receive()).await??;
+                if let NegotiationMessage::Error { code, message } = message {
+                    let err = TransportError::NegotiationRejected { code, message };
+                    if is_downgradable(code) {
+                        rejection = Some(err);
+                        continue 'attempts;
+                    }
+                    return Err(err);
+                }
+
+                match negotiator.handle_message(message) {
+                    Ok(NegotiationResponse::SendMessage(reply)) => {
+                        self.timed(exchange.send(&reply)).await??
+                    }
+                    Ok(NegotiationResponse::Complete(session)) => {
+                        let confirmation = NegotiationMessage::Ready {
+                            session_id: session.session_id,
+                        };
+                        self.timed(exchange.send(&confirmation)).await??;
+                        return Ok(session);
+                    }
+                    Ok(NegotiationResponse::Failed(message)) => {
+                        return Err(TransportError::NegotiationRejected {
+                            code: ErrorCode::Generic,
+                            message,
+                        })
+                    }
+                    Ok(NegotiationResponse::None) => {}
+                    Err(err) => {
+                        let err = TransportError::from(err);
+                        let _ = self.timed(exchange.send(&error_message(&err))).await;
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Err(rejection.expect("at least one handshake attempt"))
+    }
+
+    async fn timed<T>(&self, future: impl Future<Output = T>) -> Result<T> {
+        timed(self.config.timeout, future).await
+    }
+}
+
+/// Drives the responding side of a negotiation over a stream.
+#[derive(Debug, Clone)]
+pub struct ServerHandshake {
+    negotiator: SchemaNegotiator,
+    config: HandshakeConfig,
+}
+
+impl ServerHandshake {
+    /// Creates a server handshake answering with `negotiator`'s capabilities and FID mappings.
+    pub fn new(negotiator: SchemaNegotiator) -> Self {
+        Self {
+            negotiator,
+            config: HandshakeConfig::default(),
+        }
+    }
+
+    /// Sets the per-message timeout.
+    pub fn with_config(mut self, config: HandshakeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Answers one client's handshake on `stream`.
+    ///
+    /// Version and feature rejections are reported to the client, which may start over
+    /// with older capabilities on the same stream.
+    pub async fn run<S>(&self, stream: &mut S) -> Result<NegotiationSession>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut exchange = StreamExchange { stream };
+        let mut negotiator = self.negotiator.clone();
+        let mut attempts = 0;
+        let mut rejection: Option<TransportError> = None;
+
+        loop {
+            let message = match timed(self.config.timeout, exchange.receive()).await? {
+                Ok(message) => message,
+                // The client gave up after our rejection: report why.
+                Err(TransportError::Io(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof && rejection.is_some() =>
+                {
+                    return Err(rejection.take().expect("checked above"))
+                }
+                Err(err) => return Err(err),
+            };
+
+            if matches!(message, NegotiationMessage::Capabilities { .. }) {
+                attempts += 1;
+                if attempts > MAX_SERVER_ATTEMPTS {
+                    return Err(rejection.unwrap_or(TransportError::NegotiationRejected {
+                        code: ErrorCode::Generic,
+                        message: "too many handshake attempts".to_string(),
+                    }));
+                }
+                negotiator = self.negotiator.clone();
+            }
+
+            match negotiator.handle_message(message) {
+                Ok(NegotiationResponse::SendMessage(reply)) => {
+                    timed(self.config.timeout, exchange.send(&reply)).await??
+                }
+                Ok(NegotiationResponse::Complete(session)) => return Ok(session),
+                Ok(NegotiationResponse::Failed(message)) => {
+                    return Err(TransportError::NegotiationRejected {
+                        code: ErrorCode::Generic,
+                        message,
+                    })
+                }
+                Ok(NegotiationResponse::None) => {}
+                Err(err) => {
+                    let err = TransportError::from(err);
+                    timed(self.config.timeout, exchange.send(&error_message(&err))).await??;
+                    match &err {
+                        TransportError::Negotiation(inner)
+                            if is_downgradable(inner.error_code()) =>
+                        {
+                            rejection = Some(err)
+                        }
+                        _ => return Err(err),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Responding side of a negotiation carried over HTTP.
+///
+/// Feed every request of one client to [`handle`](Self::handle); a new
+/// `Capabilities` message restarts the handshake.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpNegotiationServer {
+    template: SchemaNegotiator,
+    negotiator: SchemaNegotiator,
+    session: Option<NegotiationSession>,
+}
+
+#[cfg(feature = "http")]
+impl HttpNegotiationServer {
+    /// Creates a server answering with `negotiator`'s capabilities and FID mappings.
+    pub fn new(negotiator: SchemaNegotiator) -> Self {
+        Self {
+            template: negotiator.clone(),
+            negotiator,
+            session: None,
+        }
+    }
+
+    /// Session agreed with the client, once the handshake is complete.
+    pub fn session(&self) -> Option<&NegotiationSession> {
+        self.session.as_ref()
+    }
+
+    /// Handles one negotiation request and returns the response to send back.
+    ///
+    /// Protocol errors are answered with `200 OK` and an `Error` message so the client
+    /// can downgrade; malformed requests get `400` or `415`.
+    pub fn handle(&mut self, request: &http::Request<Vec<u8>>) -> http::Response<Vec<u8>> {
+        let content_type = request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        if content_type != Some(CONTENT_TYPE_LNMP_NEGOTIATION) {
+            return status_response(http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+        let message = match NegotiationMessage::decode(request.body()) {
+            Ok(message) => message,
+            Err(_) => return status_response(http::StatusCode::BAD_REQUEST),
+        };
+
+        if matches!(message, NegotiationMessage::Capabilities { .. }) {
+            self.negotiator = self.template.clone();
+            self.session = None;
+        }
+
+        let reply = match self.negotiator.handle_message(message) {
+            Ok(NegotiationResponse::SendMessage(reply)) => reply,
+            Ok(NegotiationResponse::Complete(session)) => {
+                let reply = NegotiationMessage::Ready {
+                    session_id: session.session_id,
+                };
+                self.session = Some(session);
+                reply
+            }
+            Ok(NegotiationResponse::Failed(_)) | Ok(NegotiationResponse::None) => {
+                return status_response(http::StatusCode::NO_CONTENT)
+            }
+            Err(err) => error_message(&err.into()),
+        };
+        message_response(&reply)
+    }
+}
+
+/// Builds the HTTP request carrying a negotiation message.
+#[cfg(feature = "http")]
+pub fn message_to_http_request(
+    uri: http::Uri,
+    message: &NegotiationMessage,
+) -> Result<http::Request<Vec<u8>>> {
+    http::Request::post(uri)
+        .header(http::header::CONTENT_TYPE, CONTENT_TYPE_LNMP_NEGOTIATION)
+        .body(message.encode())
+        .map_err(|e| TransportError::Http(e.to_string()))
+}
+
+/// Extracts the negotiation message from an HTTP response.
+#[cfg(feature = "http")]
+pub fn http_response_to_message(response: &http::Response<Vec<u8>>) -> Result<NegotiationMessage> {
+    if !response.status().is_success() {
+        return Err(TransportError::Http(format!(
+            "negotiation endpoint returned {}",
+            response.status()
+        )));
+    }
+    Ok(NegotiationMessage::decode(response.body())?)
+}
+
+#[cfg(feature = "http")]
+fn message_response(message: &NegotiationMessage) -> http::Response<Vec<u8>> {
+    let mut response = http::Response::new(message.encode());
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static(CONTENT_TYPE_LNMP_NEGOTIATION),
+    );
+    response
+}
+
+#[cfg(feature = "http")]
+fn status_response(status: http::StatusCode) -> http::Response<Vec<u8>> {
+    let mut response = http::Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+/// Message transport used by the client handshake.
+trait Exchange {
+    async fn send(&mut self, message: &NegotiationMessage) -> Result<()>;
+    async fn receive(&mut self) -> Result<NegotiationMessage>;
+}
+
+struct StreamExchange<'a, S> {
+    stream: &'a mut S,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Exchange for StreamExchange<'_, S> {
+    async fn send(&mut self, message: &NegotiationMessage) -> Result<()> {
+        write_message(self.stream, message).await
+    }
+
+    async fn receive(&mut self) -> Result<NegotiationMessage> {
+        read_message(self.stream).await
+    }
+}
+
+/// Sends each message as a request; the response is the next message received.
+#[cfg(feature = "http")]
+struct HttpExchange<'a, F> {
+    uri: http::Uri,
+    send: &'a mut F,
+    reply: Option<NegotiationMessage>,
+}
+
+#[cfg(feature = "http")]
+impl<F, Fut> Exchange for HttpExchange<'_, F>
+where
+    F: FnMut(http::Request<Vec<u8>>) -> Fut,
+    Fut: Future<Output = std::io::Result<http::Response<Vec<u8>>>>,
+{
+    async fn send(&mut self, message: &NegotiationMessage) -> Result<()> {
+        let request = message_to_http_request(self.uri.clone(), message)?;
+        let response = (self.send)(request).await?;
+        self.reply = match response.status() {
+            http::StatusCode::NO_CONTENT => None,
+            _ => Some(http_response_to_message(&response)?),
+        };
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<NegotiationMessage> {
+        self.reply
+            .take()
+            .ok_or_else(|| TransportError::Http("negotiation endpoint sent no reply".to_string()))
+    }
+}
+
+async fn timed<T>(timeout: Duration, future: impl Future<Output = T>) -> Result<T> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| TransportError::Timeout(timeout))
+}
+
+fn error_message(err: &TransportError) -> NegotiationMessage {
+    let code = match err {
+        TransportError::Negotiation(err) => err.error_code(),
+        _ => ErrorCode::Generic,
+    };
+    NegotiationMessage::Error {
+        code,
+        message: err.to_string(),
+    }
+}
+
+/// Rejections the client can answer by offering older capabilities.
+fn is_downgradable(code: ErrorCode) -> bool {
+    matches!(
+        code,
+        ErrorCode::ProtocolVersionMismatch | ErrorCode::UnsupportedFeature
+    )
+}
+
+/// Failures worth retrying on a fresh connection.
+fn is_transient(err: &TransportError) -> bool {
+    matches!(err, TransportError::Timeout(_) | TransportError::Io(_))
+}
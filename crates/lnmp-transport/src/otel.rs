@@ -0,0 +1,490 @@
+//! OpenTelemetry integration for LNMP.
+//!
+//! This module propagates W3C Trace Context between [`EnvelopeMetadata`] and
+//! [`opentelemetry::Context`], and wraps encode, decode and routing operations in
+//! spans that record frame sizes and content types as attributes.
+//!
+//! The envelope `trace_id` carries the full `traceparent` value when injected from
+//! a context, so the parent span ID and sampling flag survive the hop. A bare trace
+//! ID (any string) is still accepted on extraction and normalized the same way the
+//! HTTP binding does.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lnmp_core::LnmpRecord;
+//! use lnmp_envelope::EnvelopeBuilder;
+//! use lnmp_net::MessageKind;
+//! use lnmp_transport::otel;
+//! use lnmp_transport::SerializerConfig;
+//! use opentelemetry::trace::{noop::NoopTracer, TraceContextExt};
+//!
+//! let envelope = EnvelopeBuilder::new(LnmpRecord::new())
+//!     .trace_id("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+//!     .build();
+//!
+//! let cx = otel::extract_context(&envelope.metadata);
+//! assert!(cx.span().span_context().is_remote());
+//!
+//! let (body, content_type) = otel::encode_with_span(
+//!     &NoopTracer::new(),
+//!     &envelope,
+//!     MessageKind::Event,
+//!     &SerializerConfig::new(),
+//! )
+//! .unwrap();
+//! assert_eq!(content_type, "application/lnmp-binary");
+//! # let _ = body;
+//! ```
+
+use crate::serializer::{self, SerializerConfig};
+use crate::Result;
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::{MessageKind, NetMessage, Router, RoutingDecision};
+use opentelemetry::trace::{
+    Span, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags, TraceId, TraceState,
+    Tracer,
+};
+use opentelemetry::{Context, KeyValue};
+use std::str::FromStr;
+
+/// Span name for record encoding.
+pub const SPAN_ENCODE: &str = "lnmp.encode";
+
+/// Span name for record decoding.
+pub const SPAN_DECODE: &str = "lnmp.decode";
+
+/// Span name for routing decisions.
+pub const SPAN_ROUTE: &str = "lnmp.route";
+
+/// Span attribute: encoded frame size in bytes.
+pub const ATTR_FRAME_SIZE: &str = "lnmp.frame.size";
+
+/// Span attribute: body content type.
+pub const ATTR_CONTENT_TYPE: &str = "lnmp.content_type";
+
+/// Span attribute: number of fields in the record.
+pub const ATTR_FIELD_COUNT: &str = "lnmp.record.field_count";
+
+/// Span attribute: message kind.
+pub const ATTR_MESSAGE_KIND: &str = "lnmp.message.kind";
+
+/// Span attribute: routing decision.
+pub const ATTR_ROUTE_DECISION: &str = "lnmp.route.decision";
+
+/// Envelope label carrying the W3C `tracestate` value.
+pub const LABEL_TRACESTATE: &str = "tracestate";
+
+/// Span ID used when the envelope trace ID carries no parent span, matching the
+/// HTTP binding's generated `traceparent`.
+const DEFAULT_SPAN_ID: &str = "0123456789abcdef";
+
+/// Extracts the remote span context carried by `meta` into a new [`Context`].
+///
+/// Returns an empty context when the envelope has no trace ID. A `trace_id` in
+/// `traceparent` format keeps its span ID and flags; any other value is normalized
+/// to 32 hex digits and marked as sampled.
+pub fn extract_context(meta: &EnvelopeMetadata) -> Context {
+    let Some(trace_id) = meta.trace_id.as_deref() else {
+        return Context::new();
+    };
+
+    let trace_state = meta
+        .label(LABEL_TRACESTATE)
+        .and_then(|s| TraceState::from_str(s).ok())
+        .unwrap_or_default();
+    let span_context = parse_traceparent(trace_id, trace_state.clone()).unwrap_or_else(|| {
+        SpanContext::new(
+            TraceId::from_hex(&normalize_hex(trace_id, 32)).unwrap_or(TraceId::INVALID),
+            SpanId::from_hex(DEFAULT_SPAN_ID).unwrap_or(SpanId::INVALID),
+            TraceFlags::SAMPLED,
+            true,
+            trace_state,
+        )
+    });
+
+    Context::new().with_remote_span_context(span_context)
+}
+
+/// Injects the active span context of `cx` into `meta`.
+///
+/// Sets `trace_id` to the `traceparent` value and stores a non-empty `tracestate`
+/// as the [`LABEL_TRACESTATE`] label. Leaves `meta` unchanged when `cx` has no
+/// valid span context.
+pub fn inject_context(cx: &Context, meta: &mut EnvelopeMetadata) {
+    let span = cx.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return;
+    }
+
+    meta.trace_id = Some(format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    ));
+    let trace_state = span_context.trace_state().header();
+    if !trace_state.is_empty() {
+        meta.labels
+            .insert(LABEL_TRACESTATE.to_string(), trace_state);
+    }
+}
+
+/// Encodes the envelope's record in the format configured for `kind` inside an
+/// [`SPAN_ENCODE`] span parented by the envelope's trace context.
+///
+/// Records the frame size, content type, field count and message kind on the span.
+pub fn encode_with_span<T: Tracer>(
+    tracer: &T,
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<(Vec<u8>, &'static str)> {
+    let mut span = tracer
+        .span_builder(SPAN_ENCODE)
+        .with_kind(SpanKind::Producer)
+        .start_with_context(tracer, &extract_context(&env.metadata));
+    span.set_attribute(KeyValue::new(ATTR_MESSAGE_KIND, kind.to_string()));
+    span.set_attribute(KeyValue::new(
+        ATTR_FIELD_COUNT,
+        env.record.fields().len() as i64,
+    ));
+
+    let result = config.encode(kind, &env.record);
+    match &result {
+        Ok((body, content_type)) => {
+            span.set_attribute(KeyValue::new(ATTR_FRAME_SIZE, body.len() as i64));
+            span.set_attribute(KeyValue::new(ATTR_CONTENT_TYPE, *content_type));
+        }
+        Err(e) => span.set_status(Status::error(e.to_string())),
+    }
+    span.end();
+    result
+}
+
+/// Decodes a body by its content type inside an [`SPAN_DECODE`] span parented by
+/// the trace context in `meta`.
+///
+/// Records the frame size and content type, plus the field count on success.
+pub fn decode_with_span<T: Tracer>(
+    tracer: &T,
+    body: &[u8],
+    content_type: &str,
+    meta: &EnvelopeMetadata,
+) -> Result<LnmpRecord> {
+    let mut span = tracer
+        .span_builder(SPAN_DECODE)
+        .with_kind(SpanKind::Consumer)
+        .start_with_context(tracer, &extract_context(meta));
+    span.set_attribute(KeyValue::new(ATTR_FRAME_SIZE, body.len() as i64));
+    span.set_attribute(KeyValue::new(ATTR_CONTENT_TYPE, content_type.to_string()));
+
+    let result = serializer::decode_body(body, content_type);
+    match &result {
+        Ok(record) => span.set_attribute(KeyValue::new(
+            ATTR_FIELD_COUNT,
+            record.fields().len() as i64,
+        )),
+        Err(e) => span.set_status(Status::error(e.to_string())),
+    }
+    span.end();
+    result
+}
+
+/// Runs `router.route` inside an [`SPAN_ROUTE`] span parented by the message's
+/// trace context, recording the message kind and the decision.
+pub fn route_with_span<T: Tracer>(
+    tracer: &T,
+    router: &impl Router,
+    msg: &NetMessage,
+    now_ms: u64,
+) -> lnmp_net::Result<RoutingDecision> {
+    let mut span = tracer
+        .span_builder(SPAN_ROUTE)
+        .with_kind(SpanKind::Internal)
+        .start_with_context(tracer, &extract_context(&msg.envelope.metadata));
+    span.set_attribute(KeyValue::new(ATTR_MESSAGE_KIND, msg.kind.to_string()));
+
+    let result = router.route(msg, now_ms);
+    match &result {
+        Ok(decision) => span.set_attribute(KeyValue::new(
+            ATTR_ROUTE_DECISION,
+            match decision {
+                RoutingDecision::SendToLLM => "send_to_llm",
+                RoutingDecision::ProcessLocally => "process_locally",
+                RoutingDecision::Drop => "drop",
+            },
+        )),
+        Err(e) => span.set_status(Status::error(e.to_string())),
+    }
+    span.end();
+    result
+}
+
+/// Parses a `traceparent` value into a remote span context.
+fn parse_traceparent(value: &str, trace_state: TraceState) -> Option<SpanContext> {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [version, trace_id, span_id, flags] = parts.as_slice() else {
+        return None;
+    };
+    if *version != "00" || trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+
+    let span_context = SpanContext::new(
+        TraceId::from_hex(trace_id).ok()?,
+        SpanId::from_hex(span_id).ok()?,
+        TraceFlags::new(u8::from_str_radix(flags, 16).ok()?),
+        true,
+        trace_state,
+    );
+    span_context.is_valid().then_some(span_context)
+}
+
+/// Keeps the hex digits of `value`, lowercased and padded or truncated to `len`.
+fn normalize_hex(value: &str, len: usize) -> String {
+    let mut hex: String = value
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_lowercase())
+        .take(len)
+        .collect();
+    while hex.len() < len {
+        hex.push('0');
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+    use lnmp_net::RoutingPolicy;
+    use opentelemetry::trace::noop::NoopTracer;
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[derive(Debug, Clone, Default)]
+    struct RecordedSpan {
+        name: String,
+        parent: Option<SpanContext>,
+        attributes: Vec<KeyValue>,
+        status: Option<Status>,
+        ended: bool,
+    }
+
+    impl RecordedSpan {
+        fn attribute(&self, key: &str) -> Option<String> {
+            self.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.as_str().into_owned())
+        }
+    }
+
+    /// Tracer that records finished spans for inspection.
+    #[derive(Default)]
+    struct RecordingTracer {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+    }
+
+    struct RecordingSpan {
+        span: RecordedSpan,
+        span_context: SpanContext,
+        sink: Arc<Mutex<Vec<RecordedSpan>>>,
+    }
+
+    impl Tracer for RecordingTracer {
+        type Span = RecordingSpan;
+
+        fn build_with_context(
+            &self,
+            builder: opentelemetry::trace::SpanBuilder,
+            parent_cx: &Context,
+        ) -> Self::Span {
+            let parent = parent_cx.span().span_context().clone();
+            RecordingSpan {
+                span: RecordedSpan {
+                    name: builder.name.into_owned(),
+                    parent: parent.is_valid().then_some(parent),
+                    ..RecordedSpan::default()
+                },
+                span_context: SpanContext::NONE,
+                sink: self.spans.clone(),
+            }
+        }
+    }
+
+    impl Span for RecordingSpan {
+        fn add_event_with_timestamp<N>(&mut self, _: N, _: SystemTime, _: Vec<KeyValue>)
+        where
+            N: Into<Cow<'static, str>>,
+        {
+        }
+
+        fn span_context(&self) -> &SpanContext {
+            &self.span_context
+        }
+
+        fn is_recording(&self) -> bool {
+            !self.span.ended
+        }
+
+        fn set_attribute(&mut self, attribute: KeyValue) {
+            self.span.attributes.push(attribute);
+        }
+
+        fn set_status(&mut self, status: Status) {
+            self.span.status = Some(status);
+        }
+
+        fn update_name<N>(&mut self, new_name: N)
+        where
+            N: Into<Cow<'static, str>>,
+        {
+            self.span.name = new_name.into().into_owned();
+        }
+
+        fn end_with_timestamp(&mut self, _: SystemTime) {
+            self.span.ended = true;
+            self.sink.lock().unwrap().push(self.span.clone());
+        }
+    }
+
+    fn sample_envelope(trace_id: &str) -> LnmpEnvelope {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        EnvelopeBuilder::new(record)
+            .timestamp(1000)
+            .trace_id(trace_id)
+            .build()
+    }
+
+    #[test]
+    fn test_extract_traceparent() {
+        let meta = sample_envelope(TRACEPARENT).metadata;
+        let cx = extract_context(&meta);
+        let span = cx.span();
+        let span_context = span.span_context();
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "0af7651916cd43dd8448eb211c80319c"
+        );
+        assert_eq!(span_context.span_id().to_string(), "b7ad6b7169203331");
+    }
+
+    #[test]
+    fn test_extract_bare_trace_id() {
+        let meta = sample_envelope("abc-123").metadata;
+        let cx = extract_context(&meta);
+        assert_eq!(
+            cx.span().span_context().trace_id().to_string(),
+            "abc12300000000000000000000000000"
+        );
+        assert!(cx.span().span_context().is_valid());
+
+        assert!(!extract_context(&EnvelopeMetadata::new()).has_active_span());
+    }
+
+    #[test]
+    fn test_inject_round_trip() {
+        let mut meta = sample_envelope(TRACEPARENT).metadata;
+        meta.labels
+            .insert(LABEL_TRACESTATE.to_string(), "vendor=abc".to_string());
+        let cx = extract_context(&meta);
+
+        let mut injected = EnvelopeMetadata::new();
+        inject_context(&cx, &mut injected);
+        assert_eq!(injected.trace_id.as_deref(), Some(TRACEPARENT));
+        assert_eq!(injected.label(LABEL_TRACESTATE), Some("vendor=abc"));
+
+        let mut untouched = EnvelopeMetadata::new();
+        inject_context(&Context::new(), &mut untouched);
+        assert_eq!(untouched, EnvelopeMetadata::new());
+    }
+
+    #[test]
+    fn test_encode_decode_spans_record_frame_size() {
+        let tracer = RecordingTracer::default();
+        let envelope = sample_envelope(TRACEPARENT);
+
+        let (body, content_type) = encode_with_span(
+            &tracer,
+            &envelope,
+            MessageKind::Event,
+            &SerializerConfig::new(),
+        )
+        .unwrap();
+        let record = decode_with_span(&tracer, &body, content_type, &envelope.metadata).unwrap();
+        assert_eq!(record, envelope.record);
+
+        let spans = tracer.spans.lock().unwrap();
+        assert_eq!(spans.len(), 2);
+        let (encode, decode) = (&spans[0], &spans[1]);
+        assert_eq!(encode.name, SPAN_ENCODE);
+        assert_eq!(decode.name, SPAN_DECODE);
+        for span in [encode, decode] {
+            assert!(span.ended);
+            assert_eq!(
+                span.attribute(ATTR_FRAME_SIZE),
+                Some(body.len().to_string())
+            );
+            assert_eq!(
+                span.attribute(ATTR_CONTENT_TYPE).as_deref(),
+                Some(content_type)
+            );
+            assert_eq!(span.attribute(ATTR_FIELD_COUNT).as_deref(), Some("1"));
+            assert_eq!(
+                span.parent.as_ref().map(|p| p.span_id().to_string()),
+                Some("b7ad6b7169203331".to_string())
+            );
+        }
+        assert_eq!(
+            encode.attribute(ATTR_MESSAGE_KIND).as_deref(),
+            Some("Event")
+        );
+    }
+
+    #[test]
+    fn test_decode_span_records_error() {
+        let tracer = RecordingTracer::default();
+        let meta = EnvelopeMetadata::new();
+        assert!(decode_with_span(&tracer, b"F1=1", "application/json", &meta).is_err());
+
+        let spans = tracer.spans.lock().unwrap();
+        assert!(matches!(spans[0].status, Some(Status::Error { .. })));
+        assert_eq!(spans[0].parent, None);
+    }
+
+    #[test]
+    fn test_route_span_records_decision() {
+        let tracer = RecordingTracer::default();
+        let msg = NetMessage::new(sample_envelope(TRACEPARENT), MessageKind::Alert);
+        let decision = route_with_span(&tracer, &RoutingPolicy::default(), &msg, 2000).unwrap();
+        assert_eq!(decision, RoutingDecision::SendToLLM);
+
+        let spans = tracer.spans.lock().unwrap();
+        assert_eq!(spans[0].name, SPAN_ROUTE);
+        assert_eq!(
+            spans[0].attribute(ATTR_ROUTE_DECISION).as_deref(),
+            Some("send_to_llm")
+        );
+
+        // Spans from a no-op tracer still run the operation
+        assert_eq!(
+            route_with_span(&NoopTracer::new(), &RoutingPolicy::default(), &msg, 2000).unwrap(),
+            RoutingDecision::SendToLLM
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Per-kind serializer selection for transport send helpers.
+//!
+//! A [`SerializerConfig`] maps each [`MessageKind`] to a [`WireFormat`], so one
+//! configuration decides how every integration encodes its bodies (e.g. Alerts as
+//! explain-annotated text for human channels, Events as binary). The selected
+//! format travels with the body as a content type, which [`decode_body`] uses to
+//! pick the matching decoder.
+
+use crate::{Result, TransportError};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_codec::{Encoder, Parser};
+use lnmp_core::LnmpRecord;
+use lnmp_llb::{ExplainEncoder, LlbConverter, SemanticDictionary};
+use lnmp_net::MessageKind;
+use std::collections::HashMap;
+
+/// Content-Type for LNMP binary format.
+pub const CONTENT_TYPE_LNMP_BINARY: &str = lnmp_envelope::CONTENT_TYPE_BINARY;
+
+/// Content-Type for LNMP text format.
+pub const CONTENT_TYPE_LNMP_TEXT: &str = lnmp_envelope::CONTENT_TYPE_TEXT;
+
+/// Content-Type for LNMP text annotated with `# field_name` comments.
+pub const CONTENT_TYPE_LNMP_EXPLAIN: &str = lnmp_envelope::CONTENT_TYPE_EXPLAIN;
+
+/// Content-Type for LNMP ShortForm (LLM input only, not canonical).
+pub const CONTENT_TYPE_LNMP_SHORTFORM: &str = lnmp_envelope::CONTENT_TYPE_SHORTFORM;
+
+/// Body encoding used when sending a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireFormat {
+    /// Canonical LNMP text.
+    Text,
+    /// LNMP text with `# field_name` comments from the explain dictionary, for human channels.
+    ExplainText,
+    /// LNMP binary format.
+    Binary,
+    /// ShortForm text without `F` prefixes.
+    ShortForm,
+}
+
+impl WireFormat {
+    /// Returns the Content-Type announcing this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WireFormat::Text => CONTENT_TYPE_LNMP_TEXT,
+            WireFormat::ExplainText => CONTENT_TYPE_LNMP_EXPLAIN,
+            WireFormat::Binary => CONTENT_TYPE_LNMP_BINARY,
+            WireFormat::ShortForm => CONTENT_TYPE_LNMP_SHORTFORM,
+        }
+    }
+
+    /// Returns the format a Content-Type announces, as accepted by [`decode_body`].
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        if content_type.contains("lnmp-binary") || content_type.contains("octet-stream") {
+            Some(WireFormat::Binary)
+        } else if content_type.contains("lnmp-explain") {
+            Some(WireFormat::ExplainText)
+        } else if content_type.contains("lnmp-shortform") {
+            Some(WireFormat::ShortForm)
+        } else if content_type.contains("lnmp-text") || content_type.contains("text/plain") {
+            Some(WireFormat::Text)
+        } else {
+            None
+        }
+    }
+}
+
+/// Central mapping from message kind to wire format.
+///
+/// Kinds without an explicit format use the default format, which is
+/// [`WireFormat::Binary`] unless changed.
+#[derive(Debug, Clone)]
+pub struct SerializerConfig {
+    formats: HashMap<MessageKind, WireFormat>,
+    default_format: WireFormat,
+    explain_dictionary: SemanticDictionary,
+}
+
+impl Default for SerializerConfig {
+    fn default() -> Self {
+        Self {
+            formats: HashMap::new(),
+            default_format: WireFormat::Binary,
+            explain_dictionary: SemanticDictionary::new(),
+        }
+    }
+}
+
+impl SerializerConfig {
+    /// Creates a configuration that encodes every kind as binary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the format used for `kind`.
+    pub fn with_format(mut self, kind: MessageKind, format: WireFormat) -> Self {
+        self.formats.insert(kind, format);
+        self
+    }
+
+    /// Sets the format used for kinds without an explicit format.
+    pub fn with_default_format(mut self, format: WireFormat) -> Self {
+        self.default_format = format;
+        self
+    }
+
+    /// Sets the field names used to annotate [`WireFormat::ExplainText`] bodies.
+    pub fn with_explain_dictionary(mut self, dictionary: SemanticDictionary) -> Self {
+        self.explain_dictionary = dictionary;
+        self
+    }
+
+    /// Returns the format selected for `kind`.
+    pub fn format_for(&self, kind: MessageKind) -> WireFormat {
+        self.formats
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.default_format)
+    }
+
+    /// Encodes `record` in the format selected for `kind`.
+    ///
+    /// Returns the encoded bytes and the Content-Type to send with them.
+    pub fn encode(
+        &self,
+        kind: MessageKind,
+        record: &LnmpRecord,
+    ) -> Result<(Vec<u8>, &'static str)> {
+        let format = self.format_for(kind);
+        Ok((self.encode_as(format, record)?, format.content_type()))
+    }
+
+    /// Encodes `record` in `format`, regardless of the configured kinds.
+    pub fn encode_as(&self, format: WireFormat, record: &LnmpRecord) -> Result<Vec<u8>> {
+        Ok(match format {
+            WireFormat::Text => Encoder::new().encode(record).into_bytes(),
+            WireFormat::ExplainText => ExplainEncoder::new(self.explain_dictionary.clone())
+                .encode_with_explanation(record)
+                .into_bytes(),
+            WireFormat::Binary => BinaryEncoder::new().encode(record)?,
+            WireFormat::ShortForm => LlbConverter::default()
+                .record_to_shortform(record)
+                .into_bytes(),
+        })
+    }
+}
+
+/// Encodes CloudEvent data in any LNMP wire format, preferring the default
+/// format during content type negotiation.
+#[cfg(feature = "cloudevents")]
+impl lnmp_envelope::cloudevents::RecordCodec for SerializerConfig {
+    fn content_types(&self) -> &[&'static str] {
+        match self.default_format {
+            WireFormat::Binary => &[
+                CONTENT_TYPE_LNMP_BINARY,
+                CONTENT_TYPE_LNMP_TEXT,
+                CONTENT_TYPE_LNMP_EXPLAIN,
+                CONTENT_TYPE_LNMP_SHORTFORM,
+            ],
+            WireFormat::Text => &[
+                CONTENT_TYPE_LNMP_TEXT,
+                CONTENT_TYPE_LNMP_BINARY,
+                CONTENT_TYPE_LNMP_EXPLAIN,
+                CONTENT_TYPE_LNMP_SHORTFORM,
+            ],
+            WireFormat::ExplainText => &[
+                CONTENT_TYPE_LNMP_EXPLAIN,
+                CONTENT_TYPE_LNMP_BINARY,
+                CONTENT_TYPE_LNMP_TEXT,
+                CONTENT_TYPE_LNMP_SHORTFORM,
+            ],
+            WireFormat::ShortForm => &[
+                CONTENT_TYPE_LNMP_SHORTFORM,
+                CONTENT_TYPE_LNMP_BINARY,
+                CONTENT_TYPE_LNMP_TEXT,
+                CONTENT_TYPE_LNMP_EXPLAIN,
+            ],
+        }
+    }
+
+    fn encode(&self, record: &LnmpRecord, content_type: &str) -> lnmp_envelope::Result<Vec<u8>> {
+        let format = [
+            WireFormat::Binary,
+            WireFormat::Text,
+            WireFormat::ExplainText,
+            WireFormat::ShortForm,
+        ]
+        .into_iter()
+        .find(|format| format.content_type() == content_type)
+        .ok_or_else(|| {
+            lnmp_envelope::EnvelopeError::CloudEvent(format!(
+                "unsupported datacontenttype {}",
+                content_type
+            ))
+        })?;
+        self.encode_as(format, record)
+            .map_err(|e| lnmp_envelope::EnvelopeError::CloudEvent(e.to_string()))
+    }
+
+    fn decode(&self, data: &[u8], content_type: &str) -> lnmp_envelope::Result<LnmpRecord> {
+        decode_body(data, content_type)
+            .map_err(|e| lnmp_envelope::EnvelopeError::CloudEvent(e.to_string()))
+    }
+}
+
+/// Decodes a body produced by [`SerializerConfig::encode`] using its Content-Type.
+///
+/// `octet-stream` is accepted as binary and `text/plain` as LNMP text.
+pub fn decode_body(body: &[u8], content_type: &str) -> Result<LnmpRecord> {
+    match WireFormat::from_content_type(content_type) {
+        Some(WireFormat::Binary) => Ok(BinaryDecoder::new().decode(body)?),
+        Some(WireFormat::ExplainText) => {
+            let text = strip_explanations(body_as_str(body)?);
+            let mut parser = Parser::new(&text)?;
+            Ok(parser.parse_record()?)
+        }
+        Some(WireFormat::ShortForm) => {
+            let text = body_as_str(body)?;
+            Ok(LlbConverter::default().shortform_to_record(text)?)
+        }
+        Some(WireFormat::Text) => {
+            let mut parser = Parser::new(body_as_str(body)?)?;
+            Ok(parser.parse_record()?)
+        }
+        None => Err(TransportError::InvalidHeaderValue(
+            "content-type".into(),
+            format!("unsupported: {}", content_type),
+        )),
+    }
+}
+
+/// Removes explain comments: a `#` outside quotes that follows whitespace.
+///
+/// Checksums are written directly after the value (`F1=2#36AAE667`) and are kept.
+fn strip_explanations(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let mut in_string = false;
+        let mut escape = false;
+        let mut prev = '\0';
+        let mut end = line.len();
+        for (idx, ch) in line.char_indices() {
+            if in_string {
+                if escape {
+                    escape = false;
+                } else if ch == '\\' {
+                    escape = true;
+                } else if ch == '"' {
+                    in_string = false;
+                }
+            } else if ch == '"' {
+                in_string = true;
+            } else if ch == '#' && prev.is_whitespace() {
+                end = idx;
+                break;
+            }
+            prev = ch;
+        }
+        out.push_str(line[..end].trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn body_as_str(body: &[u8]) -> Result<&str> {
+    std::str::from_utf8(body)
+        .map_err(|_| TransportError::InvalidHeaderValue("body".into(), "not utf8".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    fn sample_record() -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(14532),
+        });
+        record.add_field(LnmpField {
+            fid: 20,
+            value: LnmpValue::String("overheat".to_string()),
+        });
+        record
+    }
+
+    #[test]
+    fn test_default_config_is_binary() {
+        let config = SerializerConfig::new();
+        for kind in [
+            MessageKind::Event,
+            MessageKind::State,
+            MessageKind::Command,
+            MessageKind::Query,
+            MessageKind::Alert,
+        ] {
+            assert_eq!(config.format_for(kind), WireFormat::Binary);
+        }
+    }
+
+    #[test]
+    fn test_per_kind_formats_round_trip() {
+        let config = SerializerConfig::new()
+            .with_default_format(WireFormat::Text)
+            .with_format(MessageKind::Event, WireFormat::Binary)
+            .with_format(MessageKind::Query, WireFormat::ShortForm)
+            .with_format(MessageKind::Alert, WireFormat::ExplainText)
+            .with_explain_dictionary(SemanticDictionary::from_pairs(vec![
+                (12, "device_id"),
+                (20, "reason"),
+            ]));
+        let record = sample_record();
+
+        let expected = [
+            (MessageKind::Event, CONTENT_TYPE_LNMP_BINARY),
+            (MessageKind::State, CONTENT_TYPE_LNMP_TEXT),
+            (MessageKind::Query, CONTENT_TYPE_LNMP_SHORTFORM),
+            (MessageKind::Alert, CONTENT_TYPE_LNMP_EXPLAIN),
+        ];
+        for (kind, content_type) in expected {
+            let (body, actual) = config.encode(kind, &record).unwrap();
+            assert_eq!(actual, content_type, "{kind}");
+            assert_eq!(decode_body(&body, actual).unwrap(), record, "{kind}");
+        }
+
+        let (alert, _) = config.encode(MessageKind::Alert, &record).unwrap();
+        let alert = String::from_utf8(alert).unwrap();
+        assert!(alert.contains("# device_id"));
+        assert!(alert.contains("# reason"));
+    }
+
+    #[test]
+    fn test_strip_explanations_keeps_checksums_and_strings() {
+        let text = "F1:s=\"a # b\"  # label\nF2:i=7#36AAE667    # count";
+        assert_eq!(
+            strip_explanations(text),
+            "F1:s=\"a # b\"\nF2:i=7#36AAE667\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_body_rejects_unknown_content_type() {
+        assert!(decode_body(b"F1=1", "application/json").is_err());
+    }
+
+    #[cfg(feature = "cloudevents")]
+    #[test]
+    fn test_cloudevents_round_trip() {
+        use lnmp_envelope::cloudevents::{
+            from_cloudevent, to_cloudevent, CloudEvent, CloudEventData, CloudEventOptions,
+        };
+        use lnmp_envelope::EnvelopeBuilder;
+
+        let envelope = EnvelopeBuilder::new(sample_record())
+            .timestamp(1732373147000)
+            .source("sensor-7")
+            .trace_id("abc-123")
+            .build();
+        let config = SerializerConfig::new().with_default_format(WireFormat::Text);
+
+        let event = to_cloudevent(&envelope, &config, &CloudEventOptions::new()).unwrap();
+        assert_eq!(
+            event.datacontenttype.as_deref(),
+            Some(CONTENT_TYPE_LNMP_TEXT)
+        );
+        assert!(matches!(event.data, Some(CloudEventData::Text(_))));
+        let received = CloudEvent::from_structured(&event.to_structured().unwrap()).unwrap();
+        assert_eq!(
+            from_cloudevent(&received, &config).unwrap().record,
+            envelope.record
+        );
+
+        let options = CloudEventOptions::new().with_accept([CONTENT_TYPE_LNMP_BINARY]);
+        let event = to_cloudevent(&envelope, &config, &options).unwrap();
+        let (headers, body) = event.to_binary_mode();
+        let received = CloudEvent::from_binary_mode(
+            headers.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            &body,
+        )
+        .unwrap();
+        assert_eq!(
+            from_cloudevent(&received, &config).unwrap().record,
+            envelope.record
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Server-Sent Events bindings for LNMP.
+//!
+//! This module frames LNMP records as SSE events for browser dashboards and streaming
+//! LLM contexts, and reassembles them on the consumer side:
+//!
+//! ```text
+//! event: lnmp-record
+//! id: 42
+//! data: F7=1
+//! data: F12=14532
+//!
+//! ```
+//!
+//! `id` is the envelope's sequence number, so a reconnecting `EventSource` resumes via
+//! `Last-Event-ID`. Data is canonical LNMP text (one `data:` line per text line), or
+//! the binary frame base64-encoded behind a `base64,` prefix.
+
+use crate::{Result, TransportError};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_codec::{Encoder, Parser};
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::LnmpEnvelope;
+use std::fmt;
+
+/// Content-Type of an SSE response.
+pub const CONTENT_TYPE_EVENT_STREAM: &str = "text/event-stream";
+
+/// Event type of LNMP record events.
+pub const EVENT_RECORD: &str = "lnmp-record";
+
+/// Prefix of base64-encoded binary record data.
+pub const BASE64_DATA_PREFIX: &str = "base64,";
+
+/// Event type assigned to events without an `event:` field.
+pub const DEFAULT_EVENT: &str = "message";
+
+/// How record data is written into events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SseEncoding {
+    /// Canonical LNMP text, readable by browsers and LLMs.
+    #[default]
+    Text,
+    /// Binary LNMP frame, base64-encoded.
+    Base64Binary,
+}
+
+/// A Server-Sent Event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type (`event:` field).
+    pub event: String,
+    /// Event ID (`id:` field).
+    pub id: Option<String>,
+    /// Event data; lines are joined with `\n`.
+    pub data: String,
+    /// Reconnection time in milliseconds (`retry:` field).
+    pub retry: Option<u64>,
+}
+
+impl SseEvent {
+    /// Creates an event of type `event` carrying `data`.
+    pub fn new(event: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            event: event.into(),
+            id: None,
+            data: data.into(),
+            retry: None,
+        }
+    }
+
+    /// Sets the event ID.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reconnection time in milliseconds.
+    pub fn with_retry(mut self, retry_ms: u64) -> Self {
+        self.retry = Some(retry_ms);
+        self
+    }
+
+    /// Returns true if this is an LNMP record event.
+    pub fn is_record(&self) -> bool {
+        self.event == EVENT_RECORD
+    }
+
+    /// Returns the event ID as a sequence number, if it is one.
+    pub fn sequence(&self) -> Option<u64> {
+        self.id.as_deref()?.parse().ok()
+    }
+}
+
+/// Writes the event in wire format, including the blank line that ends it.
+impl fmt::Display for SseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.event != DEFAULT_EVENT {
+            writeln!(f, "event: {}", self.event)?;
+        }
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {}", id)?;
+        }
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry)?;
+        }
+        for line in self.data.split('\n') {
+            writeln!(f, "data: {}", line)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Frames a record as an `lnmp-record` event.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::sse::{self, SseEncoding};
+/// let event = sse::record_to_sse_event(&record, Some(42), SseEncoding::Text)?;
+/// response.write_all(event.to_string().as_bytes())?;
+/// ```
+pub fn record_to_sse_event(
+    record: &LnmpRecord,
+    sequence: Option<u64>,
+    encoding: SseEncoding,
+) -> Result<SseEvent> {
+    let data = match encoding {
+        SseEncoding::Text => Encoder::new().encode(record),
+        SseEncoding::Base64Binary => {
+            let frame = BinaryEncoder::new().encode(record)?;
+            format!("{}{}", BASE64_DATA_PREFIX, base64_encode(&frame))
+        }
+    };
+    let event = SseEvent::new(EVENT_RECORD, data);
+    Ok(match sequence {
+        Some(seq) => event.with_id(seq.to_string()),
+        None => event,
+    })
+}
+
+/// Frames an envelope's record as an `lnmp-record` event with its sequence as ID.
+pub fn envelope_to_sse_event(env: &LnmpEnvelope, encoding: SseEncoding) -> Result<SseEvent> {
+    record_to_sse_event(&env.record, env.metadata.sequence, encoding)
+}
+
+/// Decodes the record of an `lnmp-record` event.
+///
+/// Returns `None` for events of other types (heartbeats, application events).
+pub fn sse_event_to_record(event: &SseEvent) -> Result<Option<LnmpRecord>> {
+    if !event.is_record() {
+        return Ok(None);
+    }
+    let record = match event.data.strip_prefix(BASE64_DATA_PREFIX) {
+        Some(encoded) => {
+            let frame = base64_decode(encoded.trim()).ok_or_else(|| {
+                TransportError::InvalidHeaderValue("data".into(), "invalid base64".into())
+            })?;
+            BinaryDecoder::new().decode(&frame)?
+        }
+        None => Parser::new(&event.data)?.parse_record()?,
+    };
+    Ok(Some(record))
+}
+
+/// Reassembles SSE events from a byte stream.
+///
+/// Chunks may split lines and events anywhere. Follows the SSE parsing rules: lines
+/// end with CRLF, LF or CR, lines starting with `:` are comments, and events without
+/// data are discarded. The last event ID persists across events.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::sse::{self, SseReassembler};
+///
+/// let mut reassembler = SseReassembler::new();
+/// while let Some(chunk) = body.next().await {
+///     for event in reassembler.feed(&chunk?) {
+///         if let Some(record) = sse::sse_event_to_record(&event)? {
+///             // handle record
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SseReassembler {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    last_event_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseReassembler {
+    /// Creates a reassembler with no buffered input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last event ID seen, to send as `Last-Event-ID` when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Returns the last reconnection time announced by the server.
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// Feeds a chunk of the stream and returns the events it completes.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < self.buffer.len() {
+            let next = match self.buffer[i] {
+                b'\n' => i + 1,
+                b'\r' if i + 1 == self.buffer.len() => break, // LF may follow
+                b'\r' if self.buffer[i + 1] == b'\n' => i + 2,
+                b'\r' => i + 1,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let line = String::from_utf8_lossy(&self.buffer[start..i]).into_owned();
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+            start = next;
+            i = next;
+        }
+        self.buffer.drain(..start);
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let event = self.event.take();
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(SseEvent {
+                event: event.unwrap_or_else(|| DEFAULT_EVENT.to_string()),
+                id: self.last_event_id.clone(),
+                data: std::mem::take(&mut self.data).join("\n"),
+                retry: self.retry,
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(retry) = value.parse() {
+                    self.retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = BASE64_ALPHABET.iter().position(|a| *a == c)? as u32;
+        n = n << 6 | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,382 @@
+//! UDP datagram framing for LNMP.
+//!
+//! Packs one binary record frame and its envelope metadata into a single datagram,
+//! for LAN sensor swarms where a record fits in one packet:
+//!
+//! ```text
+//! "LU" | VERSION (1) | META_LEN (u16 BE) | META (TLV) | FRAME | CRC32C (u32 BE)
+//! ```
+//!
+//! `META` is the envelope metadata in the binary TLV codec and `FRAME` the
+//! `BinaryEncoder` output; the CRC32C covers everything before it. Datagrams larger
+//! than the configured limit are refused rather than left to IP fragmentation, which
+//! loses the whole datagram when any fragment is lost.
+//!
+//! On the receiving side, [`DatagramReceiver`] validates datagrams and puts sequenced
+//! envelopes back in order. This module does not open sockets; pass the bytes to and
+//! from any UDP socket.
+
+use crate::{Result, TransportError};
+use crc::{Crc, CRC_32_ISCSI};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_envelope::binary_codec::{TlvDecoder, TlvEncoder};
+use lnmp_envelope::LnmpEnvelope;
+use std::collections::BTreeMap;
+
+/// Magic bytes opening every datagram.
+pub const MAGIC: [u8; 2] = *b"LU";
+
+/// Datagram format version.
+pub const VERSION: u8 = 1;
+
+/// Largest UDP payload that avoids fragmentation on a 1500-byte Ethernet MTU
+/// (IPv4: 1500 - 20 IP - 8 UDP).
+pub const DEFAULT_MAX_DATAGRAM_SIZE: usize = 1472;
+
+/// Bytes of framing around the metadata and record frame.
+pub const OVERHEAD: usize = 2 + 1 + 2 + 4;
+
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// Packs envelopes into datagrams.
+#[derive(Debug, Clone)]
+pub struct DatagramEncoder {
+    max_size: usize,
+}
+
+impl Default for DatagramEncoder {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_DATAGRAM_SIZE,
+        }
+    }
+}
+
+impl DatagramEncoder {
+    /// Creates an encoder limited to [`DEFAULT_MAX_DATAGRAM_SIZE`] bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest datagram produced, e.g. from the path MTU (IPv6: MTU - 48).
+    pub fn with_max_datagram_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Returns the largest datagram produced.
+    pub fn max_datagram_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Packs an envelope into one datagram.
+    ///
+    /// # Errors
+    ///
+    /// [`TransportError::DatagramTooLarge`] if the datagram would exceed the size
+    /// limit; the record must be split or sent over a stream transport.
+    pub fn encode(&self, env: &LnmpEnvelope) -> Result<Vec<u8>> {
+        let meta = TlvEncoder::encode(&env.metadata)
+            .map_err(|e| TransportError::EnvelopeError(e.to_string()))?;
+        let meta_len = u16::try_from(meta.len()).map_err(|_| {
+            TransportError::InvalidDatagram("envelope metadata exceeds 65535 bytes".into())
+        })?;
+        let frame = BinaryEncoder::new().encode(&env.record)?;
+
+        let size = OVERHEAD + meta.len() + frame.len();
+        if size > self.max_size {
+            return Err(TransportError::DatagramTooLarge {
+                size,
+                max: self.max_size,
+            });
+        }
+
+        let mut datagram = Vec::with_capacity(size);
+        datagram.extend_from_slice(&MAGIC);
+        datagram.push(VERSION);
+        datagram.extend_from_slice(&meta_len.to_be_bytes());
+        datagram.extend_from_slice(&meta);
+        datagram.extend_from_slice(&frame);
+        let crc = CRC32C.checksum(&datagram);
+        datagram.extend_from_slice(&crc.to_be_bytes());
+        Ok(datagram)
+    }
+}
+
+/// Unpacks one datagram, validating its magic, version and CRC.
+pub fn decode_datagram(datagram: &[u8]) -> Result<LnmpEnvelope> {
+    let invalid = |reason: &str| TransportError::InvalidDatagram(reason.to_string());
+    if datagram.len() < OVERHEAD {
+        return Err(invalid("truncated"));
+    }
+    if datagram[..2] != MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    if datagram[2] != VERSION {
+        return Err(TransportError::InvalidDatagram(format!(
+            "unsupported version {}",
+            datagram[2]
+        )));
+    }
+
+    let (body, crc) = datagram.split_at(datagram.len() - 4);
+    let expected = u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]]);
+    if CRC32C.checksum(body) != expected {
+        return Err(invalid("CRC mismatch"));
+    }
+
+    let meta_len = u16::from_be_bytes([body[3], body[4]]) as usize;
+    let meta = body
+        .get(5..5 + meta_len)
+        .ok_or_else(|| invalid("truncated"))?;
+    let frame = &body[5 + meta_len..];
+    let metadata =
+        TlvDecoder::decode(meta).map_err(|e| TransportError::EnvelopeError(e.to_string()))?;
+    let record = BinaryDecoder::new().decode(frame)?;
+    Ok(LnmpEnvelope { metadata, record })
+}
+
+/// Counters collected by a [`DatagramReceiver`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReceiverStats {
+    /// Envelopes released in order
+    pub delivered: u64,
+    /// Datagrams that arrived out of order and were held back
+    pub reordered: u64,
+    /// Datagrams behind the released sequence (late or duplicate), dropped
+    pub late: u64,
+    /// Sequence numbers given up on when the reorder window overflowed
+    pub skipped: u64,
+    /// Datagrams rejected as invalid (bad magic, version or CRC)
+    pub invalid: u64,
+}
+
+/// Validates datagrams and releases envelopes in sequence order.
+///
+/// Envelopes without a sequence number are released immediately. Sequenced ones are
+/// held until the gap before them fills; once more than `window` are held, the
+/// receiver gives up on the missing ones and moves on.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::udp::DatagramReceiver;
+///
+/// let mut receiver = DatagramReceiver::new();
+/// let mut buf = [0u8; 1500];
+/// loop {
+///     let n = socket.recv(&mut buf)?;
+///     for envelope in receiver.receive(&buf[..n])? {
+///         // handle envelope
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatagramReceiver {
+    window: usize,
+    next: Option<u64>,
+    pending: BTreeMap<u64, LnmpEnvelope>,
+    stats: ReceiverStats,
+}
+
+impl Default for DatagramReceiver {
+    fn default() -> Self {
+        Self {
+            window: 32,
+            next: None,
+            pending: BTreeMap::new(),
+            stats: ReceiverStats::default(),
+        }
+    }
+}
+
+impl DatagramReceiver {
+    /// Creates a receiver holding back at most 32 out-of-order envelopes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many out-of-order envelopes are held back before skipping a gap.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Returns the sequence number expected next, once known.
+    pub fn next_sequence(&self) -> Option<u64> {
+        self.next
+    }
+
+    /// Returns the number of envelopes held back.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns a snapshot of the counters.
+    pub fn stats(&self) -> ReceiverStats {
+        self.stats
+    }
+
+    /// Validates a datagram and returns the envelopes it releases, in order.
+    ///
+    /// # Errors
+    ///
+    /// [`TransportError::InvalidDatagram`] (or a decode error) for corrupt datagrams;
+    /// the receiver state is unchanged and later datagrams are still accepted.
+    pub fn receive(&mut self, datagram: &[u8]) -> Result<Vec<LnmpEnvelope>> {
+        let env = decode_datagram(datagram).inspect_err(|_| self.stats.invalid += 1)?;
+        let Some(seq) = env.metadata.sequence else {
+            self.stats.delivered += 1;
+            return Ok(vec![env]);
+        };
+
+        let next = *self.next.get_or_insert(seq);
+        if seq < next || self.pending.contains_key(&seq) {
+            self.stats.late += 1;
+            return Ok(Vec::new());
+        }
+        if seq > next {
+            self.stats.reordered += 1;
+        }
+        self.pending.insert(seq, env);
+
+        let mut released = self.release_ready();
+        while self.pending.len() > self.window {
+            // Give up on the gap before the oldest held envelope
+            let (&oldest, _) = self.pending.iter().next().expect("pending is not empty");
+            let next = self.next.expect("set above");
+            self.stats.skipped += oldest - next;
+            self.next = Some(oldest);
+            released.extend(self.release_ready());
+        }
+        Ok(released)
+    }
+
+    /// Releases every held envelope in order, skipping the gaps (e.g. on a timeout).
+    pub fn flush(&mut self) -> Vec<LnmpEnvelope> {
+        let mut released = Vec::with_capacity(self.pending.len());
+        while let Some((seq, env)) = self.pending.pop_first() {
+            if let Some(next) = self.next {
+                self.stats.skipped += seq.saturating_sub(next);
+            }
+            self.next = Some(seq + 1);
+            self.stats.delivered += 1;
+            released.push(env);
+        }
+        released
+    }
+
+    fn release_ready(&mut self) -> Vec<LnmpEnvelope> {
+        let mut released = Vec::new();
+        while let Some(next) = self.next {
+            let Some(env) = self.pending.remove(&next) else {
+                break;
+            };
+            self.next = Some(next + 1);
+            self.stats.delivered += 1;
+            released.push(env);
+        }
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
+    use lnmp_envelope::EnvelopeBuilder;
+
+    fn datagram(seq: Option<u64>) -> Vec<u8> {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::Float(21.5),
+        });
+        let mut builder = EnvelopeBuilder::new(record).source("sensor-1");
+        if let Some(seq) = seq {
+            builder = builder.sequence(seq);
+        }
+        DatagramEncoder::new().encode(&builder.build()).unwrap()
+    }
+
+    fn sequences(envs: &[LnmpEnvelope]) -> Vec<u64> {
+        envs.iter().filter_map(|e| e.metadata.sequence).collect()
+    }
+
+    #[test]
+    fn test_round_trip_and_corruption() {
+        let mut bytes = datagram(Some(7));
+        let env = decode_datagram(&bytes).unwrap();
+        assert_eq!(env.metadata.sequence, Some(7));
+        assert_eq!(env.metadata.source.as_deref(), Some("sensor-1"));
+        assert_eq!(
+            env.record.get_field(1).unwrap().value,
+            LnmpValue::Float(21.5)
+        );
+
+        bytes[8] ^= 0x01;
+        assert!(matches!(
+            decode_datagram(&bytes),
+            Err(TransportError::InvalidDatagram(_))
+        ));
+        assert!(decode_datagram(&bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn test_refuses_oversized_datagrams() {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 1,
+            value: LnmpValue::String("x".repeat(2000)),
+        });
+        let err = DatagramEncoder::new()
+            .encode(&LnmpEnvelope::new(record))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TransportError::DatagramTooLarge { max: 1472, .. }
+        ));
+    }
+
+    #[test]
+    fn test_reorders_by_sequence() {
+        let mut receiver = DatagramReceiver::new();
+        assert_eq!(
+            sequences(&receiver.receive(&datagram(Some(10))).unwrap()),
+            [10]
+        );
+        assert!(receiver.receive(&datagram(Some(12))).unwrap().is_empty());
+        assert!(receiver.receive(&datagram(Some(13))).unwrap().is_empty());
+        assert_eq!(
+            sequences(&receiver.receive(&datagram(Some(11))).unwrap()),
+            [11, 12, 13]
+        );
+        // Duplicates and late datagrams are dropped
+        assert!(receiver.receive(&datagram(Some(12))).unwrap().is_empty());
+        // Unsequenced envelopes pass straight through
+        assert_eq!(receiver.receive(&datagram(None)).unwrap().len(), 1);
+
+        let stats = receiver.stats();
+        assert_eq!(stats.delivered, 5);
+        assert_eq!(stats.reordered, 2);
+        assert_eq!(stats.late, 1);
+        assert_eq!(receiver.next_sequence(), Some(14));
+    }
+
+    #[test]
+    fn test_window_overflow_skips_gap() {
+        let mut receiver = DatagramReceiver::new().with_window(2);
+        receiver.receive(&datagram(Some(1))).unwrap();
+        assert!(receiver.receive(&datagram(Some(3))).unwrap().is_empty());
+        assert!(receiver.receive(&datagram(Some(4))).unwrap().is_empty());
+        // A third held envelope overflows the window: 2 is given up on
+        assert_eq!(
+            sequences(&receiver.receive(&datagram(Some(6))).unwrap()),
+            [3, 4]
+        );
+        assert_eq!(receiver.stats().skipped, 1);
+        assert_eq!(sequences(&receiver.flush()), [6]);
+        assert_eq!(receiver.stats().skipped, 2);
+        assert_eq!(receiver.pending(), 0);
+    }
+}
@@ -0,0 +1,266 @@
+//! WebSocket transport bindings for LNMP.
+//!
+//! This module defines the `lnmp.v1` WebSocket sub-protocol and helpers to wrap/unwrap
+//! [`tungstenite`] messages. It does not open connections; any tungstenite-based client
+//! or server (`tokio-tungstenite`, `async-tungstenite`, ...) can send and receive the
+//! messages it produces.
+//!
+//! Text messages carry LNMP text records. Binary messages start with a one-byte frame
+//! tag:
+//!
+//! ```text
+//! 0x01 RECORD       | binary LNMP record frame
+//! 0x02 STREAM       | streaming chunk frame (StreamingEncoder output)
+//! 0x03 NEGOTIATION  | encoded NegotiationMessage
+//! 0x04 PREAMBLE     | envelope metadata (TLV) for the next record
+//! ```
+//!
+//! Envelope metadata is sent in a preamble message right before the record it belongs
+//! to; records without a preamble have empty metadata. The preamble's `content_type`
+//! names the text format (explain, ShortForm) of a following text record.
+
+use crate::serializer::{self, SerializerConfig, CONTENT_TYPE_LNMP_TEXT};
+use crate::{Result, TransportError};
+use lnmp_codec::binary::{BinaryEncoder, NegotiationMessage};
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::binary_codec::{TlvDecoder, TlvEncoder};
+use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
+use lnmp_net::MessageKind;
+
+pub use tungstenite::Message;
+
+/// Sub-protocol name to offer and accept in `Sec-WebSocket-Protocol`.
+pub const SUBPROTOCOL: &str = "lnmp.v1";
+
+/// Frame tag of binary LNMP records.
+pub const TAG_RECORD: u8 = 0x01;
+
+/// Frame tag of streaming chunk frames.
+pub const TAG_STREAM: u8 = 0x02;
+
+/// Frame tag of negotiation messages.
+pub const TAG_NEGOTIATION: u8 = 0x03;
+
+/// Frame tag of envelope metadata preambles.
+pub const TAG_PREAMBLE: u8 = 0x04;
+
+/// Kind of LNMP frame carried by a WebSocket message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameType {
+    /// LNMP text record in a text message.
+    TextRecord,
+    /// Binary LNMP record frame.
+    BinaryRecord,
+    /// Streaming chunk frame.
+    StreamChunk,
+    /// Schema negotiation message.
+    Negotiation,
+    /// Envelope metadata for the next record.
+    Preamble,
+}
+
+impl FrameType {
+    /// Returns the frame tag of binary messages; text records have none.
+    pub fn tag(&self) -> Option<u8> {
+        match self {
+            FrameType::TextRecord => None,
+            FrameType::BinaryRecord => Some(TAG_RECORD),
+            FrameType::StreamChunk => Some(TAG_STREAM),
+            FrameType::Negotiation => Some(TAG_NEGOTIATION),
+            FrameType::Preamble => Some(TAG_PREAMBLE),
+        }
+    }
+
+    /// Returns the frame type of a binary message tag.
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            TAG_RECORD => Some(FrameType::BinaryRecord),
+            TAG_STREAM => Some(FrameType::StreamChunk),
+            TAG_NEGOTIATION => Some(FrameType::Negotiation),
+            TAG_PREAMBLE => Some(FrameType::Preamble),
+            _ => None,
+        }
+    }
+
+    /// Returns the frame type of a WebSocket message.
+    ///
+    /// Returns `None` for control messages and binary messages without a known tag.
+    pub fn of(message: &Message) -> Option<Self> {
+        match message {
+            Message::Text(_) => Some(FrameType::TextRecord),
+            Message::Binary(data) => data.first().copied().and_then(Self::from_tag),
+            _ => None,
+        }
+    }
+}
+
+/// Wraps a binary record frame into a tagged binary message.
+pub fn record_to_ws_message(record: &LnmpRecord) -> Result<Message> {
+    let frame = BinaryEncoder::new().encode(record)?;
+    Ok(tagged(TAG_RECORD, &frame))
+}
+
+/// Wraps a record as a text message in canonical LNMP text.
+pub fn record_to_ws_text(record: &LnmpRecord) -> Message {
+    Message::text(lnmp_codec::Encoder::new().encode(record))
+}
+
+/// Wraps a streaming chunk frame (from `StreamingEncoder`) into a tagged binary message.
+pub fn stream_chunk_to_ws_message(frame: &[u8]) -> Message {
+    tagged(TAG_STREAM, frame)
+}
+
+/// Wraps a negotiation message into a tagged binary message.
+pub fn negotiation_to_ws_message(message: &NegotiationMessage) -> Message {
+    tagged(TAG_NEGOTIATION, &message.encode())
+}
+
+/// Wraps envelope metadata into a preamble message.
+pub fn metadata_to_ws_preamble(meta: &EnvelopeMetadata) -> Result<Message> {
+    let tlv = TlvEncoder::encode(meta).map_err(|e| TransportError::EnvelopeError(e.to_string()))?;
+    Ok(tagged(TAG_PREAMBLE, &tlv))
+}
+
+/// Encodes an LNMP Envelope to WebSocket messages: a preamble (if it has metadata) and
+/// a binary record.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::websocket;
+/// for message in websocket::envelope_to_ws_messages(&envelope)? {
+///     socket.send(message)?;
+/// }
+/// ```
+pub fn envelope_to_ws_messages(env: &LnmpEnvelope) -> Result<Vec<Message>> {
+    let mut messages = Vec::with_capacity(2);
+    if !env.metadata.is_empty() {
+        messages.push(metadata_to_ws_preamble(&env.metadata)?);
+    }
+    messages.push(record_to_ws_message(&env.record)?);
+    Ok(messages)
+}
+
+/// Encodes an LNMP Envelope to WebSocket messages in the format configured for `kind`.
+///
+/// Binary records are sent as tagged binary messages and text formats as text
+/// messages after a preamble whose `content_type` names the text format.
+pub fn envelope_to_ws_messages_for_kind(
+    env: &LnmpEnvelope,
+    kind: MessageKind,
+    config: &SerializerConfig,
+) -> Result<Vec<Message>> {
+    let (body, content_type) = config.encode(kind, &env.record)?;
+    if content_type == serializer::CONTENT_TYPE_LNMP_BINARY {
+        return envelope_to_ws_messages(env);
+    }
+
+    let text = String::from_utf8(body)
+        .map_err(|_| TransportError::InvalidHeaderValue("body".into(), "not utf8".into()))?;
+    let mut meta = env.metadata.clone();
+    meta.content_type = Some(content_type.to_string());
+    Ok(vec![metadata_to_ws_preamble(&meta)?, Message::text(text)])
+}
+
+/// A decoded LNMP WebSocket message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsFrame {
+    /// A record, with the metadata of the preamble before it.
+    Envelope(Box<LnmpEnvelope>),
+    /// A streaming chunk frame, to feed to a `StreamingDecoder`.
+    StreamChunk(Vec<u8>),
+    /// A schema negotiation message.
+    Negotiation(NegotiationMessage),
+}
+
+/// Unwraps the messages of one WebSocket connection.
+///
+/// Keeps the last preamble until the record it belongs to arrives.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use lnmp_transport::websocket::{WsDecoder, WsFrame};
+///
+/// let mut decoder = WsDecoder::new();
+/// while let Some(message) = socket.read().ok() {
+///     if let Some(WsFrame::Envelope(envelope)) = decoder.decode(&message)? {
+///         // handle envelope
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WsDecoder {
+    preamble: Option<EnvelopeMetadata>,
+}
+
+impl WsDecoder {
+    /// Creates a decoder with no pending preamble.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the metadata of a preamble still waiting for its record.
+    pub fn pending_preamble(&self) -> Option<&EnvelopeMetadata> {
+        self.preamble.as_ref()
+    }
+
+    /// Decodes one WebSocket message.
+    ///
+    /// Returns `None` for preambles (kept for the next record) and control messages
+    /// (ping, pong, close).
+    pub fn decode(&mut self, message: &Message) -> Result<Option<WsFrame>> {
+        let data = match message {
+            Message::Text(text) => {
+                let metadata = self.preamble.take().unwrap_or_default();
+                let content_type = metadata
+                    .content_type
+                    .as_deref()
+                    .filter(|ct| ct.contains("lnmp-") && !ct.contains("lnmp-binary"))
+                    .unwrap_or(CONTENT_TYPE_LNMP_TEXT);
+                let record = serializer::decode_body(text.as_bytes(), content_type)?;
+                return Ok(Some(WsFrame::Envelope(Box::new(LnmpEnvelope {
+                    metadata,
+                    record,
+                }))));
+            }
+            Message::Binary(data) => data,
+            _ => return Ok(None),
+        };
+
+        let Some((&tag, payload)) = data.split_first() else {
+            return Err(TransportError::WebSocket("empty binary message".into()));
+        };
+        match FrameType::from_tag(tag) {
+            Some(FrameType::BinaryRecord) => {
+                let record = lnmp_codec::binary::BinaryDecoder::new().decode(payload)?;
+                let metadata = self.preamble.take().unwrap_or_default();
+                Ok(Some(WsFrame::Envelope(Box::new(LnmpEnvelope {
+                    metadata,
+                    record,
+                }))))
+            }
+            Some(FrameType::StreamChunk) => Ok(Some(WsFrame::StreamChunk(payload.to_vec()))),
+            Some(FrameType::Negotiation) => Ok(Some(WsFrame::Negotiation(
+                NegotiationMessage::decode(payload)?,
+            ))),
+            Some(FrameType::Preamble) => {
+                let metadata = TlvDecoder::decode(payload)
+                    .map_err(|e| TransportError::EnvelopeError(e.to_string()))?;
+                self.preamble = Some(metadata);
+                Ok(None)
+            }
+            _ => Err(TransportError::WebSocket(format!(
+                "unknown frame tag 0x{:02X}",
+                tag
+            ))),
+        }
+    }
+}
+
+fn tagged(tag: u8, payload: &[u8]) -> Message {
+    let mut data = Vec::with_capacity(payload.len() + 1);
+    data.push(tag);
+    data.extend_from_slice(payload);
+    Message::binary(data)
+}
@@ -2,17 +2,25 @@
     feature = "http",
     feature = "kafka",
     feature = "grpc",
-    feature = "nats"
+    feature = "nats",
+    feature = "amqp",
+    feature = "sse",
+    feature = "websocket"
 ))]
 use lnmp_core::{LnmpField, LnmpRecord, LnmpValue};
 #[cfg(any(
     feature = "http",
     feature = "kafka",
     feature = "grpc",
-    feature = "nats"
+    feature = "nats",
+    feature = "amqp",
+    feature = "sse",
+    feature = "websocket"
 ))]
 use lnmp_envelope::{EnvelopeMetadata, LnmpEnvelope};
 
+#[cfg(feature = "amqp")]
+use lnmp_transport::amqp;
 #[cfg(feature = "grpc")]
 use lnmp_transport::grpc;
 #[cfg(feature = "http")]
@@ -21,23 +29,35 @@ use lnmp_transport::http;
 use lnmp_transport::kafka;
 #[cfg(feature = "nats")]
 use lnmp_transport::nats;
+#[cfg(feature = "sse")]
+use lnmp_transport::sse;
 
 #[cfg(any(
     feature = "http",
     feature = "kafka",
     feature = "grpc",
-    feature = "nats"
+    feature = "nats",
+    feature = "amqp",
+    feature = "sse",
+    feature = "websocket"
 ))]
 fn create_test_envelope() -> LnmpEnvelope {
-    let mut labels = std::collections::HashMap::new();
+    let mut labels = std::collections::BTreeMap::new();
     labels.insert("env".to_string(), "prod".to_string());
 
     let meta = EnvelopeMetadata {
         timestamp: Some(1627849200000),
+        expires_at: Some(1627849260000),
         source: Some("test-source".to_string()),
         trace_id: Some("test-trace-id".to_string()),
+        correlation_id: Some("saga-1".to_string()),
+        causation_id: Some("cmd-7".to_string()),
+        partition_key: Some("user-100".to_string()),
         sequence: Some(12345),
+        content_type: Some("application/lnmp-binary".to_string()),
+        schema_version: Some("1.4.0".to_string()),
         labels,
+        signature: None,
     };
 
     let mut record = LnmpRecord::new();
@@ -62,6 +82,10 @@ fn test_http_mapping() {
         headers.get("x-lnmp-timestamp").unwrap().to_str().unwrap(),
         "1627849200000"
     );
+    assert_eq!(
+        headers.get("x-lnmp-expires-at").unwrap().to_str().unwrap(),
+        "1627849260000"
+    );
     assert_eq!(
         headers.get("x-lnmp-source").unwrap().to_str().unwrap(),
         "test-source"
@@ -74,6 +98,30 @@ fn test_http_mapping() {
         headers.get("x-lnmp-sequence").unwrap().to_str().unwrap(),
         "12345"
     );
+    assert_eq!(
+        headers
+            .get("x-lnmp-correlation-id")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        "saga-1"
+    );
+    assert_eq!(
+        headers
+            .get("x-lnmp-content-type")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        "application/lnmp-binary"
+    );
+    assert_eq!(
+        headers
+            .get("x-lnmp-schema-version")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        "1.4.0"
+    );
     assert_eq!(
         headers.get("x-lnmp-label-env").unwrap().to_str().unwrap(),
         "prod"
@@ -81,12 +129,309 @@ fn test_http_mapping() {
 
     let meta = http::headers_to_envelope_metadata(&headers).unwrap();
     assert_eq!(meta.timestamp, env.metadata.timestamp);
+    assert_eq!(meta.expires_at, env.metadata.expires_at);
     assert_eq!(meta.source, env.metadata.source);
     assert_eq!(meta.trace_id, env.metadata.trace_id);
     assert_eq!(meta.sequence, env.metadata.sequence);
+    assert_eq!(meta.correlation_id, env.metadata.correlation_id);
+    assert_eq!(meta.causation_id, env.metadata.causation_id);
+    assert_eq!(meta.content_type, env.metadata.content_type);
+    assert_eq!(meta.schema_version, env.metadata.schema_version);
     assert_eq!(meta.labels.get("env"), env.metadata.labels.get("env"));
 }
 
+#[cfg(feature = "http")]
+#[test]
+fn test_http_keeps_traceparent_trace_id() {
+    let traceparent = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00";
+    let mut env = create_test_envelope();
+    env.metadata.trace_id = Some(traceparent.to_string());
+
+    let headers = http::envelope_to_headers(&env).unwrap();
+    assert_eq!(
+        headers.get("traceparent").unwrap().to_str().unwrap(),
+        traceparent
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_content_digest() {
+    use lnmp_transport::TransportError;
+
+    let env = create_test_envelope();
+    let mut headers = http::envelope_to_headers(&env).unwrap();
+    assert!(!http::verify_content_digest(&headers, &env.record).unwrap());
+
+    http::insert_content_digest(&mut headers, &env.record).unwrap();
+    let digest = headers.get(http::HEADER_CONTENT_DIGEST).unwrap();
+    assert_eq!(digest.len(), 64);
+
+    // Re-encoding the body in another format keeps the digest valid
+    let (body, content_type) = http::record_to_http_body(&env.record).unwrap();
+    let decoded = http::http_body_to_record(&body, content_type).unwrap();
+    assert!(http::verify_content_digest(&headers, &decoded).unwrap());
+
+    let mut tampered = env.record.clone();
+    tampered.add_field(LnmpField {
+        fid: 99,
+        value: LnmpValue::Bool(true),
+    });
+    assert!(matches!(
+        http::verify_content_digest(&headers, &tampered),
+        Err(TransportError::ContentDigestMismatch)
+    ));
+
+    headers.insert(http::HEADER_CONTENT_DIGEST, "abc".parse().unwrap());
+    assert!(matches!(
+        http::verify_content_digest(&headers, &env.record),
+        Err(TransportError::InvalidHeaderValue(..))
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn test_axum_extractor_and_responder() {
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::response::IntoResponse;
+    use lnmp_transport::http::axum::{LnmpBody, LnmpRejection};
+    use lnmp_transport::WireFormat;
+
+    let env = create_test_envelope();
+    let mut request = ::http::Request::post("/ingest")
+        .header("content-type", "application/lnmp-text")
+        .body(Body::from("F1=100"))
+        .unwrap();
+    request
+        .headers_mut()
+        .extend(http::envelope_to_headers(&env).unwrap());
+
+    let body = LnmpBody::from_request(request, &()).await.unwrap();
+    assert_eq!(body.format, WireFormat::Text);
+    assert_eq!(body.record(), &env.record);
+    assert_eq!(body.envelope.metadata.trace_id, env.metadata.trace_id);
+    assert_eq!(body.envelope.metadata.labels, env.metadata.labels);
+
+    // Responds with the encoded record and the metadata headers
+    let response = body.with_format(WireFormat::Binary).into_response();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/lnmp-binary"
+    );
+    let meta = http::headers_to_envelope_metadata(response.headers()).unwrap();
+    assert_eq!(meta.source, env.metadata.source);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let record = http::http_body_to_record(&bytes, "application/lnmp-binary").unwrap();
+    assert_eq!(record, env.record);
+
+    let request = ::http::Request::post("/ingest")
+        .header("content-type", "application/json")
+        .body(Body::from("{}"))
+        .unwrap();
+    let rejection = LnmpBody::from_request(request, &()).await.unwrap_err();
+    assert!(matches!(
+        rejection,
+        LnmpRejection::UnsupportedContentType(_)
+    ));
+    assert_eq!(rejection.into_response().status(), 415);
+
+    let request = ::http::Request::post("/ingest")
+        .header("content-type", "application/lnmp-binary")
+        .body(Body::from("not a frame"))
+        .unwrap();
+    let rejection = LnmpBody::from_request(request, &()).await.unwrap_err();
+    assert!(matches!(rejection, LnmpRejection::InvalidBody(_)));
+    assert_eq!(rejection.status(), 400);
+    // A body that does not match its content digest is rejected
+    let mut other = env.record.clone();
+    other.add_field(LnmpField {
+        fid: 99,
+        value: LnmpValue::Bool(true),
+    });
+    let mut request = ::http::Request::post("/ingest")
+        .header("content-type", "application/lnmp-text")
+        .body(Body::from("F1=100"))
+        .unwrap();
+    http::insert_content_digest(request.headers_mut(), &other).unwrap();
+    let rejection = LnmpBody::from_request(request, &()).await.unwrap_err();
+    assert!(matches!(
+        rejection,
+        LnmpRejection::InvalidBody(lnmp_transport::TransportError::ContentDigestMismatch)
+    ));
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn test_tower_envelope_propagation() {
+    use lnmp_transport::http::tower::{ExtractEnvelopeLayer, InjectEnvelopeLayer};
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::task::{Context, Poll};
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    /// Answers with the request, so tests can inspect what reached the service
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<::http::Request<()>> for Echo {
+        type Response = ::http::Request<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: ::http::Request<()>) -> Self::Future {
+            ready(Ok(req))
+        }
+    }
+
+    let layer = InjectEnvelopeLayer::new()
+        .with_source("planner")
+        .with_next_sequence(10);
+    let mut client = layer.layer(Echo);
+    let mut other = layer.clone().layer(Echo);
+
+    // Metadata from the request extension, with a fresh sequence
+    let env = create_test_envelope();
+    let mut request = ::http::Request::new(());
+    request.extensions_mut().insert(env.metadata.clone());
+    let sent = client.call(request).await.unwrap();
+    assert_eq!(sent.headers()["x-lnmp-source"], "test-source");
+    assert_eq!(sent.headers()["x-lnmp-trace-id"], "test-trace-id");
+    assert!(sent.headers().contains_key("traceparent"));
+    assert_eq!(sent.headers()["x-lnmp-sequence"], "10");
+
+    // Headers already set are kept; the source and sequence are filled in
+    let request = ::http::Request::builder()
+        .header("x-lnmp-sequence", "99")
+        .header("x-lnmp-correlation-id", "saga-2")
+        .body(())
+        .unwrap();
+    let sent = other.call(request).await.unwrap();
+    assert_eq!(sent.headers()["x-lnmp-source"], "planner");
+    assert_eq!(sent.headers()["x-lnmp-correlation-id"], "saga-2");
+    assert_eq!(sent.headers().get_all("x-lnmp-sequence").iter().count(), 1);
+    assert_eq!(sent.headers()["x-lnmp-sequence"], "11");
+    assert_eq!(layer.next_sequence(), 12);
+
+    // Invalid metadata fails the call instead of sending partial headers
+    let mut request = ::http::Request::new(());
+    request.extensions_mut().insert(EnvelopeMetadata {
+        source: Some("bad\nsource".to_string()),
+        ..Default::default()
+    });
+    assert!(client.call(request).await.is_err());
+
+    // Inbound: headers become a request extension
+    let mut server = ExtractEnvelopeLayer::new().layer(Echo);
+    let received = server.call(sent).await.unwrap();
+    let meta = received.extensions().get::<EnvelopeMetadata>().unwrap();
+    assert_eq!(meta.source.as_deref(), Some("planner"));
+    assert_eq!(meta.sequence, Some(11));
+}
+
+/// Serves one canned HTTP response per connection and returns the requests received
+#[cfg(feature = "client")]
+async fn serve_http(
+    responses: Vec<&'static str>,
+) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/ingest", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length: "))
+                        .map_or(0, |l| l.trim().parse().unwrap());
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            requests.push(String::from_utf8_lossy(&request).into_owned());
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[cfg(feature = "client")]
+#[tokio::test]
+async fn test_http_client_post_record() {
+    use lnmp_transport::http::client::{self, LnmpClient};
+    use lnmp_transport::{TransportError, WireFormat};
+    use std::time::Duration;
+
+    let env = create_test_envelope();
+    let (url, server) = serve_http(vec![
+        "HTTP/1.1 503 Service Unavailable\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        "HTTP/1.1 200 OK\r\ncontent-type: application/lnmp-text\r\nx-lnmp-source: planner\r\ncontent-length: 5\r\nconnection: close\r\n\r\nF2=ok",
+    ])
+    .await;
+    let reply = client::post_record(&url, &env, WireFormat::Text)
+        .await
+        .unwrap();
+    assert_eq!(reply.metadata.source.as_deref(), Some("planner"));
+    assert_eq!(
+        reply.record.get_field(2).unwrap().value,
+        LnmpValue::String("ok".to_string())
+    );
+
+    let requests = server.await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let request = requests[1].to_lowercase();
+    assert!(request.starts_with("post /ingest"));
+    assert!(request.contains("content-type: application/lnmp-text"));
+    assert!(request.contains("x-lnmp-source: test-source"));
+    assert!(request.ends_with("\r\n\r\nf1=100"));
+
+    // LNMP error bodies are decoded; long Retry-After delays are not waited for
+    let (url, _server) = serve_http(vec![
+        "HTTP/1.1 429 Too Many Requests\r\nretry-after: 120\r\ncontent-type: application/lnmp-text\r\ncontent-length: 15\r\nconnection: close\r\n\r\nF1=\"rate limit\"",
+    ])
+    .await;
+    let err = LnmpClient::new()
+        .with_max_retry_after(Duration::from_secs(1))
+        .post_record(&url, &env, WireFormat::Binary)
+        .await
+        .unwrap_err();
+    match err {
+        TransportError::HttpStatus {
+            status,
+            retry_after,
+            record,
+            ..
+        } => {
+            assert_eq!(status, 429);
+            assert_eq!(retry_after, Some(Duration::from_secs(120)));
+            assert_eq!(
+                record.unwrap().get_field(1).unwrap().value,
+                LnmpValue::String("rate limit".to_string())
+            );
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
 #[cfg(feature = "kafka")]
 #[test]
 fn test_kafka_mapping() {
@@ -94,19 +439,126 @@ fn test_kafka_mapping() {
     let headers = kafka::envelope_to_kafka_headers(&env).unwrap();
 
     assert_eq!(headers.get("lnmp.timestamp").unwrap(), b"1627849200000");
+    assert_eq!(headers.get("lnmp.expires_at").unwrap(), b"1627849260000");
     assert_eq!(headers.get("lnmp.source").unwrap(), b"test-source");
     assert_eq!(headers.get("lnmp.trace_id").unwrap(), b"test-trace-id");
     assert_eq!(headers.get("lnmp.sequence").unwrap(), b"12345");
+    assert_eq!(headers.get("lnmp.causation_id").unwrap(), b"cmd-7");
+    assert_eq!(headers.get("lnmp.partition_key").unwrap(), b"user-100");
+    assert_eq!(
+        headers.get("lnmp.content_type").unwrap(),
+        b"application/lnmp-binary"
+    );
+    assert_eq!(headers.get("lnmp.schema_version").unwrap(), b"1.4.0");
     assert_eq!(headers.get("lnmp.label.env").unwrap(), b"prod");
 
     let meta = kafka::kafka_headers_to_envelope_metadata(&headers).unwrap();
+    assert_eq!(meta.partition_key, env.metadata.partition_key);
     assert_eq!(meta.timestamp, env.metadata.timestamp);
+    assert_eq!(meta.expires_at, env.metadata.expires_at);
     assert_eq!(meta.source, env.metadata.source);
     assert_eq!(meta.trace_id, env.metadata.trace_id);
     assert_eq!(meta.sequence, env.metadata.sequence);
+    assert_eq!(meta.correlation_id, env.metadata.correlation_id);
+    assert_eq!(meta.causation_id, env.metadata.causation_id);
+    assert_eq!(meta.content_type, env.metadata.content_type);
+    assert_eq!(meta.schema_version, env.metadata.schema_version);
     assert_eq!(meta.labels.get("env"), env.metadata.labels.get("env"));
 }
 
+#[cfg(feature = "kafka")]
+#[test]
+fn test_kafka_partition_key() {
+    let mut env = create_test_envelope();
+    assert_eq!(
+        kafka::envelope_to_kafka_key(&env),
+        Some(b"user-100".to_vec())
+    );
+
+    env.record.add_field(LnmpField {
+        fid: 12,
+        value: LnmpValue::String("u-7".to_string()),
+    });
+    env.record.add_field(LnmpField {
+        fid: 20,
+        value: LnmpValue::StringArray(vec!["a".to_string()]),
+    });
+    assert_eq!(
+        kafka::partition_key_from_fid(&env.record, 1).as_deref(),
+        Some("100")
+    );
+    assert_eq!(
+        kafka::partition_key_from_fid(&env.record, 12).as_deref(),
+        Some("u-7")
+    );
+    assert_eq!(kafka::partition_key_from_fid(&env.record, 20), None);
+    assert_eq!(kafka::partition_key_from_fid(&env.record, 99), None);
+
+    env.metadata.partition_key = None;
+    assert_eq!(kafka::envelope_to_kafka_key(&env), None);
+}
+
+#[cfg(feature = "kafka-rdkafka")]
+#[test]
+fn test_rdkafka_serializer_round_trip() {
+    use lnmp_net::MessageKind;
+    use lnmp_transport::kafka::rdkafka::{LnmpDeserializer, LnmpSerializer};
+    use lnmp_transport::{SerializerConfig, WireFormat};
+    use rdkafka::message::{Headers, OwnedMessage, Timestamp};
+
+    let env = create_test_envelope();
+    let serializer = LnmpSerializer::new()
+        .with_config(SerializerConfig::new().with_format(MessageKind::Alert, WireFormat::Text));
+    let record = serializer.serialize(&env, MessageKind::Alert).unwrap();
+    assert_eq!(record.payload, b"F1=100");
+    assert_eq!(record.key.as_deref(), Some(&b"user-100"[..]));
+    assert!(record
+        .headers
+        .iter()
+        .any(|h| h.key == "lnmp.content_type" && h.value == Some(&b"application/lnmp-text"[..])));
+    let future = record.future_record("events");
+    assert_eq!(future.key, Some(&b"user-100"[..]));
+
+    let message = OwnedMessage::new(
+        Some(record.payload.clone()),
+        record.key.clone(),
+        "events".to_string(),
+        Timestamp::CreateTime(1),
+        0,
+        7,
+        Some(record.headers.clone()),
+    );
+    let decoded = LnmpDeserializer::new().deserialize(&message).unwrap();
+    assert_eq!(decoded.record, env.record);
+    assert_eq!(decoded.metadata.timestamp, env.metadata.timestamp);
+    assert_eq!(decoded.metadata.partition_key, env.metadata.partition_key);
+    assert_eq!(decoded.metadata.trace_id, env.metadata.trace_id);
+
+    // Binary-only consumers reject text values
+    let binary_only = LnmpDeserializer::new().with_accepted_formats(&[WireFormat::Binary]);
+    assert!(binary_only.deserialize(&message).is_err());
+
+    // Bare messages: key and Kafka timestamp fill in the metadata
+    let bare = OwnedMessage::new(
+        Some(
+            serializer
+                .serialize(&env, MessageKind::Event)
+                .unwrap()
+                .payload,
+        ),
+        Some(b"user-7".to_vec()),
+        "events".to_string(),
+        Timestamp::CreateTime(1700000000000),
+        0,
+        8,
+        None,
+    );
+    let decoded = binary_only.deserialize(&bare).unwrap();
+    assert_eq!(decoded.record, env.record);
+    assert_eq!(decoded.metadata.partition_key.as_deref(), Some("user-7"));
+    assert_eq!(decoded.metadata.timestamp, Some(1700000000000));
+}
+
 #[cfg(feature = "grpc")]
 #[test]
 fn test_grpc_mapping() {
@@ -114,19 +566,94 @@ fn test_grpc_mapping() {
     let metadata = grpc::envelope_to_metadata(&env).unwrap();
 
     assert_eq!(metadata.get("lnmp-timestamp").unwrap(), "1627849200000");
+    assert_eq!(metadata.get("lnmp-expires-at").unwrap(), "1627849260000");
     assert_eq!(metadata.get("lnmp-source").unwrap(), "test-source");
     assert_eq!(metadata.get("lnmp-trace-id").unwrap(), "test-trace-id");
     assert_eq!(metadata.get("lnmp-sequence").unwrap(), "12345");
+    assert_eq!(metadata.get("lnmp-causation-id").unwrap(), "cmd-7");
+    assert_eq!(
+        metadata.get("lnmp-content-type").unwrap(),
+        "application/lnmp-binary"
+    );
+    assert_eq!(metadata.get("lnmp-schema-version").unwrap(), "1.4.0");
     assert_eq!(metadata.get("lnmp-label-env").unwrap(), "prod");
 
     let meta = grpc::metadata_to_envelope_metadata(&metadata).unwrap();
     assert_eq!(meta.timestamp, env.metadata.timestamp);
+    assert_eq!(meta.expires_at, env.metadata.expires_at);
     assert_eq!(meta.source, env.metadata.source);
     assert_eq!(meta.trace_id, env.metadata.trace_id);
     assert_eq!(meta.sequence, env.metadata.sequence);
+    assert_eq!(meta.correlation_id, env.metadata.correlation_id);
+    assert_eq!(meta.causation_id, env.metadata.causation_id);
+    assert_eq!(meta.content_type, env.metadata.content_type);
+    assert_eq!(meta.schema_version, env.metadata.schema_version);
     assert_eq!(meta.labels.get("env"), env.metadata.labels.get("env"));
 }
 
+#[cfg(feature = "tonic")]
+#[test]
+fn test_grpc_proto_round_trip() {
+    use lnmp_transport::grpc::proto;
+    use prost::Message;
+
+    let mut env = create_test_envelope();
+    env.metadata.signature = Some(lnmp_envelope::EnvelopeSignature {
+        algorithm: "ed25519".to_string(),
+        key_id: "k1".to_string(),
+        signature: vec![1, 2, 3],
+    });
+    let message = grpc::envelope_to_proto(&env).unwrap();
+    assert_eq!(
+        message.record.as_ref().unwrap().content_type,
+        "application/lnmp-binary"
+    );
+
+    let bytes = message.encode_to_vec();
+    let decoded = proto::Envelope::decode(bytes.as_slice()).unwrap();
+    assert_eq!(decoded, message);
+    assert_eq!(grpc::proto_to_envelope(&decoded).unwrap(), env);
+
+    // Text frames decode by content type
+    let text = proto::Envelope {
+        record: Some(proto::LnmpFrame {
+            frame: b"F1=100".to_vec(),
+            content_type: "application/lnmp-text".to_string(),
+        }),
+        ..Default::default()
+    };
+    let text_env = grpc::proto_to_envelope(&text).unwrap();
+    assert_eq!(text_env.record, env.record);
+    assert_eq!(
+        text_env.metadata.content_type.as_deref(),
+        Some("application/lnmp-text")
+    );
+
+    assert!(grpc::proto_to_envelope(&proto::Envelope::default()).is_err());
+}
+
+#[cfg(feature = "tonic")]
+#[tokio::test]
+async fn test_grpc_lnmp_codec_round_trip() {
+    use lnmp_transport::grpc::LnmpCodec;
+    use tonic::codec::{Codec, EncodeBody, Streaming};
+
+    let env = create_test_envelope();
+    let mut second = LnmpRecord::new();
+    second.add_field(LnmpField {
+        fid: 7,
+        value: LnmpValue::String("overheating".to_string()),
+    });
+
+    let mut codec = LnmpCodec::new();
+    let records = vec![Ok(env.record.clone()), Ok(second.clone())];
+    let body = EncodeBody::new_client(codec.encoder(), tokio_stream::iter(records), None, None);
+    let mut stream = Streaming::new_request(codec.decoder(), body, None, None);
+    assert_eq!(stream.message().await.unwrap(), Some(env.record));
+    assert_eq!(stream.message().await.unwrap(), Some(second));
+    assert_eq!(stream.message().await.unwrap(), None);
+}
+
 #[cfg(feature = "nats")]
 #[test]
 fn test_nats_mapping() {
@@ -134,15 +661,393 @@ fn test_nats_mapping() {
     let headers = nats::envelope_to_nats_headers(&env).unwrap();
 
     assert_eq!(headers.get("lnmp-timestamp").unwrap(), "1627849200000");
+    assert_eq!(headers.get("lnmp-expires-at").unwrap(), "1627849260000");
     assert_eq!(headers.get("lnmp-source").unwrap(), "test-source");
     assert_eq!(headers.get("lnmp-trace-id").unwrap(), "test-trace-id");
     assert_eq!(headers.get("lnmp-sequence").unwrap(), "12345");
+    assert_eq!(headers.get("lnmp-correlation-id").unwrap(), "saga-1");
+    assert_eq!(
+        headers.get("lnmp-content-type").unwrap(),
+        "application/lnmp-binary"
+    );
+    assert_eq!(headers.get("lnmp-schema-version").unwrap(), "1.4.0");
     assert_eq!(headers.get("lnmp-label-env").unwrap(), "prod");
 
     let meta = nats::nats_headers_to_envelope_metadata(&headers).unwrap();
     assert_eq!(meta.timestamp, env.metadata.timestamp);
+    assert_eq!(meta.expires_at, env.metadata.expires_at);
     assert_eq!(meta.source, env.metadata.source);
     assert_eq!(meta.trace_id, env.metadata.trace_id);
     assert_eq!(meta.sequence, env.metadata.sequence);
+    assert_eq!(meta.correlation_id, env.metadata.correlation_id);
+    assert_eq!(meta.causation_id, env.metadata.causation_id);
+    assert_eq!(meta.content_type, env.metadata.content_type);
+    assert_eq!(meta.schema_version, env.metadata.schema_version);
     assert_eq!(meta.labels.get("env"), env.metadata.labels.get("env"));
 }
+
+#[cfg(feature = "nats")]
+#[test]
+fn test_nats_jetstream_helpers() {
+    use lnmp_net::{MessageKind, RoutingDecision};
+    use lnmp_transport::nats::AckKind;
+    use lnmp_transport::SerializerConfig;
+    use std::time::Duration;
+
+    assert_eq!(
+        nats::subject(MessageKind::Event, Some("robot.arm *1")),
+        "lnmp.event.robot_arm__1"
+    );
+    assert_eq!(
+        nats::subject(MessageKind::Alert, None),
+        "lnmp.alert.unknown"
+    );
+    assert_eq!(nats::subject_filter(None, None), "lnmp.>");
+    assert_eq!(
+        nats::subject_filter(Some(MessageKind::Command), None),
+        "lnmp.command.*"
+    );
+    assert_eq!(
+        nats::subject_filter(None, Some("sensor-7")),
+        "lnmp.*.sensor-7"
+    );
+
+    let env = create_test_envelope();
+    let msg =
+        nats::envelope_to_jetstream_message(&env, MessageKind::Event, &SerializerConfig::new())
+            .unwrap();
+    assert_eq!(msg.subject, "lnmp.event.test-source");
+    let id = &msg.headers[nats::HEADER_MSG_ID];
+    assert_eq!(id.len(), 64);
+
+    // IDs follow the fields, not their order, so JetStream deduplicates republished records
+    let mut extended = env.clone();
+    extended.record.add_field(LnmpField {
+        fid: 2,
+        value: LnmpValue::Bool(true),
+    });
+    let mut other = LnmpRecord::new();
+    other.add_field(LnmpField {
+        fid: 2,
+        value: LnmpValue::Bool(true),
+    });
+    other.add_field(LnmpField {
+        fid: 1,
+        value: LnmpValue::Int(100),
+    });
+    assert_ne!(&nats::message_id(&extended.record).unwrap(), id);
+    assert_eq!(
+        nats::message_id(&extended.record).unwrap(),
+        nats::message_id(&other).unwrap()
+    );
+
+    let decoded = nats::nats_message_to_envelope(&msg.payload, &msg.headers).unwrap();
+    assert_eq!(decoded.record, env.record);
+
+    assert_eq!(
+        AckKind::for_decision(RoutingDecision::SendToLLM),
+        AckKind::Ack
+    );
+    assert_eq!(AckKind::for_decision(RoutingDecision::Drop), AckKind::Term);
+    assert_eq!(AckKind::Ack.payload(), b"+ACK");
+    assert_eq!(AckKind::Nak { delay: None }.payload(), b"-NAK");
+    assert_eq!(
+        AckKind::Nak {
+            delay: Some(Duration::from_secs(1))
+        }
+        .payload(),
+        b"-NAK {\"delay\": 1000000000}"
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_body_for_kind() {
+    use lnmp_net::MessageKind;
+    use lnmp_transport::{SerializerConfig, WireFormat};
+
+    let env = create_test_envelope();
+    let config = SerializerConfig::new().with_format(MessageKind::Alert, WireFormat::Text);
+
+    let (body, content_type) =
+        http::record_to_http_body_for_kind(&env.record, MessageKind::Alert, &config).unwrap();
+    assert_eq!(content_type, http::CONTENT_TYPE_LNMP_TEXT);
+    assert_eq!(body, b"F1=100");
+    assert_eq!(
+        http::http_body_to_record(&body, content_type).unwrap(),
+        env.record
+    );
+
+    let (_, content_type) =
+        http::record_to_http_body_for_kind(&env.record, MessageKind::Event, &config).unwrap();
+    assert_eq!(content_type, http::CONTENT_TYPE_LNMP_BINARY);
+}
+
+#[cfg(feature = "kafka")]
+#[test]
+fn test_kafka_record_for_kind() {
+    use lnmp_net::MessageKind;
+    use lnmp_transport::{SerializerConfig, WireFormat};
+
+    let env = create_test_envelope();
+    let config = SerializerConfig::new().with_format(MessageKind::Query, WireFormat::ShortForm);
+
+    let (value, headers) =
+        kafka::envelope_to_kafka_record_for_kind(&env, MessageKind::Query, &config).unwrap();
+    assert_eq!(value, b"1=100");
+    assert_eq!(
+        headers.get(kafka::HEADER_CONTENT_TYPE).unwrap(),
+        b"application/lnmp-shortform"
+    );
+
+    let decoded = kafka::kafka_record_to_envelope(&value, &headers).unwrap();
+    assert_eq!(decoded.record, env.record);
+    assert_eq!(decoded.metadata.source, env.metadata.source);
+    // The announced body format wins over the envelope's stale content type
+    assert_eq!(
+        decoded.metadata.content_type.as_deref(),
+        Some("application/lnmp-shortform")
+    );
+}
+
+#[cfg(feature = "nats")]
+#[test]
+fn test_nats_message_for_kind() {
+    use lnmp_net::MessageKind;
+    use lnmp_transport::{SerializerConfig, WireFormat};
+
+    let env = create_test_envelope();
+    let config = SerializerConfig::new().with_default_format(WireFormat::Text);
+
+    let (payload, headers) =
+        nats::envelope_to_nats_message_for_kind(&env, MessageKind::State, &config).unwrap();
+    assert_eq!(
+        headers.get(nats::HEADER_CONTENT_TYPE).unwrap(),
+        "application/lnmp-text"
+    );
+
+    let decoded = nats::nats_message_to_envelope(&payload, &headers).unwrap();
+    assert_eq!(decoded.record, env.record);
+}
+
+#[cfg(feature = "websocket")]
+#[test]
+fn test_websocket_round_trip() {
+    use lnmp_codec::binary::{NegotiationMessage, StreamingEncoder};
+    use lnmp_transport::websocket::{self, FrameType, Message, WsDecoder, WsFrame};
+
+    let mut env = create_test_envelope();
+    env.metadata.content_type = None;
+    let messages = websocket::envelope_to_ws_messages(&env).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(FrameType::of(&messages[0]), Some(FrameType::Preamble));
+    assert_eq!(FrameType::of(&messages[1]), Some(FrameType::BinaryRecord));
+
+    let mut decoder = WsDecoder::new();
+    assert!(decoder.decode(&messages[0]).unwrap().is_none());
+    assert!(decoder.pending_preamble().is_some());
+    assert!(decoder
+        .decode(&Message::Ping(vec![1].into()))
+        .unwrap()
+        .is_none());
+    match decoder.decode(&messages[1]).unwrap() {
+        Some(WsFrame::Envelope(decoded)) => assert_eq!(*decoded, env),
+        other => panic!("expected envelope, got {:?}", other),
+    }
+
+    // The preamble only applies to the record right after it
+    let bare = websocket::record_to_ws_text(&env.record);
+    assert_eq!(FrameType::of(&bare), Some(FrameType::TextRecord));
+    match decoder.decode(&bare).unwrap() {
+        Some(WsFrame::Envelope(decoded)) => {
+            assert_eq!(decoded.record, env.record);
+            assert!(decoded.metadata.is_empty());
+        }
+        other => panic!("expected envelope, got {:?}", other),
+    }
+
+    let mut streaming = StreamingEncoder::new();
+    let begin = streaming.begin_stream().unwrap();
+    match decoder
+        .decode(&websocket::stream_chunk_to_ws_message(&begin))
+        .unwrap()
+    {
+        Some(WsFrame::StreamChunk(frame)) => assert_eq!(frame, begin),
+        other => panic!("expected stream chunk, got {:?}", other),
+    }
+
+    let ready = NegotiationMessage::Ready { session_id: 7 };
+    assert_eq!(
+        decoder
+            .decode(&websocket::negotiation_to_ws_message(&ready))
+            .unwrap(),
+        Some(WsFrame::Negotiation(ready))
+    );
+
+    assert!(decoder.decode(&Message::binary(vec![0x7F, 0x00])).is_err());
+}
+
+#[cfg(feature = "websocket")]
+#[test]
+fn test_websocket_text_formats_for_kind() {
+    use lnmp_net::MessageKind;
+    use lnmp_transport::websocket::{self, FrameType, WsDecoder, WsFrame};
+    use lnmp_transport::{SerializerConfig, WireFormat};
+
+    let env = create_test_envelope();
+    let config = SerializerConfig::new().with_format(MessageKind::Query, WireFormat::ShortForm);
+
+    let messages =
+        websocket::envelope_to_ws_messages_for_kind(&env, MessageKind::Query, &config).unwrap();
+    assert_eq!(FrameType::of(&messages[1]), Some(FrameType::TextRecord));
+    assert_eq!(messages[1].to_text().unwrap(), "1=100");
+
+    let mut decoder = WsDecoder::new();
+    let mut frames = messages
+        .iter()
+        .filter_map(|message| decoder.decode(message).unwrap());
+    match frames.next() {
+        Some(WsFrame::Envelope(decoded)) => {
+            assert_eq!(decoded.record, env.record);
+            assert_eq!(decoded.metadata.source, env.metadata.source);
+            assert_eq!(
+                decoded.metadata.content_type.as_deref(),
+                Some("application/lnmp-shortform")
+            );
+        }
+        other => panic!("expected envelope, got {:?}", other),
+    }
+
+    // Binary kinds go out as tagged binary records
+    let messages =
+        websocket::envelope_to_ws_messages_for_kind(&env, MessageKind::Event, &config).unwrap();
+    assert_eq!(FrameType::of(&messages[1]), Some(FrameType::BinaryRecord));
+}
+
+#[cfg(feature = "amqp")]
+#[test]
+fn test_amqp_mapping() {
+    use lnmp_net::MessageKind;
+
+    let env = create_test_envelope();
+    let message = amqp::envelope_to_amqp_message(&env, MessageKind::Alert).unwrap();
+    assert_eq!(message.routing_key, "lnmp.alert.test-source");
+
+    let props = &message.properties;
+    assert_eq!(
+        props.content_type.as_deref(),
+        Some("application/lnmp-binary")
+    );
+    assert_eq!(props.timestamp, Some(1627849200));
+    assert_eq!(props.expiration.as_deref(), Some("60000"));
+    assert_eq!(props.app_id.as_deref(), Some("test-source"));
+    assert_eq!(props.correlation_id.as_deref(), Some("saga-1"));
+    assert_eq!(props.kind.as_deref(), Some("Alert"));
+    assert_eq!(
+        props.headers.get(amqp::HEADER_TIMESTAMP).unwrap(),
+        "1627849200000"
+    );
+    assert_eq!(props.headers.get("lnmp-label-env").unwrap(), "prod");
+
+    let decoded = amqp::amqp_message_to_envelope(&message.body, props).unwrap();
+    assert_eq!(decoded, env);
+}
+
+#[cfg(feature = "amqp")]
+#[test]
+fn test_amqp_net_message_round_trip() {
+    use lnmp_net::{MessageKind, NetMessage};
+    use lnmp_transport::{SerializerConfig, WireFormat};
+
+    let mut env = create_test_envelope();
+    env.metadata.source = Some("plant.line*1".to_string());
+    env.metadata.expires_at = None;
+    let mut msg = NetMessage::with_qos(env, MessageKind::Command, 255, 1500);
+    msg.class = Some("safety".to_string());
+    let config = SerializerConfig::new().with_format(MessageKind::Command, WireFormat::Text);
+
+    let message = amqp::net_message_to_amqp(&msg, &config).unwrap();
+    assert_eq!(message.routing_key, "lnmp.command.plant_line_1");
+    assert_eq!(message.body, b"F1=100");
+    assert_eq!(message.properties.priority, Some(amqp::MAX_AMQP_PRIORITY));
+    assert_eq!(message.properties.expiration.as_deref(), Some("1500"));
+
+    let decoded = amqp::amqp_to_net_message(&message.body, &message.properties).unwrap();
+    assert_eq!(decoded.kind, MessageKind::Command);
+    assert_eq!(decoded.priority, 255);
+    assert_eq!(decoded.ttl_ms, 1500);
+    assert_eq!(decoded.class.as_deref(), Some("safety"));
+    assert_eq!(decoded.envelope.record, msg.envelope.record);
+    assert_eq!(
+        decoded.envelope.metadata.content_type.as_deref(),
+        Some("application/lnmp-text")
+    );
+
+    assert_eq!(amqp::amqp_priority(0), 0);
+    assert_eq!(amqp::amqp_priority(128), 4);
+    assert_eq!(
+        amqp::routing_key(MessageKind::Event, None),
+        "lnmp.event.unknown"
+    );
+}
+
+#[cfg(feature = "sse")]
+#[test]
+fn test_sse_event_framing() {
+    use sse::SseEncoding;
+
+    let mut env = create_test_envelope();
+    env.record.add_field(LnmpField {
+        fid: 7,
+        value: LnmpValue::Bool(true),
+    });
+
+    let event = sse::envelope_to_sse_event(&env, SseEncoding::Text).unwrap();
+    assert_eq!(
+        event.to_string(),
+        "event: lnmp-record\nid: 12345\ndata: F1=100\ndata: F7=1\n\n"
+    );
+    assert_eq!(event.sequence(), Some(12345));
+    assert_eq!(
+        sse::sse_event_to_record(&event).unwrap(),
+        Some(env.record.clone())
+    );
+
+    let binary = sse::envelope_to_sse_event(&env, SseEncoding::Base64Binary).unwrap();
+    assert!(binary.data.starts_with(sse::BASE64_DATA_PREFIX));
+    assert!(!binary.data.contains('\n'));
+    assert_eq!(sse::sse_event_to_record(&binary).unwrap(), Some(env.record));
+
+    let heartbeat = sse::SseEvent::new("heartbeat", "");
+    assert_eq!(sse::sse_event_to_record(&heartbeat).unwrap(), None);
+}
+
+#[cfg(feature = "sse")]
+#[test]
+fn test_sse_reassembly_across_chunks() {
+    let stream =
+        ": keep-alive\r\nretry: 3000\r\nevent: lnmp-record\r\nid: 1\r\ndata: F1=100\r\n\r\n\
+                  data: plain\r\rid: 2\nevent: lnmp-record\ndata: F1=1\ndata: F2=2\n\n\
+                  event: lnmp-record\n\n";
+
+    // Every split point yields the same events
+    for split in 0..stream.len() {
+        let mut reassembler = sse::SseReassembler::new();
+        let mut events = reassembler.feed(&stream.as_bytes()[..split]);
+        events.extend(reassembler.feed(&stream.as_bytes()[split..]));
+
+        assert_eq!(events.len(), 3, "split at {}", split);
+        assert_eq!(events[0].event, "lnmp-record");
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[0].retry, Some(3000));
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].data, "plain");
+        // The last event ID carries over
+        assert_eq!(events[1].id.as_deref(), Some("1"));
+        assert_eq!(events[2].data, "F1=1\nF2=2");
+        assert_eq!(reassembler.last_event_id(), Some("2"));
+
+        let record = sse::sse_event_to_record(&events[2]).unwrap().unwrap();
+        assert_eq!(record.fields().len(), 2);
+    }
+}
@@ -0,0 +1,287 @@
+#![cfg(feature = "negotiation")]
+
+//! Schema negotiation over real transports
+//!
+//! These tests verify that:
+//! - Client and server agree on a session over an in-memory stream
+//! - Version rejections make the client fall back to older capabilities
+//! - FID conflicts, silent peers and dropped connections fail with clear errors
+//! - The HTTP variant reaches the same agreement through request/response pairs
+
+use lnmp_codec::binary::{
+    Capabilities, ErrorCode, FeatureFlags, NegotiationError, SchemaNegotiator,
+};
+use lnmp_transport::negotiation::{ClientHandshake, HandshakeConfig, ServerHandshake};
+use lnmp_transport::TransportError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn mappings(entries: &[(u16, &str)]) -> HashMap<u16, String> {
+    entries
+        .iter()
+        .map(|(fid, name)| (*fid, name.to_string()))
+        .collect()
+}
+
+fn fast_config() -> HandshakeConfig {
+    HandshakeConfig::new()
+        .with_timeout(Duration::from_millis(200))
+        .with_retry_backoff(Duration::from_millis(1))
+}
+
+#[tokio::test]
+async fn test_stream_handshake_agrees_on_session() {
+    let (mut client_io, mut server_io) = tokio::io::duplex(1024);
+    let client = ClientHandshake::new(
+        SchemaNegotiator::v0_5().with_fid_mappings(mappings(&[(1, "id"), (2, "name")])),
+    );
+    let server = ServerHandshake::new(SchemaNegotiator::v0_5());
+
+    let (client_session, server_session) =
+        tokio::join!(client.run(&mut client_io), server.run(&mut server_io));
+    let client_session = client_session.unwrap();
+    let server_session = server_session.unwrap();
+
+    assert_eq!(client_session.session_id, server_session.session_id);
+    assert_eq!(client_session.agreed_features, FeatureFlags::v0_5_full());
+    assert_eq!(
+        client_session.agreed_features,
+        server_session.agreed_features
+    );
+    assert_eq!(
+        server_session.fid_mappings,
+        mappings(&[(1, "id"), (2, "name")])
+    );
+}
+
+#[tokio::test]
+async fn test_client_downgrades_after_version_rejection() {
+    let (mut client_io, mut server_io) = tokio::io::duplex(1024);
+    let client = ClientHandshake::new(SchemaNegotiator::v0_5())
+        .with_config(fast_config().with_fallback(Capabilities::v0_4()));
+    let server = ServerHandshake::new(SchemaNegotiator::v0_4());
+
+    let (client_session, server_session) =
+        tokio::join!(client.run(&mut client_io), server.run(&mut server_io));
+    let client_session = client_session.unwrap();
+
+    assert_eq!(
+        client_session.local_caps.version,
+        Capabilities::v0_4().version
+    );
+    assert_eq!(
+        client_session.agreed_features,
+        FeatureFlags::v0_4_compatible()
+    );
+    assert_eq!(
+        server_session.unwrap().agreed_features,
+        client_session.agreed_features
+    );
+}
+
+#[tokio::test]
+async fn test_version_rejection_without_fallback_fails_both_sides() {
+    let (mut client_io, mut server_io) = tokio::io::duplex(1024);
+    let client = ClientHandshake::new(SchemaNegotiator::v0_5()).with_config(fast_config());
+    let server = ServerHandshake::new(SchemaNegotiator::v0_4()).with_config(fast_config());
+
+    let (client_result, server_result) = tokio::join!(
+        async {
+            let result = client.run(&mut client_io).await;
+            drop(client_io);
+            result
+        },
+        server.run(&mut server_io)
+    );
+
+    assert!(matches!(
+        client_result,
+        Err(TransportError::NegotiationRejected {
+            code: ErrorCode::ProtocolVersionMismatch,
+            ..
+        })
+    ));
+    assert!(matches!(
+        server_result,
+        Err(TransportError::Negotiation(
+            NegotiationError::ProtocolVersionMismatch { .. }
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_fid_conflict_is_reported_to_client() {
+    let (mut client_io, mut server_io) = tokio::io::duplex(1024);
+    let client =
+        ClientHandshake::new(SchemaNegotiator::v0_5().with_fid_mappings(mappings(&[(7, "a")])));
+    let server =
+        ServerHandshake::new(SchemaNegotiator::v0_5().with_fid_mappings(mappings(&[(7, "b")])));
+
+    let (client_result, server_result) =
+        tokio::join!(client.run(&mut client_io), server.run(&mut server_io));
+
+    assert!(matches!(
+        client_result,
+        Err(TransportError::NegotiationRejected {
+            code: ErrorCode::FidConflict,
+            ..
+        })
+    ));
+    assert!(matches!(
+        server_result,
+        Err(TransportError::Negotiation(NegotiationError::FidConflict {
+            fid: 7,
+            ..
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn test_silent_peer_times_out() {
+    let (mut client_io, _server_io) = tokio::io::duplex(1024);
+    let client = ClientHandshake::new(SchemaNegotiator::v0_5())
+        .with_config(fast_config().with_timeout(Duration::from_millis(20)));
+
+    assert!(matches!(
+        client.run(&mut client_io).await,
+        Err(TransportError::Timeout(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_dropped_connection_is_retried() {
+    let attempts = AtomicUsize::new(0);
+    let client = ClientHandshake::new(SchemaNegotiator::v0_5()).with_config(fast_config());
+    let server = ServerHandshake::new(SchemaNegotiator::v0_5());
+
+    let session = client
+        .run_with_retry(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            let server = server.clone();
+            async move {
+                let (client_io, mut server_io) = tokio::io::duplex(1024);
+                if attempt == 0 {
+                    // First connection drops before the server answers.
+                    drop(server_io);
+                } else {
+                    tokio::spawn(async move { server.run(&mut server_io).await });
+                }
+                Ok(client_io)
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(session.agreed_features, FeatureFlags::v0_5_full());
+}
+
+#[tokio::test]
+async fn test_retries_are_bounded() {
+    let attempts = AtomicUsize::new(0);
+    let client = ClientHandshake::new(SchemaNegotiator::v0_5())
+        .with_config(fast_config().with_max_retries(1));
+
+    let result = client
+        .run_with_retry(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async {
+                Err::<tokio::io::DuplexStream, _>(std::io::ErrorKind::ConnectionRefused.into())
+            }
+        })
+        .await;
+
+    assert!(matches!(result, Err(TransportError::Io(_))));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_retry_delay_is_capped() {
+    let config = HandshakeConfig::new()
+        .with_retry_backoff(Duration::from_millis(100))
+        .with_max_retry_backoff(Duration::from_secs(1));
+
+    assert_eq!(config.retry_delay(0), Duration::from_millis(100));
+    assert_eq!(config.retry_delay(3), Duration::from_millis(800));
+    assert_eq!(config.retry_delay(4), Duration::from_secs(1));
+    // 2^32 overflows u32 and Duration::MAX * 2 overflows Duration
+    assert_eq!(config.retry_delay(40), Duration::from_secs(1));
+    let huge = config.with_retry_backoff(Duration::MAX);
+    assert_eq!(huge.retry_delay(1), Duration::from_secs(1));
+}
+
+#[cfg(feature = "http")]
+mod http_variant {
+    use super::*;
+    use lnmp_transport::negotiation::{HttpNegotiationServer, CONTENT_TYPE_LNMP_NEGOTIATION};
+    use std::sync::Mutex;
+
+    fn uri() -> http::Uri {
+        "http://agent.local/lnmp/negotiate".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_handshake_downgrades_and_agrees() {
+        let server = Mutex::new(HttpNegotiationServer::new(SchemaNegotiator::v0_4()));
+        let client = ClientHandshake::new(SchemaNegotiator::v0_5())
+            .with_config(fast_config().with_fallback(Capabilities::v0_4()));
+
+        let session = client
+            .run_http(uri(), |request| {
+                assert_eq!(request.method(), http::Method::POST);
+                let response = server.lock().unwrap().handle(&request);
+                async move { Ok(response) }
+            })
+            .await
+            .unwrap();
+
+        let server = server.into_inner().unwrap();
+        let server_session = server.session().unwrap();
+        assert_eq!(server_session.session_id, session.session_id);
+        assert_eq!(session.agreed_features, FeatureFlags::v0_4_compatible());
+    }
+
+    #[tokio::test]
+    async fn test_http_server_rejects_wrong_content_type() {
+        let mut server = HttpNegotiationServer::new(SchemaNegotiator::v0_5());
+        let request = http::Request::post(uri())
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Vec::new())
+            .unwrap();
+        assert_eq!(
+            server.handle(&request).status(),
+            http::StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let request = http::Request::post(uri())
+            .header(http::header::CONTENT_TYPE, CONTENT_TYPE_LNMP_NEGOTIATION)
+            .body(vec![0x7F])
+            .unwrap();
+        assert_eq!(
+            server.handle(&request).status(),
+            http::StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_server_errors_are_retried() {
+        let calls = AtomicUsize::new(0);
+        let server = Mutex::new(HttpNegotiationServer::new(SchemaNegotiator::v0_5()));
+        let client = ClientHandshake::new(SchemaNegotiator::v0_5()).with_config(fast_config());
+
+        let session = client
+            .run_http(uri(), |request| {
+                let result = if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(std::io::ErrorKind::ConnectionReset.into())
+                } else {
+                    Ok(server.lock().unwrap().handle(&request))
+                };
+                async move { result }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(session.agreed_features, FeatureFlags::v0_5_full());
+    }
+}
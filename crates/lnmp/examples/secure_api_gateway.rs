@@ -51,6 +51,10 @@ fn main() {
         auto_escape_quotes: true,
         normalize_booleans: true,
         normalize_numbers: true,
+        normalize_locale_numbers: false,
+        truncation: Default::default(),
+        extract_payload: true,
+        rules: Default::default(),
     };
 
     println!("🛡️  Processing {} API requests:\n", requests.len());
@@ -0,0 +1,425 @@
+//! Opinionated agent-to-agent channel
+//!
+//! [`AgentChannel`] wires the LNMP crates together so an agent can exchange
+//! records without assembling the stack by hand:
+//!
+//! - **envelope**: every record is wrapped with a timestamp, the channel's
+//!   source id and a per-channel sequence number
+//! - **net**: messages carry their [`MessageKind`] and QoS (priority, TTL), and
+//!   received messages are passed through a [`Router`] ([`RoutingPolicy`] by
+//!   default); expired messages are dropped
+//! - **codec**: bodies are encoded in the [`WireFormat`] selected for the kind
+//!   by a [`SerializerConfig`] (binary by default)
+//! - **transport**: envelope and net metadata travel as key/value headers next
+//!   to the body, using the same header names as the Kafka bindings, over any
+//!   [`ChannelTransport`]
+//!
+//! ```
+//! use lnmp::channel::{AgentChannel, MemoryTransport};
+//! use lnmp::core::{LnmpField, LnmpRecord, LnmpValue};
+//! use lnmp::net::MessageKind;
+//!
+//! let (left, right) = MemoryTransport::pair();
+//! let mut planner = AgentChannel::new("planner", left);
+//! let mut executor = AgentChannel::new("executor", right);
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(42) });
+//! planner.send(record, MessageKind::Command).unwrap();
+//!
+//! let envelope = executor.recv().unwrap().unwrap();
+//! assert_eq!(envelope.metadata.source.as_deref(), Some("planner"));
+//! ```
+
+use lnmp_core::LnmpRecord;
+use lnmp_envelope::{EnvelopeBuilder, LnmpEnvelope};
+use lnmp_net::transport::kafka::{kafka_headers_to_net_meta, net_to_kafka_headers};
+use lnmp_net::{
+    MessageKind, NetError, NetMessage, NetMessageBuilder, Router, RoutingDecision, RoutingPolicy,
+};
+use lnmp_transport::kafka::{
+    envelope_to_kafka_record_for_kind, kafka_record_to_envelope, KafkaHeaders,
+};
+use lnmp_transport::{SerializerConfig, TransportError, WireFormat};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors raised by an [`AgentChannel`]
+#[derive(Debug, Error)]
+pub enum ChannelError {
+    /// Encoding, decoding or header mapping failed
+    #[error("Transport error: {0}")]
+    Transport(#[from] TransportError),
+    /// Net metadata was invalid
+    #[error("Net error: {0}")]
+    Net(#[from] NetError),
+    /// The peer is gone and no messages are pending
+    #[error("Channel closed")]
+    Closed,
+}
+
+/// Result type for channel operations
+pub type Result<T> = std::result::Result<T, ChannelError>;
+
+/// A message as carried by a [`ChannelTransport`]: headers plus encoded body
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireMessage {
+    /// Envelope, net and content-type headers
+    pub headers: KafkaHeaders,
+    /// Record encoded in the announced wire format
+    pub body: Vec<u8>,
+}
+
+/// Moves [`WireMessage`]s between agents
+///
+/// Implement this for a broker client, socket or queue to run an
+/// [`AgentChannel`] over it.
+pub trait ChannelTransport {
+    /// Sends one message
+    fn send(&mut self, message: WireMessage) -> Result<()>;
+
+    /// Returns the next pending message, or `None` if nothing is waiting
+    fn recv(&mut self) -> Result<Option<WireMessage>>;
+}
+
+type Queue = Arc<Mutex<VecDeque<WireMessage>>>;
+
+/// In-process transport connecting two channels, for tests and single-process setups
+#[derive(Debug)]
+pub struct MemoryTransport {
+    outbox: Queue,
+    inbox: Queue,
+}
+
+impl MemoryTransport {
+    /// Creates two connected ends; what one sends the other receives
+    pub fn pair() -> (Self, Self) {
+        let a: Queue = Arc::default();
+        let b: Queue = Arc::default();
+        (
+            Self {
+                outbox: a.clone(),
+                inbox: b.clone(),
+            },
+            Self {
+                outbox: b,
+                inbox: a,
+            },
+        )
+    }
+}
+
+impl ChannelTransport for MemoryTransport {
+    fn send(&mut self, message: WireMessage) -> Result<()> {
+        if Arc::strong_count(&self.outbox) == 1 {
+            return Err(ChannelError::Closed);
+        }
+        self.outbox
+            .lock()
+            .expect("memory transport lock poisoned")
+            .push_back(message);
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Option<WireMessage>> {
+        let message = self
+            .inbox
+            .lock()
+            .expect("memory transport lock poisoned")
+            .pop_front();
+        match message {
+            None if Arc::strong_count(&self.inbox) == 1 => Err(ChannelError::Closed),
+            message => Ok(message),
+        }
+    }
+}
+
+/// A received message together with the routing decision taken for it
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    /// The decoded message
+    pub message: NetMessage,
+    /// [`RoutingDecision::SendToLLM`] or [`RoutingDecision::ProcessLocally`]
+    pub decision: RoutingDecision,
+}
+
+/// Sends and receives LNMP records between agents over a [`ChannelTransport`]
+pub struct AgentChannel<T> {
+    transport: T,
+    source: String,
+    serializer: SerializerConfig,
+    routing: Box<dyn Router + Send + Sync>,
+    clock: fn() -> u64,
+    sequence: u64,
+    dropped: u64,
+}
+
+impl<T: ChannelTransport> AgentChannel<T> {
+    /// Creates a channel that stamps outgoing envelopes with `source`
+    ///
+    /// Bodies are binary-encoded and incoming messages are routed with
+    /// [`RoutingPolicy::default`].
+    pub fn new(source: impl Into<String>, transport: T) -> Self {
+        Self {
+            transport,
+            source: source.into(),
+            serializer: SerializerConfig::new(),
+            routing: Box::new(RoutingPolicy::default()),
+            clock: now_ms,
+            sequence: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Sets the per-kind body encoding
+    pub fn with_serializer(mut self, serializer: SerializerConfig) -> Self {
+        self.serializer = serializer;
+        self
+    }
+
+    /// Encodes every kind in `format`
+    pub fn with_wire_format(mut self, format: WireFormat) -> Self {
+        self.serializer = self.serializer.with_default_format(format);
+        self
+    }
+
+    /// Sets the policy applied to received messages
+    pub fn with_routing_policy(self, policy: RoutingPolicy) -> Self {
+        self.with_router(policy)
+    }
+
+    /// Routes received messages with a custom [`Router`]
+    pub fn with_router(mut self, router: impl Router + Send + Sync + 'static) -> Self {
+        self.routing = Box::new(router);
+        self
+    }
+
+    /// Replaces the wall clock (epoch milliseconds) used for timestamps and expiry
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sends `record` as a `kind` message with the kind's default QoS
+    pub fn send(&mut self, record: LnmpRecord, kind: MessageKind) -> Result<()> {
+        let envelope = self.envelope(record);
+        self.send_message(NetMessage::new(envelope, kind))
+    }
+
+    /// Sends `record` with an explicit priority and TTL
+    pub fn send_with_qos(
+        &mut self,
+        record: LnmpRecord,
+        kind: MessageKind,
+        priority: u8,
+        ttl_ms: u32,
+    ) -> Result<()> {
+        let envelope = self.envelope(record);
+        self.send_message(NetMessage::with_qos(envelope, kind, priority, ttl_ms))
+    }
+
+    /// Sends a fully built message as is
+    pub fn send_message(&mut self, message: NetMessage) -> Result<()> {
+        message.validate()?;
+        let (body, mut headers) =
+            envelope_to_kafka_record_for_kind(&message.envelope, message.kind, &self.serializer)?;
+        for (key, value) in net_to_kafka_headers(&message) {
+            headers.insert(key, value.into_bytes());
+        }
+        self.transport.send(WireMessage { headers, body })
+    }
+
+    /// Receives the next envelope, skipping messages the routing policy drops
+    pub fn recv(&mut self) -> Result<Option<LnmpEnvelope>> {
+        Ok(self
+            .recv_delivery()?
+            .map(|delivery| delivery.message.envelope))
+    }
+
+    /// Receives the next message with its routing decision, skipping dropped ones
+    pub fn recv_delivery(&mut self) -> Result<Option<Delivery>> {
+        while let Some(wire) = self.transport.recv()? {
+            let envelope = kafka_record_to_envelope(&wire.body, &wire.headers)?;
+            let net_headers: Vec<(String, String)> = wire
+                .headers
+                .iter()
+                .filter_map(|(key, value)| {
+                    std::str::from_utf8(value)
+                        .ok()
+                        .map(|value| (key.clone(), value.to_string()))
+                })
+                .collect();
+            let (kind, priority, ttl_ms, class) = kafka_headers_to_net_meta(&net_headers)?;
+            let mut builder = NetMessageBuilder::new(envelope, kind)
+                .priority(priority)
+                .ttl_ms(ttl_ms);
+            if let Some(class) = class {
+                builder = builder.class(class);
+            }
+            let message = builder.build();
+
+            match self.routing.route(&message, (self.clock)())? {
+                RoutingDecision::Drop => self.dropped += 1,
+                decision => return Ok(Some(Delivery { message, decision })),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Number of received messages dropped by the routing policy
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Source id stamped on outgoing envelopes
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Consumes the channel, returning the underlying transport
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    fn envelope(&mut self, record: LnmpRecord) -> LnmpEnvelope {
+        self.sequence += 1;
+        EnvelopeBuilder::new(record)
+            .timestamp((self.clock)())
+            .source(self.source.clone())
+            .sequence(self.sequence)
+            .build()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnmp_core::{LnmpField, LnmpValue};
+
+    fn record(value: i64) -> LnmpRecord {
+        let mut record = LnmpRecord::new();
+        record.add_field(LnmpField {
+            fid: 12,
+            value: LnmpValue::Int(value),
+        });
+        record
+    }
+
+    #[test]
+    fn test_round_trip_carries_envelope_and_qos() {
+        let (left, right) = MemoryTransport::pair();
+        let mut sender = AgentChannel::new("planner", left).with_clock(|| 1_000);
+        let mut receiver = AgentChannel::new("executor", right).with_clock(|| 1_500);
+
+        sender
+            .send_with_qos(record(1), MessageKind::Alert, 250, 10_000)
+            .unwrap();
+        sender.send(record(2), MessageKind::Query).unwrap();
+
+        let alert = receiver.recv_delivery().unwrap().unwrap();
+        assert_eq!(alert.message.kind, MessageKind::Alert);
+        assert_eq!(alert.message.priority, 250);
+        assert_eq!(alert.message.ttl_ms, 10_000);
+        assert_eq!(alert.decision, RoutingDecision::SendToLLM);
+        assert_eq!(alert.message.envelope.metadata.timestamp, Some(1_000));
+        assert_eq!(alert.message.envelope.metadata.sequence, Some(1));
+        assert_eq!(alert.message.envelope.record, record(1));
+
+        let query = receiver.recv().unwrap().unwrap();
+        assert_eq!(query.metadata.sequence, Some(2));
+        assert_eq!(query.record, record(2));
+        assert!(receiver.recv().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_expired_messages_are_dropped() {
+        let (left, right) = MemoryTransport::pair();
+        let mut sender = AgentChannel::new("sensor", left).with_clock(|| 1_000);
+        let mut receiver = AgentChannel::new("hub", right).with_clock(|| 60_000);
+
+        sender
+            .send_with_qos(record(1), MessageKind::Event, 100, 1_000)
+            .unwrap();
+        sender
+            .send_with_qos(record(2), MessageKind::Event, 100, 120_000)
+            .unwrap();
+
+        let envelope = receiver.recv().unwrap().unwrap();
+        assert_eq!(envelope.record, record(2));
+        assert_eq!(receiver.dropped(), 1);
+    }
+
+    #[test]
+    fn test_custom_router() {
+        struct DropEvents;
+
+        impl Router for DropEvents {
+            fn route(&self, msg: &NetMessage, _now_ms: u64) -> lnmp_net::Result<RoutingDecision> {
+                Ok(if msg.kind.is_event() {
+                    RoutingDecision::Drop
+                } else {
+                    RoutingDecision::ProcessLocally
+                })
+            }
+        }
+
+        let (left, right) = MemoryTransport::pair();
+        let mut sender = AgentChannel::new("sensor", left);
+        let mut receiver = AgentChannel::new("hub", right).with_router(DropEvents);
+
+        sender.send(record(1), MessageKind::Event).unwrap();
+        sender.send(record(2), MessageKind::Alert).unwrap();
+
+        let delivery = receiver.recv_delivery().unwrap().unwrap();
+        assert_eq!(delivery.message.envelope.record, record(2));
+        assert_eq!(delivery.decision, RoutingDecision::ProcessLocally);
+        assert_eq!(receiver.dropped(), 1);
+    }
+
+    #[test]
+    fn test_per_kind_wire_format() {
+        let (left, right) = MemoryTransport::pair();
+        let mut sender = AgentChannel::new("planner", left).with_serializer(
+            SerializerConfig::new().with_format(MessageKind::Command, WireFormat::Text),
+        );
+        sender.send(record(7), MessageKind::Command).unwrap();
+
+        let mut raw = right;
+        let wire = raw.recv().unwrap().unwrap();
+        assert_eq!(wire.body, b"F12=7");
+        assert_eq!(
+            wire.headers.get("lnmp.kind").map(Vec::as_slice),
+            Some(&b"Command"[..])
+        );
+    }
+
+    #[test]
+    fn test_closed_peer() {
+        let (left, right) = MemoryTransport::pair();
+        let mut sender = AgentChannel::new("planner", left);
+        let mut receiver = AgentChannel::new("executor", right);
+        sender.send(record(1), MessageKind::Event).unwrap();
+        drop(sender);
+
+        assert!(receiver.recv().unwrap().is_some());
+        assert!(matches!(receiver.recv(), Err(ChannelError::Closed)));
+        assert!(matches!(
+            receiver.send(record(2), MessageKind::Event),
+            Err(ChannelError::Closed)
+        ));
+    }
+}
@@ -26,6 +26,27 @@
 //! - **`transport`**: Transport protocol bindings (HTTP, Kafka, gRPC, NATS) with W3C Trace Context
 //! - **`net`**: Network behavior layer (MessageKind, QoS, ECO routing)
 //!
+//! ## Agent Channels
+//!
+//! [`channel::AgentChannel`] ties envelope building, net QoS and routing, codec
+//! selection and transport headers together behind `send(record, kind)` and
+//! `recv()`:
+//!
+//! ```rust
+//! use lnmp::channel::{AgentChannel, MemoryTransport};
+//! use lnmp::prelude::*;
+//!
+//! let (a, b) = MemoryTransport::pair();
+//! let (mut planner, mut executor) = (AgentChannel::new("planner", a), AgentChannel::new("executor", b));
+//!
+//! let mut record = LnmpRecord::new();
+//! record.add_field(LnmpField { fid: 12, value: LnmpValue::Int(42) });
+//! planner.send(record, MessageKind::Command).unwrap();
+//!
+//! let envelope = executor.recv().unwrap().unwrap();
+//! assert_eq!(envelope.record.get_field(12).unwrap().value, LnmpValue::Int(42));
+//! ```
+//!
 //! ## Usage Examples
 //!
 //! ```rust
@@ -79,6 +100,8 @@ pub use lnmp_sfe as sfe;
 pub use lnmp_spatial as spatial;
 pub use lnmp_transport as transport;
 
+pub mod channel;
+
 // Re-export commonly used types for convenience
 pub mod prelude {
     //! Prelude module with commonly used types and traits
@@ -97,6 +120,9 @@ pub mod prelude {
 
     // Network types
     pub use lnmp_net::{MessageKind, NetMessage, RoutingPolicy};
+
+    // Agent channel
+    pub use crate::channel::{AgentChannel, ChannelTransport, MemoryTransport};
 }
 
 // WASM bindings (only when wasm feature is enabled)
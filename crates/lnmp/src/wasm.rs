@@ -47,6 +47,58 @@ pub fn encode_lnmp(
     Ok(encoder.encode(&record))
 }
 
+fn json_mode(tagged: bool) -> codec::json::JsonMode {
+    if tagged {
+        codec::json::JsonMode::Tagged
+    } else {
+        codec::json::JsonMode::Plain
+    }
+}
+
+/// Parses LNMP text into a JSON object keyed by FID.
+///
+/// With `tagged`, values are emitted as `{"t":<type>,"v":<value>}` so Int/Float/Bool
+/// distinctions survive a round-trip through JSON.
+#[wasm_bindgen]
+pub fn parse_lnmp_json(text: &str, tagged: bool) -> Result<JsValue, JsValue> {
+    let mut parser = codec::Parser::new(text)
+        .map_err(|e| JsValue::from_str(&format!("Parser init error: {}", e)))?;
+
+    let record = parser
+        .parse_record()
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let json = codec::json::record_to_json(&record, json_mode(tagged))
+        .map_err(|e| JsValue::from_str(&format!("JSON conversion error: {}", e)))?;
+
+    json.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Encodes a JSON object keyed by FID (plain or tagged) into LNMP text.
+#[wasm_bindgen]
+pub fn encode_lnmp_json(
+    json_js: JsValue,
+    tagged: bool,
+    canonical: bool,
+    type_hints: bool,
+) -> Result<String, JsValue> {
+    let json: serde_json::Value = serde_wasm_bindgen::from_value(json_js)
+        .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+
+    let record = codec::json::record_from_json(&json, json_mode(tagged))
+        .map_err(|e| JsValue::from_str(&format!("JSON conversion error: {}", e)))?;
+
+    let config = crate::codec::EncoderConfig {
+        canonical,
+        include_type_hints: type_hints,
+        ..Default::default()
+    };
+
+    let encoder = crate::codec::Encoder::with_config(config);
+    Ok(encoder.encode(&record))
+}
+
 #[wasm_bindgen]
 pub fn compute_checksum(
     fid: FieldId,
@@ -260,6 +312,8 @@ pub fn quantize_embedding(vector: Vec<f32>, scheme_id: u8) -> Result<JsValue, Js
         1 => crate::quant::QuantScheme::QInt4,
         2 => crate::quant::QuantScheme::Binary,
         3 => crate::quant::QuantScheme::FP16Passthrough,
+        4 => crate::quant::QuantScheme::QInt2,
+        5 => crate::quant::QuantScheme::MixedPrecision,
         _ => return Err(JsValue::from_str("Invalid quantization scheme ID")),
     };
 
@@ -7,11 +7,19 @@
 //!   cargo run --bin lnmp-compliance-runner
 //!   cargo run --bin lnmp-compliance-runner -- --category structural
 //!   cargo run --bin lnmp-compliance-runner -- --verbose
+//!   cargo run --bin lnmp-compliance-runner -- --format json --implementation my-sdk
+//!   cargo run --bin lnmp-compliance-runner -- --mutate 10000 --seed 42
 
+mod mutator;
+mod report;
+mod robustness;
 mod runner;
 
+use report::{ConformanceReport, Implementation};
+use robustness::Corpus;
 use runner::{TestRunner, TestSuite};
 use std::env;
+use std::fs;
 use std::process;
 
 fn main() {
@@ -20,6 +28,11 @@ fn main() {
     // Parse command line arguments
     let mut category_filter: Option<String> = None;
     let mut verbose = false;
+    let mut json = false;
+    let mut implementation_id = String::from("lnmp-rs");
+    let mut report_path: Option<String> = None;
+    let mut mutate: Option<usize> = None;
+    let mut seed: u64 = 0;
 
     let mut i = 1;
     while i < args.len() {
@@ -38,6 +51,63 @@ fn main() {
                 verbose = true;
                 i += 1;
             }
+            "--format" | "-f" => match args.get(i + 1).map(String::as_str) {
+                Some("text") => {
+                    json = false;
+                    i += 2;
+                }
+                Some("json") => {
+                    json = true;
+                    i += 2;
+                }
+                _ => {
+                    eprintln!("Error: --format requires 'text' or 'json'");
+                    print_usage();
+                    process::exit(1);
+                }
+            },
+            "--implementation" => {
+                if i + 1 < args.len() {
+                    implementation_id = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --implementation requires a value");
+                    print_usage();
+                    process::exit(1);
+                }
+            }
+            "--report" => {
+                if i + 1 < args.len() {
+                    report_path = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --report requires a path");
+                    print_usage();
+                    process::exit(1);
+                }
+            }
+            "--mutate" => match args.get(i + 1).and_then(|v| v.parse().ok()) {
+                Some(iterations) => {
+                    mutate = Some(iterations);
+                    i += 2;
+                }
+                None => {
+                    eprintln!("Error: --mutate requires an iteration count");
+                    print_usage();
+                    process::exit(1);
+                }
+            },
+            "--seed" => match args.get(i + 1).and_then(|v| v.parse().ok()) {
+                Some(value) => {
+                    seed = value;
+                    i += 2;
+                }
+                None => {
+                    eprintln!("Error: --seed requires an unsigned integer");
+                    print_usage();
+                    process::exit(1);
+                }
+            },
             "--help" | "-h" => {
                 print_usage();
                 process::exit(0);
@@ -63,25 +133,53 @@ fn main() {
         }
     };
 
-    println!("LNMP v{} Compliance Test Runner", suite.version);
-    println!();
+    if let Some(iterations) = mutate {
+        run_mutation(&suite, seed, iterations, json);
+    }
+
+    if !json {
+        println!("LNMP v{} Compliance Test Runner", suite.version);
+        println!();
+    }
 
     // Create runner and execute tests
     let mut runner = TestRunner::new(suite);
 
     if let Some(category) = category_filter {
-        println!("Running tests in category: {}", category);
+        if !json {
+            println!("Running tests in category: {}", category);
+        }
         run_category(&mut runner, &category);
     } else {
-        println!("Running all tests...");
+        if !json {
+            println!("Running all tests...");
+        }
         runner.run_all();
     }
 
     // Print results
-    if verbose {
-        runner.print_detailed();
-    } else {
-        runner.print_summary();
+    if json || report_path.is_some() {
+        let implementation = Implementation {
+            id: implementation_id,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        let report = ConformanceReport::from_runner(&runner, implementation).to_json();
+        if let Some(path) = &report_path {
+            if let Err(e) = fs::write(path, format!("{}\n", report)) {
+                eprintln!("Error: Failed to write report to '{}': {}", path, e);
+                process::exit(1);
+            }
+        }
+        if json {
+            println!("{}", report);
+        }
+    }
+    if !json {
+        if verbose {
+            runner.print_detailed();
+        } else {
+            runner.print_summary();
+        }
     }
 
     // Exit with appropriate code
@@ -91,6 +189,23 @@ fn main() {
     }
 }
 
+fn run_mutation(suite: &TestSuite, seed: u64, iterations: usize, json: bool) -> ! {
+    let corpus = Corpus::from_suite(suite);
+
+    // Panics are caught and reported; keep the default hook from printing each one.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let report = robustness::run(&corpus, seed, iterations);
+    std::panic::set_hook(hook);
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        report.print_summary();
+    }
+    process::exit(if report.panic_count() > 0 { 1 } else { 0 });
+}
+
 fn run_category(runner: &mut TestRunner, category: &str) {
     let tests: Vec<_> = match category {
         "structural" => runner.suite.structural_tests.iter().collect(),
@@ -120,10 +235,20 @@ fn print_usage() {
     println!("  -c, --category <CATEGORY>  Run only tests in the specified category");
     println!("                             (structural, semantic, error-handling, round-trip)");
     println!("  -v, --verbose              Print detailed test results");
+    println!("  -f, --format <FORMAT>      Output format: text (default) or json");
+    println!("      --implementation <ID>  Implementation id recorded in the report");
+    println!("                             (default: lnmp-rs)");
+    println!("      --report <PATH>        Also write the JSON conformance report to PATH");
+    println!("      --mutate <N>           Instead of the suite, feed N seeded mutations of");
+    println!("                             its valid records to the parser, sanitizer and");
+    println!("                             binary decoder and report how each responded");
+    println!("      --seed <SEED>          Seed for --mutate (default: 0)");
     println!("  -h, --help                 Print this help message");
     println!();
     println!("Examples:");
     println!("  lnmp-compliance-runner");
     println!("  lnmp-compliance-runner --category structural");
     println!("  lnmp-compliance-runner --verbose");
+    println!("  lnmp-compliance-runner --format json --implementation my-sdk");
+    println!("  lnmp-compliance-runner --mutate 10000 --seed 42");
 }
@@ -3,8 +3,13 @@
 //! This module provides integration tests that validate the Rust LNMP
 //! implementation against the language-agnostic compliance test suite.
 
+mod mutator;
+mod report;
+mod robustness;
 mod runner;
 
+#[allow(unused_imports)]
+use report::{ConformanceReport, Implementation};
 #[allow(unused_imports)]
 use runner::{TestRunner, TestSuite};
 
@@ -117,3 +122,95 @@ fn run_round_trip_tests() {
         }
     }
 }
+
+#[test]
+fn conformance_report_summarizes_results() {
+    let manifest_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let test_file = manifest_dir.parent().unwrap().join("test-cases.yaml");
+
+    let suite = TestSuite::load_from_file(&test_file)
+        .unwrap_or_else(|e| panic!("Failed to load test suite from {:?}: {}", test_file, e));
+
+    let mut runner = TestRunner::new(suite);
+    runner.run_all();
+
+    let implementation = Implementation {
+        id: "lnmp-rs".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    let report = ConformanceReport::from_runner(&runner, implementation);
+
+    assert!(report.conformant);
+    assert!(report.failures.is_empty());
+    assert!(report.requirements_failed.is_empty());
+    assert!(!report.requirements_passed.is_empty());
+    assert_eq!(report.spec_version, runner.suite.version);
+    assert_eq!(report.totals.total, runner.results().len());
+    assert_eq!(
+        report
+            .categories
+            .iter()
+            .map(|category| category.counts.total)
+            .sum::<usize>(),
+        report.totals.total
+    );
+    assert_eq!(report.categories_passed.len(), report.categories.len());
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["report_version"], 1);
+    assert_eq!(json["implementation"]["id"], "lnmp-rs");
+    assert!(json["categories"][0]["total"].is_number());
+}
+
+#[test]
+fn mutator_is_deterministic_per_seed() {
+    use mutator::{MutationKind, Mutator};
+
+    let record = "F1=42;F2=\"hello\";F3=1";
+    let run = |seed| {
+        let mut mutator = Mutator::new(seed);
+        (0..64)
+            .map(|_| mutator.mutate_text(record))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+
+    let mut mutator = Mutator::new(1);
+    for kind in MutationKind::TEXT {
+        let mutated = mutator.mutate_text_with(*kind, record);
+        assert_ne!(mutated, record, "{} left the record unchanged", kind.name());
+    }
+    let frame = [0x04, 0x00, 0x01, 0x01, 0x00, 0x00, 0x2A];
+    for kind in MutationKind::FRAME {
+        let mutated = mutator.mutate_frame_with(*kind, &frame);
+        assert_ne!(mutated, frame, "{} left the frame unchanged", kind.name());
+    }
+}
+
+#[test]
+fn mutated_suite_inputs_never_panic() {
+    use robustness::{Corpus, Target};
+
+    let manifest_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let test_file = manifest_dir.parent().unwrap().join("test-cases.yaml");
+    let suite = TestSuite::load_from_file(&test_file)
+        .unwrap_or_else(|e| panic!("Failed to load test suite from {:?}: {}", test_file, e));
+
+    let corpus = Corpus::from_suite(&suite);
+    assert!(!corpus.records.is_empty());
+    assert!(!corpus.frames.is_empty());
+
+    let report = robustness::run(&corpus, 42, 2000);
+    assert!(report.panics.is_empty(), "{:#?}", report.panics);
+    for target in [Target::Parser, Target::Sanitizer, Target::Decoder] {
+        let totals = report.totals(target);
+        assert_eq!(totals.total(), 2000);
+        assert!(totals.rejected > 0);
+    }
+    assert!(report.totals(Target::Sanitizer).repaired > 0);
+    assert_eq!(
+        robustness::run(&corpus, 42, 2000).to_json(),
+        report.to_json()
+    );
+}
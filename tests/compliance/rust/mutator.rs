@@ -0,0 +1,241 @@
+//! Deterministic pseudo-random mutation of LNMP records and frames
+#![allow(dead_code)]
+//!
+//! A [`Mutator`] applies small, realistic corruptions to valid inputs: the
+//! typos, swapped separators and stray quotes an LLM or a hand edit introduces
+//! into text records, and the truncations and bit flips a lossy transport
+//! introduces into binary frames. The same seed always yields the same
+//! sequence of mutations, so any interesting input can be reproduced from the
+//! seed and iteration that produced it.
+
+use serde::Serialize;
+
+/// Kind of corruption applied to an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MutationKind {
+    /// An ASCII letter or digit replaced by its neighbour (`F12` -> `F13`)
+    Typo,
+    /// A separator replaced by a similar one (`;` -> `,`, `=` -> `:`)
+    SwapSeparator,
+    /// A single character removed
+    DropChar,
+    /// A single character repeated
+    DuplicateChar,
+    /// A `"` inserted at a random position
+    StrayQuote,
+    /// The ASCII case of a letter flipped (`true` -> `True`)
+    CaseFlip,
+    /// Input cut short at a random position
+    Truncate,
+    /// A single bit flipped
+    BitFlip,
+    /// A single byte removed
+    DropByte,
+    /// A single byte repeated
+    DuplicateByte,
+}
+
+impl MutationKind {
+    /// Mutations applied to text records
+    pub const TEXT: &'static [MutationKind] = &[
+        MutationKind::Typo,
+        MutationKind::SwapSeparator,
+        MutationKind::DropChar,
+        MutationKind::DuplicateChar,
+        MutationKind::StrayQuote,
+        MutationKind::CaseFlip,
+        MutationKind::Truncate,
+    ];
+
+    /// Mutations applied to binary frames
+    pub const FRAME: &'static [MutationKind] = &[
+        MutationKind::BitFlip,
+        MutationKind::Truncate,
+        MutationKind::DropByte,
+        MutationKind::DuplicateByte,
+    ];
+
+    /// Stable name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            MutationKind::Typo => "typo",
+            MutationKind::SwapSeparator => "swap-separator",
+            MutationKind::DropChar => "drop-char",
+            MutationKind::DuplicateChar => "duplicate-char",
+            MutationKind::StrayQuote => "stray-quote",
+            MutationKind::CaseFlip => "case-flip",
+            MutationKind::Truncate => "truncate",
+            MutationKind::BitFlip => "bit-flip",
+            MutationKind::DropByte => "drop-byte",
+            MutationKind::DuplicateByte => "duplicate-byte",
+        }
+    }
+}
+
+/// Seeded generator of input corruptions
+#[derive(Debug, Clone)]
+pub struct Mutator {
+    state: u64,
+}
+
+impl Mutator {
+    /// Creates a mutator whose output is fully determined by `seed`
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next value of the underlying SplitMix64 sequence
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in `0..bound` (0 when `bound` is 0)
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Applies a randomly chosen text mutation
+    pub fn mutate_text(&mut self, input: &str) -> (MutationKind, String) {
+        let kind = MutationKind::TEXT[self.below(MutationKind::TEXT.len())];
+        (kind, self.mutate_text_with(kind, input))
+    }
+
+    /// Applies a randomly chosen frame mutation
+    pub fn mutate_frame(&mut self, frame: &[u8]) -> (MutationKind, Vec<u8>) {
+        let kind = MutationKind::FRAME[self.below(MutationKind::FRAME.len())];
+        (kind, self.mutate_frame_with(kind, frame))
+    }
+
+    /// Applies the given mutation to a text record.
+    ///
+    /// Mutations that need a particular character (a letter, a separator) fall
+    /// back to a stray quote when the input has none.
+    pub fn mutate_text_with(&mut self, kind: MutationKind, input: &str) -> String {
+        let mut chars: Vec<char> = input.chars().collect();
+        match kind {
+            MutationKind::Typo => match self.pick(&chars, |c| c.is_ascii_alphanumeric()) {
+                Some(pos) => chars[pos] = neighbour(chars[pos]),
+                None => self.insert_quote(&mut chars),
+            },
+            MutationKind::SwapSeparator => {
+                match self.pick(&chars, |c| !separator_swaps(c).is_empty()) {
+                    Some(pos) => {
+                        let swaps = separator_swaps(chars[pos]);
+                        chars[pos] = swaps[self.below(swaps.len())];
+                    }
+                    None => self.insert_quote(&mut chars),
+                }
+            }
+            MutationKind::DropChar => {
+                if !chars.is_empty() {
+                    let pos = self.below(chars.len());
+                    chars.remove(pos);
+                }
+            }
+            MutationKind::DuplicateChar => {
+                if !chars.is_empty() {
+                    let pos = self.below(chars.len());
+                    chars.insert(pos, chars[pos]);
+                }
+            }
+            MutationKind::CaseFlip => match self.pick(&chars, |c| c.is_ascii_alphabetic()) {
+                Some(pos) => chars[pos] = flip_case(chars[pos]),
+                None => self.insert_quote(&mut chars),
+            },
+            MutationKind::Truncate => {
+                let len = self.below(chars.len());
+                chars.truncate(len);
+            }
+            MutationKind::StrayQuote
+            | MutationKind::BitFlip
+            | MutationKind::DropByte
+            | MutationKind::DuplicateByte => self.insert_quote(&mut chars),
+        }
+        chars.into_iter().collect()
+    }
+
+    /// Applies the given mutation to a binary frame.
+    ///
+    /// Text-only mutations fall back to a bit flip.
+    pub fn mutate_frame_with(&mut self, kind: MutationKind, frame: &[u8]) -> Vec<u8> {
+        let mut bytes = frame.to_vec();
+        if bytes.is_empty() {
+            return bytes;
+        }
+        match kind {
+            MutationKind::Truncate => {
+                let len = self.below(bytes.len());
+                bytes.truncate(len);
+            }
+            MutationKind::DropByte => {
+                let pos = self.below(bytes.len());
+                bytes.remove(pos);
+            }
+            MutationKind::DuplicateByte => {
+                let pos = self.below(bytes.len());
+                bytes.insert(pos, bytes[pos]);
+            }
+            _ => {
+                let pos = self.below(bytes.len());
+                bytes[pos] ^= 1 << self.below(8);
+            }
+        }
+        bytes
+    }
+
+    /// Random position of a character matching `predicate`
+    fn pick(&mut self, chars: &[char], predicate: impl Fn(char) -> bool) -> Option<usize> {
+        let candidates: Vec<usize> = (0..chars.len()).filter(|&i| predicate(chars[i])).collect();
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[self.below(candidates.len())])
+        }
+    }
+
+    fn insert_quote(&mut self, chars: &mut Vec<char>) {
+        let pos = self.below(chars.len() + 1);
+        chars.insert(pos, '"');
+    }
+}
+
+/// Adjacent letter or digit, wrapping within its class
+fn neighbour(c: char) -> char {
+    let (base, span) = match c {
+        '0'..='9' => (b'0', 10),
+        'a'..='z' => (b'a', 26),
+        'A'..='Z' => (b'A', 26),
+        _ => return c,
+    };
+    (base + (c as u8 - base + 1) % span) as char
+}
+
+fn flip_case(c: char) -> char {
+    if c.is_ascii_uppercase() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_ascii_uppercase()
+    }
+}
+
+/// Separators commonly confused with `c`
+fn separator_swaps(c: char) -> &'static [char] {
+    match c {
+        ';' => &[',', '\n', ' '],
+        '\n' => &[';', ' '],
+        '=' => &[':', ' '],
+        ',' => &[';', ' '],
+        ':' => &['=', ';'],
+        '{' | '}' => &['[', ']'],
+        '[' | ']' => &['{', '}'],
+        _ => &[],
+    }
+}
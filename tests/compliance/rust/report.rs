@@ -0,0 +1,159 @@
+//! Machine-readable conformance reports
+//!
+//! Summarizes a finished [`TestRunner`] into the document described by
+//! `tests/compliance/conformance-report.schema.json`, so implementations can
+//! publish which categories and requirements they pass.
+#![allow(dead_code)]
+
+use crate::runner::{TestResult, TestRunner};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Version of the report layout (bumped on incompatible schema changes)
+pub const REPORT_VERSION: u32 = 1;
+
+/// Implementation under test
+#[derive(Debug, Clone, Serialize)]
+pub struct Implementation {
+    pub id: String,
+    pub version: String,
+}
+
+/// Pass/fail counts for a group of tests
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct Counts {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl Counts {
+    fn record(&mut self, result: &TestResult) {
+        self.total += 1;
+        match result {
+            TestResult::Pass => self.passed += 1,
+            TestResult::Fail { .. } => self.failed += 1,
+            TestResult::Skip { .. } | TestResult::Info { .. } => self.skipped += 1,
+        }
+    }
+}
+
+/// Results for one test category
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryReport {
+    pub name: String,
+    pub conformant: bool,
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+/// A failing test case
+#[derive(Debug, Clone, Serialize)]
+pub struct FailureReport {
+    pub test: String,
+    pub category: String,
+    pub reason: String,
+}
+
+/// Conformance summary for a compliance run
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceReport {
+    pub report_version: u32,
+    pub implementation: Implementation,
+    pub spec_version: String,
+    pub conformant: bool,
+    pub totals: Counts,
+    pub categories: Vec<CategoryReport>,
+    pub categories_passed: Vec<String>,
+    pub requirements_passed: Vec<String>,
+    pub requirements_failed: Vec<String>,
+    pub failures: Vec<FailureReport>,
+}
+
+impl ConformanceReport {
+    /// Builds a report from the results collected by `runner`
+    ///
+    /// A category is conformant when none of its tests fail; skipped tests do not count
+    /// against it. A requirement fails when any test citing it fails.
+    pub fn from_runner(runner: &TestRunner, implementation: Implementation) -> Self {
+        let tests: HashMap<&str, _> = runner
+            .suite
+            .all_tests()
+            .into_iter()
+            .map(|test| (test.name.as_str(), test))
+            .collect();
+
+        let mut totals = Counts::default();
+        let mut categories: Vec<(String, Counts)> = Vec::new();
+        let mut requirements: BTreeMap<&str, bool> = BTreeMap::new();
+        let mut failures = Vec::new();
+
+        for (name, result) in runner.results() {
+            let test = tests.get(name.as_str());
+            let category = test.map_or("unknown", |test| test.category.as_str());
+
+            totals.record(result);
+            match categories.iter_mut().find(|(name, _)| name == category) {
+                Some((_, counts)) => counts.record(result),
+                None => {
+                    let mut counts = Counts::default();
+                    counts.record(result);
+                    categories.push((category.to_string(), counts));
+                }
+            }
+
+            for requirement in test.map(|test| test.requirements.as_slice()).unwrap_or(&[]) {
+                let ok = requirements.entry(requirement.as_str()).or_insert(true);
+                *ok &= !result.is_fail();
+            }
+
+            if let TestResult::Fail { reason } = result {
+                failures.push(FailureReport {
+                    test: name.clone(),
+                    category: category.to_string(),
+                    reason: reason.clone(),
+                });
+            }
+        }
+
+        let categories: Vec<CategoryReport> = categories
+            .into_iter()
+            .map(|(name, counts)| CategoryReport {
+                name,
+                conformant: counts.failed == 0,
+                counts,
+            })
+            .collect();
+        let categories_passed = categories
+            .iter()
+            .filter(|category| category.conformant)
+            .map(|category| category.name.clone())
+            .collect();
+        let requirements_with = |passed: bool| {
+            requirements
+                .iter()
+                .filter(|(_, ok)| **ok == passed)
+                .map(|(id, _)| id.to_string())
+                .collect()
+        };
+
+        Self {
+            report_version: REPORT_VERSION,
+            implementation,
+            spec_version: runner.suite.version.clone(),
+            conformant: totals.failed == 0,
+            totals,
+            categories,
+            categories_passed,
+            requirements_passed: requirements_with(true),
+            requirements_failed: requirements_with(false),
+            failures,
+        }
+    }
+
+    /// Serializes the report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("conformance report is always serializable")
+    }
+}
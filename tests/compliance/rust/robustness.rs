@@ -0,0 +1,280 @@
+//! Robustness measurement over mutated compliance inputs
+#![allow(dead_code)]
+//!
+//! Takes the records the suite expects to parse, corrupts them with a seeded
+//! [`Mutator`] and tallies how the strict parser, the sanitizer and the binary
+//! decoder respond. Anything other than a clean accept or reject (a panic) is
+//! kept as a sample together with its iteration so it can be replayed.
+
+use crate::mutator::{MutationKind, Mutator};
+use crate::runner::{ExpectedOutput, TestSuite};
+use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
+use lnmp_codec::Parser;
+use lnmp_sanitize::{sanitize_lnmp_text, SanitizationConfig};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Maximum number of panic samples kept in a report
+pub const MAX_SAMPLES: usize = 20;
+
+/// Component exercised by a mutated input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Target {
+    /// Strict text parser on the mutated text
+    Parser,
+    /// Sanitizer followed by the strict parser
+    Sanitizer,
+    /// Binary decoder on the mutated frame
+    Decoder,
+}
+
+/// How a component handled one mutated input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    /// Input accepted as is
+    Accepted,
+    /// Input rewritten by the sanitizer, then accepted
+    Repaired,
+    /// Input rejected with an error
+    Rejected,
+    /// Component panicked
+    Panicked,
+}
+
+/// Outcome counts for one component and mutation kind
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct OutcomeCounts {
+    pub accepted: usize,
+    pub repaired: usize,
+    pub rejected: usize,
+    pub panicked: usize,
+}
+
+impl OutcomeCounts {
+    fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Accepted => self.accepted += 1,
+            Outcome::Repaired => self.repaired += 1,
+            Outcome::Rejected => self.rejected += 1,
+            Outcome::Panicked => self.panicked += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.accepted + self.repaired + self.rejected + self.panicked
+    }
+}
+
+/// A mutated input that made a component panic
+#[derive(Debug, Clone, Serialize)]
+pub struct PanicSample {
+    pub iteration: usize,
+    pub target: Target,
+    pub mutation: MutationKind,
+    /// Mutated text, or hex for binary frames
+    pub input: String,
+    pub message: String,
+}
+
+/// Result of a robustness run
+#[derive(Debug, Clone, Serialize)]
+pub struct RobustnessReport {
+    pub seed: u64,
+    pub iterations: usize,
+    pub corpus_size: usize,
+    pub results: BTreeMap<Target, BTreeMap<&'static str, OutcomeCounts>>,
+    pub panics: Vec<PanicSample>,
+}
+
+impl RobustnessReport {
+    /// Counts for a component summed over all mutation kinds
+    pub fn totals(&self, target: Target) -> OutcomeCounts {
+        let mut totals = OutcomeCounts::default();
+        for counts in self
+            .results
+            .get(&target)
+            .into_iter()
+            .flat_map(|m| m.values())
+        {
+            totals.accepted += counts.accepted;
+            totals.repaired += counts.repaired;
+            totals.rejected += counts.rejected;
+            totals.panicked += counts.panicked;
+        }
+        totals
+    }
+
+    pub fn panic_count(&self) -> usize {
+        [Target::Parser, Target::Sanitizer, Target::Decoder]
+            .iter()
+            .map(|t| self.totals(*t).panicked)
+            .sum()
+    }
+
+    fn record(
+        &mut self,
+        iteration: usize,
+        target: Target,
+        mutation: MutationKind,
+        input: impl FnOnce() -> String,
+        result: Result<Outcome, String>,
+    ) {
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(message) => {
+                if self.panics.len() < MAX_SAMPLES {
+                    self.panics.push(PanicSample {
+                        iteration,
+                        target,
+                        mutation,
+                        input: input(),
+                        message,
+                    });
+                }
+                Outcome::Panicked
+            }
+        };
+        self.results
+            .entry(target)
+            .or_default()
+            .entry(mutation.name())
+            .or_default()
+            .record(outcome);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("robustness report serializes")
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "Robustness run: seed {}, {} iterations over {} records",
+            self.seed, self.iterations, self.corpus_size
+        );
+        for (target, by_kind) in &self.results {
+            println!();
+            println!("{:?}", target);
+            println!(
+                "  {:<16} {:>9} {:>9} {:>9} {:>9}",
+                "mutation", "accepted", "repaired", "rejected", "panicked"
+            );
+            for (kind, counts) in by_kind {
+                println!(
+                    "  {:<16} {:>9} {:>9} {:>9} {:>9}",
+                    kind, counts.accepted, counts.repaired, counts.rejected, counts.panicked
+                );
+            }
+        }
+        for sample in &self.panics {
+            println!();
+            println!(
+                "PANIC iteration {} {:?}/{}: {}",
+                sample.iteration,
+                sample.target,
+                sample.mutation.name(),
+                sample.message
+            );
+            println!("  input: {:?}", sample.input);
+        }
+    }
+}
+
+/// Valid inputs to mutate: text records and their binary encodings
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    pub records: Vec<String>,
+    pub frames: Vec<Vec<u8>>,
+}
+
+impl Corpus {
+    /// Collects the suite inputs that are expected to parse and do parse strictly
+    pub fn from_suite(suite: &TestSuite) -> Self {
+        let mut corpus = Corpus::default();
+        let encoder = BinaryEncoder::new();
+        for test in suite.all_tests() {
+            if !matches!(test.expected, Some(ExpectedOutput::Success { .. })) {
+                continue;
+            }
+            let Ok(record) = Parser::new_strict(&test.input).and_then(|mut p| p.parse_record())
+            else {
+                continue;
+            };
+            if let Ok(frame) = encoder.encode(&record) {
+                corpus.frames.push(frame);
+            }
+            corpus.records.push(test.input.clone());
+        }
+        corpus
+    }
+}
+
+/// Runs `iterations` mutations of `corpus` seeded by `seed`.
+///
+/// Each iteration mutates one text record (fed to the parser and sanitizer)
+/// and one binary frame (fed to the decoder). Panics are caught and counted;
+/// callers may want to install a quiet panic hook to keep the output readable.
+pub fn run(corpus: &Corpus, seed: u64, iterations: usize) -> RobustnessReport {
+    let mut mutator = Mutator::new(seed);
+    let mut report = RobustnessReport {
+        seed,
+        iterations,
+        corpus_size: corpus.records.len(),
+        results: BTreeMap::new(),
+        panics: Vec::new(),
+    };
+
+    for iteration in 0..iterations {
+        if !corpus.records.is_empty() {
+            let original = &corpus.records[mutator.below(corpus.records.len())];
+            let (kind, text) = mutator.mutate_text(original);
+            let result =
+                catch(
+                    || match Parser::new_strict(&text).and_then(|mut p| p.parse_record()) {
+                        Ok(_) => Outcome::Accepted,
+                        Err(_) => Outcome::Rejected,
+                    },
+                );
+            report.record(iteration, Target::Parser, kind, || text.clone(), result);
+
+            let result = catch(|| {
+                let sanitized = sanitize_lnmp_text(&text, &SanitizationConfig::default());
+                match Parser::new_strict(&sanitized).and_then(|mut p| p.parse_record()) {
+                    Ok(_) if sanitized != text => Outcome::Repaired,
+                    Ok(_) => Outcome::Accepted,
+                    Err(_) => Outcome::Rejected,
+                }
+            });
+            report.record(iteration, Target::Sanitizer, kind, || text.clone(), result);
+        }
+
+        if !corpus.frames.is_empty() {
+            let original = &corpus.frames[mutator.below(corpus.frames.len())];
+            let (kind, frame) = mutator.mutate_frame(original);
+            let result = catch(|| match BinaryDecoder::new().decode(&frame) {
+                Ok(_) => Outcome::Accepted,
+                Err(_) => Outcome::Rejected,
+            });
+            report.record(
+                iteration,
+                Target::Decoder,
+                kind,
+                || hex::encode(&frame),
+                result,
+            );
+        }
+    }
+    report
+}
+
+fn catch(f: impl FnOnce() -> Outcome) -> Result<Outcome, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string())
+    })
+}
@@ -229,10 +229,14 @@ impl TestRunner {
                 TextInputMode::Strict
             },
             semantic_dictionary: None,
+            shared_dictionary: None,
             structural_limits: None,
             profile_config: None,
             fid_registry: None,
             fid_validation_mode: lnmp_core::registry::ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: lnmp_codec::DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         };
 
         let mut parser = match Parser::with_config(&test.input, parser_config) {
@@ -288,10 +292,14 @@ impl TestRunner {
                 TextInputMode::Strict
             },
             semantic_dictionary: None,
+            shared_dictionary: None,
             structural_limits: None,
             profile_config: None,
             fid_registry: None,
             fid_validation_mode: lnmp_core::registry::ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: lnmp_codec::DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         };
 
         let mut parser = match Parser::with_config(&test.input, parser_config) {
@@ -320,6 +328,7 @@ impl TestRunner {
             mode: ParsingMode::Loose,
             validate_checksums: test.config.validate_checksums,
             semantic_dictionary: None,
+            shared_dictionary: None,
             normalize_values: test.config.normalize_values,
             require_checksums: false,
             text_input_mode: if test.config.lenient_mode {
@@ -332,6 +341,9 @@ impl TestRunner {
             profile_config: None,
             fid_registry: None,
             fid_validation_mode: lnmp_core::registry::ValidationMode::None,
+            locale_numbers: false,
+            duplicate_fields: lnmp_codec::DuplicateFieldPolicy::KeepAll,
+            coercion: None,
         };
         let mut parser = match Parser::with_config(&test.input, parser_config) {
             Ok(p) => p,
@@ -1,12 +1,36 @@
+//! Verifies the published spec examples against the Rust codec.
+//!
+//! Usage:
+//!   cargo run --bin lnmp-verify-examples
+//!   cargo run --bin lnmp-verify-examples -- --require-checksums
+
 use lnmp_codec::binary::{BinaryDecoder, BinaryEncoder};
-use lnmp_codec::{Encoder, EncoderConfig, Parser};
-use lnmp_core::{LnmpRecord, LnmpValue};
+use lnmp_codec::{ContainerFrame, Encoder, EncoderConfig, Parser};
+use lnmp_core::{LnmpRecord, LnmpValue, LNMP_FLAG_CHECKSUM_REQUIRED};
 use serde::Deserialize;
+use std::env;
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process;
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let mut require_checksums = false;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--require-checksums" => require_checksums = true,
+            "--help" | "-h" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                eprintln!("Error: Unknown argument '{}'", other);
+                print_usage();
+                process::exit(1);
+            }
+        }
+    }
+
     let repo_root = workspace_root();
     let text_dir = repo_root.join("spec/examples/text");
     let binary_dir = repo_root.join("spec/examples/binary");
@@ -58,7 +82,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                 continue;
             }
             container_count += 1;
-            if let Err(err) = verify_container_fixture(&path, &container_dir, &repo_root) {
+            if let Err(err) =
+                verify_container_fixture(&path, &container_dir, &repo_root, require_checksums)
+            {
                 failures.push(format!("{}: {}", path.display(), err));
             }
         }
@@ -79,6 +105,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 }
 
+fn print_usage() {
+    println!("Usage:");
+    println!("  lnmp-verify-examples [OPTIONS]");
+    println!();
+    println!("Options:");
+    println!("  --require-checksums  Verify field checksums of containers with the checksum flag");
+    println!("  -h, --help           Print this help message");
+}
+
 fn workspace_root() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .ancestors()
@@ -294,6 +329,7 @@ fn verify_container_fixture(
     path: &Path,
     manifest_dir: &Path,
     repo_root: &Path,
+    require_checksums: bool,
 ) -> Result<(), Box<dyn Error>> {
     let stem = path
         .file_stem()
@@ -316,7 +352,11 @@ fn verify_container_fixture(
                 )
                 .into())
             } else {
-                verify_manifest_payload(payload, &manifest, repo_root)
+                verify_manifest_payload(payload, &manifest, repo_root)?;
+                if require_checksums && manifest.flags & LNMP_FLAG_CHECKSUM_REQUIRED != 0 {
+                    verify_container_checksums(&bytes)?;
+                }
+                Ok(())
             }
         }
         Err(err) => {
@@ -337,6 +377,20 @@ fn verify_container_fixture(
     }
 }
 
+fn verify_container_checksums(bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    let frame = ContainerFrame::parse(bytes)?;
+    let failures = frame.verify_checksums()?;
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let details = failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(format!("checksum verification failed: {}", details).into())
+}
+
 fn verify_manifest_payload(
     payload: &[u8],
     manifest: &ContainerManifest,